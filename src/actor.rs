@@ -0,0 +1,273 @@
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot, Notify};
+use tokio::task::JoinHandle;
+
+use crate::logs;
+
+// 简单的 actor: 状态只归一个任务所有，其他地方通过有界邮箱发消息访问，
+// 代替在多个任务之间共享 Mutex<HashMap>。tell 只投递，ask 附带 Reply 等待回复。
+// 处理消息时 panic 会用工厂函数重新创建状态(监督重启)，继续处理后续消息；
+// 超过重启次数上限后 actor 停止，之后的投递返回 ActorError::Stopped。
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ActorError {
+    #[error("actor {0} 已停止")]
+    Stopped(String),
+    #[error("actor {0} 的邮箱已满")]
+    MailboxFull(String),
+    // 处理消息时 panic 或丢弃了 Reply
+    #[error("actor {0} 没有回复")]
+    NoReply(String),
+    #[error("等待 actor {name} 回复超过 {timeout:?}")]
+    Timeout { name: String, timeout: Duration },
+}
+
+pub trait Actor: Send + 'static {
+    type Message: Send + 'static;
+
+    fn handle(&mut self, message: Self::Message);
+
+    // 邮箱关闭或调用 stop 后、actor 退出前调用，用来落盘缓冲的数据
+    fn stopped(&mut self) {}
+}
+
+// ask 的回复通道，随消息一起发给 actor
+pub struct Reply<T>(oneshot::Sender<T>);
+
+impl<T> Reply<T> {
+    // 提问方已经放弃等待时忽略
+    pub fn send(self, value: T) {
+        let _ = self.0.send(value);
+    }
+}
+
+impl<T> fmt::Debug for Reply<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Reply")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActorStats {
+    pub name: String,
+    pub processed: u64,
+    pub restarts: u64,
+    pub queued: usize,
+    pub stopped: bool,
+}
+
+struct Shared {
+    name: String,
+    capacity: usize,
+    processed: AtomicU64,
+    restarts: AtomicU64,
+    stopped: AtomicBool,
+    stop: Notify,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+pub struct ActorRef<M> {
+    sender: mpsc::Sender<M>,
+    shared: Arc<Shared>,
+}
+
+impl<M> Clone for ActorRef<M> {
+    fn clone(&self) -> Self {
+        ActorRef {
+            sender: self.sender.clone(),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<M: Send + 'static> ActorRef<M> {
+    pub fn name(&self) -> &str {
+        &self.shared.name
+    }
+
+    // 邮箱满时等待空位
+    pub async fn tell(&self, message: M) -> Result<(), ActorError> {
+        self.sender
+            .send(message)
+            .await
+            .map_err(|_| self.stopped_error())
+    }
+
+    // 邮箱满时立即返回 MailboxFull，用于不能被拖慢的调用方
+    pub fn try_tell(&self, message: M) -> Result<(), ActorError> {
+        self.sender.try_send(message).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => ActorError::MailboxFull(self.shared.name.clone()),
+            mpsc::error::TrySendError::Closed(_) => self.stopped_error(),
+        })
+    }
+
+    // make 用回复通道构造消息，例如 actor.ask(Msg::Get).await
+    pub async fn ask<T>(&self, make: impl FnOnce(Reply<T>) -> M) -> Result<T, ActorError> {
+        let (tx, rx) = oneshot::channel();
+        self.tell(make(Reply(tx))).await?;
+        rx.await
+            .map_err(|_| ActorError::NoReply(self.shared.name.clone()))
+    }
+
+    // 超时只是不再等待，消息仍然会被处理
+    pub async fn ask_timeout<T>(
+        &self,
+        make: impl FnOnce(Reply<T>) -> M,
+        timeout: Duration,
+    ) -> Result<T, ActorError> {
+        tokio::time::timeout(timeout, self.ask(make))
+            .await
+            .map_err(|_| ActorError::Timeout {
+                name: self.shared.name.clone(),
+                timeout,
+            })?
+    }
+
+    pub fn stats(&self) -> ActorStats {
+        let shared = &self.shared;
+        ActorStats {
+            name: shared.name.clone(),
+            processed: shared.processed.load(Ordering::Relaxed),
+            restarts: shared.restarts.load(Ordering::Relaxed),
+            queued: shared.capacity - self.sender.capacity(),
+            stopped: shared.stopped.load(Ordering::Relaxed),
+        }
+    }
+
+    // 处理完当前消息后停止，邮箱中剩余的消息被丢弃
+    pub fn stop(&self) {
+        self.shared.stop.notify_one();
+    }
+
+    // 等待 actor 退出，只有第一次调用会真正等待
+    pub async fn join(&self) {
+        let task = self.shared.task.lock().unwrap().take();
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+
+    fn stopped_error(&self) -> ActorError {
+        ActorError::Stopped(self.shared.name.clone())
+    }
+}
+
+pub struct ActorBuilder {
+    name: String,
+    mailbox: usize,
+    max_restarts: u32,
+}
+
+pub fn builder(name: &str) -> ActorBuilder {
+    ActorBuilder {
+        name: name.to_string(),
+        mailbox: 64,
+        max_restarts: 3,
+    }
+}
+
+// 默认邮箱容量 64，最多重启 3 次
+pub fn spawn<A, F>(name: &str, factory: F) -> ActorRef<A::Message>
+where
+    A: Actor,
+    F: FnMut() -> A + Send + 'static,
+{
+    builder(name).spawn(factory)
+}
+
+impl ActorBuilder {
+    pub fn mailbox(mut self, capacity: usize) -> Self {
+        self.mailbox = capacity.max(1);
+        self
+    }
+
+    // 0 表示第一次 panic 就停止
+    pub fn max_restarts(mut self, n: u32) -> Self {
+        self.max_restarts = n;
+        self
+    }
+
+    // factory 创建初始状态，重启时再次调用。需要在 tokio 运行时中调用
+    pub fn spawn<A, F>(self, mut factory: F) -> ActorRef<A::Message>
+    where
+        A: Actor,
+        F: FnMut() -> A + Send + 'static,
+    {
+        let (sender, mut receiver) = mpsc::channel(self.mailbox);
+        let shared = Arc::new(Shared {
+            name: self.name,
+            capacity: self.mailbox,
+            processed: AtomicU64::new(0),
+            restarts: AtomicU64::new(0),
+            stopped: AtomicBool::new(false),
+            stop: Notify::new(),
+            task: Mutex::new(None),
+        });
+        let state = shared.clone();
+        let max_restarts = self.max_restarts;
+        let task = tokio::spawn(async move {
+            let mut actor = factory();
+            loop {
+                let message = tokio::select! {
+                    biased;
+                    _ = state.stop.notified() => break,
+                    message = receiver.recv() => match message {
+                        Some(message) => message,
+                        None => break,
+                    },
+                };
+                let result = panic::catch_unwind(AssertUnwindSafe(|| actor.handle(message)));
+                state.processed.fetch_add(1, Ordering::Relaxed);
+                let Err(payload) = result else {
+                    continue;
+                };
+                let restarts = state.restarts.load(Ordering::Relaxed);
+                if restarts >= max_restarts as u64 {
+                    logs::error(
+                        "actor",
+                        &format!(
+                            "actor {} panic: {}，已重启 {} 次，停止",
+                            state.name,
+                            panic_message(payload.as_ref()),
+                            restarts
+                        ),
+                    );
+                    // 状态可能已经不一致，不调用 stopped
+                    receiver.close();
+                    state.stopped.store(true, Ordering::Relaxed);
+                    return;
+                }
+                logs::warn(
+                    "actor",
+                    &format!(
+                        "actor {} panic: {}，重新创建",
+                        state.name,
+                        panic_message(payload.as_ref())
+                    ),
+                );
+                state.restarts.fetch_add(1, Ordering::Relaxed);
+                actor = factory();
+            }
+            receiver.close();
+            actor.stopped();
+            state.stopped.store(true, Ordering::Relaxed);
+        });
+        *shared.task.lock().unwrap() = Some(task);
+        ActorRef { sender, shared }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "未知错误".to_string())
+}
@@ -0,0 +1,359 @@
+use std::collections::BTreeMap;
+use std::hash::Hash;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt};
+use tokio::net::UnixListener;
+
+use crate::cache::TtlCache;
+use crate::logs;
+
+pub mod shell;
+
+// 运行中实例的管理接口: 在 Unix socket 上每行接收一个 JSON 请求
+// {"command": "flag", "args": ["beta", "on"]}，每行回复一个 JSON
+// {"ok": true, "result": ...} 或 {"ok": false, "error": "..."}。
+// 应用启动时登记要暴露的缓存、开关和任务，std-app shell 是交互式客户端。
+
+pub const DEFAULT_SOCKET: &str = "data/admin.sock";
+
+#[derive(Error, Debug)]
+pub enum AdminError {
+    #[error("管理接口连接失败: {0}")]
+    Io(#[from] io::Error),
+    #[error("无效的管理协议消息: {0}")]
+    Protocol(String),
+    #[error("{0}")]
+    Command(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+impl Request {
+    // shell 输入的一行，按空白切分
+    pub fn parse_line(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace().map(str::to_string);
+        let command = words.next()?;
+        Some(Request {
+            command,
+            args: words.collect(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub result: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    fn ok(result: Value) -> Self {
+        Response {
+            ok: true,
+            result,
+            error: None,
+        }
+    }
+
+    fn err(message: impl Into<String>) -> Self {
+        Response {
+            ok: false,
+            result: Value::Null,
+            error: Some(message.into()),
+        }
+    }
+}
+
+// 可以通过管理接口查看和清空的缓存
+pub trait AdminCache: Send + Sync {
+    fn len(&self) -> usize;
+    fn clear(&self);
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K, V> AdminCache for TtlCache<K, V>
+where
+    K: Eq + Hash + Send,
+    V: Clone + Send,
+{
+    fn len(&self) -> usize {
+        TtlCache::len(self)
+    }
+
+    fn clear(&self) {
+        TtlCache::clear(self)
+    }
+}
+
+type JobFn = Box<dyn Fn(&[String]) -> Result<Value, String> + Send + Sync>;
+
+// (命令, 用法)，help 和补全都以此为准
+const COMMANDS: &[(&str, &str)] = &[
+    ("help", "help"),
+    ("caches", "caches"),
+    ("cache.clear", "cache.clear <名字>"),
+    ("flags", "flags"),
+    ("flag", "flag <名字> on|off"),
+    ("log", "log <模块>=<级别>[@秒]"),
+    ("logs", "logs [条数]"),
+    ("jobs", "jobs"),
+    ("enqueue", "enqueue <任务> [参数...]"),
+    ("complete", "complete <已输入的内容>"),
+];
+
+#[derive(Default)]
+pub struct AdminServer {
+    caches: BTreeMap<String, Arc<dyn AdminCache>>,
+    flags: BTreeMap<String, Arc<AtomicBool>>,
+    jobs: BTreeMap<String, JobFn>,
+}
+
+impl AdminServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cache(mut self, name: &str, cache: Arc<dyn AdminCache>) -> Self {
+        self.caches.insert(name.to_string(), cache);
+        self
+    }
+
+    // 开关由应用持有，管理接口只负责读写
+    pub fn flag(mut self, name: &str, flag: Arc<AtomicBool>) -> Self {
+        self.flags.insert(name.to_string(), flag);
+        self
+    }
+
+    // enqueue <name> 时调用 submit，通常在里面把任务交给 JobPool；返回值作为命令结果
+    pub fn job<F>(mut self, name: &str, submit: F) -> Self
+    where
+        F: Fn(&[String]) -> Result<Value, String> + Send + Sync + 'static,
+    {
+        self.jobs.insert(name.to_string(), Box::new(submit));
+        self
+    }
+
+    pub fn handle(&self, request: &Request) -> Response {
+        match self.dispatch(&request.command, &request.args) {
+            Ok(result) => Response::ok(result),
+            Err(message) => Response::err(message),
+        }
+    }
+
+    fn dispatch(&self, command: &str, args: &[String]) -> Result<Value, String> {
+        let arg = |i: usize| {
+            args.get(i)
+                .map(String::as_str)
+                .ok_or_else(|| format!("用法: {}", usage(command)))
+        };
+        match command {
+            "help" => Ok(json!(COMMANDS.iter().map(|(_, u)| *u).collect::<Vec<_>>())),
+            "caches" => Ok(self
+                .caches
+                .iter()
+                .map(|(name, cache)| json!({ "name": name, "len": cache.len() }))
+                .collect()),
+            "cache.clear" => {
+                let name = arg(0)?;
+                let cache = self
+                    .caches
+                    .get(name)
+                    .ok_or_else(|| format!("没有名为 {} 的缓存", name))?;
+                let cleared = cache.len();
+                cache.clear();
+                logs::info("admin", &format!("清空缓存 {}，{} 条", name, cleared));
+                Ok(json!({ "cleared": cleared }))
+            }
+            "flags" => Ok(self
+                .flags
+                .iter()
+                .map(|(name, flag)| (name.clone(), json!(flag.load(Ordering::SeqCst))))
+                .collect::<serde_json::Map<_, _>>()
+                .into()),
+            "flag" => {
+                let name = arg(0)?;
+                let flag = self
+                    .flags
+                    .get(name)
+                    .ok_or_else(|| format!("没有名为 {} 的开关", name))?;
+                let value = match arg(1)? {
+                    "on" | "true" | "1" => true,
+                    "off" | "false" | "0" => false,
+                    other => return Err(format!("开关值只能是 on 或 off，收到 {}", other)),
+                };
+                let previous = flag.swap(value, Ordering::SeqCst);
+                logs::info(
+                    "admin",
+                    &format!("开关 {}: {} -> {}", name, previous, value),
+                );
+                Ok(json!({ "name": name, "previous": previous, "value": value }))
+            }
+            "log" => {
+                logs::apply_directive(arg(0)?)?;
+                Ok(json!("ok"))
+            }
+            "logs" => {
+                let limit = match args.first() {
+                    Some(n) => n.parse().map_err(|_| format!("无效的条数: {}", n))?,
+                    None => 20,
+                };
+                let lines = logs::recent_lines();
+                let skip = lines.len().saturating_sub(limit);
+                Ok(json!(lines[skip..]))
+            }
+            "jobs" => Ok(json!(self.jobs.keys().collect::<Vec<_>>())),
+            "enqueue" => {
+                let name = arg(0)?;
+                let submit = self
+                    .jobs
+                    .get(name)
+                    .ok_or_else(|| format!("没有名为 {} 的任务", name))?;
+                logs::info("admin", &format!("提交任务 {}", name));
+                submit(&args[1..])
+            }
+            "complete" => Ok(json!(self.complete(&args.join(" ")))),
+            other => Err(format!("未知命令 {}，输入 help 查看可用命令", other)),
+        }
+    }
+
+    // 补全最后一个词: 第一个词补命令名，之后按命令补缓存、开关或任务名
+    pub fn complete(&self, line: &str) -> Vec<String> {
+        let mut words: Vec<&str> = line.split_whitespace().collect();
+        if line.is_empty() || line.ends_with(char::is_whitespace) {
+            words.push("");
+        }
+        let prefix = words.last().copied().unwrap_or("");
+        let candidates: Vec<String> = match words.as_slice() {
+            [_] => COMMANDS.iter().map(|(c, _)| c.to_string()).collect(),
+            ["cache.clear", _] => self.caches.keys().cloned().collect(),
+            ["flag", _] => self.flags.keys().cloned().collect(),
+            ["flag", _, _] => vec!["on".into(), "off".into()],
+            ["enqueue", _] => self.jobs.keys().cloned().collect(),
+            _ => Vec::new(),
+        };
+        candidates
+            .into_iter()
+            .filter(|c| c.starts_with(prefix))
+            .collect()
+    }
+
+    // 在 path 上监听，需要在 tokio 运行时中调用；残留的 socket 文件会被替换
+    pub fn serve(self, path: impl AsRef<Path>) -> io::Result<AdminHandle> {
+        let path = path.as_ref().to_path_buf();
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        let listener = UnixListener::bind(&path)?;
+        let server = Arc::new(self);
+        let task = tokio::spawn(async move {
+            loop {
+                let stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        logs::warn("admin", &format!("接受管理连接失败: {}", e));
+                        continue;
+                    }
+                };
+                let server = server.clone();
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut lines = tokio::io::BufReader::new(reader).lines();
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        let response = match serde_json::from_str::<Request>(&line) {
+                            Ok(request) => server.handle(&request),
+                            Err(e) => Response::err(format!("无效的请求: {}", e)),
+                        };
+                        let mut out =
+                            serde_json::to_vec(&response).expect("Response 总是可以序列化");
+                        out.push(b'\n');
+                        if writer.write_all(&out).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        Ok(AdminHandle { path, task })
+    }
+}
+
+pub struct AdminHandle {
+    path: PathBuf,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl AdminHandle {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // 停止监听并删除 socket 文件
+    pub fn shutdown(self) {
+        self.task.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+// 同步客户端，shell 和脚本使用
+pub struct AdminClient {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl AdminClient {
+    pub fn connect(path: impl AsRef<Path>) -> Result<Self, AdminError> {
+        let writer = UnixStream::connect(path)?;
+        let reader = BufReader::new(writer.try_clone()?);
+        Ok(AdminClient { reader, writer })
+    }
+
+    pub fn send(&mut self, request: &Request) -> Result<Response, AdminError> {
+        let mut out = serde_json::to_vec(request).expect("Request 总是可以序列化");
+        out.push(b'\n');
+        self.writer.write_all(&out)?;
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(AdminError::Protocol("连接已关闭".into()));
+        }
+        serde_json::from_str(&line).map_err(|e| AdminError::Protocol(e.to_string()))
+    }
+
+    // 命令失败时返回 AdminError::Command
+    pub fn call(&mut self, command: &str, args: &[&str]) -> Result<Value, AdminError> {
+        let response = self.send(&Request {
+            command: command.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        })?;
+        match response.error {
+            Some(message) if !response.ok => Err(AdminError::Command(message)),
+            _ => Ok(response.result),
+        }
+    }
+}
+
+fn usage(command: &str) -> &'static str {
+    COMMANDS
+        .iter()
+        .find(|(c, _)| *c == command)
+        .map(|(_, u)| *u)
+        .unwrap_or("")
+}
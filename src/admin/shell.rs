@@ -0,0 +1,103 @@
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use serde_json::Value;
+
+use super::{AdminClient, AdminError, Request, Response};
+
+// std-app shell: 读一行命令发给管理接口，打印结果。Tab 补全由服务端的 complete 命令提供，
+// 所以补全的缓存、开关和任务名总是和运行中的实例一致。quit/exit 或 Ctrl-D 退出。
+
+struct ShellHelper {
+    client: Mutex<AdminClient>,
+}
+
+impl Completer for ShellHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        let candidates = self
+            .client
+            .lock()
+            .unwrap()
+            .call("complete", &[line])
+            .ok()
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default();
+        Ok((start, candidates))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+fn readline_error(e: ReadlineError) -> AdminError {
+    AdminError::Io(io::Error::other(e.to_string()))
+}
+
+// json 为 true 时每个回复原样输出一行 JSON，方便管道给 jq
+pub fn run(socket: impl AsRef<Path>, json: bool) -> Result<(), AdminError> {
+    let socket = socket.as_ref();
+    let mut client = AdminClient::connect(socket)?;
+    let mut editor: Editor<ShellHelper, DefaultHistory> = Editor::new().map_err(readline_error)?;
+    editor.set_helper(Some(ShellHelper {
+        client: Mutex::new(AdminClient::connect(socket)?),
+    }));
+    loop {
+        let line = match editor.readline("admin> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => return Ok(()),
+            Err(e) => return Err(readline_error(e)),
+        };
+        let Some(request) = Request::parse_line(&line) else {
+            continue;
+        };
+        if request.command == "quit" || request.command == "exit" {
+            return Ok(());
+        }
+        let _ = editor.add_history_entry(line.as_str());
+        let response = client.send(&request)?;
+        println!("{}", render(&response, json));
+    }
+}
+
+pub fn render(response: &Response, json: bool) -> String {
+    if json {
+        return serde_json::to_string(response).expect("Response 总是可以序列化");
+    }
+    if let Some(error) = &response.error {
+        return format!("错误: {}", error);
+    }
+    match &response.result {
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(Value::is_string) => items
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => serde_json::to_string_pretty(other).expect("Value 总是可以序列化"),
+    }
+}
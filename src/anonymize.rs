@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use sqlx::{Column, Row, SqlitePool};
+
+use crate::db::DbError;
+use crate::reports;
+
+// 数据匿名化: 导出或复制数据库时按字段/列名替换敏感值，生成可以用于测试的数据集。
+// 所有策略都是确定性的(同样的盐和输入得到同样的输出)，关联字段在替换后仍然能对上。
+
+const FIRST_NAMES: [&str; 8] = [
+    "Alex", "Sam", "Jordan", "Taylor", "Morgan", "Casey", "Riley", "Jamie",
+];
+const LAST_NAMES: [&str; 8] = [
+    "Smith", "Lee", "Wang", "Garcia", "Brown", "Chen", "Miller", "Davis",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FakeKind {
+    Name,
+    Email,
+    Phone,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum Strategy {
+    // 加盐 SHA-256，取前 16 位十六进制
+    Hash,
+    // 只保留最后 keep_last 个字符
+    Mask {
+        #[serde(default)]
+        keep_last: usize,
+    },
+    // 替换成看起来真实的假数据
+    Fake {
+        kind: FakeKind,
+    },
+    Null,
+}
+
+// 配置文件中的 [anonymize] 段，fields 的键是字段名或列名
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnonymizeConfig {
+    pub salt: String,
+    pub fields: HashMap<String, Strategy>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Anonymizer {
+    salt: String,
+    fields: HashMap<String, Strategy>,
+}
+
+impl Anonymizer {
+    pub fn new(salt: &str) -> Self {
+        Anonymizer {
+            salt: salt.to_string(),
+            fields: HashMap::new(),
+        }
+    }
+
+    pub fn from_config(config: &AnonymizeConfig) -> Self {
+        Anonymizer {
+            salt: config.salt.clone(),
+            fields: config
+                .fields
+                .iter()
+                .map(|(k, v)| (k.to_ascii_lowercase(), v.clone()))
+                .collect(),
+        }
+    }
+
+    // 字段名比较时忽略大小写
+    pub fn field(mut self, name: &str, strategy: Strategy) -> Self {
+        self.fields.insert(name.to_ascii_lowercase(), strategy);
+        self
+    }
+
+    pub fn strategy(&self, field: &str) -> Option<&Strategy> {
+        self.fields.get(&field.to_ascii_lowercase())
+    }
+
+    fn digest(&self, value: &str) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(self.salt.as_bytes());
+        hasher.update([0]);
+        hasher.update(value.as_bytes());
+        hasher.finalize().into()
+    }
+
+    pub fn apply(&self, strategy: &Strategy, value: &str) -> Option<String> {
+        match strategy {
+            Strategy::Hash => Some(hex::encode(&self.digest(value)[..8])),
+            Strategy::Mask { keep_last } => {
+                let chars: Vec<char> = value.chars().collect();
+                let keep = (*keep_last).min(chars.len());
+                let masked = chars.len() - keep;
+                Some(
+                    std::iter::repeat_n('*', masked)
+                        .chain(chars[masked..].iter().copied())
+                        .collect(),
+                )
+            }
+            Strategy::Fake { kind } => {
+                let d = self.digest(value);
+                Some(match kind {
+                    FakeKind::Name => format!(
+                        "{} {}",
+                        FIRST_NAMES[d[0] as usize % FIRST_NAMES.len()],
+                        LAST_NAMES[d[1] as usize % LAST_NAMES.len()]
+                    ),
+                    FakeKind::Email => format!("user-{}@example.com", hex::encode(&d[..4])),
+                    FakeKind::Phone => {
+                        let n = u32::from_be_bytes([d[0], d[1], d[2], d[3]]) % 10_000_000;
+                        format!("555-{:03}-{:04}", n / 10_000, n % 10_000)
+                    }
+                })
+            }
+            Strategy::Null => None,
+        }
+    }
+
+    // 没有配置的字段原样返回
+    pub fn apply_field(&self, field: &str, value: &str) -> Option<String> {
+        match self.strategy(field) {
+            Some(strategy) => self.apply(strategy, value),
+            None => Some(value.to_string()),
+        }
+    }
+
+    // 递归处理对象中名字匹配的字段；匹配字段的非字符串值先转成字符串
+    pub fn anonymize_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, v) in map.iter_mut() {
+                    match (self.strategy(key), &*v) {
+                        (Some(_), Value::Null) => {}
+                        (Some(_), Value::Object(_) | Value::Array(_)) => self.anonymize_value(v),
+                        (Some(strategy), other) => {
+                            let text = match other {
+                                Value::String(s) => s.clone(),
+                                other => other.to_string(),
+                            };
+                            *v = self
+                                .apply(strategy, &text)
+                                .map_or(Value::Null, Value::String);
+                        }
+                        (None, _) => self.anonymize_value(v),
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|v| self.anonymize_value(v)),
+            _ => {}
+        }
+    }
+
+    pub fn anonymize<T: Serialize + ?Sized>(&self, value: &T) -> Result<Value, serde_json::Error> {
+        let mut value = serde_json::to_value(value)?;
+        self.anonymize_value(&mut value);
+        Ok(value)
+    }
+
+    // 把 from 中的表逐行复制到 to 中的同名表(需要已经存在)，返回复制的行数
+    pub async fn copy_table(
+        &self,
+        from: &SqlitePool,
+        to: &SqlitePool,
+        table: &str,
+    ) -> Result<u64, DbError> {
+        let table = quote(table);
+        let rows = sqlx::query(&format!("SELECT * FROM {}", table))
+            .fetch_all(from)
+            .await?;
+        let mut tx = to.begin().await?;
+        for row in &rows {
+            let columns: Vec<&str> = row.columns().iter().map(|c| c.name()).collect();
+            let quoted: Vec<String> = columns.iter().map(|c| quote(c)).collect();
+            let sql = format!(
+                "INSERT INTO {} ({}) VALUES ({})",
+                table,
+                quoted.join(", "),
+                vec!["?"; columns.len()].join(", ")
+            );
+            let mut query = sqlx::query(&sql);
+            for (i, column) in columns.iter().enumerate() {
+                let value = reports::value(row, i);
+                query = match (self.strategy(column), value) {
+                    (_, Value::Null) => query.bind(None::<String>),
+                    (Some(strategy), value) => {
+                        let text = match value {
+                            Value::String(s) => s,
+                            other => other.to_string(),
+                        };
+                        query.bind(self.apply(strategy, &text))
+                    }
+                    (None, Value::Number(n)) if n.is_i64() => query.bind(n.as_i64()),
+                    (None, Value::Number(n)) => query.bind(n.as_f64()),
+                    (None, Value::String(s)) => query.bind(s),
+                    (None, other) => query.bind(other.to_string()),
+                };
+            }
+            query.execute(&mut *tx).await?;
+        }
+        tx.commit().await?;
+        Ok(rows.len() as u64)
+    }
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
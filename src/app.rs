@@ -0,0 +1,393 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+
+use crate::config::AppConfig;
+use crate::container::{ContainerBuilder, ServiceContainer};
+use crate::signals::{self, SIGHUP, SIGUSR1};
+use crate::{logs, metrics};
+
+// 启动编排: 各子系统声明依赖(config → logging → db → cache → scheduler)，
+// 按拓扑顺序初始化；某一步失败时报告失败的阶段，并逆序关闭已经启动的阶段。
+
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+type StartFn = Box<dyn FnOnce() -> BoxFuture<Result<(), BoxError>> + Send>;
+type StopFn = Box<dyn FnOnce() -> BoxFuture<()> + Send>;
+type ReloadFn = Arc<dyn Fn() + Send + Sync>;
+
+#[derive(Error, Debug)]
+pub enum BootstrapError {
+    #[error("重复的启动阶段: {0}")]
+    Duplicate(String),
+
+    #[error("启动阶段 {stage} 依赖未知的阶段 {dependency}")]
+    UnknownDependency { stage: String, dependency: String },
+
+    #[error("启动阶段存在循环依赖: {}", .0.join(", "))]
+    Cycle(Vec<String>),
+
+    #[error("启动阶段 {stage} 失败: {source} (已关闭: {})", .rolled_back.join(", "))]
+    StageFailed {
+        stage: String,
+        #[source]
+        source: BoxError,
+        rolled_back: Vec<String>,
+    },
+}
+
+struct Stage {
+    name: String,
+    deps: Vec<String>,
+    start: StartFn,
+    stop: Option<StopFn>,
+}
+
+#[derive(Default)]
+pub struct Bootstrap {
+    stages: Vec<Stage>,
+}
+
+pub fn bootstrap() -> Bootstrap {
+    Bootstrap::default()
+}
+
+impl Bootstrap {
+    pub fn stage<F, Fut>(self, name: &str, deps: &[&str], start: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), BoxError>> + Send + 'static,
+    {
+        self.push(name, deps, Box::new(move || Box::pin(start())), None)
+    }
+
+    // stop 在关闭或后续阶段启动失败时调用
+    pub fn stage_with_stop<F, Fut, S, SFut>(
+        self,
+        name: &str,
+        deps: &[&str],
+        start: F,
+        stop: S,
+    ) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), BoxError>> + Send + 'static,
+        S: FnOnce() -> SFut + Send + 'static,
+        SFut: Future<Output = ()> + Send + 'static,
+    {
+        self.push(
+            name,
+            deps,
+            Box::new(move || Box::pin(start())),
+            Some(Box::new(move || Box::pin(stop()))),
+        )
+    }
+
+    fn push(mut self, name: &str, deps: &[&str], start: StartFn, stop: Option<StopFn>) -> Self {
+        self.stages.push(Stage {
+            name: name.to_string(),
+            deps: deps.iter().map(|d| d.to_string()).collect(),
+            start,
+            stop,
+        });
+        self
+    }
+
+    // 拓扑排序，依赖相同时保持注册顺序
+    pub fn order(&self) -> Result<Vec<String>, BootstrapError> {
+        let mut index = HashMap::new();
+        for (i, stage) in self.stages.iter().enumerate() {
+            if index.insert(stage.name.as_str(), i).is_some() {
+                return Err(BootstrapError::Duplicate(stage.name.clone()));
+            }
+        }
+        let mut pending = vec![0usize; self.stages.len()];
+        for (i, stage) in self.stages.iter().enumerate() {
+            for dep in &stage.deps {
+                if !index.contains_key(dep.as_str()) {
+                    return Err(BootstrapError::UnknownDependency {
+                        stage: stage.name.clone(),
+                        dependency: dep.clone(),
+                    });
+                }
+                pending[i] += 1;
+            }
+        }
+
+        let mut done = vec![false; self.stages.len()];
+        let mut order = Vec::new();
+        while order.len() < self.stages.len() {
+            let next = (0..self.stages.len()).find(|&i| !done[i] && pending[i] == 0);
+            let Some(i) = next else {
+                let cycle = (0..self.stages.len())
+                    .filter(|&i| !done[i])
+                    .map(|i| self.stages[i].name.clone())
+                    .collect();
+                return Err(BootstrapError::Cycle(cycle));
+            };
+            done[i] = true;
+            order.push(self.stages[i].name.clone());
+            for (j, stage) in self.stages.iter().enumerate() {
+                pending[j] -= stage
+                    .deps
+                    .iter()
+                    .filter(|d| **d == self.stages[i].name)
+                    .count();
+            }
+        }
+        Ok(order)
+    }
+
+    pub async fn run(mut self) -> Result<Started, BootstrapError> {
+        let order = self.order()?;
+        let mut stages: HashMap<String, Stage> =
+            self.stages.drain(..).map(|s| (s.name.clone(), s)).collect();
+
+        let mut started = Started::default();
+        for name in order {
+            let stage = stages.remove(&name).expect("order 只包含已注册的阶段");
+            logs::info("bootstrap", &format!("启动 {}", name));
+            if let Err(source) = (stage.start)().await {
+                logs::error("bootstrap", &format!("启动 {} 失败: {}", name, source));
+                let rolled_back = started.shutdown().await;
+                return Err(BootstrapError::StageFailed {
+                    stage: name,
+                    source,
+                    rolled_back,
+                });
+            }
+            started.stages.push((name, stage.stop));
+        }
+        Ok(started)
+    }
+}
+
+// 已启动的阶段，按启动顺序保存
+#[derive(Default)]
+pub struct Started {
+    stages: Vec<(String, Option<StopFn>)>,
+}
+
+impl Started {
+    pub fn names(&self) -> Vec<&str> {
+        self.stages.iter().map(|(n, _)| n.as_str()).collect()
+    }
+
+    // 逆序关闭，返回关闭的阶段名
+    pub async fn shutdown(&mut self) -> Vec<String> {
+        let mut stopped = Vec::new();
+        while let Some((name, stop)) = self.stages.pop() {
+            if let Some(stop) = stop {
+                logs::info("bootstrap", &format!("关闭 {}", name));
+                stop().await;
+            }
+            stopped.push(name);
+        }
+        stopped
+    }
+}
+
+// 按需启用子系统的应用构建器，未启用的子系统不会初始化。
+// cargo feature 只控制构建器和 App 上对应的方法(以及 scheduler 模块)，
+// http、db 模块本身和 reqwest、sqlx 依赖始终编译
+#[derive(Default)]
+pub struct AppBuilder {
+    #[cfg(feature = "http")]
+    http: bool,
+    #[cfg(feature = "db")]
+    db: Option<String>,
+    #[cfg(feature = "scheduler")]
+    scheduler: bool,
+    reload: Option<ReloadFn>,
+    config: Option<AppConfig>,
+}
+
+impl AppBuilder {
+    // 启动时先按配置设置日志级别和脱敏规则，配置本身注册为服务
+    pub fn with_config(mut self, config: AppConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    // SIGHUP 时调用 reload 重新加载配置，SIGUSR1 把运行时状态和全局指标快照写入日志；
+    // 关闭应用时注销这两个处理函数
+    pub fn with_signals<F>(mut self, reload: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.reload = Some(Arc::new(reload));
+        self
+    }
+
+    #[cfg(feature = "http")]
+    pub fn with_http(mut self) -> Self {
+        self.http = true;
+        self
+    }
+
+    // 默认使用内存数据库
+    #[cfg(feature = "db")]
+    pub fn with_db(self) -> Self {
+        self.with_db_url("sqlite::memory:")
+    }
+
+    #[cfg(feature = "db")]
+    pub fn with_db_url(mut self, url: &str) -> Self {
+        self.db = Some(url.to_string());
+        self
+    }
+
+    #[cfg(feature = "scheduler")]
+    pub fn with_scheduler(mut self) -> Self {
+        self.scheduler = true;
+        self
+    }
+
+    pub async fn build(self) -> Result<App, BootstrapError> {
+        let container = Arc::new(Mutex::new(ServiceContainer::builder()));
+        let mut stages = bootstrap();
+
+        if let Some(config) = self.config {
+            let container = container.clone();
+            stages = stages.stage("logging", &[], move || async move {
+                logs::set_output_level(config.log_level.parse()?);
+                logs::set_redaction(&config.redact)?;
+                register(&container, config);
+                Ok(())
+            });
+        }
+        #[cfg(feature = "scheduler")]
+        #[cfg_attr(not(feature = "db"), allow(unused_mut))]
+        let mut scheduler_deps: Vec<&str> = Vec::new();
+
+        #[cfg(feature = "http")]
+        if self.http {
+            let container = container.clone();
+            stages = stages.stage("http", &[], move || async move {
+                let client = reqwest::Client::builder().build()?;
+                register(&container, client);
+                Ok(())
+            });
+        }
+
+        #[cfg(feature = "db")]
+        if let Some(url) = self.db {
+            let container = container.clone();
+            #[cfg(feature = "scheduler")]
+            scheduler_deps.push("db");
+            stages = stages.stage("db", &[], move || async move {
+                let options: sqlx::sqlite::SqliteConnectOptions = url.parse()?;
+                // 内存数据库每个连接互相独立，只能使用一个连接
+                let max = if url.contains(":memory:") { 1 } else { 10 };
+                let pool = sqlx::sqlite::SqlitePoolOptions::new()
+                    .max_connections(max)
+                    .connect_with(options.create_if_missing(true))
+                    .await?;
+                register(&container, pool);
+                Ok(())
+            });
+        }
+
+        #[cfg(feature = "scheduler")]
+        if self.scheduler {
+            let container = container.clone();
+            stages = stages.stage("scheduler", &scheduler_deps, move || async move {
+                register(&container, crate::scheduler::Scheduler::new());
+                Ok(())
+            });
+        }
+
+        if let Some(reload) = self.reload {
+            let handlers = Arc::new(Mutex::new(Vec::new()));
+            let registered = handlers.clone();
+            stages = stages.stage_with_stop(
+                "signals",
+                &[],
+                move || async move {
+                    let mut ids = registered.lock().unwrap();
+                    ids.push(signals::on(SIGHUP, move || {
+                        logs::info("signals", "收到 SIGHUP，重新加载配置");
+                        reload();
+                    })?);
+                    let handle = tokio::runtime::Handle::current();
+                    ids.push(signals::on(SIGUSR1, move || {
+                        logs::info("signals", &signals::dump(&handle, metrics::global()));
+                    })?);
+                    Ok(())
+                },
+                move || async move {
+                    for id in handlers.lock().unwrap().drain(..) {
+                        signals::off(id);
+                    }
+                },
+            );
+        }
+
+        let started = stages.run().await?;
+        let builder = std::mem::take(&mut *container.lock().unwrap());
+        Ok(App {
+            container: builder.build(),
+            started,
+        })
+    }
+}
+
+fn register<T: Send + Sync + 'static>(container: &Mutex<ContainerBuilder>, service: T) {
+    let mut builder = container.lock().unwrap();
+    *builder = std::mem::take(&mut *builder).instance(service);
+}
+
+// 构建好的应用，只包含启用的服务
+pub struct App {
+    container: ServiceContainer,
+    started: Started,
+}
+
+impl App {
+    pub fn builder() -> AppBuilder {
+        AppBuilder::default()
+    }
+
+    // 启动成功的子系统，按启动顺序
+    pub fn subsystems(&self) -> Vec<&str> {
+        self.started.names()
+    }
+
+    pub fn services(&self) -> &ServiceContainer {
+        &self.container
+    }
+
+    pub fn config(&self) -> Option<Arc<AppConfig>> {
+        self.container.resolve().ok()
+    }
+
+    #[cfg(feature = "http")]
+    pub fn http(&self) -> Option<Arc<reqwest::Client>> {
+        self.container.resolve().ok()
+    }
+
+    #[cfg(feature = "db")]
+    pub fn db(&self) -> Option<Arc<sqlx::SqlitePool>> {
+        self.container.resolve().ok()
+    }
+
+    #[cfg(feature = "scheduler")]
+    pub fn scheduler(&self) -> Option<Arc<crate::scheduler::Scheduler>> {
+        self.container.resolve().ok()
+    }
+
+    pub async fn shutdown(mut self) {
+        #[cfg(feature = "scheduler")]
+        if let Some(scheduler) = self.scheduler() {
+            scheduler.shutdown();
+        }
+        #[cfg(feature = "db")]
+        if let Some(pool) = self.db() {
+            pool.close().await;
+        }
+        self.started.shutdown().await;
+    }
+}
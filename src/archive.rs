@@ -0,0 +1,212 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Component, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use thiserror::Error;
+use zip::write::SimpleFileOptions;
+
+#[derive(Error, Debug)]
+pub enum ArchiveError {
+    #[error("无法识别的归档格式: {0}")]
+    UnknownFormat(PathBuf),
+    #[error("不安全的归档路径: {0}")]
+    UnsafePath(PathBuf),
+    #[error("不支持的归档条目: {0}")]
+    UnsupportedEntry(PathBuf),
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveFormat {
+    Tar,
+    TarGz,
+    TarZst,
+    Zip,
+}
+
+impl ArchiveFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar.zst") || name.ends_with(".tzst") {
+            Some(ArchiveFormat::TarZst)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else {
+            None
+        }
+    }
+}
+
+fn detect(path: &Path) -> Result<ArchiveFormat, ArchiveError> {
+    ArchiveFormat::from_path(path).ok_or_else(|| ArchiveError::UnknownFormat(path.to_path_buf()))
+}
+
+// 把 src 目录打包到 dest，格式由扩展名决定，返回写入的文件数
+pub fn create(src: &Path, dest: &Path) -> Result<usize, ArchiveError> {
+    let format = detect(dest)?;
+    let file = File::create(dest)?;
+    write_archive(src, file, format)
+}
+
+pub fn extract(archive: &Path, dest: &Path) -> Result<usize, ArchiveError> {
+    let format = detect(archive)?;
+    let file = File::open(archive)?;
+    read_archive(file, format, dest)
+}
+
+// 逐个文件流式写入，不会把整个目录读进内存
+pub fn write_archive<W: Write + Seek>(
+    src: &Path,
+    writer: W,
+    format: ArchiveFormat,
+) -> Result<usize, ArchiveError> {
+    let files = walk(src)?;
+    match format {
+        ArchiveFormat::Tar => {
+            let mut writer = writer;
+            write_tar(src, &files, &mut writer)?;
+            writer.flush()?;
+            Ok(files.len())
+        }
+        ArchiveFormat::TarGz => {
+            let mut encoder = GzEncoder::new(writer, flate2::Compression::default());
+            write_tar(src, &files, &mut encoder)?;
+            encoder.finish()?.flush()?;
+            Ok(files.len())
+        }
+        ArchiveFormat::TarZst => {
+            let mut encoder = zstd::Encoder::new(writer, 0)?;
+            write_tar(src, &files, &mut encoder)?;
+            encoder.finish()?.flush()?;
+            Ok(files.len())
+        }
+        ArchiveFormat::Zip => {
+            let mut zip = zip::ZipWriter::new(writer);
+            let options = SimpleFileOptions::default();
+            for relative in &files {
+                zip.start_file(to_archive_name(relative), options)?;
+                io::copy(&mut File::open(src.join(relative))?, &mut zip)?;
+            }
+            zip.finish()?;
+            Ok(files.len())
+        }
+    }
+}
+
+pub fn read_archive<R: Read + Seek>(
+    reader: R,
+    format: ArchiveFormat,
+    dest: &Path,
+) -> Result<usize, ArchiveError> {
+    fs::create_dir_all(dest)?;
+    match format {
+        ArchiveFormat::Tar => read_tar(reader, dest),
+        ArchiveFormat::TarGz => read_tar(GzDecoder::new(reader), dest),
+        ArchiveFormat::TarZst => read_tar(zstd::Decoder::new(reader)?, dest),
+        ArchiveFormat::Zip => {
+            let mut zip = zip::ZipArchive::new(reader)?;
+            let mut count = 0;
+            for i in 0..zip.len() {
+                let mut entry = zip.by_index(i)?;
+                let name = PathBuf::from(entry.name());
+                if entry.is_symlink() {
+                    return Err(ArchiveError::UnsupportedEntry(name));
+                }
+                let target = safe_join(dest, &name)?;
+                if entry.is_dir() {
+                    fs::create_dir_all(&target)?;
+                    continue;
+                }
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                io::copy(&mut entry, &mut File::create(&target)?)?;
+                count += 1;
+            }
+            Ok(count)
+        }
+    }
+}
+
+fn write_tar<W: Write>(src: &Path, files: &[PathBuf], writer: W) -> io::Result<()> {
+    let mut builder = tar::Builder::new(writer);
+    for relative in files {
+        builder.append_file(relative, &mut File::open(src.join(relative))?)?;
+    }
+    builder.finish()
+}
+
+fn read_tar<R: Read>(reader: R, dest: &Path) -> Result<usize, ArchiveError> {
+    let mut archive = tar::Archive::new(reader);
+    let mut count = 0;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let name = entry.path()?.into_owned();
+        let target = safe_join(dest, &name)?;
+        match entry.header().entry_type() {
+            tar::EntryType::Directory => fs::create_dir_all(&target)?,
+            tar::EntryType::Regular | tar::EntryType::Continuous => {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                io::copy(&mut entry, &mut File::create(&target)?)?;
+                count += 1;
+            }
+            // 链接类条目可以指向 dest 之外，直接拒绝
+            _ => return Err(ArchiveError::UnsupportedEntry(name)),
+        }
+    }
+    Ok(count)
+}
+
+// 拒绝绝对路径和 `..`，防止解压到目标目录之外
+pub fn safe_join(dest: &Path, name: &Path) -> Result<PathBuf, ArchiveError> {
+    let mut target = dest.to_path_buf();
+    for component in name.components() {
+        match component {
+            Component::Normal(part) => target.push(part),
+            Component::CurDir => {}
+            _ => return Err(ArchiveError::UnsafePath(name.to_path_buf())),
+        }
+    }
+    if target == dest {
+        return Err(ArchiveError::UnsafePath(name.to_path_buf()));
+    }
+    Ok(target)
+}
+
+// 按名称排序，保证相同目录打出的包内容顺序一致
+pub(crate) fn walk(root: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut stack = vec![PathBuf::new()];
+    while let Some(relative) = stack.pop() {
+        for entry in fs::read_dir(root.join(&relative))? {
+            let entry = entry?;
+            let path = relative.join(entry.file_name());
+            if entry.file_type()?.is_dir() {
+                stack.push(path);
+            } else {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+// zip 规范要求使用 `/` 作为分隔符
+pub(crate) fn to_archive_name(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,219 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::{Index, IndexMut};
+
+// 批量分配: 同一批数据(一条 CSV 记录、一次模板解析)放在一块连续的内存里，
+// 用轻量的句柄引用，整批处理完后 clear 复用容量，避免每个字段单独分配。
+// clear 之后旧的句柄失效，继续使用会得到错误的数据或 panic。
+
+pub struct Handle<T> {
+    index: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Handle({})", self.index)
+    }
+}
+
+// 类型化的 arena，句柄只能用于分配它的 arena
+pub struct Arena<T> {
+    items: Vec<T>,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Arena::new()
+    }
+}
+
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Arena { items: Vec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Arena {
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn alloc(&mut self, value: T) -> Handle<T> {
+        let index = u32::try_from(self.items.len()).expect("arena 元素数量超过 u32");
+        self.items.push(value);
+        Handle {
+            index,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn get(&self, handle: Handle<T>) -> Option<&T> {
+        self.items.get(handle.index())
+    }
+
+    pub fn get_mut(&mut self, handle: Handle<T>) -> Option<&mut T> {
+        self.items.get_mut(handle.index())
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.items.capacity()
+    }
+
+    // 按分配顺序遍历
+    pub fn iter(&self) -> impl Iterator<Item = (Handle<T>, &T)> {
+        self.items.iter().enumerate().map(|(i, v)| {
+            (
+                Handle {
+                    index: i as u32,
+                    _marker: PhantomData,
+                },
+                v,
+            )
+        })
+    }
+
+    // 释放所有元素，保留容量
+    pub fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+impl<T> Index<Handle<T>> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, handle: Handle<T>) -> &T {
+        &self.items[handle.index()]
+    }
+}
+
+impl<T> IndexMut<Handle<T>> for Arena<T> {
+    fn index_mut(&mut self, handle: Handle<T>) -> &mut T {
+        &mut self.items[handle.index()]
+    }
+}
+
+// 字符串 arena 中的一段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StrHandle {
+    start: u32,
+    len: u32,
+}
+
+impl StrHandle {
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+// 所有字符串追加到同一个 String 里。可以逐个字符写入，finish 把上次 finish 之后写入的内容
+// 作为一个字符串，适合边解析边生成字段的场景
+#[derive(Debug, Default)]
+pub struct StrArena {
+    buf: String,
+    // 当前未完成字符串的起点
+    start: usize,
+}
+
+impl StrArena {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        StrArena {
+            buf: String::with_capacity(capacity),
+            start: 0,
+        }
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.buf.push(c);
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        self.buf.push_str(s);
+    }
+
+    pub fn finish(&mut self) -> StrHandle {
+        let handle = StrHandle {
+            start: u32::try_from(self.start).expect("arena 大小超过 u32"),
+            len: u32::try_from(self.buf.len() - self.start).expect("arena 大小超过 u32"),
+        };
+        self.start = self.buf.len();
+        handle
+    }
+
+    pub fn alloc(&mut self, s: &str) -> StrHandle {
+        self.push_str(s);
+        self.finish()
+    }
+
+    pub fn get(&self, handle: StrHandle) -> &str {
+        let start = handle.start as usize;
+        &self.buf[start..start + handle.len as usize]
+    }
+
+    // 已使用的字节数
+    pub fn bytes(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.capacity()
+    }
+
+    pub fn clear(&mut self) {
+        self.buf.clear();
+        self.start = 0;
+    }
+}
+
+impl Index<StrHandle> for StrArena {
+    type Output = str;
+
+    fn index(&self, handle: StrHandle) -> &str {
+        self.get(handle)
+    }
+}
@@ -0,0 +1,469 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures_util::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::sqlite::{Sqlite, SqliteArguments, SqliteRow};
+use sqlx::{Row, SqlitePool, TypeInfo, ValueRef};
+use thiserror::Error;
+
+use crate::archive::{self, ArchiveError};
+use crate::checksum::{HashingReader, HashingWriter};
+
+// 灾难恢复用的导出/导入: SQLite 的所有表(每行一个 JSON 数组)和 blob 存储目录打包成一个归档
+// (推荐 .tar.zst)，manifest.json 记录归档格式版本、应用版本和每个文件的 SHA-256。
+// 导入时先校验全部校验和，再在一个事务里写入数据库: 目标库已有的表(通常由新版本的迁移创建)
+// 只写两边都有的列，新版本删掉的列记录在报告里；目标库没有的表按归档中的建表语句创建。
+
+// 归档布局的版本，布局不兼容地变化时加一；导入拒绝比自己新的版本
+pub const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST: &str = "manifest.json";
+
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Archive(#[from] ArchiveError),
+    #[error(transparent)]
+    Db(#[from] sqlx::Error),
+    #[error("归档清单无效: {0}")]
+    Manifest(String),
+    #[error("归档格式版本 {found} 比当前支持的 {supported} 新，需要先升级应用")]
+    UnsupportedVersion { found: u32, supported: u32 },
+    #[error("校验和不一致: {0}")]
+    Checksum(String),
+    // 归档中有数据但没有指定写到哪里，避免悄悄丢掉一部分
+    #[error("归档包含{0}，但没有指定导入目标")]
+    MissingTarget(&'static str),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format_version: u32,
+    pub app_version: String,
+    // Unix 时间戳(秒)
+    pub created_at: u64,
+    pub tables: Vec<TableEntry>,
+    pub files: Vec<FileEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableEntry {
+    pub name: String,
+    pub sql: String,
+    pub indexes: Vec<String>,
+    // 行数据中值的顺序
+    pub columns: Vec<String>,
+    pub rows: u64,
+    pub file: String,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileEntry {
+    // 相对于 blob 存储根目录，用 / 分隔
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TableImport {
+    pub name: String,
+    pub rows: u64,
+    // 目标库中原本没有这张表
+    pub created: bool,
+    pub dropped_columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ImportReport {
+    pub format_version: u32,
+    pub app_version: String,
+    pub tables: Vec<TableImport>,
+    // 目标目录中已经存在的 blob 内容相同，不会覆盖
+    pub files: usize,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "归档格式 v{}，由 {} 版本导出",
+            self.format_version, self.app_version
+        )?;
+        for table in &self.tables {
+            write!(f, "表 {}: {} 行", table.name, table.rows)?;
+            if table.created {
+                write!(f, "(新建)")?;
+            }
+            if !table.dropped_columns.is_empty() {
+                write!(f, "，忽略已删除的列 {}", table.dropped_columns.join(", "))?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, "blob 文件: {} 个", self.files)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Backup {
+    db: Option<SqlitePool>,
+    blobs: Option<PathBuf>,
+}
+
+impl Backup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn db(mut self, pool: &SqlitePool) -> Self {
+        self.db = Some(pool.clone());
+        self
+    }
+
+    // BlobStore 的根目录
+    pub fn blobs(mut self, root: impl AsRef<Path>) -> Self {
+        self.blobs = Some(root.as_ref().to_path_buf());
+        self
+    }
+
+    pub async fn export(&self, out: impl AsRef<Path>) -> Result<Manifest, BackupError> {
+        let staging = Staging::new("export")?;
+        let mut manifest = Manifest {
+            format_version: FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            tables: Vec::new(),
+            files: Vec::new(),
+        };
+        if let Some(pool) = &self.db {
+            fs::create_dir_all(staging.0.join("tables"))?;
+            let tables: Vec<(String, String)> = sqlx::query_as(
+                "SELECT name, sql FROM sqlite_master WHERE type = 'table' \
+                 AND name NOT LIKE 'sqlite_%' ORDER BY name",
+            )
+            .fetch_all(pool)
+            .await?;
+            for (i, (name, sql)) in tables.into_iter().enumerate() {
+                let file = format!("tables/{:03}-{}.jsonl", i, file_stem(&name));
+                let entry = export_table(pool, name, sql, file, &staging.0).await?;
+                manifest.tables.push(entry);
+            }
+        }
+        if let Some(root) = &self.blobs {
+            for relative in archive::walk(root)? {
+                let path = archive::to_archive_name(&relative);
+                let target = staging.0.join("blobs").join(&relative);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let mut writer = HashingWriter::new(File::create(&target)?);
+                let size = io::copy(&mut File::open(root.join(&relative))?, &mut writer)?;
+                manifest.files.push(FileEntry {
+                    path,
+                    size,
+                    sha256: writer.hasher().clone().finalize_hex(),
+                });
+            }
+        }
+        let text = serde_json::to_string_pretty(&manifest).expect("Manifest 总是可以序列化");
+        fs::write(staging.0.join(MANIFEST), text)?;
+        archive::create(&staging.0, out.as_ref())?;
+        Ok(manifest)
+    }
+
+    // 校验全部通过后才写入；数据库部分在一个事务里，失败时目标库不变
+    pub async fn import(&self, path: impl AsRef<Path>) -> Result<ImportReport, BackupError> {
+        let staging = Staging::new("import")?;
+        archive::extract(path.as_ref(), &staging.0)?;
+        let manifest = read_manifest(&staging.0)?;
+        if !manifest.tables.is_empty() && self.db.is_none() {
+            return Err(BackupError::MissingTarget("数据库表"));
+        }
+        if !manifest.files.is_empty() && self.blobs.is_none() {
+            return Err(BackupError::MissingTarget("blob 文件"));
+        }
+        for table in &manifest.tables {
+            verify(&staging.0, &table.file, &table.sha256)?;
+        }
+        for file in &manifest.files {
+            verify(&staging.0.join("blobs"), &file.path, &file.sha256)?;
+        }
+
+        let mut report = ImportReport {
+            format_version: manifest.format_version,
+            app_version: manifest.app_version.clone(),
+            tables: Vec::new(),
+            files: 0,
+        };
+        if let Some(pool) = &self.db {
+            let mut tx = pool.begin().await?;
+            // 表按名字顺序导入，外键到提交时再检查
+            sqlx::query("PRAGMA defer_foreign_keys = ON")
+                .execute(&mut *tx)
+                .await?;
+            for table in &manifest.tables {
+                report
+                    .tables
+                    .push(import_table(&mut tx, table, &staging.0).await?);
+            }
+            tx.commit().await?;
+        }
+        if let Some(root) = &self.blobs {
+            for file in &manifest.files {
+                let target = archive::safe_join(root, Path::new(&file.path))?;
+                if target.exists() {
+                    continue;
+                }
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::copy(staging.0.join("blobs").join(&file.path), &target)?;
+                report.files += 1;
+            }
+        }
+        Ok(report)
+    }
+}
+
+// 只读取清单，不导入，用于查看归档内容
+pub fn inspect(path: impl AsRef<Path>) -> Result<Manifest, BackupError> {
+    let staging = Staging::new("inspect")?;
+    archive::extract(path.as_ref(), &staging.0)?;
+    read_manifest(&staging.0)
+}
+
+async fn export_table(
+    pool: &SqlitePool,
+    name: String,
+    sql: String,
+    file: String,
+    staging: &Path,
+) -> Result<TableEntry, BackupError> {
+    let indexes: Vec<String> = sqlx::query_scalar(
+        "SELECT sql FROM sqlite_master WHERE type = 'index' AND tbl_name = ? \
+         AND sql IS NOT NULL ORDER BY name",
+    )
+    .bind(&name)
+    .fetch_all(pool)
+    .await?;
+    let columns = table_columns(pool, &name).await?;
+    let mut writer = HashingWriter::new(BufWriter::new(File::create(staging.join(&file))?));
+    let mut rows = 0;
+    let query = format!("SELECT * FROM {}", quote(&name));
+    let mut stream = sqlx::query(&query).fetch(pool);
+    while let Some(row) = stream.try_next().await? {
+        let values: Vec<Value> = (0..columns.len()).map(|i| encode(&row, i)).collect();
+        serde_json::to_writer(&mut writer, &values).map_err(io::Error::from)?;
+        writer.write_all(b"\n")?;
+        rows += 1;
+    }
+    writer.flush()?;
+    Ok(TableEntry {
+        name,
+        sql,
+        indexes,
+        columns,
+        rows,
+        file,
+        sha256: writer.hasher().clone().finalize_hex(),
+    })
+}
+
+async fn import_table(
+    tx: &mut sqlx::Transaction<'_, Sqlite>,
+    table: &TableEntry,
+    staging: &Path,
+) -> Result<TableImport, BackupError> {
+    let mut existing: Vec<String> = sqlx::query_scalar("SELECT name FROM pragma_table_info(?)")
+        .bind(&table.name)
+        .fetch_all(&mut **tx)
+        .await?;
+    let created = existing.is_empty();
+    if created {
+        sqlx::query(&table.sql).execute(&mut **tx).await?;
+        for index in &table.indexes {
+            sqlx::query(index).execute(&mut **tx).await?;
+        }
+        existing = table.columns.clone();
+    }
+    // 归档中的列在目标表中的位置；新版本新增的列使用表定义里的默认值
+    let kept: Vec<usize> = (0..table.columns.len())
+        .filter(|&i| existing.contains(&table.columns[i]))
+        .collect();
+    let dropped_columns = table
+        .columns
+        .iter()
+        .filter(|c| !existing.contains(c))
+        .cloned()
+        .collect();
+    let insert = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        quote(&table.name),
+        kept.iter()
+            .map(|&i| quote(&table.columns[i]))
+            .collect::<Vec<_>>()
+            .join(", "),
+        vec!["?"; kept.len()].join(", ")
+    );
+    let mut rows = 0;
+    let reader = BufReader::new(File::open(staging.join(&table.file))?);
+    for line in reader.lines() {
+        let line = line?;
+        let values: Vec<Value> = serde_json::from_str(&line)
+            .map_err(|e| BackupError::Manifest(format!("{}: {}", table.file, e)))?;
+        if values.len() != table.columns.len() {
+            return Err(BackupError::Manifest(format!(
+                "{}: 第 {} 行有 {} 个值，清单中有 {} 列",
+                table.file,
+                rows + 1,
+                values.len(),
+                table.columns.len()
+            )));
+        }
+        let mut query = sqlx::query(&insert);
+        for &i in &kept {
+            query = bind(query, &values[i])?;
+        }
+        query.execute(&mut **tx).await?;
+        rows += 1;
+    }
+    Ok(TableImport {
+        name: table.name.clone(),
+        rows,
+        created,
+        dropped_columns,
+    })
+}
+
+async fn table_columns(pool: &SqlitePool, name: &str) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT name FROM pragma_table_info(?) ORDER BY cid")
+        .bind(name)
+        .fetch_all(pool)
+        .await
+}
+
+// 按每个值的实际存储类型保存；BLOB 写成 {"blob": 十六进制}，与 TEXT 区分开
+fn encode(row: &SqliteRow, index: usize) -> Value {
+    let Ok(raw) = row.try_get_raw(index) else {
+        return Value::Null;
+    };
+    if raw.is_null() {
+        return Value::Null;
+    }
+    match raw.type_info().name() {
+        "INTEGER" | "BOOLEAN" => row.try_get::<i64, _>(index).map(Value::from),
+        "REAL" => row.try_get::<f64, _>(index).map(Value::from),
+        "BLOB" => row
+            .try_get::<Vec<u8>, _>(index)
+            .map(|b| json!({ "blob": hex::encode(b) })),
+        _ => row.try_get::<String, _>(index).map(Value::from),
+    }
+    .unwrap_or(Value::Null)
+}
+
+type Query<'q> = sqlx::query::Query<'q, Sqlite, SqliteArguments<'q>>;
+
+fn bind<'q>(query: Query<'q>, value: &Value) -> Result<Query<'q>, BackupError> {
+    Ok(match value {
+        Value::Null => query.bind(None::<String>),
+        Value::Bool(b) => query.bind(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => query.bind(s.clone()),
+        Value::Object(map) => {
+            let bytes = map
+                .get("blob")
+                .and_then(Value::as_str)
+                .and_then(|h| hex::decode(h).ok())
+                .ok_or_else(|| BackupError::Manifest(format!("无法识别的值: {}", value)))?;
+            query.bind(bytes)
+        }
+        Value::Array(_) => return Err(BackupError::Manifest(format!("无法识别的值: {}", value))),
+    })
+}
+
+fn read_manifest(dir: &Path) -> Result<Manifest, BackupError> {
+    let text = fs::read_to_string(dir.join(MANIFEST))
+        .map_err(|e| BackupError::Manifest(format!("{}: {}", MANIFEST, e)))?;
+    let value: Value =
+        serde_json::from_str(&text).map_err(|e| BackupError::Manifest(e.to_string()))?;
+    // 先看版本: 更新的格式可能连清单结构都不同
+    let found = value
+        .get("format_version")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| BackupError::Manifest("缺少 format_version".into()))? as u32;
+    if found > FORMAT_VERSION {
+        return Err(BackupError::UnsupportedVersion {
+            found,
+            supported: FORMAT_VERSION,
+        });
+    }
+    serde_json::from_value(value).map_err(|e| BackupError::Manifest(e.to_string()))
+}
+
+fn verify(dir: &Path, relative: &str, expected: &str) -> Result<(), BackupError> {
+    let path = archive::safe_join(dir, Path::new(relative))?;
+    let file = File::open(&path)
+        .map_err(|e| BackupError::Manifest(format!("缺少文件 {}: {}", relative, e)))?;
+    let mut reader = HashingReader::new(file);
+    io::copy(&mut reader, &mut io::sink())?;
+    let (_, hasher) = reader.into_parts();
+    if hasher.finalize_hex().eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(BackupError::Checksum(relative.to_string()))
+    }
+}
+
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// 临时目录，drop 时删除
+struct Staging(PathBuf);
+
+impl Staging {
+    fn new(kind: &str) -> io::Result<Self> {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "std-app-{}-{}-{}",
+            kind,
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir)?;
+        Ok(Staging(dir))
+    }
+}
+
+impl Drop for Staging {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
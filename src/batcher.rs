@@ -0,0 +1,170 @@
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::Instant;
+
+// 请求合并: 把单条提交(数据库写入、webhook 发送)攒成批，凑够 max_size 条或
+// 第一条等待了 max_delay 时调用一次 flush_fn，再把结果逐条交还给各自的提交者。
+// flush_fn 按输入顺序返回每条的结果；整批失败时所有提交者都收到同一个错误。
+// 单条也可能失败时让 R 本身是 Result。
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BatchError {
+    #[error("批量处理失败: {0}")]
+    Flush(String),
+    #[error("批量处理返回了 {actual} 条结果，期望 {expected} 条")]
+    Mismatch { expected: usize, actual: usize },
+    #[error("批量处理器已关闭")]
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct BatcherStats {
+    pub batches: u64,
+    pub items: u64,
+    // 因凑满 max_size 而触发的批次，其余是等待超时触发的
+    pub full_batches: u64,
+}
+
+impl BatcherStats {
+    pub fn average_size(&self) -> f64 {
+        if self.batches == 0 {
+            0.0
+        } else {
+            self.items as f64 / self.batches as f64
+        }
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    batches: AtomicU64,
+    items: AtomicU64,
+    full_batches: AtomicU64,
+}
+
+type Pending<T, R> = (T, oneshot::Sender<Result<R, BatchError>>);
+
+// 克隆出来的句柄提交到同一个批次；所有句柄都 drop 后处理完剩余的条目再退出
+pub struct Batcher<T, R> {
+    sender: mpsc::Sender<Pending<T, R>>,
+    counters: Arc<Counters>,
+}
+
+impl<T, R> Clone for Batcher<T, R> {
+    fn clone(&self) -> Self {
+        Batcher {
+            sender: self.sender.clone(),
+            counters: self.counters.clone(),
+        }
+    }
+}
+
+impl<T, R> Batcher<T, R>
+where
+    T: Send + 'static,
+    R: Send + 'static,
+{
+    // 需要在 tokio 运行时中调用；等待中的条目超过 max_size * 4 时 submit 等待(背压)
+    pub fn new<F, Fut, E>(max_size: usize, max_delay: Duration, flush_fn: F) -> Self
+    where
+        F: Fn(Vec<T>) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Vec<R>, E>> + Send + 'static,
+        E: fmt::Display + 'static,
+    {
+        let max_size = max_size.max(1);
+        let (sender, receiver) = mpsc::channel(max_size * 4);
+        let counters = Arc::new(Counters::default());
+        tokio::spawn(run(
+            receiver,
+            max_size,
+            max_delay,
+            flush_fn,
+            counters.clone(),
+        ));
+        Batcher { sender, counters }
+    }
+
+    pub async fn submit(&self, item: T) -> Result<R, BatchError> {
+        let (tx, rx) = oneshot::channel();
+        self.sender
+            .send((item, tx))
+            .await
+            .map_err(|_| BatchError::Closed)?;
+        rx.await.unwrap_or(Err(BatchError::Closed))
+    }
+
+    pub fn stats(&self) -> BatcherStats {
+        BatcherStats {
+            batches: self.counters.batches.load(Ordering::Relaxed),
+            items: self.counters.items.load(Ordering::Relaxed),
+            full_batches: self.counters.full_batches.load(Ordering::Relaxed),
+        }
+    }
+}
+
+async fn run<T, R, F, Fut, E>(
+    mut receiver: mpsc::Receiver<Pending<T, R>>,
+    max_size: usize,
+    max_delay: Duration,
+    flush_fn: F,
+    counters: Arc<Counters>,
+) where
+    F: Fn(Vec<T>) -> Fut,
+    Fut: Future<Output = Result<Vec<R>, E>>,
+    E: fmt::Display,
+{
+    let mut batch = Vec::with_capacity(max_size);
+    while let Some(first) = receiver.recv().await {
+        batch.push(first);
+        let deadline = Instant::now() + max_delay;
+        while batch.len() < max_size {
+            tokio::select! {
+                next = receiver.recv() => match next {
+                    Some(pending) => batch.push(pending),
+                    None => break,
+                },
+                _ = tokio::time::sleep_until(deadline) => break,
+            }
+        }
+        counters.batches.fetch_add(1, Ordering::Relaxed);
+        counters
+            .items
+            .fetch_add(batch.len() as u64, Ordering::Relaxed);
+        if batch.len() == max_size {
+            counters.full_batches.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let (items, waiters): (Vec<T>, Vec<_>) = batch.drain(..).unzip();
+        let expected = items.len();
+        match flush_fn(items).await {
+            Ok(results) if results.len() == expected => {
+                for (waiter, result) in waiters.into_iter().zip(results) {
+                    // 提交者可能已经不等了
+                    let _ = waiter.send(Ok(result));
+                }
+            }
+            Ok(results) => {
+                let error = BatchError::Mismatch {
+                    expected,
+                    actual: results.len(),
+                };
+                for waiter in waiters {
+                    let _ = waiter.send(Err(error.clone()));
+                }
+            }
+            Err(e) => {
+                let error = BatchError::Flush(e.to_string());
+                for waiter in waiters {
+                    let _ = waiter.send(Err(error.clone()));
+                }
+            }
+        }
+    }
+}
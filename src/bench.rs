@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hint::black_box;
+use std::io::{Cursor, Write};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::codec::{self, Framed};
+use crate::collections::RcuCell;
+use crate::import::CsvRecords;
+use crate::stats::Digest;
+use crate::transform::{self, scalar};
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BenchReport {
+    pub name: String,
+    pub iterations: usize,
+    pub mean_ns: u64,
+    pub min_ns: u64,
+    pub max_ns: u64,
+    pub p50_ns: u64,
+    pub p90_ns: u64,
+    pub p99_ns: u64,
+}
+
+impl fmt::Display for BenchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<32} iters={:<6} mean={:?} p50={:?} p90={:?} p99={:?} max={:?}",
+            self.name,
+            self.iterations,
+            Duration::from_nanos(self.mean_ns),
+            Duration::from_nanos(self.p50_ns),
+            Duration::from_nanos(self.p90_ns),
+            Duration::from_nanos(self.p99_ns),
+            Duration::from_nanos(self.max_ns),
+        )
+    }
+}
+
+impl BenchReport {
+    // digest 中的值单位为纳秒，分位数是估计值
+    pub fn from_digest(name: &str, digest: &Digest) -> Self {
+        let mut digest = digest.clone();
+        let mut ns = |q| digest.quantile(q).unwrap_or(0.0) as u64;
+        let (min_ns, p50_ns, p90_ns, p99_ns, max_ns) =
+            (ns(0.0), ns(0.5), ns(0.9), ns(0.99), ns(1.0));
+        BenchReport {
+            name: name.to_string(),
+            iterations: digest.count() as usize,
+            mean_ns: digest.mean().unwrap_or(0.0) as u64,
+            min_ns,
+            max_ns,
+            p50_ns,
+            p90_ns,
+            p99_ns,
+        }
+    }
+}
+
+// 最近秩法，sorted 必须已经升序排列
+pub fn percentile(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+pub struct Bench {
+    name: String,
+    warmup: usize,
+    iterations: usize,
+}
+
+impl Bench {
+    pub fn new(name: &str) -> Self {
+        Bench {
+            name: name.to_string(),
+            warmup: 10,
+            iterations: 100,
+        }
+    }
+
+    pub fn warmup(mut self, n: usize) -> Self {
+        self.warmup = n;
+        self
+    }
+
+    pub fn iterations(mut self, n: usize) -> Self {
+        self.iterations = n.max(1);
+        self
+    }
+
+    // 预热若干次后逐次计时，返回的统计不包含预热
+    pub fn run<F: FnMut()>(&self, mut f: F) -> BenchReport {
+        for _ in 0..self.warmup {
+            f();
+        }
+        let mut samples: Vec<u64> = (0..self.iterations)
+            .map(|_| {
+                let start = Instant::now();
+                f();
+                start.elapsed().as_nanos() as u64
+            })
+            .collect();
+        samples.sort_unstable();
+
+        BenchReport {
+            name: self.name.clone(),
+            iterations: samples.len(),
+            mean_ns: samples.iter().sum::<u64>() / samples.len() as u64,
+            min_ns: samples[0],
+            max_ns: samples[samples.len() - 1],
+            p50_ns: percentile(&samples, 50.0),
+            p90_ns: percentile(&samples, 90.0),
+            p99_ns: percentile(&samples, 99.0),
+        }
+    }
+
+    // workers 个线程并发执行，每个线程执行 iterations 次并各自记录到 digest，最后合并
+    pub fn run_parallel<F: Fn() + Sync>(&self, workers: usize, f: F) -> BenchReport {
+        let digests: Vec<Digest> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers.max(1))
+                .map(|_| {
+                    scope.spawn(|| {
+                        for _ in 0..self.warmup {
+                            f();
+                        }
+                        let mut digest = Digest::default();
+                        for _ in 0..self.iterations {
+                            let start = Instant::now();
+                            f();
+                            digest.add(start.elapsed().as_nanos() as f64);
+                        }
+                        digest
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let mut merged = Digest::default();
+        digests.iter().for_each(|d| merged.merge(d));
+        BenchReport::from_digest(&self.name, &merged)
+    }
+}
+
+type BenchFn = Box<dyn FnMut()>;
+
+#[derive(Default)]
+pub struct Suite {
+    benches: Vec<(Bench, BenchFn)>,
+}
+
+impl Suite {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add<F: FnMut() + 'static>(mut self, bench: Bench, f: F) -> Self {
+        self.benches.push((bench, Box::new(f)));
+        self
+    }
+
+    pub fn run(self) -> Vec<BenchReport> {
+        self.benches
+            .into_iter()
+            .map(|(bench, mut f)| bench.run(&mut f))
+            .collect()
+    }
+}
+
+pub fn to_json(reports: &[BenchReport]) -> String {
+    serde_json::to_string_pretty(reports).expect("BenchReport 总是可以序列化")
+}
+
+// `std-app bench` 使用的内置基准，对应 tests 中 cursor 与缓存的性能对比
+pub fn builtin() -> Suite {
+    const SIZE: usize = 1_000_000;
+    let data = vec![1u8; SIZE];
+    let cursor_data = data.clone();
+
+    let cache: RwLock<HashMap<String, String>> = RwLock::new(
+        (0..1000)
+            .map(|i| (format!("key{}", i), format!("value{}", i)))
+            .collect(),
+    );
+    let write_cache: RwLock<HashMap<String, String>> = RwLock::new(HashMap::new());
+    let rcu_cache = RcuCell::new(cache.read().unwrap().clone());
+
+    let csv: String = (0..10_000)
+        .map(|i| format!("{},user{},user{}@example.com,note {}\n", i, i, i, i))
+        .collect();
+    let csv_strings = csv.clone();
+
+    let mut frames = Vec::new();
+    for i in 0..10_000u32 {
+        codec::write_frame(&mut frames, &[i as u8; 256]).unwrap();
+    }
+    let frames_owned = frames.clone();
+
+    let text: Vec<u8> = b"Hello World, the quick brown fox. ".repeat(32 * 1024);
+    let (text_scalar, text_hex, text_hex_scalar) = (text.clone(), text.clone(), text.clone());
+
+    Suite::new()
+        // 字节变换: 逐字节实现与向量化实现的对比
+        .add(Bench::new("upper_scalar_1mb").iterations(50), move || {
+            let mut buf = text_scalar.clone();
+            scalar::make_ascii_uppercase(&mut buf);
+            black_box(buf);
+        })
+        .add(Bench::new("upper_simd_1mb").iterations(50), move || {
+            let mut buf = text.clone();
+            transform::make_ascii_uppercase(&mut buf);
+            black_box(buf);
+        })
+        .add(
+            Bench::new("hex_encode_scalar_1mb").iterations(20),
+            move || {
+                black_box(scalar::hex_encode(&text_hex_scalar));
+            },
+        )
+        .add(
+            Bench::new("hex_encode_simd_1mb").iterations(20),
+            move || {
+                black_box(transform::hex_encode(&text_hex));
+            },
+        )
+        // 分帧读取: 每帧复制出 Vec 与借用内部缓冲区的对比
+        .add(Bench::new("framed_owned_10k").iterations(20), move || {
+            let total: usize = Framed::new(frames_owned.as_slice())
+                .owned()
+                .map(|f| f.unwrap().len())
+                .sum();
+            black_box(total);
+        })
+        .add(
+            Bench::new("framed_borrowed_10k").iterations(20),
+            move || {
+                let mut framed = Framed::new(frames.as_slice());
+                let mut total = 0;
+                while let Some(frame) = framed.next_frame().unwrap() {
+                    total += frame.len();
+                }
+                black_box(total);
+            },
+        )
+        // 同样的 CSV，逐字段分配 String 与复用 arena 的对比
+        .add(
+            Bench::new("csv_parse_strings_10k").iterations(20),
+            move || {
+                let records: Vec<Vec<String>> = csv_strings
+                    .lines()
+                    .map(|line| line.split(',').map(String::from).collect())
+                    .collect();
+                black_box(records);
+            },
+        )
+        .add(
+            Bench::new("csv_parse_arena_10k").iterations(20),
+            move || {
+                let mut records = CsvRecords::new(csv.as_bytes());
+                let mut fields = 0;
+                while let Some(record) = records.next_record().unwrap() {
+                    fields += record.len();
+                }
+                black_box(fields);
+            },
+        )
+        .add(Bench::new("vec_extend_1mb").iterations(50), move || {
+            let mut vec = Vec::new();
+            vec.extend_from_slice(&data);
+            black_box(vec);
+        })
+        .add(Bench::new("cursor_write_1mb").iterations(50), move || {
+            let mut cursor = Cursor::new(Vec::with_capacity(SIZE));
+            cursor.write_all(&cursor_data).unwrap();
+            black_box(cursor);
+        })
+        .add(Bench::new("rwlock_cache_read_1k"), move || {
+            let cache = cache.read().unwrap();
+            for i in 0..1000 {
+                black_box(cache.get(&format!("key{}", i)));
+            }
+        })
+        // 同样的读取换成 RcuCell，没有读锁
+        .add(Bench::new("rcu_cache_read_1k"), move || {
+            rcu_cache.read(|cache| {
+                for i in 0..1000 {
+                    black_box(cache.get(&format!("key{}", i)));
+                }
+            });
+        })
+        .add(Bench::new("rwlock_cache_write_1k"), move || {
+            for i in 0..1000 {
+                write_cache
+                    .write()
+                    .unwrap()
+                    .insert(format!("key{}", i), format!("value{}", i));
+            }
+        })
+}
@@ -0,0 +1,398 @@
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::chunker::Chunker;
+use crate::collections::BloomFilter;
+use crate::fs_util::FileLock;
+
+#[derive(Error, Debug)]
+pub enum BlobError {
+    #[error("blob 不存在: {0}")]
+    NotFound(BlobId),
+    #[error("无效的 blob id: {0}")]
+    InvalidId(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+// SHA-256 的十六进制表示
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct BlobId(String);
+
+impl BlobId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for BlobId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for BlobId {
+    type Err = BlobError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() == 64 && s.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+            Ok(BlobId(s.to_string()))
+        } else {
+            Err(BlobError::InvalidId(s.to_string()))
+        }
+    }
+}
+
+impl TryFrom<String> for BlobId {
+    type Error = BlobError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<BlobId> for String {
+    fn from(id: BlobId) -> String {
+        id.0
+    }
+}
+
+// 按内容切分后存储的数据，每个块是一个独立的 blob
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkedBlob {
+    pub size: u64,
+    pub chunks: Vec<ChunkRef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkRef {
+    pub id: BlobId,
+    pub len: u64,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct GcStats {
+    pub removed: usize,
+    pub freed_bytes: u64,
+}
+
+// 布局: <root>/objects/ab/abcdef...，引用计数存在同目录的 .ref 文件中。
+// 多个进程可以共享同一目录: 写入方持有 gc.lock 的共享锁，gc 持有排他锁；
+// 引用计数的读改写由 refs.lock 在进程间串行化
+pub struct BlobStore {
+    root: PathBuf,
+    // 串行化本进程内引用计数的读改写
+    lock: Mutex<()>,
+    tmp_seq: AtomicU64,
+    tmp_max_age: Duration,
+    // 启用后，过滤器判定不存在的 id 不再访问磁盘
+    filter: RwLock<Option<BloomFilter>>,
+    skipped: AtomicU64,
+}
+
+// 超过这个时间没有修改的临时文件视为崩溃残留
+pub const DEFAULT_TMP_MAX_AGE: Duration = Duration::from_secs(3600);
+
+impl BlobStore {
+    pub fn open(root: impl AsRef<Path>) -> Result<Self, BlobError> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(root.join("objects"))?;
+        fs::create_dir_all(root.join("tmp"))?;
+        Ok(BlobStore {
+            root,
+            lock: Mutex::new(()),
+            tmp_seq: AtomicU64::new(0),
+            tmp_max_age: DEFAULT_TMP_MAX_AGE,
+            filter: RwLock::new(None),
+            skipped: AtomicU64::new(0),
+        })
+    }
+
+    // gc 只删除修改时间早于 age 的临时文件
+    pub fn tmp_max_age(mut self, age: Duration) -> Self {
+        self.tmp_max_age = age;
+        self
+    }
+
+    // 扫描已有的 blob 建立布隆过滤器。只适用于本进程是唯一写入者的情况，
+    // 其他进程写入的 blob 不会出现在过滤器里
+    pub fn with_bloom_filter(self, expected_items: usize) -> Result<Self, BlobError> {
+        self.rebuild_filter(expected_items)?;
+        Ok(self)
+    }
+
+    fn rebuild_filter(&self, expected_items: usize) -> Result<(), BlobError> {
+        let ids = self.list()?;
+        let mut filter = BloomFilter::new(expected_items.max(ids.len()), 0.01);
+        ids.iter().for_each(|id| filter.insert(id.as_str()));
+        *self.filter.write().unwrap() = Some(filter);
+        Ok(())
+    }
+
+    // 因过滤器判定不存在而跳过的磁盘查找次数
+    pub fn skipped_lookups(&self) -> u64 {
+        self.skipped.load(Ordering::Relaxed)
+    }
+
+    fn definitely_absent(&self, id: &BlobId) -> bool {
+        let absent = matches!(&*self.filter.read().unwrap(), Some(f) if !f.contains(id.as_str()));
+        if absent {
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+        }
+        absent
+    }
+
+    pub fn path(&self, id: &BlobId) -> PathBuf {
+        self.root.join("objects").join(&id.0[..2]).join(&id.0)
+    }
+
+    fn ref_path(&self, id: &BlobId) -> PathBuf {
+        self.path(id).with_extension("ref")
+    }
+
+    pub fn contains(&self, id: &BlobId) -> bool {
+        !self.definitely_absent(id) && self.path(id).exists()
+    }
+
+    // 写入一份数据并增加一次引用，内容相同的数据只存一份
+    pub fn put(&self, data: &[u8]) -> Result<BlobId, BlobError> {
+        self.put_reader(data)
+    }
+
+    // 边读边算哈希，先写到 tmp 目录再 rename，避免读到写了一半的 blob
+    pub fn put_reader<R: Read>(&self, mut reader: R) -> Result<BlobId, BlobError> {
+        let _writer = self.writer_lock()?;
+        let tmp = self.root.join("tmp").join(format!(
+            "{}-{}",
+            std::process::id(),
+            self.tmp_seq.fetch_add(1, Ordering::SeqCst)
+        ));
+        let mut hasher = Sha256::new();
+        {
+            let mut file = File::create(&tmp)?;
+            let mut buffer = [0u8; 64 * 1024];
+            loop {
+                let n = match reader.read(&mut buffer) {
+                    Ok(0) => break,
+                    Ok(n) => n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        let _ = fs::remove_file(&tmp);
+                        return Err(e.into());
+                    }
+                };
+                hasher.update(&buffer[..n]);
+                file.write_all(&buffer[..n])?;
+            }
+            file.sync_all()?;
+        }
+        let id = BlobId(hex::encode(hasher.finalize()));
+
+        let _guard = self.lock.lock().unwrap();
+        let _refs = self.refs_lock()?;
+        let path = self.path(&id);
+        if path.exists() {
+            fs::remove_file(&tmp)?;
+        } else {
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::rename(&tmp, &path)?;
+        }
+        if let Some(filter) = self.filter.write().unwrap().as_mut() {
+            filter.insert(id.as_str());
+        }
+        let count = self.read_refcount(&id)?;
+        self.write_refcount(&id, count + 1)?;
+        Ok(id)
+    }
+
+    // 按内容切分后逐块写入，与已有数据相同的块只增加引用。
+    // 返回的 ChunkedBlob 需要调用方保存，读取和释放都依赖它
+    pub fn put_chunked<R: Read>(
+        &self,
+        reader: R,
+        chunker: &Chunker,
+    ) -> Result<ChunkedBlob, BlobError> {
+        let mut blob = ChunkedBlob::default();
+        for chunk in chunker.stream(reader) {
+            let chunk = chunk?;
+            let id = match self.put(&chunk.data) {
+                Ok(id) => id,
+                Err(e) => {
+                    // 已经写入的块不再被引用，释放后由 gc 清理
+                    let _ = self.release_chunked(&blob);
+                    return Err(e);
+                }
+            };
+            blob.size += chunk.data.len() as u64;
+            blob.chunks.push(ChunkRef {
+                id,
+                len: chunk.data.len() as u64,
+            });
+        }
+        Ok(blob)
+    }
+
+    // 按顺序把各个块写入 writer，返回写入的字节数
+    pub fn read_chunked<W: Write>(
+        &self,
+        blob: &ChunkedBlob,
+        mut writer: W,
+    ) -> Result<u64, BlobError> {
+        let mut total = 0;
+        for chunk in &blob.chunks {
+            total += io::copy(&mut self.open_blob(&chunk.id)?, &mut writer)?;
+        }
+        Ok(total)
+    }
+
+    pub fn release_chunked(&self, blob: &ChunkedBlob) -> Result<(), BlobError> {
+        for chunk in &blob.chunks {
+            self.release(&chunk.id)?;
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, id: &BlobId) -> Result<Vec<u8>, BlobError> {
+        let mut data = Vec::new();
+        self.open_blob(id)?.read_to_end(&mut data)?;
+        Ok(data)
+    }
+
+    pub fn open_blob(&self, id: &BlobId) -> Result<File, BlobError> {
+        if self.definitely_absent(id) {
+            return Err(BlobError::NotFound(id.clone()));
+        }
+        File::open(self.path(id)).map_err(|e| match e.kind() {
+            io::ErrorKind::NotFound => BlobError::NotFound(id.clone()),
+            _ => e.into(),
+        })
+    }
+
+    // 重新计算哈希，检查磁盘上的内容是否损坏
+    pub fn verify(&self, id: &BlobId) -> Result<bool, BlobError> {
+        let mut file = self.open_blob(id)?;
+        let mut hasher = Sha256::new();
+        io::copy(&mut file, &mut hasher)?;
+        Ok(hex::encode(hasher.finalize()) == id.0)
+    }
+
+    pub fn add_ref(&self, id: &BlobId) -> Result<u64, BlobError> {
+        let _writer = self.writer_lock()?;
+        let _guard = self.lock.lock().unwrap();
+        let _refs = self.refs_lock()?;
+        if !self.contains(id) {
+            return Err(BlobError::NotFound(id.clone()));
+        }
+        let count = self.read_refcount(id)? + 1;
+        self.write_refcount(id, count)?;
+        Ok(count)
+    }
+
+    // 减少一次引用，计数归零后由 gc 删除
+    pub fn release(&self, id: &BlobId) -> Result<u64, BlobError> {
+        let _writer = self.writer_lock()?;
+        let _guard = self.lock.lock().unwrap();
+        let _refs = self.refs_lock()?;
+        if !self.contains(id) {
+            return Err(BlobError::NotFound(id.clone()));
+        }
+        let count = self.read_refcount(id)?.saturating_sub(1);
+        self.write_refcount(id, count)?;
+        Ok(count)
+    }
+
+    pub fn refcount(&self, id: &BlobId) -> Result<u64, BlobError> {
+        if !self.contains(id) {
+            return Err(BlobError::NotFound(id.clone()));
+        }
+        self.read_refcount(id)
+    }
+
+    pub fn list(&self) -> Result<Vec<BlobId>, BlobError> {
+        let mut ids = Vec::new();
+        for shard in fs::read_dir(self.root.join("objects"))? {
+            for entry in fs::read_dir(shard?.path())? {
+                let name = entry?.file_name();
+                if let Ok(id) = name.to_string_lossy().parse::<BlobId>() {
+                    ids.push(id);
+                }
+            }
+        }
+        ids.sort();
+        Ok(ids)
+    }
+
+    // 删除没有引用的 blob，以及崩溃后残留的临时文件。
+    // 排他锁等所有进程的写入结束后才取得，gc 期间新的写入会等待
+    pub fn gc(&self) -> Result<GcStats, BlobError> {
+        // 先取文件锁再取进程内的锁: 本进程的写入持有共享锁时会去等 self.lock
+        let _gc_lock = FileLock::exclusive(self.root.join("gc.lock"))?;
+        let _guard = self.lock.lock().unwrap();
+        let mut stats = GcStats::default();
+        for id in self.list()? {
+            if self.read_refcount(&id)? > 0 {
+                continue;
+            }
+            let path = self.path(&id);
+            stats.freed_bytes += fs::metadata(&path)?.len();
+            fs::remove_file(&path)?;
+            let _ = fs::remove_file(self.ref_path(&id));
+            stats.removed += 1;
+        }
+        // flock 在网络文件系统上不一定可靠，只删除足够旧的临时文件，避免误删正在写入的
+        let now = SystemTime::now();
+        for entry in fs::read_dir(self.root.join("tmp"))? {
+            let entry = entry?;
+            let modified = entry.metadata()?.modified()?;
+            if now.duration_since(modified).unwrap_or_default() >= self.tmp_max_age {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+        // 布隆过滤器不能删除元素，gc 删除了 blob 后重建
+        let len = self
+            .filter
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|f| f.len() as usize);
+        if let Some(len) = len.filter(|_| stats.removed > 0) {
+            self.rebuild_filter(len)?;
+        }
+        Ok(stats)
+    }
+
+    fn writer_lock(&self) -> Result<FileLock, BlobError> {
+        Ok(FileLock::shared(self.root.join("gc.lock"))?)
+    }
+
+    fn refs_lock(&self) -> Result<FileLock, BlobError> {
+        Ok(FileLock::exclusive(self.root.join("refs.lock"))?)
+    }
+
+    fn read_refcount(&self, id: &BlobId) -> Result<u64, BlobError> {
+        match fs::read_to_string(self.ref_path(id)) {
+            Ok(s) => Ok(s.trim().parse().unwrap_or(0)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn write_refcount(&self, id: &BlobId, count: u64) -> Result<(), BlobError> {
+        let path = self.ref_path(id);
+        let tmp = path.with_extension("ref.tmp");
+        fs::write(&tmp, count.to_string())?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
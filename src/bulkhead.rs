@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use thiserror::Error;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::metrics::{Counter, Gauge, Registry};
+
+// 舱壁隔离: 给每个子系统(webhook 投递、报表导出、面向用户的请求……)分配固定数量的并发名额，
+// 某个子系统堵塞时只会耗尽自己的名额，不会占满共享的 HTTP 连接或阻塞线程池。
+// 名额用完时按配置等待一段时间，仍然没有空位就拒绝，并按舱壁分别计数。
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum BulkheadError {
+    #[error("舱壁 {0} 已满")]
+    Full(String),
+    #[error("未定义的舱壁: {0}")]
+    Unknown(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BulkheadStats {
+    pub name: String,
+    pub max_concurrent: usize,
+    pub active: usize,
+    pub rejected: u64,
+}
+
+struct Metrics {
+    active: Gauge,
+    rejected: Counter,
+}
+
+struct Inner {
+    name: String,
+    max_concurrent: usize,
+    max_wait: Duration,
+    semaphore: Arc<Semaphore>,
+    rejected: AtomicU64,
+    metrics: Option<Metrics>,
+}
+
+#[derive(Clone)]
+pub struct Bulkhead {
+    inner: Arc<Inner>,
+}
+
+// 持有期间占用一个名额
+pub struct BulkheadPermit {
+    _permit: OwnedSemaphorePermit,
+    bulkhead: Bulkhead,
+}
+
+impl Drop for BulkheadPermit {
+    fn drop(&mut self) {
+        // 这时名额还没有归还
+        self.bulkhead.publish(1);
+    }
+}
+
+impl Bulkhead {
+    // max_wait 为零时名额用完立即拒绝
+    pub fn new(name: &str, max_concurrent: usize, max_wait: Duration) -> Self {
+        Self::create(name, max_concurrent, max_wait, None)
+    }
+
+    fn create(
+        name: &str,
+        max_concurrent: usize,
+        max_wait: Duration,
+        registry: Option<&Registry>,
+    ) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        let metrics = registry.map(|r| Metrics {
+            active: r.gauge(&format!("bulkhead_{}_active", name)),
+            rejected: r.counter(&format!("bulkhead_{}_rejected_total", name)),
+        });
+        Bulkhead {
+            inner: Arc::new(Inner {
+                name: name.to_string(),
+                max_concurrent,
+                max_wait,
+                semaphore: Arc::new(Semaphore::new(max_concurrent)),
+                rejected: AtomicU64::new(0),
+                metrics,
+            }),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    pub fn stats(&self) -> BulkheadStats {
+        BulkheadStats {
+            name: self.inner.name.clone(),
+            max_concurrent: self.inner.max_concurrent,
+            active: self.active(),
+            rejected: self.inner.rejected.load(Ordering::Relaxed),
+        }
+    }
+
+    // 不等待
+    pub fn try_enter(&self) -> Result<BulkheadPermit, BulkheadError> {
+        match self.inner.semaphore.clone().try_acquire_owned() {
+            Ok(permit) => Ok(self.admit(permit)),
+            Err(_) => Err(self.reject()),
+        }
+    }
+
+    // 最多等待 max_wait
+    pub async fn enter(&self) -> Result<BulkheadPermit, BulkheadError> {
+        if self.inner.max_wait.is_zero() {
+            return self.try_enter();
+        }
+        let acquire = self.inner.semaphore.clone().acquire_owned();
+        match tokio::time::timeout(self.inner.max_wait, acquire).await {
+            Ok(Ok(permit)) => Ok(self.admit(permit)),
+            // 信号量不会被关闭，只可能是超时
+            _ => Err(self.reject()),
+        }
+    }
+
+    pub async fn run<F: Future>(&self, fut: F) -> Result<F::Output, BulkheadError> {
+        let _permit = self.enter().await?;
+        Ok(fut.await)
+    }
+
+    // 在阻塞线程池中执行 f，同一舱壁最多占用 max_concurrent 个线程
+    pub async fn run_blocking<F, R>(&self, f: F) -> Result<R, BulkheadError>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let permit = self.enter().await?;
+        let result = tokio::task::spawn_blocking(move || {
+            let _permit = permit;
+            f()
+        })
+        .await;
+        match result {
+            Ok(value) => Ok(value),
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+
+    fn active(&self) -> usize {
+        self.inner.max_concurrent - self.inner.semaphore.available_permits()
+    }
+
+    fn admit(&self, permit: OwnedSemaphorePermit) -> BulkheadPermit {
+        self.publish(0);
+        BulkheadPermit {
+            _permit: permit,
+            bulkhead: self.clone(),
+        }
+    }
+
+    fn reject(&self) -> BulkheadError {
+        self.inner.rejected.fetch_add(1, Ordering::Relaxed);
+        if let Some(m) = &self.inner.metrics {
+            m.rejected.inc();
+        }
+        BulkheadError::Full(self.inner.name.clone())
+    }
+
+    // releasing 是即将归还的名额数
+    fn publish(&self, releasing: usize) {
+        if let Some(m) = &self.inner.metrics {
+            m.active.set(self.active().saturating_sub(releasing) as f64);
+        }
+    }
+}
+
+// 按名称管理一组舱壁，通常在启动时定义好，之后各子系统按名称取用
+#[derive(Default)]
+pub struct Bulkheads {
+    bulkheads: Mutex<BTreeMap<String, Bulkhead>>,
+    registry: Option<Arc<Registry>>,
+}
+
+impl Bulkheads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 每个舱壁注册 bulkhead_<name>_active 仪表和 bulkhead_<name>_rejected_total 计数器
+    pub fn metrics(mut self, registry: Arc<Registry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    // 同名的舱壁已经存在时替换，已经拿到旧舱壁的调用方不受影响
+    pub fn define(&self, name: &str, max_concurrent: usize, max_wait: Duration) -> Bulkhead {
+        let bulkhead = Bulkhead::create(name, max_concurrent, max_wait, self.registry.as_deref());
+        self.bulkheads
+            .lock()
+            .unwrap()
+            .insert(name.to_string(), bulkhead.clone());
+        bulkhead
+    }
+
+    pub fn get(&self, name: &str) -> Result<Bulkhead, BulkheadError> {
+        self.bulkheads
+            .lock()
+            .unwrap()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| BulkheadError::Unknown(name.to_string()))
+    }
+
+    pub async fn run<F: Future>(&self, name: &str, fut: F) -> Result<F::Output, BulkheadError> {
+        self.get(name)?.run(fut).await
+    }
+
+    // 按名称排序
+    pub fn stats(&self) -> Vec<BulkheadStats> {
+        self.bulkheads
+            .lock()
+            .unwrap()
+            .values()
+            .map(|b| b.stats())
+            .collect()
+    }
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::clock::{self, SharedClock};
+use crate::context::costs;
+
+// 带过期时间的缓存，过期判断基于注入的 Clock
+pub struct TtlCache<K, V> {
+    ttl: Duration,
+    clock: SharedClock,
+    entries: Mutex<HashMap<K, (V, Instant)>>,
+}
+
+impl<K: Eq + Hash, V: Clone> TtlCache<K, V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_clock(ttl, clock::system())
+    }
+
+    pub fn with_clock(ttl: Duration, clock: SharedClock) -> Self {
+        TtlCache {
+            ttl,
+            clock,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        self.insert_with_ttl(key, value, self.ttl);
+    }
+
+    pub fn insert_with_ttl(&self, key: K, value: V, ttl: Duration) {
+        let expires = self.clock.now() + ttl;
+        self.entries.lock().unwrap().insert(key, (value, expires));
+    }
+
+    // 已过期的条目在读取时顺便删除
+    pub fn get(&self, key: &K) -> Option<V> {
+        let now = self.clock.now();
+        let mut entries = self.entries.lock().unwrap();
+        let value = match entries.get(key) {
+            Some((value, expires)) if *expires > now => Some(value.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        };
+        costs::record_cache(value.is_some());
+        value
+    }
+
+    pub fn remove(&self, key: &K) -> Option<V> {
+        self.entries.lock().unwrap().remove(key).map(|(v, _)| v)
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    // 包含尚未清理的过期条目
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 清理所有过期条目，返回清理数量
+    pub fn purge_expired(&self) -> usize {
+        let now = self.clock.now();
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|_, (_, expires)| *expires > now);
+        before - entries.len()
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::testkit::gen::Rng;
+
+// 故障注入: 给 HTTP 调用、数据库操作、文件读写包一层，
+// 按概率或调度表注入延迟、错误和部分失败，用于测试重试和熔断逻辑。
+
+#[derive(Error, Debug, Clone, PartialEq)]
+#[error("注入的故障 [{op}]: {message}")]
+pub struct InjectedFault {
+    pub op: String,
+    pub message: String,
+}
+
+impl From<InjectedFault> for io::Error {
+    fn from(fault: InjectedFault) -> Self {
+        io::Error::other(fault)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fault {
+    Latency(Duration),
+    Error(String),
+    // 操作实际执行了，但调用方收到错误(例如响应丢失、只写了一半)
+    Partial(String),
+}
+
+#[derive(Debug, Clone)]
+pub enum Trigger {
+    Always,
+    Probability(f64),
+    // 第几次调用触发，从 1 开始计数
+    Calls(Vec<u64>),
+    // 按调度表循环，true 表示触发
+    Schedule(Vec<bool>),
+}
+
+struct Rule {
+    op: String,
+    trigger: Trigger,
+    fault: Fault,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct OpStats {
+    pub calls: u64,
+    pub injected: u64,
+}
+
+struct State {
+    rng: Rng,
+    stats: HashMap<String, OpStats>,
+}
+
+#[derive(Clone)]
+pub struct Chaos {
+    rules: Arc<Vec<Rule>>,
+    state: Arc<Mutex<State>>,
+    enabled: bool,
+}
+
+impl Chaos {
+    // 相同的种子和调用顺序会得到相同的故障序列
+    pub fn new(seed: u64) -> Self {
+        Chaos {
+            rules: Arc::new(Vec::new()),
+            state: Arc::new(Mutex::new(State {
+                rng: Rng::new(seed),
+                stats: HashMap::new(),
+            })),
+            enabled: true,
+        }
+    }
+
+    // 不注入任何故障，生产环境下使用
+    pub fn disabled() -> Self {
+        Chaos {
+            enabled: false,
+            ..Self::new(0)
+        }
+    }
+
+    // op 为 "*" 时匹配所有操作
+    pub fn rule(mut self, op: &str, trigger: Trigger, fault: Fault) -> Self {
+        Arc::get_mut(&mut self.rules)
+            .expect("Chaos 克隆之后不能再添加规则")
+            .push(Rule {
+                op: op.to_string(),
+                trigger,
+                fault,
+            });
+        self
+    }
+
+    pub fn stats(&self, op: &str) -> OpStats {
+        self.state
+            .lock()
+            .unwrap()
+            .stats
+            .get(op)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    // 记录一次调用并返回本次需要注入的故障
+    pub fn decide(&self, op: &str) -> Vec<Fault> {
+        if !self.enabled {
+            return Vec::new();
+        }
+        let mut state = self.state.lock().unwrap();
+        let calls = {
+            let stats = state.stats.entry(op.to_string()).or_default();
+            stats.calls += 1;
+            stats.calls
+        };
+        let mut faults = Vec::new();
+        for rule in self.rules.iter().filter(|r| r.op == op || r.op == "*") {
+            let fire = match &rule.trigger {
+                Trigger::Always => true,
+                Trigger::Probability(p) => (state.rng.next_u64() as f64 / u64::MAX as f64) < *p,
+                Trigger::Calls(calls_to_fail) => calls_to_fail.contains(&calls),
+                Trigger::Schedule(schedule) => {
+                    !schedule.is_empty() && schedule[((calls - 1) as usize) % schedule.len()]
+                }
+            };
+            if fire {
+                faults.push(rule.fault.clone());
+            }
+        }
+        if faults.iter().any(|f| !matches!(f, Fault::Latency(_))) {
+            state.stats.get_mut(op).unwrap().injected += 1;
+        }
+        faults
+    }
+
+    fn fault(op: &str, message: &str) -> InjectedFault {
+        InjectedFault {
+            op: op.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    // 包装一次异步调用，例如 HTTP 请求或数据库查询
+    pub async fn call<F, Fut, T, E>(&self, op: &str, f: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: From<InjectedFault>,
+    {
+        let mut partial = None;
+        for fault in self.decide(op) {
+            match fault {
+                Fault::Latency(d) => tokio::time::sleep(d).await,
+                Fault::Error(message) => return Err(Self::fault(op, &message).into()),
+                Fault::Partial(message) => partial = Some(message),
+            }
+        }
+        let result = f().await;
+        match partial {
+            Some(message) => Err(Self::fault(op, &message).into()),
+            None => result,
+        }
+    }
+
+    // 同步版本，用于文件操作等
+    pub fn call_sync<F, T, E>(&self, op: &str, f: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+        E: From<InjectedFault>,
+    {
+        let mut partial = None;
+        for fault in self.decide(op) {
+            match fault {
+                Fault::Latency(d) => std::thread::sleep(d),
+                Fault::Error(message) => return Err(Self::fault(op, &message).into()),
+                Fault::Partial(message) => partial = Some(message),
+            }
+        }
+        let result = f();
+        match partial {
+            Some(message) => Err(Self::fault(op, &message).into()),
+            None => result,
+        }
+    }
+
+    pub fn reader<R: Read>(&self, op: &str, inner: R) -> ChaosIo<R> {
+        ChaosIo {
+            inner,
+            op: op.to_string(),
+            chaos: self.clone(),
+        }
+    }
+
+    pub fn writer<W: Write>(&self, op: &str, inner: W) -> ChaosIo<W> {
+        ChaosIo {
+            inner,
+            op: op.to_string(),
+            chaos: self.clone(),
+        }
+    }
+}
+
+// 每次 read/write 算一次调用；Partial 表现为只读写一半数据
+pub struct ChaosIo<T> {
+    inner: T,
+    op: String,
+    chaos: Chaos,
+}
+
+impl<T> ChaosIo<T> {
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn apply(&self) -> io::Result<bool> {
+        let mut partial = false;
+        for fault in self.chaos.decide(&self.op) {
+            match fault {
+                Fault::Latency(d) => std::thread::sleep(d),
+                Fault::Error(message) => return Err(Chaos::fault(&self.op, &message).into()),
+                Fault::Partial(_) => partial = true,
+            }
+        }
+        Ok(partial)
+    }
+}
+
+impl<R: Read> Read for ChaosIo<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let partial = self.apply()?;
+        let len = if partial {
+            buf.len().div_ceil(2)
+        } else {
+            buf.len()
+        };
+        self.inner.read(&mut buf[..len])
+    }
+}
+
+impl<W: Write> Write for ChaosIo<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let partial = self.apply()?;
+        let len = if partial {
+            buf.len().div_ceil(2)
+        } else {
+            buf.len()
+        };
+        self.inner.write(&buf[..len])
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
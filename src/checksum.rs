@@ -0,0 +1,278 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+
+use serde::{Deserialize, Serialize};
+use sha2::compress256;
+use sha2::digest::generic_array::GenericArray;
+use thiserror::Error;
+
+// 可以中途保存和恢复的 SHA-256: suspend 导出内部状态(8 个字、未满一块的缓冲、已处理长度)，
+// 写入文件或数据库后，下次启动用 resume 接着计算，结果与一次性计算完全相同。
+// 用于断点续传的下载校验和分片上传的完整性检查。
+
+const BLOCK: usize = 64;
+const INIT: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+// to_bytes 格式的版本标记
+const MAGIC: &[u8; 8] = b"SHA256S1";
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ChecksumError {
+    #[error("无效的哈希状态: {0}")]
+    InvalidState(String),
+
+    #[error("校验和不匹配: 期望 {expected}，实际 {actual}")]
+    Mismatch { expected: String, actual: String },
+}
+
+// 挂起时导出的状态，可以用 serde 保存，也可以用 to_bytes 得到紧凑的二进制形式
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HashState {
+    pub state: [u32; 8],
+    // 已经输入的总字节数
+    pub len: u64,
+    // 未满一块的剩余数据，长度总是 len % 64
+    pub pending: Vec<u8>,
+}
+
+impl HashState {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(MAGIC.len() + 32 + 8 + self.pending.len());
+        out.extend_from_slice(MAGIC);
+        for word in self.state {
+            out.extend_from_slice(&word.to_be_bytes());
+        }
+        out.extend_from_slice(&self.len.to_be_bytes());
+        out.extend_from_slice(&self.pending);
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ChecksumError> {
+        let header = MAGIC.len() + 32 + 8;
+        if bytes.len() < header || &bytes[..MAGIC.len()] != MAGIC {
+            return Err(ChecksumError::InvalidState("缺少状态头".into()));
+        }
+        let word = |i: usize| {
+            let at = MAGIC.len() + i * 4;
+            u32::from_be_bytes(bytes[at..at + 4].try_into().unwrap())
+        };
+        let state = std::array::from_fn(word);
+        let len = u64::from_be_bytes(bytes[header - 8..header].try_into().unwrap());
+        let state = HashState {
+            state,
+            len,
+            pending: bytes[header..].to_vec(),
+        };
+        state.validate()?;
+        Ok(state)
+    }
+
+    fn validate(&self) -> Result<(), ChecksumError> {
+        if self.pending.len() != (self.len % BLOCK as u64) as usize {
+            return Err(ChecksumError::InvalidState(format!(
+                "剩余数据 {} 字节与长度 {} 不一致",
+                self.pending.len(),
+                self.len
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct Sha256 {
+    state: [u32; 8],
+    len: u64,
+    buf: [u8; BLOCK],
+    buf_len: usize,
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Sha256::new()
+    }
+}
+
+impl fmt::Debug for Sha256 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sha256").field("len", &self.len).finish()
+    }
+}
+
+impl Sha256 {
+    pub fn new() -> Self {
+        Sha256 {
+            state: INIT,
+            len: 0,
+            buf: [0; BLOCK],
+            buf_len: 0,
+        }
+    }
+
+    pub fn resume(state: &HashState) -> Result<Self, ChecksumError> {
+        state.validate()?;
+        let mut hasher = Sha256 {
+            state: state.state,
+            len: state.len,
+            buf: [0; BLOCK],
+            buf_len: state.pending.len(),
+        };
+        hasher.buf[..state.pending.len()].copy_from_slice(&state.pending);
+        Ok(hasher)
+    }
+
+    pub fn suspend(&self) -> HashState {
+        HashState {
+            state: self.state,
+            len: self.len,
+            pending: self.buf[..self.buf_len].to_vec(),
+        }
+    }
+
+    // 已经输入的字节数，断点续传时就是下一次读取的偏移
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.len += data.len() as u64;
+        if self.buf_len > 0 {
+            let n = (BLOCK - self.buf_len).min(data.len());
+            self.buf[self.buf_len..self.buf_len + n].copy_from_slice(&data[..n]);
+            self.buf_len += n;
+            data = &data[n..];
+            if self.buf_len < BLOCK {
+                return;
+            }
+            compress256(&mut self.state, &[self.buf.into()]);
+            self.buf_len = 0;
+        }
+        let full = data.len() / BLOCK * BLOCK;
+        let blocks: Vec<_> = data[..full]
+            .chunks_exact(BLOCK)
+            .map(|b| *GenericArray::from_slice(b))
+            .collect();
+        compress256(&mut self.state, &blocks);
+        let rest = &data[full..];
+        self.buf[..rest.len()].copy_from_slice(rest);
+        self.buf_len = rest.len();
+    }
+
+    pub fn finalize(mut self) -> [u8; 32] {
+        let bits = self.len.wrapping_mul(8);
+        let mut tail = self.buf[..self.buf_len].to_vec();
+        tail.push(0x80);
+        while tail.len() % BLOCK != BLOCK - 8 {
+            tail.push(0);
+        }
+        tail.extend_from_slice(&bits.to_be_bytes());
+        let blocks: Vec<_> = tail
+            .chunks_exact(BLOCK)
+            .map(|b| *GenericArray::from_slice(b))
+            .collect();
+        compress256(&mut self.state, &blocks);
+
+        let mut out = [0u8; 32];
+        for (chunk, word) in out.chunks_exact_mut(4).zip(self.state) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        out
+    }
+
+    pub fn finalize_hex(self) -> String {
+        hex::encode(self.finalize())
+    }
+
+    pub fn digest(data: &[u8]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+// 比较十六进制摘要，忽略大小写
+pub fn verify_hex(hasher: Sha256, expected: &str) -> Result<(), ChecksumError> {
+    let actual = hasher.finalize_hex();
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(ChecksumError::Mismatch {
+            expected: expected.to_string(),
+            actual,
+        })
+    }
+}
+
+// 读取时计算哈希
+pub struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> HashingReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self::resume(inner, Sha256::new())
+    }
+
+    // inner 应该从 hasher.len() 的位置开始读
+    pub fn resume(inner: R, hasher: Sha256) -> Self {
+        HashingReader { inner, hasher }
+    }
+
+    pub fn hasher(&self) -> &Sha256 {
+        &self.hasher
+    }
+
+    pub fn into_parts(self) -> (R, Sha256) {
+        (self.inner, self.hasher)
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+}
+
+// 写入时计算哈希，只统计 inner 实际接受的部分
+pub struct HashingWriter<W> {
+    inner: W,
+    hasher: Sha256,
+}
+
+impl<W: Write> HashingWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self::resume(inner, Sha256::new())
+    }
+
+    pub fn resume(inner: W, hasher: Sha256) -> Self {
+        HashingWriter { inner, hasher }
+    }
+
+    pub fn hasher(&self) -> &Sha256 {
+        &self.hasher
+    }
+
+    pub fn into_parts(self) -> (W, Sha256) {
+        (self.inner, self.hasher)
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
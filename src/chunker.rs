@@ -0,0 +1,192 @@
+use std::io::{self, Read};
+
+// 按内容切分(FastCDC): 用 gear 滚动哈希寻找切分点，切分点只取决于附近的内容，
+// 文件中间插入或删除数据只影响附近的一两个块，其他块保持不变，适合去重存储和增量同步。
+// 在 avg_size 之前使用更严格的掩码、之后使用更宽松的掩码，让块大小集中在平均值附近。
+
+pub const DEFAULT_MIN_SIZE: usize = 2 * 1024;
+pub const DEFAULT_AVG_SIZE: usize = 8 * 1024;
+pub const DEFAULT_MAX_SIZE: usize = 64 * 1024;
+
+// 固定种子生成，保证不同进程、不同版本的切分结果一致
+const GEAR: [u64; 256] = gear_table(0x9e3779b97f4a7c15);
+
+const fn gear_table(seed: u64) -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state = seed;
+    let mut i = 0;
+    while i < 256 {
+        // splitmix64
+        state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chunker {
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+    // 掩码取哈希的高位，高位受最近 64 个字节影响
+    mask_small: u64,
+    mask_large: u64,
+}
+
+impl Default for Chunker {
+    fn default() -> Self {
+        Chunker::new(DEFAULT_MIN_SIZE, DEFAULT_AVG_SIZE, DEFAULT_MAX_SIZE)
+    }
+}
+
+impl Chunker {
+    // avg_size 会向下取整到 2 的幂
+    pub fn new(min_size: usize, avg_size: usize, max_size: usize) -> Self {
+        let min_size = min_size.max(1);
+        let bits = avg_size.max(4).ilog2();
+        let avg_size = (1usize << bits).max(min_size);
+        Chunker {
+            min_size,
+            avg_size,
+            max_size: max_size.max(avg_size),
+            mask_small: !0u64 << (64 - (bits + 1)),
+            mask_large: !0u64 << (64 - (bits - 1)),
+        }
+    }
+
+    // 最小值取平均值的 1/4，最大值取 8 倍
+    pub fn with_avg_size(avg_size: usize) -> Self {
+        Chunker::new(avg_size / 4, avg_size, avg_size.saturating_mul(8))
+    }
+
+    pub fn min_size(&self) -> usize {
+        self.min_size
+    }
+
+    pub fn avg_size(&self) -> usize {
+        self.avg_size
+    }
+
+    pub fn max_size(&self) -> usize {
+        self.max_size
+    }
+
+    // data 开头第一个块的长度；data 不足 max_size 且找不到切分点时返回整个长度
+    pub fn cut(&self, data: &[u8]) -> usize {
+        if data.len() <= self.min_size {
+            return data.len();
+        }
+        let end = data.len().min(self.max_size);
+        let normal = end.min(self.avg_size);
+        let mut hash = 0u64;
+        for (i, &b) in data.iter().enumerate().take(normal).skip(self.min_size) {
+            hash = (hash << 1).wrapping_add(GEAR[b as usize]);
+            if hash & self.mask_small == 0 {
+                return i + 1;
+            }
+        }
+        for (i, &b) in data.iter().enumerate().take(end).skip(normal) {
+            hash = (hash << 1).wrapping_add(GEAR[b as usize]);
+            if hash & self.mask_large == 0 {
+                return i + 1;
+            }
+        }
+        end
+    }
+
+    pub fn chunks<'a>(&self, data: &'a [u8]) -> Chunks<'a> {
+        Chunks {
+            chunker: *self,
+            rest: data,
+        }
+    }
+
+    pub fn stream<R: Read>(&self, reader: R) -> StreamChunks<R> {
+        StreamChunks {
+            chunker: *self,
+            reader,
+            buf: Vec::with_capacity(self.max_size),
+            offset: 0,
+            eof: false,
+        }
+    }
+}
+
+pub struct Chunks<'a> {
+    chunker: Chunker,
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for Chunks<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let (chunk, rest) = self.rest.split_at(self.chunker.cut(self.rest));
+        self.rest = rest;
+        Some(chunk)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    // 在整个流中的起始位置
+    pub offset: u64,
+    pub data: Vec<u8>,
+}
+
+// 从 reader 流式切分，最多缓存 max_size 字节；切分结果与对完整数据调用 chunks 相同
+pub struct StreamChunks<R> {
+    chunker: Chunker,
+    reader: R,
+    buf: Vec<u8>,
+    offset: u64,
+    eof: bool,
+}
+
+impl<R: Read> StreamChunks<R> {
+    fn fill(&mut self) -> io::Result<()> {
+        let max = self.chunker.max_size;
+        while !self.eof && self.buf.len() < max {
+            let len = self.buf.len();
+            self.buf.resize(max, 0);
+            match self.reader.read(&mut self.buf[len..]) {
+                Ok(n) => {
+                    self.buf.truncate(len + n);
+                    self.eof = n == 0;
+                }
+                Err(e) => {
+                    self.buf.truncate(len);
+                    if e.kind() != io::ErrorKind::Interrupted {
+                        return Err(e);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read> Iterator for StreamChunks<R> {
+    type Item = io::Result<Chunk>;
+
+    fn next(&mut self) -> Option<io::Result<Chunk>> {
+        if let Err(e) = self.fill() {
+            return Some(Err(e));
+        }
+        if self.buf.is_empty() {
+            return None;
+        }
+        let len = self.chunker.cut(&self.buf);
+        let data: Vec<u8> = self.buf.drain(..len).collect();
+        let offset = self.offset;
+        self.offset += len as u64;
+        Some(Ok(Chunk { offset, data }))
+    }
+}
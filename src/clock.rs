@@ -0,0 +1,153 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::{Duration, Instant, SystemTime};
+
+// 时间来源抽象: 生产环境使用系统时间，测试中使用可手动推进的模拟时钟，
+// TTL 缓存、重试退避、限流器都通过它读取时间和等待。
+
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+pub trait Clock: Send + Sync + fmt::Debug {
+    fn now(&self) -> Instant;
+
+    fn system_time(&self) -> SystemTime;
+
+    fn sleep(&self, duration: Duration) -> Sleep;
+}
+
+pub type SharedClock = Arc<dyn Clock>;
+
+// 默认使用系统时钟
+pub fn system() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(tokio::time::sleep(duration))
+    }
+}
+
+#[derive(Debug)]
+struct SimState {
+    elapsed: Duration,
+    timers: Vec<(Duration, Waker)>,
+}
+
+// 模拟时钟: 时间只在 advance 时前进，到期的 sleep 会被唤醒
+#[derive(Debug, Clone)]
+pub struct SimClock {
+    base: Instant,
+    base_system: SystemTime,
+    state: Arc<Mutex<SimState>>,
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimClock {
+    pub fn new() -> Self {
+        Self::at(SystemTime::now())
+    }
+
+    // 指定起始的墙上时间，便于断言日期相关的逻辑
+    pub fn at(system_time: SystemTime) -> Self {
+        SimClock {
+            base: Instant::now(),
+            base_system: system_time,
+            state: Arc::new(Mutex::new(SimState {
+                elapsed: Duration::ZERO,
+                timers: Vec::new(),
+            })),
+        }
+    }
+
+    pub fn shared(&self) -> SharedClock {
+        Arc::new(self.clone())
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.state.lock().unwrap().elapsed
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        let due: Vec<Waker> = {
+            let mut state = self.state.lock().unwrap();
+            state.elapsed += duration;
+            let now = state.elapsed;
+            let (due, pending) = state.timers.drain(..).partition(|(at, _)| *at <= now);
+            state.timers = pending;
+            due.into_iter().map(|(_, waker)| waker).collect()
+        };
+        for waker in due {
+            waker.wake();
+        }
+    }
+
+    // 正在等待的 sleep 数量，测试中用来确认任务已经进入等待
+    pub fn pending_timers(&self) -> usize {
+        self.state.lock().unwrap().timers.len()
+    }
+
+    // 最近一个 sleep 的到期时间与当前时间的差值
+    pub fn next_timer(&self) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        state
+            .timers
+            .iter()
+            .map(|(at, _)| at.saturating_sub(state.elapsed))
+            .min()
+    }
+}
+
+impl Clock for SimClock {
+    fn now(&self) -> Instant {
+        self.base + self.elapsed()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.base_system + self.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(SimSleep {
+            deadline: self.elapsed() + duration,
+            state: self.state.clone(),
+        })
+    }
+}
+
+struct SimSleep {
+    deadline: Duration,
+    state: Arc<Mutex<SimState>>,
+}
+
+impl Future for SimSleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.elapsed >= self.deadline {
+            return Poll::Ready(());
+        }
+        state.timers.push((self.deadline, cx.waker().clone()));
+        Poll::Pending
+    }
+}
@@ -0,0 +1,158 @@
+use std::io::{self, Read, Write};
+
+use bytes::{Buf, Bytes, BytesMut};
+use thiserror::Error;
+
+// 长度前缀分帧: 每帧是 4 字节大端长度加内容。
+// Framed 提供三种读取方式:
+// - next_frame 返回指向内部缓冲区的切片，不复制；切片借用 Framed，下一次读取前必须用完
+// - next_bytes 返回 Bytes，与内部缓冲区共享同一块内存，不复制，可以跨调用保存
+// - next_owned 复制出独立的 Vec<u8>，兼容需要所有权的调用方
+
+pub const DEFAULT_MAX_FRAME: usize = 16 * 1024 * 1024;
+const HEADER: usize = 4;
+const READ_CHUNK: usize = 8 * 1024;
+
+#[derive(Error, Debug)]
+pub enum FrameError {
+    #[error("帧长度 {len} 超过上限 {max}")]
+    TooLarge { len: usize, max: usize },
+
+    #[error("数据在帧中间结束: 需要 {expected} 字节，只有 {actual} 字节")]
+    Truncated { expected: usize, actual: usize },
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+pub fn write_frame<W: Write>(writer: &mut W, payload: &[u8]) -> Result<(), FrameError> {
+    let len = u32::try_from(payload.len()).map_err(|_| FrameError::TooLarge {
+        len: payload.len(),
+        max: u32::MAX as usize,
+    })?;
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(payload)?;
+    Ok(())
+}
+
+pub struct Framed<R> {
+    reader: R,
+    buf: BytesMut,
+    // 上一次 next_frame 返回的帧还在缓冲区里，下次读取前丢弃
+    consumed: usize,
+    max_frame: usize,
+}
+
+impl<R: Read> Framed<R> {
+    pub fn new(reader: R) -> Self {
+        Framed {
+            reader,
+            buf: BytesMut::with_capacity(READ_CHUNK),
+            consumed: 0,
+            max_frame: DEFAULT_MAX_FRAME,
+        }
+    }
+
+    pub fn max_frame_len(mut self, max: usize) -> Self {
+        self.max_frame = max;
+        self
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    // 读到 EOF 返回 false
+    fn fill(&mut self) -> io::Result<bool> {
+        let len = self.buf.len();
+        self.buf.resize(len + READ_CHUNK, 0);
+        let result = loop {
+            match self.reader.read(&mut self.buf[len..]) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                result => break result,
+            }
+        };
+        let n = result.inspect_err(|_| self.buf.truncate(len))?;
+        self.buf.truncate(len + n);
+        Ok(n > 0)
+    }
+
+    // 确保缓冲区里有一个完整的帧，返回内容长度
+    fn ready(&mut self) -> Result<Option<usize>, FrameError> {
+        self.buf.advance(self.consumed);
+        self.consumed = 0;
+        loop {
+            if self.buf.len() >= HEADER {
+                let len = u32::from_be_bytes(self.buf[..HEADER].try_into().unwrap()) as usize;
+                if len > self.max_frame {
+                    return Err(FrameError::TooLarge {
+                        len,
+                        max: self.max_frame,
+                    });
+                }
+                if self.buf.len() >= HEADER + len {
+                    return Ok(Some(len));
+                }
+                self.buf.reserve(HEADER + len - self.buf.len());
+            }
+            if !self.fill()? {
+                return match self.buf.len() {
+                    0 => Ok(None),
+                    actual => Err(FrameError::Truncated {
+                        expected: self.expected(),
+                        actual,
+                    }),
+                };
+            }
+        }
+    }
+
+    fn expected(&self) -> usize {
+        if self.buf.len() < HEADER {
+            return HEADER;
+        }
+        HEADER + u32::from_be_bytes(self.buf[..HEADER].try_into().unwrap()) as usize
+    }
+
+    // 借用内部缓冲区，切片在下一次读取前有效
+    pub fn next_frame(&mut self) -> Result<Option<&[u8]>, FrameError> {
+        let Some(len) = self.ready()? else {
+            return Ok(None);
+        };
+        self.consumed = HEADER + len;
+        Ok(Some(&self.buf[HEADER..HEADER + len]))
+    }
+
+    // 从缓冲区切出这一帧，与缓冲区共享内存
+    pub fn next_bytes(&mut self) -> Result<Option<Bytes>, FrameError> {
+        let Some(len) = self.ready()? else {
+            return Ok(None);
+        };
+        let mut frame = self.buf.split_to(HEADER + len);
+        frame.advance(HEADER);
+        Ok(Some(frame.freeze()))
+    }
+
+    pub fn next_owned(&mut self) -> Result<Option<Vec<u8>>, FrameError> {
+        Ok(self.next_frame()?.map(<[u8]>::to_vec))
+    }
+
+    // 按所有权迭代，每帧复制一次
+    pub fn owned(self) -> Owned<R> {
+        Owned(self)
+    }
+}
+
+pub struct Owned<R>(Framed<R>);
+
+impl<R: Read> Iterator for Owned<R> {
+    type Item = Result<Vec<u8>, FrameError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_owned().transpose()
+    }
+}
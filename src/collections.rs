@@ -0,0 +1,45 @@
+use std::hash::{Hash, Hasher};
+
+// 通用数据结构
+
+pub mod bloom;
+pub mod hash_ring;
+pub mod ordered;
+pub mod rcu;
+pub mod snapshot;
+
+pub use bloom::{BloomFilter, CountingBloomFilter};
+pub use hash_ring::HashRing;
+pub use ordered::OrderedMap;
+pub use rcu::RcuCell;
+pub use snapshot::SnapshotMap;
+
+// 固定的 FNV-1a 加 splitmix64 混合，不同进程、不同版本结果一致，
+// 可以用于需要持久化或跨进程一致的场景
+struct Fnv(u64);
+
+impl Hasher for Fnv {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+pub(crate) fn mix(mut h: u64) -> u64 {
+    h = h.wrapping_add(0x9e3779b97f4a7c15);
+    h = (h ^ (h >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94d049bb133111eb);
+    h ^ (h >> 31)
+}
+
+pub(crate) fn stable_hash<T: Hash + ?Sized>(item: &T) -> u64 {
+    let mut hasher = Fnv(0xcbf29ce484222325);
+    item.hash(&mut hasher);
+    mix(hasher.finish())
+}
@@ -0,0 +1,169 @@
+use std::f64::consts::LN_2;
+use std::hash::Hash;
+
+use serde::{Deserialize, Serialize};
+
+use super::{mix, stable_hash};
+
+// 布隆过滤器: contains 返回 false 时元素一定不存在，返回 true 时可能误判。
+// 用于在查磁盘/数据库之前排除肯定不存在的 key。
+// 使用 stable_hash，序列化后在其他进程里加载结果一致。
+
+// 双重哈希: 第 i 个位置为 h1 + i * h2，h2 保证是奇数
+fn hashes<T: Hash + ?Sized>(item: &T) -> (u64, u64) {
+    let h1 = stable_hash(item);
+    (h1, mix(h1) | 1)
+}
+
+// 按预期元素数量和误判率计算位数和哈希函数个数
+fn optimal(expected_items: usize, fp_rate: f64) -> (u64, u32) {
+    let n = expected_items.max(1) as f64;
+    let p = fp_rate.clamp(1e-9, 0.5);
+    let bits = (-n * p.ln() / (LN_2 * LN_2)).ceil().max(64.0) as u64;
+    let hashes = ((bits as f64 / n) * LN_2).round().clamp(1.0, 32.0) as u32;
+    (bits, hashes)
+}
+
+fn positions<T: Hash + ?Sized>(item: &T, bits: u64, hashes: u32) -> impl Iterator<Item = usize> {
+    let (h1, h2) = self::hashes(item);
+    (0..hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % bits) as usize)
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    hashes: u32,
+    len: u64,
+}
+
+impl BloomFilter {
+    // fp_rate 是插入 expected_items 个元素后的误判率
+    pub fn new(expected_items: usize, fp_rate: f64) -> Self {
+        let (num_bits, hashes) = optimal(expected_items, fp_rate);
+        BloomFilter {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            hashes,
+            len: 0,
+        }
+    }
+
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        for pos in positions(item, self.num_bits, self.hashes) {
+            self.bits[pos / 64] |= 1 << (pos % 64);
+        }
+        self.len += 1;
+    }
+
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        positions(item, self.num_bits, self.hashes)
+            .all(|pos| self.bits[pos / 64] & (1 << (pos % 64)) != 0)
+    }
+
+    // 插入次数，重复插入同一个元素也会计数
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn num_bits(&self) -> u64 {
+        self.num_bits
+    }
+
+    pub fn num_hashes(&self) -> u32 {
+        self.hashes
+    }
+
+    // 根据已置位的比例估计当前误判率
+    pub fn estimated_fp_rate(&self) -> f64 {
+        let set: u32 = self.bits.iter().map(|w| w.count_ones()).sum();
+        (set as f64 / self.num_bits as f64).powi(self.hashes as i32)
+    }
+
+    // 参数相同的两个过滤器取并集，参数不同时返回 false
+    pub fn union(&mut self, other: &BloomFilter) -> bool {
+        if self.num_bits != other.num_bits || self.hashes != other.hashes {
+            return false;
+        }
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a |= b;
+        }
+        self.len += other.len;
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|w| *w = 0);
+        self.len = 0;
+    }
+}
+
+// 计数布隆过滤器: 每个位置是一个计数器，支持删除。
+// 计数器到 255 后不再增减，避免溢出造成漏判
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CountingBloomFilter {
+    counters: Vec<u8>,
+    hashes: u32,
+    len: u64,
+}
+
+impl CountingBloomFilter {
+    pub fn new(expected_items: usize, fp_rate: f64) -> Self {
+        let (num_bits, hashes) = optimal(expected_items, fp_rate);
+        CountingBloomFilter {
+            counters: vec![0; num_bits as usize],
+            hashes,
+            len: 0,
+        }
+    }
+
+    fn positions<T: Hash + ?Sized>(&self, item: &T) -> impl Iterator<Item = usize> {
+        positions(item, self.counters.len() as u64, self.hashes)
+    }
+
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        let positions: Vec<usize> = self.positions(item).collect();
+        for pos in positions {
+            let counter = &mut self.counters[pos];
+            *counter = counter.saturating_add(1);
+        }
+        self.len += 1;
+    }
+
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.positions(item).all(|pos| self.counters[pos] > 0)
+    }
+
+    // 只能删除插入过的元素，元素可能不存在时返回 false 且不做修改
+    pub fn remove<T: Hash + ?Sized>(&mut self, item: &T) -> bool {
+        if !self.contains(item) {
+            return false;
+        }
+        let positions: Vec<usize> = self.positions(item).collect();
+        for pos in positions {
+            let counter = &mut self.counters[pos];
+            if *counter < u8::MAX {
+                *counter -= 1;
+            }
+        }
+        self.len = self.len.saturating_sub(1);
+        true
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.counters.iter_mut().for_each(|c| *c = 0);
+        self.len = 0;
+    }
+}
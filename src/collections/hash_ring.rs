@@ -0,0 +1,128 @@
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::ops::Bound;
+
+use super::stable_hash;
+
+// 一致性哈希环: 每个节点在环上放若干个虚拟节点，key 落到顺时针方向的第一个虚拟节点。
+// 增删节点时只有相邻区间的 key 会迁移，其余 key 的归属不变；
+// 哈希固定，不同进程对同一组节点得到相同的映射。
+
+pub const DEFAULT_VNODES: usize = 160;
+
+#[derive(Debug, Clone)]
+pub struct HashRing<N> {
+    vnodes: usize,
+    ring: BTreeMap<u64, N>,
+    // 节点 -> 虚拟节点个数
+    nodes: HashMap<N, usize>,
+}
+
+impl<N: Hash + Eq + Clone> Default for HashRing<N> {
+    fn default() -> Self {
+        HashRing::new(DEFAULT_VNODES)
+    }
+}
+
+impl<N: Hash + Eq + Clone> HashRing<N> {
+    // vnodes 是权重为 1 的节点的虚拟节点个数，越多分布越均匀
+    pub fn new(vnodes: usize) -> Self {
+        HashRing {
+            vnodes: vnodes.max(1),
+            ring: BTreeMap::new(),
+            nodes: HashMap::new(),
+        }
+    }
+
+    pub fn with_nodes(vnodes: usize, nodes: impl IntoIterator<Item = N>) -> Self {
+        let mut ring = HashRing::new(vnodes);
+        nodes.into_iter().for_each(|n| {
+            ring.add(n);
+        });
+        ring
+    }
+
+    // 节点已存在时返回 false
+    pub fn add(&mut self, node: N) -> bool {
+        self.add_weighted(node, 1)
+    }
+
+    // weight 为 2 的节点分到的 key 大约是权重 1 的两倍
+    pub fn add_weighted(&mut self, node: N, weight: usize) -> bool {
+        if self.nodes.contains_key(&node) {
+            return false;
+        }
+        let count = self.vnodes * weight.max(1);
+        for i in 0..count {
+            let point = stable_hash(&(&node, i));
+            // 哈希冲突时先加入的节点保留这个位置
+            self.ring.entry(point).or_insert_with(|| node.clone());
+        }
+        self.nodes.insert(node, count);
+        true
+    }
+
+    pub fn remove(&mut self, node: &N) -> bool {
+        if self.nodes.remove(node).is_none() {
+            return false;
+        }
+        self.ring.retain(|_, n| n != node);
+        true
+    }
+
+    pub fn contains(&self, node: &N) -> bool {
+        self.nodes.contains_key(node)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.nodes.keys()
+    }
+
+    // 从 key 的位置开始顺时针遍历虚拟节点，到末尾后回到开头
+    fn walk(&self, key: u64) -> impl Iterator<Item = &N> {
+        self.ring
+            .range((Bound::Included(key), Bound::Unbounded))
+            .chain(self.ring.range(..key))
+            .map(|(_, n)| n)
+    }
+
+    pub fn get<K: Hash + ?Sized>(&self, key: &K) -> Option<&N> {
+        self.walk(stable_hash(key)).next()
+    }
+
+    // 顺时针方向的前 n 个不同节点，用于副本放置；节点不足 n 个时返回全部
+    pub fn get_n<K: Hash + ?Sized>(&self, key: &K, n: usize) -> Vec<&N> {
+        let mut result: Vec<&N> = Vec::with_capacity(n.min(self.nodes.len()));
+        for node in self.walk(stable_hash(key)) {
+            if result.len() == n.min(self.nodes.len()) {
+                break;
+            }
+            if !result.contains(&node) {
+                result.push(node);
+            }
+        }
+        result
+    }
+
+    // 从当前环切换到 next 时需要迁移的 key: (key, 原节点, 新节点)
+    pub fn moves<'a, 'k, K: Hash>(
+        &'a self,
+        next: &'a HashRing<N>,
+        keys: impl IntoIterator<Item = &'k K>,
+    ) -> Vec<(&'k K, &'a N, &'a N)> {
+        keys.into_iter()
+            .filter_map(|key| match (self.get(key), next.get(key)) {
+                (Some(from), Some(to)) if from != to => Some((key, from, to)),
+                _ => None,
+            })
+            .collect()
+    }
+}
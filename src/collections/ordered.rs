@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::hash::Hash;
+use std::ops::RangeBounds;
+use std::sync::RwLock;
+use std::time::Instant;
+
+use super::stable_hash;
+
+// 并发有序映射: key 按哈希分到多个 BTreeMap 分片，每个分片一把读写锁，
+// 不同分片的读写互不阻塞。按序操作(first/pop_first/range)需要合并所有分片的结果。
+// 以截止时间为 key 时可以作为 TTL 缓存、定时任务和限流器共用的过期索引。
+
+pub const DEFAULT_SHARDS: usize = 16;
+
+// 可以作为过期索引 key 的类型
+pub trait Deadline {
+    fn deadline(&self) -> Instant;
+}
+
+impl Deadline for Instant {
+    fn deadline(&self) -> Instant {
+        *self
+    }
+}
+
+// (截止时间, id)，截止时间相同的条目用 id 区分
+impl<T> Deadline for (Instant, T) {
+    fn deadline(&self) -> Instant {
+        self.0
+    }
+}
+
+pub struct OrderedMap<K, V> {
+    shards: Vec<RwLock<BTreeMap<K, V>>>,
+}
+
+impl<K: Ord + Hash + Clone, V: Clone> Default for OrderedMap<K, V> {
+    fn default() -> Self {
+        OrderedMap::new()
+    }
+}
+
+impl<K: Ord + Hash + Clone, V: Clone> OrderedMap<K, V> {
+    pub fn new() -> Self {
+        Self::with_shards(DEFAULT_SHARDS)
+    }
+
+    pub fn with_shards(shards: usize) -> Self {
+        OrderedMap {
+            shards: (0..shards.max(1))
+                .map(|_| RwLock::new(BTreeMap::new()))
+                .collect(),
+        }
+    }
+
+    fn shard(&self, key: &K) -> &RwLock<BTreeMap<K, V>> {
+        &self.shards[(stable_hash(key) % self.shards.len() as u64) as usize]
+    }
+
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.shard(&key).write().unwrap().insert(key, value)
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.shard(key).read().unwrap().get(key).cloned()
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.shard(key).read().unwrap().contains_key(key)
+    }
+
+    pub fn remove(&self, key: &K) -> Option<V> {
+        self.shard(key).write().unwrap().remove(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|s| s.read().unwrap().len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|s| s.read().unwrap().is_empty())
+    }
+
+    pub fn clear(&self) {
+        self.shards.iter().for_each(|s| s.write().unwrap().clear());
+    }
+
+    // 所有分片中最小的 key 所在的分片
+    fn first_shard(&self) -> Option<(usize, K)> {
+        self.shards
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| s.read().unwrap().keys().next().map(|k| (i, k.clone())))
+            .min_by(|a, b| a.1.cmp(&b.1))
+    }
+
+    pub fn first(&self) -> Option<(K, V)> {
+        self.shards
+            .iter()
+            .filter_map(|s| {
+                let shard = s.read().unwrap();
+                shard.first_key_value().map(|(k, v)| (k.clone(), v.clone()))
+            })
+            .min_by(|a, b| a.0.cmp(&b.0))
+    }
+
+    // 取出最小的条目；pred 返回 false 时不取出。
+    // 查找和取出之间分片可能被其他线程修改，此时重新查找
+    pub fn pop_first_if(&self, pred: impl Fn(&K) -> bool) -> Option<(K, V)> {
+        loop {
+            let (index, key) = self.first_shard()?;
+            if !pred(&key) {
+                return None;
+            }
+            let mut shard = self.shards[index].write().unwrap();
+            if shard.keys().next() == Some(&key) {
+                return shard.pop_first();
+            }
+        }
+    }
+
+    pub fn pop_first(&self) -> Option<(K, V)> {
+        self.pop_first_if(|_| true)
+    }
+
+    // 按 key 排序的区间内的条目
+    pub fn range<R: RangeBounds<K> + Clone>(&self, range: R) -> Vec<(K, V)> {
+        let mut items: Vec<(K, V)> = Vec::new();
+        for shard in &self.shards {
+            let shard = shard.read().unwrap();
+            items.extend(
+                shard
+                    .range(range.clone())
+                    .map(|(k, v)| (k.clone(), v.clone())),
+            );
+        }
+        items.sort_by(|a, b| a.0.cmp(&b.0));
+        items
+    }
+}
+
+impl<K: Ord + Hash + Clone + Deadline, V: Clone> OrderedMap<K, V> {
+    // 取出最早的一个截止时间不晚于 now 的条目
+    pub fn pop_first_expiring(&self, now: Instant) -> Option<(K, V)> {
+        self.pop_first_if(|k| k.deadline() <= now)
+    }
+
+    // 取出所有已到期的条目，按截止时间排序
+    pub fn drain_expiring(&self, now: Instant) -> Vec<(K, V)> {
+        std::iter::from_fn(|| self.pop_first_expiring(now)).collect()
+    }
+
+    // 最早的截止时间，用于计算下一次需要醒来的时间
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.first_shard().map(|(_, k)| k.deadline())
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use arc_swap::ArcSwap;
+
+// 读多写少的共享值(RCU): 读者取得当前版本的 Arc 快照，不加锁，也不会被写者阻塞；
+// 写者构造新版本后原子替换，已经取得旧快照的读者继续使用旧版本。
+// 回收: 不需要 epoch，旧版本由 Arc 引用计数管理，最后一个持有快照的读者 drop 时释放。
+// arc-swap 的 load 借用线程本地的 debt 槽位代替增加引用计数，所以 read 比 load 更便宜；
+// 长时间持有快照会推迟旧版本的释放，读者不应跨越 await 或长循环持有 load 的结果。
+
+pub struct RcuCell<T> {
+    current: ArcSwap<T>,
+    // 串行化 update，避免两个写者基于同一版本修改、后写的覆盖先写的
+    write: Mutex<()>,
+    version: AtomicU64,
+}
+
+impl<T> RcuCell<T> {
+    pub fn new(value: T) -> Self {
+        RcuCell {
+            current: ArcSwap::from_pointee(value),
+            write: Mutex::new(()),
+            version: AtomicU64::new(0),
+        }
+    }
+
+    // 当前版本的快照，之后的写入不影响它
+    pub fn load(&self) -> Arc<T> {
+        self.current.load_full()
+    }
+
+    // 在当前版本上执行 f，不增加引用计数
+    pub fn read<R>(&self, f: impl FnOnce(&T) -> R) -> R {
+        f(&self.current.load())
+    }
+
+    // 每次写入加 1，可以用来判断缓存的派生数据是否过期
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    // 整体替换，返回旧版本
+    pub fn store(&self, value: T) -> Arc<T> {
+        let _guard = self.write.lock().unwrap();
+        let old = self.current.swap(Arc::new(value));
+        self.version.fetch_add(1, Ordering::Release);
+        old
+    }
+
+    // 基于当前版本构造新版本，f 的返回值作为新版本
+    pub fn update(&self, f: impl FnOnce(&T) -> T) -> Arc<T> {
+        let _guard = self.write.lock().unwrap();
+        let next = Arc::new(f(&self.current.load()));
+        self.current.store(next.clone());
+        self.version.fetch_add(1, Ordering::Release);
+        next
+    }
+}
+
+impl<T: Clone> RcuCell<T> {
+    // 在当前版本的副本上修改
+    pub fn modify<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let _guard = self.write.lock().unwrap();
+        let mut next = T::clone(&self.current.load());
+        let result = f(&mut next);
+        self.current.store(Arc::new(next));
+        self.version.fetch_add(1, Ordering::Release);
+        result
+    }
+}
+
+impl<T: Default> Default for RcuCell<T> {
+    fn default() -> Self {
+        RcuCell::new(T::default())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RcuCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RcuCell")
+            .field("version", &self.version())
+            .field("value", &self.current.load())
+            .finish()
+    }
+}
@@ -0,0 +1,92 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use arc_swap::ArcSwap;
+
+// 写时复制的映射: 读取直接访问当前快照，不加锁；写入复制一份修改后原子替换。
+// 适合读远多于写的数据(热加载的配置、功能开关)，写入代价与映射大小成正比。
+
+pub struct SnapshotMap<K, V> {
+    current: ArcSwap<HashMap<K, V>>,
+    // 串行化写入，避免并发写互相覆盖
+    write: Mutex<()>,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> Default for SnapshotMap<K, V> {
+    fn default() -> Self {
+        SnapshotMap::new()
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> From<HashMap<K, V>> for SnapshotMap<K, V> {
+    fn from(map: HashMap<K, V>) -> Self {
+        SnapshotMap {
+            current: ArcSwap::from_pointee(map),
+            write: Mutex::new(()),
+        }
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> SnapshotMap<K, V> {
+    pub fn new() -> Self {
+        HashMap::new().into()
+    }
+
+    // 当前快照，之后的写入不影响已经取得的快照
+    pub fn snapshot(&self) -> Arc<HashMap<K, V>> {
+        self.current.load_full()
+    }
+
+    pub fn get<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.current.load().get(key).cloned()
+    }
+
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.current.load().contains_key(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.current.load().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.current.load().is_empty()
+    }
+
+    // 在一份副本上执行多个修改，完成后一次性替换，读者要么看到全部修改要么都看不到
+    pub fn update<R>(&self, f: impl FnOnce(&mut HashMap<K, V>) -> R) -> R {
+        let _guard = self.write.lock().unwrap();
+        let mut next = HashMap::clone(&self.current.load());
+        let result = f(&mut next);
+        self.current.store(Arc::new(next));
+        result
+    }
+
+    pub fn insert(&self, key: K, value: V) -> Option<V> {
+        self.update(|map| map.insert(key, value))
+    }
+
+    pub fn remove<Q>(&self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.update(|map| map.remove(key))
+    }
+
+    // 整体替换(例如重新加载配置文件)，返回旧的快照
+    pub fn replace(&self, map: HashMap<K, V>) -> Arc<HashMap<K, V>> {
+        let _guard = self.write.lock().unwrap();
+        self.current.swap(Arc::new(map))
+    }
+}
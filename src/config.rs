@@ -0,0 +1,431 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::serde_any::{self, Format};
+
+pub mod app;
+pub mod schema;
+pub mod validate;
+
+pub use app::AppConfig;
+pub use schema::Documented;
+pub use validate::{FieldError, Validate, Validator};
+
+// 分层配置加载: 默认值 < 配置文件(按添加顺序) < 环境变量 < set 设置的值，
+// 后面的层按键覆盖前面的层，嵌套表逐键合并而不是整体替换。
+// 环境变量 APP_PORT 对应键 port，用双下划线表示嵌套: APP_DB__URL 对应 db.url。
+// 配置文件按扩展名识别格式，支持 TOML、YAML 和 JSON。
+// 按环境区分时用 config.toml 放公共配置，config.<profile>.toml 放该环境的覆盖，
+// profile 由 APP_ENV 选择(dev/test/prod 等)。
+// 字符串值中的 ${NAME} 替换为环境变量，${file:/run/secrets/db_pass} 替换为文件内容
+// (去掉末尾换行)，密钥因此不需要写进配置文件；$${ 表示字面的 ${。
+
+// 选择配置环境的环境变量
+pub const PROFILE_ENV: &str = "APP_ENV";
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("配置文件读取失败: {path}: {source}")]
+    Io { path: PathBuf, source: io::Error },
+    #[error("配置文件解析失败: {path}: {message}")]
+    Parse { path: PathBuf, message: String },
+    #[error("无法根据扩展名识别配置文件格式: {0}")]
+    UnknownFormat(PathBuf),
+    #[error("配置无效: {0}")]
+    Invalid(String),
+    // 不包含密钥的值，只有占位符
+    #[error("配置项 {key} 的占位符 ${{{placeholder}}} 无法解析: {reason}")]
+    MissingSecret {
+        key: String,
+        placeholder: String,
+        reason: String,
+    },
+    // 列出所有不合法的字段，而不是只报第一个
+    #[error("配置校验失败: {}", validate::describe(.0))]
+    Validation(Vec<FieldError>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    Toml,
+    Yaml,
+    Json,
+}
+
+impl ConfigFormat {
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "toml" => Some(ConfigFormat::Toml),
+            "yaml" | "yml" => Some(ConfigFormat::Yaml),
+            "json" => Some(ConfigFormat::Json),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(Self::from_extension)
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ConfigFormat::Toml => "toml",
+            ConfigFormat::Yaml => "yaml",
+            ConfigFormat::Json => "json",
+        }
+    }
+
+    fn parse(self, text: &str, path: &Path) -> Result<Value, ConfigError> {
+        let format = match self {
+            ConfigFormat::Toml => Format::Toml,
+            ConfigFormat::Yaml => Format::Yaml,
+            ConfigFormat::Json => Format::Json,
+        };
+        let value: Value = serde_any::from_str(text, format).map_err(|e| ConfigError::Parse {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })?;
+        // 空的 YAML 文件解析为 null，当作没有任何键
+        Ok(if value.is_null() {
+            Value::Object(Map::new())
+        } else {
+            value
+        })
+    }
+}
+
+// 从文件和环境变量加载配置的入口: AppConfig::from_path("app.yaml")。
+// 需要显式 impl Config for XxxConfig {}，避免所有可反序列化的类型都带上这些方法
+pub trait Config: DeserializeOwned {
+    // 按扩展名识别格式
+    fn from_path(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        ConfigLoader::new().file(path).load()
+    }
+
+    fn from_str_as(text: &str, format: ConfigFormat) -> Result<Self, ConfigError> {
+        let value = format.parse(text, Path::new("<string>"))?;
+        serde_json::from_value(value).map_err(|e| ConfigError::Invalid(e.to_string()))
+    }
+
+    // config.toml < config.<profile>.toml < APP_ 环境变量，文件都在当前目录；
+    // profile 为 None 时取 APP_ENV，两者都没有时只加载 config.toml
+    fn load_for_profile(profile: Option<&str>) -> Result<Self, ConfigError> {
+        Self::load_for_profile_in(".", profile)
+    }
+
+    fn load_for_profile_in(
+        dir: impl AsRef<Path>,
+        profile: Option<&str>,
+    ) -> Result<Self, ConfigError> {
+        let profile = match profile {
+            Some(profile) => Some(profile.to_string()),
+            None => active_profile(),
+        };
+        ConfigLoader::new()
+            .profile(dir, profile.as_deref())?
+            .env_prefix("APP")
+            .load()
+    }
+}
+
+// APP_ENV 的值，未设置或为空时返回 None
+pub fn active_profile() -> Option<String> {
+    std::env::var(PROFILE_ENV)
+        .ok()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Defaults(Value),
+    File {
+        path: PathBuf,
+        format: Option<ConfigFormat>,
+        required: bool,
+    },
+    Env {
+        prefix: String,
+    },
+    Set {
+        key: String,
+        value: Value,
+    },
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigLoader {
+    sources: Vec<Source>,
+}
+
+impl ConfigLoader {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 通常传 T::default()
+    pub fn defaults<T: Serialize>(mut self, defaults: &T) -> Self {
+        let value = serde_json::to_value(defaults).expect("默认配置总是可以序列化");
+        self.sources.push(Source::Defaults(value));
+        self
+    }
+
+    // 文件不存在时报错，格式按扩展名识别
+    pub fn file(mut self, path: impl AsRef<Path>) -> Self {
+        self.sources.push(Source::File {
+            path: path.as_ref().to_path_buf(),
+            format: None,
+            required: true,
+        });
+        self
+    }
+
+    // 扩展名不能说明格式时(例如 /etc/app/config)指定格式
+    pub fn file_with_format(mut self, path: impl AsRef<Path>, format: ConfigFormat) -> Self {
+        self.sources.push(Source::File {
+            path: path.as_ref().to_path_buf(),
+            format: Some(format),
+            required: true,
+        });
+        self
+    }
+
+    // 文件不存在时跳过，例如只在部分环境中存在的本地覆盖文件
+    pub fn optional_file(mut self, path: impl AsRef<Path>) -> Self {
+        self.sources.push(Source::File {
+            path: path.as_ref().to_path_buf(),
+            format: None,
+            required: false,
+        });
+        self
+    }
+
+    // dir 下的 config.toml 和 config.<profile>.toml，都不存在时跳过；
+    // profile 只能包含字母、数字、- 和 _，避免 APP_ENV 被拼进任意路径
+    pub fn profile(
+        mut self,
+        dir: impl AsRef<Path>,
+        profile: Option<&str>,
+    ) -> Result<Self, ConfigError> {
+        let dir = dir.as_ref();
+        self = self.optional_file(dir.join("config.toml"));
+        if let Some(profile) = profile {
+            let valid = !profile.is_empty()
+                && profile
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid {
+                return Err(ConfigError::Invalid(format!(
+                    "无效的配置环境名: {:?}",
+                    profile
+                )));
+            }
+            self = self.optional_file(dir.join(format!("config.{}.toml", profile)));
+        }
+        Ok(self)
+    }
+
+    // 在加载时读取 <PREFIX>_ 开头的环境变量
+    pub fn env_prefix(mut self, prefix: &str) -> Self {
+        self.sources.push(Source::Env {
+            prefix: format!("{}_", prefix.trim_end_matches('_').to_ascii_uppercase()),
+        });
+        self
+    }
+
+    // 优先级最高，用于命令行参数；key 用 . 表示嵌套，例如 "db.url"
+    pub fn set(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.sources.push(Source::Set {
+            key: key.to_string(),
+            value: value.into(),
+        });
+        self
+    }
+
+    pub fn load<T: DeserializeOwned>(&self) -> Result<T, ConfigError> {
+        serde_json::from_value(self.merged()?).map_err(|e| ConfigError::Invalid(e.to_string()))
+    }
+
+    // 加载后按 Validate 的规则校验
+    pub fn load_validated<T: DeserializeOwned + Validate>(&self) -> Result<T, ConfigError> {
+        let config: T = self.load()?;
+        config.check()?;
+        Ok(config)
+    }
+
+    // 合并后还没有反序列化的配置树
+    pub fn merged(&self) -> Result<Value, ConfigError> {
+        let mut root = Value::Object(Map::new());
+        // 环境变量和 set 在文件之后应用，与添加顺序无关
+        let (late, early): (Vec<_>, Vec<_>) = self
+            .sources
+            .iter()
+            .partition(|s| matches!(s, Source::Env { .. } | Source::Set { .. }));
+        for source in early.into_iter().chain(late) {
+            match source {
+                Source::Defaults(value) => merge(&mut root, value.clone()),
+                Source::File {
+                    path,
+                    format,
+                    required,
+                } => {
+                    let format = format
+                        .or_else(|| ConfigFormat::from_path(path))
+                        .ok_or_else(|| ConfigError::UnknownFormat(path.clone()))?;
+                    let text = match fs::read_to_string(path) {
+                        Ok(text) => text,
+                        Err(e) if e.kind() == io::ErrorKind::NotFound && !required => continue,
+                        Err(source) => {
+                            return Err(ConfigError::Io {
+                                path: path.clone(),
+                                source,
+                            })
+                        }
+                    };
+                    merge(&mut root, format.parse(&text, path)?);
+                }
+                Source::Env { prefix } => {
+                    // APP_ENV 用来选择配置环境，不是配置项
+                    let mut vars: Vec<(String, String)> = std::env::vars()
+                        .filter(|(k, _)| k.starts_with(prefix.as_str()) && k != PROFILE_ENV)
+                        .collect();
+                    vars.sort();
+                    for (name, raw) in vars {
+                        let path: Vec<String> = name[prefix.len()..]
+                            .to_ascii_lowercase()
+                            .split("__")
+                            .map(str::to_string)
+                            .collect();
+                        if path.iter().any(String::is_empty) {
+                            continue;
+                        }
+                        let value = env_value(lookup(&root, &path), &raw);
+                        insert(&mut root, &path, value);
+                    }
+                }
+                Source::Set { key, value } => {
+                    let path: Vec<String> = key.split('.').map(str::to_string).collect();
+                    insert(&mut root, &path, value.clone());
+                }
+            }
+        }
+        interpolate(&mut root, "")?;
+        Ok(root)
+    }
+}
+
+// 对象逐键合并，其他类型直接覆盖
+pub(crate) fn merge(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn lookup<'a>(root: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(root, |value, key| value.get(key))
+}
+
+fn insert(root: &mut Value, path: &[String], value: Value) {
+    let mut current = root;
+    for key in &path[..path.len() - 1] {
+        if !current.is_object() {
+            *current = Value::Object(Map::new());
+        }
+        current = current
+            .as_object_mut()
+            .expect("刚刚确保是对象")
+            .entry(key.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    if !current.is_object() {
+        *current = Value::Object(Map::new());
+    }
+    current
+        .as_object_mut()
+        .expect("刚刚确保是对象")
+        .insert(path[path.len() - 1].clone(), value);
+}
+
+// 展开所有字符串值中的占位符，key 是当前值的路径，用于报错
+fn interpolate(value: &mut Value, key: &str) -> Result<(), ConfigError> {
+    match value {
+        Value::String(s) if s.contains("${") => *s = expand(s, key)?,
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                interpolate(item, &format!("{}[{}]", key, i))?;
+            }
+        }
+        Value::Object(map) => {
+            for (name, child) in map.iter_mut() {
+                let path = if key.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}.{}", key, name)
+                };
+                interpolate(child, &path)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn expand(text: &str, key: &str) -> Result<String, ConfigError> {
+    let missing = |placeholder: &str, reason: String| ConfigError::MissingSecret {
+        key: key.to_string(),
+        placeholder: placeholder.to_string(),
+        reason,
+    };
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            out.push_str(&rest[..start - 1]);
+            out.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        out.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| missing(&rest[start + 2..], "缺少 }".into()))?;
+        let placeholder = &rest[start + 2..start + end];
+        let secret = match placeholder.strip_prefix("file:") {
+            Some(path) => fs::read_to_string(path)
+                .map(|s| s.trim_end_matches(['\r', '\n']).to_string())
+                .map_err(|e| missing(placeholder, e.to_string()))?,
+            None => std::env::var(placeholder)
+                .map_err(|_| missing(placeholder, "环境变量未设置".into()))?,
+        };
+        out.push_str(&secret);
+        rest = &rest[start + end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+// 环境变量只有字符串: 已有的值是字符串时保持字符串(host=123 不会变成数字)，
+// 否则按 JSON 解析(数字、bool、数组)，解析失败时作为字符串
+fn env_value(existing: Option<&Value>, raw: &str) -> Value {
+    if matches!(existing, Some(Value::String(_))) {
+        return Value::String(raw.to_string());
+    }
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
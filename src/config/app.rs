@@ -0,0 +1,124 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use super::schema::Documented;
+use super::validate::{Validate, Validator};
+use super::Config;
+use crate::redact::RedactConfig;
+
+// 应用自身的配置，selftest 读取的 db.url、http.base_url 等键也在这里声明。
+// 新增键时同时更新 Default 和 docs，config check/docs 命令以此为准
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub host: String,
+    pub port: u16,
+    pub log_level: String,
+    pub data_dir: PathBuf,
+    pub db: DbSection,
+    pub http: HttpSection,
+    pub redact: RedactConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DbSection {
+    pub url: String,
+    pub max_connections: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpSection {
+    pub base_url: Option<String>,
+    pub endpoints: Vec<String>,
+    pub timeout_secs: u64,
+}
+
+impl Config for AppConfig {}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            host: "127.0.0.1".into(),
+            port: 8080,
+            log_level: "info".into(),
+            data_dir: PathBuf::from("data"),
+            db: DbSection::default(),
+            http: HttpSection::default(),
+            redact: RedactConfig::default(),
+        }
+    }
+}
+
+impl Default for DbSection {
+    fn default() -> Self {
+        DbSection {
+            url: "sqlite://data/app.db".into(),
+            max_connections: 10,
+        }
+    }
+}
+
+impl Default for HttpSection {
+    fn default() -> Self {
+        HttpSection {
+            base_url: None,
+            endpoints: Vec::new(),
+            timeout_secs: 30,
+        }
+    }
+}
+
+impl Validate for AppConfig {
+    fn validate(&self, v: &mut Validator) {
+        v.field("host", &self.host).not_empty().hostname();
+        v.field("port", self.port).nonzero();
+        v.field("log_level", &self.log_level)
+            .one_of(&["trace", "debug", "info", "warn", "error"]);
+        v.nested("db", &self.db);
+        v.nested("http", &self.http);
+    }
+}
+
+impl Validate for DbSection {
+    fn validate(&self, v: &mut Validator) {
+        v.field("url", &self.url).not_empty();
+        v.field("max_connections", self.max_connections)
+            .range(1..=1000);
+    }
+}
+
+impl Validate for HttpSection {
+    fn validate(&self, v: &mut Validator) {
+        if let Some(base_url) = &self.base_url {
+            v.field("base_url", base_url).matches("^https?://");
+        }
+        v.field("timeout_secs", self.timeout_secs).nonzero();
+    }
+}
+
+impl Documented for AppConfig {
+    fn docs() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("host", "监听地址"),
+            ("port", "监听端口"),
+            ("log_level", "日志级别: trace/debug/info/warn/error"),
+            ("data_dir", "数据目录，selftest 会检查是否可写"),
+            ("db.url", "数据库地址，形如 sqlite://data/app.db"),
+            ("db.max_connections", "连接池最大连接数，1..=1000"),
+            ("http.base_url", "上游服务地址，未设置时不访问上游"),
+            ("http.endpoints", "selftest 额外检查的 HTTP 端点"),
+            ("http.timeout_secs", "上游请求超时(秒)"),
+            (
+                "redact.builtin",
+                "日志脱敏是否启用内置规则(敏感字段、邮箱、卡号)",
+            ),
+            ("redact.fields", "额外按字段名脱敏的键"),
+            ("redact.patterns", "额外的正则规则，每项包含 name 和 regex"),
+            ("redact.mask", "替换敏感内容的文本"),
+        ]
+    }
+}
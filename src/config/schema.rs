@@ -0,0 +1,406 @@
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use super::{ConfigError, ConfigFormat, ConfigLoader, Validate};
+
+// 配置的元数据: 键、类型和默认值来自 T::default() 序列化后的结构，说明来自 Documented::docs。
+// 加载(ConfigLoader::defaults)、检查(check_file)和文档(reference)使用同一份信息，
+// 新增字段只需要改结构体和 Default。JSON Schema 和带注释的默认配置文件也由此生成。
+
+pub trait Documented: Default + Serialize {
+    // (键, 说明)，键用 . 表示嵌套，例如 ("db.url", "数据库地址")
+    fn docs() -> Vec<(&'static str, &'static str)> {
+        Vec::new()
+    }
+
+    fn schema() -> Value {
+        json_schema::<Self>()
+    }
+
+    // 按扩展名选择格式写入带注释的默认配置，文件已存在时返回错误而不是覆盖
+    fn write_default(path: impl AsRef<Path>) -> Result<(), ConfigError> {
+        let path = path.as_ref();
+        let format = ConfigFormat::from_path(path)
+            .ok_or_else(|| ConfigError::UnknownFormat(path.to_path_buf()))?;
+        let io_error = |source: io::Error| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        };
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .and_then(|mut f| f.write_all(default_file::<Self>(format).as_bytes()))
+            .map_err(io_error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyDoc {
+    pub key: String,
+    pub kind: &'static str,
+    pub default: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<&'static str>,
+}
+
+// 所有叶子键，按键名排序；值为空表(任意键的映射)的也算一个键
+pub fn keys<T: Documented>() -> Vec<KeyDoc> {
+    let defaults = serde_json::to_value(T::default()).expect("默认配置总是可以序列化");
+    let docs = T::docs();
+    let mut out = Vec::new();
+    walk(&defaults, String::new(), &mut |key, value| {
+        out.push(KeyDoc {
+            description: docs.iter().find(|(k, _)| *k == key).map(|(_, d)| *d),
+            key,
+            kind: kind(value),
+            default: value.clone(),
+        });
+    });
+    out
+}
+
+fn walk(value: &Value, prefix: String, f: &mut impl FnMut(String, &Value)) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                walk(child, path, f);
+            }
+        }
+        _ if !prefix.is_empty() => f(prefix, value),
+        _ => {}
+    }
+}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "optional",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "table",
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnknownKey {
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+impl fmt::Display for UnknownKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "未知的键 {}", self.key)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "，是不是 {}?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+// 配置树中 T 没有声明的键。拼错的键会被 serde 静默忽略，只能靠这里发现
+pub fn unknown_keys<T: Documented>(config: &Value) -> Vec<UnknownKey> {
+    let known = keys::<T>();
+    let mut unknown = Vec::new();
+    find_unknown(config, String::new(), &known, &mut unknown);
+    unknown
+}
+
+fn find_unknown(value: &Value, prefix: String, known: &[KeyDoc], out: &mut Vec<UnknownKey>) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    for (key, child) in map {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        if known.iter().any(|k| k.key == path) {
+            continue;
+        }
+        let table = format!("{}.", path);
+        if known.iter().any(|k| k.key.starts_with(&table)) {
+            find_unknown(child, path, known, out);
+            continue;
+        }
+        out.push(UnknownKey {
+            suggestion: suggest(&path, known),
+            key: path,
+        });
+    }
+}
+
+// 编辑距离不超过 2(短键为 1)的已知键中最接近的一个
+fn suggest(key: &str, known: &[KeyDoc]) -> Option<String> {
+    let limit = if key.len() <= 4 { 1 } else { 2 };
+    known
+        .iter()
+        .map(|k| (edit_distance(key, &k.key), &k.key))
+        .filter(|(d, _)| *d <= limit)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, k)| k.clone())
+}
+
+// 相邻字符交换(prot/port)也算一次编辑
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    // 读取、解析、类型和校验错误
+    pub errors: Vec<String>,
+    pub unknown: Vec<UnknownKey>,
+}
+
+impl CheckReport {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty() && self.unknown.is_empty()
+    }
+}
+
+impl fmt::Display for CheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_ok() {
+            return write!(f, "配置有效");
+        }
+        let lines: Vec<String> = self
+            .errors
+            .iter()
+            .cloned()
+            .chain(self.unknown.iter().map(UnknownKey::to_string))
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+// 按 T 的默认值、类型和校验规则检查一个配置文件，不读取环境变量
+pub fn check_file<T>(path: impl AsRef<Path>) -> CheckReport
+where
+    T: Documented + DeserializeOwned + Validate,
+{
+    let path = path.as_ref();
+    let mut report = CheckReport::default();
+    let raw = match ConfigLoader::new().file(path).merged() {
+        Ok(raw) => raw,
+        Err(e) => {
+            report.errors.push(e.to_string());
+            return report;
+        }
+    };
+    report.unknown = unknown_keys::<T>(&raw);
+    match ConfigLoader::new()
+        .defaults(&T::default())
+        .file(path)
+        .load_validated::<T>()
+    {
+        Ok(_) => {}
+        Err(ConfigError::Validation(errors)) => {
+            report
+                .errors
+                .extend(errors.iter().map(|e| format!("字段 {}", e)));
+        }
+        Err(e) => report.errors.push(e.to_string()),
+    }
+    report
+}
+
+// Markdown 格式的配置参考
+pub fn reference<T: Documented>() -> String {
+    let mut out = String::from("| 键 | 类型 | 默认值 | 说明 |\n|---|---|---|---|\n");
+    for key in keys::<T>() {
+        let default = match &key.default {
+            Value::Null => "-".to_string(),
+            value => format!("`{}`", value),
+        };
+        out.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            key.key,
+            key.kind,
+            default,
+            key.description.unwrap_or("")
+        ));
+    }
+    out
+}
+
+// JSON Schema (draft 2020-12)，类型和默认值取自 T::default()；
+// 非空的表不允许未声明的键，与 check_file 的未知键检查一致
+pub fn json_schema<T: Documented>() -> Value {
+    let defaults = serde_json::to_value(T::default()).expect("默认配置总是可以序列化");
+    let docs = T::docs();
+    let mut schema = schema_for(&defaults, "", &docs);
+    if let Value::Object(map) = &mut schema {
+        map.insert(
+            "$schema".into(),
+            "https://json-schema.org/draft/2020-12/schema".into(),
+        );
+    }
+    schema
+}
+
+fn schema_for(value: &Value, key: &str, docs: &[(&'static str, &'static str)]) -> Value {
+    let mut schema = serde_json::Map::new();
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            schema.insert("type".into(), "object".into());
+            let properties: serde_json::Map<String, Value> = map
+                .iter()
+                .map(|(name, child)| {
+                    let path = if key.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{}.{}", key, name)
+                    };
+                    (name.clone(), schema_for(child, &path, docs))
+                })
+                .collect();
+            schema.insert("properties".into(), properties.into());
+            schema.insert("additionalProperties".into(), false.into());
+            return schema.into();
+        }
+        Value::Object(_) => {
+            schema.insert("type".into(), "object".into());
+        }
+        // 默认为空的可选值，类型无法从默认值推断
+        Value::Null => {}
+        Value::Bool(_) => {
+            schema.insert("type".into(), "boolean".into());
+        }
+        Value::Number(n) if n.is_f64() => {
+            schema.insert("type".into(), "number".into());
+        }
+        Value::Number(_) => {
+            schema.insert("type".into(), "integer".into());
+        }
+        Value::String(_) => {
+            schema.insert("type".into(), "string".into());
+        }
+        Value::Array(items) => {
+            schema.insert("type".into(), "array".into());
+            if let Some(first) = items.first() {
+                schema.insert("items".into(), schema_for(first, key, &[]));
+            }
+        }
+    }
+    if let Some((_, description)) = docs.iter().find(|(k, _)| *k == key) {
+        schema.insert("description".into(), (*description).into());
+    }
+    schema.insert("default".into(), value.clone());
+    schema.into()
+}
+
+// 带注释的默认配置文件内容；JSON 不支持注释，只输出默认值
+pub fn default_file<T: Documented>(format: ConfigFormat) -> String {
+    let defaults = serde_json::to_value(T::default()).expect("默认配置总是可以序列化");
+    let docs = T::docs();
+    let mut out = String::new();
+    match format {
+        ConfigFormat::Json => {
+            out = serde_json::to_string_pretty(&defaults).expect("Value 总是可以序列化");
+            out.push('\n');
+        }
+        ConfigFormat::Toml => write_toml(&defaults, "", &docs, &mut out),
+        ConfigFormat::Yaml => write_yaml(&defaults, "", 0, &docs, &mut out),
+    }
+    out
+}
+
+fn comment(docs: &[(&'static str, &'static str)], key: &str, indent: usize, out: &mut String) {
+    if let Some((_, description)) = docs.iter().find(|(k, _)| *k == key) {
+        out.push_str(&format!("{}# {}\n", " ".repeat(indent), description));
+    }
+}
+
+fn child_key(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", prefix, name)
+    }
+}
+
+fn is_section(value: &Value) -> bool {
+    matches!(value, Value::Object(map) if !map.is_empty())
+}
+
+// 先写本表的键，再写子表 [a.b]；TOML 没有 null，未设置的可选值写成注释
+fn write_toml(
+    value: &Value,
+    prefix: &str,
+    docs: &[(&'static str, &'static str)],
+    out: &mut String,
+) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    for (name, child) in map.iter().filter(|(_, v)| !is_section(v)) {
+        let key = child_key(prefix, name);
+        comment(docs, &key, 0, out);
+        match toml::Value::try_from(child) {
+            Ok(v) if !child.is_null() => out.push_str(&format!("{} = {}\n", name, v)),
+            _ => out.push_str(&format!("# {} =\n", name)),
+        }
+    }
+    for (name, child) in map.iter().filter(|(_, v)| is_section(v)) {
+        let key = child_key(prefix, name);
+        out.push_str(&format!("\n[{}]\n", key));
+        write_toml(child, &key, docs, out);
+    }
+}
+
+// JSON 的标量和数组写法也是合法的 YAML
+fn write_yaml(
+    value: &Value,
+    prefix: &str,
+    indent: usize,
+    docs: &[(&'static str, &'static str)],
+    out: &mut String,
+) {
+    let Value::Object(map) = value else {
+        return;
+    };
+    for (name, child) in map {
+        let key = child_key(prefix, name);
+        comment(docs, &key, indent, out);
+        if is_section(child) {
+            out.push_str(&format!("{}{}:\n", " ".repeat(indent), name));
+            write_yaml(child, &key, indent + 2, docs, out);
+        } else {
+            let text = serde_json::to_string(child).expect("Value 总是可以序列化");
+            out.push_str(&format!("{}{}: {}\n", " ".repeat(indent), name, text));
+        }
+    }
+}
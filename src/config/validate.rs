@@ -0,0 +1,219 @@
+use std::fmt;
+use std::net::IpAddr;
+use std::ops::RangeInclusive;
+
+use regex::Regex;
+use serde::Serialize;
+
+use super::ConfigError;
+
+// 配置校验: 实现 Validate 时用 Validator 逐个字段声明规则，
+// 所有规则都会执行，失败的字段一起放进 ConfigError::Validation。
+//
+//   impl Validate for Server {
+//       fn validate(&self, v: &mut Validator) {
+//           v.field("port", self.port).nonzero();
+//           v.field("host", &self.host).not_empty().hostname();
+//           v.nested("db", &self.db);
+//       }
+//   }
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FieldError {
+    // 嵌套字段用 . 连接，例如 db.url
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+pub(super) fn describe(errors: &[FieldError]) -> String {
+    errors
+        .iter()
+        .map(FieldError::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+pub trait Validate {
+    fn validate(&self, v: &mut Validator);
+
+    fn check(&self) -> Result<(), ConfigError> {
+        let mut v = Validator::new();
+        self.validate(&mut v);
+        v.finish()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Validator {
+    prefix: String,
+    errors: Vec<FieldError>,
+}
+
+impl Validator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn field<V>(&mut self, name: &str, value: V) -> Field<'_, V> {
+        let name = if self.prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", self.prefix, name)
+        };
+        Field {
+            validator: self,
+            name,
+            value,
+        }
+    }
+
+    // 嵌套的配置段，错误的字段名加上 name. 前缀
+    pub fn nested<T: Validate + ?Sized>(&mut self, name: &str, value: &T) {
+        let saved = std::mem::take(&mut self.prefix);
+        self.prefix = if saved.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", saved, name)
+        };
+        value.validate(self);
+        self.prefix = saved;
+    }
+
+    // 跨字段的规则，例如 min <= max
+    pub fn error(&mut self, field: &str, message: impl Into<String>) {
+        let field = if self.prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", self.prefix, field)
+        };
+        self.errors.push(FieldError {
+            field,
+            message: message.into(),
+        });
+    }
+
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    pub fn finish(self) -> Result<(), ConfigError> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Validation(self.errors))
+        }
+    }
+}
+
+// 规则可以链式调用，每条规则独立检查
+pub struct Field<'a, V> {
+    validator: &'a mut Validator,
+    name: String,
+    value: V,
+}
+
+impl<V> Field<'_, V> {
+    fn fail(&mut self, message: String) {
+        self.validator.errors.push(FieldError {
+            field: self.name.clone(),
+            message,
+        });
+    }
+
+    pub fn custom(mut self, rule: impl FnOnce(&V) -> Result<(), String>) -> Self {
+        if let Err(message) = rule(&self.value) {
+            self.fail(message);
+        }
+        self
+    }
+}
+
+impl<V: PartialEq + Default> Field<'_, V> {
+    pub fn nonzero(mut self) -> Self {
+        if self.value == V::default() {
+            self.fail("不能为 0".into());
+        }
+        self
+    }
+}
+
+impl<V: PartialOrd + fmt::Display> Field<'_, V> {
+    pub fn range(mut self, range: RangeInclusive<V>) -> Self {
+        if !range.contains(&self.value) {
+            let message = format!(
+                "{} 不在 {}..={} 范围内",
+                self.value,
+                range.start(),
+                range.end()
+            );
+            self.fail(message);
+        }
+        self
+    }
+}
+
+impl<V: AsRef<str>> Field<'_, V> {
+    pub fn not_empty(mut self) -> Self {
+        if self.value.as_ref().trim().is_empty() {
+            self.fail("不能为空".into());
+        }
+        self
+    }
+
+    pub fn max_len(mut self, max: usize) -> Self {
+        let len = self.value.as_ref().chars().count();
+        if len > max {
+            self.fail(format!("长度 {} 超过 {}", len, max));
+        }
+        self
+    }
+
+    // 主机名(RFC 1123)或 IP 地址；空字符串由 not_empty 负责
+    pub fn hostname(mut self) -> Self {
+        let host = self.value.as_ref();
+        if !host.is_empty() && !is_hostname(host) {
+            self.fail(format!("{:?} 不是有效的主机名", host));
+        }
+        self
+    }
+
+    pub fn one_of(mut self, allowed: &[&str]) -> Self {
+        let value = self.value.as_ref();
+        if !allowed.contains(&value) {
+            self.fail(format!("{:?} 不是 {} 之一", value, allowed.join("/")));
+        }
+        self
+    }
+
+    pub fn matches(mut self, pattern: &str) -> Self {
+        let ok = Regex::new(pattern)
+            .map(|re| re.is_match(self.value.as_ref()))
+            .unwrap_or(false);
+        if !ok {
+            let message = format!("{:?} 不符合格式 {}", self.value.as_ref(), pattern);
+            self.fail(message);
+        }
+        self
+    }
+}
+
+fn is_hostname(host: &str) -> bool {
+    if host.parse::<IpAddr>().is_ok() {
+        return true;
+    }
+    let host = host.strip_suffix('.').unwrap_or(host);
+    host.len() <= 253
+        && host.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
@@ -0,0 +1,213 @@
+use std::any::{type_name, Any, TypeId};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+
+// 服务容器: 启动时按类型注册构造函数，使用方按类型取得服务。
+// singleton 在整个进程内只构造一次，scoped 在每个 Scope(例如一次请求)内构造一次。
+
+type Instance = Arc<dyn Any + Send + Sync>;
+type Factory = Arc<dyn Fn(&Scope) -> Result<Instance, ContainerError> + Send + Sync>;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum ContainerError {
+    #[error("服务未注册: {0}")]
+    NotRegistered(&'static str),
+
+    #[error("服务存在循环依赖: {0}")]
+    Cycle(&'static str),
+
+    #[error("scoped 服务只能在 Scope 中获取: {0}")]
+    ScopeRequired(&'static str),
+
+    #[error("构造服务 {service} 失败: {message}")]
+    Construct {
+        service: &'static str,
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lifetime {
+    Singleton,
+    Scoped,
+}
+
+struct Registration {
+    name: &'static str,
+    lifetime: Lifetime,
+    factory: Factory,
+}
+
+struct Inner {
+    registrations: HashMap<TypeId, Registration>,
+    singletons: Mutex<HashMap<TypeId, Instance>>,
+}
+
+#[derive(Default)]
+pub struct ContainerBuilder {
+    registrations: HashMap<TypeId, Registration>,
+    instances: HashMap<TypeId, Instance>,
+}
+
+impl ContainerBuilder {
+    fn register<T, F>(mut self, lifetime: Lifetime, factory: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&Scope) -> Result<T, ContainerError> + Send + Sync + 'static,
+    {
+        self.registrations.insert(
+            TypeId::of::<T>(),
+            Registration {
+                name: type_name::<T>(),
+                lifetime,
+                factory: Arc::new(move |scope| Ok(Arc::new(factory(scope)?) as Instance)),
+            },
+        );
+        self
+    }
+
+    pub fn singleton<T, F>(self, factory: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&Scope) -> Result<T, ContainerError> + Send + Sync + 'static,
+    {
+        self.register(Lifetime::Singleton, factory)
+    }
+
+    pub fn scoped<T, F>(self, factory: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&Scope) -> Result<T, ContainerError> + Send + Sync + 'static,
+    {
+        self.register(Lifetime::Scoped, factory)
+    }
+
+    // 注册已经构造好的实例，测试中用来替换真实服务
+    pub fn instance<T: Send + Sync + 'static>(mut self, value: T) -> Self {
+        let id = TypeId::of::<T>();
+        self.instances.insert(id, Arc::new(value));
+        self.registrations.insert(
+            id,
+            Registration {
+                name: type_name::<T>(),
+                lifetime: Lifetime::Singleton,
+                factory: Arc::new(|_| unreachable!("实例在构建容器时已经放入")),
+            },
+        );
+        self
+    }
+
+    pub fn build(self) -> ServiceContainer {
+        ServiceContainer {
+            inner: Arc::new(Inner {
+                registrations: self.registrations,
+                singletons: Mutex::new(self.instances),
+            }),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ServiceContainer {
+    inner: Arc<Inner>,
+}
+
+impl ServiceContainer {
+    pub fn builder() -> ContainerBuilder {
+        ContainerBuilder::default()
+    }
+
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.inner.registrations.contains_key(&TypeId::of::<T>())
+    }
+
+    // 已注册服务的类型名，用于启动时输出诊断信息
+    pub fn services(&self) -> Vec<&'static str> {
+        let mut names: Vec<_> = self.inner.registrations.values().map(|r| r.name).collect();
+        names.sort();
+        names
+    }
+
+    // 只能获取 singleton 服务
+    pub fn resolve<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, ContainerError> {
+        self.root().resolve()
+    }
+
+    pub fn scope(&self) -> Scope {
+        Scope {
+            inner: self.inner.clone(),
+            scoped: Some(Mutex::new(HashMap::new())),
+            resolving: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn root(&self) -> Scope {
+        Scope {
+            inner: self.inner.clone(),
+            scoped: None,
+            resolving: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+// 一次请求的作用域，也是构造函数获取依赖的入口
+pub struct Scope {
+    inner: Arc<Inner>,
+    // 根作用域为 None，singleton 不能依赖 scoped 服务
+    scoped: Option<Mutex<HashMap<TypeId, Instance>>>,
+    resolving: Mutex<Vec<TypeId>>,
+}
+
+impl Scope {
+    pub fn resolve<T: Send + Sync + 'static>(&self) -> Result<Arc<T>, ContainerError> {
+        let instance = self.resolve_any(TypeId::of::<T>(), type_name::<T>())?;
+        Ok(instance
+            .downcast::<T>()
+            .expect("注册时的类型与 TypeId 一致"))
+    }
+
+    fn resolve_any(&self, id: TypeId, name: &'static str) -> Result<Instance, ContainerError> {
+        let registration = self
+            .inner
+            .registrations
+            .get(&id)
+            .ok_or(ContainerError::NotRegistered(name))?;
+        let cache = match registration.lifetime {
+            Lifetime::Singleton => &self.inner.singletons,
+            Lifetime::Scoped => self
+                .scoped
+                .as_ref()
+                .ok_or(ContainerError::ScopeRequired(name))?,
+        };
+        if let Some(instance) = cache.lock().unwrap().get(&id) {
+            return Ok(instance.clone());
+        }
+
+        {
+            let mut resolving = self.resolving.lock().unwrap();
+            if resolving.contains(&id) {
+                return Err(ContainerError::Cycle(name));
+            }
+            resolving.push(id);
+        }
+        // singleton 的依赖在根作用域中解析
+        let result = match registration.lifetime {
+            Lifetime::Singleton if self.scoped.is_some() => {
+                let root = Scope {
+                    inner: self.inner.clone(),
+                    scoped: None,
+                    resolving: Mutex::new(self.resolving.lock().unwrap().clone()),
+                };
+                (registration.factory)(&root)
+            }
+            _ => (registration.factory)(self),
+        };
+        self.resolving.lock().unwrap().retain(|r| *r != id);
+
+        // 并发构造时保留先放入的实例
+        let instance = result?;
+        Ok(cache.lock().unwrap().entry(id).or_insert(instance).clone())
+    }
+}
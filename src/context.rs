@@ -0,0 +1,204 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::semaphore::Priority;
+use crate::tenant::TenantId;
+
+pub mod costs;
+
+use costs::{CostSummary, CostTracker};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+pub const USER_ID_HEADER: &str = "x-user-id";
+pub const TENANT_ID_HEADER: &str = "x-tenant-id";
+
+tokio::task_local! {
+    static TASK_CONTEXT: RequestContext;
+}
+
+thread_local! {
+    static THREAD_CONTEXT: RefCell<Vec<RequestContext>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestContext {
+    pub request_id: String,
+    pub user_id: Option<String>,
+    pub tenant_id: Option<TenantId>,
+    pub locale: Option<String>,
+    pub deadline: Option<Instant>,
+    // 开启下游开销统计时才有，见 costs 模块
+    pub costs: Option<CostTracker>,
+    // 排队等待 db 连接、出站请求等受限资源时使用
+    pub priority: Priority,
+}
+
+impl Default for RequestContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RequestContext {
+    // 自动生成 request id
+    pub fn new() -> Self {
+        Self::with_request_id(&generate_request_id())
+    }
+
+    pub fn with_request_id(request_id: &str) -> Self {
+        RequestContext {
+            request_id: request_id.to_string(),
+            user_id: None,
+            tenant_id: None,
+            locale: None,
+            deadline: None,
+            costs: None,
+            priority: Priority::Normal,
+        }
+    }
+
+    pub fn user(mut self, user_id: &str) -> Self {
+        self.user_id = Some(user_id.to_string());
+        self
+    }
+
+    pub fn tenant(mut self, tenant: TenantId) -> Self {
+        self.tenant_id = Some(tenant);
+        self
+    }
+
+    pub fn locale(mut self, locale: &str) -> Self {
+        self.locale = Some(locale.to_string());
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.deadline = Some(Instant::now() + timeout);
+        self
+    }
+
+    // 统计这个请求的数据库、缓存和 HTTP 开销
+    pub fn track_costs(mut self) -> Self {
+        self.costs = Some(CostTracker::new());
+        self
+    }
+
+    // 排队等待 PrioritySemaphore 时的优先级，默认 Normal
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn cost_summary(&self) -> Option<CostSummary> {
+        self.costs.as_ref().map(|c| c.summary())
+    }
+
+    // 距离 deadline 的剩余时间，已超时返回 0
+    pub fn remaining(&self) -> Option<Duration> {
+        self.deadline
+            .map(|d| d.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.remaining() == Some(Duration::ZERO)
+    }
+
+    // 需要透传给下游服务的请求头
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let mut headers = vec![(REQUEST_ID_HEADER, self.request_id.clone())];
+        if let Some(user) = &self.user_id {
+            headers.push((USER_ID_HEADER, user.clone()));
+        }
+        if let Some(tenant) = &self.tenant_id {
+            headers.push((TENANT_ID_HEADER, tenant.to_string()));
+        }
+        if let Some(locale) = &self.locale {
+            headers.push(("accept-language", locale.clone()));
+        }
+        headers
+    }
+}
+
+// 日志行中使用的 key=value 形式
+impl fmt::Display for RequestContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "request_id={}", self.request_id)?;
+        if let Some(user) = &self.user_id {
+            write!(f, " user_id={}", user)?;
+        }
+        if let Some(tenant) = &self.tenant_id {
+            write!(f, " tenant_id={}", tenant)?;
+        }
+        if let Some(locale) = &self.locale {
+            write!(f, " locale={}", locale)?;
+        }
+        Ok(())
+    }
+}
+
+fn generate_request_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0);
+    format!(
+        "{:016x}-{:04x}-{:08x}",
+        nanos,
+        std::process::id() & 0xffff,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+// 当前上下文，异步任务优先于线程
+pub fn current() -> Option<RequestContext> {
+    TASK_CONTEXT
+        .try_with(|ctx| ctx.clone())
+        .ok()
+        .or_else(|| THREAD_CONTEXT.with(|stack| stack.borrow().last().cloned()))
+}
+
+// 在异步任务中设置上下文
+pub async fn scope<F: Future>(ctx: RequestContext, fut: F) -> F::Output {
+    TASK_CONTEXT.scope(ctx, fut).await
+}
+
+// 在同步代码中设置上下文，闭包返回后自动恢复
+pub fn sync_scope<R>(ctx: RequestContext, f: impl FnOnce() -> R) -> R {
+    struct Guard;
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            THREAD_CONTEXT.with(|stack| stack.borrow_mut().pop());
+        }
+    }
+
+    THREAD_CONTEXT.with(|stack| stack.borrow_mut().push(ctx));
+    let _guard = Guard;
+    f()
+}
+
+// tokio::spawn 不会继承 task-local，这里把当前上下文带到新任务中
+pub fn spawn<F>(fut: F) -> tokio::task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match current() {
+        Some(ctx) => tokio::spawn(TASK_CONTEXT.scope(ctx, fut)),
+        None => tokio::spawn(fut),
+    }
+}
+
+// 给出站 HTTP 请求加上当前上下文的请求头
+pub fn inject(builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    match current() {
+        Some(ctx) => ctx
+            .headers()
+            .into_iter()
+            .fold(builder, |b, (k, v)| b.header(k, v)),
+        None => builder,
+    }
+}
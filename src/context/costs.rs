@@ -0,0 +1,162 @@
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use super::{RequestContext, TASK_CONTEXT, THREAD_CONTEXT};
+use crate::logs::{self, Level};
+
+// 单个请求的下游开销: 数据库查询、缓存命中/未命中、出站 HTTP 调用的次数和耗时。
+// 计数器挂在 RequestContext 上，随上下文传到 spawn 的任务中；
+// db::timeout、TtlCache 和 HttpClient 会自动记录，其他下游调用可以手动 record_*。
+// instrument 在请求结束时把汇总写到最后一条日志里，排查慢请求时不用再拼各处的日志。
+
+pub const LOG_TARGET: &str = "request";
+
+#[derive(Debug, Default)]
+struct Counters {
+    db_queries: AtomicU64,
+    db_micros: AtomicU64,
+    cache_hits: AtomicU64,
+    cache_misses: AtomicU64,
+    http_calls: AtomicU64,
+    http_micros: AtomicU64,
+}
+
+// 克隆共享同一组计数器；只有同一个实例才相等
+#[derive(Debug, Clone, Default)]
+pub struct CostTracker(Arc<Counters>);
+
+impl PartialEq for CostTracker {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl CostTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn db(&self, elapsed: Duration) {
+        self.0.db_queries.fetch_add(1, Ordering::Relaxed);
+        self.0
+            .db_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn cache(&self, hit: bool) {
+        let counter = if hit {
+            &self.0.cache_hits
+        } else {
+            &self.0.cache_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn http(&self, elapsed: Duration) {
+        self.0.http_calls.fetch_add(1, Ordering::Relaxed);
+        self.0
+            .http_micros
+            .fetch_add(elapsed.as_micros() as u64, Ordering::Relaxed);
+    }
+
+    pub fn summary(&self) -> CostSummary {
+        let c = &self.0;
+        CostSummary {
+            db_queries: c.db_queries.load(Ordering::Relaxed),
+            db_time: Duration::from_micros(c.db_micros.load(Ordering::Relaxed)),
+            cache_hits: c.cache_hits.load(Ordering::Relaxed),
+            cache_misses: c.cache_misses.load(Ordering::Relaxed),
+            http_calls: c.http_calls.load(Ordering::Relaxed),
+            http_time: Duration::from_micros(c.http_micros.load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct CostSummary {
+    pub db_queries: u64,
+    pub db_time: Duration,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub http_calls: u64,
+    pub http_time: Duration,
+}
+
+// 日志行中使用的 key=value 形式，耗时单位是毫秒
+impl fmt::Display for CostSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "db_queries={} db_ms={:.1} cache_hits={} cache_misses={} http_calls={} http_ms={:.1}",
+            self.db_queries,
+            self.db_time.as_secs_f64() * 1000.0,
+            self.cache_hits,
+            self.cache_misses,
+            self.http_calls,
+            self.http_time.as_secs_f64() * 1000.0
+        )
+    }
+}
+
+// 当前上下文的计数器，没有上下文或没有开启统计时为 None。不克隆整个上下文
+pub fn current() -> Option<CostTracker> {
+    TASK_CONTEXT
+        .try_with(|ctx| ctx.costs.clone())
+        .ok()
+        .flatten()
+        .or_else(|| {
+            THREAD_CONTEXT.with(|stack| stack.borrow().last().and_then(|ctx| ctx.costs.clone()))
+        })
+}
+
+pub fn record_db(elapsed: Duration) {
+    if let Some(tracker) = current() {
+        tracker.db(elapsed);
+    }
+}
+
+pub fn record_cache(hit: bool) {
+    if let Some(tracker) = current() {
+        tracker.cache(hit);
+    }
+}
+
+pub fn record_http(elapsed: Duration) {
+    if let Some(tracker) = current() {
+        tracker.http(elapsed);
+    }
+}
+
+// 在 ctx 中执行请求，结束时输出一条 INFO 日志:
+//   <name> 完成 elapsed_ms=.. db_queries=.. db_ms=.. cache_hits=.. ...
+// ctx 没有开启统计时自动开启
+pub async fn instrument<F: Future>(name: &str, ctx: RequestContext, fut: F) -> F::Output {
+    let ctx = if ctx.costs.is_some() {
+        ctx
+    } else {
+        ctx.track_costs()
+    };
+    let tracker = ctx.costs.clone().unwrap_or_default();
+    super::scope(ctx, async {
+        let start = Instant::now();
+        let output = fut.await;
+        if logs::enabled(Level::Info, LOG_TARGET) {
+            logs::info(
+                LOG_TARGET,
+                &format!(
+                    "{} 完成 elapsed_ms={:.1} {}",
+                    name,
+                    start.elapsed().as_secs_f64() * 1000.0,
+                    tracker.summary()
+                ),
+            );
+        }
+        output
+    })
+    .await
+}
@@ -0,0 +1,134 @@
+use std::backtrace::Backtrace;
+use std::fs;
+use std::io;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use crate::logs;
+
+type LogSource = Arc<dyn Fn() -> Vec<String> + Send + Sync>;
+
+// panic 时写入崩溃目录的报告
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReport {
+    pub timestamp: u64,
+    pub pid: u32,
+    pub thread: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub backtrace: String,
+    pub config_fingerprint: Option<String>,
+    pub config: Option<Value>,
+    pub recent_logs: Vec<String>,
+}
+
+// 配置的指纹: 对原始配置取 sha256，密钥不同指纹也不同，但报告中只出现脱敏后的内容
+pub fn fingerprint<T: Serialize + ?Sized>(config: &T) -> Result<String, serde_json::Error> {
+    let bytes = serde_json::to_vec(&serde_json::to_value(config)?)?;
+    Ok(hex::encode(&Sha256::digest(&bytes)[..8]))
+}
+
+#[derive(Clone)]
+pub struct CrashReporter {
+    dir: PathBuf,
+    config: Option<(String, Value)>,
+    logs: Option<LogSource>,
+}
+
+impl CrashReporter {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        CrashReporter {
+            dir: dir.into(),
+            config: None,
+            logs: None,
+        }
+    }
+
+    // 记录当前配置，写入报告前按日志使用的脱敏规则处理
+    pub fn config<T: Serialize + ?Sized>(mut self, config: &T) -> Result<Self, serde_json::Error> {
+        let redacted = logs::redactor().redact(config)?;
+        self.config = Some((fingerprint(config)?, redacted));
+        Ok(self)
+    }
+
+    // 最近日志的来源
+    pub fn recent_logs<F>(mut self, source: F) -> Self
+    where
+        F: Fn() -> Vec<String> + Send + Sync + 'static,
+    {
+        self.logs = Some(Arc::new(source));
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn report(&self, message: &str, location: Option<String>) -> CrashReport {
+        CrashReport {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            pid: std::process::id(),
+            thread: std::thread::current()
+                .name()
+                .unwrap_or("<unnamed>")
+                .to_string(),
+            message: message.to_string(),
+            location,
+            backtrace: Backtrace::force_capture().to_string(),
+            config_fingerprint: self.config.as_ref().map(|(f, _)| f.clone()),
+            config: self.config.as_ref().map(|(_, c)| c.clone()),
+            recent_logs: self.logs.as_ref().map(|f| f()).unwrap_or_default(),
+        }
+    }
+
+    // 写入 crash-<时间戳>-<pid>.json，返回文件路径
+    pub fn write(&self, report: &CrashReport) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let mut path = self
+            .dir
+            .join(format!("crash-{}-{}.json", report.timestamp, report.pid));
+        let mut n = 1;
+        while path.exists() {
+            path = self.dir.join(format!(
+                "crash-{}-{}-{}.json",
+                report.timestamp, report.pid, n
+            ));
+            n += 1;
+        }
+        fs::write(&path, serde_json::to_vec_pretty(report)?)?;
+        Ok(path)
+    }
+
+    // 安装 panic hook，写完报告后继续调用原来的 hook
+    pub fn install(self) {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let report = self.report(&panic_message(info), info.location().map(|l| l.to_string()));
+            match self.write(&report) {
+                Ok(path) => eprintln!("崩溃报告已写入: {}", path.display()),
+                Err(e) => eprintln!("写入崩溃报告失败: {}", e),
+            }
+            previous(info);
+        }));
+    }
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "<non-string panic payload>".to_string()
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::app::BoxError;
+use crate::logs;
+use crate::pool::jobs::{JobContext, JobPool};
+
+// 有依赖关系的任务图: 每个任务声明依赖的任务，依赖全部成功后提交到任务池执行，
+// 互不依赖的任务并行运行。失败时按策略立即停止(不再启动新任务)或继续执行与失败无关的任务，
+// 结束后报告每个任务的状态、开始时间和耗时。用于应用内的构建、导入等多步骤流程。
+
+#[derive(Error, Debug)]
+pub enum DagError {
+    #[error("重复的任务: {0}")]
+    Duplicate(String),
+
+    #[error("任务 {job} 依赖未知的任务 {dependency}")]
+    UnknownDependency { job: String, dependency: String },
+
+    #[error("任务存在循环依赖: {}", .0.join(", "))]
+    Cycle(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OnError {
+    // 不再启动新任务，已经在运行的任务执行完
+    #[default]
+    FailFast,
+    // 只跳过依赖失败任务的任务
+    Continue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "error", rename_all = "lowercase")]
+pub enum NodeStatus {
+    Succeeded,
+    Failed(String),
+    // 依赖的任务失败
+    Skipped,
+    // FailFast 时因其他任务失败而没有启动
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeReport {
+    pub name: String,
+    pub status: NodeStatus,
+    // 相对于整个图开始运行的时间，没有运行的任务为 None
+    pub started_at: Option<Duration>,
+    pub duration: Option<Duration>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DagReport {
+    // 按注册顺序
+    pub nodes: Vec<NodeReport>,
+    pub elapsed: Duration,
+}
+
+impl DagReport {
+    pub fn is_success(&self) -> bool {
+        self.nodes.iter().all(|n| n.status == NodeStatus::Succeeded)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&NodeReport> {
+        self.nodes.iter().find(|n| n.name == name)
+    }
+
+    pub fn failed(&self) -> Vec<&str> {
+        self.with_status(|s| matches!(s, NodeStatus::Failed(_)))
+    }
+
+    pub fn skipped(&self) -> Vec<&str> {
+        self.with_status(|s| matches!(s, NodeStatus::Skipped | NodeStatus::Cancelled))
+    }
+
+    fn with_status(&self, f: impl Fn(&NodeStatus) -> bool) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter(|n| f(&n.status))
+            .map(|n| n.name.as_str())
+            .collect()
+    }
+}
+
+type JobFn = Box<dyn FnOnce(&JobContext) -> Result<(), BoxError> + Send>;
+
+struct Node {
+    name: String,
+    deps: Vec<String>,
+    run: JobFn,
+}
+
+#[derive(Default)]
+pub struct Dag {
+    nodes: Vec<Node>,
+}
+
+pub fn dag() -> Dag {
+    Dag::default()
+}
+
+impl Dag {
+    pub fn job<F>(mut self, name: &str, deps: &[&str], run: F) -> Self
+    where
+        F: FnOnce(&JobContext) -> Result<(), BoxError> + Send + 'static,
+    {
+        self.nodes.push(Node {
+            name: name.to_string(),
+            deps: deps.iter().map(|d| d.to_string()).collect(),
+            run: Box::new(run),
+        });
+        self
+    }
+
+    // 一种合法的执行顺序，依赖相同时保持注册顺序
+    pub fn order(&self) -> Result<Vec<String>, DagError> {
+        let deps = self.resolve()?;
+        let mut pending: Vec<usize> = deps.iter().map(|d| d.len()).collect();
+        let mut done = vec![false; self.nodes.len()];
+        let mut order = Vec::new();
+        while order.len() < self.nodes.len() {
+            let next = (0..self.nodes.len()).find(|&i| !done[i] && pending[i] == 0);
+            let Some(i) = next else {
+                let cycle = (0..self.nodes.len())
+                    .filter(|&i| !done[i])
+                    .map(|i| self.nodes[i].name.clone())
+                    .collect();
+                return Err(DagError::Cycle(cycle));
+            };
+            done[i] = true;
+            order.push(self.nodes[i].name.clone());
+            for (j, d) in deps.iter().enumerate() {
+                pending[j] -= d.iter().filter(|&&k| k == i).count();
+            }
+        }
+        Ok(order)
+    }
+
+    // 每个任务依赖的任务下标
+    fn resolve(&self) -> Result<Vec<Vec<usize>>, DagError> {
+        let mut index = HashMap::new();
+        for (i, node) in self.nodes.iter().enumerate() {
+            if index.insert(node.name.as_str(), i).is_some() {
+                return Err(DagError::Duplicate(node.name.clone()));
+            }
+        }
+        self.nodes
+            .iter()
+            .map(|node| {
+                node.deps
+                    .iter()
+                    .map(|dep| {
+                        index.get(dep.as_str()).copied().ok_or_else(|| {
+                            DagError::UnknownDependency {
+                                job: node.name.clone(),
+                                dependency: dep.clone(),
+                            }
+                        })
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    // 图不合法(重复、未知依赖、循环)时不运行任何任务
+    pub fn run(self, pool: &JobPool, on_error: OnError) -> Result<DagReport, DagError> {
+        let deps = self.resolve()?;
+        self.order()?;
+        let n = self.nodes.len();
+        let mut dependents = vec![Vec::new(); n];
+        for (i, d) in deps.iter().enumerate() {
+            for &k in d {
+                dependents[k].push(i);
+            }
+        }
+        let mut pending: Vec<usize> = deps.iter().map(|d| d.len()).collect();
+        let mut reports: Vec<NodeReport> = self
+            .nodes
+            .iter()
+            .map(|node| NodeReport {
+                name: node.name.clone(),
+                status: NodeStatus::Cancelled,
+                started_at: None,
+                duration: None,
+            })
+            .collect();
+        let names: Vec<String> = self.nodes.iter().map(|n| n.name.clone()).collect();
+        let mut jobs: Vec<Option<JobFn>> = self.nodes.into_iter().map(|n| Some(n.run)).collect();
+
+        let start = Instant::now();
+        let (tx, rx) = mpsc::channel::<(usize, Duration, Duration, Result<(), String>)>();
+        let mut failed = false;
+        let mut running = 0;
+        let submit = |i: usize, job: JobFn, running: &mut usize| {
+            let tx = tx.clone();
+            *running += 1;
+            pool.submit(&names[i], move |ctx| {
+                let started_at = start.elapsed();
+                let result = panic::catch_unwind(AssertUnwindSafe(|| job(ctx)))
+                    .unwrap_or_else(|_| Err("任务 panic".into()))
+                    .map_err(|e| e.to_string());
+                let _ = tx.send((i, started_at, start.elapsed() - started_at, result));
+                Ok(())
+            });
+        };
+        for i in 0..n {
+            if pending[i] == 0 {
+                let job = jobs[i].take().expect("每个任务只提交一次");
+                submit(i, job, &mut running);
+            }
+        }
+        while running > 0 {
+            let (i, started_at, duration, result) = rx.recv().expect("发送端由运行中的任务持有");
+            running -= 1;
+            let report = &mut reports[i];
+            report.started_at = Some(started_at);
+            report.duration = Some(duration);
+            match result {
+                Ok(()) => {
+                    report.status = NodeStatus::Succeeded;
+                    if failed && on_error == OnError::FailFast {
+                        continue;
+                    }
+                    for &j in &dependents[i] {
+                        pending[j] -= 1;
+                        if pending[j] == 0 {
+                            if let Some(job) = jobs[j].take() {
+                                submit(j, job, &mut running);
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    logs::error("dag", &format!("任务 {} 失败: {}", report.name, e));
+                    report.status = NodeStatus::Failed(e);
+                    failed = true;
+                    skip_dependents(i, &dependents, &mut jobs, &mut reports);
+                }
+            }
+        }
+        Ok(DagReport {
+            nodes: reports,
+            elapsed: start.elapsed(),
+        })
+    }
+}
+
+// 失败任务的所有下游任务(包括间接依赖的)标记为 Skipped
+fn skip_dependents(
+    failed: usize,
+    dependents: &[Vec<usize>],
+    jobs: &mut [Option<JobFn>],
+    reports: &mut [NodeReport],
+) {
+    let mut stack = dependents[failed].clone();
+    while let Some(j) = stack.pop() {
+        if jobs[j].take().is_some() {
+            reports[j].status = NodeStatus::Skipped;
+            stack.extend(&dependents[j]);
+        }
+    }
+}
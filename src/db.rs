@@ -0,0 +1,45 @@
+use std::time::Duration;
+
+use thiserror::Error;
+
+pub mod batch;
+pub mod hooks;
+pub mod plan;
+pub mod pool;
+pub mod routing;
+pub mod sqlite;
+pub mod timeout;
+pub mod users;
+
+pub use plan::{explain, QueryPlan};
+
+#[derive(Error, Debug)]
+pub enum DbError {
+    #[error("数据库连接失败: {0}")]
+    Connection(String),
+
+    #[error("查询数据失败: {0}")]
+    Query(sqlx::Error),
+
+    #[error("记录不存在： ID= {0}")]
+    NotFound(i64),
+
+    #[error("记录已存在: {0}")]
+    Duplicate(String),
+
+    #[error("操作被拒绝: {0}")]
+    Rejected(String),
+
+    #[error("查询超时: 已执行 {elapsed:?}")]
+    Timeout { elapsed: Duration },
+}
+
+// 唯一约束冲突映射为 Duplicate，其余保留原始错误
+impl From<sqlx::Error> for DbError {
+    fn from(e: sqlx::Error) -> Self {
+        match e.as_database_error() {
+            Some(db) if db.is_unique_violation() => DbError::Duplicate(db.message().to_string()),
+            _ => DbError::Query(e),
+        }
+    }
+}
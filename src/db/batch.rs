@@ -0,0 +1,72 @@
+use std::collections::{HashMap, HashSet};
+
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, SqlitePool};
+
+use super::DbError;
+
+// 按 id 批量读取: 一条 IN (...) 查询(超过参数上限时分批)，结果按请求的顺序返回，
+// 并报告哪些 id 不存在。
+
+// 老版本 SQLite 的参数上限是 999
+pub const CHUNK_SIZE: usize = 500;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchResult<T> {
+    // 按请求顺序，重复的 id 只出现一次
+    pub found: Vec<T>,
+    pub missing: Vec<i64>,
+}
+
+impl<T> BatchResult<T> {
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    // 有缺失时返回第一个缺失的 id
+    pub fn require_all(self) -> Result<Vec<T>, DbError> {
+        match self.missing.first() {
+            Some(id) => Err(DbError::NotFound(*id)),
+            None => Ok(self.found),
+        }
+    }
+}
+
+// select 是不带 WHERE 的查询，例如 "SELECT id, name FROM users"
+pub async fn find_many<T, F>(
+    pool: &SqlitePool,
+    select: &str,
+    ids: &[i64],
+    id_of: F,
+) -> Result<BatchResult<T>, DbError>
+where
+    T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+    F: Fn(&T) -> i64,
+{
+    let mut seen = HashSet::new();
+    let ids: Vec<i64> = ids.iter().copied().filter(|id| seen.insert(*id)).collect();
+
+    let mut rows = HashMap::with_capacity(ids.len());
+    for chunk in ids.chunks(CHUNK_SIZE) {
+        let placeholders = vec!["?"; chunk.len()].join(", ");
+        let sql = format!("{} WHERE id IN ({})", select, placeholders);
+        let query = chunk
+            .iter()
+            .fold(sqlx::query_as::<_, T>(&sql), |q, id| q.bind(id));
+        for row in query.fetch_all(pool).await? {
+            rows.insert(id_of(&row), row);
+        }
+    }
+
+    let mut result = BatchResult {
+        found: Vec::with_capacity(rows.len()),
+        missing: Vec::new(),
+    };
+    for id in ids {
+        match rows.remove(&id) {
+            Some(row) => result.found.push(row),
+            None => result.missing.push(id),
+        }
+    }
+    Ok(result)
+}
@@ -0,0 +1,131 @@
+use std::sync::Arc;
+
+use super::DbError;
+use crate::logs;
+
+// 仓库的实体变更钩子: 审计日志、缓存失效、outbox 事件等统一在这里注册，
+// 不用在每个调用方重复。before 钩子返回错误时操作被拒绝(DbError::Rejected)，
+// after 钩子在写入成功后按注册顺序调用。
+
+pub trait Entity: Send + Sync + 'static {
+    type New: Send + Sync + 'static;
+    type Update: Send + Sync + 'static;
+}
+
+type Before<A> = Arc<dyn Fn(&A) -> Result<(), String> + Send + Sync>;
+type After<A> = Arc<dyn Fn(&A) + Send + Sync>;
+type BeforeUpdate<U> = Arc<dyn Fn(i64, &U) -> Result<(), String> + Send + Sync>;
+
+pub struct Hooks<E: Entity> {
+    before_insert: Vec<Before<E::New>>,
+    after_insert: Vec<After<E>>,
+    before_update: Vec<BeforeUpdate<E::Update>>,
+    after_update: Vec<After<E>>,
+    before_delete: Vec<Before<i64>>,
+    after_delete: Vec<After<i64>>,
+}
+
+impl<E: Entity> Default for Hooks<E> {
+    fn default() -> Self {
+        Hooks {
+            before_insert: Vec::new(),
+            after_insert: Vec::new(),
+            before_update: Vec::new(),
+            after_update: Vec::new(),
+            before_delete: Vec::new(),
+            after_delete: Vec::new(),
+        }
+    }
+}
+
+impl<E: Entity> Clone for Hooks<E> {
+    fn clone(&self) -> Self {
+        Hooks {
+            before_insert: self.before_insert.clone(),
+            after_insert: self.after_insert.clone(),
+            before_update: self.before_update.clone(),
+            after_update: self.after_update.clone(),
+            before_delete: self.before_delete.clone(),
+            after_delete: self.after_delete.clone(),
+        }
+    }
+}
+
+fn check<A>(hooks: &[Before<A>], arg: &A) -> Result<(), DbError> {
+    hooks
+        .iter()
+        .try_for_each(|hook| hook(arg))
+        .map_err(DbError::Rejected)
+}
+
+// after 钩子 panic 不影响已经完成的写入
+fn notify<A>(hooks: &[After<A>], arg: &A) {
+    for hook in hooks {
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| hook(arg)));
+        if result.is_err() {
+            logs::error("db::hooks", "after 钩子 panic，已忽略");
+        }
+    }
+}
+
+impl<E: Entity> Hooks<E> {
+    pub fn on_before_insert<F>(&mut self, f: F)
+    where
+        F: Fn(&E::New) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.before_insert.push(Arc::new(f));
+    }
+
+    pub fn on_after_insert<F: Fn(&E) + Send + Sync + 'static>(&mut self, f: F) {
+        self.after_insert.push(Arc::new(f));
+    }
+
+    pub fn on_before_update<F>(&mut self, f: F)
+    where
+        F: Fn(i64, &E::Update) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.before_update.push(Arc::new(f));
+    }
+
+    pub fn on_after_update<F: Fn(&E) + Send + Sync + 'static>(&mut self, f: F) {
+        self.after_update.push(Arc::new(f));
+    }
+
+    pub fn on_before_delete<F>(&mut self, f: F)
+    where
+        F: Fn(i64) -> Result<(), String> + Send + Sync + 'static,
+    {
+        self.before_delete.push(Arc::new(move |id: &i64| f(*id)));
+    }
+
+    pub fn on_after_delete<F: Fn(i64) + Send + Sync + 'static>(&mut self, f: F) {
+        self.after_delete.push(Arc::new(move |id: &i64| f(*id)));
+    }
+
+    pub fn before_insert(&self, new: &E::New) -> Result<(), DbError> {
+        check(&self.before_insert, new)
+    }
+
+    pub fn after_insert(&self, entity: &E) {
+        notify(&self.after_insert, entity);
+    }
+
+    pub fn before_update(&self, id: i64, update: &E::Update) -> Result<(), DbError> {
+        self.before_update
+            .iter()
+            .try_for_each(|hook| hook(id, update))
+            .map_err(DbError::Rejected)
+    }
+
+    pub fn after_update(&self, entity: &E) {
+        notify(&self.after_update, entity);
+    }
+
+    pub fn before_delete(&self, id: i64) -> Result<(), DbError> {
+        check(&self.before_delete, &id)
+    }
+
+    pub fn after_delete(&self, id: i64) {
+        notify(&self.after_delete, &id);
+    }
+}
@@ -0,0 +1,117 @@
+use std::fmt;
+
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+
+use super::DbError;
+
+// 查询计划诊断: 用 EXPLAIN QUERY PLAN 取得 SQLite 的执行计划并还原成树，
+// 方便定位全表扫描和临时 B 树排序。目前只支持 SQLite。
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlanNode {
+    pub id: i64,
+    pub detail: String,
+    pub children: Vec<PlanNode>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueryPlan {
+    pub query: String,
+    pub nodes: Vec<PlanNode>,
+}
+
+impl PlanNode {
+    fn walk<'a>(&'a self, out: &mut Vec<&'a PlanNode>) {
+        out.push(self);
+        for child in &self.children {
+            child.walk(out);
+        }
+    }
+}
+
+impl QueryPlan {
+    // 深度优先遍历所有节点
+    pub fn all(&self) -> Vec<&PlanNode> {
+        let mut out = Vec::new();
+        for node in &self.nodes {
+            node.walk(&mut out);
+        }
+        out
+    }
+
+    // 没有使用索引的全表扫描，返回表名
+    pub fn full_scans(&self) -> Vec<&str> {
+        self.all()
+            .into_iter()
+            .filter(|n| !n.detail.contains(" USING "))
+            .filter_map(|n| n.detail.strip_prefix("SCAN "))
+            .map(|rest| rest.split_whitespace().next().unwrap_or(rest))
+            .collect()
+    }
+
+    // 为 ORDER BY / GROUP BY / DISTINCT 建立的临时 B 树
+    pub fn uses_temp_btree(&self) -> bool {
+        self.all().iter().any(|n| n.detail.contains("TEMP B-TREE"))
+    }
+}
+
+impl fmt::Display for QueryPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write(f: &mut fmt::Formatter<'_>, node: &PlanNode, depth: usize) -> fmt::Result {
+            writeln!(f, "{}{}", "  ".repeat(depth), node.detail)?;
+            node.children
+                .iter()
+                .try_for_each(|c| write(f, c, depth + 1))
+        }
+        writeln!(f, "QUERY PLAN")?;
+        self.nodes.iter().try_for_each(|n| write(f, n, 1))
+    }
+}
+
+// EXPLAIN QUERY PLAN 每行是 (id, parent, notused, detail)，parent 为 0 表示顶层
+fn build(rows: &[(i64, i64, String)], parent: i64) -> Vec<PlanNode> {
+    rows.iter()
+        .filter(|(_, p, _)| *p == parent)
+        .map(|(id, _, detail)| PlanNode {
+            id: *id,
+            detail: detail.clone(),
+            children: build(rows, *id),
+        })
+        .collect()
+}
+
+// query 不会被执行，带参数的查询不需要绑定参数。
+// sqlx 会依次执行字符串中的每条语句，所以只接受单条语句，否则后面的语句会真的执行
+pub async fn explain(pool: &SqlitePool, query: &str) -> Result<QueryPlan, DbError> {
+    if !is_single_statement(query) {
+        return Err(DbError::Rejected("只能分析单条语句".to_string()));
+    }
+    let rows = sqlx::query(&format!("EXPLAIN QUERY PLAN {}", query))
+        .fetch_all(pool)
+        .await?
+        .iter()
+        .map(|row| (row.get(0), row.get(1), row.get(3)))
+        .collect::<Vec<_>>();
+    Ok(QueryPlan {
+        query: query.to_string(),
+        nodes: build(&rows, 0),
+    })
+}
+
+// 除末尾的一个分号外，引号和标识符引用之外不能出现分号；注释中的分号也按语句分隔处理
+fn is_single_statement(query: &str) -> bool {
+    let query = query.trim_end();
+    let query = query.strip_suffix(';').unwrap_or(query);
+    let mut quote = None;
+    for c in query.chars() {
+        match (quote, c) {
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, '[') => quote = Some(']'),
+            (None, ';') => return false,
+            (Some(q), c) if c == q => quote = None,
+            _ => {}
+        }
+    }
+    true
+}
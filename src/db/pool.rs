@@ -0,0 +1,199 @@
+use std::ops::{Deref, DerefMut};
+use std::time::{Duration, Instant};
+
+use futures_util::future::try_join_all;
+use sqlx::pool::PoolConnection;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Sqlite, SqliteConnection, SqlitePool};
+
+use super::sqlite::Profile;
+use super::DbError;
+use crate::context;
+use crate::semaphore::{AcquireError, Permit, Priority, PrioritySemaphore};
+
+// 连接池包装: 启动时预先建立连接，避免第一批请求承担建连开销；
+// 开启 pre-ping 后连接在交给调用方前先检查一次，失效的连接会被关闭并透明地重新建立，
+// 而不是让第一条查询报错。
+// 连接不够用时，通过 acquire_with 取连接的调用方按优先级排队，关键操作先拿到连接。
+
+pub struct PoolBuilder {
+    url: String,
+    max_connections: u32,
+    pre_ping: bool,
+    acquire_timeout: Duration,
+    profile: Option<Profile>,
+}
+
+impl PoolBuilder {
+    pub fn max_connections(mut self, max: u32) -> Self {
+        self.max_connections = max.max(1);
+        self
+    }
+
+    // 每次取连接多一次往返，连接可能被服务端或网络设备断开时开启
+    pub fn pre_ping(mut self, enabled: bool) -> Self {
+        self.pre_ping = enabled;
+        self
+    }
+
+    pub fn acquire_timeout(mut self, timeout: Duration) -> Self {
+        self.acquire_timeout = timeout;
+        self
+    }
+
+    // 每个连接都应用 SQLite 调优配置
+    pub fn profile(mut self, profile: Profile) -> Self {
+        self.profile = Some(profile);
+        self
+    }
+
+    pub async fn connect(self) -> Result<Pool, DbError> {
+        let mut options: SqliteConnectOptions = self
+            .url
+            .parse()
+            .map_err(|e: sqlx::Error| DbError::Connection(e.to_string()))?;
+        if let Some(profile) = self.profile {
+            options = profile.options(options);
+        }
+        // 内存数据库每个连接互相独立，只能使用一个连接
+        let max = if self.url.contains(":memory:") {
+            1
+        } else {
+            self.max_connections
+        };
+        let inner = SqlitePoolOptions::new()
+            .max_connections(max)
+            .test_before_acquire(self.pre_ping)
+            .acquire_timeout(self.acquire_timeout)
+            .connect_with(options.create_if_missing(true))
+            .await
+            .map_err(|e| DbError::Connection(e.to_string()))?;
+        Ok(Pool {
+            inner,
+            gate: PrioritySemaphore::new(max as usize),
+            acquire_timeout: self.acquire_timeout,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Pool {
+    inner: SqlitePool,
+    // 许可数等于最大连接数，克隆出来的 Pool 共享
+    gate: PrioritySemaphore,
+    acquire_timeout: Duration,
+}
+
+impl Pool {
+    pub fn builder(url: &str) -> PoolBuilder {
+        PoolBuilder {
+            url: url.to_string(),
+            max_connections: 10,
+            pre_ping: false,
+            acquire_timeout: Duration::from_secs(30),
+            profile: None,
+        }
+    }
+
+    pub async fn connect(url: &str) -> Result<Pool, DbError> {
+        Self::builder(url).connect().await
+    }
+
+    pub fn inner(&self) -> &SqlitePool {
+        &self.inner
+    }
+
+    // 同时占用 n 个连接(不超过上限)再全部释放，返回连接池中已建立的连接数
+    pub async fn warm_up(&self, n: u32) -> Result<u32, DbError> {
+        let n = n.min(self.inner.options().get_max_connections());
+        let connections = try_join_all((0..n).map(|_| self.inner.acquire()))
+            .await
+            .map_err(|e| DbError::Connection(e.to_string()))?;
+        drop(connections);
+        Ok(self.inner.size())
+    }
+
+    // 按优先级排队取连接。等待时间不超过请求上下文的 deadline，没有上下文时不超过 acquire_timeout，
+    // 超时返回 DbError::Timeout。直接通过 inner 取连接的调用方不参与排队
+    pub async fn acquire_with(&self, priority: Priority) -> Result<PriorityConnection, DbError> {
+        let start = Instant::now();
+        let deadline = context::current()
+            .and_then(|ctx| ctx.deadline)
+            .unwrap_or(start + self.acquire_timeout);
+        let permit = self.gate.acquire_until(priority, Some(deadline)).await?;
+        let conn = self.inner.acquire().await.map_err(|e| match e {
+            sqlx::Error::PoolTimedOut => DbError::Timeout {
+                elapsed: start.elapsed(),
+            },
+            e => DbError::Connection(e.to_string()),
+        })?;
+        Ok(PriorityConnection {
+            conn,
+            _permit: permit,
+        })
+    }
+
+    // 使用请求上下文中的优先级，没有上下文时为 Normal
+    pub async fn acquire_prioritized(&self) -> Result<PriorityConnection, DbError> {
+        let priority = context::current()
+            .map(|ctx| ctx.priority)
+            .unwrap_or_default();
+        self.acquire_with(priority).await
+    }
+
+    pub fn gate(&self) -> &PrioritySemaphore {
+        &self.gate
+    }
+
+    pub fn size(&self) -> u32 {
+        self.inner.size()
+    }
+
+    pub fn idle(&self) -> usize {
+        self.inner.num_idle()
+    }
+}
+
+// 归还连接时同时释放排队许可。查询时传 &mut *conn
+pub struct PriorityConnection {
+    conn: PoolConnection<Sqlite>,
+    _permit: Permit,
+}
+
+impl Deref for PriorityConnection {
+    type Target = SqliteConnection;
+
+    fn deref(&self) -> &SqliteConnection {
+        &self.conn
+    }
+}
+
+impl DerefMut for PriorityConnection {
+    fn deref_mut(&mut self) -> &mut SqliteConnection {
+        &mut self.conn
+    }
+}
+
+impl From<AcquireError> for DbError {
+    fn from(e: AcquireError) -> Self {
+        match e {
+            AcquireError::Timeout { waited, .. } => DbError::Timeout { elapsed: waited },
+        }
+    }
+}
+
+// 可以直接在 Pool 上执行查询: query.execute(&*pool)
+impl Deref for Pool {
+    type Target = SqlitePool;
+
+    fn deref(&self) -> &SqlitePool {
+        &self.inner
+    }
+}
+
+// 现有的仓库类型接收 SqlitePool
+impl From<Pool> for SqlitePool {
+    fn from(pool: Pool) -> SqlitePool {
+        pool.inner
+    }
+}
@@ -0,0 +1,124 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+
+use super::pool::Pool;
+use super::DbError;
+use crate::logs;
+
+// 读写分离: 写操作和需要读到最新数据的查询走主库，只读查询轮询健康的只读副本，
+// 没有健康的副本时回落到主库。副本的健康状态由 check_health 定期更新。
+
+struct Replica {
+    url: String,
+    pool: SqlitePool,
+    healthy: AtomicBool,
+}
+
+struct Inner {
+    primary: SqlitePool,
+    replicas: Vec<Replica>,
+    next: AtomicUsize,
+}
+
+#[derive(Clone)]
+pub struct Router {
+    inner: Arc<Inner>,
+}
+
+impl Router {
+    // 副本初始视为健康
+    pub fn new(primary: SqlitePool, replicas: Vec<(String, SqlitePool)>) -> Self {
+        let replicas = replicas
+            .into_iter()
+            .map(|(url, pool)| Replica {
+                url,
+                pool,
+                healthy: AtomicBool::new(true),
+            })
+            .collect();
+        Router {
+            inner: Arc::new(Inner {
+                primary,
+                replicas,
+                next: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    pub async fn connect(primary: &str, replicas: &[&str]) -> Result<Self, DbError> {
+        let primary = Pool::connect(primary).await?.into();
+        let mut pools = Vec::new();
+        for url in replicas {
+            pools.push((url.to_string(), Pool::connect(url).await?.into()));
+        }
+        Ok(Self::new(primary, pools))
+    }
+
+    pub fn primary(&self) -> &SqlitePool {
+        &self.inner.primary
+    }
+
+    // 只读查询使用的连接池
+    pub fn read(&self) -> &SqlitePool {
+        let replicas = &self.inner.replicas;
+        let start = self.inner.next.fetch_add(1, Ordering::Relaxed);
+        (0..replicas.len())
+            .map(|i| &replicas[(start + i) % replicas.len()])
+            .find(|r| r.healthy.load(Ordering::Relaxed))
+            .map(|r| &r.pool)
+            .unwrap_or(&self.inner.primary)
+    }
+
+    // 所有查询都走主库的路由，用于写后立即读
+    pub fn primary_only(&self) -> Self {
+        Self::new(self.inner.primary.clone(), Vec::new())
+    }
+
+    pub fn healthy_replicas(&self) -> Vec<&str> {
+        self.inner
+            .replicas
+            .iter()
+            .filter(|r| r.healthy.load(Ordering::Relaxed))
+            .map(|r| r.url.as_str())
+            .collect()
+    }
+
+    // 对每个副本执行 SELECT 1，返回健康的副本数
+    pub async fn check_health(&self, timeout: Duration) -> usize {
+        let mut healthy = 0;
+        for replica in &self.inner.replicas {
+            let ping = sqlx::query("SELECT 1").execute(&replica.pool);
+            let ok = matches!(tokio::time::timeout(timeout, ping).await, Ok(Ok(_)));
+            if replica.healthy.swap(ok, Ordering::Relaxed) != ok {
+                let state = if ok { "恢复" } else { "不可用" };
+                logs::warn(
+                    "db::routing",
+                    &format!("只读副本 {} {}", replica.url, state),
+                );
+            }
+            healthy += ok as usize;
+        }
+        healthy
+    }
+
+    // 后台定期检查，返回的任务在应用关闭时 abort
+    pub fn spawn_health_checks(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let router = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                router.check_health(interval).await;
+            }
+        })
+    }
+}
+
+impl From<SqlitePool> for Router {
+    fn from(primary: SqlitePool) -> Self {
+        Self::new(primary, Vec::new())
+    }
+}
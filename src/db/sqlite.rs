@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+
+use super::DbError;
+
+// SQLite 调优配置。Server 适合多线程、多任务并发读写(WAL + NORMAL 同步 + 较大的缓存)，
+// Embedded 适合单进程、内存有限、更看重掉电安全的场景。
+// journal_mode 写在数据库文件里，其他 PRAGMA 只对当前连接有效，所以连接池应该通过
+// Profile::options 让每个新连接都应用同样的设置；内存数据库不支持 WAL 和 mmap，校验时会报告不一致。
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Server,
+    Embedded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Settings {
+    pub journal_mode: &'static str,
+    pub synchronous: &'static str,
+    pub busy_timeout: Duration,
+    // 负数表示 KiB，正数表示页数
+    pub cache_size: i64,
+    pub mmap_size: u64,
+}
+
+impl Profile {
+    pub fn settings(self) -> Settings {
+        match self {
+            Profile::Server => Settings {
+                journal_mode: "WAL",
+                synchronous: "NORMAL",
+                busy_timeout: Duration::from_secs(5),
+                cache_size: -64 * 1024,
+                mmap_size: 256 * 1024 * 1024,
+            },
+            Profile::Embedded => Settings {
+                journal_mode: "WAL",
+                synchronous: "FULL",
+                busy_timeout: Duration::from_secs(1),
+                cache_size: -2 * 1024,
+                mmap_size: 0,
+            },
+        }
+    }
+
+    // 每个新建的连接都会执行这些 PRAGMA
+    pub fn options(self, options: SqliteConnectOptions) -> SqliteConnectOptions {
+        let settings = self.settings();
+        settings
+            .pragmas()
+            .into_iter()
+            .filter(|(name, _)| *name != "busy_timeout")
+            .fold(options, |options, (name, value)| {
+                options.pragma(name, value)
+            })
+            .busy_timeout(settings.busy_timeout)
+    }
+}
+
+impl Settings {
+    fn pragmas(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("journal_mode", self.journal_mode.to_string()),
+            ("synchronous", self.synchronous.to_string()),
+            ("busy_timeout", self.busy_timeout.as_millis().to_string()),
+            ("cache_size", self.cache_size.to_string()),
+            ("mmap_size", self.mmap_size.to_string()),
+        ]
+    }
+
+    // PRAGMA 查询返回的值，synchronous 返回的是数字
+    fn expected(name: &str, value: &str) -> String {
+        match (name, value) {
+            ("synchronous", "OFF") => "0".to_string(),
+            ("synchronous", "NORMAL") => "1".to_string(),
+            ("synchronous", "FULL") => "2".to_string(),
+            ("synchronous", "EXTRA") => "3".to_string(),
+            _ => value.to_ascii_lowercase(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    pub pragma: &'static str,
+    pub expected: String,
+    pub actual: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileReport {
+    pub mismatches: Vec<Mismatch>,
+}
+
+impl ProfileReport {
+    pub fn is_ok(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+async fn pragma_value(conn: &mut SqliteConnection, name: &str) -> Result<Option<String>, DbError> {
+    let row = sqlx::query(&format!("PRAGMA {}", name))
+        .fetch_optional(&mut *conn)
+        .await?;
+    Ok(row.map(|row| match row.try_get::<i64, _>(0) {
+        Ok(n) => n.to_string(),
+        Err(_) => row.get::<String, _>(0).to_ascii_lowercase(),
+    }))
+}
+
+// 在一个连接上应用配置并逐项查询校验
+pub async fn apply_profile(
+    conn: &mut SqliteConnection,
+    profile: Profile,
+) -> Result<ProfileReport, DbError> {
+    let settings = profile.settings();
+    for (name, value) in settings.pragmas() {
+        sqlx::query(&format!("PRAGMA {} = {}", name, value))
+            .execute(&mut *conn)
+            .await?;
+    }
+    verify(conn, profile).await
+}
+
+pub async fn verify(
+    conn: &mut SqliteConnection,
+    profile: Profile,
+) -> Result<ProfileReport, DbError> {
+    let mut report = ProfileReport::default();
+    for (name, value) in profile.settings().pragmas() {
+        let expected = Settings::expected(name, &value);
+        let actual = pragma_value(conn, name).await?;
+        if actual.as_deref() != Some(expected.as_str()) {
+            report.mismatches.push(Mismatch {
+                pragma: name,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(report)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckpointMode {
+    Passive,
+    Full,
+    Restart,
+    Truncate,
+}
+
+impl CheckpointMode {
+    fn as_str(self) -> &'static str {
+        match self {
+            CheckpointMode::Passive => "PASSIVE",
+            CheckpointMode::Full => "FULL",
+            CheckpointMode::Restart => "RESTART",
+            CheckpointMode::Truncate => "TRUNCATE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    // 有读写事务占用，没有完成全部检查点
+    pub busy: bool,
+    // WAL 中的帧数，非 WAL 模式为 -1
+    pub log_frames: i64,
+    pub checkpointed_frames: i64,
+}
+
+// 把 WAL 写回数据库文件；Truncate 同时清空 WAL 文件
+pub async fn checkpoint(pool: &SqlitePool, mode: CheckpointMode) -> Result<Checkpoint, DbError> {
+    let row = sqlx::query(&format!("PRAGMA wal_checkpoint({})", mode.as_str()))
+        .fetch_one(pool)
+        .await?;
+    Ok(Checkpoint {
+        busy: row.get::<i64, _>(0) != 0,
+        log_frames: row.get(1),
+        checkpointed_frames: row.get(2),
+    })
+}
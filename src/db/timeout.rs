@@ -0,0 +1,77 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+
+use sqlx::pool::PoolConnection;
+use sqlx::{Sqlite, SqliteConnection, SqlitePool};
+
+use super::DbError;
+use crate::context::{self, costs};
+
+// 带超时的查询: 超时时间默认取自当前请求上下文的 deadline，超时返回 DbError::Timeout。
+// 查询被取消(超时或外层 future 被 drop)时连接可能还在执行语句，这时不把它还给连接池，
+// 而是从池中分离后关闭，由连接池重新建立连接。
+// 注意内存数据库的每个连接互相独立，分离后新建的连接看不到原来的数据。
+
+pub type QueryFuture<'c, T> = Pin<Box<dyn Future<Output = Result<T, sqlx::Error>> + Send + 'c>>;
+
+// 查询正常结束前被 drop 时分离连接
+struct Guard {
+    conn: Option<PoolConnection<Sqlite>>,
+}
+
+impl Guard {
+    fn conn(&mut self) -> &mut SqliteConnection {
+        self.conn.as_mut().expect("查询结束前连接不会被取走")
+    }
+
+    fn finish(mut self) {
+        self.conn.take();
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            drop(conn.detach());
+        }
+    }
+}
+
+// timeout 为 None 时不限时，包括等待连接的时间
+pub async fn run<T, F>(pool: &SqlitePool, timeout: Option<Duration>, query: F) -> Result<T, DbError>
+where
+    F: for<'c> FnOnce(&'c mut SqliteConnection) -> QueryFuture<'c, T>,
+{
+    let start = Instant::now();
+    let work = async {
+        let mut guard = Guard {
+            conn: Some(pool.acquire().await?),
+        };
+        let result = query(guard.conn()).await;
+        guard.finish();
+        result
+    };
+    let result = match timeout {
+        None => Ok(work.await),
+        Some(timeout) => tokio::time::timeout(timeout, work).await,
+    };
+    // 超时的查询同样占用了时间，也计入请求的数据库耗时
+    let elapsed = start.elapsed();
+    costs::record_db(elapsed);
+    Ok(result.map_err(|_| DbError::Timeout { elapsed })??)
+}
+
+// 使用当前请求上下文剩余的时间，已经超时的请求不会执行查询
+pub async fn run_in_context<T, F>(pool: &SqlitePool, query: F) -> Result<T, DbError>
+where
+    F: for<'c> FnOnce(&'c mut SqliteConnection) -> QueryFuture<'c, T>,
+{
+    let remaining = context::current().and_then(|ctx| ctx.remaining());
+    if remaining == Some(Duration::ZERO) {
+        return Err(DbError::Timeout {
+            elapsed: Duration::ZERO,
+        });
+    }
+    run(pool, remaining, query).await
+}
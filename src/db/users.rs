@@ -0,0 +1,310 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use super::batch::{self, BatchResult};
+use super::hooks::{Entity, Hooks};
+use super::routing::Router;
+use super::DbError;
+use crate::page::{Page, PageRequest};
+use crate::search::{SearchDoc, Searchable};
+use crate::tenant::{self, TenantId};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct User {
+    pub id: i64,
+    pub name: String,
+    pub email: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct NewUser {
+    pub name: String,
+    pub email: String,
+}
+
+// 为 None 的字段保持不变
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct UserUpdate {
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+impl Entity for User {
+    type New = NewUser;
+    type Update = UserUpdate;
+}
+
+impl Searchable for User {
+    const KIND: &'static str = "user";
+
+    fn to_doc(&self) -> SearchDoc {
+        SearchDoc {
+            kind: Self::KIND,
+            id: self.id,
+            title: self.name.clone(),
+            body: self.email.clone(),
+        }
+    }
+}
+
+// 写操作走主库，查询按 Router 路由到只读副本。
+// tenant_scoped 之后所有读写都限定在当前上下文的租户内
+#[derive(Clone)]
+pub struct UserRepository {
+    db: Router,
+    hooks: Arc<Hooks<User>>,
+    scoped: bool,
+}
+
+impl UserRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self::with_router(pool.into())
+    }
+
+    pub fn with_router(db: Router) -> Self {
+        UserRepository {
+            db,
+            hooks: Arc::default(),
+            scoped: false,
+        }
+    }
+
+    // 共享表按 tenant_id 列隔离，同一个邮箱可以在不同租户下各注册一次；
+    // 上下文中没有租户时所有操作都返回 Rejected
+    pub fn tenant_scoped(mut self) -> Self {
+        self.scoped = true;
+        self
+    }
+
+    // 所有查询都读主库，用于写后立即读的场景
+    pub fn primary(&self) -> Self {
+        UserRepository {
+            db: self.db.primary_only(),
+            hooks: self.hooks.clone(),
+            scoped: self.scoped,
+        }
+    }
+
+    // 在 clone 出去之前注册，已经 clone 的仓库不受影响
+    pub fn on_before_insert<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&NewUser) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.hooks).on_before_insert(f);
+        self
+    }
+
+    pub fn on_after_insert<F: Fn(&User) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        Arc::make_mut(&mut self.hooks).on_after_insert(f);
+        self
+    }
+
+    pub fn on_before_update<F>(mut self, f: F) -> Self
+    where
+        F: Fn(i64, &UserUpdate) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.hooks).on_before_update(f);
+        self
+    }
+
+    pub fn on_after_update<F: Fn(&User) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        Arc::make_mut(&mut self.hooks).on_after_update(f);
+        self
+    }
+
+    pub fn on_before_delete<F>(mut self, f: F) -> Self
+    where
+        F: Fn(i64) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Arc::make_mut(&mut self.hooks).on_before_delete(f);
+        self
+    }
+
+    pub fn on_after_delete<F: Fn(i64) + Send + Sync + 'static>(mut self, f: F) -> Self {
+        Arc::make_mut(&mut self.hooks).on_after_delete(f);
+        self
+    }
+
+    // 一次注册多个钩子，例如 repo.hooks(|h| search.register(h))
+    pub fn hooks(mut self, f: impl FnOnce(&mut Hooks<User>)) -> Self {
+        f(Arc::make_mut(&mut self.hooks));
+        self
+    }
+
+    fn tenant(&self) -> Result<Option<TenantId>, DbError> {
+        if !self.scoped {
+            return Ok(None);
+        }
+        tenant::require()
+            .map(Some)
+            .map_err(|e| DbError::Rejected(e.to_string()))
+    }
+
+    // 查询使用的表: 按租户隔离时是只包含当前租户数据的子查询。
+    // TenantId 只含字母、数字、'-' 和 '_'，可以直接写进 SQL
+    fn table(&self) -> Result<String, DbError> {
+        Ok(match self.tenant()? {
+            Some(tenant) => format!(
+                "(SELECT * FROM users WHERE tenant_id = '{}') AS users",
+                tenant
+            ),
+            None => "users".to_string(),
+        })
+    }
+
+    // 追加在 UPDATE/DELETE 的 WHERE 之后
+    fn tenant_filter(&self) -> Result<String, DbError> {
+        Ok(match self.tenant()? {
+            Some(tenant) => format!(" AND tenant_id = '{}'", tenant),
+            None => String::new(),
+        })
+    }
+
+    // 邮箱唯一且不区分大小写，按租户隔离时在租户内唯一
+    pub async fn migrate(&self) -> Result<(), DbError> {
+        let sql = if self.scoped {
+            "CREATE TABLE IF NOT EXISTS users (
+                id INTEGER PRIMARY KEY,
+                tenant_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                email TEXT NOT NULL COLLATE NOCASE,
+                UNIQUE (tenant_id, email)
+            )"
+        } else {
+            "CREATE TABLE IF NOT EXISTS users (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL,
+                email TEXT NOT NULL UNIQUE COLLATE NOCASE
+            )"
+        };
+        sqlx::query(sql).execute(self.db.primary()).await?;
+        Ok(())
+    }
+
+    pub async fn create(&self, user: &NewUser) -> Result<User, DbError> {
+        let tenant = self.tenant()?;
+        self.hooks.before_insert(user)?;
+        let query = match &tenant {
+            Some(tenant) => {
+                sqlx::query("INSERT INTO users (tenant_id, name, email) VALUES (?, ?, ?)")
+                    .bind(tenant.as_str())
+            }
+            None => sqlx::query("INSERT INTO users (name, email) VALUES (?, ?)"),
+        };
+        let id = query
+            .bind(&user.name)
+            .bind(&user.email)
+            .execute(self.db.primary())
+            .await?
+            .last_insert_rowid();
+        let created = self.fetch(self.db.primary(), id).await?;
+        self.hooks.after_insert(&created);
+        Ok(created)
+    }
+
+    pub async fn get(&self, id: i64) -> Result<User, DbError> {
+        self.fetch(self.db.read(), id).await
+    }
+
+    pub async fn find_many(&self, ids: &[i64]) -> Result<BatchResult<User>, DbError> {
+        batch::find_many(
+            self.db.read(),
+            &format!("SELECT id, name, email FROM {}", self.table()?),
+            ids,
+            |u: &User| u.id,
+        )
+        .await
+    }
+
+    pub async fn find_by_email(&self, email: &str) -> Result<Option<User>, DbError> {
+        Ok(sqlx::query_as(&format!(
+            "SELECT id, name, email FROM {} WHERE email = ?",
+            self.table()?
+        ))
+        .bind(email)
+        .fetch_optional(self.db.read())
+        .await?)
+    }
+
+    pub async fn update(&self, id: i64, update: &UserUpdate) -> Result<User, DbError> {
+        let filter = self.tenant_filter()?;
+        self.hooks.before_update(id, update)?;
+        let result = sqlx::query(&format!(
+            "UPDATE users SET name = COALESCE(?, name), email = COALESCE(?, email) WHERE id = ?{}",
+            filter
+        ))
+        .bind(&update.name)
+        .bind(&update.email)
+        .bind(id)
+        .execute(self.db.primary())
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound(id));
+        }
+        let updated = self.fetch(self.db.primary(), id).await?;
+        self.hooks.after_update(&updated);
+        Ok(updated)
+    }
+
+    pub async fn delete(&self, id: i64) -> Result<(), DbError> {
+        let filter = self.tenant_filter()?;
+        self.hooks.before_delete(id)?;
+        let result = sqlx::query(&format!("DELETE FROM users WHERE id = ?{}", filter))
+            .bind(id)
+            .execute(self.db.primary())
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(DbError::NotFound(id));
+        }
+        self.hooks.after_delete(id);
+        Ok(())
+    }
+
+    async fn fetch(&self, pool: &SqlitePool, id: i64) -> Result<User, DbError> {
+        sqlx::query_as(&format!(
+            "SELECT id, name, email FROM {} WHERE id = ?",
+            self.table()?
+        ))
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(DbError::NotFound(id))
+    }
+
+    pub async fn list(&self, page: PageRequest) -> Result<Page<User>, DbError> {
+        self.search("", page).await
+    }
+
+    // 按名称或邮箱模糊搜索，不区分大小写，按 id 排序
+    pub async fn search(&self, query: &str, page: PageRequest) -> Result<Page<User>, DbError> {
+        let pattern = format!("%{}%", escape_like(&query.to_lowercase()));
+        // 总数和当前页使用同一个连接池，避免两次查询落到不同的副本
+        let pool = self.db.read();
+        const FILTER: &str =
+            "WHERE lower(name) LIKE ?1 ESCAPE '\\' OR lower(email) LIKE ?1 ESCAPE '\\'";
+        let table = self.table()?;
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {} {}", table, FILTER))
+            .bind(&pattern)
+            .fetch_one(pool)
+            .await?;
+        let items = sqlx::query_as(&format!(
+            "SELECT id, name, email FROM {} {} ORDER BY id LIMIT ?2 OFFSET ?3",
+            table, FILTER
+        ))
+        .bind(&pattern)
+        .bind(page.limit() as i64)
+        .bind(page.offset() as i64)
+        .fetch_all(pool)
+        .await?;
+        Ok(Page::new(items, total as u64, page))
+    }
+}
+
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
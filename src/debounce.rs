@@ -0,0 +1,249 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::runtime::Handle;
+
+use crate::clock::{self, SharedClock};
+
+// 防抖和节流，用于事件订阅者和文件监听回调: 短时间内的一串事件(连续保存配置文件)
+// 只触发一次处理。
+//   debounce: 事件停止 duration 之后才处理，只处理最后一个(trailing)；
+//             leading 时一串事件的第一个立即处理
+//   throttle: 每个 interval 最多处理一次；leading 时窗口开始的事件立即处理，
+//             trailing 时窗口内最后一个被压下的事件在窗口结束时处理
+// 等待在创建时所在的 tokio 运行时中进行，回调可以从任意线程调用。
+
+type Handler<T> = Arc<dyn Fn(T) + Send + Sync>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Edges {
+    leading: bool,
+    trailing: bool,
+}
+
+struct DebounceState<T> {
+    pending: Option<T>,
+    // 每次调用加一，计时结束时没有新调用才算一串事件结束
+    generation: u64,
+    in_burst: bool,
+}
+
+pub struct Debouncer<T> {
+    duration: Duration,
+    edges: Edges,
+    clock: SharedClock,
+    runtime: Handle,
+    handler: Handler<T>,
+    state: Arc<Mutex<DebounceState<T>>>,
+}
+
+impl<T> Clone for Debouncer<T> {
+    fn clone(&self) -> Self {
+        Debouncer {
+            duration: self.duration,
+            edges: self.edges,
+            clock: self.clock.clone(),
+            runtime: self.runtime.clone(),
+            handler: self.handler.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+// 默认只有 trailing；需要在 tokio 运行时中创建
+pub fn debounce<T, F>(duration: Duration, handler: F) -> Debouncer<T>
+where
+    T: Send + 'static,
+    F: Fn(T) + Send + Sync + 'static,
+{
+    Debouncer {
+        duration,
+        edges: Edges {
+            leading: false,
+            trailing: true,
+        },
+        clock: clock::system(),
+        runtime: Handle::current(),
+        handler: Arc::new(handler),
+        state: Arc::new(Mutex::new(DebounceState {
+            pending: None,
+            generation: 0,
+            in_burst: false,
+        })),
+    }
+}
+
+impl<T: Send + 'static> Debouncer<T> {
+    pub fn leading(mut self, leading: bool) -> Self {
+        self.edges.leading = leading;
+        self
+    }
+
+    pub fn trailing(mut self, trailing: bool) -> Self {
+        self.edges.trailing = trailing;
+        self
+    }
+
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn call(&self, event: T) {
+        let (generation, fire_now) = {
+            let mut state = self.state.lock().unwrap();
+            state.generation += 1;
+            let fire_now = if !state.in_burst && self.edges.leading {
+                state.pending = None;
+                Some(event)
+            } else {
+                if self.edges.trailing {
+                    state.pending = Some(event);
+                }
+                None
+            };
+            state.in_burst = true;
+            (state.generation, fire_now)
+        };
+        if let Some(event) = fire_now {
+            (self.handler)(event);
+        }
+
+        let this = self.clone();
+        self.runtime.spawn(async move {
+            this.clock.sleep(this.duration).await;
+            let pending = {
+                let mut state = this.state.lock().unwrap();
+                if state.generation != generation {
+                    return;
+                }
+                state.in_burst = false;
+                state.pending.take()
+            };
+            if let Some(event) = pending {
+                (this.handler)(event);
+            }
+        });
+    }
+
+    // 当作普通回调传给订阅接口
+    pub fn handler(&self) -> impl Fn(T) + Send + Sync + Clone + 'static {
+        let this = self.clone();
+        move |event| this.call(event)
+    }
+}
+
+struct ThrottleState<T> {
+    // 处于一个节流窗口中
+    in_window: bool,
+    pending: Option<T>,
+}
+
+pub struct Throttler<T> {
+    interval: Duration,
+    edges: Edges,
+    clock: SharedClock,
+    runtime: Handle,
+    handler: Handler<T>,
+    state: Arc<Mutex<ThrottleState<T>>>,
+}
+
+impl<T> Clone for Throttler<T> {
+    fn clone(&self) -> Self {
+        Throttler {
+            interval: self.interval,
+            edges: self.edges,
+            clock: self.clock.clone(),
+            runtime: self.runtime.clone(),
+            handler: self.handler.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+// 默认 leading 和 trailing 都开启；需要在 tokio 运行时中创建
+pub fn throttle<T, F>(interval: Duration, handler: F) -> Throttler<T>
+where
+    T: Send + 'static,
+    F: Fn(T) + Send + Sync + 'static,
+{
+    Throttler {
+        interval,
+        edges: Edges {
+            leading: true,
+            trailing: true,
+        },
+        clock: clock::system(),
+        runtime: Handle::current(),
+        handler: Arc::new(handler),
+        state: Arc::new(Mutex::new(ThrottleState {
+            in_window: false,
+            pending: None,
+        })),
+    }
+}
+
+impl<T: Send + 'static> Throttler<T> {
+    pub fn leading(mut self, leading: bool) -> Self {
+        self.edges.leading = leading;
+        self
+    }
+
+    pub fn trailing(mut self, trailing: bool) -> Self {
+        self.edges.trailing = trailing;
+        self
+    }
+
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn call(&self, event: T) {
+        let fire_now = {
+            let mut state = self.state.lock().unwrap();
+            if state.in_window {
+                if self.edges.trailing {
+                    state.pending = Some(event);
+                }
+                return;
+            }
+            state.in_window = true;
+            if self.edges.leading {
+                Some(event)
+            } else {
+                if self.edges.trailing {
+                    state.pending = Some(event);
+                }
+                None
+            }
+        };
+        if let Some(event) = fire_now {
+            (self.handler)(event);
+        }
+
+        let this = self.clone();
+        self.runtime.spawn(async move {
+            loop {
+                this.clock.sleep(this.interval).await;
+                let pending = {
+                    let mut state = this.state.lock().unwrap();
+                    match state.pending.take() {
+                        // 窗口结束时处理的事件开始一个新窗口
+                        Some(event) => event,
+                        None => {
+                            state.in_window = false;
+                            return;
+                        }
+                    }
+                };
+                (this.handler)(pending);
+            }
+        });
+    }
+
+    pub fn handler(&self) -> impl Fn(T) + Send + Sync + Clone + 'static {
+        let this = self.clone();
+        move |event| this.call(event)
+    }
+}
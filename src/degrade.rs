@@ -0,0 +1,399 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::clock::{self, SharedClock};
+use crate::logs;
+
+// 依赖降级: 每个外部依赖(数据库、上游 HTTP 服务、缓存持久化)有一个断路器，
+// 连续失败达到阈值或健康检查报告不可用时进入降级状态，调用直接按声明的策略处理:
+// 返回上次成功的旧值、返回默认值，或者返回 DegradeError::Unavailable，不再访问依赖。
+// 断路器打开 cooldown 之后放行一次试探调用，成功则恢复。
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DegradeError<E> {
+    #[error("依赖 {dependency} 不可用: {reason}")]
+    Unavailable { dependency: String, reason: String },
+    // 依赖可用时调用本身的错误
+    #[error(transparent)]
+    Failed(E),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum Health {
+    Healthy,
+    // 连续失败，断路器打开
+    Open { failures: u32 },
+    // 健康检查报告不可用
+    Unhealthy { reason: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DependencyStatus {
+    pub name: String,
+    pub health: Health,
+    pub degraded_calls: u64,
+}
+
+struct State {
+    failures: u32,
+    opened_at: Option<Instant>,
+    // 半开状态下只放行一个试探调用
+    probing: bool,
+    unhealthy: Option<String>,
+    degraded_calls: u64,
+}
+
+struct Inner {
+    name: String,
+    failure_threshold: u32,
+    cooldown: Duration,
+    clock: SharedClock,
+    state: Mutex<State>,
+}
+
+#[derive(Clone)]
+pub struct Dependency {
+    inner: Arc<Inner>,
+}
+
+impl fmt::Debug for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dependency")
+            .field("status", &self.status())
+            .finish()
+    }
+}
+
+pub struct DependencyBuilder {
+    name: String,
+    failure_threshold: u32,
+    cooldown: Duration,
+    clock: SharedClock,
+}
+
+impl DependencyBuilder {
+    // 连续失败多少次后打开断路器，默认 5
+    pub fn failure_threshold(mut self, n: u32) -> Self {
+        self.failure_threshold = n.max(1);
+        self
+    }
+
+    // 断路器打开多久后放行试探调用，默认 30 秒
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn build(self) -> Dependency {
+        Dependency {
+            inner: Arc::new(Inner {
+                name: self.name,
+                failure_threshold: self.failure_threshold,
+                cooldown: self.cooldown,
+                clock: self.clock,
+                state: Mutex::new(State {
+                    failures: 0,
+                    opened_at: None,
+                    probing: false,
+                    unhealthy: None,
+                    degraded_calls: 0,
+                }),
+            }),
+        }
+    }
+}
+
+impl Dependency {
+    pub fn new(name: &str) -> Self {
+        Self::builder(name).build()
+    }
+
+    pub fn builder(name: &str) -> DependencyBuilder {
+        DependencyBuilder {
+            name: name.to_string(),
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+            clock: clock::system(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    pub fn status(&self) -> DependencyStatus {
+        let state = self.inner.state.lock().unwrap();
+        let health = match (&state.unhealthy, state.opened_at) {
+            (Some(reason), _) => Health::Unhealthy {
+                reason: reason.clone(),
+            },
+            (None, Some(_)) => Health::Open {
+                failures: state.failures,
+            },
+            (None, None) => Health::Healthy,
+        };
+        DependencyStatus {
+            name: self.inner.name.clone(),
+            health,
+            degraded_calls: state.degraded_calls,
+        }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.status().health == Health::Healthy
+    }
+
+    // 健康检查的结果；不可用期间所有调用都走降级策略，恢复后断路器一并复位
+    pub fn report_health(&self, result: Result<(), String>) {
+        let mut state = self.inner.state.lock().unwrap();
+        match result {
+            Ok(()) => {
+                if state.unhealthy.take().is_some() {
+                    logs::info("degrade", &format!("依赖 {} 恢复", self.inner.name));
+                }
+                state.failures = 0;
+                state.opened_at = None;
+                state.probing = false;
+            }
+            Err(reason) => {
+                if state.unhealthy.is_none() {
+                    logs::warn(
+                        "degrade",
+                        &format!(
+                            "依赖 {} 健康检查失败，开始降级: {}",
+                            self.inner.name, reason
+                        ),
+                    );
+                }
+                state.unhealthy = Some(reason);
+            }
+        }
+    }
+
+    // 按间隔执行健康检查，需要在 tokio 运行时中调用
+    pub fn spawn_health_check<F, Fut>(
+        &self,
+        interval: Duration,
+        check: F,
+    ) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), String>> + Send,
+    {
+        let dependency = self.clone();
+        tokio::spawn(async move {
+            loop {
+                dependency.report_health(check().await);
+                dependency.inner.clock.sleep(interval).await;
+            }
+        })
+    }
+
+    // 返回不能访问依赖的原因；断路器打开超过 cooldown 时放行一次试探
+    fn admit(&self) -> Result<(), String> {
+        let mut state = self.inner.state.lock().unwrap();
+        let result = if let Some(reason) = &state.unhealthy {
+            Err(format!("健康检查失败: {}", reason))
+        } else if let Some(opened_at) = state.opened_at {
+            let now = self.inner.clock.now();
+            if !state.probing && now.saturating_duration_since(opened_at) >= self.inner.cooldown {
+                state.probing = true;
+                Ok(())
+            } else {
+                Err(format!("断路器打开，连续失败 {} 次", state.failures))
+            }
+        } else {
+            Ok(())
+        };
+        if result.is_err() {
+            state.degraded_calls += 1;
+        }
+        result
+    }
+
+    fn record(&self, success: bool) {
+        let mut state = self.inner.state.lock().unwrap();
+        if success {
+            if state.opened_at.take().is_some() {
+                logs::info("degrade", &format!("依赖 {} 断路器关闭", self.inner.name));
+            }
+            state.failures = 0;
+            state.probing = false;
+            return;
+        }
+        state.failures += 1;
+        let was_probing = std::mem::take(&mut state.probing);
+        if was_probing || state.failures >= self.inner.failure_threshold {
+            if state.opened_at.is_none() {
+                logs::warn(
+                    "degrade",
+                    &format!(
+                        "依赖 {} 连续失败 {} 次，断路器打开",
+                        self.inner.name, state.failures
+                    ),
+                );
+            }
+            state.opened_at = Some(self.inner.clock.now());
+        }
+    }
+
+    pub fn with_fallback<T>(&self, fallback: Fallback<T>) -> Guarded<T> {
+        Guarded {
+            dependency: self.clone(),
+            fallback,
+            last_good: Mutex::new(None),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Fallback<T> {
+    // 返回最近一次成功的结果；超过 max_age 或者还没有成功过时按 Reject 处理
+    ServeStale { max_age: Option<Duration> },
+    Default(T),
+    Reject,
+}
+
+// 结果来自哪里，调用方可以据此在响应中标记数据可能过期
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Served<T> {
+    Live(T),
+    Stale { value: T, age: Duration },
+    Default(T),
+}
+
+impl<T> Served<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            Served::Live(value) | Served::Stale { value, .. } | Served::Default(value) => value,
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        !matches!(self, Served::Live(_))
+    }
+}
+
+// 某一类调用(同一个依赖上可以有多个)的降级策略
+pub struct Guarded<T> {
+    dependency: Dependency,
+    fallback: Fallback<T>,
+    last_good: Mutex<Option<(T, Instant)>>,
+}
+
+impl<T: Clone> Guarded<T> {
+    pub fn dependency(&self) -> &Dependency {
+        &self.dependency
+    }
+
+    // 依赖可用时执行 f；f 失败且断路器因此打开时同样走降级策略，否则原样返回错误
+    pub async fn call<F, Fut, E>(&self, f: F) -> Result<Served<T>, DegradeError<E>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if let Err(reason) = self.dependency.admit() {
+            return self.fallback(reason);
+        }
+        match f().await {
+            Ok(value) => {
+                self.dependency.record(true);
+                if matches!(self.fallback, Fallback::ServeStale { .. }) {
+                    let now = self.dependency.inner.clock.now();
+                    *self.last_good.lock().unwrap() = Some((value.clone(), now));
+                }
+                Ok(Served::Live(value))
+            }
+            Err(e) => {
+                self.dependency.record(false);
+                if self.dependency.is_available() {
+                    return Err(DegradeError::Failed(e));
+                }
+                self.dependency.inner.state.lock().unwrap().degraded_calls += 1;
+                // 策略为拒绝时，触发降级的这次调用返回原始错误
+                self.fallback::<E>("断路器打开".to_string())
+                    .map_err(|_| DegradeError::Failed(e))
+            }
+        }
+    }
+
+    fn fallback<E>(&self, reason: String) -> Result<Served<T>, DegradeError<E>> {
+        let unavailable = || DegradeError::Unavailable {
+            dependency: self.dependency.inner.name.clone(),
+            reason: reason.clone(),
+        };
+        match &self.fallback {
+            Fallback::Default(value) => Ok(Served::Default(value.clone())),
+            Fallback::Reject => Err(unavailable()),
+            Fallback::ServeStale { max_age } => {
+                let now = self.dependency.inner.clock.now();
+                let last_good = self.last_good.lock().unwrap();
+                match &*last_good {
+                    Some((value, at))
+                        if max_age.is_none_or(|max| now.saturating_duration_since(*at) <= max) =>
+                    {
+                        Ok(Served::Stale {
+                            value: value.clone(),
+                            age: now.saturating_duration_since(*at),
+                        })
+                    }
+                    _ => Err(unavailable()),
+                }
+            }
+        }
+    }
+}
+
+// 按名字登记所有依赖，用于状态页和健康检查接口
+#[derive(Default)]
+pub struct Dependencies {
+    dependencies: Mutex<BTreeMap<String, Dependency>>,
+}
+
+impl Dependencies {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(&self, dependency: Dependency) -> Dependency {
+        self.dependencies
+            .lock()
+            .unwrap()
+            .insert(dependency.name().to_string(), dependency.clone());
+        dependency
+    }
+
+    pub fn get(&self, name: &str) -> Option<Dependency> {
+        self.dependencies.lock().unwrap().get(name).cloned()
+    }
+
+    // 按名字排序
+    pub fn status(&self) -> Vec<DependencyStatus> {
+        self.dependencies
+            .lock()
+            .unwrap()
+            .values()
+            .map(Dependency::status)
+            .collect()
+    }
+
+    pub fn degraded(&self) -> Vec<String> {
+        self.status()
+            .into_iter()
+            .filter(|s| s.health != Health::Healthy)
+            .map(|s| s.name)
+            .collect()
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::chunker::Chunker;
+
+// 二进制增量: 把旧数据和新数据都按内容切块，新数据中在旧数据里出现过的块记为 Copy，
+// 其余记为 Insert，传输补丁时只需要带上变化的部分。
+// 补丁记录了新旧数据的 SHA-256，apply 前检查基准数据，apply 后校验结果。
+
+// 比文件同步默认的块更小，补丁更精细
+pub const DEFAULT_AVG_CHUNK: usize = 1024;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum PatchError {
+    #[error("基准数据与补丁不匹配: 期望 {expected}，实际 {actual}")]
+    SourceMismatch { expected: String, actual: String },
+
+    #[error("补丁引用的范围 {offset}+{len} 超出基准数据")]
+    OutOfRange { offset: u64, len: u64 },
+
+    #[error("应用补丁后校验失败: 期望 {expected}，实际 {actual}")]
+    Corrupt { expected: String, actual: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Op {
+    // 从旧数据复制一段
+    Copy { offset: u64, len: u64 },
+    Insert(Vec<u8>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Patch {
+    pub old_len: u64,
+    pub old_sha256: String,
+    pub new_len: u64,
+    pub new_sha256: String,
+    pub ops: Vec<Op>,
+}
+
+impl Patch {
+    // 需要随补丁传输的新数据字节数
+    pub fn insert_bytes(&self) -> u64 {
+        self.ops
+            .iter()
+            .map(|op| match op {
+                Op::Insert(data) => data.len() as u64,
+                Op::Copy { .. } => 0,
+            })
+            .sum()
+    }
+
+    pub fn copy_bytes(&self) -> u64 {
+        self.new_len - self.insert_bytes()
+    }
+
+    // 新旧数据相同
+    pub fn is_identity(&self) -> bool {
+        self.old_sha256 == self.new_sha256
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+pub fn diff_bytes(old: &[u8], new: &[u8]) -> Patch {
+    diff_bytes_with(old, new, &Chunker::with_avg_size(DEFAULT_AVG_CHUNK))
+}
+
+pub fn diff_bytes_with(old: &[u8], new: &[u8], chunker: &Chunker) -> Patch {
+    // 相同内容的块只记录第一次出现的位置
+    let mut index: HashMap<&[u8], u64> = HashMap::new();
+    let mut offset = 0u64;
+    for chunk in chunker.chunks(old) {
+        index.entry(chunk).or_insert(offset);
+        offset += chunk.len() as u64;
+    }
+
+    let mut ops: Vec<Op> = Vec::new();
+    for chunk in chunker.chunks(new) {
+        let len = chunk.len() as u64;
+        match (index.get(chunk), ops.last_mut()) {
+            // 与上一段连续的复制合并成一个操作
+            (Some(&at), Some(Op::Copy { offset, len: prev })) if *offset + *prev == at => {
+                *prev += len;
+            }
+            (Some(&at), _) => ops.push(Op::Copy { offset: at, len }),
+            (None, Some(Op::Insert(data))) => data.extend_from_slice(chunk),
+            (None, _) => ops.push(Op::Insert(chunk.to_vec())),
+        }
+    }
+
+    Patch {
+        old_len: old.len() as u64,
+        old_sha256: sha256_hex(old),
+        new_len: new.len() as u64,
+        new_sha256: sha256_hex(new),
+        ops,
+    }
+}
+
+pub fn apply(old: &[u8], patch: &Patch) -> Result<Vec<u8>, PatchError> {
+    let actual = sha256_hex(old);
+    if actual != patch.old_sha256 {
+        return Err(PatchError::SourceMismatch {
+            expected: patch.old_sha256.clone(),
+            actual,
+        });
+    }
+
+    let mut out = Vec::with_capacity(patch.new_len as usize);
+    for op in &patch.ops {
+        match op {
+            Op::Copy { offset, len } => {
+                let range = offset
+                    .checked_add(*len)
+                    .filter(|end| *end <= old.len() as u64)
+                    .map(|end| *offset as usize..end as usize)
+                    .ok_or(PatchError::OutOfRange {
+                        offset: *offset,
+                        len: *len,
+                    })?;
+                out.extend_from_slice(&old[range]);
+            }
+            Op::Insert(data) => out.extend_from_slice(data),
+        }
+    }
+
+    let actual = sha256_hex(&out);
+    if actual != patch.new_sha256 {
+        return Err(PatchError::Corrupt {
+            expected: patch.new_sha256.clone(),
+            actual,
+        });
+    }
+    Ok(out)
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+// path 使用 JSON Pointer 格式，例如 /items/0/name，根节点为空串
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Added {
+        path: String,
+        value: Value,
+    },
+    Removed {
+        path: String,
+        value: Value,
+    },
+    Modified {
+        path: String,
+        old: Value,
+        new: Value,
+    },
+}
+
+impl Change {
+    pub fn path(&self) -> &str {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Modified { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Added { path, value } => write!(f, "+ {}: {}", path, value),
+            Change::Removed { path, value } => write!(f, "- {}: {}", path, value),
+            Change::Modified { path, old, new } => write!(f, "~ {}: {} -> {}", path, old, new),
+        }
+    }
+}
+
+pub fn compare(a: &Value, b: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    walk(String::new(), a, b, &mut changes);
+    changes
+}
+
+// 任意可序列化的值(包括 toml::Value)先转换成 serde_json::Value 再比较
+pub fn compare_values<A, B>(a: &A, b: &B) -> Result<Vec<Change>, serde_json::Error>
+where
+    A: Serialize + ?Sized,
+    B: Serialize + ?Sized,
+{
+    Ok(compare(
+        &serde_json::to_value(a)?,
+        &serde_json::to_value(b)?,
+    ))
+}
+
+// 每行一个变更，便于在测试失败信息中阅读
+pub fn render(changes: &[Change]) -> String {
+    changes
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn walk(path: String, a: &Value, b: &Value, changes: &mut Vec<Change>) {
+    match (a, b) {
+        (Value::Object(left), Value::Object(right)) => {
+            for (key, old) in left {
+                let child = format!("{}/{}", path, escape(key));
+                match right.get(key) {
+                    Some(new) => walk(child, old, new, changes),
+                    None => changes.push(Change::Removed {
+                        path: child,
+                        value: old.clone(),
+                    }),
+                }
+            }
+            for (key, new) in right {
+                if !left.contains_key(key) {
+                    changes.push(Change::Added {
+                        path: format!("{}/{}", path, escape(key)),
+                        value: new.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for i in 0..left.len().max(right.len()) {
+                let child = format!("{}/{}", path, i);
+                match (left.get(i), right.get(i)) {
+                    (Some(old), Some(new)) => walk(child, old, new, changes),
+                    (Some(old), None) => changes.push(Change::Removed {
+                        path: child,
+                        value: old.clone(),
+                    }),
+                    (None, Some(new)) => changes.push(Change::Added {
+                        path: child,
+                        value: new.clone(),
+                    }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if a != b => changes.push(Change::Modified {
+            path,
+            old: a.clone(),
+            new: b.clone(),
+        }),
+        _ => {}
+    }
+}
+
+// RFC 6901: `~` -> `~0`, `/` -> `~1`
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
@@ -0,0 +1,303 @@
+use std::fs::{self, File, TryLockError};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::checksum::{HashState, Sha256};
+
+pub const DEFAULT_COPY_CHUNK: usize = 4 * 1024 * 1024;
+
+#[derive(Error, Debug)]
+pub enum CopyError {
+    #[error("复制被中断，已完成 {copied} 字节")]
+    Interrupted { copied: u64 },
+    #[error("复制结果校验失败: 期望 {expected}，实际 {actual}")]
+    Verify { expected: String, actual: String },
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+// 参数是已复制字节数和总大小，返回 false 时停止复制，保留进度以便下次继续
+pub type CopyProgressFn = Arc<dyn Fn(u64, u64) -> bool + Send + Sync>;
+
+#[derive(Clone)]
+pub struct CopyOptions {
+    chunk_size: usize,
+    progress: Option<CopyProgressFn>,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        CopyOptions {
+            chunk_size: DEFAULT_COPY_CHUNK,
+            progress: None,
+        }
+    }
+}
+
+impl CopyOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 每复制这么多字节保存一次进度
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size.max(1);
+        self
+    }
+
+    pub fn on_progress<F>(mut self, f: F) -> Self
+    where
+        F: Fn(u64, u64) -> bool + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(f));
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyReport {
+    pub bytes: u64,
+    // 从上次中断的位置继续时大于 0
+    pub resumed_from: u64,
+    pub sha256: String,
+}
+
+// 旁路状态文件的内容。源文件的大小或修改时间变化后进度作废，从头开始
+#[derive(Debug, Serialize, Deserialize)]
+struct CopyState {
+    src_len: u64,
+    src_mtime_ns: u128,
+    copied: u64,
+    hash: HashState,
+}
+
+fn sidecar(dst: &Path, suffix: &str) -> PathBuf {
+    let mut name = dst.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+pub fn copy_resumable(src: &Path, dst: &Path) -> Result<CopyReport, CopyError> {
+    copy_resumable_with(src, dst, &CopyOptions::default())
+}
+
+// 数据先写到 <dst>.part，进度记录在 <dst>.part.state。每复制一块先落盘数据再更新进度，
+// 进程中途退出后再次调用会从记录的位置继续。全部复制后重新读取 .part 校验哈希，
+// 一致才 rename 成 dst
+pub fn copy_resumable_with(
+    src: &Path,
+    dst: &Path,
+    options: &CopyOptions,
+) -> Result<CopyReport, CopyError> {
+    let part = sidecar(dst, ".part");
+    let state_path = sidecar(dst, ".part.state");
+    let meta = fs::metadata(src)?;
+    let src_len = meta.len();
+    let src_mtime_ns = meta
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+
+    let (mut hasher, mut copied) = match load_state(&state_path, &part) {
+        Some(state) if state.src_len == src_len && state.src_mtime_ns == src_mtime_ns => {
+            match Sha256::resume(&state.hash) {
+                Ok(hasher) => (hasher, state.copied),
+                Err(_) => (Sha256::new(), 0),
+            }
+        }
+        _ => (Sha256::new(), 0),
+    };
+    let resumed_from = copied;
+
+    let mut input = File::open(src)?;
+    input.seek(SeekFrom::Start(copied))?;
+    let mut output = File::options()
+        .create(true)
+        .write(true)
+        .truncate(false)
+        .open(&part)?;
+    // 丢弃上次记录进度之后写入的部分
+    output.set_len(copied)?;
+    output.seek(SeekFrom::Start(copied))?;
+
+    let mut buffer = vec![0u8; options.chunk_size.min(src_len.max(1) as usize)];
+    while copied < src_len {
+        let n = read_up_to(&mut input, &mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        output.write_all(&buffer[..n])?;
+        output.sync_data()?;
+        hasher.update(&buffer[..n]);
+        copied += n as u64;
+        save_state(
+            &state_path,
+            &CopyState {
+                src_len,
+                src_mtime_ns,
+                copied,
+                hash: hasher.suspend(),
+            },
+        )?;
+        if let Some(progress) = &options.progress {
+            if !progress(copied, src_len) && copied < src_len {
+                return Err(CopyError::Interrupted { copied });
+            }
+        }
+    }
+    drop(output);
+
+    let expected = hasher.finalize_hex();
+    let actual = hash_file(&part)?;
+    if actual != expected {
+        // .part 已经损坏，不能再从中间继续
+        let _ = fs::remove_file(&part);
+        let _ = fs::remove_file(&state_path);
+        return Err(CopyError::Verify { expected, actual });
+    }
+    fs::rename(&part, dst)?;
+    let _ = fs::remove_file(&state_path);
+    Ok(CopyReport {
+        bytes: copied,
+        resumed_from,
+        sha256: expected,
+    })
+}
+
+// 状态文件损坏或与 .part 长度对不上时当作没有进度
+fn load_state(state_path: &Path, part: &Path) -> Option<CopyState> {
+    let state: CopyState = serde_json::from_slice(&fs::read(state_path).ok()?).ok()?;
+    let part_len = fs::metadata(part).ok()?.len();
+    (part_len >= state.copied).then_some(state)
+}
+
+fn save_state(path: &Path, state: &CopyState) -> io::Result<()> {
+    let tmp = sidecar(path, ".tmp");
+    fs::write(&tmp, serde_json::to_vec(state)?)?;
+    fs::rename(&tmp, path)
+}
+
+fn read_up_to(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(filled)
+}
+
+fn hash_file(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let n = read_up_to(&mut file, &mut buffer)?;
+        if n == 0 {
+            return Ok(hasher.finalize_hex());
+        }
+        hasher.update(&buffer[..n]);
+    }
+}
+
+// 建议性文件锁(flock)，drop 时释放。用来保证共享数据目录上的迁移、压缩等操作
+// 同一时间只有一个进程在做；同一进程内对同一路径再次加锁也会冲突。
+// 进程退出(包括崩溃)时操作系统自动释放，不会留下需要手动清理的锁
+#[derive(Debug)]
+pub struct FileLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl FileLock {
+    // 阻塞直到取得锁，锁文件不存在时创建
+    pub fn exclusive(path: impl AsRef<Path>) -> io::Result<FileLock> {
+        let (file, path) = open_lock_file(path.as_ref())?;
+        file.lock()?;
+        Ok(FileLock::acquired(file, path))
+    }
+
+    // 已被其他持有者锁定时立即返回 None
+    pub fn try_exclusive(path: impl AsRef<Path>) -> io::Result<Option<FileLock>> {
+        let (file, path) = open_lock_file(path.as_ref())?;
+        match file.try_lock() {
+            Ok(()) => Ok(Some(FileLock::acquired(file, path))),
+            Err(TryLockError::WouldBlock) => Ok(None),
+            Err(TryLockError::Error(e)) => Err(e),
+        }
+    }
+
+    // 共享锁可以被多个持有者同时持有，和 exclusive 互斥；不写入进程号
+    pub fn shared(path: impl AsRef<Path>) -> io::Result<FileLock> {
+        let (file, path) = open_lock_file(path.as_ref())?;
+        file.lock_shared()?;
+        Ok(FileLock { file, path })
+    }
+
+    // 超时返回 ErrorKind::TimedOut
+    pub fn exclusive_timeout(path: impl AsRef<Path>, timeout: Duration) -> io::Result<FileLock> {
+        let path = path.as_ref();
+        let deadline = Instant::now() + timeout;
+        let mut delay = Duration::from_millis(5);
+        loop {
+            if let Some(lock) = FileLock::try_exclusive(path)? {
+                return Ok(lock);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("等待文件锁超时: {}", path.display()),
+                ));
+            }
+            thread::sleep(delay.min(deadline - now));
+            delay = (delay * 2).min(Duration::from_millis(100));
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // 持有锁的进程号，只用于诊断
+    pub fn holder(path: impl AsRef<Path>) -> Option<u32> {
+        fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+
+    fn acquired(mut file: File, path: PathBuf) -> FileLock {
+        // 写入进程号失败不影响加锁本身
+        let _ = file
+            .set_len(0)
+            .and_then(|_| file.write_all(std::process::id().to_string().as_bytes()));
+        FileLock { file, path }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+fn open_lock_file(path: &Path) -> io::Result<(File, PathBuf)> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let file = File::options()
+        .create(true)
+        .read(true)
+        .write(true)
+        .truncate(false)
+        .open(path)?;
+    Ok((file, path.to_path_buf()))
+}
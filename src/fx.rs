@@ -0,0 +1,298 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::cache::TtlCache;
+use crate::clock::{self, SharedClock};
+use crate::collections::RcuCell;
+use crate::logs;
+
+// 汇率换算: 汇率来自 RatesProvider(HTTP 拉取并缓存，失败时退回静态汇率)，
+// 换算结果带上汇率时间，业务代码可以拒绝过期的汇率。
+
+pub type FxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum FxError {
+    #[error("无效的币种: {0}")]
+    InvalidCurrency(String),
+
+    #[error("没有 {0} → {1} 的汇率")]
+    UnknownRate(Currency, Currency),
+
+    #[error("汇率已过期: {age:?} 超过 {max_age:?}")]
+    Stale { age: Duration, max_age: Duration },
+
+    #[error("获取汇率失败: {0}")]
+    Provider(String),
+
+    #[error("没有设置汇率来源")]
+    NoProvider,
+}
+
+// ISO 4217 三位字母代码
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Currency(String);
+
+impl Currency {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Currency {
+    type Err = FxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 3 || !s.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(FxError::InvalidCurrency(s.to_string()));
+        }
+        Ok(Currency(s.to_ascii_uppercase()))
+    }
+}
+
+impl fmt::Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.0)
+    }
+}
+
+// 金额使用最小货币单位(分)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Money {
+    pub amount: i64,
+    pub currency: Currency,
+}
+
+impl Money {
+    pub fn new(amount: i64, currency: Currency) -> Self {
+        Money { amount, currency }
+    }
+}
+
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.amount < 0 { "-" } else { "" };
+        let abs = self.amount.unsigned_abs();
+        write!(
+            f,
+            "{}{}.{:02} {}",
+            sign,
+            abs / 100,
+            abs % 100,
+            self.currency
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rate {
+    pub from: Currency,
+    pub to: Currency,
+    pub rate: f64,
+    pub as_of: SystemTime,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Converted {
+    pub money: Money,
+    pub rate: Rate,
+}
+
+impl Converted {
+    pub fn age(&self, now: SystemTime) -> Duration {
+        now.duration_since(self.rate.as_of).unwrap_or_default()
+    }
+
+    // 汇率时间早于 max_age 时返回 Stale
+    pub fn ensure_fresh(self, max_age: Duration, now: SystemTime) -> Result<Self, FxError> {
+        let age = self.age(now);
+        if age > max_age {
+            return Err(FxError::Stale { age, max_age });
+        }
+        Ok(self)
+    }
+}
+
+pub trait RatesProvider: Send + Sync {
+    fn rate<'a>(
+        &'a self,
+        from: &'a Currency,
+        to: &'a Currency,
+    ) -> FxFuture<'a, Result<Rate, FxError>>;
+}
+
+// 固定汇率表，缺少的方向用反向汇率推算
+pub struct StaticRates {
+    rates: HashMap<(Currency, Currency), f64>,
+    as_of: SystemTime,
+}
+
+impl StaticRates {
+    pub fn new(as_of: SystemTime) -> Self {
+        StaticRates {
+            rates: HashMap::new(),
+            as_of,
+        }
+    }
+
+    pub fn rate(mut self, from: &str, to: &str, rate: f64) -> Result<Self, FxError> {
+        self.rates.insert((from.parse()?, to.parse()?), rate);
+        Ok(self)
+    }
+
+    fn lookup(&self, from: &Currency, to: &Currency) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        self.rates
+            .get(&(from.clone(), to.clone()))
+            .copied()
+            .or_else(|| self.rates.get(&(to.clone(), from.clone())).map(|r| 1.0 / r))
+    }
+}
+
+impl RatesProvider for StaticRates {
+    fn rate<'a>(
+        &'a self,
+        from: &'a Currency,
+        to: &'a Currency,
+    ) -> FxFuture<'a, Result<Rate, FxError>> {
+        Box::pin(async move {
+            let rate = self
+                .lookup(from, to)
+                .ok_or_else(|| FxError::UnknownRate(from.clone(), to.clone()))?;
+            Ok(Rate {
+                from: from.clone(),
+                to: to.clone(),
+                rate,
+                as_of: self.as_of,
+            })
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct RatesResponse {
+    // unix 秒
+    timestamp: u64,
+    rates: HashMap<String, f64>,
+}
+
+// GET {url}?base=USD 返回 {"timestamp": 1700000000, "rates": {"EUR": 0.92}}，
+// 按基准币种缓存 ttl；请求失败时使用 fallback
+pub struct HttpRates {
+    client: reqwest::Client,
+    url: String,
+    cache: TtlCache<Currency, RatesResponse>,
+    fallback: Option<StaticRates>,
+}
+
+impl HttpRates {
+    pub fn new(url: &str, ttl: Duration) -> Self {
+        Self::with_clock(url, ttl, clock::system())
+    }
+
+    pub fn with_clock(url: &str, ttl: Duration, clock: SharedClock) -> Self {
+        HttpRates {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            cache: TtlCache::with_clock(ttl, clock),
+            fallback: None,
+        }
+    }
+
+    pub fn fallback(mut self, rates: StaticRates) -> Self {
+        self.fallback = Some(rates);
+        self
+    }
+
+    async fn fetch(&self, base: &Currency) -> Result<RatesResponse, FxError> {
+        if let Some(cached) = self.cache.get(base) {
+            return Ok(cached);
+        }
+        let response = self
+            .client
+            .get(&self.url)
+            .query(&[("base", base.as_str())])
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| FxError::Provider(e.to_string()))?;
+        let text = response
+            .text()
+            .await
+            .map_err(|e| FxError::Provider(e.to_string()))?;
+        let rates: RatesResponse =
+            serde_json::from_str(&text).map_err(|e| FxError::Provider(e.to_string()))?;
+        self.cache.insert(base.clone(), rates.clone());
+        Ok(rates)
+    }
+}
+
+impl RatesProvider for HttpRates {
+    fn rate<'a>(
+        &'a self,
+        from: &'a Currency,
+        to: &'a Currency,
+    ) -> FxFuture<'a, Result<Rate, FxError>> {
+        Box::pin(async move {
+            let fetched = self.fetch(from).await.and_then(|response| {
+                let rate = if from == to {
+                    Some(1.0)
+                } else {
+                    response.rates.get(to.as_str()).copied()
+                };
+                let rate = rate.ok_or_else(|| FxError::UnknownRate(from.clone(), to.clone()))?;
+                Ok(Rate {
+                    from: from.clone(),
+                    to: to.clone(),
+                    rate,
+                    as_of: UNIX_EPOCH + Duration::from_secs(response.timestamp),
+                })
+            });
+            match (fetched, &self.fallback) {
+                (Err(e), Some(fallback)) => {
+                    logs::warn("fx", &format!("使用静态汇率 {} → {}: {}", from, to, e));
+                    fallback.rate(from, to).await
+                }
+                (result, _) => result,
+            }
+        })
+    }
+}
+
+pub async fn convert_with(
+    provider: &dyn RatesProvider,
+    money: &Money,
+    to: &Currency,
+) -> Result<Converted, FxError> {
+    let rate = provider.rate(&money.currency, to).await?;
+    Ok(Converted {
+        money: Money::new((money.amount as f64 * rate.rate).round() as i64, to.clone()),
+        rate,
+    })
+}
+
+// 每次换算都要读取，用 RcuCell 避免读锁竞争
+fn provider() -> &'static RcuCell<Option<Arc<dyn RatesProvider>>> {
+    static PROVIDER: OnceLock<RcuCell<Option<Arc<dyn RatesProvider>>>> = OnceLock::new();
+    PROVIDER.get_or_init(RcuCell::default)
+}
+
+// 进程级的汇率来源，启动时设置一次
+pub fn set_provider(rates: Arc<dyn RatesProvider>) {
+    provider().store(Some(rates));
+}
+
+pub async fn convert(money: Money, to: Currency) -> Result<Converted, FxError> {
+    let rates = provider().read(Option::clone).ok_or(FxError::NoProvider)?;
+    convert_with(rates.as_ref(), &money, &to).await
+}
@@ -0,0 +1,533 @@
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::{Request, RequestBuilder, Response};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::clock::{self, SharedClock};
+use crate::context;
+use crate::ratelimit::adaptive::AdaptiveLimiter;
+use crate::retry::Backoff;
+use crate::semaphore::{AcquireError, PrioritySemaphore};
+use crate::status::HttpStatus;
+use budget::RetryBudget;
+use pool::{ClientStats, ConnectionConfig, CountConnections, Counters};
+
+// HTTP 客户端门面: 统一 base url、默认请求头、上下文透传和错误状态码映射，
+// 生成的 API 客户端(见 openapi 模块)也基于它。
+
+pub mod budget;
+pub mod conditional;
+pub mod negotiate;
+pub mod pool;
+pub mod signing;
+pub mod sse;
+
+pub use reqwest::Method;
+
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error("请求失败: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("未授权: {0}")]
+    Unauthorized(String),
+
+    #[error("没有权限: {0}")]
+    Forbidden(String),
+
+    #[error("资源不存在: {0}")]
+    NotFound(String),
+
+    #[error("冲突: {0}")]
+    Conflict(String),
+
+    #[error("请求过多，{retry_after:?} 后重试")]
+    RateLimited { retry_after: Option<Duration> },
+
+    #[error("HTTP {status}: {body}")]
+    Status { status: u16, body: String },
+
+    #[error("响应解析失败: {0}")]
+    Decode(#[from] serde_json::Error),
+
+    #[error("响应内容无法处理: {0}")]
+    Content(String),
+
+    #[error("响应解压后超过上限 {limit} 字节")]
+    TooLarge { limit: usize },
+
+    #[error("请求中间件失败: {0}")]
+    Middleware(String),
+
+    #[error("重试预算已用完，放弃重试: {0}")]
+    RetryBudgetExhausted(Box<ApiError>),
+
+    #[error("本地并发已满: {0}")]
+    Saturated(#[from] AcquireError),
+}
+
+impl ApiError {
+    pub fn from_status(status: u16, retry_after: Option<Duration>, body: String) -> Self {
+        match status {
+            401 => ApiError::Unauthorized(body),
+            403 => ApiError::Forbidden(body),
+            404 => ApiError::NotFound(body),
+            409 => ApiError::Conflict(body),
+            429 => ApiError::RateLimited { retry_after },
+            _ => ApiError::Status { status, body },
+        }
+    }
+}
+
+impl HttpStatus for ApiError {
+    fn status(&self) -> u16 {
+        match self {
+            ApiError::Unauthorized(_) => 401,
+            ApiError::Forbidden(_) => 403,
+            ApiError::NotFound(_) => 404,
+            ApiError::Conflict(_) => 409,
+            ApiError::RateLimited { .. } => 429,
+            ApiError::Status { status, .. } => *status,
+            ApiError::Request(_)
+            | ApiError::Decode(_)
+            | ApiError::Content(_)
+            | ApiError::TooLarge { .. } => 502,
+            ApiError::Middleware(_) => 500,
+            ApiError::RetryBudgetExhausted(e) => e.status(),
+            ApiError::Saturated(_) => 503,
+        }
+    }
+}
+
+// 请求发送前按注册顺序调用，可以修改请求(签名、加请求头等)或拒绝发送
+pub trait Middleware: Send + Sync {
+    fn handle(&self, request: &mut Request) -> Result<(), ApiError>;
+}
+
+pub struct HttpClientBuilder {
+    base_url: String,
+    headers: Vec<(String, String)>,
+    timeout: Option<Duration>,
+    connection: ConnectionConfig,
+    middleware: Vec<Arc<dyn Middleware>>,
+    retry: Option<Retry>,
+    limiter: Option<AdaptiveLimiter>,
+    gate: Option<PrioritySemaphore>,
+    max_decoded_size: usize,
+}
+
+// 只重试幂等请求的连接错误、429 和 5xx；budget 在克隆出来的客户端之间共享
+#[derive(Clone)]
+struct Retry {
+    backoff: Backoff,
+    budget: RetryBudget,
+    clock: SharedClock,
+}
+
+impl HttpClientBuilder {
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn middleware<M: Middleware + 'static>(mut self, middleware: M) -> Self {
+        self.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    pub fn retry(self, backoff: Backoff) -> Self {
+        self.retry_with_budget(backoff, RetryBudget::default())
+    }
+
+    pub fn retry_with_budget(mut self, backoff: Backoff, budget: RetryBudget) -> Self {
+        self.retry = Some(Retry {
+            backoff,
+            budget,
+            clock: clock::system(),
+        });
+        self
+    }
+
+    // 重试等待使用的时钟，需要在 retry 之后调用
+    pub fn retry_clock(mut self, clock: SharedClock) -> Self {
+        if let Some(retry) = &mut self.retry {
+            retry.clock = clock;
+        }
+        self
+    }
+
+    // 按上游的延迟和错误自动调整并发上限，克隆出来的客户端共享同一个限制
+    pub fn concurrency_limit(mut self, limiter: AdaptiveLimiter) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    // 并发请求数不超过信号量的许可数，排队时按请求上下文的优先级分配，
+    // 等到上下文的 deadline 仍拿不到许可时返回 ApiError::Saturated
+    pub fn priority_gate(mut self, gate: PrioritySemaphore) -> Self {
+        self.gate = Some(gate);
+        self
+    }
+
+    // 压缩响应解压后的大小上限，超过时返回 ApiError::TooLarge，防止压缩炸弹
+    pub fn max_decoded_size(mut self, max: usize) -> Self {
+        self.max_decoded_size = max;
+        self
+    }
+
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.connection.http2_prior_knowledge = true;
+        self
+    }
+
+    // 每个 host 最多保留的空闲连接数，0 表示不复用连接
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.connection.max_idle_per_host = Some(max);
+        self
+    }
+
+    pub fn pool_idle_timeout(mut self, timeout: Duration) -> Self {
+        self.connection.idle_timeout_secs = Some(timeout.as_secs());
+        self
+    }
+
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.connection.tcp_keepalive_secs = Some(interval.as_secs());
+        self
+    }
+
+    // 使用配置文件中的连接池设置，覆盖之前的设置
+    pub fn connection(mut self, config: &ConnectionConfig) -> Self {
+        self.connection = config.clone();
+        self
+    }
+
+    pub fn build(self) -> Result<HttpClient, ApiError> {
+        let counters = Arc::new(Counters::default());
+        let mut builder =
+            reqwest::Client::builder().connector_layer(CountConnections(counters.clone()));
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        let connection = &self.connection;
+        if connection.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(max) = connection.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = connection.idle_timeout() {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(interval) = connection.tcp_keepalive() {
+            builder = builder.tcp_keepalive(interval);
+        }
+        Ok(HttpClient {
+            client: builder.build()?,
+            base_url: self.base_url,
+            headers: self.headers,
+            counters,
+            middleware: self.middleware.into(),
+            retry: self.retry,
+            limiter: self.limiter,
+            gate: self.gate,
+            max_decoded_size: self.max_decoded_size,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    base_url: String,
+    headers: Vec<(String, String)>,
+    counters: Arc<Counters>,
+    middleware: Arc<[Arc<dyn Middleware>]>,
+    retry: Option<Retry>,
+    limiter: Option<AdaptiveLimiter>,
+    gate: Option<PrioritySemaphore>,
+    max_decoded_size: usize,
+}
+
+impl HttpClient {
+    pub fn new(base_url: &str) -> Self {
+        Self::builder(base_url)
+            .build()
+            .expect("默认配置的 HTTP 客户端")
+    }
+
+    pub fn builder(base_url: &str) -> HttpClientBuilder {
+        HttpClientBuilder {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            headers: Vec::new(),
+            timeout: None,
+            connection: ConnectionConfig::default(),
+            middleware: Vec::new(),
+            retry: None,
+            limiter: None,
+            gate: None,
+            max_decoded_size: negotiate::DEFAULT_MAX_DECODED_SIZE,
+        }
+    }
+
+    // 请求数和新建连接数，用来观察连接复用情况；克隆出来的客户端共享统计
+    pub fn stats(&self) -> ClientStats {
+        self.counters.snapshot()
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    // path 以 "/" 开头时拼接到 base url 上，否则当作完整的 url
+    pub fn url(&self, path: &str) -> String {
+        if path.starts_with('/') {
+            format!("{}{}", self.base_url, path)
+        } else {
+            path.to_string()
+        }
+    }
+
+    // 带上默认请求头和当前请求上下文
+    pub fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let builder = self
+            .headers
+            .iter()
+            .fold(self.client.request(method, self.url(path)), |b, (k, v)| {
+                b.header(k, v)
+            });
+        context::inject(builder)
+    }
+
+    // 非 2xx (304 除外)响应转换为 ApiError；配置了 retry 时按退避策略重试
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response, ApiError> {
+        self.dispatch(request.build()?).await
+    }
+
+    // 对冲请求: hedge_after 内没有完成时再发一个相同的请求，取先成功的响应，另一个被取消。
+    // 用多一点上游负载换更低的尾延迟，hedge_after 一般取 p95 左右的延迟
+    pub async fn get_hedged(
+        &self,
+        path: &str,
+        hedge_after: Duration,
+    ) -> Result<Response, ApiError> {
+        self.send_hedged(self.request(Method::GET, path), hedge_after)
+            .await
+    }
+
+    // 只对冲幂等并且请求体可以复制的请求，其他请求照常只发送一次
+    pub async fn send_hedged(
+        &self,
+        request: RequestBuilder,
+        hedge_after: Duration,
+    ) -> Result<Response, ApiError> {
+        let request = request.build()?;
+        let Some(hedge) = request.try_clone().filter(|_| idempotent(request.method())) else {
+            return self.dispatch(request).await;
+        };
+        let first = self.dispatch(request);
+        tokio::pin!(first);
+        tokio::select! {
+            result = &mut first => return result,
+            _ = tokio::time::sleep(hedge_after) => {}
+        }
+        // 对冲请求是额外的上游负载，和重试一样从预算中扣除，预算不足时不发
+        if let Some(retry) = &self.retry {
+            if !retry.budget.try_retry() {
+                return first.await;
+            }
+        }
+        self.counters.hedged.fetch_add(1, Ordering::Relaxed);
+        let second = self.attempt(hedge);
+        tokio::pin!(second);
+        // 先完成的失败时继续等另一个；select 返回后未完成的请求随 future 一起 drop
+        tokio::select! {
+            result = &mut first => match result {
+                Ok(response) => Ok(response),
+                Err(_) => second.await,
+            },
+            result = &mut second => match result {
+                Ok(response) => Ok(response),
+                Err(_) => first.await,
+            },
+        }
+    }
+
+    async fn dispatch(&self, request: Request) -> Result<Response, ApiError> {
+        if let Some(retry) = &self.retry {
+            retry.budget.record_request();
+        }
+        self.attempt(request).await
+    }
+
+    // 发送并按退避策略重试，不计入预算的请求数
+    async fn attempt(&self, mut request: Request) -> Result<Response, ApiError> {
+        let Some(retry) = &self.retry else {
+            return self.execute(request).await;
+        };
+        let idempotent = idempotent(request.method());
+        let mut attempt = 1;
+        loop {
+            // 流式请求体无法复制，只能发送一次
+            let next = request.try_clone().filter(|_| idempotent);
+            let e = match self.execute(request).await {
+                Err(e) if retryable(&e) => e,
+                result => return result,
+            };
+            let Some(next) = next else {
+                return Err(e);
+            };
+            if attempt >= retry.backoff.max_attempts {
+                return Err(e);
+            }
+            if !retry.budget.try_retry() {
+                return Err(ApiError::RetryBudgetExhausted(Box::new(e)));
+            }
+            let mut delay = retry.backoff.delay(attempt);
+            if let ApiError::RateLimited {
+                retry_after: Some(after),
+            } = e
+            {
+                delay = delay.max(after);
+            }
+            retry.clock.sleep(delay).await;
+            attempt += 1;
+            request = next;
+        }
+    }
+
+    async fn execute(&self, request: Request) -> Result<Response, ApiError> {
+        let _permit = match &self.gate {
+            Some(gate) => {
+                let ctx = context::current().unwrap_or_default();
+                Some(gate.acquire_until(ctx.priority, ctx.deadline).await?)
+            }
+            None => None,
+        };
+        let Some(limiter) = &self.limiter else {
+            return self.execute_unlimited(request).await;
+        };
+        let permit = limiter.acquire().await;
+        let result = self.execute_unlimited(request).await;
+        // 只有说明上游过载的错误才减小并发
+        match &result {
+            Err(ApiError::Request(_) | ApiError::RateLimited { .. }) => permit.failure(),
+            Err(ApiError::Status { status, .. }) if *status >= 500 => permit.failure(),
+            _ => permit.success(),
+        }
+        result
+    }
+
+    // 中间件在每次实际发送前运行，重试和对冲请求都从未处理的请求复制，
+    // 签名之类带时间戳的请求头每次都重新生成
+    async fn execute_unlimited(&self, mut request: Request) -> Result<Response, ApiError> {
+        for middleware in self.middleware.iter() {
+            middleware.handle(&mut request)?;
+        }
+        self.counters.requests.fetch_add(1, Ordering::Relaxed);
+        let start = Instant::now();
+        let response = self.client.execute(request).await;
+        context::costs::record_http(start.elapsed());
+        let response = response?;
+        let status = response.status();
+        // 304 只会出现在条件请求中，交给调用方处理
+        if status.is_success() || status.as_u16() == 304 {
+            return Ok(response);
+        }
+        let retry_after = response
+            .headers()
+            .get("retry-after")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs);
+        let body = response.text().await.unwrap_or_default();
+        Err(ApiError::from_status(status.as_u16(), retry_after, body))
+    }
+
+    // 重试预算的使用情况，没有配置重试时为 None
+    pub fn retry_budget(&self) -> Option<&RetryBudget> {
+        self.retry.as_ref().map(|r| &r.budget)
+    }
+
+    // 发送 JSON 请求并解析 JSON 响应，空响应体按 null 解析(对应 ())
+    pub async fn call<B, R>(
+        &self,
+        method: Method,
+        path: &str,
+        query: &[(&str, String)],
+        body: Option<&B>,
+    ) -> Result<R, ApiError>
+    where
+        B: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        let mut request = self
+            .request(method, path)
+            .header("accept", "application/json");
+        if !query.is_empty() {
+            request = request.query(query);
+        }
+        if let Some(body) = body {
+            request = request
+                .header("content-type", "application/json")
+                .body(serde_json::to_vec(body)?);
+        }
+        let text = self.send(request).await?.text().await?;
+        let text = if text.trim().is_empty() {
+            "null"
+        } else {
+            &text
+        };
+        Ok(serde_json::from_str(text)?)
+    }
+
+    pub async fn get_json<R: DeserializeOwned>(&self, path: &str) -> Result<R, ApiError> {
+        self.call::<(), R>(Method::GET, path, &[], None).await
+    }
+
+    pub async fn post_json<B, R>(&self, path: &str, body: &B) -> Result<R, ApiError>
+    where
+        B: Serialize + ?Sized,
+        R: DeserializeOwned,
+    {
+        self.call(Method::POST, path, &[], Some(body)).await
+    }
+}
+
+fn idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+    )
+}
+
+fn retryable(e: &ApiError) -> bool {
+    match e {
+        ApiError::Request(_) | ApiError::RateLimited { .. } => true,
+        ApiError::Status { status, .. } => *status >= 500,
+        _ => false,
+    }
+}
+
+// 路径参数的百分号编码，只保留 RFC 3986 的非保留字符
+pub fn encode_path(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for b in segment.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(b as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::clock::{self, SharedClock};
+use crate::metrics::{Counter, Gauge, Registry};
+
+// 整个客户端共享的重试预算: 滑动窗口内的重试次数不超过请求数的 ratio 倍(再加上
+// min_retries 的保底)，上游持续出错时重试不会把流量放大数倍。
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetStats {
+    pub requests: usize,
+    pub retries: usize,
+    // 当前还允许的重试次数
+    pub available: usize,
+}
+
+struct Metrics {
+    retries: Counter,
+    denied: Counter,
+    available: Gauge,
+}
+
+struct State {
+    requests: VecDeque<Instant>,
+    retries: VecDeque<Instant>,
+}
+
+#[derive(Clone)]
+pub struct RetryBudget {
+    state: Arc<Mutex<State>>,
+    ratio: f64,
+    min_retries: usize,
+    window: Duration,
+    clock: SharedClock,
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl Default for RetryBudget {
+    // 10 秒窗口内最多多出 20% 的请求
+    fn default() -> Self {
+        Self::new(0.2, Duration::from_secs(10))
+    }
+}
+
+impl RetryBudget {
+    pub fn new(ratio: f64, window: Duration) -> Self {
+        RetryBudget {
+            state: Arc::new(Mutex::new(State {
+                requests: VecDeque::new(),
+                retries: VecDeque::new(),
+            })),
+            ratio: ratio.max(0.0),
+            min_retries: 3,
+            window,
+            clock: clock::system(),
+            metrics: None,
+        }
+    }
+
+    // 请求量很小时也允许的重试次数
+    pub fn min_retries(mut self, n: usize) -> Self {
+        self.min_retries = n;
+        self
+    }
+
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // 注册 http_client_retries_total、http_client_retries_denied_total 和
+    // http_client_retry_budget_available
+    pub fn metrics(mut self, registry: &Registry) -> Self {
+        self.metrics = Some(Arc::new(Metrics {
+            retries: registry.counter("http_client_retries_total"),
+            denied: registry.counter("http_client_retries_denied_total"),
+            available: registry.gauge("http_client_retry_budget_available"),
+        }));
+        self
+    }
+
+    fn prune(&self, state: &mut State) -> Instant {
+        let now = self.clock.now();
+        for queue in [&mut state.requests, &mut state.retries] {
+            while queue
+                .front()
+                .is_some_and(|t| now.duration_since(*t) >= self.window)
+            {
+                queue.pop_front();
+            }
+        }
+        now
+    }
+
+    fn available(&self, state: &State) -> usize {
+        let allowed = self.min_retries + (state.requests.len() as f64 * self.ratio) as usize;
+        allowed.saturating_sub(state.retries.len())
+    }
+
+    fn update_gauge(&self, state: &State) {
+        if let Some(metrics) = &self.metrics {
+            metrics.available.set(self.available(state) as f64);
+        }
+    }
+
+    // 每个请求的第一次发送调用一次
+    pub fn record_request(&self) {
+        let mut state = self.state.lock().unwrap();
+        let now = self.prune(&mut state);
+        state.requests.push_back(now);
+        self.update_gauge(&state);
+    }
+
+    // 预算足够时占用一次重试并返回 true
+    pub fn try_retry(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let now = self.prune(&mut state);
+        let allowed = self.available(&state) > 0;
+        if allowed {
+            state.retries.push_back(now);
+        }
+        if let Some(metrics) = &self.metrics {
+            if allowed {
+                metrics.retries.inc();
+            } else {
+                metrics.denied.inc();
+            }
+        }
+        self.update_gauge(&state);
+        allowed
+    }
+
+    pub fn stats(&self) -> BudgetStats {
+        let mut state = self.state.lock().unwrap();
+        self.prune(&mut state);
+        BudgetStats {
+            requests: state.requests.len(),
+            retries: state.retries.len(),
+            available: self.available(&state),
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use sqlx::{Row, SqlitePool};
+use thiserror::Error;
+
+use super::{ApiError, HttpClient, Method};
+use crate::db::DbError;
+
+// 条件请求: 带上上次的 ETag / Last-Modified，服务端返回 304 时不再下载。
+// 定时同步任务用 ValidatorStore 把校验信息持久化在数据库中，重启后仍然有效。
+
+#[derive(Error, Debug)]
+pub enum SyncError {
+    #[error(transparent)]
+    Api(#[from] ApiError),
+
+    #[error(transparent)]
+    Db(#[from] DbError),
+}
+
+impl From<sqlx::Error> for SyncError {
+    fn from(e: sqlx::Error) -> Self {
+        SyncError::Db(e.into())
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fetched {
+    Unchanged,
+    Changed(Vec<u8>, Validators),
+}
+
+impl HttpClient {
+    // cached 为 None 时总是下载
+    pub async fn fetch_if_changed(
+        &self,
+        url: &str,
+        cached: Option<&Validators>,
+    ) -> Result<Fetched, ApiError> {
+        let mut request = self.request(Method::GET, url);
+        if let Some(cached) = cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header("if-none-match", etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header("if-modified-since", last_modified);
+            }
+        }
+        let response = self.send(request).await?;
+        if response.status().as_u16() == 304 {
+            return Ok(Fetched::Unchanged);
+        }
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let validators = Validators {
+            etag: header("etag"),
+            last_modified: header("last-modified"),
+        };
+        Ok(Fetched::Changed(
+            response.bytes().await?.to_vec(),
+            validators,
+        ))
+    }
+}
+
+#[derive(Clone)]
+pub struct ValidatorStore {
+    pool: SqlitePool,
+}
+
+impl ValidatorStore {
+    pub fn new(pool: SqlitePool) -> Self {
+        ValidatorStore { pool }
+    }
+
+    pub async fn migrate(&self) -> Result<(), SyncError> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS http_validators (
+                url TEXT PRIMARY KEY,
+                etag TEXT,
+                last_modified TEXT
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get(&self, url: &str) -> Result<Option<Validators>, SyncError> {
+        let row = sqlx::query("SELECT etag, last_modified FROM http_validators WHERE url = ?1")
+            .bind(url)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|row| Validators {
+            etag: row.get(0),
+            last_modified: row.get(1),
+        }))
+    }
+
+    pub async fn put(&self, url: &str, validators: &Validators) -> Result<(), SyncError> {
+        sqlx::query(
+            "INSERT INTO http_validators (url, etag, last_modified) VALUES (?1, ?2, ?3)
+             ON CONFLICT(url) DO UPDATE SET etag = excluded.etag, last_modified = excluded.last_modified",
+        )
+        .bind(url)
+        .bind(&validators.etag)
+        .bind(&validators.last_modified)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn remove(&self, url: &str) -> Result<(), SyncError> {
+        sqlx::query("DELETE FROM http_validators WHERE url = ?1")
+            .bind(url)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    // 使用保存的校验信息发起条件请求，内容变化时更新校验信息；
+    // 服务端没有返回任何校验信息时删除旧记录，下次完整下载
+    pub async fn fetch_if_changed(
+        &self,
+        client: &HttpClient,
+        url: &str,
+    ) -> Result<Fetched, SyncError> {
+        let cached = self.get(url).await?;
+        let fetched = client.fetch_if_changed(url, cached.as_ref()).await?;
+        if let Fetched::Changed(_, validators) = &fetched {
+            if validators.is_empty() {
+                self.remove(url).await?;
+            } else {
+                self.put(url, validators).await?;
+            }
+        }
+        Ok(fetched)
+    }
+}
@@ -0,0 +1,152 @@
+use std::io::Read;
+
+use reqwest::Response;
+use serde::de::DeserializeOwned;
+
+use super::{ApiError, HttpClient, Method};
+use crate::pool;
+
+// 内容协商: 请求时声明可以接受的格式和压缩方式，响应按 Content-Encoding 解压，
+// 再按 Content-Type 解析为 JSON、TOML 或纯文本。
+// 解压后的大小有上限(HttpClientBuilder::max_decoded_size)，超过时返回 ApiError::TooLarge。
+
+pub const ACCEPT: &str = "application/json, application/toml;q=0.9, text/plain;q=0.8";
+pub const ACCEPT_ENCODING: &str = "gzip, br";
+pub const DEFAULT_MAX_DECODED_SIZE: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Identity,
+    Gzip,
+    Brotli,
+}
+
+impl Encoding {
+    pub fn from_header(value: Option<&str>) -> Result<Self, ApiError> {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            None | Some("") | Some("identity") => Ok(Encoding::Identity),
+            Some("gzip") | Some("x-gzip") => Ok(Encoding::Gzip),
+            Some("br") => Ok(Encoding::Brotli),
+            Some(other) => Err(ApiError::Content(format!("不支持的压缩方式: {}", other))),
+        }
+    }
+
+    pub fn decode(self, bytes: Vec<u8>, limit: usize) -> Result<Vec<u8>, ApiError> {
+        if self == Encoding::Identity {
+            return Ok(bytes);
+        }
+        let mut out = Vec::new();
+        self.decode_into(&bytes, &mut out, limit)?;
+        Ok(out)
+    }
+
+    // 解压后追加到 out，可以配合缓冲池复用 out；解压出的内容超过 limit 时
+    // 不再继续读取，out 恢复原样并返回 ApiError::TooLarge
+    pub fn decode_into(
+        self,
+        bytes: &[u8],
+        out: &mut Vec<u8>,
+        limit: usize,
+    ) -> Result<(), ApiError> {
+        match self {
+            Encoding::Identity => {
+                out.extend_from_slice(bytes);
+                Ok(())
+            }
+            Encoding::Gzip => read_limited(flate2::read::GzDecoder::new(bytes), out, limit),
+            Encoding::Brotli => read_limited(brotli::Decompressor::new(bytes, 4096), out, limit),
+        }
+    }
+}
+
+// 最多多读一个字节，用来判断是否超过上限
+fn read_limited(reader: impl Read, out: &mut Vec<u8>, limit: usize) -> Result<(), ApiError> {
+    let start = out.len();
+    let result = match reader.take(limit as u64 + 1).read_to_end(out) {
+        Err(e) => Err(ApiError::Content(format!("响应解压失败: {}", e))),
+        Ok(n) if n > limit => Err(ApiError::TooLarge { limit }),
+        Ok(_) => Ok(()),
+    };
+    if result.is_err() {
+        out.truncate(start);
+    }
+    result
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Body {
+    Json(serde_json::Value),
+    Toml(toml::Value),
+    Text(String),
+}
+
+impl Body {
+    // 按 Content-Type 解析，缺省时按纯文本处理
+    pub fn parse(content_type: Option<&str>, bytes: &[u8]) -> Result<Self, ApiError> {
+        let mime = content_type
+            .and_then(|v| v.split(';').next())
+            .map(|v| v.trim().to_ascii_lowercase())
+            .unwrap_or_else(|| "text/plain".to_string());
+        let text = || {
+            String::from_utf8(bytes.to_vec())
+                .map_err(|_| ApiError::Content("响应不是有效的 UTF-8".to_string()))
+        };
+        match mime.as_str() {
+            m if m == "application/json" || m.ends_with("+json") => {
+                Ok(Body::Json(serde_json::from_slice(bytes)?))
+            }
+            "application/toml" | "text/toml" => Ok(Body::Toml(
+                toml::from_str(&text()?).map_err(|e| ApiError::Content(e.to_string()))?,
+            )),
+            m if m.starts_with("text/") => Ok(Body::Text(text()?)),
+            other => Err(ApiError::Content(format!("不支持的内容类型: {}", other))),
+        }
+    }
+
+    // 文本响应只能转换为字符串类型
+    pub fn into_value<T: DeserializeOwned>(self) -> Result<T, ApiError> {
+        match self {
+            Body::Json(value) => Ok(serde_json::from_value(value)?),
+            Body::Toml(value) => {
+                T::deserialize(value).map_err(|e| ApiError::Content(e.to_string()))
+            }
+            Body::Text(text) => Ok(serde_json::from_value(serde_json::Value::String(text))?),
+        }
+    }
+}
+
+// 解压并解析响应体，解压后超过 limit 字节时返回 ApiError::TooLarge
+pub async fn read_body(response: Response, limit: usize) -> Result<Body, ApiError> {
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string)
+    };
+    let encoding = Encoding::from_header(header("content-encoding").as_deref())?;
+    let content_type = header("content-type");
+    let raw = response.bytes().await?;
+    if encoding == Encoding::Identity {
+        return Body::parse(content_type.as_deref(), &raw);
+    }
+    // 解压用的临时缓冲区从共享缓冲池借用
+    let mut decoded = pool::buffers().get();
+    encoding.decode_into(&raw, &mut decoded, limit)?;
+    Body::parse(content_type.as_deref(), &decoded)
+}
+
+impl HttpClient {
+    pub async fn negotiate(&self, path: &str) -> Result<Body, ApiError> {
+        let request = self
+            .request(Method::GET, path)
+            .header("accept", ACCEPT)
+            .header("accept-encoding", ACCEPT_ENCODING);
+        read_body(self.send(request).await?, self.max_decoded_size).await
+    }
+
+    // 按响应的格式反序列化为目标类型
+    pub async fn fetch<T: DeserializeOwned>(&self, path: &str) -> Result<T, ApiError> {
+        self.negotiate(path).await?.into_value()
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tower_layer::Layer;
+use tower_service::Service;
+
+// 连接池相关配置和连接复用统计。ConnectionConfig 可以直接从配置文件反序列化(见 serde_any)。
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ConnectionConfig {
+    // 不经过协商直接使用 HTTP/2，只适用于确定支持 h2c 的服务端
+    pub http2_prior_knowledge: bool,
+    pub max_idle_per_host: Option<usize>,
+    pub idle_timeout_secs: Option<u64>,
+    pub tcp_keepalive_secs: Option<u64>,
+}
+
+impl ConnectionConfig {
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout_secs.map(Duration::from_secs)
+    }
+
+    pub fn tcp_keepalive(&self) -> Option<Duration> {
+        self.tcp_keepalive_secs.map(Duration::from_secs)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    pub(crate) requests: AtomicU64,
+    pub(crate) connections: AtomicU64,
+    pub(crate) hedged: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) fn snapshot(&self) -> ClientStats {
+        ClientStats {
+            requests: self.requests.load(Ordering::Relaxed),
+            connections: self.connections.load(Ordering::Relaxed),
+            hedged: self.hedged.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct ClientStats {
+    pub requests: u64,
+    // 新建连接的次数(包括失败的尝试)
+    pub connections: u64,
+    // get_hedged 实际发出的第二个请求数
+    pub hedged: u64,
+}
+
+impl ClientStats {
+    // 复用已有连接的请求数
+    pub fn reused(&self) -> u64 {
+        self.requests.saturating_sub(self.connections)
+    }
+
+    pub fn reuse_rate(&self) -> f64 {
+        if self.requests == 0 {
+            return 0.0;
+        }
+        self.reused() as f64 / self.requests as f64
+    }
+}
+
+// 包在 reqwest 连接器外面，每次建立新连接时计数
+#[derive(Clone)]
+pub(crate) struct CountConnections(pub(crate) Arc<Counters>);
+
+impl<S> Layer<S> for CountConnections {
+    type Service = Counted<S>;
+
+    fn layer(&self, inner: S) -> Counted<S> {
+        Counted {
+            inner,
+            counters: self.0.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct Counted<S> {
+    inner: S,
+    counters: Arc<Counters>,
+}
+
+impl<S, R> Service<R> for Counted<S>
+where
+    S: Service<R>,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = S::Future;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        self.counters.connections.fetch_add(1, Ordering::Relaxed);
+        self.inner.call(request)
+    }
+}
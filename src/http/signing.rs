@@ -0,0 +1,219 @@
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::Request;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use super::{ApiError, Middleware};
+use crate::clock::{self, SharedClock};
+
+// 请求签名: 对 method + path + body + 时间戳计算 HMAC-SHA256，写入签名头。
+// 不同合作方的待签名串格式不同，通过 Canonicalize 替换；接收方用 verify 校验，
+// 时间戳超出重放窗口的请求会被拒绝。
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "x-signature";
+pub const TIMESTAMP_HEADER: &str = "x-signature-timestamp";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SignatureError {
+    #[error("无效的签名时间戳: {0}")]
+    InvalidTimestamp(String),
+
+    #[error("签名已过期: 时间差 {skew:?} 超出重放窗口")]
+    Expired { skew: Duration },
+
+    #[error("签名不匹配")]
+    Mismatch,
+}
+
+// 参与签名的请求内容，path 包含 query
+#[derive(Debug, Clone, Copy)]
+pub struct SignedParts<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub body: &'a [u8],
+    pub timestamp: u64,
+}
+
+pub trait Canonicalize: Send + Sync {
+    fn canonicalize(&self, parts: &SignedParts<'_>) -> Vec<u8>;
+}
+
+// 默认格式: "METHOD\npath\ntimestamp\nhex(sha256(body))"
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Lines;
+
+impl Canonicalize for Lines {
+    fn canonicalize(&self, parts: &SignedParts<'_>) -> Vec<u8> {
+        format!(
+            "{}\n{}\n{}\n{}",
+            parts.method.to_ascii_uppercase(),
+            parts.path,
+            parts.timestamp,
+            hex::encode(Sha256::digest(parts.body))
+        )
+        .into_bytes()
+    }
+}
+
+// "timestamp.body"，常见于 webhook 签名，不包含 method 和 path
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TimestampDotBody;
+
+impl Canonicalize for TimestampDotBody {
+    fn canonicalize(&self, parts: &SignedParts<'_>) -> Vec<u8> {
+        let mut out = format!("{}.", parts.timestamp).into_bytes();
+        out.extend_from_slice(parts.body);
+        out
+    }
+}
+
+#[derive(Clone)]
+pub struct HmacSigner {
+    secret: Vec<u8>,
+    canonical: Arc<dyn Canonicalize>,
+    clock: SharedClock,
+    signature_header: String,
+    timestamp_header: String,
+    key_id: Option<(String, String)>,
+    replay_window: Duration,
+}
+
+impl HmacSigner {
+    pub fn new(secret: impl Into<Vec<u8>>) -> Self {
+        HmacSigner {
+            secret: secret.into(),
+            canonical: Arc::new(Lines),
+            clock: clock::system(),
+            signature_header: SIGNATURE_HEADER.to_string(),
+            timestamp_header: TIMESTAMP_HEADER.to_string(),
+            key_id: None,
+            replay_window: Duration::from_secs(300),
+        }
+    }
+
+    pub fn canonicalize<C: Canonicalize + 'static>(mut self, canonical: C) -> Self {
+        self.canonical = Arc::new(canonical);
+        self
+    }
+
+    pub fn headers(mut self, signature: &str, timestamp: &str) -> Self {
+        self.signature_header = signature.to_ascii_lowercase();
+        self.timestamp_header = timestamp.to_ascii_lowercase();
+        self
+    }
+
+    // 多个密钥轮换时告诉对方用的是哪一个
+    pub fn key_id(mut self, header: &str, id: &str) -> Self {
+        self.key_id = Some((header.to_ascii_lowercase(), id.to_string()));
+        self
+    }
+
+    // 校验时允许的时间差(两个方向)
+    pub fn replay_window(mut self, window: Duration) -> Self {
+        self.replay_window = window;
+        self
+    }
+
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    fn mac(&self, parts: &SignedParts<'_>) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.secret).expect("HMAC 接受任意长度的密钥");
+        mac.update(&self.canonical.canonicalize(parts));
+        mac
+    }
+
+    // 十六进制小写的签名
+    pub fn sign(&self, parts: &SignedParts<'_>) -> String {
+        hex::encode(self.mac(parts).finalize().into_bytes())
+    }
+
+    fn now(&self) -> u64 {
+        self.clock
+            .system_time()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    // 接收方校验签名，签名比较是常量时间的
+    pub fn verify(
+        &self,
+        method: &str,
+        path: &str,
+        body: &[u8],
+        timestamp: &str,
+        signature: &str,
+    ) -> Result<(), SignatureError> {
+        let timestamp: u64 = timestamp
+            .trim()
+            .parse()
+            .map_err(|_| SignatureError::InvalidTimestamp(timestamp.to_string()))?;
+        let skew = Duration::from_secs(self.now().abs_diff(timestamp));
+        if skew > self.replay_window {
+            return Err(SignatureError::Expired { skew });
+        }
+        let expected = hex::decode(signature.trim()).map_err(|_| SignatureError::Mismatch)?;
+        let parts = SignedParts {
+            method,
+            path,
+            body,
+            timestamp,
+        };
+        self.mac(&parts)
+            .verify_slice(&expected)
+            .map_err(|_| SignatureError::Mismatch)
+    }
+}
+
+fn header(name: &str, value: &str) -> Result<(HeaderName, HeaderValue), ApiError> {
+    let invalid = || ApiError::Middleware(format!("无效的请求头: {}", name));
+    Ok((
+        HeaderName::from_bytes(name.as_bytes()).map_err(|_| invalid())?,
+        HeaderValue::from_str(value).map_err(|_| invalid())?,
+    ))
+}
+
+impl Middleware for HmacSigner {
+    fn handle(&self, request: &mut Request) -> Result<(), ApiError> {
+        // 流式请求体无法在发送前读取
+        let body = match request.body() {
+            Some(body) => body
+                .as_bytes()
+                .ok_or_else(|| ApiError::Middleware("流式请求体无法签名".to_string()))?,
+            None => &[],
+        };
+        let url = request.url();
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let timestamp = self.now();
+        let signature = self.sign(&SignedParts {
+            method: request.method().as_str(),
+            path: &path,
+            body,
+            timestamp,
+        });
+
+        let mut headers = vec![
+            header(&self.signature_header, &signature)?,
+            header(&self.timestamp_header, &timestamp.to_string())?,
+        ];
+        if let Some((name, id)) = &self.key_id {
+            headers.push(header(name, id)?);
+        }
+        for (name, value) in headers {
+            request.headers_mut().insert(name, value);
+        }
+        Ok(())
+    }
+}
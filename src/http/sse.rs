@@ -0,0 +1,241 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use futures_util::stream::{self, Stream};
+use reqwest::Response;
+
+use super::{ApiError, HttpClient, Method};
+use crate::clock::{self, SharedClock};
+use crate::logs;
+use crate::retry::Backoff;
+use crate::status::HttpStatus;
+
+// Server-Sent Events 客户端: 解析 text/event-stream，连接断开或心跳超时后按退避策略重连，
+// 重连时带上 Last-Event-ID 让服务端从断点继续。服务端返回 204 或 4xx 时不再重连。
+
+pub const LAST_EVENT_ID: &str = "last-event-id";
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SseEvent {
+    pub id: Option<String>,
+    // 没有 event 字段时为 "message"
+    pub event: String,
+    pub data: String,
+}
+
+#[derive(Default)]
+struct Parser {
+    buffer: Vec<u8>,
+    id: Option<String>,
+    event: Option<String>,
+    data: Vec<String>,
+    retry: Option<Duration>,
+}
+
+impl Parser {
+    fn feed(&mut self, chunk: &[u8], events: &mut VecDeque<SseEvent>) {
+        self.buffer.extend_from_slice(chunk);
+        while let Some(pos) = self.buffer.iter().position(|&b| b == b'\n') {
+            let mut line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            self.line(&String::from_utf8_lossy(&line), events);
+        }
+    }
+
+    fn line(&mut self, line: &str, events: &mut VecDeque<SseEvent>) {
+        if line.is_empty() {
+            let event = self.event.take();
+            if !self.data.is_empty() {
+                events.push_back(SseEvent {
+                    id: self.id.clone(),
+                    event: event.unwrap_or_else(|| "message".to_string()),
+                    data: std::mem::take(&mut self.data).join("\n"),
+                });
+            }
+            return;
+        }
+        // 以 ':' 开头的是注释，服务端用来发心跳
+        if line.starts_with(':') {
+            return;
+        }
+        let (field, value) = line.split_once(':').unwrap_or((line, ""));
+        let value = value.strip_prefix(' ').unwrap_or(value);
+        match field {
+            "id" if !value.contains('\0') => self.id = Some(value.to_string()),
+            "event" => self.event = Some(value.to_string()),
+            "data" => self.data.push(value.to_string()),
+            "retry" => {
+                if let Ok(ms) = value.parse() {
+                    self.retry = Some(Duration::from_millis(ms));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // 连接断开时丢弃未完成的事件，保留 id
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.event = None;
+        self.data.clear();
+    }
+}
+
+pub struct EventStream {
+    client: HttpClient,
+    url: String,
+    clock: SharedClock,
+    backoff: Backoff,
+    heartbeat_timeout: Duration,
+    response: Option<Response>,
+    parser: Parser,
+    pending: VecDeque<SseEvent>,
+    // 连续失败次数，收到数据后清零
+    failures: usize,
+    reconnects: u64,
+    closed: bool,
+}
+
+impl EventStream {
+    fn new(client: HttpClient, url: &str) -> Self {
+        EventStream {
+            client,
+            url: url.to_string(),
+            clock: clock::system(),
+            backoff: Backoff::new(Duration::from_millis(500), Duration::from_secs(30)),
+            heartbeat_timeout: Duration::from_secs(60),
+            response: None,
+            parser: Parser::default(),
+            pending: VecDeque::new(),
+            failures: 0,
+            reconnects: 0,
+            closed: false,
+        }
+    }
+
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    // 超过这个时间没有收到任何数据(包括注释心跳)就认为连接已失效
+    pub fn heartbeat_timeout(mut self, timeout: Duration) -> Self {
+        self.heartbeat_timeout = timeout;
+        self
+    }
+
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.parser.id.as_deref()
+    }
+
+    pub fn reconnects(&self) -> u64 {
+        self.reconnects
+    }
+
+    async fn connect(&mut self) -> Result<Option<Response>, ApiError> {
+        let mut request = self
+            .client
+            .request(Method::GET, &self.url)
+            .header("accept", "text/event-stream")
+            .header("cache-control", "no-cache");
+        if let Some(id) = &self.parser.id {
+            request = request.header(LAST_EVENT_ID, id);
+        }
+        let response = self.client.send(request).await?;
+        if response.status().as_u16() == 204 {
+            return Ok(None);
+        }
+        Ok(Some(response))
+    }
+
+    // 第一次重连使用服务端 retry 字段指定的间隔，之后按退避策略
+    async fn wait_before_reconnect(&self) {
+        let delay = match self.parser.retry {
+            Some(retry) if self.failures <= 1 => retry,
+            _ => self.backoff.delay(self.failures),
+        };
+        self.clock.sleep(delay).await;
+    }
+
+    // 下一个事件；流结束(服务端要求停止、不可恢复的错误或重试次数用完)时返回 None
+    pub async fn next(&mut self) -> Option<Result<SseEvent, ApiError>> {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(Ok(event));
+            }
+            if self.closed {
+                return None;
+            }
+
+            let Some(response) = self.response.as_mut() else {
+                if self.failures >= self.backoff.max_attempts {
+                    self.closed = true;
+                    continue;
+                }
+                if self.failures > 0 {
+                    self.wait_before_reconnect().await;
+                    self.reconnects += 1;
+                }
+                match self.connect().await {
+                    Ok(Some(response)) => self.response = Some(response),
+                    Ok(None) => self.closed = true,
+                    Err(e) => {
+                        self.failures += 1;
+                        let fatal =
+                            e.is_client_error() && !matches!(e, ApiError::RateLimited { .. });
+                        if fatal || self.failures >= self.backoff.max_attempts {
+                            self.closed = true;
+                            return Some(Err(e));
+                        }
+                        logs::warn("sse", &format!("连接 {} 失败，准备重连: {}", self.url, e));
+                    }
+                }
+                continue;
+            };
+
+            let chunk = tokio::select! {
+                chunk = response.chunk() => chunk,
+                _ = self.clock.sleep(self.heartbeat_timeout) => {
+                    logs::warn("sse", &format!("{} 心跳超时，准备重连", self.url));
+                    Ok(None)
+                }
+            };
+            match chunk {
+                Ok(Some(bytes)) => {
+                    self.failures = 0;
+                    self.parser.feed(&bytes, &mut self.pending);
+                }
+                Ok(None) | Err(_) => {
+                    self.response = None;
+                    self.parser.reset();
+                    self.failures += 1;
+                }
+            }
+        }
+    }
+}
+
+impl EventStream {
+    // 转换成 futures::Stream，便于和 streamx 中的组合子一起使用
+    pub fn into_stream(self) -> impl Stream<Item = Result<SseEvent, ApiError>> {
+        stream::unfold(self, |mut events| async move {
+            let event = events.next().await?;
+            Some((event, events))
+        })
+    }
+}
+
+impl HttpClient {
+    // 订阅 SSE 流，第一次调用 next 时才建立连接
+    pub fn sse(&self, url: &str) -> EventStream {
+        EventStream::new(self.clone(), url)
+    }
+}
@@ -0,0 +1,444 @@
+use std::fmt;
+use std::io::{self, BufRead};
+use std::sync::Arc;
+
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::SqlitePool;
+use thiserror::Error;
+
+use crate::arena::{StrArena, StrHandle};
+use crate::db::DbError;
+use crate::reports::Format;
+
+// 数据导入: 解析 CSV 或 JSON Lines，每条记录按列规则校验，
+// 合格的记录分批插入，不合格的记录连同行号和原因写进报告，不影响其他记录。
+
+#[derive(Error, Debug)]
+pub enum ImportError {
+    #[error("无效的表名或列名: {0}")]
+    InvalidIdentifier(String),
+
+    #[error("CSV 缺少表头")]
+    MissingHeader,
+
+    #[error(transparent)]
+    Db(#[from] DbError),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl From<sqlx::Error> for ImportError {
+    fn from(e: sqlx::Error) -> Self {
+        ImportError::Db(e.into())
+    }
+}
+
+type Check = Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync>;
+
+#[derive(Clone)]
+pub enum Rule {
+    Required,
+    Integer,
+    Number,
+    MaxLen(usize),
+    Pattern(Regex),
+    OneOf(Vec<String>),
+    Custom(Check),
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::Required => write!(f, "Required"),
+            Rule::Integer => write!(f, "Integer"),
+            Rule::Number => write!(f, "Number"),
+            Rule::MaxLen(n) => write!(f, "MaxLen({})", n),
+            Rule::Pattern(re) => write!(f, "Pattern({})", re),
+            Rule::OneOf(values) => write!(f, "OneOf({:?})", values),
+            Rule::Custom(_) => write!(f, "Custom"),
+        }
+    }
+}
+
+impl Rule {
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Rule::Custom(Arc::new(f))
+    }
+
+    // 空值只由 Required 检查
+    fn check(&self, value: Option<&str>) -> Result<(), String> {
+        let value = match (self, value) {
+            (Rule::Required, None) => return Err("不能为空".to_string()),
+            (_, None) => return Ok(()),
+            (_, Some(v)) => v,
+        };
+        match self {
+            Rule::Required => Ok(()),
+            Rule::Integer => value
+                .parse::<i64>()
+                .map(|_| ())
+                .map_err(|_| format!("不是整数: {}", value)),
+            Rule::Number => value
+                .parse::<f64>()
+                .map(|_| ())
+                .map_err(|_| format!("不是数字: {}", value)),
+            Rule::MaxLen(n) if value.chars().count() > *n => Err(format!("长度超过 {}", n)),
+            Rule::MaxLen(_) => Ok(()),
+            Rule::Pattern(re) if !re.is_match(value) => Err(format!("格式不符合 {}", re)),
+            Rule::Pattern(_) => Ok(()),
+            Rule::OneOf(values) if !values.iter().any(|v| v == value) => {
+                Err(format!("取值必须是 {:?} 之一", values))
+            }
+            Rule::OneOf(_) => Ok(()),
+            Rule::Custom(f) => f(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct ColumnSpec {
+    name: String,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportSpec {
+    table: String,
+    format: Format,
+    columns: Vec<ColumnSpec>,
+    batch_size: usize,
+}
+
+impl ImportSpec {
+    pub fn new(table: &str, format: Format) -> Self {
+        ImportSpec {
+            table: table.to_string(),
+            format,
+            columns: Vec::new(),
+            batch_size: 500,
+        }
+    }
+
+    // 只导入声明过的列，输入中的其他字段被忽略
+    pub fn column(mut self, name: &str, rules: Vec<Rule>) -> Self {
+        self.columns.push(ColumnSpec {
+            name: name.to_string(),
+            rules,
+        });
+        self
+    }
+
+    pub fn batch_size(mut self, n: usize) -> Self {
+        self.batch_size = n.max(1);
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rejected {
+    // 记录开始的行号，从 1 开始(CSV 表头是第 1 行)
+    pub line: usize,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ImportReport {
+    pub inserted: u64,
+    pub rejected: Vec<Rejected>,
+}
+
+fn valid_identifier(name: &str) -> Result<(), ImportError> {
+    let ok = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit());
+    if ok {
+        Ok(())
+    } else {
+        Err(ImportError::InvalidIdentifier(name.to_string()))
+    }
+}
+
+pub async fn run<R: BufRead>(
+    pool: &SqlitePool,
+    reader: R,
+    spec: &ImportSpec,
+) -> Result<ImportReport, ImportError> {
+    valid_identifier(&spec.table)?;
+    for column in &spec.columns {
+        valid_identifier(&column.name)?;
+    }
+
+    let mut report = ImportReport::default();
+    let mut batch: Vec<Vec<Option<String>>> = Vec::new();
+    let columns: Vec<&str> = spec.columns.iter().map(|c| c.name.as_str()).collect();
+    let mut records = Records::new(reader, spec.format, &columns)?;
+    while let Some((line, record)) = records.next_record()? {
+        let values = match record {
+            Ok(values) => values,
+            Err(reason) => {
+                report.rejected.push(Rejected {
+                    line,
+                    reasons: vec![reason],
+                });
+                continue;
+            }
+        };
+
+        let reasons: Vec<String> = spec
+            .columns
+            .iter()
+            .zip(&values)
+            .flat_map(|(column, value)| {
+                column
+                    .rules
+                    .iter()
+                    .filter_map(|rule| rule.check(value.as_deref()).err())
+                    .map(|reason| format!("{}: {}", column.name, reason))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        if !reasons.is_empty() {
+            report.rejected.push(Rejected { line, reasons });
+            continue;
+        }
+
+        batch.push(values);
+        if batch.len() >= spec.batch_size {
+            report.inserted += insert(pool, spec, &batch).await?;
+            batch.clear();
+        }
+    }
+    if !batch.is_empty() {
+        report.inserted += insert(pool, spec, &batch).await?;
+    }
+    Ok(report)
+}
+
+// 一批记录用一条多行 INSERT，放在同一个事务里
+async fn insert(
+    pool: &SqlitePool,
+    spec: &ImportSpec,
+    rows: &[Vec<Option<String>>],
+) -> Result<u64, ImportError> {
+    let columns: Vec<&str> = spec.columns.iter().map(|c| c.name.as_str()).collect();
+    let placeholders = format!("({})", vec!["?"; columns.len()].join(", "));
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES {}",
+        spec.table,
+        columns.join(", "),
+        vec![placeholders.as_str(); rows.len()].join(", ")
+    );
+    let mut query = sqlx::query(&sql);
+    for row in rows {
+        for value in row {
+            query = query.bind(value.clone());
+        }
+    }
+    let mut tx = pool.begin().await?;
+    let inserted = query.execute(&mut *tx).await?.rows_affected();
+    tx.commit().await?;
+    Ok(inserted)
+}
+
+// 流式 CSV 解析，字段存放在复用的 arena 里，读取下一条记录时覆盖
+pub struct CsvRecords<R> {
+    reader: R,
+    line: usize,
+    text: String,
+    arena: StrArena,
+    fields: Vec<StrHandle>,
+}
+
+// 一条 CSV 记录，借用 CsvRecords 内部的缓冲区
+pub struct CsvRecord<'a> {
+    arena: &'a StrArena,
+    fields: &'a [StrHandle],
+}
+
+impl CsvRecord<'_> {
+    pub fn len(&self) -> usize {
+        self.fields.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.fields.get(index).map(|&h| self.arena.get(h))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|&h| self.arena.get(h))
+    }
+}
+
+impl<R: BufRead> CsvRecords<R> {
+    pub fn new(reader: R) -> Self {
+        CsvRecords {
+            reader,
+            line: 0,
+            text: String::new(),
+            arena: StrArena::with_capacity(1024),
+            fields: Vec::new(),
+        }
+    }
+
+    // 已读取的行数
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    // 读取一条记录，引号内的字段可以跨行
+    pub fn next_record(&mut self) -> io::Result<Option<CsvRecord<'_>>> {
+        self.arena.clear();
+        self.fields.clear();
+        let mut quoted = false;
+        loop {
+            self.text.clear();
+            if self.reader.read_line(&mut self.text)? == 0 {
+                if self.fields.is_empty() && self.arena.bytes() == 0 && !quoted {
+                    return Ok(None);
+                }
+                self.fields.push(self.arena.finish());
+                return Ok(Some(self.record()));
+            }
+            self.line += 1;
+            let mut chars = self.text.chars().peekable();
+            while let Some(c) = chars.next() {
+                match (quoted, c) {
+                    (true, '"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        self.arena.push('"');
+                    }
+                    (true, '"') => quoted = false,
+                    (true, c) => self.arena.push(c),
+                    (false, '"') => quoted = true,
+                    (false, ',') => self.fields.push(self.arena.finish()),
+                    (false, '\r') if chars.peek() == Some(&'\n') => {}
+                    (false, '\n') => {
+                        self.fields.push(self.arena.finish());
+                        return Ok(Some(self.record()));
+                    }
+                    (false, c) => self.arena.push(c),
+                }
+            }
+        }
+    }
+
+    fn record(&self) -> CsvRecord<'_> {
+        CsvRecord {
+            arena: &self.arena,
+            fields: &self.fields,
+        }
+    }
+}
+
+// (起始行号, 各列的值或解析错误)
+type Row = (usize, Result<Vec<Option<String>>, String>);
+
+enum Source<R> {
+    // 每个导入列在表头中的位置，表头中没有的列为 None
+    Csv {
+        records: CsvRecords<R>,
+        header_len: usize,
+        indices: Vec<Option<usize>>,
+    },
+    JsonLines {
+        reader: R,
+        line: usize,
+    },
+}
+
+// 按导入列的顺序读出每条记录的值，只为需要的列分配字符串
+struct Records<'c, R> {
+    source: Source<R>,
+    columns: &'c [&'c str],
+}
+
+impl<'c, R: BufRead> Records<'c, R> {
+    fn new(reader: R, format: Format, columns: &'c [&'c str]) -> Result<Self, ImportError> {
+        let source = match format {
+            Format::Csv => {
+                let mut records = CsvRecords::new(reader);
+                let header: Vec<String> = records
+                    .next_record()?
+                    .ok_or(ImportError::MissingHeader)?
+                    .iter()
+                    .map(str::to_string)
+                    .collect();
+                Source::Csv {
+                    records,
+                    header_len: header.len(),
+                    indices: columns
+                        .iter()
+                        .map(|c| header.iter().position(|h| h == c))
+                        .collect(),
+                }
+            }
+            Format::JsonLines => Source::JsonLines { reader, line: 0 },
+        };
+        Ok(Records { source, columns })
+    }
+
+    fn next_record(&mut self) -> io::Result<Option<Row>> {
+        match &mut self.source {
+            Source::Csv {
+                records,
+                header_len,
+                indices,
+            } => loop {
+                let start = records.line() + 1;
+                let Some(record) = records.next_record()? else {
+                    return Ok(None);
+                };
+                if record.len() == 1 && record.get(0) == Some("") {
+                    continue;
+                }
+                if record.len() != *header_len {
+                    let reason = format!("字段数 {} 与表头 {} 不一致", record.len(), header_len);
+                    return Ok(Some((start, Err(reason))));
+                }
+                let values = indices
+                    .iter()
+                    .map(|i| {
+                        i.and_then(|i| record.get(i))
+                            .filter(|v| !v.is_empty())
+                            .map(str::to_string)
+                    })
+                    .collect();
+                return Ok(Some((start, Ok(values))));
+            },
+            Source::JsonLines { reader, line } => loop {
+                let mut text = String::new();
+                if reader.read_line(&mut text)? == 0 {
+                    return Ok(None);
+                }
+                *line += 1;
+                if text.trim().is_empty() {
+                    continue;
+                }
+                let record = match serde_json::from_str::<Value>(&text) {
+                    Ok(Value::Object(mut map)) => Ok(self
+                        .columns
+                        .iter()
+                        .map(|c| match map.remove(*c) {
+                            None | Some(Value::Null) => None,
+                            Some(Value::String(s)) => Some(s),
+                            Some(other) => Some(other.to_string()),
+                        })
+                        .collect()),
+                    Ok(_) => Err("不是 JSON 对象".to_string()),
+                    Err(e) => Err(format!("无效的 JSON: {}", e)),
+                };
+                return Ok(Some((*line, record)));
+            },
+        }
+    }
+}
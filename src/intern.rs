@@ -0,0 +1,175 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::{Mutex, OnceLock};
+
+use serde::Serialize;
+
+// 全局字符串驻留表: 重复出现的字符串(指标名、日志 target、配置 key)只保存一份，
+// 调用方持有 Copy 的 Symbol。字符串一旦驻留就不会释放，只适合取值范围有限的名字，
+// 不要驻留用户输入。reset 清空表和统计(主要用于测试)，之前得到的 Symbol 仍然有效。
+
+#[derive(Clone, Copy)]
+pub struct Symbol(&'static str);
+
+impl Symbol {
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+// 同一张表里相同的字符串指针相同，先比较指针；reset 前后的 Symbol 按内容比较
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0) || self.0 == other.0
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(other.0)
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        self.0
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.0, f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.0)
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0)
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(s: &str) -> Self {
+        intern(s)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct InternStats {
+    pub symbols: usize,
+    // 表中字符串的总字节数
+    pub bytes: usize,
+    pub lookups: u64,
+    // 如果每次都分配新字符串需要的总字节数
+    pub requested_bytes: u64,
+}
+
+impl InternStats {
+    pub fn saved_bytes(&self) -> u64 {
+        self.requested_bytes.saturating_sub(self.bytes as u64)
+    }
+}
+
+#[derive(Default)]
+struct Table {
+    // 值是命中次数
+    symbols: HashMap<&'static str, u64>,
+    stats: InternStats,
+}
+
+fn table() -> &'static Mutex<Table> {
+    static TABLE: OnceLock<Mutex<Table>> = OnceLock::new();
+    TABLE.get_or_init(Default::default)
+}
+
+pub fn intern(s: &str) -> Symbol {
+    let mut table = table().lock().unwrap();
+    table.stats.lookups += 1;
+    table.stats.requested_bytes += s.len() as u64;
+    if let Some((&existing, hits)) = table.symbols.get_key_value(s) {
+        let hits = *hits;
+        table.symbols.insert(existing, hits + 1);
+        return Symbol(existing);
+    }
+    let leaked: &'static str = Box::leak(s.to_string().into_boxed_str());
+    table.symbols.insert(leaked, 1);
+    table.stats.symbols += 1;
+    table.stats.bytes += s.len();
+    Symbol(leaked)
+}
+
+// 只查找不驻留
+pub fn get(s: &str) -> Option<Symbol> {
+    let table = table().lock().unwrap();
+    table.symbols.get_key_value(s).map(|(&k, _)| Symbol(k))
+}
+
+pub fn stats() -> InternStats {
+    table().lock().unwrap().stats
+}
+
+// 按命中次数从多到少，次数相同按字符串排序
+pub fn dump() -> Vec<(Symbol, u64)> {
+    let table = table().lock().unwrap();
+    let mut entries: Vec<(Symbol, u64)> = table
+        .symbols
+        .iter()
+        .map(|(&s, &hits)| (Symbol(s), hits))
+        .collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    entries
+}
+
+// 调试用的文本形式，每行 "命中次数 字符串"
+pub fn dump_text() -> String {
+    let stats = stats();
+    let mut out = format!(
+        "symbols={} bytes={} lookups={} saved_bytes={}\n",
+        stats.symbols,
+        stats.bytes,
+        stats.lookups,
+        stats.saved_bytes()
+    );
+    for (symbol, hits) in dump() {
+        out.push_str(&format!("{:>8} {}\n", hits, symbol));
+    }
+    out
+}
+
+pub fn reset() {
+    let mut table = table().lock().unwrap();
+    table.symbols.clear();
+    table.stats = InternStats::default();
+}
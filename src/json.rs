@@ -0,0 +1,260 @@
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum JsonError {
+    #[error("查询表达式无效: {0}")]
+    InvalidQuery(String),
+    #[error("JSON 解析失败: {0}")]
+    Parse(#[from] serde_json::Error),
+    #[error("schema 无效: {0}")]
+    InvalidSchema(String),
+    #[error("schema 校验失败: {}", join_violations(.0))]
+    Validation(Vec<Violation>),
+}
+
+// 一条校验失败记录，path 为出错值的 JSON Pointer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "/"
+        } else {
+            &self.path
+        };
+        write!(f, "{}: {}", path, self.message)
+    }
+}
+
+fn join_violations(violations: &[Violation]) -> String {
+    violations
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+// 编译后的 JSON Schema，可以重复使用
+pub struct Schema {
+    validator: jsonschema::Validator,
+}
+
+impl Schema {
+    pub fn compile(schema: &Value) -> Result<Self, JsonError> {
+        let validator = jsonschema::validator_for(schema)
+            .map_err(|e| JsonError::InvalidSchema(e.to_string()))?;
+        Ok(Schema { validator })
+    }
+
+    // 收集所有不符合 schema 的地方，而不是遇到第一个就返回
+    pub fn validate(&self, value: &Value) -> Result<(), JsonError> {
+        let violations: Vec<Violation> = self
+            .validator
+            .iter_errors(value)
+            .map(|e| Violation {
+                path: e.instance_path.to_string(),
+                message: e.to_string(),
+            })
+            .collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(JsonError::Validation(violations))
+        }
+    }
+}
+
+// 先按 schema 校验再反序列化，避免部分字段错误的数据被悄悄接受
+pub fn parse_validated<T: DeserializeOwned>(bytes: &[u8], schema: &Schema) -> Result<T, JsonError> {
+    let value: Value = serde_json::from_slice(bytes)?;
+    schema.validate(&value)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+// JSON Pointer 取值，例如 get_path(&v, "/items/0/name")
+pub fn get_path<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    value.pointer(path)
+}
+
+// 在 JSON Pointer 的基础上支持:
+//   `*`                 匹配数组的所有元素或对象的所有值
+//   `[field op value]`  过滤数组元素，op 为 == != > >= < <=，field 可以用 `.` 访问嵌套字段
+// 例如 query(&v, "/items/[price>10]/name")
+pub fn query<'a>(value: &'a Value, expr: &str) -> Result<Vec<&'a Value>, JsonError> {
+    let mut current = vec![value];
+    for segment in split_segments(expr)? {
+        let mut next = Vec::new();
+        match segment {
+            Segment::Key(key) => {
+                for v in current {
+                    let child = match v {
+                        Value::Object(map) => map.get(&key),
+                        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+                        _ => None,
+                    };
+                    next.extend(child);
+                }
+            }
+            Segment::Wildcard => {
+                for v in current {
+                    next.extend(children(v));
+                }
+            }
+            Segment::Filter(filter) => {
+                for v in current {
+                    next.extend(children(v).into_iter().filter(|c| filter.matches(c)));
+                }
+            }
+        }
+        current = next;
+    }
+    Ok(current)
+}
+
+// 只取第一个匹配结果
+pub fn query_one<'a>(value: &'a Value, expr: &str) -> Result<Option<&'a Value>, JsonError> {
+    Ok(query(value, expr)?.into_iter().next())
+}
+
+fn children(value: &Value) -> Vec<&Value> {
+    match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(map) => map.values().collect(),
+        _ => Vec::new(),
+    }
+}
+
+enum Segment {
+    Key(String),
+    Wildcard,
+    Filter(Filter),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+struct Filter {
+    field: Vec<String>,
+    op: Op,
+    expected: Value,
+}
+
+impl Filter {
+    fn parse(expr: &str) -> Result<Self, JsonError> {
+        // 两个字符的运算符要先匹配
+        const OPS: [(&str, Op); 6] = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            (">=", Op::Ge),
+            ("<=", Op::Le),
+            (">", Op::Gt),
+            ("<", Op::Lt),
+        ];
+        for (token, op) in OPS {
+            if let Some(pos) = expr.find(token) {
+                let field = expr[..pos].trim();
+                let literal = expr[pos + token.len()..].trim();
+                if field.is_empty() || literal.is_empty() {
+                    break;
+                }
+                // 字面量按 JSON 解析，解析失败时当作裸字符串
+                let expected = serde_json::from_str(literal)
+                    .unwrap_or_else(|_| Value::String(literal.to_string()));
+                return Ok(Filter {
+                    field: field.split('.').map(str::to_string).collect(),
+                    op,
+                    expected,
+                });
+            }
+        }
+        Err(JsonError::InvalidQuery(format!("[{}]", expr)))
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        let mut actual = value;
+        for key in &self.field {
+            match actual.get(key) {
+                Some(v) => actual = v,
+                None => return false,
+            }
+        }
+        match self.op {
+            Op::Eq => actual == &self.expected,
+            Op::Ne => actual != &self.expected,
+            op => {
+                let ordering = match (actual, &self.expected) {
+                    (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+                    (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+                    _ => None,
+                };
+                match ordering {
+                    Some(o) => match op {
+                        Op::Gt => o.is_gt(),
+                        Op::Ge => o.is_ge(),
+                        Op::Lt => o.is_lt(),
+                        _ => o.is_le(),
+                    },
+                    None => false,
+                }
+            }
+        }
+    }
+}
+
+fn split_segments(expr: &str) -> Result<Vec<Segment>, JsonError> {
+    if expr.is_empty() {
+        return Ok(Vec::new());
+    }
+    let rest = expr
+        .strip_prefix('/')
+        .ok_or_else(|| JsonError::InvalidQuery(format!("必须以 / 开头: {}", expr)))?;
+
+    // 过滤条件中的字面量可能包含 `/`，方括号内不切分
+    let mut raw = Vec::new();
+    let mut start = 0;
+    let mut depth = 0;
+    let mut in_string = false;
+    for (i, c) in rest.char_indices() {
+        match c {
+            '"' if depth > 0 => in_string = !in_string,
+            '[' if !in_string => depth += 1,
+            ']' if !in_string => depth -= 1,
+            '/' if depth == 0 => {
+                raw.push(&rest[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 || in_string {
+        return Err(JsonError::InvalidQuery(format!("括号不匹配: {}", expr)));
+    }
+    raw.push(&rest[start..]);
+
+    raw.into_iter()
+        .map(|s| {
+            if s == "*" {
+                Ok(Segment::Wildcard)
+            } else if let Some(inner) = s.strip_prefix('[').and_then(|s| s.strip_suffix(']')) {
+                Filter::parse(inner).map(Segment::Filter)
+            } else {
+                Ok(Segment::Key(s.replace("~1", "/").replace("~0", "~")))
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,306 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use thiserror::Error;
+
+use crate::clock::{self, SharedClock};
+use crate::db::DbError;
+
+// 复式记账: 每笔交易由若干分录组成，所有分录金额之和必须为 0。
+// 金额统一使用最小货币单位(分)的整数，余额约束在数据库事务内检查。
+
+#[derive(Error, Debug)]
+pub enum LedgerError {
+    #[error("交易至少需要两条分录")]
+    TooFewPostings,
+
+    #[error("分录金额不能为 0")]
+    ZeroAmount,
+
+    #[error("交易借贷不平衡: 合计 {0}")]
+    Unbalanced(i64),
+
+    #[error("分录金额超出范围")]
+    AmountOutOfRange,
+
+    #[error("账户不存在: {0}")]
+    AccountNotFound(String),
+
+    #[error("余额不足: 账户 {account} 需要 {required}，当前 {available}")]
+    InsufficientFunds {
+        account: String,
+        required: i64,
+        available: i64,
+    },
+
+    #[error(transparent)]
+    Db(#[from] DbError),
+}
+
+impl From<sqlx::Error> for LedgerError {
+    fn from(e: sqlx::Error) -> Self {
+        LedgerError::Db(e.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct Account {
+    pub id: i64,
+    pub name: String,
+    // 外部资金来源等账户允许为负
+    pub allow_negative: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Posting {
+    pub account: i64,
+    pub amount: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow)]
+pub struct Entry {
+    pub transaction_id: i64,
+    pub description: String,
+    pub amount: i64,
+    pub created_at_ms: i64,
+}
+
+#[derive(Clone)]
+pub struct Ledger {
+    pool: SqlitePool,
+    clock: SharedClock,
+}
+
+fn millis(t: SystemTime) -> i64 {
+    t.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn transfer_postings(from: i64, to: i64, amount: i64) -> Result<[Posting; 2], LedgerError> {
+    Ok([
+        Posting {
+            account: from,
+            amount: amount.checked_neg().ok_or(LedgerError::AmountOutOfRange)?,
+        },
+        Posting {
+            account: to,
+            amount,
+        },
+    ])
+}
+
+impl Ledger {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self::with_clock(pool, clock::system())
+    }
+
+    pub fn with_clock(pool: SqlitePool, clock: SharedClock) -> Self {
+        Ledger { pool, clock }
+    }
+
+    pub fn pool(&self) -> &SqlitePool {
+        &self.pool
+    }
+
+    pub async fn migrate(&self) -> Result<(), LedgerError> {
+        sqlx::raw_sql(
+            "CREATE TABLE IF NOT EXISTS ledger_accounts (
+                id INTEGER PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                allow_negative INTEGER NOT NULL DEFAULT 0
+            );
+            CREATE TABLE IF NOT EXISTS ledger_transactions (
+                id INTEGER PRIMARY KEY,
+                description TEXT NOT NULL,
+                created_at_ms INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS ledger_postings (
+                id INTEGER PRIMARY KEY,
+                transaction_id INTEGER NOT NULL REFERENCES ledger_transactions(id),
+                account_id INTEGER NOT NULL REFERENCES ledger_accounts(id),
+                amount INTEGER NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS ledger_postings_account
+                ON ledger_postings(account_id, transaction_id);",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn open_account(
+        &self,
+        name: &str,
+        allow_negative: bool,
+    ) -> Result<Account, LedgerError> {
+        let id = sqlx::query("INSERT INTO ledger_accounts (name, allow_negative) VALUES (?, ?)")
+            .bind(name)
+            .bind(allow_negative)
+            .execute(&self.pool)
+            .await?
+            .last_insert_rowid();
+        Ok(Account {
+            id,
+            name: name.to_string(),
+            allow_negative,
+        })
+    }
+
+    pub async fn account(&self, name: &str) -> Result<Account, LedgerError> {
+        sqlx::query_as("SELECT id, name, allow_negative FROM ledger_accounts WHERE name = ?")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| LedgerError::AccountNotFound(name.to_string()))
+    }
+
+    // 在一个数据库事务中写入交易，余额不足时整体回滚，返回交易 id
+    pub async fn post(&self, description: &str, postings: &[Posting]) -> Result<i64, LedgerError> {
+        let mut tx = self.pool.begin().await?;
+        let id = self.post_in(&mut tx, description, postings).await?;
+        tx.commit().await?;
+        Ok(id)
+    }
+
+    // 在调用方的事务中写入交易，和其他写入一起提交；出错时调用方应回滚
+    pub async fn post_in(
+        &self,
+        tx: &mut SqliteConnection,
+        description: &str,
+        postings: &[Posting],
+    ) -> Result<i64, LedgerError> {
+        if postings.len() < 2 {
+            return Err(LedgerError::TooFewPostings);
+        }
+        if postings.iter().any(|p| p.amount == 0) {
+            return Err(LedgerError::ZeroAmount);
+        }
+        // 金额取反或合计溢出的交易直接拒绝，不能让它回绕成看似平衡的结果
+        if postings.iter().any(|p| p.amount.checked_neg().is_none()) {
+            return Err(LedgerError::AmountOutOfRange);
+        }
+        let sum = postings
+            .iter()
+            .try_fold(0i64, |sum, p| sum.checked_add(p.amount))
+            .ok_or(LedgerError::AmountOutOfRange)?;
+        if sum != 0 {
+            return Err(LedgerError::Unbalanced(sum));
+        }
+
+        let id = sqlx::query(
+            "INSERT INTO ledger_transactions (description, created_at_ms) VALUES (?, ?)",
+        )
+        .bind(description)
+        .bind(millis(self.clock.system_time()))
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+        for posting in postings {
+            let account =
+                sqlx::query("SELECT name, allow_negative FROM ledger_accounts WHERE id = ?")
+                    .bind(posting.account)
+                    .fetch_optional(&mut *tx)
+                    .await?
+                    .ok_or_else(|| LedgerError::AccountNotFound(posting.account.to_string()))?;
+            sqlx::query(
+                "INSERT INTO ledger_postings (transaction_id, account_id, amount) VALUES (?, ?, ?)",
+            )
+            .bind(id)
+            .bind(posting.account)
+            .bind(posting.amount)
+            .execute(&mut *tx)
+            .await?;
+
+            let allow_negative: bool = account.get(1);
+            if posting.amount < 0 && !allow_negative {
+                let balance: i64 = sqlx::query_scalar(
+                    "SELECT COALESCE(SUM(amount), 0) FROM ledger_postings WHERE account_id = ?",
+                )
+                .bind(posting.account)
+                .fetch_one(&mut *tx)
+                .await?;
+                if balance < 0 {
+                    return Err(LedgerError::InsufficientFunds {
+                        account: account.get(0),
+                        required: posting
+                            .amount
+                            .checked_neg()
+                            .ok_or(LedgerError::AmountOutOfRange)?,
+                        available: balance
+                            .checked_sub(posting.amount)
+                            .ok_or(LedgerError::AmountOutOfRange)?,
+                    });
+                }
+            }
+        }
+        Ok(id)
+    }
+
+    pub async fn transfer(
+        &self,
+        from: i64,
+        to: i64,
+        amount: i64,
+        description: &str,
+    ) -> Result<i64, LedgerError> {
+        self.post(description, &transfer_postings(from, to, amount)?)
+            .await
+    }
+
+    pub async fn transfer_in(
+        &self,
+        tx: &mut SqliteConnection,
+        from: i64,
+        to: i64,
+        amount: i64,
+        description: &str,
+    ) -> Result<i64, LedgerError> {
+        self.post_in(tx, description, &transfer_postings(from, to, amount)?)
+            .await
+    }
+
+    pub async fn balance(&self, account: i64) -> Result<i64, LedgerError> {
+        Ok(sqlx::query_scalar(
+            "SELECT COALESCE(SUM(amount), 0) FROM ledger_postings WHERE account_id = ?",
+        )
+        .bind(account)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    // 截止到某个时间点(含)的余额
+    pub async fn balance_at(&self, account: i64, at: SystemTime) -> Result<i64, LedgerError> {
+        Ok(sqlx::query_scalar(
+            "SELECT COALESCE(SUM(p.amount), 0) FROM ledger_postings p
+             JOIN ledger_transactions t ON t.id = p.transaction_id
+             WHERE p.account_id = ? AND t.created_at_ms <= ?",
+        )
+        .bind(account)
+        .bind(millis(at))
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    // 账户流水，按交易顺序
+    pub async fn entries(&self, account: i64) -> Result<Vec<Entry>, LedgerError> {
+        Ok(sqlx::query_as(
+            "SELECT t.id AS transaction_id, t.description, p.amount, t.created_at_ms
+             FROM ledger_postings p JOIN ledger_transactions t ON t.id = p.transaction_id
+             WHERE p.account_id = ? ORDER BY t.id",
+        )
+        .bind(account)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    // 所有账户余额之和，复式记账下恒为 0
+    pub async fn trial_balance(&self) -> Result<i64, LedgerError> {
+        Ok(
+            sqlx::query_scalar("SELECT COALESCE(SUM(amount), 0) FROM ledger_postings")
+                .fetch_one(&self.pool)
+                .await?,
+        )
+    }
+}
@@ -0,0 +1,77 @@
+pub mod actor;
+pub mod admin;
+pub mod anonymize;
+pub mod app;
+pub mod archive;
+pub mod arena;
+pub mod backup;
+pub mod batcher;
+pub mod bench;
+pub mod blobs;
+pub mod bulkhead;
+pub mod cache;
+pub mod chaos;
+pub mod checksum;
+pub mod chunker;
+pub mod clock;
+pub mod codec;
+pub mod collections;
+pub mod config;
+pub mod container;
+pub mod context;
+pub mod crash;
+pub mod dag;
+pub mod db;
+pub mod debounce;
+pub mod degrade;
+pub mod delta;
+pub mod diff;
+pub mod fs_util;
+pub mod fx;
+pub mod http;
+pub mod import;
+pub mod intern;
+pub mod json;
+pub mod ledger;
+pub mod locks;
+pub mod logs;
+pub mod logscan;
+pub mod mail;
+pub mod metrics;
+pub mod notify;
+pub mod openapi;
+pub mod otlp;
+pub mod page;
+pub mod payments;
+pub mod pipeline;
+pub mod plugins;
+pub mod pool;
+pub mod proc;
+pub mod quota;
+pub mod quota_fs;
+pub mod ratelimit;
+pub mod rbac;
+pub mod redact;
+pub mod reports;
+pub mod retry;
+pub mod ring;
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
+pub mod search;
+pub mod selftest;
+pub mod semaphore;
+pub mod serde_any;
+pub mod signals;
+pub mod stats;
+pub mod status;
+pub mod storage;
+pub mod streamx;
+pub mod sync;
+pub mod sysinfo;
+pub mod tail;
+pub mod tenant;
+pub mod testkit;
+pub mod trace;
+pub mod transform;
+pub mod tsdb;
+pub mod watchdog;
@@ -0,0 +1,386 @@
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{
+    LockResult, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::logs;
+
+// 排查死锁用的 Mutex/RwLock 包装，API 与标准库相同，只是构造时多一个名字。
+// debug 构建中记录每个线程的加锁顺序: 持有 A 时获取 B 记为 A -> B，
+// 之后出现 B -> ... -> A 的顺序即为锁顺序反转(两个线程交错执行时可能死锁)；
+// 持有时间超过阈值的也会记录。发现问题时输出 WARN 日志(target "locks")，
+// 汇总用 report() 查看。release 构建中不做任何记录。
+// 名字相同的锁视为同一类(例如分片缓存的每个分片)，同类锁之间不检查顺序。
+
+const ENABLED: bool = cfg!(debug_assertions);
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Inversion {
+    // 之前观察到的顺序是 second -> first，这次是 first -> second
+    pub first: &'static str,
+    pub second: &'static str,
+    pub thread: String,
+}
+
+impl fmt::Display for Inversion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "线程 {} 持有 {} 时获取 {}，与之前的顺序 {} -> {} 相反",
+            self.thread, self.first, self.second, self.second, self.first
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LongHold {
+    pub name: &'static str,
+    pub held: Duration,
+    pub thread: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LockReport {
+    pub inversions: Vec<Inversion>,
+    // 按持有时间从长到短
+    pub long_holds: Vec<LongHold>,
+}
+
+impl LockReport {
+    pub fn is_clean(&self) -> bool {
+        self.inversions.is_empty() && self.long_holds.is_empty()
+    }
+
+    // 只保留涉及这些锁的记录
+    pub fn involving(&self, names: &[&str]) -> LockReport {
+        LockReport {
+            inversions: self
+                .inversions
+                .iter()
+                .filter(|i| names.contains(&i.first) || names.contains(&i.second))
+                .cloned()
+                .collect(),
+            long_holds: self
+                .long_holds
+                .iter()
+                .filter(|h| names.contains(&h.name))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+struct State {
+    // 观察到的加锁顺序
+    edges: HashMap<&'static str, HashSet<&'static str>>,
+    inversions: BTreeSet<Inversion>,
+    reported: HashSet<(&'static str, &'static str)>,
+    long_holds: Vec<LongHold>,
+    hold_threshold: Duration,
+}
+
+fn state() -> &'static Mutex<State> {
+    static STATE: std::sync::OnceLock<Mutex<State>> = std::sync::OnceLock::new();
+    STATE.get_or_init(|| {
+        Mutex::new(State {
+            edges: HashMap::new(),
+            inversions: BTreeSet::new(),
+            reported: HashSet::new(),
+            long_holds: Vec::new(),
+            hold_threshold: Duration::from_millis(100),
+        })
+    })
+}
+
+thread_local! {
+    static HELD: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+}
+
+// 持有时间超过这个值时记录，默认 100ms
+pub fn set_hold_threshold(threshold: Duration) {
+    state().lock().unwrap().hold_threshold = threshold;
+}
+
+pub fn report() -> LockReport {
+    let state = state().lock().unwrap();
+    let mut long_holds = state.long_holds.clone();
+    long_holds.sort_by_key(|h| std::cmp::Reverse(h.held));
+    LockReport {
+        inversions: state.inversions.iter().cloned().collect(),
+        long_holds,
+    }
+}
+
+// 清空记录的顺序和问题，阈值不变
+pub fn reset() {
+    let mut state = state().lock().unwrap();
+    state.edges.clear();
+    state.inversions.clear();
+    state.reported.clear();
+    state.long_holds.clear();
+}
+
+fn thread_name() -> String {
+    let current = thread::current();
+    current
+        .name()
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{:?}", current.id()))
+}
+
+// 在真正加锁之前检查，这样即使接下来真的死锁，日志里也已经有记录
+fn before_acquire(name: &'static str) {
+    if !ENABLED {
+        return;
+    }
+    let held: Vec<&'static str> = HELD.with(|h| h.borrow().clone());
+    if held.is_empty() {
+        return;
+    }
+    let mut state = state().lock().unwrap();
+    for &holding in held.iter().filter(|&&h| h != name) {
+        if reachable(&state.edges, name, holding) && state.reported.insert((holding, name)) {
+            let inversion = Inversion {
+                first: holding,
+                second: name,
+                thread: thread_name(),
+            };
+            logs::warn("locks", &format!("锁顺序反转: {}", inversion));
+            state.inversions.insert(inversion);
+        }
+        state.edges.entry(holding).or_default().insert(name);
+    }
+}
+
+fn reachable(edges: &HashMap<&'static str, HashSet<&'static str>>, from: &str, to: &str) -> bool {
+    let mut stack = vec![from];
+    let mut seen = HashSet::new();
+    while let Some(node) = stack.pop() {
+        if node == to {
+            return true;
+        }
+        if seen.insert(node) {
+            if let Some(next) = edges.get(node) {
+                stack.extend(next.iter().copied());
+            }
+        }
+    }
+    false
+}
+
+// 加锁成功后由 guard 持有，drop 时出栈并检查持有时间
+struct Held {
+    name: &'static str,
+    since: Instant,
+}
+
+impl Held {
+    fn acquired(name: &'static str) -> Option<Held> {
+        if !ENABLED {
+            return None;
+        }
+        HELD.with(|h| h.borrow_mut().push(name));
+        Some(Held {
+            name,
+            since: Instant::now(),
+        })
+    }
+}
+
+impl Drop for Held {
+    fn drop(&mut self) {
+        HELD.with(|h| {
+            let mut held = h.borrow_mut();
+            if let Some(i) = held.iter().rposition(|&n| n == self.name) {
+                held.remove(i);
+            }
+        });
+        let held = self.since.elapsed();
+        let mut state = state().lock().unwrap();
+        if held > state.hold_threshold {
+            let hold = LongHold {
+                name: self.name,
+                held,
+                thread: thread_name(),
+            };
+            logs::warn(
+                "locks",
+                &format!("锁 {} 被线程 {} 持有 {:?}", hold.name, hold.thread, held),
+            );
+            state.long_holds.push(hold);
+        }
+    }
+}
+
+fn wrap<G, T>(result: LockResult<G>, f: impl FnOnce(G) -> T) -> LockResult<T> {
+    match result {
+        Ok(guard) => Ok(f(guard)),
+        Err(poisoned) => Err(PoisonError::new(f(poisoned.into_inner()))),
+    }
+}
+
+pub struct TrackedMutex<T: ?Sized> {
+    name: &'static str,
+    inner: Mutex<T>,
+}
+
+impl<T> TrackedMutex<T> {
+    pub fn new(name: &'static str, value: T) -> Self {
+        TrackedMutex {
+            name,
+            inner: Mutex::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> LockResult<T> {
+        self.inner.into_inner()
+    }
+}
+
+impl<T: ?Sized> TrackedMutex<T> {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn lock(&self) -> LockResult<TrackedMutexGuard<'_, T>> {
+        before_acquire(self.name);
+        wrap(self.inner.lock(), |guard| TrackedMutexGuard {
+            guard,
+            _held: Held::acquired(self.name),
+        })
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TrackedMutex<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrackedMutex")
+            .field("name", &self.name)
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+// 字段顺序保证先释放锁再检查持有时间
+pub struct TrackedMutexGuard<'a, T: ?Sized> {
+    guard: MutexGuard<'a, T>,
+    _held: Option<Held>,
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for TrackedMutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> Deref for TrackedMutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: ?Sized> DerefMut for TrackedMutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+pub struct TrackedRwLock<T: ?Sized> {
+    name: &'static str,
+    inner: RwLock<T>,
+}
+
+impl<T> TrackedRwLock<T> {
+    pub fn new(name: &'static str, value: T) -> Self {
+        TrackedRwLock {
+            name,
+            inner: RwLock::new(value),
+        }
+    }
+
+    pub fn into_inner(self) -> LockResult<T> {
+        self.inner.into_inner()
+    }
+}
+
+// 读锁和写锁按同一类处理: 读写交错同样可能死锁(写者等待时新的读者会被阻塞)
+impl<T: ?Sized> TrackedRwLock<T> {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn read(&self) -> LockResult<TrackedReadGuard<'_, T>> {
+        before_acquire(self.name);
+        wrap(self.inner.read(), |guard| TrackedReadGuard {
+            guard,
+            _held: Held::acquired(self.name),
+        })
+    }
+
+    pub fn write(&self) -> LockResult<TrackedWriteGuard<'_, T>> {
+        before_acquire(self.name);
+        wrap(self.inner.write(), |guard| TrackedWriteGuard {
+            guard,
+            _held: Held::acquired(self.name),
+        })
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for TrackedRwLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrackedRwLock")
+            .field("name", &self.name)
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+pub struct TrackedReadGuard<'a, T: ?Sized> {
+    guard: RwLockReadGuard<'a, T>,
+    _held: Option<Held>,
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for TrackedReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> Deref for TrackedReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+pub struct TrackedWriteGuard<'a, T: ?Sized> {
+    guard: RwLockWriteGuard<'a, T>,
+    _held: Option<Held>,
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for TrackedWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized> Deref for TrackedWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T: ?Sized> DerefMut for TrackedWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
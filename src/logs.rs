@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::context;
+use crate::intern::{self, Symbol};
+use crate::redact::{RedactConfig, RedactError, Redactor};
+use crate::ring::RingBuffer;
+
+// 最近的日志保存在内存环形缓冲区中，按需导出(管理接口、崩溃报告)，
+// 缓冲区记录的级别可以比输出到 stderr 的级别更详细。
+// 写入 stderr 和缓冲区之前按脱敏规则处理消息，默认使用内置规则。
+
+pub const DEFAULT_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        };
+        f.pad(s)
+    }
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "error" => Ok(Level::Error),
+            "warn" | "warning" => Ok(Level::Warn),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("未知的日志级别: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LogRecord {
+    pub timestamp_ms: u64,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    pub request_id: Option<String>,
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<5} {}: {}",
+            self.timestamp_ms, self.level, self.target, self.message
+        )?;
+        if let Some(id) = &self.request_id {
+            write!(f, " request_id={}", id)?;
+        }
+        Ok(())
+    }
+}
+
+struct State {
+    buffer: RingBuffer<LogRecord>,
+    buffer_level: Level,
+    output_level: Level,
+    overrides: Vec<Override>,
+    // 日志 target 取值有限，驻留后作为 key
+    samplers: HashMap<Symbol, Sampler>,
+}
+
+// 某个模块临时调整的输出级别，expires 到期后自动恢复
+struct Override {
+    target: Symbol,
+    level: Level,
+    expires: Option<Instant>,
+}
+
+// 每 every 条 Info 及更详细的日志只保留一条
+struct Sampler {
+    every: u64,
+    seen: u64,
+}
+
+// target 等于 prefix 或以 "prefix::" 开头
+fn matches_target(target: &str, prefix: &str) -> bool {
+    target == prefix
+        || target
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with("::"))
+}
+
+impl State {
+    // 最具体的未过期覆盖优先，没有覆盖时使用全局输出级别
+    fn output_level_for(&mut self, target: &str) -> Level {
+        let now = Instant::now();
+        self.overrides
+            .retain(|o| o.expires.is_none_or(|expires| expires > now));
+        self.overrides
+            .iter()
+            .filter(|o| matches_target(target, &o.target))
+            .max_by_key(|o| o.target.len())
+            .map(|o| o.level)
+            .unwrap_or(self.output_level)
+    }
+
+    fn sampled_out(&mut self, level: Level, target: &str) -> bool {
+        if level <= Level::Warn {
+            return false;
+        }
+        let sampler = self
+            .samplers
+            .iter_mut()
+            .filter(|(prefix, _)| matches_target(target, prefix))
+            .max_by_key(|(prefix, _)| prefix.len());
+        match sampler {
+            Some((_, sampler)) => {
+                sampler.seen += 1;
+                (sampler.seen - 1) % sampler.every != 0
+            }
+            None => false,
+        }
+    }
+}
+
+fn state() -> &'static Mutex<State> {
+    static STATE: OnceLock<Mutex<State>> = OnceLock::new();
+    STATE.get_or_init(|| {
+        Mutex::new(State {
+            buffer: RingBuffer::new(DEFAULT_CAPACITY),
+            buffer_level: Level::Debug,
+            output_level: Level::Info,
+            overrides: Vec::new(),
+            samplers: HashMap::new(),
+        })
+    })
+}
+
+fn redactor_slot() -> &'static RwLock<Arc<Redactor>> {
+    static REDACTOR: OnceLock<RwLock<Arc<Redactor>>> = OnceLock::new();
+    REDACTOR.get_or_init(|| RwLock::new(Arc::new(Redactor::default())))
+}
+
+// 按配置文件的 [redact] 段替换日志使用的脱敏规则，启动时调用
+pub fn set_redaction(config: &RedactConfig) -> Result<(), RedactError> {
+    set_redactor(Redactor::from_config(config)?);
+    Ok(())
+}
+
+pub fn set_redactor(redactor: Redactor) {
+    *redactor_slot().write().unwrap() = Arc::new(redactor);
+}
+
+// 当前生效的脱敏规则，崩溃报告等其他输出也应使用它
+pub fn redactor() -> Arc<Redactor> {
+    redactor_slot().read().unwrap().clone()
+}
+
+pub fn set_capacity(capacity: usize) {
+    state().lock().unwrap().buffer.set_capacity(capacity);
+}
+
+// 写入缓冲区的最详细级别
+pub fn set_buffer_level(level: Level) {
+    state().lock().unwrap().buffer_level = level;
+}
+
+// 输出到 stderr 的最详细级别
+pub fn set_output_level(level: Level) {
+    state().lock().unwrap().output_level = level;
+}
+
+// 调整某个模块(及其子模块)的输出级别，不影响其他模块
+pub fn set_level(target: &str, level: Level) {
+    set_override(target, level, None);
+}
+
+// 临时调整级别，duration 之后自动恢复
+pub fn set_level_for(target: &str, level: Level, duration: Duration) {
+    set_override(target, level, Some(Instant::now() + duration));
+}
+
+fn set_override(target: &str, level: Level, expires: Option<Instant>) {
+    let mut state = state().lock().unwrap();
+    state.overrides.retain(|o| *o.target != *target);
+    state.overrides.push(Override {
+        target: intern::intern(target),
+        level,
+        expires,
+    });
+}
+
+pub fn reset_level(target: &str) {
+    state()
+        .lock()
+        .unwrap()
+        .overrides
+        .retain(|o| *o.target != *target);
+}
+
+// 管理接口/IPC 使用的指令格式: "db=debug" 或 "db=debug@300" (300 秒后恢复)
+pub fn apply_directive(directive: &str) -> Result<(), String> {
+    let (target, rest) = directive
+        .split_once('=')
+        .ok_or_else(|| format!("无效的日志指令: {}", directive))?;
+    let (level, secs) = match rest.split_once('@') {
+        Some((level, secs)) => {
+            let secs: u64 = secs
+                .trim()
+                .parse()
+                .map_err(|_| format!("无效的持续时间: {}", secs))?;
+            (level, Some(secs))
+        }
+        None => (rest, None),
+    };
+    let level: Level = level.trim().parse()?;
+    match secs {
+        Some(secs) => set_level_for(target.trim(), level, Duration::from_secs(secs)),
+        None => set_level(target.trim(), level),
+    }
+    Ok(())
+}
+
+// 对高频模块采样: 每 every 条 Info/Debug/Trace 日志保留一条，Warn 及以上不受影响
+pub fn set_sampling(target: &str, every: u64) {
+    let mut state = state().lock().unwrap();
+    if every <= 1 {
+        state.samplers.remove(target);
+    } else {
+        state
+            .samplers
+            .insert(intern::intern(target), Sampler { every, seen: 0 });
+    }
+}
+
+// 该级别的日志是否会输出，可以用来跳过昂贵的格式化
+pub fn enabled(level: Level, target: &str) -> bool {
+    level <= state().lock().unwrap().output_level_for(target)
+}
+
+pub fn log(level: Level, target: &str, message: &str) {
+    let mut state = state().lock().unwrap();
+    let output_level = state.output_level_for(target);
+    // 临时调高的级别同时作用于缓冲区
+    let buffer_level = state.buffer_level.max(output_level);
+    if level > buffer_level || state.sampled_out(level, target) {
+        return;
+    }
+    let record = LogRecord {
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0),
+        level,
+        target: target.to_string(),
+        message: redactor().redact_str(message),
+        request_id: context::current().map(|c| c.request_id),
+    };
+    if level <= output_level {
+        eprintln!("{}", record);
+    }
+    if level <= buffer_level {
+        state.buffer.push(record);
+    }
+}
+
+pub fn error(target: &str, message: &str) {
+    log(Level::Error, target, message);
+}
+
+pub fn warn(target: &str, message: &str) {
+    log(Level::Warn, target, message);
+}
+
+pub fn info(target: &str, message: &str) {
+    log(Level::Info, target, message);
+}
+
+pub fn debug(target: &str, message: &str) {
+    log(Level::Debug, target, message);
+}
+
+// 缓冲区中的日志，从旧到新
+pub fn dump_recent() -> Vec<LogRecord> {
+    state().lock().unwrap().buffer.to_vec()
+}
+
+// 文本形式，供崩溃报告使用: CrashReporter::recent_logs(logs::recent_lines)
+pub fn recent_lines() -> Vec<String> {
+    state()
+        .lock()
+        .unwrap()
+        .buffer
+        .iter()
+        .map(|r| r.to_string())
+        .collect()
+}
+
+// 管理接口返回的 JSON
+pub fn dump_json() -> String {
+    serde_json::to_string_pretty(&dump_recent()).expect("LogRecord 总是可以序列化")
+}
+
+pub fn clear() {
+    state().lock().unwrap().buffer.clear();
+}
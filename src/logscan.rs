@@ -0,0 +1,353 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::logs::{self, Level, LogRecord};
+
+// JSON-lines 日志的解析和查询: 每行一个 JSON 对象，level/message 必须有，
+// timestamp_ms、target、request_id 可选，其余字段放进 fields。
+// 过滤表达式由空格分隔的条件组成，全部满足才匹配，例如
+//   level>=warn target=db since=1700000000000 user_id=42 message~timeout
+// 支持的条件:
+//   level=X / level>=X(X 及更严重) / level<=X
+//   target=T  T 本身或其子模块(T::...)
+//   since=MS / until=MS  时间范围，until 不包含
+//   message~S  消息包含 S
+//   KEY=V / KEY~S  字段等于 V / 包含 S，字符串字段按原文比较，其他按 JSON 文本比较
+// 值中有空格时用双引号括起来。
+
+#[derive(Error, Debug)]
+pub enum ScanError {
+    #[error("第 {line} 行不是有效的 JSON: {source}")]
+    Json {
+        line: usize,
+        source: serde_json::Error,
+    },
+    #[error("第 {line} 行缺少字段 {field}")]
+    Missing { line: usize, field: &'static str },
+    #[error("第 {line} 行: {message}")]
+    Invalid { line: usize, message: String },
+    #[error("无效的过滤条件: {0}")]
+    Filter(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Record {
+    // 在输入中的行号，从 1 开始；来自内存缓冲区时为 0
+    pub line: usize,
+    pub timestamp_ms: u64,
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, Value>,
+}
+
+impl Record {
+    pub fn parse(line_no: usize, line: &str) -> Result<Record, ScanError> {
+        let value: Value = serde_json::from_str(line).map_err(|source| ScanError::Json {
+            line: line_no,
+            source,
+        })?;
+        let Value::Object(mut map) = value else {
+            return Err(ScanError::Invalid {
+                line: line_no,
+                message: "不是 JSON 对象".into(),
+            });
+        };
+        let mut take_str = |key: &str| match map.remove(key) {
+            Some(Value::String(s)) => Some(s),
+            Some(other) => Some(other.to_string()),
+            None => None,
+        };
+        let level = take_str("level").ok_or(ScanError::Missing {
+            line: line_no,
+            field: "level",
+        })?;
+        let level = level
+            .parse::<Level>()
+            .map_err(|message| ScanError::Invalid {
+                line: line_no,
+                message,
+            })?;
+        let message = take_str("message").ok_or(ScanError::Missing {
+            line: line_no,
+            field: "message",
+        })?;
+        let target = take_str("target").unwrap_or_default();
+        let request_id = match map.remove("request_id") {
+            Some(Value::String(s)) => Some(s),
+            _ => None,
+        };
+        let timestamp_ms = map
+            .remove("timestamp_ms")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(0);
+        Ok(Record {
+            line: line_no,
+            timestamp_ms,
+            level,
+            target,
+            message,
+            request_id,
+            fields: map.into_iter().collect(),
+        })
+    }
+
+    pub fn field(&self, key: &str) -> Option<&Value> {
+        self.fields.get(key)
+    }
+}
+
+impl From<LogRecord> for Record {
+    fn from(r: LogRecord) -> Self {
+        Record {
+            line: 0,
+            timestamp_ms: r.timestamp_ms,
+            level: r.level,
+            target: r.target,
+            message: r.message,
+            request_id: r.request_id,
+            fields: BTreeMap::new(),
+        }
+    }
+}
+
+impl fmt::Display for Record {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:<5} {}: {}",
+            self.timestamp_ms, self.level, self.target, self.message
+        )?;
+        for (key, value) in &self.fields {
+            write!(f, " {}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Cond {
+    LevelEq(Level),
+    // 至少这么严重
+    LevelAtLeast(Level),
+    LevelAtMost(Level),
+    Target(String),
+    Since(u64),
+    Until(u64),
+    MessageContains(String),
+    FieldEq(String, String),
+    FieldContains(String, String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    conds: Vec<Cond>,
+}
+
+impl Filter {
+    // 匹配所有记录
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(expr: &str) -> Result<Filter, ScanError> {
+        let mut filter = Filter::new();
+        for term in split_terms(expr)? {
+            filter.conds.push(parse_term(&term)?);
+        }
+        Ok(filter)
+    }
+
+    pub fn level(mut self, level: Level) -> Self {
+        self.conds.push(Cond::LevelEq(level));
+        self
+    }
+
+    pub fn min_level(mut self, level: Level) -> Self {
+        self.conds.push(Cond::LevelAtLeast(level));
+        self
+    }
+
+    pub fn target(mut self, target: &str) -> Self {
+        self.conds.push(Cond::Target(target.to_string()));
+        self
+    }
+
+    pub fn since(mut self, timestamp_ms: u64) -> Self {
+        self.conds.push(Cond::Since(timestamp_ms));
+        self
+    }
+
+    pub fn until(mut self, timestamp_ms: u64) -> Self {
+        self.conds.push(Cond::Until(timestamp_ms));
+        self
+    }
+
+    pub fn message_contains(mut self, text: &str) -> Self {
+        self.conds.push(Cond::MessageContains(text.to_string()));
+        self
+    }
+
+    pub fn field(mut self, key: &str, value: &str) -> Self {
+        self.conds
+            .push(Cond::FieldEq(key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn matches(&self, record: &Record) -> bool {
+        self.conds.iter().all(|cond| match cond {
+            // Level 的顺序是 Error < Warn < ...，越小越严重
+            Cond::LevelEq(level) => record.level == *level,
+            Cond::LevelAtLeast(level) => record.level <= *level,
+            Cond::LevelAtMost(level) => record.level >= *level,
+            Cond::Target(target) => {
+                record.target == *target
+                    || record
+                        .target
+                        .strip_prefix(target.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            }
+            Cond::Since(ts) => record.timestamp_ms >= *ts,
+            Cond::Until(ts) => record.timestamp_ms < *ts,
+            Cond::MessageContains(text) => record.message.contains(text.as_str()),
+            Cond::FieldEq(key, value) => field_text(record, key).is_some_and(|v| v == *value),
+            Cond::FieldContains(key, text) => {
+                field_text(record, key).is_some_and(|v| v.contains(text.as_str()))
+            }
+        })
+    }
+}
+
+// request_id 也可以按字段匹配
+fn field_text(record: &Record, key: &str) -> Option<String> {
+    if key == "request_id" {
+        return record.request_id.clone();
+    }
+    match record.fields.get(key)? {
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+fn split_terms(expr: &str) -> Result<Vec<String>, ScanError> {
+    let mut terms = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in expr.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    terms.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if quoted {
+        return Err(ScanError::Filter(format!("引号不匹配: {}", expr)));
+    }
+    if !current.is_empty() {
+        terms.push(current);
+    }
+    Ok(terms)
+}
+
+fn parse_term(term: &str) -> Result<Cond, ScanError> {
+    let invalid = |why: &str| ScanError::Filter(format!("{}: {}", why, term));
+    let level = |s: &str| s.parse::<Level>().map_err(ScanError::Filter);
+    let millis = |s: &str| s.parse::<u64>().map_err(|_| invalid("无效的时间戳"));
+
+    if let Some(v) = term.strip_prefix("level>=") {
+        return Ok(Cond::LevelAtLeast(level(v)?));
+    }
+    if let Some(v) = term.strip_prefix("level<=") {
+        return Ok(Cond::LevelAtMost(level(v)?));
+    }
+    if let Some(v) = term.strip_prefix("message~") {
+        return Ok(Cond::MessageContains(v.to_string()));
+    }
+    // = 和 ~ 中先出现的那个是操作符
+    let Some(i) = term.find(['=', '~']) else {
+        return Err(invalid("缺少操作符"));
+    };
+    let (key, op, value) = (&term[..i], &term[i..i + 1], &term[i + 1..]);
+    if key.is_empty() {
+        return Err(invalid("缺少字段名"));
+    }
+    Ok(match (key, op) {
+        ("level", "=") => Cond::LevelEq(level(value)?),
+        ("target", "=") => Cond::Target(value.to_string()),
+        ("since", "=") => Cond::Since(millis(value)?),
+        ("until", "=") => Cond::Until(millis(value)?),
+        (_, "=") => Cond::FieldEq(key.to_string(), value.to_string()),
+        _ => Cond::FieldContains(key.to_string(), value.to_string()),
+    })
+}
+
+// 逐行解析并过滤，不会把整个文件读进内存。无法解析的行作为错误返回，迭代继续
+pub struct Scan<R> {
+    reader: R,
+    filter: Filter,
+    line_no: usize,
+    buf: String,
+}
+
+pub fn scan<R: BufRead>(reader: R, filter: Filter) -> Scan<R> {
+    Scan {
+        reader,
+        filter,
+        line_no: 0,
+        buf: String::new(),
+    }
+}
+
+pub fn scan_file(path: impl AsRef<Path>, filter: Filter) -> io::Result<Scan<BufReader<File>>> {
+    Ok(scan(BufReader::new(File::open(path)?), filter))
+}
+
+impl<R: BufRead> Iterator for Scan<R> {
+    type Item = Result<Record, ScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buf.clear();
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => return Some(Err(e.into())),
+            }
+            self.line_no += 1;
+            let line = self.buf.trim();
+            if line.is_empty() {
+                continue;
+            }
+            match Record::parse(self.line_no, line) {
+                Ok(record) if self.filter.matches(&record) => return Some(Ok(record)),
+                Ok(_) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+// 查询内存缓冲区中的日志，方便在测试中断言输出了哪些日志
+pub fn recent(filter: &Filter) -> Vec<Record> {
+    logs::dump_recent()
+        .into_iter()
+        .map(Record::from)
+        .filter(|r| filter.matches(r))
+        .collect()
+}
@@ -0,0 +1,105 @@
+use std::io;
+
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+// 最小的 SMTP 客户端: 不支持认证和 TLS，用于内网中继或测试中的 MockSmtp
+
+#[derive(Error, Debug)]
+pub enum MailError {
+    #[error("SMTP 服务器拒绝 {command}: {reply}")]
+    Rejected { command: String, reply: String },
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mail {
+    pub from: String,
+    pub to: Vec<String>,
+    pub subject: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Mailer {
+    addr: String,
+}
+
+impl Mailer {
+    // host:port
+    pub fn new(addr: &str) -> Self {
+        Mailer {
+            addr: addr.to_string(),
+        }
+    }
+
+    pub async fn send(&self, mail: &Mail) -> Result<(), MailError> {
+        let mut stream = BufReader::new(TcpStream::connect(&self.addr).await?);
+        expect(&mut stream, "CONNECT", '2').await?;
+        command(&mut stream, "EHLO localhost", '2').await?;
+        command(&mut stream, &format!("MAIL FROM:<{}>", mail.from), '2').await?;
+        for to in &mail.to {
+            command(&mut stream, &format!("RCPT TO:<{}>", to), '2').await?;
+        }
+        command(&mut stream, "DATA", '3').await?;
+
+        let mut data = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\n\r\n",
+            mail.from,
+            mail.to.join(", "),
+            mail.subject
+        );
+        for line in mail.body.lines() {
+            // dot-stuffing
+            if line.starts_with('.') {
+                data.push('.');
+            }
+            data.push_str(line);
+            data.push_str("\r\n");
+        }
+        data.push_str(".\r\n");
+        stream.get_mut().write_all(data.as_bytes()).await?;
+        expect(&mut stream, "DATA", '2').await?;
+        command(&mut stream, "QUIT", '2').await?;
+        Ok(())
+    }
+}
+
+async fn command(
+    stream: &mut BufReader<TcpStream>,
+    line: &str,
+    expected: char,
+) -> Result<(), MailError> {
+    stream
+        .get_mut()
+        .write_all(format!("{}\r\n", line).as_bytes())
+        .await?;
+    expect(stream, line, expected).await
+}
+
+// 读取一条回复(可能有多行 "250-...")，检查状态码的第一位
+async fn expect(
+    stream: &mut BufReader<TcpStream>,
+    command: &str,
+    expected: char,
+) -> Result<(), MailError> {
+    loop {
+        let mut reply = String::new();
+        if stream.read_line(&mut reply).await? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        if reply.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+        if !reply.starts_with(expected) {
+            return Err(MailError::Rejected {
+                command: command.to_string(),
+                reply: reply.trim_end().to_string(),
+            });
+        }
+        return Ok(());
+    }
+}
@@ -1,3 +1,248 @@
+use std::env;
+
+use std_app::admin::{self, shell};
+use std_app::backup::Backup;
+use std_app::config::{schema, AppConfig, Config, ConfigLoader, Documented};
+use std_app::db::{self, pool::Pool};
+use std_app::logscan::{self, Filter};
+use std_app::selftest::SelfTest;
+use std_app::{bench, openapi, serde_any};
+
 fn main() {
-    println!("Hello, world!");
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("bench") => {
+            let reports = bench::builtin().run();
+            if args.iter().any(|a| a == "--json") {
+                println!("{}", bench::to_json(&reports));
+            } else {
+                for report in reports {
+                    println!("{}", report);
+                }
+            }
+        }
+        // std-app openapi <spec.json|yaml> <ClientName>，生成的代码输出到 stdout
+        Some("openapi") if args.len() == 3 => {
+            let code = serde_any::from_path::<serde_json::Value>(&args[1])
+                .map_err(|e| e.to_string())
+                .and_then(|spec| openapi::generate(&spec, &args[2]).map_err(|e| e.to_string()));
+            match code {
+                Ok(code) => print!("{}", code),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        // std-app explain <db-url> <sql> [--json]，查询不会被执行
+        Some("explain") if args.len() >= 3 => {
+            let runtime = tokio::runtime::Runtime::new().expect("创建 tokio 运行时");
+            let plan = runtime.block_on(async {
+                let pool = Pool::connect(&args[1]).await?;
+                db::explain(&pool, &args[2]).await
+            });
+            match plan {
+                Ok(plan) if args.iter().any(|a| a == "--json") => {
+                    println!(
+                        "{}",
+                        serde_json::to_string_pretty(&plan).expect("QueryPlan 总是可以序列化")
+                    )
+                }
+                Ok(plan) => {
+                    print!("{}", plan);
+                    for table in plan.full_scans() {
+                        println!("警告: {} 全表扫描", table);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        // std-app logscan <file.jsonl> [条件...] [--json]，例如 logscan app.log level>=warn target=db
+        Some("logscan") if args.len() >= 2 => {
+            let json = args.iter().any(|a| a == "--json");
+            let expr = args[2..]
+                .iter()
+                .filter(|a| *a != "--json")
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let records = Filter::parse(&expr)
+                .map_err(|e| e.to_string())
+                .and_then(|filter| logscan::scan_file(&args[1], filter).map_err(|e| e.to_string()));
+            let records = match records {
+                Ok(records) => records,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            for record in records {
+                match record {
+                    Ok(record) if json => println!(
+                        "{}",
+                        serde_json::to_string(&record).expect("Record 总是可以序列化")
+                    ),
+                    Ok(record) => println!("{}", record),
+                    Err(e) => eprintln!("{}", e),
+                }
+            }
+        }
+        // std-app selftest [--config 文件] [--db URL] [--http URL] [--dir 目录] [--json]，
+        // 选项可以重复；有失败项时退出码为 1
+        Some("selftest") => {
+            let mut selftest = SelfTest::new();
+            let mut json = false;
+            let mut rest = args[1..].iter();
+            while let Some(arg) = rest.next() {
+                if arg == "--json" {
+                    json = true;
+                    continue;
+                }
+                let Some(value) = rest.next() else {
+                    eprintln!("{} 缺少参数", arg);
+                    std::process::exit(2);
+                };
+                selftest = match arg.as_str() {
+                    "--config" => selftest.config(value),
+                    "--db" => selftest.db(value),
+                    "--http" => selftest.endpoint(value),
+                    "--dir" => selftest.data_dir(value),
+                    _ => {
+                        eprintln!("未知选项: {}", arg);
+                        std::process::exit(2);
+                    }
+                };
+            }
+            let runtime = tokio::runtime::Runtime::new().expect("创建 tokio 运行时");
+            let report = runtime.block_on(selftest.run());
+            if json {
+                println!("{}", report.to_json());
+            } else {
+                println!("{}", report);
+            }
+            if !report.is_success() {
+                std::process::exit(1);
+            }
+        }
+        // std-app config check <file>，按 AppConfig 的类型和校验规则检查，
+        // 拼错的键给出最接近的已知键；有问题时退出码为 1
+        Some("config") if args.get(1).map(String::as_str) == Some("check") && args.len() == 3 => {
+            let report = schema::check_file::<AppConfig>(&args[2]);
+            println!("{}", report);
+            if !report.is_ok() {
+                std::process::exit(1);
+            }
+        }
+        // std-app config docs，所有配置键及默认值，Markdown 表格
+        Some("config") if args.get(1).map(String::as_str) == Some("docs") => {
+            print!("{}", schema::reference::<AppConfig>());
+        }
+        // std-app shell [--socket 路径] [--json]，连接运行中实例的管理接口
+        Some("shell") => {
+            let socket = args
+                .iter()
+                .position(|a| a == "--socket")
+                .and_then(|i| args.get(i + 1))
+                .map_or(admin::DEFAULT_SOCKET, String::as_str);
+            if let Err(e) = shell::run(socket, args.iter().any(|a| a == "--json")) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        // std-app config schema，AppConfig 的 JSON Schema
+        Some("config") if args.get(1).map(String::as_str) == Some("schema") => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&AppConfig::schema()).expect("Value 总是可以序列化")
+            );
+        }
+        // std-app config init <file>，写入带注释的默认配置，格式按扩展名选择，不覆盖已有文件
+        Some("config") if args.get(1).map(String::as_str) == Some("init") && args.len() == 3 => {
+            if let Err(e) = AppConfig::write_default(&args[2]) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        // std-app export --out dump.tar.zst [--config 文件] [--db URL] [--blobs 目录]
+        // std-app import dump.tar.zst [--config 文件] [--db URL] [--blobs 目录]
+        // 数据库默认取配置中的 db.url，只有指定 --blobs 时才包含 blob 存储
+        Some(command @ ("export" | "import")) => {
+            let mut options = std::collections::HashMap::new();
+            let mut archive = None;
+            let mut rest = args[1..].iter();
+            while let Some(arg) = rest.next() {
+                if !arg.starts_with("--") {
+                    archive = Some(arg.clone());
+                    continue;
+                }
+                let Some(value) = rest.next() else {
+                    eprintln!("{} 缺少参数", arg);
+                    std::process::exit(2);
+                };
+                match arg.as_str() {
+                    "--out" => archive = Some(value.clone()),
+                    "--config" | "--db" | "--blobs" => {
+                        options.insert(arg.as_str(), value.clone());
+                    }
+                    _ => {
+                        eprintln!("未知选项: {}", arg);
+                        std::process::exit(2);
+                    }
+                }
+            }
+            let Some(archive) = archive else {
+                eprintln!("缺少归档路径");
+                std::process::exit(2);
+            };
+            // 配置有误时直接退出，不能退回默认地址而读写到别的数据库
+            let config = match (options.get("--db"), options.get("--config")) {
+                (Some(_), _) => Ok(AppConfig::default()),
+                (None, Some(path)) => ConfigLoader::new()
+                    .defaults(&AppConfig::default())
+                    .file(path)
+                    .load::<AppConfig>(),
+                (None, None) => AppConfig::load_for_profile(None),
+            };
+            let db_url = match config {
+                Ok(config) => options.get("--db").cloned().unwrap_or(config.db.url),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            };
+            let runtime = tokio::runtime::Runtime::new().expect("创建 tokio 运行时");
+            let result = runtime.block_on(async {
+                let pool = Pool::connect(&db_url).await.map_err(|e| e.to_string())?;
+                let mut backup = Backup::new().db(pool.inner());
+                if let Some(blobs) = options.get("--blobs") {
+                    backup = backup.blobs(blobs);
+                }
+                if command == "export" {
+                    let manifest = backup.export(&archive).await.map_err(|e| e.to_string())?;
+                    let rows: u64 = manifest.tables.iter().map(|t| t.rows).sum();
+                    Ok(format!(
+                        "导出 {} 张表({} 行)、{} 个 blob 文件到 {}",
+                        manifest.tables.len(),
+                        rows,
+                        manifest.files.len(),
+                        archive
+                    ))
+                } else {
+                    let report = backup.import(&archive).await.map_err(|e| e.to_string())?;
+                    Ok::<_, String>(report.to_string())
+                }
+            });
+            match result {
+                Ok(summary) => println!("{}", summary),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        _ => println!("Hello, world!"),
+    }
 }
@@ -0,0 +1,179 @@
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::Serialize;
+
+use crate::intern::{self, Symbol};
+use crate::stats::Digest;
+
+pub mod prometheus;
+pub mod push;
+
+// 进程内的指标注册表: 计数器、仪表和直方图，按名称注册，重复注册返回同一个实例；
+// 名称经过驻留，热路径上重复取同一个指标不会分配字符串
+
+#[derive(Debug, Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    pub fn inc(&self) {
+        self.add(1);
+    }
+
+    pub fn add(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// f64 按位存放在 AtomicU64 中
+#[derive(Debug, Clone, Default)]
+pub struct Gauge(Arc<AtomicU64>);
+
+impl Gauge {
+    pub fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct HistogramSummary {
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+}
+
+// 分位数由 t-digest 估计
+#[derive(Debug, Clone, Default)]
+pub struct Histogram(Arc<Mutex<Digest>>);
+
+impl Histogram {
+    pub fn observe(&self, value: f64) {
+        self.0.lock().unwrap().add(value);
+    }
+
+    // 汇总其他 worker 记录的数据
+    pub fn merge(&self, digest: &Digest) {
+        self.0.lock().unwrap().merge(digest);
+    }
+
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        self.0.lock().unwrap().quantile(q)
+    }
+
+    pub fn digest(&self) -> Digest {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn summary(&self) -> HistogramSummary {
+        let mut digest = self.0.lock().unwrap();
+        let mut quantile = |q| digest.quantile(q).unwrap_or(0.0);
+        let (p50, p90, p99) = (quantile(0.5), quantile(0.9), quantile(0.99));
+        HistogramSummary {
+            count: digest.count(),
+            sum: digest.sum(),
+            min: digest.min().unwrap_or(0.0),
+            max: digest.max().unwrap_or(0.0),
+            p50,
+            p90,
+            p99,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum MetricValue {
+    Counter { value: u64 },
+    Gauge { value: f64 },
+    Histogram(HistogramSummary),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Metric {
+    pub name: String,
+    #[serde(flatten)]
+    pub value: MetricValue,
+}
+
+#[derive(Default)]
+pub struct Registry {
+    counters: Mutex<BTreeMap<Symbol, Counter>>,
+    gauges: Mutex<BTreeMap<Symbol, Gauge>>,
+    histograms: Mutex<BTreeMap<Symbol, Histogram>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn counter(&self, name: &str) -> Counter {
+        self.counters
+            .lock()
+            .unwrap()
+            .entry(intern::intern(name))
+            .or_default()
+            .clone()
+    }
+
+    pub fn gauge(&self, name: &str) -> Gauge {
+        self.gauges
+            .lock()
+            .unwrap()
+            .entry(intern::intern(name))
+            .or_default()
+            .clone()
+    }
+
+    pub fn histogram(&self, name: &str) -> Histogram {
+        self.histograms
+            .lock()
+            .unwrap()
+            .entry(intern::intern(name))
+            .or_default()
+            .clone()
+    }
+
+    // 按名称排序的当前值
+    pub fn snapshot(&self) -> Vec<Metric> {
+        let mut metrics: Vec<Metric> = Vec::new();
+        for (name, c) in self.counters.lock().unwrap().iter() {
+            metrics.push(Metric {
+                name: name.to_string(),
+                value: MetricValue::Counter { value: c.get() },
+            });
+        }
+        for (name, g) in self.gauges.lock().unwrap().iter() {
+            metrics.push(Metric {
+                name: name.to_string(),
+                value: MetricValue::Gauge { value: g.get() },
+            });
+        }
+        for (name, h) in self.histograms.lock().unwrap().iter() {
+            metrics.push(Metric {
+                name: name.to_string(),
+                value: MetricValue::Histogram(h.summary()),
+            });
+        }
+        metrics.sort_by(|a, b| a.name.cmp(&b.name));
+        metrics
+    }
+}
+
+pub fn global() -> &'static Registry {
+    static GLOBAL: OnceLock<Registry> = OnceLock::new();
+    GLOBAL.get_or_init(Registry::new)
+}
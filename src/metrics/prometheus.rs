@@ -0,0 +1,55 @@
+use std::fmt::Write;
+
+use super::{Metric, MetricValue, Registry};
+
+// Prometheus 文本格式(0.0.4)。直方图按 summary 输出分位数、_sum 和 _count。
+
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+// 不合法的字符替换成下划线，数字开头时加下划线前缀
+pub fn sanitize_name(name: &str) -> String {
+    let mut out: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == ':' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if out.starts_with(|c: char| c.is_ascii_digit()) || out.is_empty() {
+        out.insert(0, '_');
+    }
+    out
+}
+
+pub fn render(metrics: &[Metric]) -> String {
+    let mut out = String::new();
+    for metric in metrics {
+        let name = sanitize_name(&metric.name);
+        match &metric.value {
+            MetricValue::Counter { value } => {
+                let _ = writeln!(out, "# TYPE {} counter\n{} {}", name, name, value);
+            }
+            MetricValue::Gauge { value } => {
+                let _ = writeln!(out, "# TYPE {} gauge\n{} {}", name, name, value);
+            }
+            MetricValue::Histogram(h) => {
+                let _ = writeln!(out, "# TYPE {} summary", name);
+                for (q, v) in [("0.5", h.p50), ("0.9", h.p90), ("0.99", h.p99)] {
+                    let _ = writeln!(out, "{}{{quantile=\"{}\"}} {}", name, q, v);
+                }
+                let _ = writeln!(out, "{}_sum {}\n{}_count {}", name, h.sum, name, h.count);
+            }
+        }
+    }
+    out
+}
+
+impl Registry {
+    // 抓取接口返回的内容
+    pub fn render_prometheus(&self) -> String {
+        render(&self.snapshot())
+    }
+}
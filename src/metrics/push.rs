@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::prometheus::CONTENT_TYPE;
+use super::{MetricValue, Registry};
+use crate::http::{ApiError, HttpClient, Method};
+use crate::logs;
+
+// 主动推送指标，给活不到下一次抓取的短命令行进程用:
+// PushGateway 通过 HttpClient 把注册表推到 Prometheus push-gateway，
+// StatsdClient 通过 UDP 发给 statsd，多条指标合并成一个数据包发送。
+// 两者都不应该因为监控系统不可用而让命令失败，提供只记日志的版本。
+
+pub struct PushGateway {
+    client: HttpClient,
+    job: String,
+    // 分组键，job 之外的标签
+    labels: Vec<(String, String)>,
+}
+
+impl PushGateway {
+    // client 的 base_url 指向 push-gateway，重试和超时按 client 的配置
+    pub fn new(client: HttpClient, job: &str) -> Self {
+        PushGateway {
+            client,
+            job: job.to_string(),
+            labels: Vec::new(),
+        }
+    }
+
+    pub fn label(mut self, key: &str, value: &str) -> Self {
+        self.labels.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    // /metrics/job/<job>/<k>/<v>...
+    pub fn path(&self) -> String {
+        let mut path = format!("/metrics/job/{}", encode_segment(&self.job));
+        for (key, value) in &self.labels {
+            path.push('/');
+            path.push_str(&encode_segment(key));
+            path.push('/');
+            path.push_str(&encode_segment(value));
+        }
+        path
+    }
+
+    // PUT: 替换这个分组下的所有指标
+    pub async fn push(&self, registry: &Registry) -> Result<(), ApiError> {
+        self.send(Method::PUT, Some(registry.render_prometheus()))
+            .await
+    }
+
+    // POST: 只替换同名指标
+    pub async fn push_add(&self, registry: &Registry) -> Result<(), ApiError> {
+        self.send(Method::POST, Some(registry.render_prometheus()))
+            .await
+    }
+
+    pub async fn delete(&self) -> Result<(), ApiError> {
+        self.send(Method::DELETE, None).await
+    }
+
+    // 推送失败只记一条警告，返回是否成功
+    pub async fn push_or_warn(&self, registry: &Registry) -> bool {
+        match self.push(registry).await {
+            Ok(()) => true,
+            Err(e) => {
+                logs::warn(
+                    "metrics::push",
+                    &format!("推送指标到 {} 失败: {}", self.client.url(&self.path()), e),
+                );
+                false
+            }
+        }
+    }
+
+    async fn send(&self, method: Method, body: Option<String>) -> Result<(), ApiError> {
+        let mut request = self.client.request(method, &self.path());
+        if let Some(body) = body {
+            request = request.header("content-type", CONTENT_TYPE).body(body);
+        }
+        self.client.send(request).await?;
+        Ok(())
+    }
+}
+
+// 标签值中的 `/` 等字符需要转义
+fn encode_segment(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-_.~".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{:02X}", b));
+        }
+    }
+    out
+}
+
+// 以太网 MTU 减去 IP 和 UDP 头，避免分片
+pub const DEFAULT_MAX_PACKET: usize = 1432;
+
+#[derive(Default)]
+struct StatsdState {
+    // 还没发送的行
+    pending: Vec<String>,
+    pending_bytes: usize,
+    // send_registry 上次发送时各计数器的值，用于计算增量
+    last_counters: HashMap<String, u64>,
+}
+
+pub struct StatsdClient {
+    socket: UdpSocket,
+    prefix: String,
+    max_packet: usize,
+    state: Mutex<StatsdState>,
+    sent: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl StatsdClient {
+    // prefix 非空时每个指标名前加 "<prefix>."
+    pub fn new(addr: impl ToSocketAddrs, prefix: &str) -> io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        socket.connect(addr)?;
+        Ok(StatsdClient {
+            socket,
+            prefix: prefix.to_string(),
+            max_packet: DEFAULT_MAX_PACKET,
+            state: Mutex::new(StatsdState::default()),
+            sent: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        })
+    }
+
+    pub fn max_packet(mut self, bytes: usize) -> Self {
+        self.max_packet = bytes.max(64);
+        self
+    }
+
+    pub fn count(&self, name: &str, value: i64) {
+        self.push_line(format!("{}:{}|c", self.name(name), value));
+    }
+
+    pub fn gauge(&self, name: &str, value: f64) {
+        self.push_line(format!("{}:{}|g", self.name(name), value));
+    }
+
+    pub fn timing(&self, name: &str, duration: Duration) {
+        self.push_line(format!(
+            "{}:{}|ms",
+            self.name(name),
+            duration.as_secs_f64() * 1000.0
+        ));
+    }
+
+    // 计数器发送自上次调用以来的增量，仪表原样发送，直方图发送 p50/p99/count 三个仪表
+    pub fn send_registry(&self, registry: &Registry) {
+        let metrics = registry.snapshot();
+        let mut lines = Vec::new();
+        {
+            let mut state = self.state.lock().unwrap();
+            for metric in metrics {
+                let name = self.name(&metric.name);
+                match metric.value {
+                    MetricValue::Counter { value } => {
+                        let last = state.last_counters.insert(metric.name, value).unwrap_or(0);
+                        if value > last {
+                            lines.push(format!("{}:{}|c", name, value - last));
+                        }
+                    }
+                    MetricValue::Gauge { value } => lines.push(format!("{}:{}|g", name, value)),
+                    MetricValue::Histogram(h) => {
+                        lines.push(format!("{}.p50:{}|g", name, h.p50));
+                        lines.push(format!("{}.p99:{}|g", name, h.p99));
+                        lines.push(format!("{}.count:{}|g", name, h.count));
+                    }
+                }
+            }
+        }
+        for line in lines {
+            self.push_line(line);
+        }
+        self.flush();
+    }
+
+    // 发送所有缓冲的指标。发送失败的数据包计入 dropped，不返回错误
+    pub fn flush(&self) {
+        let pending = {
+            let mut state = self.state.lock().unwrap();
+            state.pending_bytes = 0;
+            std::mem::take(&mut state.pending)
+        };
+        let mut packet = String::new();
+        for line in pending {
+            if !packet.is_empty() && packet.len() + 1 + line.len() > self.max_packet {
+                self.send_packet(&std::mem::take(&mut packet));
+            }
+            if !packet.is_empty() {
+                packet.push('\n');
+            }
+            packet.push_str(&line);
+        }
+        if !packet.is_empty() {
+            self.send_packet(&packet);
+        }
+    }
+
+    // 成功发送的数据包数
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn name(&self, name: &str) -> String {
+        if self.prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", self.prefix, name)
+        }
+    }
+
+    // 缓冲的内容够一个数据包时立即发送
+    fn push_line(&self, line: String) {
+        let full = {
+            let mut state = self.state.lock().unwrap();
+            state.pending_bytes += line.len() + 1;
+            state.pending.push(line);
+            state.pending_bytes >= self.max_packet
+        };
+        if full {
+            self.flush();
+        }
+    }
+
+    fn send_packet(&self, packet: &str) {
+        match self.socket.send(packet.as_bytes()) {
+            Ok(_) => self.sent.fetch_add(1, Ordering::Relaxed),
+            Err(_) => self.dropped.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+}
+
+impl Drop for StatsdClient {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use crate::app::BoxError;
+use crate::clock::{self, SharedClock};
+use crate::logs;
+use crate::mail::{Mail, Mailer};
+use crate::ratelimit::TokenBucket;
+use crate::retry::{retry, Backoff};
+
+// 通知分发: 每种事件按配置发往一个或多个渠道(邮件、webhook、仅日志)，
+// 通过后台队列投递，失败时重试，每个渠道单独限流。
+
+pub type NotifyFuture<'a> = Pin<Box<dyn Future<Output = Result<(), BoxError>> + Send + 'a>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notification {
+    pub event: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl Notification {
+    pub fn new(event: &str, subject: &str, body: &str) -> Self {
+        Notification {
+            event: event.to_string(),
+            subject: subject.to_string(),
+            body: body.to_string(),
+        }
+    }
+}
+
+pub trait Notifier: Send + Sync {
+    fn send<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a>;
+}
+
+pub struct EmailNotifier {
+    mailer: Mailer,
+    from: String,
+    to: Vec<String>,
+}
+
+impl EmailNotifier {
+    pub fn new(mailer: Mailer, from: &str, to: &[&str]) -> Self {
+        EmailNotifier {
+            mailer,
+            from: from.to_string(),
+            to: to.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+}
+
+impl Notifier for EmailNotifier {
+    fn send<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let mail = Mail {
+                from: self.from.clone(),
+                to: self.to.clone(),
+                subject: notification.subject.clone(),
+                body: notification.body.clone(),
+            };
+            self.mailer.send(&mail).await?;
+            Ok(())
+        })
+    }
+}
+
+// 把通知以 JSON POST 到指定地址
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: &str) -> Self {
+        WebhookNotifier {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+        }
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn send<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            self.client
+                .post(&self.url)
+                .header("content-type", "application/json")
+                .body(serde_json::to_string(notification)?)
+                .send()
+                .await?
+                .error_for_status()?;
+            Ok(())
+        })
+    }
+}
+
+pub struct LogNotifier;
+
+impl Notifier for LogNotifier {
+    fn send<'a>(&'a self, notification: &'a Notification) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            logs::info(
+                "notify",
+                &format!(
+                    "[{}] {}: {}",
+                    notification.event, notification.subject, notification.body
+                ),
+            );
+            Ok(())
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RateLimit {
+    pub burst: u32,
+    pub per_second: f64,
+}
+
+// routes: 事件 → 渠道名；没有配置的事件使用 default
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotifyConfig {
+    pub routes: HashMap<String, Vec<String>>,
+    pub default: Vec<String>,
+    pub rate_limits: HashMap<String, RateLimit>,
+}
+
+struct Channel {
+    name: String,
+    notifier: Arc<dyn Notifier>,
+    limiter: Option<TokenBucket>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delivery {
+    pub channel: String,
+    pub event: String,
+    pub error: Option<String>,
+}
+
+pub struct DispatcherBuilder {
+    config: NotifyConfig,
+    clock: SharedClock,
+    backoff: Backoff,
+    channels: HashMap<String, Arc<dyn Notifier>>,
+}
+
+impl DispatcherBuilder {
+    pub fn channel(mut self, name: &str, notifier: impl Notifier + 'static) -> Self {
+        self.channels.insert(name.to_string(), Arc::new(notifier));
+        self
+    }
+
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    // 每个渠道一个后台投递任务，限流的渠道不会拖慢其他渠道
+    pub fn start(self) -> Dispatcher {
+        let inner = Arc::new(Inner {
+            clock: self.clock,
+            backoff: self.backoff,
+            deliveries: Mutex::new(Vec::new()),
+        });
+        let mut queues = HashMap::new();
+        for (name, notifier) in self.channels {
+            let limiter = self.config.rate_limits.get(&name).map(|limit| {
+                TokenBucket::with_clock(limit.burst, limit.per_second, inner.clock.clone())
+            });
+            let (tx, rx) = mpsc::unbounded_channel();
+            let channel = Channel {
+                name: name.clone(),
+                notifier,
+                limiter,
+            };
+            tokio::spawn(worker(inner.clone(), channel, rx));
+            queues.insert(name, tx);
+        }
+        Dispatcher {
+            config: Arc::new(self.config),
+            inner,
+            queues,
+        }
+    }
+}
+
+struct Inner {
+    clock: SharedClock,
+    backoff: Backoff,
+    deliveries: Mutex<Vec<Delivery>>,
+}
+
+#[derive(Clone)]
+pub struct Dispatcher {
+    config: Arc<NotifyConfig>,
+    inner: Arc<Inner>,
+    queues: HashMap<String, mpsc::UnboundedSender<Notification>>,
+}
+
+impl Dispatcher {
+    pub fn builder(config: NotifyConfig) -> DispatcherBuilder {
+        DispatcherBuilder {
+            config,
+            clock: clock::system(),
+            backoff: Backoff::default(),
+            channels: HashMap::new(),
+        }
+    }
+
+    // 事件对应的渠道，未注册的渠道会被忽略并记录日志
+    pub fn channels_for(&self, event: &str) -> Vec<String> {
+        let config = &self.config;
+        config.routes.get(event).unwrap_or(&config.default).clone()
+    }
+
+    // 放入投递队列后立即返回
+    pub fn notify(&self, notification: Notification) {
+        for channel in self.channels_for(&notification.event) {
+            match self.queues.get(&channel) {
+                Some(queue) => {
+                    let _ = queue.send(notification.clone());
+                }
+                None => logs::warn("notify", &format!("未注册的通知渠道: {}", channel)),
+            }
+        }
+    }
+
+    // 已完成(成功或最终失败)的投递记录
+    pub fn deliveries(&self) -> Vec<Delivery> {
+        self.inner.deliveries.lock().unwrap().clone()
+    }
+}
+
+async fn worker(
+    inner: Arc<Inner>,
+    channel: Channel,
+    mut rx: mpsc::UnboundedReceiver<Notification>,
+) {
+    while let Some(notification) = rx.recv().await {
+        if let Some(limiter) = &channel.limiter {
+            limiter.acquire().await;
+        }
+        let result = retry(inner.clock.as_ref(), &inner.backoff, || {
+            channel.notifier.send(&notification)
+        })
+        .await;
+        if let Err(e) = &result {
+            logs::error(
+                "notify",
+                &format!(
+                    "通知 {} 经 {} 投递失败: {}",
+                    notification.event, channel.name, e
+                ),
+            );
+        }
+        inner.deliveries.lock().unwrap().push(Delivery {
+            channel: channel.name.clone(),
+            event: notification.event,
+            error: result.err().map(|e| e.to_string()),
+        });
+    }
+}
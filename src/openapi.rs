@@ -0,0 +1,420 @@
+use std::fmt::Write;
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+// 根据 OpenAPI 3 描述生成类型化的客户端代码: components.schemas 生成结构体和枚举，
+// 每个带 operationId 的操作生成一个基于 http::HttpClient 的异步方法。
+// 可以在 build.rs 中调用 generate 写入 OUT_DIR，或者用 `std-app openapi spec.json Name` 生成后提交。
+
+#[derive(Error, Debug, PartialEq)]
+pub enum CodegenError {
+    #[error("无效的 OpenAPI 描述: {0}")]
+    Invalid(String),
+
+    #[error("不支持的 $ref: {0}")]
+    UnsupportedRef(String),
+}
+
+const METHODS: [&str; 5] = ["get", "put", "post", "delete", "patch"];
+
+const KEYWORDS: [&str; 35] = [
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false",
+    "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+    "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where", "while",
+    "yield",
+];
+
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_ascii_uppercase() && prev_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        current.push(c.to_ascii_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn snake(name: &str) -> String {
+    let ident = words(name).join("_");
+    let ident = if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("f_{}", ident)
+    } else {
+        ident
+    };
+    match ident.as_str() {
+        "self" | "crate" | "super" => format!("{}_", ident),
+        _ if KEYWORDS.contains(&ident.as_str()) => format!("r#{}", ident),
+        _ => ident,
+    }
+}
+
+fn pascal(name: &str) -> String {
+    let ident: String = words(name)
+        .iter()
+        .map(|w| {
+            let mut chars = w.chars();
+            chars
+                .next()
+                .map(|c| c.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("V{}", ident)
+    } else {
+        ident
+    }
+}
+
+fn ref_name(reference: &str) -> Result<String, CodegenError> {
+    reference
+        .strip_prefix("#/components/schemas/")
+        .map(pascal)
+        .ok_or_else(|| CodegenError::UnsupportedRef(reference.to_string()))
+}
+
+// 内联的对象和枚举不单独生成类型，使用 serde_json::Value / String
+fn rust_type(schema: &Value) -> Result<String, CodegenError> {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return ref_name(reference);
+    }
+    let format = schema.get("format").and_then(Value::as_str);
+    Ok(match schema.get("type").and_then(Value::as_str) {
+        Some("string") => "String".to_string(),
+        Some("integer") if format == Some("int32") => "i32".to_string(),
+        Some("integer") => "i64".to_string(),
+        Some("number") if format == Some("float") => "f32".to_string(),
+        Some("number") => "f64".to_string(),
+        Some("boolean") => "bool".to_string(),
+        Some("array") => format!(
+            "Vec<{}>",
+            rust_type(schema.get("items").unwrap_or(&Value::Null))?
+        ),
+        _ => "serde_json::Value".to_string(),
+    })
+}
+
+fn comment(out: &mut String, indent: &str, text: Option<&str>) {
+    for line in text
+        .unwrap_or_default()
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+    {
+        let _ = writeln!(out, "{}// {}", indent, line.trim());
+    }
+}
+
+fn schema_item(out: &mut String, name: &str, schema: &Value) -> Result<(), CodegenError> {
+    let type_name = pascal(name);
+    comment(out, "", schema.get("description").and_then(Value::as_str));
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        let _ = writeln!(
+            out,
+            "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]"
+        );
+        let _ = writeln!(out, "pub enum {} {{", type_name);
+        for value in values {
+            let value = value
+                .as_str()
+                .ok_or_else(|| CodegenError::Invalid(format!("{} 的枚举值必须是字符串", name)))?;
+            let _ = writeln!(out, "    #[serde(rename = {:?})]", value);
+            let _ = writeln!(out, "    {},", pascal(value));
+        }
+        let _ = writeln!(out, "}}");
+        return Ok(());
+    }
+
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        let _ = writeln!(out, "pub type {} = {};", type_name, rust_type(schema)?);
+        return Ok(());
+    };
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|r| r.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let _ = writeln!(
+        out,
+        "#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]"
+    );
+    let _ = writeln!(out, "pub struct {} {{", type_name);
+    for (field, property) in properties {
+        comment(
+            out,
+            "    ",
+            property.get("description").and_then(Value::as_str),
+        );
+        let ident = snake(field);
+        let optional = !required.contains(&field.as_str());
+        let mut attrs = Vec::new();
+        if ident.trim_start_matches("r#") != field {
+            attrs.push(format!("rename = {:?}", field));
+        }
+        if optional {
+            attrs.push("default".to_string());
+            attrs.push("skip_serializing_if = \"Option::is_none\"".to_string());
+        }
+        if !attrs.is_empty() {
+            let _ = writeln!(out, "    #[serde({})]", attrs.join(", "));
+        }
+        let ty = rust_type(property)?;
+        let ty = if optional {
+            format!("Option<{}>", ty)
+        } else {
+            ty
+        };
+        let _ = writeln!(out, "    pub {}: {},", ident, ty);
+    }
+    let _ = writeln!(out, "}}");
+    Ok(())
+}
+
+fn json_schema(content: Option<&Value>) -> Option<&Value> {
+    content?
+        .get("content")?
+        .get("application/json")?
+        .get("schema")
+}
+
+struct Param {
+    name: String,
+    ident: String,
+    ty: String,
+    location: String,
+    required: bool,
+}
+
+fn params(operation: &Value, shared: Option<&Value>) -> Result<Vec<Param>, CodegenError> {
+    let mut params = Vec::new();
+    let all = shared
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .chain(
+            operation
+                .get("parameters")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten(),
+        );
+    for param in all {
+        let name = param
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| CodegenError::Invalid("参数缺少 name".to_string()))?;
+        let location = param.get("in").and_then(Value::as_str).unwrap_or("query");
+        if location != "path" && location != "query" {
+            continue;
+        }
+        let schema = param.get("schema").unwrap_or(&Value::Null);
+        params.push(Param {
+            name: name.to_string(),
+            ident: snake(name),
+            ty: match rust_type(schema)?.as_str() {
+                "String" => "&str".to_string(),
+                other => other.to_string(),
+            },
+            location: location.to_string(),
+            required: location == "path"
+                || param
+                    .get("required")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false),
+        });
+    }
+    Ok(params)
+}
+
+fn operation_item(
+    out: &mut String,
+    method: &str,
+    path: &str,
+    operation: &Value,
+    shared: Option<&Value>,
+) -> Result<(), CodegenError> {
+    let Some(operation_id) = operation.get("operationId").and_then(Value::as_str) else {
+        return Ok(());
+    };
+    let params = params(operation, shared)?;
+    let body = json_schema(operation.get("requestBody"))
+        .map(rust_type)
+        .transpose()?;
+    let response = operation
+        .get("responses")
+        .and_then(Value::as_object)
+        .and_then(|responses| responses.iter().find(|(status, _)| status.starts_with('2')))
+        .and_then(|(_, response)| json_schema(Some(response)))
+        .map(rust_type)
+        .transpose()?
+        .unwrap_or_else(|| "()".to_string());
+
+    let mut args: Vec<String> = params
+        .iter()
+        .map(|p| {
+            if p.required {
+                format!("{}: {}", p.ident, p.ty)
+            } else {
+                format!("{}: Option<{}>", p.ident, p.ty)
+            }
+        })
+        .collect();
+    if let Some(body) = &body {
+        args.push(format!("body: &{}", body));
+    }
+
+    let _ = writeln!(out);
+    let summary = operation
+        .get("summary")
+        .and_then(Value::as_str)
+        .map(|s| format!(": {}", s))
+        .unwrap_or_default();
+    let _ = writeln!(out, "    // {} {}{}", method.to_uppercase(), path, summary);
+    let _ = writeln!(
+        out,
+        "    pub async fn {}(&self{}) -> Result<{}, ApiError> {{",
+        snake(operation_id),
+        args.iter().map(|a| format!(", {}", a)).collect::<String>(),
+        response
+    );
+
+    let mut template = path.to_string();
+    let mut format_args = Vec::new();
+    for param in params.iter().filter(|p| p.location == "path") {
+        template = template.replace(&format!("{{{}}}", param.name), "{}");
+        if param.ty == "&str" {
+            format_args.push(format!("encode_path({})", param.ident));
+        } else {
+            format_args.push(format!("encode_path(&{}.to_string())", param.ident));
+        }
+    }
+    let path_arg = if format_args.is_empty() {
+        let _ = writeln!(out, "        let path = {:?};", path);
+        "path"
+    } else {
+        let _ = writeln!(
+            out,
+            "        let path = format!({:?}, {});",
+            template,
+            format_args.join(", ")
+        );
+        "&path"
+    };
+
+    let query: Vec<&Param> = params.iter().filter(|p| p.location == "query").collect();
+    let query_arg = if query.is_empty() {
+        "&[]"
+    } else {
+        let _ = writeln!(
+            out,
+            "        let mut query: Vec<(&str, String)> = Vec::new();"
+        );
+        for param in query {
+            if param.required {
+                let _ = writeln!(
+                    out,
+                    "        query.push(({:?}, {}.to_string()));",
+                    param.name, param.ident
+                );
+            } else {
+                let _ = writeln!(out, "        if let Some(value) = {} {{", param.ident);
+                let _ = writeln!(
+                    out,
+                    "            query.push(({:?}, value.to_string()));",
+                    param.name
+                );
+                let _ = writeln!(out, "        }}");
+            }
+        }
+        "&query"
+    };
+    let (body_type, body_arg) = match &body {
+        Some(body) => (body.as_str(), "Some(body)"),
+        None => ("()", "None"),
+    };
+    let _ = writeln!(
+        out,
+        "        self.http.call::<{}, _>(Method::{}, {}, {}, {}).await",
+        body_type,
+        method.to_uppercase(),
+        path_arg,
+        query_arg,
+        body_arg
+    );
+    let _ = writeln!(out, "    }}");
+    Ok(())
+}
+
+// 生成的代码依赖 serde 和 std_app::http，通常 include! 到单独的模块中；
+// 输出是确定的，同样的输入得到同样的代码
+pub fn generate(spec: &Value, client: &str) -> Result<String, CodegenError> {
+    let paths = spec
+        .get("paths")
+        .and_then(Value::as_object)
+        .ok_or_else(|| CodegenError::Invalid("缺少 paths".to_string()))?;
+    let empty = Map::new();
+    let schemas = spec
+        .pointer("/components/schemas")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+
+    let mut out = String::new();
+    let title = spec
+        .pointer("/info/title")
+        .and_then(Value::as_str)
+        .unwrap_or("API");
+    let version = spec
+        .pointer("/info/version")
+        .and_then(Value::as_str)
+        .unwrap_or("");
+    let _ = writeln!(
+        out,
+        "// 由 std_app::openapi 根据 {} {} 生成，不要手动修改",
+        title, version
+    );
+    let _ = writeln!(out);
+    let _ = writeln!(out, "use serde::{{Deserialize, Serialize}};");
+    let _ = writeln!(
+        out,
+        "use std_app::http::{{encode_path, ApiError, HttpClient, Method}};"
+    );
+
+    for (name, schema) in schemas {
+        let _ = writeln!(out);
+        schema_item(&mut out, name, schema)?;
+    }
+
+    let client = pascal(client);
+    let _ = writeln!(out);
+    let _ = writeln!(out, "pub struct {} {{", client);
+    let _ = writeln!(out, "    http: HttpClient,");
+    let _ = writeln!(out, "}}");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "impl {} {{", client);
+    let _ = writeln!(out, "    pub fn new(http: HttpClient) -> Self {{");
+    let _ = writeln!(out, "        {} {{ http }}", client);
+    let _ = writeln!(out, "    }}");
+    for (path, item) in paths {
+        for method in METHODS {
+            if let Some(operation) = item.get(method) {
+                operation_item(&mut out, method, path, operation, item.get("parameters"))?;
+            }
+        }
+    }
+    let _ = writeln!(out, "}}");
+    Ok(out)
+}
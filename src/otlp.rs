@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::logs;
+use crate::metrics::{Metric, MetricValue, Registry};
+use crate::trace::{self, SpanData};
+
+// OTLP/HTTP (JSON 编码) 导出器，把 trace 的 span 和指标注册表发送到 collector
+
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct OtlpConfig {
+    pub enabled: bool,
+    // 例如 http://localhost:4318
+    pub endpoint: String,
+    pub headers: HashMap<String, String>,
+    // 0.0 ~ 1.0，按 trace id 决定是否采样，同一个 trace 的 span 结果一致
+    pub sampling_ratio: f64,
+    pub service_name: String,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        OtlpConfig {
+            enabled: false,
+            endpoint: "http://localhost:4318".to_string(),
+            headers: HashMap::new(),
+            sampling_ratio: 1.0,
+            service_name: "std-app".to_string(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct OtlpExporter {
+    config: Arc<OtlpConfig>,
+    enabled: Arc<AtomicBool>,
+    client: reqwest::Client,
+}
+
+fn nanos(t: SystemTime) -> String {
+    t.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0)
+        .to_string()
+}
+
+impl OtlpExporter {
+    pub fn new(config: OtlpConfig) -> Self {
+        OtlpExporter {
+            enabled: Arc::new(AtomicBool::new(config.enabled)),
+            config: Arc::new(config),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    // 运行时开关，关闭后导出调用直接返回
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn sampled(&self, trace_id: &str) -> bool {
+        let ratio = self.config.sampling_ratio;
+        if ratio >= 1.0 {
+            return true;
+        }
+        // trace id 的低 64 位均匀分布
+        let tail = &trace_id[trace_id.len().saturating_sub(16)..];
+        let value = u64::from_str_radix(tail, 16).unwrap_or(0);
+        (value as f64 / u64::MAX as f64) < ratio
+    }
+
+    fn resource(&self) -> Value {
+        json!({
+            "attributes": [
+                {"key": "service.name", "value": {"stringValue": self.config.service_name}}
+            ]
+        })
+    }
+
+    pub fn encode_spans(&self, spans: &[SpanData]) -> Value {
+        let spans: Vec<Value> = spans
+            .iter()
+            .filter(|s| self.sampled(&s.trace_id))
+            .map(|s| {
+                json!({
+                    "traceId": s.trace_id,
+                    "spanId": s.span_id,
+                    "parentSpanId": s.parent_span_id.clone().unwrap_or_default(),
+                    "name": s.name,
+                    "kind": 1,
+                    "startTimeUnixNano": nanos(s.start),
+                    "endTimeUnixNano": nanos(s.end),
+                    "attributes": s.attributes.iter().map(|(k, v)| {
+                        json!({"key": k, "value": {"stringValue": v}})
+                    }).collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        json!({
+            "resourceSpans": [{
+                "resource": self.resource(),
+                "scopeSpans": [{"scope": {"name": "std-app"}, "spans": spans}]
+            }]
+        })
+    }
+
+    pub fn encode_metrics(&self, metrics: &[Metric]) -> Value {
+        let now = nanos(SystemTime::now());
+        let metrics: Vec<Value> = metrics
+            .iter()
+            .map(|m| match &m.value {
+                MetricValue::Counter { value } => json!({
+                    "name": m.name,
+                    "sum": {
+                        "aggregationTemporality": 2,
+                        "isMonotonic": true,
+                        "dataPoints": [{"asInt": value.to_string(), "timeUnixNano": now}]
+                    }
+                }),
+                MetricValue::Gauge { value } => json!({
+                    "name": m.name,
+                    "gauge": {"dataPoints": [{"asDouble": value, "timeUnixNano": now}]}
+                }),
+                MetricValue::Histogram(h) => json!({
+                    "name": m.name,
+                    "summary": {"dataPoints": [{
+                        "count": h.count.to_string(),
+                        "sum": h.sum,
+                        "quantileValues": [
+                            {"quantile": 0.0, "value": h.min},
+                            {"quantile": 0.5, "value": h.p50},
+                            {"quantile": 0.9, "value": h.p90},
+                            {"quantile": 0.99, "value": h.p99},
+                            {"quantile": 1.0, "value": h.max}
+                        ],
+                        "timeUnixNano": now
+                    }]}
+                }),
+            })
+            .collect();
+        json!({
+            "resourceMetrics": [{
+                "resource": self.resource(),
+                "scopeMetrics": [{"scope": {"name": "std-app"}, "metrics": metrics}]
+            }]
+        })
+    }
+
+    async fn post(&self, path: &str, body: Value) -> Result<(), reqwest::Error> {
+        let url = format!("{}{}", self.config.endpoint.trim_end_matches('/'), path);
+        let mut request = self
+            .client
+            .post(url)
+            .header("content-type", "application/json")
+            .body(body.to_string());
+        for (k, v) in &self.config.headers {
+            request = request.header(k, v);
+        }
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn export_spans(&self, spans: &[SpanData]) -> Result<(), reqwest::Error> {
+        if !self.is_enabled() || spans.is_empty() {
+            return Ok(());
+        }
+        self.post("/v1/traces", self.encode_spans(spans)).await
+    }
+
+    pub async fn export_metrics(&self, registry: &Registry) -> Result<(), reqwest::Error> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        self.post("/v1/metrics", self.encode_metrics(&registry.snapshot()))
+            .await
+    }
+
+    // 后台定期导出；关闭期间结束的 span 直接丢弃
+    pub fn spawn(
+        &self,
+        registry: &'static Registry,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let exporter = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+                let spans = trace::drain();
+                if let Err(e) = exporter.export_spans(&spans).await {
+                    logs::warn("otlp", &format!("导出 trace 失败: {}", e));
+                }
+                if let Err(e) = exporter.export_metrics(registry).await {
+                    logs::warn("otlp", &format!("导出指标失败: {}", e));
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+// 分页参数，page 从 1 开始
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageRequest {
+    pub page: u32,
+    pub per_page: u32,
+}
+
+impl Default for PageRequest {
+    fn default() -> Self {
+        PageRequest {
+            page: 1,
+            per_page: 20,
+        }
+    }
+}
+
+impl PageRequest {
+    pub const MAX_PER_PAGE: u32 = 100;
+
+    // 超出范围的参数自动修正
+    pub fn new(page: u32, per_page: u32) -> Self {
+        PageRequest {
+            page: page.max(1),
+            per_page: per_page.clamp(1, Self::MAX_PER_PAGE),
+        }
+    }
+
+    pub fn offset(&self) -> u64 {
+        (self.page.max(1) as u64 - 1) * self.limit()
+    }
+
+    pub fn limit(&self) -> u64 {
+        self.per_page.clamp(1, Self::MAX_PER_PAGE) as u64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+    pub page: u32,
+    pub per_page: u32,
+}
+
+impl<T> Page<T> {
+    pub fn new(items: Vec<T>, total: u64, request: PageRequest) -> Self {
+        Page {
+            items,
+            total,
+            page: request.page.max(1),
+            per_page: request.limit() as u32,
+        }
+    }
+
+    pub fn total_pages(&self) -> u64 {
+        self.total.div_ceil(self.per_page.max(1) as u64)
+    }
+
+    pub fn has_next(&self) -> bool {
+        (self.page as u64) < self.total_pages()
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            page: self.page,
+            per_page: self.per_page,
+        }
+    }
+}
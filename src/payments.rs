@@ -0,0 +1,746 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::clock::{self, SharedClock};
+use crate::db::DbError;
+use crate::ledger::{Ledger, LedgerError};
+use crate::logs;
+use crate::quota::{Quota, QuotaError};
+
+// 支付流程: 参数校验 → 风控规则 → 用户限额 → 网关扣款 → 记账。
+// 同一个幂等键只会扣款一次；扣款成功但记账失败时向网关发起退款作为补偿。
+// 处理过程中进程崩溃留下的记录在租期过后由 reconcile 接手继续处理。
+
+pub type GatewayFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+#[derive(Error, Debug)]
+pub enum PaymentError {
+    #[error("参数错误: {0}")]
+    Validation(String),
+
+    #[error("风控拒绝 [{rule}]: {reason}")]
+    Rejected { rule: String, reason: String },
+
+    #[error(transparent)]
+    Quota(#[from] QuotaError),
+
+    #[error("网关错误: {0}")]
+    Gateway(#[from] GatewayError),
+
+    #[error("幂等键 {0} 已用于不同的支付请求")]
+    IdempotencyConflict(String),
+
+    #[error("幂等键 {0} 对应的支付正在处理中")]
+    InProgress(String),
+
+    #[error("记账失败，已退款: {0}")]
+    Reversed(#[source] LedgerError),
+
+    #[error("记账失败且退款失败，需要人工处理: {ledger}; {refund}")]
+    ReversalFailed {
+        ledger: LedgerError,
+        refund: GatewayError,
+    },
+
+    #[error(transparent)]
+    Ledger(#[from] LedgerError),
+}
+
+impl From<sqlx::Error> for PaymentError {
+    fn from(e: sqlx::Error) -> Self {
+        PaymentError::Ledger(e.into())
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum GatewayError {
+    #[error("扣款被拒绝: {0}")]
+    Declined(String),
+
+    #[error("网关不可用: {0}")]
+    Unavailable(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PaymentRequest {
+    pub idempotency_key: String,
+    pub from: i64,
+    pub to: i64,
+    pub amount: i64,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Receipt {
+    pub idempotency_key: String,
+    pub charge_id: String,
+    pub transaction_id: i64,
+    pub amount: i64,
+}
+
+pub trait Gateway: Send + Sync {
+    // 返回网关侧的扣款 id
+    fn charge<'a>(
+        &'a self,
+        request: &'a PaymentRequest,
+    ) -> GatewayFuture<'a, Result<String, GatewayError>>;
+
+    fn refund<'a>(&'a self, charge_id: &'a str) -> GatewayFuture<'a, Result<(), GatewayError>>;
+}
+
+pub trait RiskRule: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn check(&self, request: &PaymentRequest) -> Result<(), String>;
+
+    // 有状态的规则(例如限额)需要的表，在 Payments::migrate 中创建
+    fn migrate(&self) -> GatewayFuture<'_, Result<(), PaymentError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    // 占用幂等键之后、扣款之前调用，有状态的规则在这里计数
+    fn reserve<'a>(
+        &'a self,
+        _request: &'a PaymentRequest,
+    ) -> GatewayFuture<'a, Result<(), PaymentError>> {
+        Box::pin(async { Ok(()) })
+    }
+
+    // 支付没有完成时撤销 reserve
+    fn revert<'a>(
+        &'a self,
+        _request: &'a PaymentRequest,
+    ) -> GatewayFuture<'a, Result<(), PaymentError>> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+// 单笔金额上限
+pub struct MaxAmount(pub i64);
+
+impl RiskRule for MaxAmount {
+    fn name(&self) -> &str {
+        "max_amount"
+    }
+
+    fn check(&self, request: &PaymentRequest) -> Result<(), String> {
+        if request.amount > self.0 {
+            return Err(format!("金额 {} 超过上限 {}", request.amount, self.0));
+        }
+        Ok(())
+    }
+}
+
+// 按付款账户限额: 每笔支付计一次 requests，金额计入 spend，超限时返回 PaymentError::Quota
+pub struct QuotaRule(pub Quota);
+
+impl RiskRule for QuotaRule {
+    fn name(&self) -> &str {
+        "quota"
+    }
+
+    fn check(&self, _request: &PaymentRequest) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn migrate(&self) -> GatewayFuture<'_, Result<(), PaymentError>> {
+        Box::pin(async move { Ok(self.0.migrate().await?) })
+    }
+
+    fn reserve<'a>(
+        &'a self,
+        request: &'a PaymentRequest,
+    ) -> GatewayFuture<'a, Result<(), PaymentError>> {
+        Box::pin(async move {
+            let user = request.from.to_string();
+            self.0.consume(&user, QUOTA_REQUESTS, 1).await?;
+            if let Err(e) = self.0.consume(&user, QUOTA_SPEND, request.amount).await {
+                self.0.refund(&user, QUOTA_REQUESTS, 1).await?;
+                return Err(e.into());
+            }
+            Ok(())
+        })
+    }
+
+    fn revert<'a>(
+        &'a self,
+        request: &'a PaymentRequest,
+    ) -> GatewayFuture<'a, Result<(), PaymentError>> {
+        Box::pin(async move {
+            let user = request.from.to_string();
+            self.0.refund(&user, QUOTA_REQUESTS, 1).await?;
+            self.0.refund(&user, QUOTA_SPEND, request.amount).await?;
+            Ok(())
+        })
+    }
+}
+
+// 内存网关，测试用；可以预设下一次扣款或退款失败。
+// 和真实网关一样按幂等键去重: 同一个键在退款之前重复扣款返回同一个扣款 id
+#[derive(Clone, Default)]
+pub struct MockGateway {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    charges: Vec<(String, i64)>,
+    refunds: Vec<String>,
+    by_key: HashMap<String, String>,
+    fail_charge: Option<GatewayError>,
+    fail_refund: Option<GatewayError>,
+}
+
+impl MockGateway {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn fail_next_charge(&self, error: GatewayError) {
+        self.state.lock().unwrap().fail_charge = Some(error);
+    }
+
+    pub fn fail_next_refund(&self, error: GatewayError) {
+        self.state.lock().unwrap().fail_refund = Some(error);
+    }
+
+    // (扣款 id, 金额)
+    pub fn charges(&self) -> Vec<(String, i64)> {
+        self.state.lock().unwrap().charges.clone()
+    }
+
+    pub fn refunds(&self) -> Vec<String> {
+        self.state.lock().unwrap().refunds.clone()
+    }
+}
+
+impl Gateway for MockGateway {
+    fn charge<'a>(
+        &'a self,
+        request: &'a PaymentRequest,
+    ) -> GatewayFuture<'a, Result<String, GatewayError>> {
+        Box::pin(async move {
+            let mut state = self.state.lock().unwrap();
+            if let Some(error) = state.fail_charge.take() {
+                return Err(error);
+            }
+            if let Some(id) = state.by_key.get(&request.idempotency_key) {
+                return Ok(id.clone());
+            }
+            let id = format!("ch_{}", state.charges.len() + 1);
+            state.charges.push((id.clone(), request.amount));
+            state
+                .by_key
+                .insert(request.idempotency_key.clone(), id.clone());
+            Ok(id)
+        })
+    }
+
+    fn refund<'a>(&'a self, charge_id: &'a str) -> GatewayFuture<'a, Result<(), GatewayError>> {
+        Box::pin(async move {
+            let mut state = self.state.lock().unwrap();
+            if let Some(error) = state.fail_refund.take() {
+                return Err(error);
+            }
+            state.refunds.push(charge_id.to_string());
+            state.by_key.retain(|_, id| id != charge_id);
+            Ok(())
+        })
+    }
+}
+
+// 基于 HTTP 的网关: POST {base}/charges 返回 {"id": ...}，
+// POST {base}/charges/{id}/refund；幂等键放在 Idempotency-Key 头中
+pub struct HttpGateway {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl HttpGateway {
+    pub fn new(base_url: &str) -> Self {
+        HttpGateway {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn post(&self, path: &str, key: &str, body: String) -> Result<Value, GatewayError> {
+        let response = self
+            .client
+            .post(format!("{}{}", self.base_url, path))
+            .header("content-type", "application/json")
+            .header("idempotency-key", key)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| GatewayError::Unavailable(e.to_string()))?;
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .map_err(|e| GatewayError::Unavailable(e.to_string()))?;
+        if status.is_client_error() {
+            return Err(GatewayError::Declined(text));
+        }
+        if !status.is_success() {
+            return Err(GatewayError::Unavailable(format!("HTTP {}", status)));
+        }
+        if text.is_empty() {
+            return Ok(Value::Null);
+        }
+        serde_json::from_str(&text).map_err(|e| GatewayError::Unavailable(e.to_string()))
+    }
+}
+
+impl Gateway for HttpGateway {
+    fn charge<'a>(
+        &'a self,
+        request: &'a PaymentRequest,
+    ) -> GatewayFuture<'a, Result<String, GatewayError>> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "amount": request.amount,
+                "description": request.description,
+            });
+            let response = self
+                .post("/charges", &request.idempotency_key, body.to_string())
+                .await?;
+            response["id"]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| GatewayError::Unavailable("响应中缺少 id".to_string()))
+        })
+    }
+
+    fn refund<'a>(&'a self, charge_id: &'a str) -> GatewayFuture<'a, Result<(), GatewayError>> {
+        Box::pin(async move {
+            let path = format!("/charges/{}/refund", charge_id);
+            let key = format!("refund-{}", charge_id);
+            self.post(&path, &key, String::new()).await.map(|_| ())
+        })
+    }
+}
+
+pub struct Payments {
+    ledger: Ledger,
+    gateway: Arc<dyn Gateway>,
+    rules: Vec<Box<dyn RiskRule>>,
+    clock: SharedClock,
+    lease: Duration,
+}
+
+// 限额指标: 每笔支付计一次 requests，金额计入 spend，按付款账户统计
+pub const QUOTA_REQUESTS: &str = "requests";
+pub const QUOTA_SPEND: &str = "spend";
+
+// 未完成的记录超过这个时间没有进展，视为处理它的进程已经退出
+pub const DEFAULT_LEASE: Duration = Duration::from_secs(300);
+
+// 幂等记录的状态:
+//   claimed: 占用了幂等键，规则还没有计数
+//   pending: 规则已计数，准备扣款
+//   charged: 扣款成功，charge_id 已保存，等待记账
+//   completed: 已记账
+//   reversal_failed: 记账失败且退款失败
+// 每次状态变化都更新 updated_at_ms，reconcile 据此找出崩溃遗留的记录
+#[derive(sqlx::FromRow)]
+struct Record {
+    idempotency_key: String,
+    from_account: i64,
+    to_account: i64,
+    amount: i64,
+    description: String,
+    status: String,
+    charge_id: Option<String>,
+    transaction_id: Option<i64>,
+    updated_at_ms: i64,
+}
+
+impl Record {
+    fn request(&self) -> PaymentRequest {
+        PaymentRequest {
+            idempotency_key: self.idempotency_key.clone(),
+            from: self.from_account,
+            to: self.to_account,
+            amount: self.amount,
+            description: self.description.clone(),
+        }
+    }
+}
+
+// reconcile 的处理结果，按幂等键分类
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ReconcileReport {
+    // 重新驱动后完成的支付
+    pub completed: Vec<Receipt>,
+    // 没有扣款或已经退款，幂等键已释放
+    pub released: Vec<String>,
+    // 仍然失败，下一个租期之后再次处理
+    pub failed: Vec<String>,
+}
+
+const RECORD_COLUMNS: &str = "idempotency_key, from_account, to_account, amount, description,
+     status, charge_id, transaction_id, updated_at_ms";
+
+impl Payments {
+    pub fn new(ledger: Ledger, gateway: Arc<dyn Gateway>) -> Self {
+        Payments {
+            ledger,
+            gateway,
+            rules: Vec::new(),
+            clock: clock::system(),
+            lease: DEFAULT_LEASE,
+        }
+    }
+
+    pub fn rule(mut self, rule: impl RiskRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    pub async fn migrate(&self) -> Result<(), PaymentError> {
+        self.ledger.migrate().await?;
+        for rule in &self.rules {
+            rule.migrate().await?;
+        }
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS payments (
+                idempotency_key TEXT PRIMARY KEY,
+                from_account INTEGER NOT NULL,
+                to_account INTEGER NOT NULL,
+                amount INTEGER NOT NULL,
+                description TEXT NOT NULL DEFAULT '',
+                status TEXT NOT NULL,
+                charge_id TEXT,
+                transaction_id INTEGER,
+                updated_at_ms INTEGER NOT NULL DEFAULT 0
+            )",
+        )
+        .execute(self.ledger.pool())
+        .await?;
+        Ok(())
+    }
+
+    fn validate(request: &PaymentRequest) -> Result<(), PaymentError> {
+        if request.idempotency_key.is_empty() {
+            return Err(PaymentError::Validation("缺少幂等键".to_string()));
+        }
+        if request.amount <= 0 {
+            return Err(PaymentError::Validation("金额必须大于0".to_string()));
+        }
+        if request.from == request.to {
+            return Err(PaymentError::Validation(
+                "付款账户和收款账户相同".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn now_ms(&self) -> i64 {
+        self.clock
+            .system_time()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0)
+    }
+
+    pub async fn process_payment(&self, request: &PaymentRequest) -> Result<Receipt, PaymentError> {
+        Self::validate(request)?;
+        for rule in &self.rules {
+            rule.check(request)
+                .map_err(|reason| PaymentError::Rejected {
+                    rule: rule.name().to_string(),
+                    reason,
+                })?;
+        }
+        if let Some(receipt) = self.claim(request).await? {
+            return Ok(receipt);
+        }
+        self.reserve(request).await?;
+        self.drive(request, None).await
+    }
+
+    // 规则计数，失败时撤销已经计数的规则并释放幂等键
+    async fn reserve(&self, request: &PaymentRequest) -> Result<(), PaymentError> {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if let Err(e) = rule.reserve(request).await {
+                for rule in &self.rules[..i] {
+                    rule.revert(request).await?;
+                }
+                self.forget(request).await?;
+                return Err(e);
+            }
+        }
+        self.touch(request, "pending", None).await
+    }
+
+    // 从 pending(charge_id 为 None) 或 charged 状态继续: 扣款 → 记账，记账失败时退款
+    async fn drive(
+        &self,
+        request: &PaymentRequest,
+        charge_id: Option<String>,
+    ) -> Result<Receipt, PaymentError> {
+        let charge_id = match charge_id {
+            Some(id) => id,
+            None => match self.gateway.charge(request).await {
+                Ok(id) => id,
+                Err(e) => {
+                    self.release(request).await?;
+                    return Err(e.into());
+                }
+            },
+        };
+        self.touch(request, "charged", Some(&charge_id)).await?;
+
+        let transaction_id = match self.record(request, &charge_id).await {
+            Ok(id) => id,
+            Err(ledger) => {
+                // 补偿: 退款后释放幂等键，允许之后重试
+                logs::warn(
+                    "payments",
+                    &format!(
+                        "支付 {} 记账失败，退款 {}: {}",
+                        request.idempotency_key, charge_id, ledger
+                    ),
+                );
+                return match self.gateway.refund(&charge_id).await {
+                    Ok(()) => {
+                        self.release(request).await?;
+                        Err(PaymentError::Reversed(ledger))
+                    }
+                    Err(refund) => {
+                        self.touch(request, "reversal_failed", None).await?;
+                        Err(PaymentError::ReversalFailed { ledger, refund })
+                    }
+                };
+            }
+        };
+
+        Ok(Receipt {
+            idempotency_key: request.idempotency_key.clone(),
+            charge_id,
+            transaction_id,
+            amount: request.amount,
+        })
+    }
+
+    // 接手超过租期仍未完成的记录(处理它的进程崩溃或失去响应)，重新驱动网关扣款或退款。
+    // 网关按幂等键去重，pending 记录重新扣款不会重复收费。
+    // 定期调用，例如由 scheduler 每个租期执行一次
+    pub async fn reconcile(&self) -> Result<ReconcileReport, PaymentError> {
+        let stale_before = self.now_ms() - self.lease.as_millis() as i64;
+        let records: Vec<Record> = sqlx::query_as(&format!(
+            "SELECT {} FROM payments
+             WHERE status != 'completed' AND updated_at_ms <= ?
+             ORDER BY updated_at_ms",
+            RECORD_COLUMNS
+        ))
+        .bind(stale_before)
+        .fetch_all(self.ledger.pool())
+        .await?;
+
+        let mut report = ReconcileReport::default();
+        for record in records {
+            if !self.take_over(&record).await? {
+                continue;
+            }
+            let request = record.request();
+            logs::warn(
+                "payments",
+                &format!(
+                    "接手未完成的支付 {} ({})",
+                    request.idempotency_key, record.status
+                ),
+            );
+            let result = match (record.status.as_str(), record.charge_id) {
+                ("claimed", _) => {
+                    self.forget(&request).await?;
+                    report.released.push(request.idempotency_key);
+                    continue;
+                }
+                ("reversal_failed", Some(charge_id)) => {
+                    match self.gateway.refund(&charge_id).await {
+                        Ok(()) => {
+                            self.release(&request).await?;
+                            report.released.push(request.idempotency_key);
+                        }
+                        Err(e) => {
+                            logs::error(
+                                "payments",
+                                &format!(
+                                    "支付 {} 退款 {} 仍然失败: {}",
+                                    request.idempotency_key, charge_id, e
+                                ),
+                            );
+                            report.failed.push(request.idempotency_key);
+                        }
+                    }
+                    continue;
+                }
+                ("charged", Some(charge_id)) => self.drive(&request, Some(charge_id)).await,
+                _ => self.drive(&request, None).await,
+            };
+            match result {
+                Ok(receipt) => report.completed.push(receipt),
+                Err(PaymentError::Gateway(_) | PaymentError::Reversed(_)) => {
+                    report.released.push(request.idempotency_key)
+                }
+                Err(PaymentError::ReversalFailed { .. }) => {
+                    report.failed.push(request.idempotency_key)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(report)
+    }
+
+    // 只有 updated_at_ms 没有变化时才能接手，避免多个进程同时处理同一条记录
+    async fn take_over(&self, record: &Record) -> Result<bool, PaymentError> {
+        let result = sqlx::query(
+            "UPDATE payments SET updated_at_ms = ?
+             WHERE idempotency_key = ? AND status = ? AND updated_at_ms = ?",
+        )
+        .bind(self.now_ms())
+        .bind(&record.idempotency_key)
+        .bind(&record.status)
+        .bind(record.updated_at_ms)
+        .execute(self.ledger.pool())
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    // 记账和把幂等记录标记为 completed 在同一个事务中提交，
+    // 任何一步失败都整体回滚，由调用方退款，不会出现记了账但记录仍是 charged 的情况
+    async fn record(&self, request: &PaymentRequest, charge_id: &str) -> Result<i64, LedgerError> {
+        let mut tx = self.ledger.pool().begin().await?;
+        let transaction_id = self
+            .ledger
+            .transfer_in(
+                &mut tx,
+                request.from,
+                request.to,
+                request.amount,
+                &request.description,
+            )
+            .await?;
+        sqlx::query(
+            "UPDATE payments
+             SET status = 'completed', charge_id = ?, transaction_id = ?, updated_at_ms = ?
+             WHERE idempotency_key = ?",
+        )
+        .bind(charge_id)
+        .bind(transaction_id)
+        .bind(self.now_ms())
+        .bind(&request.idempotency_key)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(transaction_id)
+    }
+
+    // 占用幂等键；已完成的请求直接返回之前的结果
+    async fn claim(&self, request: &PaymentRequest) -> Result<Option<Receipt>, PaymentError> {
+        let inserted = sqlx::query(
+            "INSERT INTO payments
+             (idempotency_key, from_account, to_account, amount, description, status, updated_at_ms)
+             VALUES (?, ?, ?, ?, ?, 'claimed', ?)",
+        )
+        .bind(&request.idempotency_key)
+        .bind(request.from)
+        .bind(request.to)
+        .bind(request.amount)
+        .bind(&request.description)
+        .bind(self.now_ms())
+        .execute(self.ledger.pool())
+        .await;
+        match inserted {
+            Ok(_) => return Ok(None),
+            Err(e) => match DbError::from(e) {
+                DbError::Duplicate(_) => {}
+                other => return Err(PaymentError::Ledger(other.into())),
+            },
+        }
+
+        let record: Record = sqlx::query_as(&format!(
+            "SELECT {} FROM payments WHERE idempotency_key = ?",
+            RECORD_COLUMNS
+        ))
+        .bind(&request.idempotency_key)
+        .fetch_one(self.ledger.pool())
+        .await?;
+        if (record.from_account, record.to_account, record.amount)
+            != (request.from, request.to, request.amount)
+        {
+            return Err(PaymentError::IdempotencyConflict(
+                request.idempotency_key.clone(),
+            ));
+        }
+        match (
+            record.status.as_str(),
+            record.charge_id,
+            record.transaction_id,
+        ) {
+            ("completed", Some(charge_id), Some(transaction_id)) => Ok(Some(Receipt {
+                idempotency_key: request.idempotency_key.clone(),
+                charge_id,
+                transaction_id,
+                amount: record.amount,
+            })),
+            // 租期内由原来的进程处理，过期后由 reconcile 接手
+            _ => Err(PaymentError::InProgress(request.idempotency_key.clone())),
+        }
+    }
+
+    // 支付没有完成: 撤销规则的计数(例如退回限额)并释放幂等键
+    async fn release(&self, request: &PaymentRequest) -> Result<(), PaymentError> {
+        for rule in &self.rules {
+            rule.revert(request).await?;
+        }
+        self.forget(request).await
+    }
+
+    async fn forget(&self, request: &PaymentRequest) -> Result<(), PaymentError> {
+        sqlx::query("DELETE FROM payments WHERE idempotency_key = ?")
+            .bind(&request.idempotency_key)
+            .execute(self.ledger.pool())
+            .await?;
+        Ok(())
+    }
+
+    // 推进状态并续租；charge_id 为 None 时保留原值
+    async fn touch(
+        &self,
+        request: &PaymentRequest,
+        status: &str,
+        charge_id: Option<&str>,
+    ) -> Result<(), PaymentError> {
+        sqlx::query(
+            "UPDATE payments SET status = ?, charge_id = COALESCE(?, charge_id), updated_at_ms = ?
+             WHERE idempotency_key = ?",
+        )
+        .bind(status)
+        .bind(charge_id)
+        .bind(self.now_ms())
+        .bind(&request.idempotency_key)
+        .execute(self.ledger.pool())
+        .await?;
+        Ok(())
+    }
+}
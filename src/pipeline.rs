@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// 分块处理管道: reader 按块读入，依次经过各个阶段，最后写入 writer。
+// 标记为并行的阶段由多个线程同时处理不同的块，输出在进入下一阶段前按原始顺序重排，
+// 所以串行阶段(可以有状态)和 writer 看到的块顺序总是与输入一致。
+// 阶段之间用有界队列连接，下游处理不过来时上游阻塞。
+
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+type StageFn = Arc<dyn Fn(Vec<u8>) -> io::Result<Vec<u8>> + Send + Sync>;
+type Message = (u64, io::Result<Vec<u8>>);
+
+struct Stage {
+    name: String,
+    f: StageFn,
+    concurrency: usize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PipelineStats {
+    pub chunks: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+}
+
+pub struct Pipeline {
+    stages: Vec<Stage>,
+    chunk_size: usize,
+    queue: usize,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Pipeline::new()
+    }
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Pipeline {
+            stages: Vec::new(),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            queue: 16,
+        }
+    }
+
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size.max(1);
+        self
+    }
+
+    // 每个阶段之间最多缓存的块数
+    pub fn queue(mut self, n: usize) -> Self {
+        self.queue = n.max(1);
+        self
+    }
+
+    // 串行阶段，按顺序逐块处理
+    pub fn stage<F>(self, name: &str, f: F) -> Self
+    where
+        F: Fn(Vec<u8>) -> io::Result<Vec<u8>> + Send + Sync + 'static,
+    {
+        self.parallel(name, 1, f)
+    }
+
+    // 最多 concurrency 个线程同时处理，f 不能依赖块的处理顺序
+    pub fn parallel<F>(mut self, name: &str, concurrency: usize, f: F) -> Self
+    where
+        F: Fn(Vec<u8>) -> io::Result<Vec<u8>> + Send + Sync + 'static,
+    {
+        self.stages.push(Stage {
+            name: name.to_string(),
+            f: Arc::new(f),
+            concurrency: concurrency.max(1),
+        });
+        self
+    }
+
+    pub fn stage_names(&self) -> Vec<&str> {
+        self.stages.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    // 任一阶段或读写出错时停止并返回第一个错误
+    pub fn run<R, W>(&self, reader: R, mut writer: W) -> io::Result<PipelineStats>
+    where
+        R: Read + Send,
+        W: Write,
+    {
+        let mut stats = PipelineStats::default();
+        thread::scope(|scope| {
+            let (tx, mut rx) = sync_channel::<Message>(self.queue);
+            let chunk_size = self.chunk_size;
+            let source = scope.spawn(move || read_chunks(reader, chunk_size, tx));
+
+            for stage in &self.stages {
+                let (tx, next) = sync_channel::<Message>(self.queue);
+                if stage.concurrency == 1 {
+                    let f = stage.f.clone();
+                    scope.spawn(move || work(&Mutex::new(rx), &f, &tx));
+                } else {
+                    let input = Arc::new(Mutex::new(rx));
+                    for _ in 0..stage.concurrency {
+                        let (input, f, tx) = (input.clone(), stage.f.clone(), tx.clone());
+                        scope.spawn(move || work(&input, &f, &tx));
+                    }
+                    drop(tx);
+                    // 并行阶段的输出是乱序的，重排后再交给下一阶段
+                    let (ordered_tx, ordered) = sync_channel::<Message>(self.queue);
+                    scope.spawn(move || reorder(next, ordered_tx));
+                    rx = ordered;
+                    continue;
+                }
+                rx = next;
+            }
+
+            for (_, chunk) in rx {
+                let chunk = chunk?;
+                stats.chunks += 1;
+                stats.bytes_out += chunk.len() as u64;
+                writer.write_all(&chunk)?;
+            }
+            writer.flush()?;
+            stats.bytes_in = source.join().expect("读取线程 panic");
+            Ok::<_, io::Error>(())
+        })?;
+        Ok(stats)
+    }
+}
+
+// 返回读取的总字节数
+fn read_chunks<R: Read>(mut reader: R, chunk_size: usize, tx: SyncSender<Message>) -> u64 {
+    let mut total = 0;
+    for seq in 0.. {
+        let mut chunk = vec![0u8; chunk_size];
+        let mut filled = 0;
+        while filled < chunk_size {
+            match reader.read(&mut chunk[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    let _ = tx.send((seq, Err(e)));
+                    return total;
+                }
+            }
+        }
+        if filled == 0 {
+            break;
+        }
+        total += filled as u64;
+        chunk.truncate(filled);
+        // 下游已经停止(出错)时发送失败，直接退出
+        if tx.send((seq, Ok(chunk))).is_err() || filled < chunk_size {
+            break;
+        }
+    }
+    total
+}
+
+fn work(input: &Mutex<Receiver<Message>>, f: &StageFn, tx: &SyncSender<Message>) {
+    loop {
+        // 只在取消息时持有锁，处理时其他线程可以继续取
+        let message = input.lock().unwrap().recv();
+        let Ok((seq, chunk)) = message else {
+            return;
+        };
+        if tx.send((seq, chunk.and_then(|c| f(c)))).is_err() {
+            return;
+        }
+    }
+}
+
+fn reorder(rx: Receiver<Message>, tx: SyncSender<Message>) {
+    let mut pending = BTreeMap::new();
+    let mut next = 0u64;
+    for (seq, chunk) in rx {
+        pending.insert(seq, chunk);
+        while let Some(chunk) = pending.remove(&next) {
+            if tx.send((next, chunk)).is_err() {
+                return;
+            }
+            next += 1;
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::SqlitePool;
+use tokio::sync::broadcast;
+
+use crate::app::BoxError;
+use crate::logs;
+
+// 插件系统: 下游应用实现 Plugin 并用 register_plugin! 注册，
+// 运行时通过 PluginHost 统一初始化、检查健康状态和关闭，不需要修改本 crate。
+
+#[doc(hidden)]
+pub use linkme;
+
+#[doc(hidden)]
+#[linkme::distributed_slice]
+pub static PLUGINS: [fn() -> Box<dyn Plugin>];
+
+// 在任意模块中注册插件，表达式在加载时求值一次
+#[macro_export]
+macro_rules! register_plugin {
+    ($ctor:expr) => {
+        const _: () = {
+            #[$crate::plugins::linkme::distributed_slice($crate::plugins::PLUGINS)]
+            #[linkme(crate = $crate::plugins::linkme)]
+            static PLUGIN: fn() -> Box<dyn $crate::plugins::Plugin> = || Box::new($ctor);
+        };
+    };
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "lowercase")]
+pub enum Health {
+    Healthy,
+    Degraded(String),
+    Unhealthy(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    pub topic: String,
+    pub payload: Value,
+}
+
+// 插件初始化时可以访问的运行时资源
+#[derive(Clone)]
+pub struct PluginContext {
+    // 配置中 plugins.<插件名> 这一节，没有时为 Null
+    pub config: Value,
+    pub events: broadcast::Sender<Event>,
+    pub db: Option<SqlitePool>,
+}
+
+impl PluginContext {
+    pub fn publish(&self, topic: &str, payload: Value) {
+        let _ = self.events.send(Event {
+            topic: topic.to_string(),
+            payload,
+        });
+    }
+}
+
+pub trait Plugin: Send {
+    fn name(&self) -> &str;
+
+    fn init(&mut self, ctx: &PluginContext) -> Result<(), BoxError>;
+
+    fn shutdown(&mut self) {}
+
+    fn health(&self) -> Health {
+        Health::Healthy
+    }
+}
+
+#[derive(Debug)]
+pub struct PluginError {
+    pub plugin: String,
+    pub source: BoxError,
+}
+
+impl fmt::Display for PluginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "插件 {} 初始化失败: {}", self.plugin, self.source)
+    }
+}
+
+impl std::error::Error for PluginError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+pub struct PluginHost {
+    config: Value,
+    events: broadcast::Sender<Event>,
+    db: Option<SqlitePool>,
+    plugins: Vec<Box<dyn Plugin>>,
+    started: usize,
+}
+
+impl PluginHost {
+    // 加载所有通过 register_plugin! 注册的插件，按名称排序
+    pub fn new(config: Value) -> Self {
+        let mut plugins: Vec<Box<dyn Plugin>> = PLUGINS.iter().map(|ctor| ctor()).collect();
+        plugins.sort_by(|a, b| a.name().cmp(b.name()));
+        PluginHost {
+            config,
+            events: broadcast::channel(256).0,
+            db: None,
+            plugins,
+            started: 0,
+        }
+    }
+
+    pub fn db(mut self, pool: SqlitePool) -> Self {
+        self.db = Some(pool);
+        self
+    }
+
+    // 手动添加插件，例如测试中或条件启用的插件
+    pub fn plugin(mut self, plugin: Box<dyn Plugin>) -> Self {
+        self.plugins.push(plugin);
+        self
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.plugins.iter().map(|p| p.name()).collect()
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.events.subscribe()
+    }
+
+    // 依次初始化；某个插件失败时关闭已初始化的插件
+    pub fn init(&mut self) -> Result<(), PluginError> {
+        while self.started < self.plugins.len() {
+            let plugin = &mut self.plugins[self.started];
+            let ctx = PluginContext {
+                config: self
+                    .config
+                    .get("plugins")
+                    .and_then(|p| p.get(plugin.name()))
+                    .cloned()
+                    .unwrap_or(Value::Null),
+                events: self.events.clone(),
+                db: self.db.clone(),
+            };
+            if let Err(source) = plugin.init(&ctx) {
+                let plugin = plugin.name().to_string();
+                self.shutdown();
+                return Err(PluginError { plugin, source });
+            }
+            logs::info("plugins", &format!("插件已加载: {}", plugin.name()));
+            self.started += 1;
+        }
+        Ok(())
+    }
+
+    pub fn health(&self) -> Vec<(String, Health)> {
+        self.plugins[..self.started]
+            .iter()
+            .map(|p| (p.name().to_string(), p.health()))
+            .collect()
+    }
+
+    // 逆序关闭已初始化的插件
+    pub fn shutdown(&mut self) {
+        while self.started > 0 {
+            self.started -= 1;
+            self.plugins[self.started].shutdown();
+        }
+    }
+}
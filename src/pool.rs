@@ -0,0 +1,251 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::Serialize;
+
+pub mod jobs;
+
+// 可复用缓冲区的对象池: get 取出一个清空的缓冲区，离开作用域时自动归还。
+// 用完后容量变得过大的缓冲区不归还，避免一次大请求让池子长期占用大量内存。
+
+pub const DEFAULT_BUFFER_CAPACITY: usize = 8 * 1024;
+
+// 可以放进池子的类型
+pub trait Reusable: Send {
+    fn with_capacity(capacity: usize) -> Self;
+    fn capacity(&self) -> usize;
+    // 归还前清空内容，保留容量
+    fn reset(&mut self);
+}
+
+impl Reusable for Vec<u8> {
+    fn with_capacity(capacity: usize) -> Self {
+        Vec::with_capacity(capacity)
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn reset(&mut self) {
+        self.clear();
+    }
+}
+
+impl Reusable for String {
+    fn with_capacity(capacity: usize) -> Self {
+        String::with_capacity(capacity)
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity()
+    }
+
+    fn reset(&mut self) {
+        self.clear();
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct PoolStats {
+    // 新分配的缓冲区个数
+    pub allocated: u64,
+    // 从池子里复用的次数
+    pub reused: u64,
+    // 因池子已满或容量过大而丢弃的个数
+    pub discarded: u64,
+    pub in_use: usize,
+    // 同时借出的最大数量
+    pub high_water: usize,
+    pub idle: usize,
+}
+
+impl PoolStats {
+    pub fn reuse_rate(&self) -> f64 {
+        let total = self.allocated + self.reused;
+        if total == 0 {
+            0.0
+        } else {
+            self.reused as f64 / total as f64
+        }
+    }
+}
+
+struct State<T> {
+    idle: Vec<T>,
+    stats: PoolStats,
+}
+
+struct Inner<T> {
+    capacity: usize,
+    max_capacity: usize,
+    max_idle: usize,
+    state: Mutex<State<T>>,
+}
+
+// 克隆出来的句柄共享同一个池子
+pub struct Pool<T> {
+    inner: Arc<Inner<T>>,
+}
+
+pub type BufferPool = Pool<Vec<u8>>;
+pub type StringPool = Pool<String>;
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Self {
+        Pool {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Reusable> Pool<T> {
+    // capacity 是新缓冲区的初始容量，最多保留 max_idle 个空闲缓冲区；
+    // 容量超过初始容量 16 倍的缓冲区归还时直接丢弃
+    pub fn new(capacity: usize, max_idle: usize) -> Self {
+        Self::with_max_capacity(capacity, max_idle, capacity.saturating_mul(16))
+    }
+
+    pub fn with_max_capacity(capacity: usize, max_idle: usize, max_capacity: usize) -> Self {
+        Pool {
+            inner: Arc::new(Inner {
+                capacity,
+                max_capacity: max_capacity.max(capacity),
+                max_idle,
+                state: Mutex::new(State {
+                    idle: Vec::new(),
+                    stats: PoolStats::default(),
+                }),
+            }),
+        }
+    }
+
+    // 预先分配 n 个空闲缓冲区
+    pub fn prefill(&self, n: usize) {
+        let mut state = self.inner.state.lock().unwrap();
+        while state.idle.len() < n.min(self.inner.max_idle) {
+            state.idle.push(T::with_capacity(self.inner.capacity));
+            state.stats.allocated += 1;
+        }
+    }
+
+    pub fn get(&self) -> Pooled<T> {
+        let mut state = self.inner.state.lock().unwrap();
+        let item = match state.idle.pop() {
+            Some(item) => {
+                state.stats.reused += 1;
+                item
+            }
+            None => {
+                state.stats.allocated += 1;
+                T::with_capacity(self.inner.capacity)
+            }
+        };
+        state.stats.in_use += 1;
+        state.stats.high_water = state.stats.high_water.max(state.stats.in_use);
+        Pooled {
+            item: Some(item),
+            pool: self.inner.clone(),
+        }
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        let state = self.inner.state.lock().unwrap();
+        PoolStats {
+            idle: state.idle.len(),
+            ..state.stats
+        }
+    }
+
+    // 释放所有空闲缓冲区，借出的不受影响
+    pub fn shrink(&self) {
+        self.inner.state.lock().unwrap().idle.clear();
+    }
+}
+
+impl<T: Reusable> Inner<T> {
+    fn give_back(&self, mut item: T) {
+        let mut state = self.state.lock().unwrap();
+        state.stats.in_use -= 1;
+        if item.capacity() > self.max_capacity || state.idle.len() >= self.max_idle {
+            state.stats.discarded += 1;
+            return;
+        }
+        item.reset();
+        state.idle.push(item);
+    }
+}
+
+// 借出的缓冲区，drop 时归还
+pub struct Pooled<T: Reusable> {
+    item: Option<T>,
+    pool: Arc<Inner<T>>,
+}
+
+impl<T: Reusable> Pooled<T> {
+    // 取得所有权，不再归还到池子
+    pub fn detach(mut self) -> T {
+        let item = self
+            .item
+            .take()
+            .expect("缓冲区只会在 drop 或 detach 时取出");
+        self.pool.state.lock().unwrap().stats.in_use -= 1;
+        item
+    }
+}
+
+impl<T: Reusable> Deref for Pooled<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.item
+            .as_ref()
+            .expect("缓冲区只会在 drop 或 detach 时取出")
+    }
+}
+
+impl<T: Reusable> DerefMut for Pooled<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.item
+            .as_mut()
+            .expect("缓冲区只会在 drop 或 detach 时取出")
+    }
+}
+
+impl<T: Reusable> Drop for Pooled<T> {
+    fn drop(&mut self) {
+        if let Some(item) = self.item.take() {
+            self.pool.give_back(item);
+        }
+    }
+}
+
+impl<T: Reusable + fmt::Debug> fmt::Debug for Pooled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl std::io::Write for Pooled<Vec<u8>> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl fmt::Write for Pooled<String> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        (**self).push_str(s);
+        Ok(())
+    }
+}
+
+// 进程内共享的字节缓冲池，供 I/O 和 HTTP body 处理使用
+pub fn buffers() -> &'static BufferPool {
+    static BUFFERS: OnceLock<BufferPool> = OnceLock::new();
+    BUFFERS.get_or_init(|| BufferPool::new(DEFAULT_BUFFER_CAPACITY, 64))
+}
@@ -0,0 +1,401 @@
+use std::collections::BTreeMap;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use crate::clock::{self, SharedClock};
+use crate::logs;
+
+// 固定数量工作线程的任务池。任务收到 JobContext，长时间运行的任务应该定期调用
+// checkpoint 报告进度、检查是否被要求中止，并用 should_yield 判断是否该让出线程
+// (保存进度后重新提交)。看门狗线程定期检查运行中的任务，超过 max_runtime 的任务
+// 记一条警告，配置了 abort_overdue 时要求它在下一个 checkpoint 中止。
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum JobError {
+    #[error("任务 {name} 运行 {elapsed:?} 后被中止")]
+    Aborted { name: String, elapsed: Duration },
+    #[error("任务 {name} panic: {message}")]
+    Panicked { name: String, message: String },
+    #[error("任务池已关闭")]
+    ShutDown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overdue {
+    pub name: String,
+    pub elapsed: Duration,
+    // 距离上次 checkpoint 的时间，很大说明任务卡住了而不只是慢
+    pub since_checkpoint: Duration,
+    pub aborted: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JobStats {
+    pub completed: u64,
+    pub aborted: u64,
+    pub panicked: u64,
+    pub overdue: u64,
+    pub running: usize,
+    pub queued: usize,
+}
+
+struct JobState {
+    name: String,
+    started: Instant,
+    last_checkpoint: Mutex<Instant>,
+    abort: AtomicBool,
+    // 已经报告过超时，不重复报告
+    reported: AtomicBool,
+}
+
+struct Shared {
+    clock: SharedClock,
+    time_slice: Duration,
+    max_runtime: Option<Duration>,
+    abort_overdue: bool,
+    running: Mutex<BTreeMap<u64, Arc<JobState>>>,
+    next_id: AtomicU64,
+    queued: AtomicUsize,
+    completed: AtomicU64,
+    aborted: AtomicU64,
+    panicked: AtomicU64,
+    overdue: AtomicU64,
+    // 关闭时唤醒看门狗线程
+    stop: (Mutex<bool>, Condvar),
+}
+
+// 传给任务的上下文
+pub struct JobContext {
+    state: Arc<JobState>,
+    shared: Arc<Shared>,
+}
+
+impl JobContext {
+    pub fn name(&self) -> &str {
+        &self.state.name
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.now().saturating_duration_since(self.state.started)
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.state.abort.load(Ordering::Relaxed)
+    }
+
+    // 距离上次 checkpoint 超过时间片，并且有任务在排队
+    pub fn should_yield(&self) -> bool {
+        let last = *self.state.last_checkpoint.lock().unwrap();
+        self.shared.queued.load(Ordering::Relaxed) > 0
+            && self.now().saturating_duration_since(last) >= self.shared.time_slice
+    }
+
+    // 报告进度；任务被要求中止时返回错误，任务应该尽快返回
+    pub fn checkpoint(&self) -> Result<(), JobError> {
+        *self.state.last_checkpoint.lock().unwrap() = self.now();
+        if self.is_aborted() {
+            return Err(JobError::Aborted {
+                name: self.state.name.clone(),
+                elapsed: self.elapsed(),
+            });
+        }
+        Ok(())
+    }
+
+    fn now(&self) -> Instant {
+        self.shared.clock.now()
+    }
+}
+
+type Job = Box<dyn FnOnce(&Arc<Shared>) + Send>;
+
+pub struct JobPoolBuilder {
+    workers: usize,
+    name: String,
+    time_slice: Duration,
+    max_runtime: Option<Duration>,
+    abort_overdue: bool,
+    check_interval: Duration,
+    clock: SharedClock,
+}
+
+impl JobPoolBuilder {
+    // 工作线程名的前缀
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn time_slice(mut self, slice: Duration) -> Self {
+        self.time_slice = slice;
+        self
+    }
+
+    pub fn max_runtime(mut self, max: Duration) -> Self {
+        self.max_runtime = Some(max);
+        self
+    }
+
+    // 超时的任务在下一个 checkpoint 返回 JobError::Aborted
+    pub fn abort_overdue(mut self, abort: bool) -> Self {
+        self.abort_overdue = abort;
+        self
+    }
+
+    // 看门狗的检查间隔
+    pub fn check_interval(mut self, interval: Duration) -> Self {
+        self.check_interval = interval;
+        self
+    }
+
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn build(self) -> JobPool {
+        let shared = Arc::new(Shared {
+            clock: self.clock,
+            time_slice: self.time_slice,
+            max_runtime: self.max_runtime,
+            abort_overdue: self.abort_overdue,
+            running: Mutex::new(BTreeMap::new()),
+            next_id: AtomicU64::new(0),
+            queued: AtomicUsize::new(0),
+            completed: AtomicU64::new(0),
+            aborted: AtomicU64::new(0),
+            panicked: AtomicU64::new(0),
+            overdue: AtomicU64::new(0),
+            stop: (Mutex::new(false), Condvar::new()),
+        });
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let mut threads: Vec<JoinHandle<()>> = (0..self.workers.max(1))
+            .map(|i| {
+                let receiver = receiver.clone();
+                let shared = shared.clone();
+                thread::Builder::new()
+                    .name(format!("{}-{}", self.name, i))
+                    .spawn(move || worker(&receiver, &shared))
+                    .expect("创建工作线程")
+            })
+            .collect();
+        if shared.max_runtime.is_some() {
+            let shared = shared.clone();
+            let interval = self.check_interval;
+            threads.push(
+                thread::Builder::new()
+                    .name(format!("{}-watchdog", self.name))
+                    .spawn(move || watchdog(&shared, interval))
+                    .expect("创建看门狗线程"),
+            );
+        }
+        JobPool {
+            sender: Some(sender),
+            shared,
+            threads,
+        }
+    }
+}
+
+fn worker(receiver: &Mutex<Receiver<Job>>, shared: &Arc<Shared>) {
+    loop {
+        // 只在取任务时持有锁
+        let job = receiver.lock().unwrap().recv();
+        match job {
+            Ok(job) => job(shared),
+            Err(_) => return,
+        }
+    }
+}
+
+fn watchdog(shared: &Arc<Shared>, interval: Duration) {
+    let (lock, condvar) = &shared.stop;
+    let mut stopped = lock.lock().unwrap();
+    while !*stopped {
+        stopped = condvar.wait_timeout(stopped, interval).unwrap().0;
+        check(shared);
+    }
+}
+
+fn check(shared: &Shared) -> Vec<Overdue> {
+    let Some(max_runtime) = shared.max_runtime else {
+        return Vec::new();
+    };
+    let now = shared.clock.now();
+    let running: Vec<Arc<JobState>> = shared.running.lock().unwrap().values().cloned().collect();
+    let mut overdue = Vec::new();
+    for job in running {
+        let elapsed = now.saturating_duration_since(job.started);
+        if elapsed < max_runtime || job.reported.swap(true, Ordering::Relaxed) {
+            continue;
+        }
+        if shared.abort_overdue {
+            job.abort.store(true, Ordering::Relaxed);
+        }
+        shared.overdue.fetch_add(1, Ordering::Relaxed);
+        let since_checkpoint = now.saturating_duration_since(*job.last_checkpoint.lock().unwrap());
+        logs::warn(
+            "pool::jobs",
+            &format!(
+                "任务 {} 已运行 {:?}，超过 {:?}，距上次 checkpoint {:?}{}",
+                job.name,
+                elapsed,
+                max_runtime,
+                since_checkpoint,
+                if shared.abort_overdue {
+                    "，要求中止"
+                } else {
+                    ""
+                }
+            ),
+        );
+        overdue.push(Overdue {
+            name: job.name.clone(),
+            elapsed,
+            since_checkpoint,
+            aborted: shared.abort_overdue,
+        });
+    }
+    overdue
+}
+
+pub struct JobPool {
+    sender: Option<Sender<Job>>,
+    shared: Arc<Shared>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl JobPool {
+    pub fn new(workers: usize) -> Self {
+        Self::builder(workers).build()
+    }
+
+    // 默认时间片 100ms，不限制运行时间，看门狗每秒检查一次
+    pub fn builder(workers: usize) -> JobPoolBuilder {
+        JobPoolBuilder {
+            workers,
+            name: "job".to_string(),
+            time_slice: Duration::from_millis(100),
+            max_runtime: None,
+            abort_overdue: false,
+            check_interval: Duration::from_secs(1),
+            clock: clock::system(),
+        }
+    }
+
+    pub fn submit<F, R>(&self, name: &str, f: F) -> JobHandle<R>
+    where
+        F: FnOnce(&JobContext) -> Result<R, JobError> + Send + 'static,
+        R: Send + 'static,
+    {
+        let (tx, rx) = mpsc::sync_channel(1);
+        let name = name.to_string();
+        let job: Job = Box::new(move |shared| {
+            shared.queued.fetch_sub(1, Ordering::Relaxed);
+            let now = shared.clock.now();
+            let state = Arc::new(JobState {
+                name: name.clone(),
+                started: now,
+                last_checkpoint: Mutex::new(now),
+                abort: AtomicBool::new(false),
+                reported: AtomicBool::new(false),
+            });
+            let id = shared.next_id.fetch_add(1, Ordering::Relaxed);
+            shared.running.lock().unwrap().insert(id, state.clone());
+            let ctx = JobContext {
+                state,
+                shared: shared.clone(),
+            };
+            let result =
+                panic::catch_unwind(AssertUnwindSafe(|| f(&ctx))).unwrap_or_else(|payload| {
+                    Err(JobError::Panicked {
+                        name,
+                        message: panic_message(payload.as_ref()),
+                    })
+                });
+            shared.running.lock().unwrap().remove(&id);
+            let counter = match &result {
+                Err(JobError::Aborted { .. }) => &shared.aborted,
+                Err(JobError::Panicked { .. }) => &shared.panicked,
+                _ => &shared.completed,
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+            // 调用方可能已经不关心结果
+            let _ = tx.send(result);
+        });
+        self.shared.queued.fetch_add(1, Ordering::Relaxed);
+        if let Some(sender) = &self.sender {
+            if sender.send(job).is_err() {
+                self.shared.queued.fetch_sub(1, Ordering::Relaxed);
+            }
+        }
+        JobHandle { result: rx }
+    }
+
+    // 立即检查一次运行中的任务，返回本次新发现的超时任务
+    pub fn check(&self) -> Vec<Overdue> {
+        check(&self.shared)
+    }
+
+    pub fn stats(&self) -> JobStats {
+        let s = &self.shared;
+        JobStats {
+            completed: s.completed.load(Ordering::Relaxed),
+            aborted: s.aborted.load(Ordering::Relaxed),
+            panicked: s.panicked.load(Ordering::Relaxed),
+            overdue: s.overdue.load(Ordering::Relaxed),
+            running: s.running.lock().unwrap().len(),
+            queued: s.queued.load(Ordering::Relaxed),
+        }
+    }
+
+    // 不再接受新任务，等待已提交的任务执行完
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn stop(&mut self) {
+        self.sender.take();
+        *self.shared.stop.0.lock().unwrap() = true;
+        self.shared.stop.1.notify_all();
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for JobPool {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+pub struct JobHandle<R> {
+    result: Receiver<Result<R, JobError>>,
+}
+
+impl<R> JobHandle<R> {
+    // 阻塞等待任务结束
+    pub fn join(self) -> Result<R, JobError> {
+        self.result.recv().unwrap_or(Err(JobError::ShutDown))
+    }
+
+    pub fn join_timeout(&self, timeout: Duration) -> Option<Result<R, JobError>> {
+        self.result.recv_timeout(timeout).ok()
+    }
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "未知错误".to_string())
+}
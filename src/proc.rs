@@ -0,0 +1,253 @@
+use std::ffi::OsString;
+use std::fmt;
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
+
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+#[derive(Error, Debug)]
+pub enum ProcError {
+    #[error("启动进程失败: {program}: {source}")]
+    Spawn {
+        program: String,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("进程超时被终止: {program} ({timeout:?})")]
+    Timeout { program: String, timeout: Duration },
+
+    #[error("进程执行失败: {program} 退出码 {code:?}: {stderr}")]
+    Failed {
+        program: String,
+        code: Option<i32>,
+        stderr: String,
+    },
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub stream: Stream,
+    pub text: String,
+}
+
+// stdout、stderr 分别收集，combined 按输出到达的顺序合并
+#[derive(Debug, Clone)]
+pub struct Output {
+    pub status: ExitStatus,
+    pub stdout: String,
+    pub stderr: String,
+    pub combined: String,
+}
+
+impl Output {
+    pub fn success(&self) -> bool {
+        self.status.success()
+    }
+
+    pub fn code(&self) -> Option<i32> {
+        self.status.code()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Command {
+    program: OsString,
+    args: Vec<OsString>,
+    envs: Vec<(OsString, OsString)>,
+    cwd: Option<PathBuf>,
+    stdin: Option<Vec<u8>>,
+    timeout: Option<Duration>,
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.program.to_string_lossy())?;
+        for arg in &self.args {
+            write!(f, " {}", arg.to_string_lossy())?;
+        }
+        Ok(())
+    }
+}
+
+impl Command {
+    pub fn new(program: impl Into<OsString>) -> Self {
+        Command {
+            program: program.into(),
+            args: Vec::new(),
+            envs: Vec::new(),
+            cwd: None,
+            stdin: None,
+            timeout: None,
+        }
+    }
+
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<OsString>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    pub fn env(mut self, key: impl Into<OsString>, value: impl Into<OsString>) -> Self {
+        self.envs.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cwd = Some(dir.into());
+        self
+    }
+
+    pub fn stdin(mut self, input: impl Into<Vec<u8>>) -> Self {
+        self.stdin = Some(input.into());
+        self
+    }
+
+    // 超时后杀掉进程并返回 ProcError::Timeout
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn program_name(&self) -> String {
+        self.program.to_string_lossy().into_owned()
+    }
+
+    // 逐行回调输出，返回退出状态
+    pub async fn run<F: FnMut(Line)>(&self, mut on_line: F) -> Result<ExitStatus, ProcError> {
+        let mut command = tokio::process::Command::new(&self.program);
+        command
+            .args(&self.args)
+            .envs(self.envs.iter().map(|(k, v)| (k, v)))
+            .stdin(if self.stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+        let mut child = command.spawn().map_err(|source| ProcError::Spawn {
+            program: self.program_name(),
+            source,
+        })?;
+
+        if let (Some(mut pipe), Some(input)) = (child.stdin.take(), self.stdin.clone()) {
+            tokio::spawn(async move {
+                let _ = pipe.write_all(&input).await;
+            });
+        }
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(forward(stdout, Stream::Stdout, tx.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(forward(stderr, Stream::Stderr, tx));
+        }
+
+        let run = async {
+            while let Some(line) = rx.recv().await {
+                on_line(line);
+            }
+            child.wait().await
+        };
+        match self.timeout {
+            None => Ok(run.await?),
+            Some(timeout) => match tokio::time::timeout(timeout, run).await {
+                Ok(status) => Ok(status?),
+                Err(_) => {
+                    let _ = child.kill().await;
+                    Err(ProcError::Timeout {
+                        program: self.program_name(),
+                        timeout,
+                    })
+                }
+            },
+        }
+    }
+
+    // 收集全部输出
+    pub async fn output(&self) -> Result<Output, ProcError> {
+        let (mut stdout, mut stderr, mut combined) = (String::new(), String::new(), String::new());
+        let status = self
+            .run(|line| {
+                let target = match line.stream {
+                    Stream::Stdout => &mut stdout,
+                    Stream::Stderr => &mut stderr,
+                };
+                target.push_str(&line.text);
+                target.push('\n');
+                combined.push_str(&line.text);
+                combined.push('\n');
+            })
+            .await?;
+        Ok(Output {
+            status,
+            stdout,
+            stderr,
+            combined,
+        })
+    }
+
+    // 非 0 退出码视为错误
+    pub async fn check(&self) -> Result<Output, ProcError> {
+        let output = self.output().await?;
+        if !output.success() {
+            return Err(ProcError::Failed {
+                program: self.program_name(),
+                code: output.code(),
+                stderr: output.stderr.trim_end().to_string(),
+            });
+        }
+        Ok(output)
+    }
+
+    // 以 channel 的形式异步读取输出行，进程结束后 channel 关闭
+    pub fn stream(
+        self,
+    ) -> (
+        mpsc::UnboundedReceiver<Line>,
+        tokio::task::JoinHandle<Result<ExitStatus, ProcError>>,
+    ) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let handle = tokio::spawn(async move {
+            self.run(|line| {
+                let _ = tx.send(line);
+            })
+            .await
+        });
+        (rx, handle)
+    }
+}
+
+async fn forward<R: AsyncRead + Unpin>(reader: R, stream: Stream, tx: mpsc::UnboundedSender<Line>) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(text)) = lines.next_line().await {
+        if tx.send(Line { stream, text }).is_err() {
+            break;
+        }
+    }
+}
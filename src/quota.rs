@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::time::{Duration, UNIX_EPOCH};
+
+use sqlx::{SqliteConnection, SqlitePool};
+use thiserror::Error;
+
+use crate::clock::{self, SharedClock};
+use crate::db::DbError;
+
+// 按用户统计的计数器(请求数、消费金额等)，滚动窗口内超过上限时拒绝。
+// 计数按时间桶持久化在数据库中，窗口被切成 BUCKETS 个桶，过期的桶在写入时清理。
+
+pub const BUCKETS: u64 = 24;
+
+#[derive(Error, Debug)]
+pub enum QuotaError {
+    #[error("{user} 的 {metric} 超出限额: 上限 {limit}，已用 {used}，本次 {requested}")]
+    Exceeded {
+        user: String,
+        metric: String,
+        limit: i64,
+        used: i64,
+        requested: i64,
+    },
+
+    #[error(transparent)]
+    Db(#[from] DbError),
+}
+
+impl From<sqlx::Error> for QuotaError {
+    fn from(e: sqlx::Error) -> Self {
+        QuotaError::Db(e.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Limit {
+    pub max: i64,
+    pub window: Duration,
+}
+
+impl Limit {
+    fn bucket_ms(&self) -> i64 {
+        ((self.window.as_millis() as u64 / BUCKETS).max(1)) as i64
+    }
+}
+
+#[derive(Clone)]
+pub struct Quota {
+    pool: SqlitePool,
+    clock: SharedClock,
+    limits: HashMap<String, Limit>,
+}
+
+impl Quota {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self::with_clock(pool, clock::system())
+    }
+
+    pub fn with_clock(pool: SqlitePool, clock: SharedClock) -> Self {
+        Quota {
+            pool,
+            clock,
+            limits: HashMap::new(),
+        }
+    }
+
+    // 例如 limit("spend", 100_000, 一天)
+    pub fn limit(mut self, metric: &str, max: i64, window: Duration) -> Self {
+        self.limits
+            .insert(metric.to_string(), Limit { max, window });
+        self
+    }
+
+    pub async fn migrate(&self) -> Result<(), QuotaError> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS quota_counters (
+                user TEXT NOT NULL,
+                metric TEXT NOT NULL,
+                bucket INTEGER NOT NULL,
+                value INTEGER NOT NULL,
+                PRIMARY KEY (user, metric, bucket)
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    fn now_ms(&self) -> i64 {
+        self.clock
+            .system_time()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0)
+    }
+
+    // 没有配置限额的指标按一天的窗口统计
+    fn limit_for(&self, metric: &str) -> Limit {
+        self.limits.get(metric).cloned().unwrap_or(Limit {
+            max: i64::MAX,
+            window: Duration::from_secs(86_400),
+        })
+    }
+
+    // 当前窗口内的用量
+    pub async fn usage(&self, user: &str, metric: &str) -> Result<i64, QuotaError> {
+        let limit = self.limit_for(metric);
+        let since = self.window_start(&limit);
+        Ok(sqlx::query_scalar(
+            "SELECT COALESCE(SUM(value), 0) FROM quota_counters
+             WHERE user = ? AND metric = ? AND bucket >= ?",
+        )
+        .bind(user)
+        .bind(metric)
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?)
+    }
+
+    pub async fn remaining(&self, user: &str, metric: &str) -> Result<i64, QuotaError> {
+        let limit = self.limit_for(metric);
+        Ok((limit.max - self.usage(user, metric).await?).max(0))
+    }
+
+    fn window_start(&self, limit: &Limit) -> i64 {
+        let bucket = limit.bucket_ms();
+        let current = self.now_ms() / bucket * bucket;
+        current - bucket * (BUCKETS as i64 - 1)
+    }
+
+    // 检查并计数，在同一个数据库事务中完成；超限时不计数。
+    // 用 BEGIN IMMEDIATE 在读取前就取得写锁，并发的 consume 排队执行，
+    // 不会都读到旧的用量，也不会在读锁升级为写锁时失败
+    pub async fn consume(&self, user: &str, metric: &str, amount: i64) -> Result<(), QuotaError> {
+        let mut conn = self.pool.acquire().await?;
+        sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+        let result = match self.consume_in(&mut conn, user, metric, amount).await {
+            Ok(()) => sqlx::query("COMMIT")
+                .execute(&mut *conn)
+                .await
+                .map(|_| ())
+                .map_err(QuotaError::from),
+            Err(e) => Err(e),
+        };
+        if result.is_err() {
+            // 连接会回到连接池，不能留下未结束的事务
+            let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+        }
+        result
+    }
+
+    async fn consume_in(
+        &self,
+        tx: &mut SqliteConnection,
+        user: &str,
+        metric: &str,
+        amount: i64,
+    ) -> Result<(), QuotaError> {
+        let limit = self.limit_for(metric);
+        let bucket_ms = limit.bucket_ms();
+        let since = self.window_start(&limit);
+        let bucket = self.now_ms() / bucket_ms * bucket_ms;
+
+        let used: i64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(value), 0) FROM quota_counters
+             WHERE user = ? AND metric = ? AND bucket >= ?",
+        )
+        .bind(user)
+        .bind(metric)
+        .bind(since)
+        .fetch_one(&mut *tx)
+        .await?;
+        if amount > 0 && used.saturating_add(amount) > limit.max {
+            return Err(QuotaError::Exceeded {
+                user: user.to_string(),
+                metric: metric.to_string(),
+                limit: limit.max,
+                used,
+                requested: amount,
+            });
+        }
+        sqlx::query("DELETE FROM quota_counters WHERE user = ? AND metric = ? AND bucket < ?")
+            .bind(user)
+            .bind(metric)
+            .bind(since)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "INSERT INTO quota_counters (user, metric, bucket, value) VALUES (?, ?, ?, ?)
+             ON CONFLICT (user, metric, bucket) DO UPDATE SET value = value + excluded.value",
+        )
+        .bind(user)
+        .bind(metric)
+        .bind(bucket)
+        .bind(amount)
+        .execute(&mut *tx)
+        .await?;
+        Ok(())
+    }
+
+    // 操作失败后退回已计的用量
+    pub async fn refund(&self, user: &str, metric: &str, amount: i64) -> Result<(), QuotaError> {
+        self.consume(user, metric, -amount).await
+    }
+}
@@ -0,0 +1,266 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use thiserror::Error;
+
+use crate::metrics::{Counter, Gauge, Registry};
+
+// 数据目录的磁盘配额: 登记受管目录(缓存溢出、blob、日志)和各自的上限，
+// 写入前调用 reserve 检查，超出时按策略拒绝写入、删除最旧的文件，或调用压缩回调腾出空间。
+// 用量在 open 时扫描一次，之后靠 record 增量维护，可以随时 rescan 校正。
+
+#[derive(Error, Debug)]
+pub enum QuotaFsError {
+    #[error("目录 {dir} 超出配额: 已用 {used} 字节，上限 {limit}，本次需要 {requested}")]
+    Exceeded {
+        dir: String,
+        used: u64,
+        limit: u64,
+        requested: u64,
+    },
+    #[error("未登记的目录: {0}")]
+    UnknownDir(String),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+// 参数是目录路径和需要腾出的字节数，返回实际释放的字节数
+pub type CompactFn = Arc<dyn Fn(&Path, u64) -> io::Result<u64> + Send + Sync>;
+
+#[derive(Clone)]
+pub enum OnFull {
+    Reject,
+    // 按修改时间从旧到新删除文件，直到放得下
+    EvictOldest,
+    Compact(CompactFn),
+}
+
+impl fmt::Debug for OnFull {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OnFull::Reject => f.write_str("Reject"),
+            OnFull::EvictOldest => f.write_str("EvictOldest"),
+            OnFull::Compact(_) => f.write_str("Compact"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DirUsage {
+    pub used: u64,
+    pub limit: u64,
+    pub rejected: u64,
+    // 因淘汰或压缩释放的字节数
+    pub reclaimed: u64,
+}
+
+struct DirMetrics {
+    used: Gauge,
+    limit: Gauge,
+    rejected: Counter,
+    reclaimed: Counter,
+}
+
+struct Managed {
+    path: PathBuf,
+    on_full: OnFull,
+    usage: DirUsage,
+    metrics: Option<DirMetrics>,
+}
+
+impl Managed {
+    fn publish(&self) {
+        if let Some(m) = &self.metrics {
+            m.used.set(self.usage.used as f64);
+            m.limit.set(self.usage.limit as f64);
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct DiskQuota {
+    dirs: Mutex<BTreeMap<String, Managed>>,
+    registry: Option<Arc<Registry>>,
+}
+
+impl DiskQuota {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 每个目录注册 disk_quota_<name>_used_bytes、_limit_bytes 两个仪表和
+    // _rejected_total、_reclaimed_bytes_total 两个计数器
+    pub fn metrics(mut self, registry: Arc<Registry>) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    // 登记目录并扫描当前用量，目录不存在时创建
+    pub fn manage(
+        &self,
+        name: &str,
+        path: impl AsRef<Path>,
+        limit: u64,
+        on_full: OnFull,
+    ) -> Result<DirUsage, QuotaFsError> {
+        let path = path.as_ref().to_path_buf();
+        fs::create_dir_all(&path)?;
+        let metrics = self.registry.as_ref().map(|r| {
+            let prefix = format!("disk_quota_{}", name);
+            DirMetrics {
+                used: r.gauge(&format!("{}_used_bytes", prefix)),
+                limit: r.gauge(&format!("{}_limit_bytes", prefix)),
+                rejected: r.counter(&format!("{}_rejected_total", prefix)),
+                reclaimed: r.counter(&format!("{}_reclaimed_bytes_total", prefix)),
+            }
+        });
+        let managed = Managed {
+            usage: DirUsage {
+                used: dir_size(&path)?,
+                limit,
+                ..Default::default()
+            },
+            path,
+            on_full,
+            metrics,
+        };
+        managed.publish();
+        let usage = managed.usage;
+        self.dirs.lock().unwrap().insert(name.to_string(), managed);
+        Ok(usage)
+    }
+
+    pub fn usage(&self, name: &str) -> Option<DirUsage> {
+        self.dirs.lock().unwrap().get(name).map(|m| m.usage)
+    }
+
+    pub fn path(&self, name: &str) -> Option<PathBuf> {
+        self.dirs.lock().unwrap().get(name).map(|m| m.path.clone())
+    }
+
+    // 重新扫描磁盘，校正其他途径(外部进程、手动删除)造成的偏差
+    pub fn rescan(&self, name: &str) -> Result<DirUsage, QuotaFsError> {
+        let mut dirs = self.dirs.lock().unwrap();
+        let managed = dirs
+            .get_mut(name)
+            .ok_or_else(|| QuotaFsError::UnknownDir(name.to_string()))?;
+        managed.usage.used = dir_size(&managed.path)?;
+        managed.publish();
+        Ok(managed.usage)
+    }
+
+    // 写入 bytes 字节之前调用。成功后用量已经计入，写入失败时应调用 record 退回
+    pub fn reserve(&self, name: &str, bytes: u64) -> Result<(), QuotaFsError> {
+        let mut dirs = self.dirs.lock().unwrap();
+        let managed = dirs
+            .get_mut(name)
+            .ok_or_else(|| QuotaFsError::UnknownDir(name.to_string()))?;
+        if managed.usage.used + bytes > managed.usage.limit {
+            let needed = managed.usage.used + bytes - managed.usage.limit;
+            let freed = match &managed.on_full {
+                OnFull::Reject => 0,
+                OnFull::EvictOldest => evict_oldest(&managed.path, needed)?,
+                OnFull::Compact(f) => f(&managed.path, needed)?,
+            };
+            if freed > 0 {
+                managed.usage.reclaimed += freed;
+                if let Some(m) = &managed.metrics {
+                    m.reclaimed.add(freed);
+                }
+                // 以磁盘上的实际用量为准
+                managed.usage.used = dir_size(&managed.path)?;
+            }
+            if managed.usage.used + bytes > managed.usage.limit {
+                managed.usage.rejected += 1;
+                if let Some(m) = &managed.metrics {
+                    m.rejected.inc();
+                }
+                managed.publish();
+                return Err(QuotaFsError::Exceeded {
+                    dir: name.to_string(),
+                    used: managed.usage.used,
+                    limit: managed.usage.limit,
+                    requested: bytes,
+                });
+            }
+        }
+        managed.usage.used += bytes;
+        managed.publish();
+        Ok(())
+    }
+
+    // 记录 reserve 之外的变化，删除文件时传负数
+    pub fn record(&self, name: &str, delta: i64) {
+        if let Some(managed) = self.dirs.lock().unwrap().get_mut(name) {
+            managed.usage.used = managed.usage.used.saturating_add_signed(delta);
+            managed.publish();
+        }
+    }
+
+    // 检查配额后写入 <dir>/<relative>，覆盖已有文件时只计算增加的部分
+    pub fn write(&self, name: &str, relative: &str, data: &[u8]) -> Result<PathBuf, QuotaFsError> {
+        let path = self
+            .path(name)
+            .ok_or_else(|| QuotaFsError::UnknownDir(name.to_string()))?
+            .join(relative);
+        let existing = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        let grow = (data.len() as u64).saturating_sub(existing);
+        self.reserve(name, grow)?;
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, data));
+        if let Err(e) = result {
+            self.record(name, -(grow as i64));
+            return Err(e.into());
+        }
+        let shrink = existing.saturating_sub(data.len() as u64);
+        self.record(name, -(shrink as i64));
+        Ok(path)
+    }
+}
+
+fn files(root: &Path) -> io::Result<Vec<(PathBuf, u64, SystemTime)>> {
+    let mut out = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else {
+                out.push((entry.path(), meta.len(), meta.modified()?));
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn dir_size(root: &Path) -> io::Result<u64> {
+    Ok(files(root)?.iter().map(|(_, len, _)| len).sum())
+}
+
+// 返回释放的字节数；锁文件正在被使用，不删除
+fn evict_oldest(root: &Path, needed: u64) -> io::Result<u64> {
+    let mut candidates = files(root)?;
+    candidates.retain(|(path, _, _)| path.extension().is_none_or(|e| e != "lock"));
+    candidates.sort_by_key(|(_, _, modified)| *modified);
+    let mut freed = 0;
+    for (path, len, _) in candidates {
+        if freed >= needed {
+            break;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => freed += len,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(freed)
+}
@@ -0,0 +1,72 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::clock::{self, SharedClock};
+
+pub mod adaptive;
+
+// 令牌桶限流: 容量 capacity，每秒补充 rate 个令牌
+pub struct TokenBucket {
+    capacity: f64,
+    rate: f64,
+    clock: SharedClock,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: u32, rate: f64) -> Self {
+        Self::with_clock(capacity, rate, clock::system())
+    }
+
+    pub fn with_clock(capacity: u32, rate: f64, clock: SharedClock) -> Self {
+        let now = clock.now();
+        TokenBucket {
+            capacity: capacity as f64,
+            rate,
+            clock,
+            state: Mutex::new((capacity as f64, now)),
+        }
+    }
+
+    fn refill(&self, state: &mut (f64, Instant)) {
+        let now = self.clock.now();
+        let elapsed = now.saturating_duration_since(state.1).as_secs_f64();
+        state.0 = (state.0 + elapsed * self.rate).min(self.capacity);
+        state.1 = now;
+    }
+
+    pub fn try_acquire(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state);
+        if state.0 >= 1.0 {
+            state.0 -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    // 距离下一个令牌可用还需要等待的时间
+    pub fn wait_time(&self) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state);
+        if state.0 >= 1.0 || self.rate <= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64((1.0 - state.0) / self.rate)
+        }
+    }
+
+    // 等待直到拿到令牌
+    pub async fn acquire(&self) {
+        while !self.try_acquire() {
+            self.clock.sleep(self.wait_time()).await;
+        }
+    }
+
+    pub fn available(&self) -> u32 {
+        let mut state = self.state.lock().unwrap();
+        self.refill(&mut state);
+        state.0 as u32
+    }
+}
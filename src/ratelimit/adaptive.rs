@@ -0,0 +1,283 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tokio::sync::Notify;
+
+use crate::clock::{self, SharedClock};
+use crate::metrics::{Counter, Gauge, Registry};
+
+// 自适应并发限制(AIMD): 令牌桶限制的是速率，这里限制同时进行中的请求数，并根据结果调整上限。
+// 请求成功且延迟不超过阈值、并且并发已经用到上限的一半以上时，上限加 1/limit(每轮约加 1)；
+// 失败(错误、超时、被上游限流)或延迟超过阈值时，上限乘以 backoff_ratio。
+// 上游变慢时自动减少并发，恢复后再逐步放开。
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LimiterStats {
+    pub limit: usize,
+    pub inflight: usize,
+    pub rejected: u64,
+}
+
+struct Metrics {
+    limit: Gauge,
+    inflight: Gauge,
+    rejected: Counter,
+}
+
+struct State {
+    // 小数部分用来累积加性增长
+    limit: f64,
+    inflight: usize,
+    rejected: u64,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    notify: Notify,
+    min_limit: usize,
+    max_limit: usize,
+    latency_threshold: Option<Duration>,
+    backoff_ratio: f64,
+    clock: SharedClock,
+    metrics: Option<Metrics>,
+}
+
+#[derive(Clone)]
+pub struct AdaptiveLimiter {
+    shared: Arc<Shared>,
+}
+
+pub struct AdaptiveLimiterBuilder {
+    initial: usize,
+    min_limit: usize,
+    max_limit: usize,
+    latency_threshold: Option<Duration>,
+    backoff_ratio: f64,
+    clock: SharedClock,
+    metrics: Option<(Arc<Registry>, String)>,
+}
+
+impl AdaptiveLimiterBuilder {
+    pub fn min_limit(mut self, n: usize) -> Self {
+        self.min_limit = n.max(1);
+        self
+    }
+
+    pub fn max_limit(mut self, n: usize) -> Self {
+        self.max_limit = n.max(1);
+        self
+    }
+
+    // 延迟超过这个值视为上游过载；默认只看成功失败
+    pub fn latency_threshold(mut self, threshold: Duration) -> Self {
+        self.latency_threshold = Some(threshold);
+        self
+    }
+
+    // 乘性减小的比例，限制在 (0, 1) 之间
+    pub fn backoff_ratio(mut self, ratio: f64) -> Self {
+        self.backoff_ratio = ratio.clamp(0.1, 0.99);
+        self
+    }
+
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    // 注册 <name>_concurrency_limit、<name>_inflight 两个仪表和 <name>_rejected_total 计数器
+    pub fn metrics(mut self, registry: Arc<Registry>, name: &str) -> Self {
+        self.metrics = Some((registry, name.to_string()));
+        self
+    }
+
+    pub fn build(self) -> AdaptiveLimiter {
+        let min_limit = self.min_limit.min(self.max_limit);
+        let initial = self.initial.clamp(min_limit, self.max_limit);
+        let metrics = self.metrics.map(|(registry, name)| Metrics {
+            limit: registry.gauge(&format!("{}_concurrency_limit", name)),
+            inflight: registry.gauge(&format!("{}_inflight", name)),
+            rejected: registry.counter(&format!("{}_rejected_total", name)),
+        });
+        let limiter = AdaptiveLimiter {
+            shared: Arc::new(Shared {
+                state: Mutex::new(State {
+                    limit: initial as f64,
+                    inflight: 0,
+                    rejected: 0,
+                }),
+                notify: Notify::new(),
+                min_limit,
+                max_limit: self.max_limit,
+                latency_threshold: self.latency_threshold,
+                backoff_ratio: self.backoff_ratio,
+                clock: self.clock,
+                metrics,
+            }),
+        };
+        limiter.publish(&limiter.shared.state.lock().unwrap());
+        limiter
+    }
+}
+
+impl AdaptiveLimiter {
+    pub fn new(initial: usize) -> Self {
+        Self::builder(initial).build()
+    }
+
+    // 默认范围 1..=1000，减小比例 0.9
+    pub fn builder(initial: usize) -> AdaptiveLimiterBuilder {
+        AdaptiveLimiterBuilder {
+            initial,
+            min_limit: 1,
+            max_limit: 1000,
+            latency_threshold: None,
+            backoff_ratio: 0.9,
+            clock: clock::system(),
+            metrics: None,
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.shared.state.lock().unwrap().limit as usize
+    }
+
+    pub fn stats(&self) -> LimiterStats {
+        let state = self.shared.state.lock().unwrap();
+        LimiterStats {
+            limit: state.limit as usize,
+            inflight: state.inflight,
+            rejected: state.rejected,
+        }
+    }
+
+    // 已经达到上限时返回 None 并计入 rejected
+    pub fn try_acquire(&self) -> Option<Permit> {
+        let mut state = self.shared.state.lock().unwrap();
+        if state.inflight >= state.limit as usize {
+            state.rejected += 1;
+            if let Some(m) = &self.shared.metrics {
+                m.rejected.inc();
+            }
+            return None;
+        }
+        Some(self.admit(&mut state))
+    }
+
+    // 等待直到有空位
+    pub async fn acquire(&self) -> Permit {
+        loop {
+            let notified = self.shared.notify.notified();
+            {
+                let mut state = self.shared.state.lock().unwrap();
+                if state.inflight < state.limit as usize {
+                    return self.admit(&mut state);
+                }
+            }
+            notified.await;
+        }
+    }
+
+    // 在许可内执行 fut，Ok 记为成功、Err 记为失败
+    pub async fn run<T, E, F>(&self, fut: F) -> Result<T, E>
+    where
+        F: Future<Output = Result<T, E>>,
+    {
+        let permit = self.acquire().await;
+        let result = fut.await;
+        match &result {
+            Ok(_) => permit.success(),
+            Err(_) => permit.failure(),
+        }
+        result
+    }
+
+    fn admit(&self, state: &mut State) -> Permit {
+        state.inflight += 1;
+        self.publish(state);
+        Permit {
+            limiter: Some(self.clone()),
+            started: self.shared.clock.now(),
+        }
+    }
+
+    fn release(&self, outcome: Outcome) {
+        let shared = &self.shared;
+        let mut state = shared.state.lock().unwrap();
+        let inflight = state.inflight;
+        state.inflight = inflight.saturating_sub(1);
+        match outcome {
+            Outcome::Success(latency) if shared.latency_threshold.is_some_and(|t| latency > t) => {
+                decrease(shared, &mut state)
+            }
+            Outcome::Success(_) => {
+                // 没用满时上游能否承受更多并发是未知的，不增加
+                if inflight * 2 >= state.limit as usize {
+                    state.limit = (state.limit + 1.0 / state.limit).min(shared.max_limit as f64);
+                }
+            }
+            Outcome::Failure => decrease(shared, &mut state),
+            Outcome::Ignore => {}
+        }
+        self.publish(&state);
+        drop(state);
+        shared.notify.notify_waiters();
+    }
+
+    fn publish(&self, state: &State) {
+        if let Some(m) = &self.shared.metrics {
+            m.limit.set(state.limit.floor());
+            m.inflight.set(state.inflight as f64);
+        }
+    }
+}
+
+fn decrease(shared: &Shared, state: &mut State) {
+    state.limit = (state.limit * shared.backoff_ratio)
+        .floor()
+        .max(shared.min_limit as f64);
+}
+
+enum Outcome {
+    Success(Duration),
+    Failure,
+    Ignore,
+}
+
+// 进行中的请求。结束时调用 success 或 failure 反馈结果；直接 drop 时只释放名额，不调整上限
+pub struct Permit {
+    limiter: Option<AdaptiveLimiter>,
+    started: Instant,
+}
+
+impl Permit {
+    pub fn success(mut self) {
+        let latency = self.elapsed();
+        self.finish(Outcome::Success(latency));
+    }
+
+    // 错误、超时或被上游限流
+    pub fn failure(mut self) {
+        self.finish(Outcome::Failure);
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match &self.limiter {
+            Some(l) => l.shared.clock.now().saturating_duration_since(self.started),
+            None => Duration::ZERO,
+        }
+    }
+
+    fn finish(&mut self, outcome: Outcome) {
+        if let Some(limiter) = self.limiter.take() {
+            limiter.release(outcome);
+        }
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.finish(Outcome::Ignore);
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use thiserror::Error;
+
+use crate::cache::TtlCache;
+use crate::context::RequestContext;
+use crate::db::DbError;
+use crate::status::HttpStatus;
+
+// 基于角色的权限控制: 角色拥有权限，用户拥有角色。
+// 权限形如 "orders:read"，"orders:*" 匹配 orders 下的所有权限，"*" 匹配全部。
+// 用户的权限集合缓存一段时间，授权关系变化时清空缓存。
+
+#[derive(Error, Debug)]
+pub enum AuthError {
+    #[error("未登录")]
+    Unauthenticated,
+
+    #[error("没有权限: 需要 {needed}")]
+    Forbidden { needed: String },
+
+    #[error(transparent)]
+    Db(#[from] DbError),
+}
+
+impl From<sqlx::Error> for AuthError {
+    fn from(e: sqlx::Error) -> Self {
+        AuthError::Db(e.into())
+    }
+}
+
+impl HttpStatus for AuthError {
+    fn status(&self) -> u16 {
+        match self {
+            AuthError::Unauthenticated => 401,
+            AuthError::Forbidden { .. } => 403,
+            AuthError::Db(e) => e.status(),
+        }
+    }
+}
+
+pub type Permissions = Arc<BTreeSet<String>>;
+
+fn matches(granted: &str, needed: &str) -> bool {
+    granted == "*"
+        || granted == needed
+        || granted
+            .strip_suffix('*')
+            .is_some_and(|prefix| prefix.ends_with(':') && needed.starts_with(prefix))
+}
+
+pub struct Rbac {
+    pool: SqlitePool,
+    cache: TtlCache<String, Permissions>,
+}
+
+impl Rbac {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self::with_cache(pool, TtlCache::new(Duration::from_secs(60)))
+    }
+
+    pub fn with_cache(pool: SqlitePool, cache: TtlCache<String, Permissions>) -> Self {
+        Rbac { pool, cache }
+    }
+
+    pub async fn migrate(&self) -> Result<(), AuthError> {
+        sqlx::raw_sql(
+            "CREATE TABLE IF NOT EXISTS rbac_role_permissions (
+                role TEXT NOT NULL,
+                permission TEXT NOT NULL,
+                PRIMARY KEY (role, permission)
+            );
+            CREATE TABLE IF NOT EXISTS rbac_user_roles (
+                user_id TEXT NOT NULL,
+                role TEXT NOT NULL,
+                PRIMARY KEY (user_id, role)
+            );",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn grant(&self, role: &str, permission: &str) -> Result<(), AuthError> {
+        sqlx::query("INSERT OR IGNORE INTO rbac_role_permissions (role, permission) VALUES (?, ?)")
+            .bind(role)
+            .bind(permission)
+            .execute(&self.pool)
+            .await?;
+        self.cache.clear();
+        Ok(())
+    }
+
+    pub async fn revoke(&self, role: &str, permission: &str) -> Result<(), AuthError> {
+        sqlx::query("DELETE FROM rbac_role_permissions WHERE role = ? AND permission = ?")
+            .bind(role)
+            .bind(permission)
+            .execute(&self.pool)
+            .await?;
+        self.cache.clear();
+        Ok(())
+    }
+
+    pub async fn assign(&self, user_id: &str, role: &str) -> Result<(), AuthError> {
+        sqlx::query("INSERT OR IGNORE INTO rbac_user_roles (user_id, role) VALUES (?, ?)")
+            .bind(user_id)
+            .bind(role)
+            .execute(&self.pool)
+            .await?;
+        self.cache.remove(&user_id.to_string());
+        Ok(())
+    }
+
+    pub async fn unassign(&self, user_id: &str, role: &str) -> Result<(), AuthError> {
+        sqlx::query("DELETE FROM rbac_user_roles WHERE user_id = ? AND role = ?")
+            .bind(user_id)
+            .bind(role)
+            .execute(&self.pool)
+            .await?;
+        self.cache.remove(&user_id.to_string());
+        Ok(())
+    }
+
+    pub async fn roles(&self, user_id: &str) -> Result<Vec<String>, AuthError> {
+        Ok(
+            sqlx::query_scalar("SELECT role FROM rbac_user_roles WHERE user_id = ? ORDER BY role")
+                .bind(user_id)
+                .fetch_all(&self.pool)
+                .await?,
+        )
+    }
+
+    pub async fn permissions(&self, user_id: &str) -> Result<Permissions, AuthError> {
+        let key = user_id.to_string();
+        if let Some(permissions) = self.cache.get(&key) {
+            return Ok(permissions);
+        }
+        let rows: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT p.permission FROM rbac_user_roles r
+             JOIN rbac_role_permissions p ON p.role = r.role
+             WHERE r.user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let permissions: Permissions = Arc::new(rows.into_iter().collect());
+        self.cache.insert(key, permissions.clone());
+        Ok(permissions)
+    }
+
+    pub async fn has_permission(&self, user_id: &str, permission: &str) -> Result<bool, AuthError> {
+        Ok(self
+            .permissions(user_id)
+            .await?
+            .iter()
+            .any(|granted| matches(granted, permission)))
+    }
+
+    // 请求上下文中的用户需要拥有 permission
+    pub async fn authorize(&self, ctx: &RequestContext, permission: &str) -> Result<(), AuthError> {
+        let user_id = ctx.user_id.as_deref().ok_or(AuthError::Unauthenticated)?;
+        if self.has_permission(user_id, permission).await? {
+            Ok(())
+        } else {
+            Err(AuthError::Forbidden {
+                needed: permission.to_string(),
+            })
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+pub const DEFAULT_MASK: &str = "***";
+
+// 默认按字段名脱敏的键，比较时忽略大小写
+const BUILTIN_FIELDS: [&str; 7] = [
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "authorization",
+    "secret_key",
+];
+
+const EMAIL_PATTERN: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}";
+const CARD_PATTERN: &str = r"\b(?:\d[ -]?){12,18}\d\b";
+
+#[derive(Error, Debug)]
+pub enum RedactError {
+    #[error("脱敏规则 {name} 的正则无效: {source}")]
+    InvalidPattern {
+        name: String,
+        #[source]
+        source: regex::Error,
+    },
+}
+
+// 配置文件中的 [redact] 段
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RedactConfig {
+    #[serde(default = "default_true")]
+    pub builtin: bool,
+    #[serde(default)]
+    pub fields: Vec<String>,
+    #[serde(default)]
+    pub patterns: Vec<PatternRule>,
+    #[serde(default = "default_mask")]
+    pub mask: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PatternRule {
+    pub name: String,
+    pub regex: String,
+}
+
+impl Default for RedactConfig {
+    fn default() -> Self {
+        RedactConfig {
+            builtin: true,
+            fields: Vec::new(),
+            patterns: Vec::new(),
+            mask: default_mask(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_mask() -> String {
+    DEFAULT_MASK.to_string()
+}
+
+enum Kind {
+    Plain,
+    // 信用卡号需要通过 Luhn 校验，避免把普通长数字也打码
+    Card,
+}
+
+struct Pattern {
+    name: String,
+    regex: Regex,
+    kind: Kind,
+}
+
+pub struct Redactor {
+    fields: HashSet<String>,
+    patterns: Vec<Pattern>,
+    mask: String,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::from_config(&RedactConfig::default()).expect("内置规则必须合法")
+    }
+}
+
+impl Redactor {
+    // 不带任何规则
+    pub fn empty() -> Self {
+        Redactor {
+            fields: HashSet::new(),
+            patterns: Vec::new(),
+            mask: default_mask(),
+        }
+    }
+
+    pub fn from_config(config: &RedactConfig) -> Result<Self, RedactError> {
+        let mut redactor = Self::empty().mask(&config.mask);
+        if config.builtin {
+            for field in BUILTIN_FIELDS {
+                redactor = redactor.field(field);
+            }
+            redactor = redactor.pattern("email", EMAIL_PATTERN)?;
+            redactor.patterns.push(Pattern {
+                name: "card".to_string(),
+                regex: Regex::new(CARD_PATTERN).unwrap(),
+                kind: Kind::Card,
+            });
+        }
+        for field in &config.fields {
+            redactor = redactor.field(field);
+        }
+        for rule in &config.patterns {
+            redactor = redactor.pattern(&rule.name, &rule.regex)?;
+        }
+        Ok(redactor)
+    }
+
+    pub fn mask(mut self, mask: &str) -> Self {
+        self.mask = mask.to_string();
+        self
+    }
+
+    pub fn field(mut self, name: &str) -> Self {
+        self.fields.insert(name.to_lowercase());
+        self
+    }
+
+    pub fn pattern(mut self, name: &str, regex: &str) -> Result<Self, RedactError> {
+        let regex = Regex::new(regex).map_err(|source| RedactError::InvalidPattern {
+            name: name.to_string(),
+            source,
+        })?;
+        self.patterns.push(Pattern {
+            name: name.to_string(),
+            regex,
+            kind: Kind::Plain,
+        });
+        Ok(self)
+    }
+
+    pub fn pattern_names(&self) -> Vec<&str> {
+        self.patterns.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    // 用于日志行、错误信息等纯文本
+    pub fn redact_str(&self, input: &str) -> String {
+        let mut output = input.to_string();
+        for pattern in &self.patterns {
+            output = pattern
+                .regex
+                .replace_all(&output, |caps: &regex::Captures| {
+                    let matched = &caps[0];
+                    match pattern.kind {
+                        Kind::Card if !luhn(matched) => matched.to_string(),
+                        _ => self.mask.clone(),
+                    }
+                })
+                .into_owned();
+        }
+        output
+    }
+
+    // 敏感字段整体替换为 mask，其余字符串值按正则规则处理
+    pub fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, v) in map.iter_mut() {
+                    if self.fields.contains(&key.to_lowercase()) {
+                        *v = Value::String(self.mask.clone());
+                    } else {
+                        self.redact_value(v);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|v| self.redact_value(v)),
+            Value::String(s) => *s = self.redact_str(s),
+            _ => {}
+        }
+    }
+
+    pub fn redact<T: Serialize + ?Sized>(&self, value: &T) -> Result<Value, serde_json::Error> {
+        let mut value = serde_json::to_value(value)?;
+        self.redact_value(&mut value);
+        Ok(value)
+    }
+
+    // 连同 source 链一起输出，保证错误信息中的敏感内容也被处理
+    pub fn redact_error(&self, error: &dyn std::error::Error) -> String {
+        let mut message = error.to_string();
+        let mut source = error.source();
+        while let Some(e) = source {
+            message.push_str(": ");
+            message.push_str(&e.to_string());
+            source = e.source();
+        }
+        self.redact_str(&message)
+    }
+}
+
+fn luhn(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    if digits.len() < 13 {
+        return false;
+    }
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &d)| {
+            if i % 2 == 1 {
+                let doubled = d * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                d
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
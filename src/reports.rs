@@ -0,0 +1,208 @@
+use std::io::{self, Write};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures_util::TryStreamExt;
+use serde_json::{Map, Value};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Column, Row, SqlitePool, TypeInfo, ValueRef};
+use thiserror::Error;
+
+use crate::anonymize::Anonymizer;
+use crate::db::DbError;
+
+// 报表导出: 逐行读取查询结果并写出 CSV 或 JSON Lines，不把整个结果集放进内存；
+// 可选 gzip 压缩和匿名化，长时间导出时通过回调报告进度。
+
+#[derive(Error, Debug)]
+pub enum ReportError {
+    #[error(transparent)]
+    Db(#[from] DbError),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl From<sqlx::Error> for ReportError {
+    fn from(e: sqlx::Error) -> Self {
+        ReportError::Db(e.into())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    JsonLines,
+}
+
+type Progress<'a> = Box<dyn FnMut(u64) + Send + 'a>;
+
+pub struct Report<'a> {
+    query: String,
+    format: Format,
+    gzip: bool,
+    progress_every: u64,
+    progress: Option<Progress<'a>>,
+    anonymizer: Option<Anonymizer>,
+}
+
+impl<'a> Report<'a> {
+    pub fn new(query: &str, format: Format) -> Self {
+        Report {
+            query: query.to_string(),
+            format,
+            gzip: false,
+            progress_every: 1000,
+            progress: None,
+            anonymizer: None,
+        }
+    }
+
+    pub fn gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+
+    // 按列名替换敏感值后再写出
+    pub fn anonymize(mut self, anonymizer: Anonymizer) -> Self {
+        self.anonymizer = Some(anonymizer);
+        self
+    }
+
+    fn cell(&self, row: &SqliteRow, index: usize) -> Value {
+        let value = value(row, index);
+        let strategy = self
+            .anonymizer
+            .as_ref()
+            .and_then(|a| a.strategy(row.columns()[index].name()).map(|s| (a, s)));
+        match (strategy, value) {
+            (_, Value::Null) => Value::Null,
+            (Some((anonymizer, strategy)), value) => {
+                let text = match value {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                };
+                anonymizer
+                    .apply(strategy, &text)
+                    .map_or(Value::Null, Value::String)
+            }
+            (None, value) => value,
+        }
+    }
+
+    // 每写出 every 行调用一次，结束时再调用一次，参数为已写出的行数
+    pub fn progress<F>(mut self, every: u64, f: F) -> Self
+    where
+        F: FnMut(u64) + Send + 'a,
+    {
+        self.progress_every = every.max(1);
+        self.progress = Some(Box::new(f));
+        self
+    }
+
+    // 返回写出的行数(不含 CSV 表头)
+    pub async fn write_to<W: Write>(
+        mut self,
+        pool: &SqlitePool,
+        writer: W,
+    ) -> Result<u64, ReportError> {
+        if self.gzip {
+            let mut encoder = GzEncoder::new(writer, Compression::default());
+            let rows = self.stream(pool, &mut encoder).await?;
+            encoder.finish()?;
+            Ok(rows)
+        } else {
+            let mut writer = writer;
+            let rows = self.stream(pool, &mut writer).await?;
+            writer.flush()?;
+            Ok(rows)
+        }
+    }
+
+    async fn stream(&mut self, pool: &SqlitePool, out: &mut dyn Write) -> Result<u64, ReportError> {
+        let mut out = io::BufWriter::new(out);
+        let mut rows = sqlx::query(&self.query).fetch(pool);
+        let mut count = 0;
+        while let Some(row) = rows.try_next().await? {
+            if count == 0 && self.format == Format::Csv {
+                let header: Vec<String> =
+                    row.columns().iter().map(|c| c.name().to_string()).collect();
+                write_csv_line(&mut out, &header)?;
+            }
+            match self.format {
+                Format::Csv => {
+                    let fields: Vec<String> = (0..row.len())
+                        .map(|i| match self.cell(&row, i) {
+                            Value::Null => String::new(),
+                            Value::String(s) => s,
+                            other => other.to_string(),
+                        })
+                        .collect();
+                    write_csv_line(&mut out, &fields)?;
+                }
+                Format::JsonLines => {
+                    let object: Map<String, Value> = row
+                        .columns()
+                        .iter()
+                        .map(|c| (c.name().to_string(), self.cell(&row, c.ordinal())))
+                        .collect();
+                    serde_json::to_writer(&mut out, &object).map_err(io::Error::from)?;
+                    out.write_all(b"\n")?;
+                }
+            }
+            count += 1;
+            if count % self.progress_every == 0 {
+                if let Some(progress) = &mut self.progress {
+                    progress(count);
+                }
+            }
+        }
+        out.flush()?;
+        if let Some(progress) = &mut self.progress {
+            progress(count);
+        }
+        Ok(count)
+    }
+}
+
+pub async fn generate<W: Write>(
+    pool: &SqlitePool,
+    query: &str,
+    format: Format,
+    writer: W,
+) -> Result<u64, ReportError> {
+    Report::new(query, format).write_to(pool, writer).await
+}
+
+// SQLite 是动态类型，按每个值的实际存储类型转换
+pub(crate) fn value(row: &SqliteRow, index: usize) -> Value {
+    let Ok(raw) = row.try_get_raw(index) else {
+        return Value::Null;
+    };
+    if raw.is_null() {
+        return Value::Null;
+    }
+    match raw.type_info().name() {
+        "INTEGER" | "BOOLEAN" => row.try_get::<i64, _>(index).map(Value::from),
+        "REAL" => row.try_get::<f64, _>(index).map(Value::from),
+        "BLOB" => row
+            .try_get::<Vec<u8>, _>(index)
+            .map(|b| Value::from(hex::encode(b))),
+        _ => row.try_get::<String, _>(index).map(Value::from),
+    }
+    .unwrap_or(Value::Null)
+}
+
+fn write_csv_line(out: &mut impl Write, fields: &[String]) -> io::Result<()> {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.write_all(b",")?;
+        }
+        if field.contains([',', '"', '\n', '\r']) {
+            write!(out, "\"{}\"", field.replace('"', "\"\""))?;
+        } else {
+            out.write_all(field.as_bytes())?;
+        }
+    }
+    out.write_all(b"\r\n")
+}
@@ -0,0 +1,72 @@
+use std::future::Future;
+use std::time::Duration;
+
+use crate::clock::Clock;
+
+// 指数退避策略，等待通过 Clock 完成，测试中可以用模拟时钟瞬间跑完
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub multiplier: u32,
+    pub max_attempts: usize,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial: Duration::from_millis(100),
+            max: Duration::from_secs(10),
+            multiplier: 2,
+            max_attempts: 5,
+        }
+    }
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Backoff {
+            initial,
+            max,
+            ..Self::default()
+        }
+    }
+
+    pub fn multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier.max(1);
+        self
+    }
+
+    pub fn max_attempts(mut self, n: usize) -> Self {
+        self.max_attempts = n.max(1);
+        self
+    }
+
+    // 第 attempt 次失败后(从 1 开始)需要等待的时间
+    pub fn delay(&self, attempt: usize) -> Duration {
+        let factor = self
+            .multiplier
+            .checked_pow(attempt.saturating_sub(1) as u32)
+            .unwrap_or(u32::MAX);
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+}
+
+// 失败后按退避策略重试，返回最后一次的结果
+pub async fn retry<F, Fut, T, E>(clock: &dyn Clock, backoff: &Backoff, mut f: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt >= backoff.max_attempts => return Err(e),
+            Err(_) => {
+                clock.sleep(backoff.delay(attempt)).await;
+                attempt += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::task::JoinHandle;
+
+use crate::clock::{self, SharedClock};
+use crate::logs;
+
+// 周期任务调度，等待通过 Clock 完成，测试中可以用模拟时钟驱动
+pub struct Scheduler {
+    clock: SharedClock,
+    tasks: Mutex<Vec<(String, JoinHandle<()>)>>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::with_clock(clock::system())
+    }
+
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Scheduler {
+            clock,
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    // 每隔 interval 执行一次，第一次在 interval 之后；上一次没执行完不会开始下一次
+    pub fn every<F, Fut>(&self, name: &str, interval: Duration, task: F)
+    where
+        F: Fn() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let clock = self.clock.clone();
+        let task_name = name.to_string();
+        let handle = tokio::spawn(async move {
+            loop {
+                clock.sleep(interval).await;
+                logs::debug("scheduler", &format!("执行任务 {}", task_name));
+                task().await;
+            }
+        });
+        self.tasks.lock().unwrap().push((name.to_string(), handle));
+    }
+
+    pub fn tasks(&self) -> Vec<String> {
+        self.tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+
+    // 停止所有任务
+    pub fn shutdown(&self) {
+        for (_, handle) in self.tasks.lock().unwrap().drain(..) {
+            handle.abort();
+        }
+    }
+}
+
+impl Drop for Scheduler {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
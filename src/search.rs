@@ -0,0 +1,194 @@
+use std::sync::{Arc, Mutex};
+
+use sqlx::{Row, SqlitePool};
+
+use crate::db::hooks::{Entity, Hooks};
+use crate::db::DbError;
+use crate::page::{Page, PageRequest};
+
+// 基于 SQLite FTS5 的全文搜索，不依赖外部搜索引擎。所有实体共用一张虚拟表，
+// 按 (kind, entity_id) 区分。注册到仓库钩子后实体的增删改会进入待处理队列，
+// 查询前统一写入索引，所以查询总能看到已经提交的变更。
+
+pub const HIGHLIGHT_START: &str = "<mark>";
+pub const HIGHLIGHT_END: &str = "</mark>";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchDoc {
+    pub kind: &'static str,
+    pub id: i64,
+    pub title: String,
+    pub body: String,
+}
+
+pub trait Searchable: Entity {
+    const KIND: &'static str;
+
+    fn to_doc(&self) -> SearchDoc;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub kind: String,
+    pub id: i64,
+    // 匹配的词用 <mark></mark> 包围
+    pub title: String,
+    pub snippet: String,
+}
+
+#[derive(Debug)]
+enum Op {
+    Upsert(SearchDoc),
+    Remove(&'static str, i64),
+}
+
+#[derive(Clone)]
+pub struct SearchIndex {
+    pool: SqlitePool,
+    pending: Arc<Mutex<Vec<Op>>>,
+}
+
+impl SearchIndex {
+    pub fn new(pool: SqlitePool) -> Self {
+        SearchIndex {
+            pool,
+            pending: Arc::default(),
+        }
+    }
+
+    pub async fn migrate(&self) -> Result<(), DbError> {
+        sqlx::query(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+                kind UNINDEXED,
+                entity_id UNINDEXED,
+                title,
+                body,
+                tokenize = 'unicode61'
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // 实体写入成功后自动更新索引
+    pub fn register<E: Searchable>(&self, hooks: &mut Hooks<E>) {
+        let pending = self.pending.clone();
+        let upsert = move |entity: &E| pending.lock().unwrap().push(Op::Upsert(entity.to_doc()));
+        hooks.on_after_insert(upsert.clone());
+        hooks.on_after_update(upsert);
+        let pending = self.pending.clone();
+        hooks.on_after_delete(move |id| pending.lock().unwrap().push(Op::Remove(E::KIND, id)));
+    }
+
+    pub async fn index<E: Searchable>(&self, entity: &E) -> Result<(), DbError> {
+        self.index_doc(&entity.to_doc()).await
+    }
+
+    // 不对应仓库实体的文档，例如帮助文章
+    pub async fn index_doc(&self, doc: &SearchDoc) -> Result<(), DbError> {
+        self.flush().await?;
+        upsert(&self.pool, doc).await
+    }
+
+    pub async fn remove(&self, kind: &str, id: i64) -> Result<(), DbError> {
+        self.flush().await?;
+        remove(&self.pool, kind, id).await
+    }
+
+    // 把钩子收集的变更写入索引
+    pub async fn flush(&self) -> Result<(), DbError> {
+        let ops = std::mem::take(&mut *self.pending.lock().unwrap());
+        for (i, op) in ops.iter().enumerate() {
+            let result = match op {
+                Op::Upsert(doc) => upsert(&self.pool, doc).await,
+                Op::Remove(kind, id) => remove(&self.pool, kind, *id).await,
+            };
+            // 失败时把没有处理的变更放回队列，下次重试
+            if let Err(e) = result {
+                let mut pending = self.pending.lock().unwrap();
+                let rest: Vec<Op> = ops.into_iter().skip(i).collect();
+                pending.splice(0..0, rest);
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    // 按相关度排序，每个词都需要匹配，最后一个词按前缀匹配
+    pub async fn query(&self, text: &str, page: PageRequest) -> Result<Page<Hit>, DbError> {
+        self.flush().await?;
+        let Some(expr) = match_expr(text) else {
+            return Ok(Page::new(Vec::new(), 0, page));
+        };
+        let total: i64 =
+            sqlx::query_scalar("SELECT count(*) FROM search_index WHERE search_index MATCH ?1")
+                .bind(&expr)
+                .fetch_one(&self.pool)
+                .await?;
+        let rows = sqlx::query(
+            "SELECT kind, entity_id,
+                    highlight(search_index, 2, ?2, ?3),
+                    snippet(search_index, 3, ?2, ?3, '…', 12)
+             FROM search_index WHERE search_index MATCH ?1
+             ORDER BY bm25(search_index, 10.0, 1.0), entity_id
+             LIMIT ?4 OFFSET ?5",
+        )
+        .bind(&expr)
+        .bind(HIGHLIGHT_START)
+        .bind(HIGHLIGHT_END)
+        .bind(page.limit() as i64)
+        .bind(page.offset() as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        let hits = rows
+            .iter()
+            .map(|row| Hit {
+                kind: row.get(0),
+                id: row.get(1),
+                title: row.get(2),
+                snippet: row.get(3),
+            })
+            .collect();
+        Ok(Page::new(hits, total as u64, page))
+    }
+}
+
+async fn upsert(pool: &SqlitePool, doc: &SearchDoc) -> Result<(), DbError> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM search_index WHERE kind = ?1 AND entity_id = ?2")
+        .bind(doc.kind)
+        .bind(doc.id)
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query("INSERT INTO search_index (kind, entity_id, title, body) VALUES (?1, ?2, ?3, ?4)")
+        .bind(doc.kind)
+        .bind(doc.id)
+        .bind(&doc.title)
+        .bind(&doc.body)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(())
+}
+
+async fn remove(pool: &SqlitePool, kind: &str, id: i64) -> Result<(), DbError> {
+    sqlx::query("DELETE FROM search_index WHERE kind = ?1 AND entity_id = ?2")
+        .bind(kind)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+// 用户输入不按 FTS5 语法解析: 每个词加引号，避免引号、括号、AND/OR 造成语法错误
+fn match_expr(text: &str) -> Option<String> {
+    let terms: Vec<String> = text
+        .split_whitespace()
+        .map(|t| format!("\"{}\"", t.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        return None;
+    }
+    Some(format!("{}*", terms.join(" ")))
+}
@@ -0,0 +1,366 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::cache::TtlCache;
+use crate::config::ConfigLoader;
+use crate::db::pool::Pool;
+
+// 启动自检: 逐项检查配置的子系统(配置文件、数据库、HTTP 端点、缓存、数据目录)，
+// 汇总成通过/失败矩阵，失败项附带可以照着操作的修复建议。
+// 配置文件中的 db.url、http.base_url、http.endpoints、data_dir、data_dirs
+// 会自动加入对应的检查，命令行指定的目标追加在后面。
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Pass,
+    Fail,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CheckResult {
+    pub subsystem: &'static str,
+    pub target: String,
+    pub outcome: Outcome,
+    pub detail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SelfTestReport {
+    pub results: Vec<CheckResult>,
+}
+
+impl SelfTestReport {
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(|r| r.outcome == Outcome::Pass)
+    }
+
+    pub fn failures(&self) -> Vec<&CheckResult> {
+        self.results
+            .iter()
+            .filter(|r| r.outcome == Outcome::Fail)
+            .collect()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("SelfTestReport 总是可以序列化")
+    }
+}
+
+// 每项一行: 结果、子系统、目标、说明，失败项下一行是修复建议
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .results
+            .iter()
+            .map(|r| r.target.chars().count())
+            .max()
+            .unwrap_or(0);
+        for r in &self.results {
+            let mark = match r.outcome {
+                Outcome::Pass => "PASS",
+                Outcome::Fail => "FAIL",
+            };
+            writeln!(
+                f,
+                "{}  {:<7} {:<width$}  {} ({}ms)",
+                mark,
+                r.subsystem,
+                r.target,
+                r.detail,
+                r.elapsed_ms,
+                width = width
+            )?;
+            if let Some(hint) = &r.hint {
+                writeln!(f, "      -> {}", hint)?;
+            }
+        }
+        let failed = self.failures().len();
+        write!(
+            f,
+            "{} 项通过，{} 项失败",
+            self.results.len() - failed,
+            failed
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SelfTest {
+    config: Option<PathBuf>,
+    db_urls: Vec<String>,
+    endpoints: Vec<String>,
+    data_dirs: Vec<PathBuf>,
+    timeout: Duration,
+}
+
+impl Default for SelfTest {
+    fn default() -> Self {
+        SelfTest {
+            config: None,
+            db_urls: Vec::new(),
+            endpoints: Vec::new(),
+            data_dirs: Vec::new(),
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl SelfTest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn config(mut self, path: impl AsRef<Path>) -> Self {
+        self.config = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn db(mut self, url: &str) -> Self {
+        self.db_urls.push(url.to_string());
+        self
+    }
+
+    pub fn endpoint(mut self, url: &str) -> Self {
+        self.endpoints.push(url.to_string());
+        self
+    }
+
+    pub fn data_dir(mut self, path: impl AsRef<Path>) -> Self {
+        self.data_dirs.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    // 单项网络检查的超时，默认 5 秒
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub async fn run(&self) -> SelfTestReport {
+        let mut report = SelfTestReport::default();
+        let (mut db_urls, mut endpoints, mut data_dirs) = (Vec::new(), Vec::new(), Vec::new());
+        if let Some(path) = &self.config {
+            let start = Instant::now();
+            let target = path.display().to_string();
+            match ConfigLoader::new().file(path).merged() {
+                Ok(config) => {
+                    collect_targets(&config, &mut db_urls, &mut endpoints, &mut data_dirs);
+                    report
+                        .results
+                        .push(pass("config", target, "解析成功".into(), start));
+                }
+                Err(e) => report.results.push(fail(
+                    "config",
+                    target,
+                    e.to_string(),
+                    "检查文件路径和语法，扩展名需要是 .toml/.yaml/.yml/.json",
+                    start,
+                )),
+            }
+        }
+        db_urls.extend(self.db_urls.iter().cloned());
+        endpoints.extend(self.endpoints.iter().cloned());
+        data_dirs.extend(self.data_dirs.iter().cloned());
+
+        for url in &db_urls {
+            report.results.push(self.check_db(url).await);
+        }
+        for url in &endpoints {
+            report.results.push(self.check_http(url).await);
+        }
+        report.results.push(check_cache());
+        for dir in &data_dirs {
+            report.results.push(check_dir(dir));
+        }
+        report
+    }
+
+    async fn check_db(&self, url: &str) -> CheckResult {
+        let start = Instant::now();
+        let result = tokio::time::timeout(self.timeout, async {
+            let pool = Pool::builder(url)
+                .acquire_timeout(self.timeout)
+                .connect()
+                .await
+                .map_err(|e| e.to_string())?;
+            let tables: Vec<String> = sqlx::query_scalar(
+                "SELECT name FROM sqlite_master WHERE type = 'table' \
+                 AND name NOT LIKE 'sqlite_%' ORDER BY name",
+            )
+            .fetch_all(pool.inner())
+            .await
+            .map_err(|e| e.to_string())?;
+            Ok::<_, String>(tables)
+        })
+        .await
+        .unwrap_or_else(|_| Err(format!("{:?} 内没有完成", self.timeout)));
+        match result {
+            // 表都是启动时按需创建的，没有表说明还没有运行过迁移
+            Ok(tables) if tables.is_empty() => pass(
+                "db",
+                url.to_string(),
+                "已连接，尚未创建任何表(迁移会在首次启动时执行)".into(),
+                start,
+            ),
+            Ok(tables) => pass(
+                "db",
+                url.to_string(),
+                format!("已连接，{} 张表: {}", tables.len(), tables.join(", ")),
+                start,
+            ),
+            Err(e) => fail(
+                "db",
+                url.to_string(),
+                e,
+                "确认数据库文件所在目录存在且可写，URL 形如 sqlite://data/app.db",
+                start,
+            ),
+        }
+    }
+
+    async fn check_http(&self, url: &str) -> CheckResult {
+        let start = Instant::now();
+        let client = reqwest::Client::builder().timeout(self.timeout).build();
+        let result = match client {
+            Ok(client) => client.get(url).send().await.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match result {
+            // 能收到响应就说明网络可达，4xx 通常只是路径或认证问题
+            Ok(response) if response.status().as_u16() < 500 => pass(
+                "http",
+                url.to_string(),
+                format!("HTTP {}", response.status().as_u16()),
+                start,
+            ),
+            Ok(response) => fail(
+                "http",
+                url.to_string(),
+                format!("HTTP {}", response.status().as_u16()),
+                "上游服务返回 5xx，检查该服务的状态和日志",
+                start,
+            ),
+            Err(e) => fail(
+                "http",
+                url.to_string(),
+                e,
+                "检查 DNS、防火墙和代理设置，确认服务已启动并监听该地址",
+                start,
+            ),
+        }
+    }
+}
+
+fn collect_targets(
+    config: &Value,
+    db_urls: &mut Vec<String>,
+    endpoints: &mut Vec<String>,
+    data_dirs: &mut Vec<PathBuf>,
+) {
+    let strings = |value: Option<&Value>| -> Vec<String> {
+        match value {
+            Some(Value::String(s)) => vec![s.clone()],
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        }
+    };
+    db_urls.extend(strings(config.pointer("/db/url")));
+    endpoints.extend(strings(config.pointer("/http/base_url")));
+    endpoints.extend(strings(config.pointer("/http/endpoints")));
+    for key in ["/data_dir", "/data_dirs"] {
+        data_dirs.extend(strings(config.pointer(key)).into_iter().map(PathBuf::from));
+    }
+}
+
+fn check_cache() -> CheckResult {
+    let start = Instant::now();
+    let cache = TtlCache::new(Duration::from_secs(60));
+    cache.insert("selftest", 42u32);
+    let ok = cache.get(&"selftest") == Some(42) && cache.remove(&"selftest") == Some(42);
+    if ok {
+        pass(
+            "cache",
+            "memory".into(),
+            "写入、读取、删除正常".into(),
+            start,
+        )
+    } else {
+        fail(
+            "cache",
+            "memory".into(),
+            "写入的值读不回来".into(),
+            "检查是否使用了不兼容的时钟或缓存配置",
+            start,
+        )
+    }
+}
+
+// 写入并删除一个临时文件，目录不存在时不自动创建
+fn check_dir(dir: &Path) -> CheckResult {
+    let start = Instant::now();
+    let target = dir.display().to_string();
+    if !dir.is_dir() {
+        return fail(
+            "dir",
+            target,
+            "目录不存在".into(),
+            &format!("创建目录: mkdir -p {}", dir.display()),
+            start,
+        );
+    }
+    let probe = dir.join(format!(".selftest-{}", std::process::id()));
+    let result = fs::write(&probe, b"selftest").and_then(|_| fs::remove_file(&probe));
+    match result {
+        Ok(()) => pass("dir", target, "可写".into(), start),
+        Err(e) => fail(
+            "dir",
+            target,
+            e.to_string(),
+            &format!(
+                "确认运行用户对目录有写权限，例如 chown $(id -u) {}",
+                dir.display()
+            ),
+            start,
+        ),
+    }
+}
+
+fn pass(subsystem: &'static str, target: String, detail: String, start: Instant) -> CheckResult {
+    CheckResult {
+        subsystem,
+        target,
+        outcome: Outcome::Pass,
+        detail,
+        hint: None,
+        elapsed_ms: start.elapsed().as_millis() as u64,
+    }
+}
+
+fn fail(
+    subsystem: &'static str,
+    target: String,
+    detail: String,
+    hint: &str,
+    start: Instant,
+) -> CheckResult {
+    CheckResult {
+        subsystem,
+        target,
+        outcome: Outcome::Fail,
+        detail,
+        hint: Some(hint.to_string()),
+        elapsed_ms: start.elapsed().as_millis() as u64,
+    }
+}
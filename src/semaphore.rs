@@ -0,0 +1,289 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::oneshot;
+
+// 按优先级分配许可的异步信号量: 许可用完时等待者排队，释放的许可先给优先级最高的，
+// 同一优先级按到达顺序。等待者可以带 deadline，到期返回 AcquireError::Timeout。
+// 用来在高负载时给数据库连接和出站 HTTP 请求排队，保证关键操作先拿到资源。
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    // 后台任务、报表导出
+    Low,
+    #[default]
+    Normal,
+    // 面向用户的请求
+    High,
+    // 支付、健康检查等不能被饿死的操作
+    Critical,
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+            Priority::Critical => "critical",
+        };
+        f.write_str(s)
+    }
+}
+
+impl FromStr for Priority {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "low" => Ok(Priority::Low),
+            "normal" => Ok(Priority::Normal),
+            "high" => Ok(Priority::High),
+            "critical" => Ok(Priority::Critical),
+            _ => Err(format!("未知的优先级: {}", s)),
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AcquireError {
+    #[error("优先级 {priority} 的请求等待许可 {waited:?} 后超时")]
+    Timeout {
+        priority: Priority,
+        waited: Duration,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SemaphoreStats {
+    pub permits: usize,
+    pub available: usize,
+    pub waiting: usize,
+    pub timeouts: u64,
+}
+
+struct Waiter {
+    priority: Priority,
+    seq: u64,
+    grant: oneshot::Sender<()>,
+}
+
+// BinaryHeap 是最大堆: 优先级高的在前，同优先级先到的(seq 小)在前
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+struct State {
+    available: usize,
+    // 可能包含已经超时或被取消的等待者，分配许可时跳过
+    waiters: BinaryHeap<Waiter>,
+    next_seq: u64,
+}
+
+struct Inner {
+    permits: usize,
+    state: Mutex<State>,
+    timeouts: AtomicU64,
+}
+
+impl Inner {
+    // 交给下一个还在等待的人，没有人等待时放回
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        while let Some(waiter) = state.waiters.pop() {
+            if waiter.grant.send(()).is_ok() {
+                return;
+            }
+        }
+        state.available += 1;
+    }
+}
+
+#[derive(Clone)]
+pub struct PrioritySemaphore {
+    inner: Arc<Inner>,
+}
+
+impl fmt::Debug for PrioritySemaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrioritySemaphore")
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+// drop 时归还许可
+pub struct Permit {
+    inner: Arc<Inner>,
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.inner.release();
+    }
+}
+
+impl fmt::Debug for Permit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Permit")
+    }
+}
+
+// 等待中的 future 被取消时，已经分到的许可要归还
+struct Pending {
+    receiver: Option<oneshot::Receiver<()>>,
+    inner: Arc<Inner>,
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        if let Some(mut receiver) = self.receiver.take() {
+            receiver.close();
+            if receiver.try_recv().is_ok() {
+                self.inner.release();
+            }
+        }
+    }
+}
+
+impl PrioritySemaphore {
+    pub fn new(permits: usize) -> Self {
+        PrioritySemaphore {
+            inner: Arc::new(Inner {
+                permits,
+                state: Mutex::new(State {
+                    available: permits,
+                    waiters: BinaryHeap::new(),
+                    next_seq: 0,
+                }),
+                timeouts: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    pub fn stats(&self) -> SemaphoreStats {
+        let state = self.inner.state.lock().unwrap();
+        SemaphoreStats {
+            permits: self.inner.permits,
+            available: state.available,
+            waiting: state
+                .waiters
+                .iter()
+                .filter(|w| !w.grant.is_closed())
+                .count(),
+            timeouts: self.inner.timeouts.load(atomic::Ordering::Relaxed),
+        }
+    }
+
+    // 有空闲许可时立即返回，不插队到等待者前面
+    pub fn try_acquire(&self) -> Option<Permit> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.available == 0 {
+            return None;
+        }
+        state.available -= 1;
+        Some(self.permit())
+    }
+
+    // 不限时等待
+    pub async fn acquire(&self, priority: Priority) -> Permit {
+        match self.acquire_until(priority, None).await {
+            Ok(permit) => permit,
+            Err(_) => unreachable!("没有 deadline 时不会超时"),
+        }
+    }
+
+    pub async fn acquire_timeout(
+        &self,
+        priority: Priority,
+        timeout: Duration,
+    ) -> Result<Permit, AcquireError> {
+        self.acquire_until(priority, Some(Instant::now() + timeout))
+            .await
+    }
+
+    // deadline 为 None 时不限时；已经过期的 deadline 在没有空闲许可时立即超时
+    pub async fn acquire_until(
+        &self,
+        priority: Priority,
+        deadline: Option<Instant>,
+    ) -> Result<Permit, AcquireError> {
+        let start = Instant::now();
+        let receiver = {
+            let mut state = self.inner.state.lock().unwrap();
+            if state.available > 0 {
+                state.available -= 1;
+                return Ok(self.permit());
+            }
+            let (grant, receiver) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiters.push(Waiter {
+                priority,
+                seq,
+                grant,
+            });
+            receiver
+        };
+        let mut pending = Pending {
+            receiver: Some(receiver),
+            inner: self.inner.clone(),
+        };
+        let receiver = pending.receiver.as_mut().expect("刚刚放入");
+        let granted = match deadline {
+            None => receiver.await.is_ok(),
+            Some(deadline) => {
+                let deadline = tokio::time::Instant::from_std(deadline);
+                matches!(
+                    tokio::time::timeout_at(deadline, receiver).await,
+                    Ok(Ok(()))
+                )
+            }
+        };
+        if granted {
+            pending.receiver = None;
+            return Ok(self.permit());
+        }
+        // 超时，drop(pending) 处理超时和分配同时发生的情况
+        drop(pending);
+        self.inner.timeouts.fetch_add(1, atomic::Ordering::Relaxed);
+        Err(AcquireError::Timeout {
+            priority,
+            waited: start.elapsed(),
+        })
+    }
+
+    fn permit(&self) -> Permit {
+        Permit {
+            inner: self.inner.clone(),
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ini::Ini;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SerdeAnyError {
+    #[error("无法根据扩展名识别格式: {0}")]
+    UnknownFormat(PathBuf),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("JSON 格式错误: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("TOML 解析失败: {0}")]
+    TomlDe(#[from] toml::de::Error),
+    #[error("TOML 序列化失败: {0}")]
+    TomlSer(#[from] toml::ser::Error),
+    #[error("YAML 格式错误: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("INI 格式错误: {0}")]
+    Ini(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Toml,
+    Yaml,
+    Ini,
+}
+
+impl Format {
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "json" => Some(Format::Json),
+            "toml" => Some(Format::Toml),
+            "yaml" | "yml" => Some(Format::Yaml),
+            "ini" => Some(Format::Ini),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .and_then(Self::from_extension)
+    }
+}
+
+fn detect(path: &Path) -> Result<Format, SerdeAnyError> {
+    Format::from_path(path).ok_or_else(|| SerdeAnyError::UnknownFormat(path.to_path_buf()))
+}
+
+pub fn from_path<T: DeserializeOwned>(path: impl AsRef<Path>) -> Result<T, SerdeAnyError> {
+    let path = path.as_ref();
+    let format = detect(path)?;
+    from_str(&fs::read_to_string(path)?, format)
+}
+
+pub fn to_path<T: Serialize>(path: impl AsRef<Path>, value: &T) -> Result<(), SerdeAnyError> {
+    let path = path.as_ref();
+    let format = detect(path)?;
+    fs::write(path, to_string(value, format)?)?;
+    Ok(())
+}
+
+pub fn from_str<T: DeserializeOwned>(s: &str, format: Format) -> Result<T, SerdeAnyError> {
+    Ok(match format {
+        Format::Json => serde_json::from_str(s)?,
+        Format::Toml => toml::from_str(s)?,
+        Format::Yaml => serde_yaml::from_str(s)?,
+        Format::Ini => serde_json::from_value(ini_to_value(s)?)?,
+    })
+}
+
+pub fn to_string<T: Serialize>(value: &T, format: Format) -> Result<String, SerdeAnyError> {
+    Ok(match format {
+        Format::Json => serde_json::to_string_pretty(value)?,
+        Format::Toml => toml::to_string_pretty(value)?,
+        Format::Yaml => serde_yaml::to_string(value)?,
+        Format::Ini => value_to_ini(&serde_json::to_value(value)?)?,
+    })
+}
+
+// INI 只有字符串，这里按 bool / 整数 / 浮点数 / 字符串的顺序推断类型。
+// 无节名的键放在顶层，[section] 下的键放在同名的嵌套对象中。
+fn ini_to_value(s: &str) -> Result<Value, SerdeAnyError> {
+    let ini = Ini::load_from_str(s).map_err(|e| SerdeAnyError::Ini(e.to_string()))?;
+    let mut root = Map::new();
+    for (section, properties) in ini.iter() {
+        let mut map = Map::new();
+        for (k, v) in properties.iter() {
+            map.insert(k.to_string(), infer(v));
+        }
+        match section {
+            None => root.extend(map),
+            Some(name) => {
+                root.insert(name.to_string(), Value::Object(map));
+            }
+        }
+    }
+    Ok(Value::Object(root))
+}
+
+fn infer(raw: &str) -> Value {
+    if let Ok(b) = raw.parse::<bool>() {
+        Value::Bool(b)
+    } else if let Ok(i) = raw.parse::<i64>() {
+        Value::from(i)
+    } else if let Some(n) = raw
+        .parse::<f64>()
+        .ok()
+        .and_then(serde_json::Number::from_f64)
+    {
+        Value::Number(n)
+    } else {
+        Value::String(raw.to_string())
+    }
+}
+
+fn value_to_ini(value: &Value) -> Result<String, SerdeAnyError> {
+    let root = value
+        .as_object()
+        .ok_or_else(|| SerdeAnyError::Ini("顶层必须是对象".to_string()))?;
+    let mut ini = Ini::new();
+    // 先写无节名的键，否则它们会被读回到前一个 section 中
+    for (key, v) in root.iter().filter(|(_, v)| !v.is_object()) {
+        let scalar =
+            scalar(v).ok_or_else(|| SerdeAnyError::Ini(format!("{} 不能表示为 INI 值", key)))?;
+        ini.with_general_section().set(key.as_str(), scalar);
+    }
+    for (key, v) in root {
+        let Value::Object(section) = v else {
+            continue;
+        };
+        for (k, v) in section {
+            let scalar = scalar(v).ok_or_else(|| {
+                SerdeAnyError::Ini(format!("{}.{} 嵌套过深，INI 只支持一层 section", key, k))
+            })?;
+            ini.with_section(Some(key.as_str())).set(k.as_str(), scalar);
+        }
+    }
+    let mut out = Vec::new();
+    ini.write_to(&mut out)?;
+    Ok(String::from_utf8_lossy(&out).into_owned())
+}
+
+fn scalar(v: &Value) -> Option<String> {
+    match v {
+        Value::String(s) => Some(s.clone()),
+        Value::Bool(_) | Value::Number(_) => Some(v.to_string()),
+        _ => None,
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+use tokio::signal::unix::{signal, SignalKind};
+
+use crate::metrics;
+
+// 进程信号分发: 同一个信号可以注册多个处理函数，每种信号只启动一个监听任务。
+// SIGINT/SIGTERM 留给 shutdown() 使用，避免和优雅退出逻辑冲突。
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    Hup,
+    Usr1,
+    Usr2,
+    Int,
+    Term,
+}
+
+pub const SIGHUP: Signal = Signal::Hup;
+pub const SIGUSR1: Signal = Signal::Usr1;
+pub const SIGUSR2: Signal = Signal::Usr2;
+pub const SIGINT: Signal = Signal::Int;
+pub const SIGTERM: Signal = Signal::Term;
+
+impl Signal {
+    fn kind(self) -> SignalKind {
+        match self {
+            Signal::Hup => SignalKind::hangup(),
+            Signal::Usr1 => SignalKind::user_defined1(),
+            Signal::Usr2 => SignalKind::user_defined2(),
+            Signal::Int => SignalKind::interrupt(),
+            Signal::Term => SignalKind::terminate(),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum SignalError {
+    #[error("信号由 shutdown 处理，不能单独注册: {0:?}")]
+    Reserved(Signal),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+type Handler = Arc<dyn Fn() + Send + Sync>;
+type Registry = Mutex<HashMap<Signal, Vec<(u64, Handler)>>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandlerId(Signal, u64);
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+fn registry() -> &'static Registry {
+    static REGISTRY: std::sync::OnceLock<Registry> = std::sync::OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// 监听任务运行在独立的运行时上，不依赖调用方的运行时生命周期
+fn runtime() -> &'static tokio::runtime::Runtime {
+    static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+    RUNTIME.get_or_init(|| {
+        tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .thread_name("signals")
+            .enable_all()
+            .build()
+            .expect("创建信号处理运行时失败")
+    })
+}
+
+// 注册信号处理函数，处理函数在信号线程上执行
+pub fn on<F>(sig: Signal, handler: F) -> Result<HandlerId, SignalError>
+where
+    F: Fn() + Send + Sync + 'static,
+{
+    if matches!(sig, Signal::Int | Signal::Term) {
+        return Err(SignalError::Reserved(sig));
+    }
+    let mut registered = registry().lock().unwrap();
+    if !registered.contains_key(&sig) {
+        let rt = runtime();
+        let mut stream = {
+            let _guard = rt.enter();
+            signal(sig.kind())?
+        };
+        rt.spawn(async move {
+            while stream.recv().await.is_some() {
+                let handlers: Vec<Handler> = registry()
+                    .lock()
+                    .unwrap()
+                    .get(&sig)
+                    .map(|hs| hs.iter().map(|(_, h)| h.clone()).collect())
+                    .unwrap_or_default();
+                for handler in handlers {
+                    handler();
+                }
+            }
+        });
+    }
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    registered
+        .entry(sig)
+        .or_default()
+        .push((id, Arc::new(handler)));
+    Ok(HandlerId(sig, id))
+}
+
+// 移除处理函数，监听任务保留，之后收到的信号直接忽略
+pub fn off(id: HandlerId) {
+    if let Some(handlers) = registry().lock().unwrap().get_mut(&id.0) {
+        handlers.retain(|(i, _)| *i != id.1);
+    }
+}
+
+// 等待 SIGINT 或 SIGTERM，返回收到的信号
+pub async fn shutdown() -> Result<Signal, SignalError> {
+    let mut int = signal(SignalKind::interrupt())?;
+    let mut term = signal(SignalKind::terminate())?;
+    tokio::select! {
+        _ = int.recv() => Ok(Signal::Int),
+        _ = term.recv() => Ok(Signal::Term),
+    }
+}
+
+// SIGUSR1 时输出的运行时状态；处理函数运行在信号线程上，需要传入业务运行时的 Handle
+pub fn runtime_stats(handle: &tokio::runtime::Handle) -> String {
+    let metrics = handle.metrics();
+    format!(
+        "workers={} alive_tasks={}",
+        metrics.num_workers(),
+        metrics.num_alive_tasks()
+    )
+}
+
+// SIGUSR1 的完整输出: 第一行是运行时状态，之后每行一个指标的 JSON
+pub fn dump(handle: &tokio::runtime::Handle, registry: &metrics::Registry) -> String {
+    let mut out = runtime_stats(handle);
+    for metric in registry.snapshot() {
+        out.push('\n');
+        out.push_str(&serde_json::to_string(&metric).expect("Metric 总是可以序列化"));
+    }
+    out
+}
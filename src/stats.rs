@@ -0,0 +1,303 @@
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::clock::{self, SharedClock};
+
+// 流式统计: t-digest 分位数估计和按时间滑动的窗口。
+// Digest 可以合并，各个 worker 分别记录，汇总时 merge 即可得到整体分位数；
+// 可以序列化，跨进程汇总时直接传输。
+
+pub const DEFAULT_COMPRESSION: f64 = 100.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Centroid {
+    pub mean: f64,
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Digest {
+    compression: f64,
+    centroids: Vec<Centroid>,
+    // 还没有合并进 centroids 的数据，攒够一批再压缩
+    buffer: Vec<Centroid>,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for Digest {
+    fn default() -> Self {
+        Digest::new(DEFAULT_COMPRESSION)
+    }
+}
+
+impl Digest {
+    // compression 越大越精确，质心数量大约不超过 compression
+    pub fn new(compression: f64) -> Self {
+        Digest {
+            compression: compression.max(10.0),
+            centroids: Vec::new(),
+            buffer: Vec::new(),
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+
+    pub fn add(&mut self, value: f64) {
+        if value.is_nan() {
+            return;
+        }
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.buffer.push(Centroid {
+            mean: value,
+            weight: 1.0,
+        });
+        if self.buffer.len() >= (self.compression * 5.0) as usize {
+            self.compress();
+        }
+    }
+
+    pub fn merge(&mut self, other: &Digest) {
+        if other.count == 0 {
+            return;
+        }
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.buffer.extend_from_slice(&other.centroids);
+        self.buffer.extend_from_slice(&other.buffer);
+        self.compress();
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    pub fn centroids(&mut self) -> &[Centroid] {
+        self.compress();
+        &self.centroids
+    }
+
+    // k1 尺度函数: 两端的质心小、中间的大，所以尾部分位数(p99)更精确
+    fn k(&self, q: f64) -> f64 {
+        self.compression / (2.0 * PI) * (2.0 * q - 1.0).asin()
+    }
+
+    fn q(&self, k: f64) -> f64 {
+        let k = k.min(self.compression / 4.0);
+        ((k * 2.0 * PI / self.compression).sin() + 1.0) / 2.0
+    }
+
+    fn compress(&mut self) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        let mut all = std::mem::take(&mut self.centroids);
+        all.append(&mut self.buffer);
+        all.sort_by(|a, b| a.mean.total_cmp(&b.mean));
+
+        let total: f64 = all.iter().map(|c| c.weight).sum();
+        let mut merged = Vec::with_capacity(self.compression as usize);
+        let mut iter = all.into_iter();
+        let mut current = iter.next().expect("buffer 不为空");
+        let mut weight_so_far = 0.0;
+        let mut limit = total * self.q(self.k(0.0) + 1.0);
+        for next in iter {
+            if weight_so_far + current.weight + next.weight <= limit {
+                let weight = current.weight + next.weight;
+                current.mean += (next.mean - current.mean) * next.weight / weight;
+                current.weight = weight;
+            } else {
+                weight_so_far += current.weight;
+                merged.push(current);
+                limit = total * self.q(self.k(weight_so_far / total) + 1.0);
+                current = next;
+            }
+        }
+        merged.push(current);
+        self.centroids = merged;
+    }
+
+    // q 取 0..=1；没有数据时返回 None。相邻质心之间线性插值，两端用 min/max
+    pub fn quantile(&mut self, q: f64) -> Option<f64> {
+        if self.count == 0 {
+            return None;
+        }
+        self.compress();
+        let q = q.clamp(0.0, 1.0);
+        if q == 0.0 {
+            return Some(self.min);
+        }
+        if q == 1.0 {
+            return Some(self.max);
+        }
+        let centroids = &self.centroids;
+        let total = self.count as f64;
+        let index = q * total;
+
+        let first = centroids[0];
+        if index < first.weight / 2.0 {
+            let t = index / (first.weight / 2.0);
+            return Some(self.min + t * (first.mean - self.min));
+        }
+        let mut cumulative = first.weight / 2.0;
+        for pair in centroids.windows(2) {
+            let step = (pair[0].weight + pair[1].weight) / 2.0;
+            if cumulative + step > index {
+                let t = (index - cumulative) / step;
+                return Some(pair[0].mean + t * (pair[1].mean - pair[0].mean));
+            }
+            cumulative += step;
+        }
+        let last = centroids[centroids.len() - 1];
+        let t = ((index - cumulative) / (last.weight / 2.0)).min(1.0);
+        Some(last.mean + t * (self.max - last.mean))
+    }
+}
+
+impl FromIterator<f64> for Digest {
+    fn from_iter<I: IntoIterator<Item = f64>>(iter: I) -> Self {
+        let mut digest = Digest::default();
+        iter.into_iter().for_each(|v| digest.add(v));
+        digest
+    }
+}
+
+// 最近 window 时间内的数据，分成若干个桶，整桶过期
+#[derive(Debug)]
+pub struct SlidingWindow {
+    bucket_width: Duration,
+    buckets: VecDeque<(u64, Digest)>,
+    len: u64,
+    compression: f64,
+    clock: SharedClock,
+    origin: Instant,
+}
+
+impl SlidingWindow {
+    // buckets 越多过期越平滑，代价是合并时更慢
+    pub fn new(window: Duration, buckets: usize) -> Self {
+        let buckets = buckets.max(1);
+        let clock = clock::system();
+        SlidingWindow {
+            bucket_width: (window / buckets as u32).max(Duration::from_millis(1)),
+            buckets: VecDeque::with_capacity(buckets),
+            len: buckets as u64,
+            compression: DEFAULT_COMPRESSION,
+            origin: clock.now(),
+            clock,
+        }
+    }
+
+    pub fn clock(mut self, clock: SharedClock) -> Self {
+        self.origin = clock.now();
+        self.clock = clock;
+        self
+    }
+
+    pub fn compression(mut self, compression: f64) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    pub fn window(&self) -> Duration {
+        self.bucket_width * self.len as u32
+    }
+
+    fn current(&self) -> u64 {
+        let elapsed = self.clock.now().saturating_duration_since(self.origin);
+        (elapsed.as_nanos() / self.bucket_width.as_nanos()) as u64
+    }
+
+    fn expire(&mut self) -> u64 {
+        let current = self.current();
+        while let Some((index, _)) = self.buckets.front() {
+            if index + self.len > current {
+                break;
+            }
+            self.buckets.pop_front();
+        }
+        current
+    }
+
+    pub fn record(&mut self, value: f64) {
+        let current = self.expire();
+        match self.buckets.back_mut() {
+            Some((index, digest)) if *index == current => digest.add(value),
+            _ => {
+                let mut digest = Digest::new(self.compression);
+                digest.add(value);
+                self.buckets.push_back((current, digest));
+            }
+        }
+    }
+
+    // 窗口内所有数据合并后的 digest
+    pub fn digest(&mut self) -> Digest {
+        self.expire();
+        let mut merged = Digest::new(self.compression);
+        for (_, digest) in &self.buckets {
+            merged.merge(digest);
+        }
+        merged
+    }
+
+    pub fn count(&mut self) -> u64 {
+        self.expire();
+        self.buckets.iter().map(|(_, d)| d.count()).sum()
+    }
+
+    pub fn sum(&mut self) -> f64 {
+        self.expire();
+        self.buckets.iter().map(|(_, d)| d.sum()).sum()
+    }
+
+    pub fn mean(&mut self) -> Option<f64> {
+        let count = self.count();
+        (count > 0).then(|| self.sum() / count as f64)
+    }
+
+    // 每秒事件数
+    pub fn rate(&mut self) -> f64 {
+        self.count() as f64 / self.window().as_secs_f64()
+    }
+
+    pub fn quantile(&mut self, q: f64) -> Option<f64> {
+        self.digest().quantile(q)
+    }
+
+    pub fn clear(&mut self) {
+        self.buckets.clear();
+    }
+}
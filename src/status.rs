@@ -0,0 +1,23 @@
+use crate::db::DbError;
+
+// 错误到 HTTP 状态码的映射，HTTP 层据此生成响应而不用逐个 match 错误类型
+pub trait HttpStatus {
+    fn status(&self) -> u16;
+
+    fn is_client_error(&self) -> bool {
+        (400..500).contains(&self.status())
+    }
+}
+
+impl HttpStatus for DbError {
+    fn status(&self) -> u16 {
+        match self {
+            DbError::NotFound(_) => 404,
+            DbError::Duplicate(_) => 409,
+            DbError::Rejected(_) => 422,
+            DbError::Connection(_) => 503,
+            DbError::Query(_) => 500,
+            DbError::Timeout { .. } => 504,
+        }
+    }
+}
@@ -0,0 +1,371 @@
+pub mod sigv4;
+
+use std::time::SystemTime;
+
+use reqwest::{Method, StatusCode, Url};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::context;
+
+pub use sigv4::Credentials;
+use sigv4::{sha256_hex, uri_encode, SigningRequest};
+
+// S3 要求除最后一个分片外每片至少 5MB
+pub const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("对象不存在: {0}")]
+    NotFound(String),
+    #[error("请求失败: HTTP {status}: {body}")]
+    Status { status: u16, body: String },
+    #[error("响应格式错误: {0}")]
+    InvalidResponse(String),
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone)]
+pub struct StorageConfig {
+    // 例如 http://127.0.0.1:9000，使用 path-style 访问 bucket
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub credentials: Credentials,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjectInfo {
+    pub key: String,
+    pub size: u64,
+    pub etag: String,
+}
+
+pub struct ObjectStorage {
+    client: reqwest::Client,
+    config: StorageConfig,
+    part_size: usize,
+}
+
+impl ObjectStorage {
+    pub fn new(config: StorageConfig) -> Self {
+        Self::with_client(reqwest::Client::new(), config)
+    }
+
+    pub fn with_client(client: reqwest::Client, config: StorageConfig) -> Self {
+        ObjectStorage {
+            client,
+            config,
+            part_size: MIN_PART_SIZE,
+        }
+    }
+
+    // 超过 part_size 的上传会走 multipart
+    pub fn part_size(mut self, size: usize) -> Self {
+        self.part_size = size.max(1);
+        self
+    }
+
+    pub async fn put(&self, key: &str, data: Vec<u8>) -> Result<(), StorageError> {
+        self.send(Method::PUT, key, Vec::new(), data).await?;
+        Ok(())
+    }
+
+    pub async fn get(&self, key: &str) -> Result<Vec<u8>, StorageError> {
+        let response = self.send(Method::GET, key, Vec::new(), Vec::new()).await?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    pub async fn delete(&self, key: &str) -> Result<(), StorageError> {
+        self.send(Method::DELETE, key, Vec::new(), Vec::new())
+            .await?;
+        Ok(())
+    }
+
+    pub async fn list(&self, prefix: &str) -> Result<Vec<ObjectInfo>, StorageError> {
+        let mut objects = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+            let mut query = vec![
+                ("list-type".to_string(), "2".to_string()),
+                ("prefix".to_string(), prefix.to_string()),
+            ];
+            if let Some(t) = token.take() {
+                query.push(("continuation-token".to_string(), t));
+            }
+            let body = self
+                .send(Method::GET, "", query, Vec::new())
+                .await?
+                .text()
+                .await?;
+
+            for block in xml_blocks(&body, "Contents") {
+                let key = xml_tag(block, "Key")
+                    .ok_or_else(|| StorageError::InvalidResponse("Contents 缺少 Key".into()))?;
+                objects.push(ObjectInfo {
+                    key: xml_unescape(key),
+                    size: xml_tag(block, "Size")
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or(0),
+                    etag: xml_unescape(xml_tag(block, "ETag").unwrap_or_default()),
+                });
+            }
+
+            match xml_tag(&body, "IsTruncated") {
+                Some("true") => {
+                    token = xml_tag(&body, "NextContinuationToken").map(xml_unescape);
+                    if token.is_none() {
+                        return Err(StorageError::InvalidResponse(
+                            "缺少 NextContinuationToken".into(),
+                        ));
+                    }
+                }
+                _ => return Ok(objects),
+            }
+        }
+    }
+
+    // 流式上传，progress 收到累计已上传的字节数
+    pub async fn upload<R, F>(
+        &self,
+        key: &str,
+        mut reader: R,
+        mut progress: F,
+    ) -> Result<u64, StorageError>
+    where
+        R: AsyncRead + Unpin,
+        F: FnMut(u64),
+    {
+        let mut buffer = vec![0u8; self.part_size];
+        let n = read_full(&mut reader, &mut buffer).await?;
+        if n < self.part_size {
+            buffer.truncate(n);
+            self.put(key, buffer).await?;
+            progress(n as u64);
+            return Ok(n as u64);
+        }
+
+        let body = self
+            .send(
+                Method::POST,
+                key,
+                vec![("uploads".to_string(), String::new())],
+                Vec::new(),
+            )
+            .await?
+            .text()
+            .await?;
+        let upload_id = xml_tag(&body, "UploadId")
+            .map(xml_unescape)
+            .ok_or_else(|| StorageError::InvalidResponse("缺少 UploadId".into()))?;
+
+        let result = self
+            .upload_parts(key, &upload_id, &mut reader, buffer, &mut progress)
+            .await;
+        if result.is_err() {
+            // 失败时尽量清理已上传的分片，清理失败不覆盖原始错误
+            let query = vec![("uploadId".to_string(), upload_id.clone())];
+            let _ = self.send(Method::DELETE, key, query, Vec::new()).await;
+        }
+        result
+    }
+
+    async fn upload_parts<R, F>(
+        &self,
+        key: &str,
+        upload_id: &str,
+        reader: &mut R,
+        first: Vec<u8>,
+        progress: &mut F,
+    ) -> Result<u64, StorageError>
+    where
+        R: AsyncRead + Unpin,
+        F: FnMut(u64),
+    {
+        let mut etags = Vec::new();
+        let mut total = 0u64;
+        let mut chunk = first;
+        loop {
+            let part_number = etags.len() + 1;
+            let len = chunk.len() as u64;
+            let query = vec![
+                ("partNumber".to_string(), part_number.to_string()),
+                ("uploadId".to_string(), upload_id.to_string()),
+            ];
+            let response = self.send(Method::PUT, key, query, chunk).await?;
+            let etag = response
+                .headers()
+                .get("etag")
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| StorageError::InvalidResponse("分片响应缺少 ETag".into()))?
+                .to_string();
+            etags.push(etag);
+            total += len;
+            progress(total);
+
+            let mut next = vec![0u8; self.part_size];
+            let n = read_full(reader, &mut next).await?;
+            if n == 0 {
+                break;
+            }
+            next.truncate(n);
+            chunk = next;
+        }
+
+        let mut xml = String::from("<CompleteMultipartUpload>");
+        for (i, etag) in etags.iter().enumerate() {
+            xml.push_str(&format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                i + 1,
+                etag
+            ));
+        }
+        xml.push_str("</CompleteMultipartUpload>");
+        let query = vec![("uploadId".to_string(), upload_id.to_string())];
+        self.send(Method::POST, key, query, xml.into_bytes())
+            .await?;
+        Ok(total)
+    }
+
+    // 流式下载到 writer，不在内存中缓存整个对象
+    pub async fn download<W, F>(
+        &self,
+        key: &str,
+        mut writer: W,
+        mut progress: F,
+    ) -> Result<u64, StorageError>
+    where
+        W: AsyncWrite + Unpin,
+        F: FnMut(u64),
+    {
+        let mut response = self.send(Method::GET, key, Vec::new(), Vec::new()).await?;
+        let mut total = 0u64;
+        while let Some(chunk) = response.chunk().await? {
+            writer.write_all(&chunk).await?;
+            total += chunk.len() as u64;
+            progress(total);
+        }
+        writer.flush().await?;
+        Ok(total)
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        key: &str,
+        query: Vec<(String, String)>,
+        body: Vec<u8>,
+    ) -> Result<reqwest::Response, StorageError> {
+        let base = Url::parse(&self.config.endpoint)
+            .map_err(|e| StorageError::InvalidResponse(format!("endpoint 无效: {}", e)))?;
+        let host = match (base.host_str(), base.port()) {
+            (Some(h), Some(p)) => format!("{}:{}", h, p),
+            (Some(h), None) => h.to_string(),
+            _ => return Err(StorageError::InvalidResponse("endpoint 缺少 host".into())),
+        };
+
+        let path = if key.is_empty() {
+            format!("/{}", self.config.bucket)
+        } else {
+            format!("/{}/{}", self.config.bucket, uri_encode(key, false))
+        };
+        let query: Vec<(String, String)> = query
+            .into_iter()
+            .map(|(k, v)| (uri_encode(&k, true), uri_encode(&v, true)))
+            .collect();
+
+        let payload_hash = sha256_hex(&body);
+        // 请求头和签名必须使用同一个时间，跨秒时分别取值会导致签名不匹配
+        let now = SystemTime::now();
+        let (stamp, _) = sigv4::amz_date(now);
+        let headers = vec![
+            ("host".to_string(), host),
+            ("x-amz-content-sha256".to_string(), payload_hash.clone()),
+            ("x-amz-date".to_string(), stamp.clone()),
+        ];
+        let authorization = sigv4::authorization(
+            &self.config.credentials,
+            &self.config.region,
+            "s3",
+            &SigningRequest {
+                method: method.as_str(),
+                path: &path,
+                query: &query,
+                headers: &headers,
+                payload_hash: &payload_hash,
+            },
+            now,
+        );
+
+        let mut url = format!("{}{}", self.config.endpoint.trim_end_matches('/'), path);
+        if !query.is_empty() {
+            let qs = query
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect::<Vec<_>>()
+                .join("&");
+            url = format!("{}?{}", url, qs);
+        }
+
+        let request = self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", stamp)
+            .header("authorization", authorization)
+            .body(body);
+        let response = context::inject(request).send().await?;
+
+        match response.status() {
+            s if s.is_success() => Ok(response),
+            StatusCode::NOT_FOUND => Err(StorageError::NotFound(key.to_string())),
+            s => Err(StorageError::Status {
+                status: s.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+async fn read_full<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        let n = reader.read(&mut buf[filled..]).await?;
+        if n == 0 {
+            break;
+        }
+        filled += n;
+    }
+    Ok(filled)
+}
+
+// S3 的响应结构很简单，这里只做按标签名的提取
+fn xml_tag<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = xml[start..].find(&close)? + start;
+    Some(&xml[start..end])
+}
+
+fn xml_blocks<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let mut blocks = Vec::new();
+    let mut rest = xml;
+    while let Some(block) = xml_tag(rest, tag) {
+        blocks.push(block);
+        let consumed = block.as_ptr() as usize - rest.as_ptr() as usize + block.len();
+        rest = &rest[consumed..];
+    }
+    blocks
+}
+
+fn xml_unescape(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
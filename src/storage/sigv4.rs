@@ -0,0 +1,146 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+type HmacSha256 = Hmac<Sha256>;
+
+// 空 body 的 SHA-256
+pub const EMPTY_PAYLOAD_SHA256: &str =
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+#[derive(Debug, Clone)]
+pub struct Credentials {
+    pub access_key: String,
+    pub secret_key: String,
+}
+
+// 一次待签名的请求，path 和 query 都需要是已经编码好的形式
+#[derive(Debug)]
+pub struct SigningRequest<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub query: &'a [(String, String)],
+    pub headers: &'a [(String, String)],
+    pub payload_hash: &'a str,
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+// 按 AWS 规则编码，保留 unreserved 字符，encode_slash 为 false 时保留 '/'
+pub fn uri_encode(input: &str, encode_slash: bool) -> String {
+    let mut out = String::with_capacity(input.len());
+    for b in input.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                out.push(b as char)
+            }
+            b'/' if !encode_slash => out.push('/'),
+            _ => out.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    out
+}
+
+// 返回 (yyyyMMdd'T'HHmmss'Z', yyyyMMdd)
+pub fn amz_date(time: SystemTime) -> (String, String) {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (y, m, d) = civil_from_days((secs / 86_400) as i64);
+    let rem = secs % 86_400;
+    let date = format!("{:04}{:02}{:02}", y, m, d);
+    let stamp = format!(
+        "{}T{:02}{:02}{:02}Z",
+        date,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    );
+    (stamp, date)
+}
+
+// Howard Hinnant 的 days -> civil 算法
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
+}
+
+fn hmac(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC 接受任意长度的密钥");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+pub fn canonical_request(req: &SigningRequest) -> (String, String) {
+    let mut query: Vec<_> = req.query.to_vec();
+    query.sort();
+    let query = query
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let mut headers: Vec<(String, String)> = req
+        .headers
+        .iter()
+        .map(|(k, v)| (k.to_lowercase(), v.trim().to_string()))
+        .collect();
+    headers.sort();
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(k, v)| format!("{}:{}\n", k, v))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(k, _)| k.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        req.method, req.path, query, canonical_headers, signed_headers, req.payload_hash
+    );
+    (canonical, signed_headers)
+}
+
+// 计算 Authorization 头，headers 中必须已经包含 host / x-amz-date / x-amz-content-sha256
+pub fn authorization(
+    creds: &Credentials,
+    region: &str,
+    service: &str,
+    req: &SigningRequest,
+    time: SystemTime,
+) -> String {
+    let (stamp, date) = amz_date(time);
+    let (canonical, signed_headers) = canonical_request(req);
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        stamp,
+        scope,
+        sha256_hex(canonical.as_bytes())
+    );
+
+    let k_date = hmac(format!("AWS4{}", creds.secret_key).as_bytes(), &date);
+    let k_region = hmac(&k_date, region);
+    let k_service = hmac(&k_region, service);
+    let k_signing = hmac(&k_service, "aws4_request");
+    let signature = hex::encode(hmac(&k_signing, &string_to_sign));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        creds.access_key, scope, signed_headers, signature
+    )
+}
@@ -0,0 +1,161 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures_util::stream::{self, Stream, StreamExt};
+
+use crate::clock::SharedClock;
+use crate::logs;
+use crate::page::{Page, PageRequest};
+use crate::ratelimit::TokenBucket;
+use crate::retry::Backoff;
+
+// futures::Stream 上的常用组合子。分页接口(paginate)、SSE 客户端(EventStream::into_stream)
+// 和 sqlx 的 fetch 结果都是 Stream，可以直接组合:
+//   paginate(..) → rate_limit → parallel_map → chunked_timeout → 批量写入
+
+// 每凑够 max 个元素或第一个元素等待了 timeout 就输出一批；上游结束时输出剩余的元素
+pub fn chunked_timeout<S: Stream>(
+    stream: S,
+    max: usize,
+    timeout: Duration,
+) -> impl Stream<Item = Vec<S::Item>> {
+    let max = max.max(1);
+    stream::unfold(
+        (Box::pin(stream), false),
+        move |(mut stream, done)| async move {
+            if done {
+                return None;
+            }
+            // 第一个元素不限时等待，不输出空批次
+            let mut chunk = vec![stream.next().await?];
+            let deadline = tokio::time::Instant::now() + timeout;
+            while chunk.len() < max {
+                match tokio::time::timeout_at(deadline, stream.next()).await {
+                    Ok(Some(item)) => chunk.push(item),
+                    Ok(None) => return Some((chunk, (stream, true))),
+                    Err(_) => break,
+                }
+            }
+            Some((chunk, (stream, false)))
+        },
+    )
+}
+
+// 每个元素先从令牌桶取得令牌再输出；多个流共享同一个桶时共同受限
+pub fn rate_limit<S: Stream>(stream: S, bucket: Arc<TokenBucket>) -> impl Stream<Item = S::Item> {
+    stream.then(move |item| {
+        let bucket = bucket.clone();
+        async move {
+            bucket.acquire().await;
+            item
+        }
+    })
+}
+
+// 流出错时按退避策略调用 make 重新建立流，成功收到元素后失败次数清零。
+// 连续失败 max_attempts 次后输出最后一个错误并结束。
+// 从哪里继续由 make 决定，例如捕获最后处理的 id 或游标
+pub fn retry_stream<F, S, T, E>(
+    clock: SharedClock,
+    backoff: Backoff,
+    make: F,
+) -> impl Stream<Item = Result<T, E>>
+where
+    F: FnMut() -> S,
+    S: Stream<Item = Result<T, E>>,
+    E: std::fmt::Display,
+{
+    struct State<F, S> {
+        make: F,
+        current: Option<std::pin::Pin<Box<S>>>,
+        failures: usize,
+        finished: bool,
+    }
+
+    let state = State {
+        make,
+        current: None,
+        failures: 0,
+        finished: false,
+    };
+    stream::unfold(state, move |mut state| {
+        let clock = clock.clone();
+        let backoff = backoff.clone();
+        async move {
+            loop {
+                if state.finished {
+                    return None;
+                }
+                let make = &mut state.make;
+                let current = state.current.get_or_insert_with(|| Box::pin(make()));
+                match current.next().await {
+                    Some(Ok(item)) => {
+                        state.failures = 0;
+                        return Some((Ok(item), state));
+                    }
+                    None => return None,
+                    Some(Err(e)) => {
+                        state.current = None;
+                        state.failures += 1;
+                        if state.failures >= backoff.max_attempts {
+                            state.finished = true;
+                            return Some((Err(e), state));
+                        }
+                        logs::warn("streamx", &format!("流出错，准备重新建立: {}", e));
+                        clock.sleep(backoff.delay(state.failures)).await;
+                    }
+                }
+            }
+        }
+    })
+}
+
+// 最多 n 个 f 同时执行，按输入顺序输出
+pub fn parallel_map<S, F, Fut>(stream: S, n: usize, f: F) -> impl Stream<Item = Fut::Output>
+where
+    S: Stream,
+    F: FnMut(S::Item) -> Fut,
+    Fut: Future,
+{
+    stream.map(f).buffered(n.max(1))
+}
+
+// 按完成顺序输出，慢的元素不会挡住后面的
+pub fn parallel_map_unordered<S, F, Fut>(
+    stream: S,
+    n: usize,
+    f: F,
+) -> impl Stream<Item = Fut::Output>
+where
+    S: Stream,
+    F: FnMut(S::Item) -> Fut,
+    Fut: Future,
+{
+    stream.map(f).buffer_unordered(n.max(1))
+}
+
+// 逐页请求，把分页接口展开成元素流；出错时输出错误并结束
+pub fn paginate<T, E, F, Fut>(per_page: u32, fetch: F) -> impl Stream<Item = Result<T, E>>
+where
+    F: FnMut(PageRequest) -> Fut,
+    Fut: Future<Output = Result<Page<T>, E>>,
+{
+    let pages = stream::unfold(
+        (fetch, Some(PageRequest::new(1, per_page))),
+        |(mut fetch, next)| async move {
+            let request = next?;
+            match fetch(request).await {
+                Ok(page) => {
+                    let next = page
+                        .has_next()
+                        .then(|| PageRequest::new(request.page + 1, request.per_page));
+                    let items: Vec<Result<T, E>> = page.items.into_iter().map(Ok).collect();
+                    Some((stream::iter(items), (fetch, next)))
+                }
+                Err(e) => Some((stream::iter(vec![Err(e)]), (fetch, None))),
+            }
+        },
+    );
+    pages.flatten()
+}
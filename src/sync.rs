@@ -0,0 +1,380 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::checksum::Sha256;
+use crate::pipeline::Pipeline;
+use crate::storage::{ObjectStorage, StorageError};
+
+// 目录同步: 让目标(本地目录或对象存储的一个前缀)与源目录内容一致。
+// 比较大小和修改时间(或内容哈希)找出需要复制的文件，复制时计算 SHA-256 并报告进度，
+// 可选删除目标中多余的文件。结果汇总成可以序列化为 JSON 的 SyncSummary。
+
+// 复制中的临时文件后缀，遍历时忽略
+const TMP_SUFFIX: &str = ".sync-tmp";
+
+#[derive(Error, Debug)]
+pub enum SyncError {
+    #[error("源路径不是目录: {0}")]
+    NotADirectory(PathBuf),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Compare {
+    // 大小和修改时间都相同视为未变化；对象存储没有修改时间，只比较大小
+    #[default]
+    SizeAndMtime,
+    // 大小相同时再比较内容哈希
+    Checksum,
+}
+
+// 参数依次是相对路径、已复制字节数、文件总大小
+pub type ProgressFn = Arc<dyn Fn(&str, u64, u64) + Send + Sync>;
+
+#[derive(Clone, Default)]
+pub struct SyncOptions {
+    pub delete: bool,
+    pub compare: Compare,
+    pub dry_run: bool,
+    progress: Option<ProgressFn>,
+}
+
+impl SyncOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 删除目标中源目录没有的文件
+    pub fn delete(mut self, delete: bool) -> Self {
+        self.delete = delete;
+        self
+    }
+
+    pub fn compare(mut self, compare: Compare) -> Self {
+        self.compare = compare;
+        self
+    }
+
+    // 只计算差异，不修改目标
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn on_progress<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, u64, u64) + Send + Sync + 'static,
+    {
+        self.progress = Some(Arc::new(f));
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Create,
+    Update,
+    Delete,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SyncEntry {
+    pub path: String,
+    pub action: Action,
+    pub bytes: u64,
+    // 复制的文件内容哈希，删除和 dry_run 时为空
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct SyncSummary {
+    pub entries: Vec<SyncEntry>,
+    pub unchanged: usize,
+    pub bytes_copied: u64,
+    pub dry_run: bool,
+}
+
+impl SyncSummary {
+    pub fn paths(&self, action: Action) -> Vec<&str> {
+        self.entries
+            .iter()
+            .filter(|e| e.action == action)
+            .map(|e| e.path.as_str())
+            .collect()
+    }
+
+    pub fn is_noop(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("SyncSummary 总是可以序列化")
+    }
+}
+
+// 本地目录到本地目录
+pub fn run(src: &Path, dst: &Path, options: &SyncOptions) -> Result<SyncSummary, SyncError> {
+    let sources = walk(src)?;
+    let targets = if dst.is_dir() {
+        walk(dst)?
+    } else {
+        BTreeMap::new()
+    };
+    let mut summary = SyncSummary {
+        dry_run: options.dry_run,
+        ..Default::default()
+    };
+
+    for (name, path) in &sources {
+        let meta = fs::metadata(path)?;
+        let target = dst.join(name);
+        let action = match targets.get(name) {
+            None => Action::Create,
+            Some(existing) if same_local(path, &meta, existing, options.compare)? => {
+                summary.unchanged += 1;
+                continue;
+            }
+            Some(_) => Action::Update,
+        };
+        let sha256 = if options.dry_run {
+            None
+        } else {
+            Some(copy_file(path, &target, name, meta.len(), options)?)
+        };
+        summary.bytes_copied += meta.len();
+        summary.entries.push(SyncEntry {
+            path: name.clone(),
+            action,
+            bytes: meta.len(),
+            sha256,
+        });
+    }
+
+    if options.delete {
+        for (name, path) in targets.iter().filter(|(n, _)| !sources.contains_key(*n)) {
+            let bytes = fs::metadata(path)?.len();
+            if !options.dry_run {
+                fs::remove_file(path)?;
+            }
+            summary.entries.push(SyncEntry {
+                path: name.clone(),
+                action: Action::Delete,
+                bytes,
+                sha256: None,
+            });
+        }
+    }
+    Ok(summary)
+}
+
+// 本地目录到对象存储，文件 a/b.txt 对应 key <prefix>/a/b.txt
+pub async fn run_remote(
+    src: &Path,
+    storage: &ObjectStorage,
+    prefix: &str,
+    options: &SyncOptions,
+) -> Result<SyncSummary, SyncError> {
+    let sources = walk(src)?;
+    let prefix = prefix.trim_end_matches('/');
+    let key_of = |name: &str| {
+        if prefix.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}/{}", prefix, name)
+        }
+    };
+    let list_prefix = if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", prefix)
+    };
+    let remote: BTreeMap<String, u64> = storage
+        .list(&list_prefix)
+        .await?
+        .into_iter()
+        .map(|o| (o.key[list_prefix.len()..].to_string(), o.size))
+        .collect();
+    let mut summary = SyncSummary {
+        dry_run: options.dry_run,
+        ..Default::default()
+    };
+
+    for (name, path) in &sources {
+        let size = fs::metadata(path)?.len();
+        let key = key_of(name);
+        let action = match remote.get(name) {
+            None => Action::Create,
+            Some(&remote_size) if remote_size == size => {
+                let same = match options.compare {
+                    Compare::SizeAndMtime => true,
+                    Compare::Checksum => {
+                        Sha256::digest(&storage.get(&key).await?) == hash_file(path)?
+                    }
+                };
+                if same {
+                    summary.unchanged += 1;
+                    continue;
+                }
+                Action::Update
+            }
+            Some(_) => Action::Update,
+        };
+        let sha256 = if options.dry_run {
+            None
+        } else {
+            let digest = hex::encode(hash_file(path)?);
+            let file = tokio::fs::File::open(path).await?;
+            let progress = options.progress.clone();
+            storage
+                .upload(&key, file, |done| {
+                    if let Some(f) = &progress {
+                        f(name, done, size);
+                    }
+                })
+                .await?;
+            Some(digest)
+        };
+        summary.bytes_copied += size;
+        summary.entries.push(SyncEntry {
+            path: name.clone(),
+            action,
+            bytes: size,
+            sha256,
+        });
+    }
+
+    if options.delete {
+        for (name, &bytes) in remote.iter().filter(|(n, _)| !sources.contains_key(*n)) {
+            if !options.dry_run {
+                storage.delete(&key_of(name)).await?;
+            }
+            summary.entries.push(SyncEntry {
+                path: name.clone(),
+                action: Action::Delete,
+                bytes,
+                sha256: None,
+            });
+        }
+    }
+    Ok(summary)
+}
+
+// 相对路径(用 `/` 分隔) -> 绝对路径，按路径排序
+fn walk(root: &Path) -> Result<BTreeMap<String, PathBuf>, SyncError> {
+    if !root.is_dir() {
+        return Err(SyncError::NotADirectory(root.to_path_buf()));
+    }
+    let mut files = BTreeMap::new();
+    let mut stack = vec![(String::new(), root.to_path_buf())];
+    while let Some((relative, dir)) = stack.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            let name = if relative.is_empty() {
+                file_name
+            } else {
+                format!("{}/{}", relative, file_name)
+            };
+            if entry.file_type()?.is_dir() {
+                stack.push((name, entry.path()));
+            } else if !name.ends_with(TMP_SUFFIX) {
+                files.insert(name, entry.path());
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn same_local(
+    src: &Path,
+    meta: &fs::Metadata,
+    dst: &Path,
+    compare: Compare,
+) -> Result<bool, SyncError> {
+    let existing = fs::metadata(dst)?;
+    if existing.len() != meta.len() {
+        return Ok(false);
+    }
+    Ok(match compare {
+        Compare::SizeAndMtime => existing.modified()? == meta.modified()?,
+        Compare::Checksum => hash_file(src)? == hash_file(dst)?,
+    })
+}
+
+fn hash_file(path: &Path) -> io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(path)?, &mut HashSink(&mut hasher))?;
+    Ok(hasher.finalize())
+}
+
+struct HashSink<'a>(&'a mut Sha256);
+
+impl io::Write for HashSink<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// 先写临时文件再 rename，并把修改时间设成与源文件相同，返回内容哈希
+fn copy_file(
+    src: &Path,
+    dst: &Path,
+    name: &str,
+    size: u64,
+    options: &SyncOptions,
+) -> Result<String, SyncError> {
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut tmp = dst.as_os_str().to_owned();
+    tmp.push(TMP_SUFFIX);
+    let tmp = PathBuf::from(tmp);
+
+    let hasher = Arc::new(Mutex::new(Sha256::new()));
+    let state = hasher.clone();
+    let progress = options.progress.clone();
+    let name_owned = name.to_string();
+    let pipeline = Pipeline::new().stage("checksum", move |chunk| {
+        let mut hasher = state.lock().unwrap();
+        hasher.update(&chunk);
+        if let Some(f) = &progress {
+            f(&name_owned, hasher.len(), size);
+        }
+        Ok(chunk)
+    });
+    let result = File::create(&tmp).and_then(|out| {
+        pipeline.run(File::open(src)?, &out)?;
+        out.sync_all()
+    });
+    if let Err(e) = result {
+        let _ = fs::remove_file(&tmp);
+        return Err(e.into());
+    }
+
+    let modified = fs::metadata(src)?.modified()?;
+    set_modified(&tmp, modified)?;
+    fs::rename(&tmp, dst)?;
+    let digest = hasher.lock().unwrap().clone().finalize_hex();
+    Ok(digest)
+}
+
+fn set_modified(path: &Path, time: SystemTime) -> io::Result<()> {
+    File::options().write(true).open(path)?.set_modified(time)
+}
@@ -0,0 +1,197 @@
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+// 进程资源信息，数据来自 Linux 的 /proc 和 statvfs
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProcessStats {
+    pub rss_bytes: u64,
+    pub open_fds: usize,
+    pub threads: usize,
+}
+
+pub fn process() -> io::Result<ProcessStats> {
+    let status = fs::read_to_string("/proc/self/status")?;
+    let field = |name: &str| -> u64 {
+        status
+            .lines()
+            .find_map(|l| l.strip_prefix(name))
+            .and_then(|v| v.split_whitespace().next())
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0)
+    };
+    Ok(ProcessStats {
+        // VmRSS 的单位是 kB
+        rss_bytes: field("VmRSS:") * 1024,
+        open_fds: fs::read_dir("/proc/self/fd")?.count(),
+        threads: field("Threads:") as usize,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DiskStats {
+    pub path: PathBuf,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+    // 非 root 用户可用的空间
+    pub available_bytes: u64,
+}
+
+impl DiskStats {
+    pub fn available_ratio(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+        self.available_bytes as f64 / self.total_bytes as f64
+    }
+}
+
+pub fn disk(path: impl AsRef<Path>) -> io::Result<DiskStats> {
+    let path = path.as_ref();
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path 是合法的 C 字符串，stat 指向有效内存
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let block = stat.f_frsize as u64;
+    Ok(DiskStats {
+        path: path.to_path_buf(),
+        total_bytes: stat.f_blocks as u64 * block,
+        free_bytes: stat.f_bfree as u64 * block,
+        available_bytes: stat.f_bavail as u64 * block,
+    })
+}
+
+// 进程累计 CPU 时间(用户态+内核态)，单位秒
+fn cpu_seconds() -> io::Result<f64> {
+    let stat = fs::read_to_string("/proc/self/stat")?;
+    // comm 字段可能包含空格，从最后一个 ')' 之后开始解析
+    let rest = stat.rsplit_once(')').map(|(_, r)| r).unwrap_or("");
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let ticks = |i: usize| {
+        fields
+            .get(i)
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0)
+    };
+    // rest 从第 3 个字段 state 开始，utime/stime 是第 14、15 个字段
+    let total = ticks(11) + ticks(12);
+    let hz = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+    Ok(total as f64 / hz)
+}
+
+// 两次采样之间的 CPU 使用率，100 表示占满一个核
+pub struct CpuSampler {
+    last_cpu: f64,
+    last_time: Instant,
+}
+
+impl CpuSampler {
+    pub fn new() -> io::Result<Self> {
+        Ok(CpuSampler {
+            last_cpu: cpu_seconds()?,
+            last_time: Instant::now(),
+        })
+    }
+
+    pub fn sample(&mut self) -> io::Result<f64> {
+        let cpu = cpu_seconds()?;
+        let now = Instant::now();
+        let wall = now.duration_since(self.last_time).as_secs_f64();
+        let usage = if wall > 0.0 {
+            (cpu - self.last_cpu) / wall * 100.0
+        } else {
+            0.0
+        };
+        self.last_cpu = cpu;
+        self.last_time = now;
+        Ok(usage)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Snapshot {
+    pub process: ProcessStats,
+    pub cpu_percent: Option<f64>,
+    pub disks: Vec<DiskStats>,
+}
+
+// 配置文件中的资源阈值，未配置的项不检查
+#[derive(Debug, Clone, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Thresholds {
+    pub max_rss_bytes: Option<u64>,
+    pub max_open_fds: Option<usize>,
+    pub max_cpu_percent: Option<f64>,
+    pub min_disk_available_bytes: Option<u64>,
+    pub min_disk_available_ratio: Option<f64>,
+    pub data_dirs: Vec<PathBuf>,
+}
+
+impl Thresholds {
+    // 采集 data_dirs 所在磁盘以及当前进程的资源信息
+    pub fn snapshot(&self, cpu: Option<&mut CpuSampler>) -> io::Result<Snapshot> {
+        Ok(Snapshot {
+            process: process()?,
+            cpu_percent: cpu.map(|c| c.sample()).transpose()?,
+            disks: self.data_dirs.iter().map(disk).collect::<io::Result<_>>()?,
+        })
+    }
+
+    // 返回超出阈值的项，为空表示健康
+    pub fn check(&self, snapshot: &Snapshot) -> Vec<String> {
+        let mut problems = Vec::new();
+        if let Some(max) = self.max_rss_bytes {
+            if snapshot.process.rss_bytes > max {
+                problems.push(format!(
+                    "内存占用过高: {} > {} 字节",
+                    snapshot.process.rss_bytes, max
+                ));
+            }
+        }
+        if let Some(max) = self.max_open_fds {
+            if snapshot.process.open_fds > max {
+                problems.push(format!(
+                    "打开的文件描述符过多: {} > {}",
+                    snapshot.process.open_fds, max
+                ));
+            }
+        }
+        if let (Some(max), Some(cpu)) = (self.max_cpu_percent, snapshot.cpu_percent) {
+            if cpu > max {
+                problems.push(format!("CPU 使用率过高: {:.1}% > {:.1}%", cpu, max));
+            }
+        }
+        for disk in &snapshot.disks {
+            if let Some(min) = self.min_disk_available_bytes {
+                if disk.available_bytes < min {
+                    problems.push(format!(
+                        "磁盘空间不足: {} 可用 {} < {} 字节",
+                        disk.path.display(),
+                        disk.available_bytes,
+                        min
+                    ));
+                }
+            }
+            if let Some(min) = self.min_disk_available_ratio {
+                if disk.available_ratio() < min {
+                    problems.push(format!(
+                        "磁盘空间不足: {} 可用比例 {:.2} < {:.2}",
+                        disk.path.display(),
+                        disk.available_ratio(),
+                        min
+                    ));
+                }
+            }
+        }
+        problems
+    }
+}
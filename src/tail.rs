@@ -0,0 +1,179 @@
+use std::collections::VecDeque;
+use std::io::{self, SeekFrom};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::time::Instant;
+
+// 类似 tail -F: 持续读取文件新追加的行。读到末尾后定期检查路径，
+// inode 变化(日志轮转，旧文件被改名、新文件被创建)时读完旧文件再切换到新文件，
+// 文件变短(被截断)时从头开始读。文件暂时不存在时一直等待它出现。
+
+pub struct Follow {
+    path: PathBuf,
+    file: Option<File>,
+    inode: u64,
+    pos: u64,
+    // 还没有遇到换行符的部分
+    partial: Vec<u8>,
+    lines: VecDeque<String>,
+    // 调用 follow 时文件的 (inode, 长度)，第一次打开时从这里开始读
+    start: Option<(u64, u64)>,
+    poll_interval: Duration,
+    idle_timeout: Option<Duration>,
+    last_data: Instant,
+    rotations: u64,
+    truncations: u64,
+}
+
+// 默认从文件当前末尾开始，只返回之后追加的行
+pub fn follow(path: impl AsRef<Path>) -> Follow {
+    let start = std::fs::metadata(path.as_ref())
+        .ok()
+        .map(|meta| (meta.ino(), meta.len()));
+    Follow {
+        path: path.as_ref().to_path_buf(),
+        file: None,
+        inode: 0,
+        pos: 0,
+        partial: Vec::new(),
+        lines: VecDeque::new(),
+        start,
+        poll_interval: Duration::from_millis(250),
+        idle_timeout: None,
+        last_data: Instant::now(),
+        rotations: 0,
+        truncations: 0,
+    }
+}
+
+impl Follow {
+    // 先返回文件中已有的内容
+    pub fn from_start(mut self) -> Self {
+        self.start = None;
+        self
+    }
+
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    // 超过这个时间没有新数据时结束；默认一直等待
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn rotations(&self) -> u64 {
+        self.rotations
+    }
+
+    pub fn truncations(&self) -> u64 {
+        self.truncations
+    }
+
+    // 下一行，不含换行符；只有设置了 idle_timeout 时才会返回 None
+    pub async fn next(&mut self) -> Option<io::Result<String>> {
+        loop {
+            if let Some(line) = self.lines.pop_front() {
+                return Some(Ok(line));
+            }
+            match self.poll().await {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => return Some(Err(e)),
+            }
+            if self
+                .idle_timeout
+                .is_some_and(|timeout| self.last_data.elapsed() >= timeout)
+            {
+                self.flush_partial();
+                return self.lines.pop_front().map(Ok);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+
+    // 读取新数据或处理轮转，有进展时返回 true
+    async fn poll(&mut self) -> io::Result<bool> {
+        let Some(file) = self.file.as_mut() else {
+            return self.open().await;
+        };
+        let mut buf = [0u8; 8192];
+        let n = file.read(&mut buf).await?;
+        if n > 0 {
+            self.pos += n as u64;
+            self.push(&buf[..n]);
+            self.last_data = Instant::now();
+            return Ok(true);
+        }
+
+        // 已经读到末尾，检查文件是否被轮转或截断
+        let meta = match tokio::fs::metadata(&self.path).await {
+            Ok(meta) => meta,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        if meta.ino() != self.inode {
+            self.flush_partial();
+            self.file = None;
+            self.rotations += 1;
+            return self.open().await;
+        }
+        if meta.len() < self.pos {
+            file.seek(SeekFrom::Start(0)).await?;
+            self.pos = 0;
+            self.partial.clear();
+            self.truncations += 1;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    async fn open(&mut self) -> io::Result<bool> {
+        let mut file = match File::open(&self.path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let meta = file.metadata().await?;
+        // 开始跟踪之后才创建的文件和轮转后的新文件都从头读
+        self.pos = match self.start.take() {
+            Some((inode, len)) if inode == meta.ino() => file.seek(SeekFrom::Start(len)).await?,
+            _ => 0,
+        };
+        self.inode = meta.ino();
+        self.file = Some(file);
+        Ok(true)
+    }
+
+    fn push(&mut self, data: &[u8]) {
+        self.partial.extend_from_slice(data);
+        while let Some(i) = self.partial.iter().position(|&b| b == b'\n') {
+            let mut line: Vec<u8> = self.partial.drain(..=i).collect();
+            line.pop();
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            self.lines
+                .push_back(String::from_utf8_lossy(&line).into_owned());
+        }
+    }
+
+    // 旧文件结尾没有换行符的内容也作为一行返回
+    fn flush_partial(&mut self) {
+        if !self.partial.is_empty() {
+            let line = String::from_utf8_lossy(&self.partial).into_owned();
+            self.partial.clear();
+            self.lines.push_back(line);
+        }
+    }
+}
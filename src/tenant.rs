@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::sqlite::{SqlitePoolOptions, SqliteRow};
+use sqlx::{FromRow, SqlitePool};
+use thiserror::Error;
+
+use crate::cache::TtlCache;
+use crate::db::DbError;
+use crate::{config, context};
+
+// 多租户: 租户 id 随请求上下文传递，数据库查询、缓存 key 和配置都按当前租户隔离。
+// 共享表通过 tenant_id 列过滤，也可以每个租户一个数据库文件；
+// debug 构建下读到其他租户的数据会直接 panic。
+
+#[derive(Error, Debug)]
+pub enum TenantError {
+    #[error("当前上下文没有租户")]
+    Missing,
+
+    #[error("无效的租户 id: {0}")]
+    Invalid(String),
+
+    #[error("查询没有按租户过滤: {0}")]
+    Unscoped(String),
+
+    #[error(transparent)]
+    Db(#[from] DbError),
+}
+
+impl From<sqlx::Error> for TenantError {
+    fn from(e: sqlx::Error) -> Self {
+        TenantError::Db(e.into())
+    }
+}
+
+// 只允许字母、数字、'-' 和 '_'，可以安全地用在文件名和缓存 key 中
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TenantId(String);
+
+impl TenantId {
+    pub fn new(id: &str) -> Result<Self, TenantError> {
+        let valid = !id.is_empty()
+            && id.len() <= 64
+            && id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(TenantError::Invalid(id.to_string()));
+        }
+        Ok(TenantId(id.to_string()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TenantId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.0)
+    }
+}
+
+impl FromStr for TenantId {
+    type Err = TenantError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for TenantId {
+    type Error = TenantError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::new(&s)
+    }
+}
+
+impl From<TenantId> for String {
+    fn from(id: TenantId) -> Self {
+        id.0
+    }
+}
+
+pub fn current() -> Option<TenantId> {
+    context::current().and_then(|ctx| ctx.tenant_id)
+}
+
+pub fn require() -> Result<TenantId, TenantError> {
+    current().ok_or(TenantError::Missing)
+}
+
+// debug 构建下检查数据行属于当前租户，release 构建下不做任何事
+pub fn guard(row_tenant: &str) {
+    #[cfg(debug_assertions)]
+    if let Some(tenant) = current() {
+        assert_eq!(
+            tenant.as_str(),
+            row_tenant,
+            "跨租户读取: 当前租户 {}，数据属于 {}",
+            tenant,
+            row_tenant
+        );
+    }
+    #[cfg(not(debug_assertions))]
+    let _ = row_tenant;
+}
+
+// 带 tenant_id 列的数据行
+pub trait TenantOwned {
+    fn tenant_id(&self) -> &str;
+}
+
+// sql 中必须包含 tenant_id = ?1 条件(例如 "... WHERE tenant_id = ?1 AND status = ?2")，
+// args 依次绑定到 ?2、?3 ...；返回的每一行都会经过 guard 检查，
+// release 构建下读到其他租户的数据返回 Unscoped 错误
+pub async fn fetch_all<T>(
+    pool: &SqlitePool,
+    sql: &str,
+    args: &[&str],
+) -> Result<Vec<T>, TenantError>
+where
+    T: for<'r> FromRow<'r, SqliteRow> + TenantOwned + Send + Unpin,
+{
+    if !has_tenant_predicate(sql) {
+        return Err(TenantError::Unscoped(sql.to_string()));
+    }
+    let tenant = require()?;
+    let mut query = sqlx::query_as::<_, T>(sql).bind(tenant.as_str());
+    for arg in args {
+        query = query.bind(*arg);
+    }
+    let rows = query.fetch_all(pool).await?;
+    for row in &rows {
+        guard(row.tenant_id());
+        if row.tenant_id() != tenant.as_str() {
+            return Err(TenantError::Unscoped(sql.to_string()));
+        }
+    }
+    Ok(rows)
+}
+
+// tenant_id(可以带表名前缀) = ?1，?10、?11 等不算
+fn has_tenant_predicate(sql: &str) -> bool {
+    static PREDICATE: OnceLock<Regex> = OnceLock::new();
+    PREDICATE
+        .get_or_init(|| Regex::new(r"(?i)\btenant_id\s*=\s*\?1\b").unwrap())
+        .is_match(sql)
+}
+
+// 缓存 key 加上租户前缀
+pub fn namespace(key: &str) -> Result<String, TenantError> {
+    Ok(format!("tenant:{}:{}", require()?, key))
+}
+
+// 按租户隔离的缓存，当前上下文没有租户时读写都会失败
+pub struct TenantCache<K, V> {
+    inner: TtlCache<(TenantId, K), V>,
+}
+
+impl<K: Eq + Hash, V: Clone> TenantCache<K, V> {
+    pub fn new(ttl: Duration) -> Self {
+        TenantCache {
+            inner: TtlCache::new(ttl),
+        }
+    }
+
+    pub fn get(&self, key: K) -> Result<Option<V>, TenantError> {
+        Ok(self.inner.get(&(require()?, key)))
+    }
+
+    pub fn insert(&self, key: K, value: V) -> Result<(), TenantError> {
+        self.inner.insert((require()?, key), value);
+        Ok(())
+    }
+
+    pub fn remove(&self, key: K) -> Result<Option<V>, TenantError> {
+        Ok(self.inner.remove(&(require()?, key)))
+    }
+}
+
+// 每个租户一个 SQLite 数据库，url 模板中的 {tenant} 替换为租户 id，连接池按需创建
+pub struct TenantPools {
+    template: String,
+    pools: Mutex<HashMap<TenantId, SqlitePool>>,
+}
+
+impl TenantPools {
+    // 例如 "sqlite://data/{tenant}.db?mode=rwc"
+    pub fn new(template: &str) -> Self {
+        TenantPools {
+            template: template.to_string(),
+            pools: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn pool_for(&self, tenant: &TenantId) -> Result<SqlitePool, TenantError> {
+        if let Some(pool) = self.pools.lock().unwrap().get(tenant) {
+            return Ok(pool.clone());
+        }
+        let url = self.template.replace("{tenant}", tenant.as_str());
+        let pool = SqlitePoolOptions::new()
+            .connect(&url)
+            .await
+            .map_err(|e| DbError::Connection(e.to_string()))?;
+        let mut pools = self.pools.lock().unwrap();
+        Ok(pools.entry(tenant.clone()).or_insert(pool).clone())
+    }
+
+    // 当前租户的连接池
+    pub async fn pool(&self) -> Result<SqlitePool, TenantError> {
+        self.pool_for(&require()?).await
+    }
+
+    pub fn tenants(&self) -> Vec<TenantId> {
+        let mut tenants: Vec<_> = self.pools.lock().unwrap().keys().cloned().collect();
+        tenants.sort();
+        tenants
+    }
+}
+
+// 租户配置覆盖: overrides 中的对象递归合并到 base 上
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TenantConfig {
+    pub base: Value,
+    #[serde(default)]
+    pub overrides: HashMap<TenantId, Value>,
+}
+
+impl TenantConfig {
+    pub fn new(base: Value) -> Self {
+        TenantConfig {
+            base,
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn override_for(mut self, tenant: TenantId, value: Value) -> Self {
+        self.overrides.insert(tenant, value);
+        self
+    }
+
+    pub fn get_for(&self, tenant: &TenantId) -> Value {
+        let mut merged = self.base.clone();
+        if let Some(overrides) = self.overrides.get(tenant) {
+            config::merge(&mut merged, overrides.clone());
+        }
+        merged
+    }
+
+    // 当前上下文没有租户时返回 base
+    pub fn get(&self) -> Value {
+        match current() {
+            Some(tenant) => self.get_for(&tenant),
+            None => self.base.clone(),
+        }
+    }
+}
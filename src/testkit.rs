@@ -0,0 +1,11 @@
+pub mod env;
+pub mod gen;
+pub mod http;
+pub mod s3;
+pub mod smtp;
+pub mod snapshot;
+pub mod workspace;
+
+pub use env::TestEnv;
+pub use snapshot::assert_snapshot;
+pub use workspace::TestWorkspace;
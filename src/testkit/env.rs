@@ -0,0 +1,105 @@
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+
+use super::s3::MockS3;
+use super::smtp::MockSmtp;
+
+static NEXT_ENV: AtomicU64 = AtomicU64::new(0);
+
+// 集成测试环境: 按需启动 SMTP、对象存储的进程内替身，并提供临时 SQLite 数据库，
+// 所有文件放在一个临时目录中，TestEnv 释放时删除
+#[derive(Default)]
+pub struct TestEnvBuilder {
+    smtp: bool,
+    object_storage: bool,
+    migrations: Vec<String>,
+}
+
+impl TestEnvBuilder {
+    pub fn smtp(mut self) -> Self {
+        self.smtp = true;
+        self
+    }
+
+    pub fn object_storage(mut self) -> Self {
+        self.object_storage = true;
+        self
+    }
+
+    // 每个新建的 SQLite 数据库都会先执行这些语句
+    pub fn migration(mut self, sql: &str) -> Self {
+        self.migrations.push(sql.to_string());
+        self
+    }
+
+    pub async fn build(self) -> std::io::Result<TestEnv> {
+        let dir = std::env::temp_dir().join(format!(
+            "std-app-env-{}-{}",
+            std::process::id(),
+            NEXT_ENV.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&dir)?;
+        let smtp = match self.smtp {
+            true => Some(MockSmtp::start().await?),
+            false => None,
+        };
+        let s3 = match self.object_storage {
+            true => Some(MockS3::start().await?),
+            false => None,
+        };
+        Ok(TestEnv {
+            dir,
+            smtp,
+            s3,
+            migrations: self.migrations,
+            next_db: AtomicU64::new(0),
+        })
+    }
+}
+
+pub struct TestEnv {
+    dir: PathBuf,
+    smtp: Option<MockSmtp>,
+    s3: Option<MockS3>,
+    migrations: Vec<String>,
+    next_db: AtomicU64,
+}
+
+impl TestEnv {
+    pub fn builder() -> TestEnvBuilder {
+        TestEnvBuilder::default()
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn smtp(&self) -> &MockSmtp {
+        self.smtp.as_ref().expect("TestEnv 未启用 smtp()")
+    }
+
+    pub fn s3(&self) -> &MockS3 {
+        self.s3.as_ref().expect("TestEnv 未启用 object_storage()")
+    }
+
+    // 每次调用都返回一个全新的数据库
+    pub async fn sqlite(&self) -> Result<SqlitePool, sqlx::Error> {
+        let n = self.next_db.fetch_add(1, Ordering::Relaxed);
+        let options = SqliteConnectOptions::new()
+            .filename(self.dir.join(format!("db-{}.sqlite", n)))
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await?;
+        for sql in &self.migrations {
+            sqlx::raw_sql(sql).execute(&pool).await?;
+        }
+        Ok(pool)
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
@@ -0,0 +1,520 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+// 属性测试用的生成器，失败后会尝试把输入缩小到最简单的反例。
+// 默认种子固定，可以用 TESTKIT_SEED / TESTKIT_CASES 环境变量覆盖。
+
+pub const DEFAULT_SEED: u64 = 0x5eed_1234_abcd_0001;
+pub const DEFAULT_CASES: usize = 100;
+const MAX_SHRINK_STEPS: usize = 1000;
+
+// splitmix64，足够用于测试数据生成
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // [lo, hi] 闭区间
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        if lo >= hi {
+            return lo;
+        }
+        let span = (hi as i128 - lo as i128 + 1) as u128;
+        (lo as i128 + (self.next_u64() as u128 % span) as i128) as i64
+    }
+
+    pub fn chance(&mut self, percent: u64) -> bool {
+        self.next_u64() % 100 < percent
+    }
+}
+
+pub trait Gen {
+    type Value: Clone + fmt::Debug;
+
+    fn generate(&self, rng: &mut Rng) -> Self::Value;
+
+    // 返回比 value 更"简单"的候选值，越靠前越简单
+    fn shrink(&self, _value: &Self::Value) -> Vec<Self::Value> {
+        Vec::new()
+    }
+
+    // to 把生成的值转换成目标类型，from 用于缩小时还原，
+    // 例如把 (String, u32) 映射成 Person
+    fn map<B, F, R>(self, to: F, from: R) -> Map<Self, F, R>
+    where
+        Self: Sized,
+        B: Clone + fmt::Debug,
+        F: Fn(Self::Value) -> B,
+        R: Fn(&B) -> Self::Value,
+    {
+        Map {
+            inner: self,
+            to,
+            from,
+        }
+    }
+
+    // 只保留满足条件的值，条件太苛刻会导致生成失败
+    fn filter<F>(self, predicate: F) -> Filter<Self, F>
+    where
+        Self: Sized,
+        F: Fn(&Self::Value) -> bool,
+    {
+        Filter {
+            inner: self,
+            predicate,
+        }
+    }
+}
+
+pub struct Ints<T> {
+    lo: i64,
+    hi: i64,
+    _marker: PhantomData<T>,
+}
+
+macro_rules! int_gen {
+    ($name:ident, $ty:ty) => {
+        pub fn $name(lo: $ty, hi: $ty) -> Ints<$ty> {
+            Ints {
+                lo: lo as i64,
+                hi: hi as i64,
+                _marker: PhantomData,
+            }
+        }
+
+        impl Gen for Ints<$ty> {
+            type Value = $ty;
+
+            fn generate(&self, rng: &mut Rng) -> $ty {
+                // 边界值更容易暴露问题，提高它们出现的概率
+                if rng.chance(10) {
+                    return if rng.chance(50) { self.lo } else { self.hi } as $ty;
+                }
+                rng.range(self.lo, self.hi) as $ty
+            }
+
+            fn shrink(&self, value: &$ty) -> Vec<$ty> {
+                let v = *value as i64;
+                // 向 0 收缩，0 不在范围内时向下界收缩
+                let target = 0i64.clamp(self.lo, self.hi);
+                let mut out = Vec::new();
+                let mut candidate = target;
+                while candidate != v {
+                    if !out.contains(&(candidate as $ty)) {
+                        out.push(candidate as $ty);
+                    }
+                    let next = candidate + (v - candidate) / 2;
+                    if next == candidate {
+                        break;
+                    }
+                    candidate = next;
+                }
+                out
+            }
+        }
+    };
+}
+
+int_gen!(u8s, u8);
+int_gen!(u32s, u32);
+int_gen!(i32s, i32);
+int_gen!(i64s, i64);
+int_gen!(usizes, usize);
+
+pub struct Bools;
+
+pub fn bools() -> Bools {
+    Bools
+}
+
+impl Gen for Bools {
+    type Value = bool;
+
+    fn generate(&self, rng: &mut Rng) -> bool {
+        rng.chance(50)
+    }
+
+    fn shrink(&self, value: &bool) -> Vec<bool> {
+        if *value {
+            vec![false]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+pub struct Strings {
+    charset: Vec<char>,
+    min_len: usize,
+    max_len: usize,
+}
+
+pub const ALNUM: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+pub fn strings(charset: &str, min_len: usize, max_len: usize) -> Strings {
+    Strings {
+        charset: charset.chars().collect(),
+        min_len,
+        max_len: max_len.max(min_len),
+    }
+}
+
+pub fn alnum(max_len: usize) -> Strings {
+    strings(ALNUM, 0, max_len)
+}
+
+// 包含空白、标点、多字节字符，用于测试序列化
+pub fn any_text(max_len: usize) -> Strings {
+    strings("abcXYZ019 _-./\\\"'\n\t{}[]:,=#中文é😀", 0, max_len)
+}
+
+impl Gen for Strings {
+    type Value = String;
+
+    fn generate(&self, rng: &mut Rng) -> String {
+        let len = rng.range(self.min_len as i64, self.max_len as i64) as usize;
+        (0..len)
+            .map(|_| self.charset[rng.range(0, self.charset.len() as i64 - 1) as usize])
+            .collect()
+    }
+
+    fn shrink(&self, value: &String) -> Vec<String> {
+        let chars: Vec<char> = value.chars().collect();
+        let mut out = Vec::new();
+        if chars.len() > self.min_len {
+            out.push(chars[..self.min_len].iter().collect());
+            let half = (chars.len() / 2).max(self.min_len);
+            if half > self.min_len {
+                out.push(chars[..half].iter().collect());
+            }
+            for i in 0..chars.len() {
+                let mut shorter = chars.clone();
+                shorter.remove(i);
+                out.push(shorter.into_iter().collect());
+            }
+        }
+        // 把字符换成字符集中的第一个
+        let first = self.charset[0];
+        for i in 0..chars.len() {
+            if chars[i] != first {
+                let mut simpler = chars.clone();
+                simpler[i] = first;
+                out.push(simpler.into_iter().collect());
+            }
+        }
+        out.dedup();
+        out
+    }
+}
+
+pub struct Vecs<G> {
+    inner: G,
+    max_len: usize,
+}
+
+pub fn vecs<G: Gen>(inner: G, max_len: usize) -> Vecs<G> {
+    Vecs { inner, max_len }
+}
+
+impl<G: Gen> Gen for Vecs<G> {
+    type Value = Vec<G::Value>;
+
+    fn generate(&self, rng: &mut Rng) -> Self::Value {
+        let len = rng.range(0, self.max_len as i64) as usize;
+        (0..len).map(|_| self.inner.generate(rng)).collect()
+    }
+
+    fn shrink(&self, value: &Self::Value) -> Vec<Self::Value> {
+        let mut out = Vec::new();
+        if !value.is_empty() {
+            out.push(Vec::new());
+            out.push(value[..value.len() / 2].to_vec());
+            for i in 0..value.len() {
+                let mut shorter = value.clone();
+                shorter.remove(i);
+                out.push(shorter);
+            }
+        }
+        for (i, item) in value.iter().enumerate() {
+            for simpler in self.inner.shrink(item) {
+                let mut copy = value.clone();
+                copy[i] = simpler;
+                out.push(copy);
+            }
+        }
+        out
+    }
+}
+
+pub struct Options<G>(G);
+
+pub fn options<G: Gen>(inner: G) -> Options<G> {
+    Options(inner)
+}
+
+impl<G: Gen> Gen for Options<G> {
+    type Value = Option<G::Value>;
+
+    fn generate(&self, rng: &mut Rng) -> Self::Value {
+        if rng.chance(25) {
+            None
+        } else {
+            Some(self.0.generate(rng))
+        }
+    }
+
+    fn shrink(&self, value: &Self::Value) -> Vec<Self::Value> {
+        match value {
+            None => Vec::new(),
+            Some(v) => std::iter::once(None)
+                .chain(self.0.shrink(v).into_iter().map(Some))
+                .collect(),
+        }
+    }
+}
+
+// 从固定的候选值中选择，缩小时向第一个靠拢
+pub struct OneOf<T>(Vec<T>);
+
+pub fn one_of<T: Clone + fmt::Debug + PartialEq>(values: Vec<T>) -> OneOf<T> {
+    assert!(!values.is_empty(), "one_of 至少需要一个候选值");
+    OneOf(values)
+}
+
+impl<T: Clone + fmt::Debug + PartialEq> Gen for OneOf<T> {
+    type Value = T;
+
+    fn generate(&self, rng: &mut Rng) -> T {
+        self.0[rng.range(0, self.0.len() as i64 - 1) as usize].clone()
+    }
+
+    fn shrink(&self, value: &T) -> Vec<T> {
+        let pos = self.0.iter().position(|v| v == value).unwrap_or(0);
+        self.0[..pos].to_vec()
+    }
+}
+
+pub struct Map<G, F, R> {
+    inner: G,
+    to: F,
+    from: R,
+}
+
+impl<G, B, F, R> Gen for Map<G, F, R>
+where
+    G: Gen,
+    B: Clone + fmt::Debug,
+    F: Fn(G::Value) -> B,
+    R: Fn(&B) -> G::Value,
+{
+    type Value = B;
+
+    fn generate(&self, rng: &mut Rng) -> B {
+        (self.to)(self.inner.generate(rng))
+    }
+
+    fn shrink(&self, value: &B) -> Vec<B> {
+        self.inner
+            .shrink(&(self.from)(value))
+            .into_iter()
+            .map(&self.to)
+            .collect()
+    }
+}
+
+pub struct Filter<G, F> {
+    inner: G,
+    predicate: F,
+}
+
+impl<G, F> Gen for Filter<G, F>
+where
+    G: Gen,
+    F: Fn(&G::Value) -> bool,
+{
+    type Value = G::Value;
+
+    fn generate(&self, rng: &mut Rng) -> G::Value {
+        for _ in 0..1000 {
+            let value = self.inner.generate(rng);
+            if (self.predicate)(&value) {
+                return value;
+            }
+        }
+        panic!("filter 连续 1000 次没有生成满足条件的值");
+    }
+
+    fn shrink(&self, value: &G::Value) -> Vec<G::Value> {
+        self.inner
+            .shrink(value)
+            .into_iter()
+            .filter(|v| (self.predicate)(v))
+            .collect()
+    }
+}
+
+macro_rules! tuple_gen {
+    ($($g:ident $idx:tt),+) => {
+        impl<$($g: Gen),+> Gen for ($($g,)+) {
+            type Value = ($($g::Value,)+);
+
+            fn generate(&self, rng: &mut Rng) -> Self::Value {
+                ($(self.$idx.generate(rng),)+)
+            }
+
+            // 每次只缩小一个分量
+            fn shrink(&self, value: &Self::Value) -> Vec<Self::Value> {
+                let mut out = Vec::new();
+                $(
+                    for simpler in self.$idx.shrink(&value.$idx) {
+                        let mut copy = value.clone();
+                        copy.$idx = simpler;
+                        out.push(copy);
+                    }
+                )+
+                out
+            }
+        }
+    };
+}
+
+tuple_gen!(A 0, B 1);
+tuple_gen!(A 0, B 1, C 2);
+tuple_gen!(A 0, B 1, C 2, D 3);
+tuple_gen!(A 0, B 1, C 2, D 3, E 4);
+
+#[derive(Debug)]
+pub struct Failure<T> {
+    pub seed: u64,
+    pub case: usize,
+    pub original: T,
+    pub shrunk: T,
+    pub message: String,
+    pub shrink_steps: usize,
+}
+
+impl<T: fmt::Debug> fmt::Display for Failure<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "属性测试在第 {} 个用例失败 (TESTKIT_SEED={})\n  原始输入: {:?}\n  最小反例: {:?} (缩小 {} 步)\n  原因: {}",
+            self.case, self.seed, self.original, self.shrunk, self.shrink_steps, self.message
+        )
+    }
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    std::env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+pub fn check<G, F>(gen: &G, prop: F) -> Result<(), Failure<G::Value>>
+where
+    G: Gen,
+    F: Fn(&G::Value) -> Result<(), String>,
+{
+    check_with(
+        gen,
+        env_or("TESTKIT_SEED", DEFAULT_SEED),
+        env_or("TESTKIT_CASES", DEFAULT_CASES),
+        prop,
+    )
+}
+
+pub fn check_with<G, F>(gen: &G, seed: u64, cases: usize, prop: F) -> Result<(), Failure<G::Value>>
+where
+    G: Gen,
+    F: Fn(&G::Value) -> Result<(), String>,
+{
+    let mut rng = Rng::new(seed);
+    for case in 0..cases {
+        let value = gen.generate(&mut rng);
+        let Err(message) = prop(&value) else {
+            continue;
+        };
+
+        // 贪心缩小: 找到第一个仍然失败的更简单的值就继续从它开始
+        let mut shrunk = value.clone();
+        let mut last_message = message;
+        let mut steps = 0;
+        'outer: while steps < MAX_SHRINK_STEPS {
+            for candidate in gen.shrink(&shrunk) {
+                if let Err(m) = prop(&candidate) {
+                    shrunk = candidate;
+                    last_message = m;
+                    steps += 1;
+                    continue 'outer;
+                }
+            }
+            break;
+        }
+        return Err(Failure {
+            seed,
+            case,
+            original: value,
+            shrunk,
+            message: last_message,
+            shrink_steps: steps,
+        });
+    }
+    Ok(())
+}
+
+// 在属性中代替 assert!，失败时返回 Err 以便继续缩小
+#[macro_export]
+macro_rules! prop_assert {
+    ($cond:expr) => {
+        if !$cond {
+            return Err(format!("断言失败: {}", stringify!($cond)));
+        }
+    };
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            return Err(format!($($arg)+));
+        }
+    };
+}
+
+#[macro_export]
+macro_rules! prop_assert_eq {
+    ($left:expr, $right:expr) => {{
+        let (left, right) = (&$left, &$right);
+        if left != right {
+            return Err(format!(
+                "断言失败: {} == {}\n  left: {:?}\n right: {:?}",
+                stringify!($left),
+                stringify!($right),
+                left,
+                right
+            ));
+        }
+    }};
+}
+
+// prop_check!(gen, |value| { prop_assert!(...); })，失败时 panic 并打印最小反例
+#[macro_export]
+macro_rules! prop_check {
+    ($gen:expr, |$value:pat_param| $body:block) => {
+        if let Err(failure) = $crate::testkit::gen::check(&$gen, |$value| {
+            $body;
+            #[allow(unreachable_code)]
+            Ok(())
+        }) {
+            panic!("{}", failure);
+        }
+    };
+}
@@ -0,0 +1,199 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+use crate::storage::{Credentials, ObjectStorage, StorageConfig};
+
+pub const BUCKET: &str = "bucket";
+
+#[derive(Default)]
+struct MockState {
+    objects: HashMap<String, Vec<u8>>,
+    uploads: HashMap<String, BTreeMap<u32, Vec<u8>>>,
+    next_upload: u32,
+}
+
+// 进程内的极简 S3 兼容服务端，只实现 ObjectStorage 用到的接口
+#[derive(Clone)]
+pub struct MockS3 {
+    endpoint: String,
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockS3 {
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let shared = Arc::clone(&state);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = Arc::clone(&shared);
+                tokio::spawn(async move {
+                    let _ = serve(stream, &state).await;
+                });
+            }
+        });
+        Ok(MockS3 {
+            endpoint: format!("http://{}", addr),
+            state,
+        })
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    // 指向该服务端的客户端
+    pub fn storage(&self) -> ObjectStorage {
+        ObjectStorage::new(StorageConfig {
+            endpoint: self.endpoint.clone(),
+            region: "us-east-1".to_string(),
+            bucket: BUCKET.to_string(),
+            credentials: Credentials {
+                access_key: "test".to_string(),
+                secret_key: "secret".to_string(),
+            },
+        })
+    }
+
+    pub fn object(&self, key: &str) -> Option<Vec<u8>> {
+        self.state.lock().unwrap().objects.get(key).cloned()
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<_> = self.state.lock().unwrap().objects.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    // 尚未完成或中止的分片上传数量
+    pub fn pending_uploads(&self) -> usize {
+        self.state.lock().unwrap().uploads.len()
+    }
+}
+
+async fn serve(stream: tokio::net::TcpStream, state: &Mutex<MockState>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).await?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    let mut length = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((k, v)) = header.split_once(':') {
+            if k.eq_ignore_ascii_case("content-length") {
+                length = v.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body = vec![0u8; length];
+    reader.read_exact(&mut body).await?;
+
+    let (status, headers, payload) = handle(state, &method, &target, body);
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n",
+        status,
+        payload.len(),
+        headers
+    )
+    .into_bytes();
+    response.extend_from_slice(&payload);
+    reader.get_mut().write_all(&response).await
+}
+
+fn handle(
+    state: &Mutex<MockState>,
+    method: &str,
+    target: &str,
+    body: Vec<u8>,
+) -> (&'static str, String, Vec<u8>) {
+    let mut state = state.lock().unwrap();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let key = path
+        .trim_start_matches('/')
+        .trim_start_matches(BUCKET)
+        .trim_start_matches('/');
+    let params: HashMap<&str, &str> = query
+        .split('&')
+        .filter(|s| !s.is_empty())
+        .map(|p| p.split_once('=').unwrap_or((p, "")))
+        .collect();
+
+    match method {
+        "GET" if key.is_empty() => {
+            let prefix = params
+                .get("prefix")
+                .map(|p| p.replace("%2F", "/"))
+                .unwrap_or_default();
+            let mut keys: Vec<_> = state
+                .objects
+                .iter()
+                .filter(|(k, _)| k.starts_with(&prefix))
+                .collect();
+            keys.sort();
+            let mut xml = String::from("<ListBucketResult><IsTruncated>false</IsTruncated>");
+            for (k, v) in keys {
+                xml.push_str(&format!(
+                    "<Contents><Key>{}</Key><Size>{}</Size><ETag>&quot;x&quot;</ETag></Contents>",
+                    k,
+                    v.len()
+                ));
+            }
+            xml.push_str("</ListBucketResult>");
+            ("200 OK", String::new(), xml.into_bytes())
+        }
+        "GET" => match state.objects.get(key) {
+            Some(data) => ("200 OK", String::new(), data.clone()),
+            None => ("404 Not Found", String::new(), Vec::new()),
+        },
+        "PUT" if params.contains_key("partNumber") => {
+            let id = params
+                .get("uploadId")
+                .copied()
+                .unwrap_or_default()
+                .to_string();
+            let n: u32 = params["partNumber"].parse().unwrap_or(0);
+            state.uploads.entry(id).or_default().insert(n, body);
+            ("200 OK", format!("ETag: \"part{}\"\r\n", n), Vec::new())
+        }
+        "PUT" => {
+            state.objects.insert(key.to_string(), body);
+            ("200 OK", String::new(), Vec::new())
+        }
+        "POST" if params.contains_key("uploads") => {
+            state.next_upload += 1;
+            let id = format!("u{}", state.next_upload);
+            state.uploads.insert(id.clone(), BTreeMap::new());
+            let xml = format!(
+                "<InitiateMultipartUploadResult><UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+                id
+            );
+            ("200 OK", String::new(), xml.into_bytes())
+        }
+        "POST" => {
+            let id = params.get("uploadId").copied().unwrap_or_default();
+            let parts = state.uploads.remove(id).unwrap_or_default();
+            let data = parts.into_values().flatten().collect();
+            state.objects.insert(key.to_string(), data);
+            ("200 OK", String::new(), Vec::new())
+        }
+        "DELETE" if params.contains_key("uploadId") => {
+            state.uploads.remove(params["uploadId"]);
+            ("204 No Content", String::new(), Vec::new())
+        }
+        "DELETE" => {
+            state.objects.remove(key);
+            ("204 No Content", String::new(), Vec::new())
+        }
+        _ => ("400 Bad Request", String::new(), Vec::new()),
+    }
+}
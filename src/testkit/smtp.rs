@@ -0,0 +1,136 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReceivedMail {
+    pub from: String,
+    pub to: Vec<String>,
+    pub data: String,
+}
+
+impl ReceivedMail {
+    // 按名称查找邮件头，不区分大小写
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.data
+            .lines()
+            .take_while(|l| !l.is_empty())
+            .filter_map(|l| l.split_once(':'))
+            .find(|(k, _)| k.trim().eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.trim())
+    }
+
+    pub fn body(&self) -> &str {
+        self.data
+            .split_once("\r\n\r\n")
+            .or_else(|| self.data.split_once("\n\n"))
+            .map(|(_, body)| body)
+            .unwrap_or("")
+    }
+}
+
+// 进程内的 SMTP 收件器，接受所有邮件并保存在内存中
+#[derive(Clone)]
+pub struct MockSmtp {
+    addr: String,
+    mails: Arc<Mutex<Vec<ReceivedMail>>>,
+}
+
+impl MockSmtp {
+    pub async fn start() -> std::io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?.to_string();
+        let mails = Arc::new(Mutex::new(Vec::new()));
+        let shared = Arc::clone(&mails);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let mails = Arc::clone(&shared);
+                tokio::spawn(async move {
+                    let _ = session(stream, &mails).await;
+                });
+            }
+        });
+        Ok(MockSmtp { addr, mails })
+    }
+
+    // host:port
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    pub fn mails(&self) -> Vec<ReceivedMail> {
+        self.mails.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.mails.lock().unwrap().clear();
+    }
+}
+
+async fn session(stream: TcpStream, mails: &Mutex<Vec<ReceivedMail>>) -> std::io::Result<()> {
+    let mut stream = BufReader::new(stream);
+    stream.get_mut().write_all(b"220 mock ESMTP\r\n").await?;
+
+    let mut from = String::new();
+    let mut to = Vec::new();
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if stream.read_line(&mut line).await? == 0 {
+            return Ok(());
+        }
+        let command = line.trim_end();
+        let upper = command.to_ascii_uppercase();
+        let reply: &[u8] = if upper.starts_with("EHLO") || upper.starts_with("HELO") {
+            b"250 mock\r\n"
+        } else if upper.starts_with("MAIL FROM:") {
+            from = address(&command[10..]);
+            to.clear();
+            b"250 OK\r\n"
+        } else if upper.starts_with("RCPT TO:") {
+            to.push(address(&command[8..]));
+            b"250 OK\r\n"
+        } else if upper == "DATA" {
+            stream
+                .get_mut()
+                .write_all(b"354 End data with <CR><LF>.<CR><LF>\r\n")
+                .await?;
+            let mut data = String::new();
+            loop {
+                line.clear();
+                if stream.read_line(&mut line).await? == 0 {
+                    return Ok(());
+                }
+                if line.trim_end() == "." {
+                    break;
+                }
+                // 去掉 dot-stuffing
+                data.push_str(line.strip_prefix('.').unwrap_or(&line));
+            }
+            mails.lock().unwrap().push(ReceivedMail {
+                from: from.clone(),
+                to: std::mem::take(&mut to),
+                data,
+            });
+            b"250 OK queued\r\n"
+        } else if upper == "QUIT" {
+            stream.get_mut().write_all(b"221 Bye\r\n").await?;
+            return Ok(());
+        } else if upper == "RSET" || upper == "NOOP" {
+            b"250 OK\r\n"
+        } else {
+            b"502 Command not implemented\r\n"
+        };
+        stream.get_mut().write_all(reply).await?;
+    }
+}
+
+fn address(s: &str) -> String {
+    s.trim()
+        .trim_start_matches('<')
+        .split('>')
+        .next()
+        .unwrap_or("")
+        .to_string()
+}
@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::diff;
+
+// 设置为 1 时用当前输出覆盖快照文件
+pub const UPDATE_ENV: &str = "UPDATE_SNAPSHOTS";
+
+#[derive(Error, Debug)]
+pub enum SnapshotError {
+    #[error("快照不匹配: {name}\n{diff}\n设置 {UPDATE_ENV}=1 以更新快照")]
+    Mismatch { name: String, diff: String },
+
+    #[error("快照序列化失败: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+pub struct Snapshots {
+    dir: PathBuf,
+    update: bool,
+}
+
+impl Snapshots {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Snapshots {
+            dir: dir.into(),
+            update: std::env::var(UPDATE_ENV).is_ok_and(|v| v == "1"),
+        }
+    }
+
+    pub fn update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    pub fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", name))
+    }
+
+    // 快照不存在时直接写入；存在时比较，不一致返回可读的差异
+    pub fn check<T: Serialize + ?Sized>(&self, name: &str, value: &T) -> Result<(), SnapshotError> {
+        let actual = serde_json::to_value(value)?;
+        let path = self.path(name);
+        if !self.update && path.exists() {
+            let expected: Value = serde_json::from_slice(&fs::read(&path)?)?;
+            let changes = diff::compare(&expected, &actual);
+            if changes.is_empty() {
+                return Ok(());
+            }
+            return Err(SnapshotError::Mismatch {
+                name: name.to_string(),
+                diff: diff::render(&changes),
+            });
+        }
+        write(&path, &actual)
+    }
+}
+
+fn write(path: &Path, value: &Value) -> Result<(), SnapshotError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut text = serde_json::to_string_pretty(value)?;
+    text.push('\n');
+    fs::write(path, text)?;
+    Ok(())
+}
+
+// 默认的快照目录 tests/snapshots，相对于 crate 根目录
+pub fn default_dir() -> PathBuf {
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_else(|_| ".".to_string());
+    Path::new(&root).join("tests").join("snapshots")
+}
+
+pub fn assert_snapshot<T: Serialize + ?Sized>(name: &str, value: &T) {
+    if let Err(e) = Snapshots::new(default_dir()).check(name, value) {
+        panic!("{}", e);
+    }
+}
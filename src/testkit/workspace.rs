@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use serde::Serialize;
+
+use crate::serde_any::{self, SerdeAnyError};
+
+static NEXT_WORKSPACE: AtomicU64 = AtomicU64::new(0);
+
+// 环境变量是进程级的，修改环境变量的测试之间需要串行
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+// 每个测试独立的临时目录，释放时删除目录并恢复修改过的环境变量
+pub struct TestWorkspace {
+    root: PathBuf,
+    saved_env: HashMap<String, Option<String>>,
+    env_guard: Option<MutexGuard<'static, ()>>,
+}
+
+impl TestWorkspace {
+    pub fn new() -> io::Result<Self> {
+        let root = std::env::temp_dir().join(format!(
+            "std-app-ws-{}-{}",
+            std::process::id(),
+            NEXT_WORKSPACE.fetch_add(1, Ordering::Relaxed)
+        ));
+        if root.exists() {
+            fs::remove_dir_all(&root)?;
+        }
+        fs::create_dir_all(&root)?;
+        Ok(TestWorkspace {
+            root,
+            saved_env: HashMap::new(),
+            env_guard: None,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn path(&self, name: impl AsRef<Path>) -> PathBuf {
+        self.root.join(name)
+    }
+
+    // 写入文件，自动创建父目录
+    pub fn file(&self, name: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<PathBuf> {
+        let path = self.path(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, contents)?;
+        Ok(path)
+    }
+
+    // 按扩展名选择格式写入配置文件
+    pub fn config<T: Serialize>(&self, name: &str, value: &T) -> Result<PathBuf, SerdeAnyError> {
+        let path = self.path(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        serde_any::to_path(&path, value)?;
+        Ok(path)
+    }
+
+    pub fn read(&self, name: impl AsRef<Path>) -> io::Result<String> {
+        fs::read_to_string(self.path(name))
+    }
+
+    pub fn set_env(&mut self, key: &str, value: &str) {
+        self.save_env(key);
+        std::env::set_var(key, value);
+    }
+
+    pub fn remove_env(&mut self, key: &str) {
+        self.save_env(key);
+        std::env::remove_var(key);
+    }
+
+    // 第一次修改某个变量前记录原值；第一次修改环境变量时获取全局锁
+    fn save_env(&mut self, key: &str) {
+        if self.env_guard.is_none() {
+            self.env_guard = Some(ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner()));
+        }
+        self.saved_env
+            .entry(key.to_string())
+            .or_insert_with(|| std::env::var(key).ok());
+    }
+}
+
+impl Drop for TestWorkspace {
+    fn drop(&mut self) {
+        for (key, value) in self.saved_env.drain() {
+            match value {
+                Some(v) => std::env::set_var(&key, v),
+                None => std::env::remove_var(&key),
+            }
+        }
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
@@ -0,0 +1,105 @@
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+
+use crate::ring::RingBuffer;
+
+// 轻量的 span 记录: span 结束时放入全局收集器，由导出器定期取走。
+// 父子关系通过线程内的 span 栈维护。
+
+pub const COLLECTOR_CAPACITY: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanData {
+    pub trace_id: String,
+    pub span_id: String,
+    pub parent_span_id: Option<String>,
+    pub name: String,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub attributes: Vec<(String, String)>,
+}
+
+thread_local! {
+    static STACK: RefCell<Vec<(String, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+fn collector() -> &'static Mutex<RingBuffer<SpanData>> {
+    static COLLECTOR: OnceLock<Mutex<RingBuffer<SpanData>>> = OnceLock::new();
+    COLLECTOR.get_or_init(|| Mutex::new(RingBuffer::new(COLLECTOR_CAPACITY)))
+}
+
+fn random_u64() -> u64 {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.finish()
+}
+
+pub struct Span {
+    data: SpanData,
+}
+
+// 开始一个 span，当前线程已有 span 时作为其子 span
+pub fn span(name: &str) -> Span {
+    let parent = STACK.with(|s| s.borrow().last().cloned());
+    let (trace_id, parent_span_id) = match parent {
+        Some((trace, span)) => (trace, Some(span)),
+        None => (format!("{:016x}{:016x}", random_u64(), random_u64()), None),
+    };
+    let span_id = format!("{:016x}", random_u64());
+    STACK.with(|s| s.borrow_mut().push((trace_id.clone(), span_id.clone())));
+    let now = SystemTime::now();
+    Span {
+        data: SpanData {
+            trace_id,
+            span_id,
+            parent_span_id,
+            name: name.to_string(),
+            start: now,
+            end: now,
+            attributes: Vec::new(),
+        },
+    }
+}
+
+impl Span {
+    pub fn attr(&mut self, key: &str, value: impl ToString) -> &mut Self {
+        self.data
+            .attributes
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn trace_id(&self) -> &str {
+        &self.data.trace_id
+    }
+
+    pub fn span_id(&self) -> &str {
+        &self.data.span_id
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        STACK.with(|s| {
+            let mut stack = s.borrow_mut();
+            if let Some(pos) = stack.iter().rposition(|(_, id)| *id == self.data.span_id) {
+                stack.remove(pos);
+            }
+        });
+        self.data.end = SystemTime::now();
+        collector().lock().unwrap().push(self.data.clone());
+    }
+}
+
+// 取走所有已结束的 span
+pub fn drain() -> Vec<SpanData> {
+    let mut collector = collector().lock().unwrap();
+    let spans = collector.to_vec();
+    collector.clear();
+    spans
+}
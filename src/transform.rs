@@ -0,0 +1,301 @@
+use std::sync::OnceLock;
+
+use thiserror::Error;
+
+// 字节变换: ASCII 大小写转换、十六进制编解码、分隔符查找。
+// x86_64 上运行时检测 CPU 特性，使用 AVX2/SSSE3/SSE2 一次处理 16~32 字节，
+// 其他平台或不支持时退回逐字节实现；各实现的结果完全一致(scalar 模块可用来对照)。
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Backend {
+    Scalar,
+    Sse2,
+    Avx2,
+}
+
+// 当前 CPU 可用的最好实现，只检测一次
+pub fn backend() -> Backend {
+    static BACKEND: OnceLock<Backend> = OnceLock::new();
+    *BACKEND.get_or_init(|| {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") {
+                return Backend::Avx2;
+            }
+            // SSE2 是 x86_64 的基础指令集
+            Backend::Sse2
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        Backend::Scalar
+    })
+}
+
+#[cfg(target_arch = "x86_64")]
+fn has_ssse3() -> bool {
+    static SSSE3: OnceLock<bool> = OnceLock::new();
+    *SSSE3.get_or_init(|| is_x86_feature_detected!("ssse3"))
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum HexError {
+    #[error("十六进制字符串长度不是偶数: {0}")]
+    OddLength(usize),
+
+    #[error("无效的十六进制字符 {byte:#04x}，位置 {index}")]
+    InvalidChar { index: usize, byte: u8 },
+}
+
+// 逐字节的参考实现
+pub mod scalar {
+    use super::HexError;
+
+    pub fn make_ascii_uppercase(buf: &mut [u8]) {
+        buf.make_ascii_uppercase();
+    }
+
+    pub fn make_ascii_lowercase(buf: &mut [u8]) {
+        buf.make_ascii_lowercase();
+    }
+
+    pub fn hex_encode(input: &[u8]) -> String {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let mut out = String::with_capacity(input.len() * 2);
+        for &b in input {
+            out.push(DIGITS[(b >> 4) as usize] as char);
+            out.push(DIGITS[(b & 0xf) as usize] as char);
+        }
+        out
+    }
+
+    pub fn hex_decode(input: &[u8]) -> Result<Vec<u8>, HexError> {
+        if !input.len().is_multiple_of(2) {
+            return Err(HexError::OddLength(input.len()));
+        }
+        let nibble = |index: usize| {
+            let byte = input[index];
+            match byte {
+                b'0'..=b'9' => Ok(byte - b'0'),
+                b'a'..=b'f' => Ok(byte - b'a' + 10),
+                b'A'..=b'F' => Ok(byte - b'A' + 10),
+                _ => Err(HexError::InvalidChar { index, byte }),
+            }
+        };
+        (0..input.len() / 2)
+            .map(|i| Ok(nibble(2 * i)? << 4 | nibble(2 * i + 1)?))
+            .collect()
+    }
+
+    pub fn find_byte(haystack: &[u8], needle: u8) -> Option<usize> {
+        haystack.iter().position(|&b| b == needle)
+    }
+}
+
+pub fn make_ascii_uppercase(buf: &mut [u8]) {
+    flip_case(buf, b'a');
+}
+
+pub fn make_ascii_lowercase(buf: &mut [u8]) {
+    flip_case(buf, b'A');
+}
+
+// 把 [from, from + 25] 范围内的字节异或 0x20
+fn flip_case(buf: &mut [u8], from: u8) {
+    let done = match backend() {
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { x86::flip_case_avx2(buf, from) },
+        #[cfg(target_arch = "x86_64")]
+        Backend::Sse2 => unsafe { x86::flip_case_sse2(buf, from) },
+        _ => 0,
+    };
+    for b in &mut buf[done..] {
+        if b.wrapping_sub(from) < 26 {
+            *b ^= 0x20;
+        }
+    }
+}
+
+pub fn to_ascii_uppercase(input: &[u8]) -> Vec<u8> {
+    let mut out = input.to_vec();
+    make_ascii_uppercase(&mut out);
+    out
+}
+
+// 小写十六进制
+pub fn hex_encode(input: &[u8]) -> String {
+    #[cfg(target_arch = "x86_64")]
+    if has_ssse3() && input.len() >= 16 {
+        let mut out = vec![0u8; input.len() * 2];
+        let done = unsafe { x86::hex_encode_ssse3(input, &mut out) };
+        out.truncate(done * 2);
+        out.extend_from_slice(scalar::hex_encode(&input[done..]).as_bytes());
+        return String::from_utf8(out).expect("十六进制字符都是 ASCII");
+    }
+    scalar::hex_encode(input)
+}
+
+// 大小写都接受；按 8 个字符一组查表，先整组校验再合并
+pub fn hex_decode(input: impl AsRef<[u8]>) -> Result<Vec<u8>, HexError> {
+    let input = input.as_ref();
+    if !input.len().is_multiple_of(2) {
+        return Err(HexError::OddLength(input.len()));
+    }
+    const INVALID: u8 = 0xff;
+    static TABLE: OnceLock<[u8; 256]> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let mut table = [INVALID; 256];
+        for (i, c) in b"0123456789abcdef".iter().enumerate() {
+            table[*c as usize] = i as u8;
+            table[c.to_ascii_uppercase() as usize] = i as u8;
+        }
+        table
+    });
+
+    let mut out = Vec::with_capacity(input.len() / 2);
+    let mut chunks = input.chunks_exact(8);
+    for chunk in &mut chunks {
+        let n: [u8; 8] = std::array::from_fn(|i| table[chunk[i] as usize]);
+        if n.contains(&INVALID) {
+            break;
+        }
+        out.extend_from_slice(&[
+            n[0] << 4 | n[1],
+            n[2] << 4 | n[3],
+            n[4] << 4 | n[5],
+            n[6] << 4 | n[7],
+        ]);
+    }
+    // 剩余部分(或含有非法字符的那一组)逐字节处理，报告准确的位置
+    let done = out.len() * 2;
+    let rest = scalar::hex_decode(&input[done..]).map_err(|e| match e {
+        HexError::InvalidChar { index, byte } => HexError::InvalidChar {
+            index: index + done,
+            byte,
+        },
+        other => other,
+    })?;
+    out.extend(rest);
+    Ok(out)
+}
+
+pub fn find_byte(haystack: &[u8], needle: u8) -> Option<usize> {
+    match backend() {
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { x86::find_byte_avx2(haystack, needle) },
+        #[cfg(target_arch = "x86_64")]
+        Backend::Sse2 => unsafe { x86::find_byte_sse2(haystack, needle) },
+        _ => scalar::find_byte(haystack, needle),
+    }
+}
+
+// 按分隔符切分，与 slice::split 相同的语义(结尾的分隔符会产生一个空段)
+pub fn split(haystack: &[u8], delimiter: u8) -> Split<'_> {
+    Split {
+        rest: Some(haystack),
+        delimiter,
+    }
+}
+
+pub struct Split<'a> {
+    rest: Option<&'a [u8]>,
+    delimiter: u8,
+}
+
+impl<'a> Iterator for Split<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        let rest = self.rest?;
+        match find_byte(rest, self.delimiter) {
+            Some(i) => {
+                self.rest = Some(&rest[i + 1..]);
+                Some(&rest[..i])
+            }
+            None => {
+                self.rest = None;
+                Some(rest)
+            }
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::arch::x86_64::*;
+
+    // 返回已处理的字节数，剩余不足一个向量的部分由调用方处理
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn flip_case_avx2(buf: &mut [u8], from: u8) -> usize {
+        // 加上 0x80 - from 后，目标范围落在有符号的 [-128, -103]
+        let shift = _mm256_set1_epi8(0x80u8.wrapping_sub(from) as i8);
+        let limit = _mm256_set1_epi8((0x80u8 + 26) as i8);
+        let flip = _mm256_set1_epi8(0x20);
+        let chunks = buf.len() / 32;
+        for i in 0..chunks {
+            let ptr = buf.as_mut_ptr().add(i * 32) as *mut __m256i;
+            let v = _mm256_loadu_si256(ptr);
+            let in_range = _mm256_cmpgt_epi8(limit, _mm256_add_epi8(v, shift));
+            _mm256_storeu_si256(ptr, _mm256_xor_si256(v, _mm256_and_si256(in_range, flip)));
+        }
+        chunks * 32
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn flip_case_sse2(buf: &mut [u8], from: u8) -> usize {
+        let shift = _mm_set1_epi8(0x80u8.wrapping_sub(from) as i8);
+        let limit = _mm_set1_epi8((0x80u8 + 26) as i8);
+        let flip = _mm_set1_epi8(0x20);
+        let chunks = buf.len() / 16;
+        for i in 0..chunks {
+            let ptr = buf.as_mut_ptr().add(i * 16) as *mut __m128i;
+            let v = _mm_loadu_si128(ptr);
+            let in_range = _mm_cmpgt_epi8(limit, _mm_add_epi8(v, shift));
+            _mm_storeu_si128(ptr, _mm_xor_si128(v, _mm_and_si128(in_range, flip)));
+        }
+        chunks * 16
+    }
+
+    // 每 16 个输入字节生成 32 个字符，返回已处理的输入字节数
+    #[target_feature(enable = "ssse3")]
+    pub unsafe fn hex_encode_ssse3(input: &[u8], out: &mut [u8]) -> usize {
+        let digits = _mm_loadu_si128(b"0123456789abcdef".as_ptr() as *const __m128i);
+        let mask = _mm_set1_epi8(0x0f);
+        let chunks = input.len() / 16;
+        for i in 0..chunks {
+            let v = _mm_loadu_si128(input.as_ptr().add(i * 16) as *const __m128i);
+            let hi = _mm_shuffle_epi8(digits, _mm_and_si128(_mm_srli_epi16(v, 4), mask));
+            let lo = _mm_shuffle_epi8(digits, _mm_and_si128(v, mask));
+            let dst = out.as_mut_ptr().add(i * 32) as *mut __m128i;
+            _mm_storeu_si128(dst, _mm_unpacklo_epi8(hi, lo));
+            _mm_storeu_si128(dst.add(1), _mm_unpackhi_epi8(hi, lo));
+        }
+        chunks * 16
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn find_byte_avx2(haystack: &[u8], needle: u8) -> Option<usize> {
+        let target = _mm256_set1_epi8(needle as i8);
+        let chunks = haystack.len() / 32;
+        for i in 0..chunks {
+            let v = _mm256_loadu_si256(haystack.as_ptr().add(i * 32) as *const __m256i);
+            let mask = _mm256_movemask_epi8(_mm256_cmpeq_epi8(v, target)) as u32;
+            if mask != 0 {
+                return Some(i * 32 + mask.trailing_zeros() as usize);
+            }
+        }
+        find_byte_sse2(&haystack[chunks * 32..], needle).map(|i| i + chunks * 32)
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub unsafe fn find_byte_sse2(haystack: &[u8], needle: u8) -> Option<usize> {
+        let target = _mm_set1_epi8(needle as i8);
+        let chunks = haystack.len() / 16;
+        for i in 0..chunks {
+            let v = _mm_loadu_si128(haystack.as_ptr().add(i * 16) as *const __m128i);
+            let mask = _mm_movemask_epi8(_mm_cmpeq_epi8(v, target)) as u32;
+            if mask != 0 {
+                return Some(i * 16 + mask.trailing_zeros() as usize);
+            }
+        }
+        super::scalar::find_byte(&haystack[chunks * 16..], needle).map(|i| i + chunks * 16)
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+
+use crate::clock::{self, SharedClock};
+use crate::db::DbError;
+use crate::logs;
+use crate::metrics::{MetricValue, Registry};
+
+// 时间序列: 采样先写入内存缓冲区，flush 时按分钟和小时汇总(最小、最大、平均)后写入
+// SQLite。同一个时间桶多次 flush 的结果会合并，监控面板和资源监控只读汇总数据。
+
+// 缓冲区超过这个数量时丢弃最旧的采样
+pub const DEFAULT_BUFFER: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Resolution {
+    Minute,
+    Hour,
+}
+
+impl Resolution {
+    pub const ALL: [Resolution; 2] = [Resolution::Minute, Resolution::Hour];
+
+    pub fn width(self) -> Duration {
+        match self {
+            Resolution::Minute => Duration::from_secs(60),
+            Resolution::Hour => Duration::from_secs(3600),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Resolution::Minute => "minute",
+            Resolution::Hour => "hour",
+        }
+    }
+
+    fn bucket(self, at_ms: i64) -> i64 {
+        let width = self.width().as_millis() as i64;
+        at_ms - at_ms.rem_euclid(width)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Rollup {
+    pub series: String,
+    pub resolution: Resolution,
+    // 时间桶起点，Unix 毫秒
+    pub bucket_ms: i64,
+    pub count: i64,
+    pub min: f64,
+    pub max: f64,
+    pub sum: f64,
+}
+
+impl Rollup {
+    pub fn avg(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.sum / self.count as f64
+    }
+
+    fn add(&mut self, value: f64) {
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Sample {
+    series: String,
+    at_ms: i64,
+    value: f64,
+}
+
+fn millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[derive(Clone)]
+pub struct Tsdb {
+    pool: SqlitePool,
+    clock: SharedClock,
+    buffer: Arc<Mutex<VecDeque<Sample>>>,
+    capacity: usize,
+}
+
+impl Tsdb {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self::with_clock(pool, clock::system())
+    }
+
+    pub fn with_clock(pool: SqlitePool, clock: SharedClock) -> Self {
+        Tsdb {
+            pool,
+            clock,
+            buffer: Arc::default(),
+            capacity: DEFAULT_BUFFER,
+        }
+    }
+
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    pub async fn migrate(&self) -> Result<(), DbError> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS tsdb_rollups (
+                series TEXT NOT NULL,
+                resolution TEXT NOT NULL,
+                bucket_ms INTEGER NOT NULL,
+                count INTEGER NOT NULL,
+                min REAL NOT NULL,
+                max REAL NOT NULL,
+                sum REAL NOT NULL,
+                PRIMARY KEY (series, resolution, bucket_ms)
+            )",
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub fn record(&self, series: &str, value: f64) {
+        self.record_at(series, self.clock.system_time(), value);
+    }
+
+    pub fn record_at(&self, series: &str, at: SystemTime, value: f64) {
+        let mut buffer = self.buffer.lock().unwrap();
+        if buffer.len() >= self.capacity {
+            buffer.pop_front();
+        }
+        buffer.push_back(Sample {
+            series: series.to_string(),
+            at_ms: millis(at),
+            value,
+        });
+    }
+
+    // 计数器和仪表盘的当前值各记一个采样，直方图记录平均值
+    pub fn sample_registry(&self, registry: &Registry) {
+        for metric in registry.snapshot() {
+            let value = match metric.value {
+                MetricValue::Counter { value } => value as f64,
+                MetricValue::Gauge { value } => value,
+                MetricValue::Histogram(h) if h.count > 0 => h.sum / h.count as f64,
+                MetricValue::Histogram(_) => continue,
+            };
+            self.record(&metric.name, value);
+        }
+    }
+
+    pub fn buffered(&self) -> usize {
+        self.buffer.lock().unwrap().len()
+    }
+
+    // 汇总缓冲区中的采样并写入数据库，返回写入的采样数；写入失败时采样放回缓冲区
+    pub async fn flush(&self) -> Result<usize, DbError> {
+        let samples = std::mem::take(&mut *self.buffer.lock().unwrap());
+        if samples.is_empty() {
+            return Ok(0);
+        }
+        let mut rollups: HashMap<(String, Resolution, i64), Rollup> = HashMap::new();
+        for sample in &samples {
+            for resolution in Resolution::ALL {
+                let bucket_ms = resolution.bucket(sample.at_ms);
+                rollups
+                    .entry((sample.series.clone(), resolution, bucket_ms))
+                    .or_insert_with(|| Rollup {
+                        series: sample.series.clone(),
+                        resolution,
+                        bucket_ms,
+                        count: 0,
+                        min: f64::INFINITY,
+                        max: f64::NEG_INFINITY,
+                        sum: 0.0,
+                    })
+                    .add(sample.value);
+            }
+        }
+        if let Err(e) = self.write(rollups.values()).await {
+            let mut buffer = self.buffer.lock().unwrap();
+            let newer = std::mem::replace(&mut *buffer, samples);
+            buffer.extend(newer);
+            return Err(e);
+        }
+        Ok(samples.len())
+    }
+
+    async fn write(&self, rollups: impl Iterator<Item = &Rollup>) -> Result<(), DbError> {
+        let mut tx = self.pool.begin().await?;
+        for r in rollups {
+            sqlx::query(
+                "INSERT INTO tsdb_rollups (series, resolution, bucket_ms, count, min, max, sum)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                 ON CONFLICT (series, resolution, bucket_ms) DO UPDATE SET
+                    count = count + excluded.count,
+                    min = MIN(min, excluded.min),
+                    max = MAX(max, excluded.max),
+                    sum = sum + excluded.sum",
+            )
+            .bind(&r.series)
+            .bind(r.resolution.as_str())
+            .bind(r.bucket_ms)
+            .bind(r.count)
+            .bind(r.min)
+            .bind(r.max)
+            .bind(r.sum)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // [from, to) 之间的时间桶，按时间排序
+    pub async fn query(
+        &self,
+        series: &str,
+        resolution: Resolution,
+        from: SystemTime,
+        to: SystemTime,
+    ) -> Result<Vec<Rollup>, DbError> {
+        let rows = sqlx::query(
+            "SELECT bucket_ms, count, min, max, sum FROM tsdb_rollups
+             WHERE series = ?1 AND resolution = ?2 AND bucket_ms >= ?3 AND bucket_ms < ?4
+             ORDER BY bucket_ms",
+        )
+        .bind(series)
+        .bind(resolution.as_str())
+        .bind(resolution.bucket(millis(from)))
+        .bind(millis(to))
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows
+            .iter()
+            .map(|row| Rollup {
+                series: series.to_string(),
+                resolution,
+                bucket_ms: row.get(0),
+                count: row.get(1),
+                min: row.get(2),
+                max: row.get(3),
+                sum: row.get(4),
+            })
+            .collect())
+    }
+
+    pub async fn series(&self) -> Result<Vec<String>, DbError> {
+        Ok(
+            sqlx::query_scalar("SELECT DISTINCT series FROM tsdb_rollups ORDER BY series")
+                .fetch_all(&self.pool)
+                .await?,
+        )
+    }
+
+    // 删除早于 before 的时间桶，返回删除的行数
+    pub async fn prune(&self, resolution: Resolution, before: SystemTime) -> Result<u64, DbError> {
+        let result =
+            sqlx::query("DELETE FROM tsdb_rollups WHERE resolution = ?1 AND bucket_ms < ?2")
+                .bind(resolution.as_str())
+                .bind(millis(before))
+                .execute(&self.pool)
+                .await?;
+        Ok(result.rows_affected())
+    }
+
+    // 后台定期 flush，返回的任务在应用关闭时 abort
+    pub fn spawn_flusher(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let tsdb = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tsdb.clock.sleep(interval).await;
+                if let Err(e) = tsdb.flush().await {
+                    logs::warn("tsdb", &format!("写入汇总数据失败: {}", e));
+                }
+            }
+        })
+    }
+}
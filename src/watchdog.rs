@@ -0,0 +1,196 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::clock::{self, SharedClock};
+use crate::logs;
+use crate::sysinfo;
+
+// 自监控: 注册的组件(调度器、任务 worker、outbox relay 等)需要定期发送心跳，
+// 超时未发送时输出诊断信息、通知监听者，并可选地重启组件。
+
+type Restart = Arc<dyn Fn() + Send + Sync>;
+type Listener = Arc<dyn Fn(&Stall) + Send + Sync>;
+
+struct Component {
+    name: String,
+    timeout: Duration,
+    last_beat: Instant,
+    stalled: bool,
+    restart: Option<Restart>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stall {
+    pub name: String,
+    pub silent_for: Duration,
+    pub restarted: bool,
+}
+
+struct Inner {
+    clock: SharedClock,
+    components: Mutex<Vec<Component>>,
+    listeners: Mutex<Vec<Listener>>,
+}
+
+#[derive(Clone)]
+pub struct Watchdog {
+    inner: Arc<Inner>,
+}
+
+// 组件持有的心跳句柄
+#[derive(Clone)]
+pub struct Heartbeat {
+    inner: Arc<Inner>,
+    index: usize,
+}
+
+impl Heartbeat {
+    pub fn beat(&self) {
+        let now = self.inner.clock.now();
+        let mut components = self.inner.components.lock().unwrap();
+        let component = &mut components[self.index];
+        component.last_beat = now;
+        component.stalled = false;
+    }
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Self::with_clock(clock::system())
+    }
+
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Watchdog {
+            inner: Arc::new(Inner {
+                clock,
+                components: Mutex::new(Vec::new()),
+                listeners: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    pub fn register(&self, name: &str, timeout: Duration) -> Heartbeat {
+        self.add(name, timeout, None)
+    }
+
+    // 超时后调用 restart，并重新开始计时
+    pub fn register_with_restart<F>(&self, name: &str, timeout: Duration, restart: F) -> Heartbeat
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.add(name, timeout, Some(Arc::new(restart)))
+    }
+
+    fn add(&self, name: &str, timeout: Duration, restart: Option<Restart>) -> Heartbeat {
+        let mut components = self.inner.components.lock().unwrap();
+        components.push(Component {
+            name: name.to_string(),
+            timeout,
+            last_beat: self.inner.clock.now(),
+            stalled: false,
+            restart,
+        });
+        Heartbeat {
+            inner: self.inner.clone(),
+            index: components.len() - 1,
+        }
+    }
+
+    pub fn on_stall<F>(&self, listener: F)
+    where
+        F: Fn(&Stall) + Send + Sync + 'static,
+    {
+        self.inner
+            .listeners
+            .lock()
+            .unwrap()
+            .push(Arc::new(listener));
+    }
+
+    // 检查一次，返回本次新发现的停滞组件；同一次停滞只报告一次
+    pub fn check(&self) -> Vec<Stall> {
+        let now = self.inner.clock.now();
+        let mut stalls = Vec::new();
+        let mut restarts = Vec::new();
+        {
+            let mut components = self.inner.components.lock().unwrap();
+            for component in components.iter_mut() {
+                let silent_for = now.saturating_duration_since(component.last_beat);
+                if component.stalled || silent_for < component.timeout {
+                    continue;
+                }
+                let restarted = match &component.restart {
+                    Some(restart) => {
+                        restarts.push(restart.clone());
+                        component.last_beat = now;
+                        true
+                    }
+                    None => {
+                        component.stalled = true;
+                        false
+                    }
+                };
+                stalls.push(Stall {
+                    name: component.name.clone(),
+                    silent_for,
+                    restarted,
+                });
+            }
+        }
+
+        for stall in &stalls {
+            logs::warn(
+                "watchdog",
+                &format!(
+                    "组件 {} 已 {:?} 未发送心跳{}; {}",
+                    stall.name,
+                    stall.silent_for,
+                    if stall.restarted {
+                        "，正在重启"
+                    } else {
+                        ""
+                    },
+                    diagnostics()
+                ),
+            );
+        }
+        for restart in restarts {
+            restart();
+        }
+        let listeners = self.inner.listeners.lock().unwrap().clone();
+        for stall in &stalls {
+            for listener in &listeners {
+                listener(stall);
+            }
+        }
+        stalls
+    }
+
+    // 按固定间隔检查，间隔通过 Clock 等待
+    pub async fn run(self, interval: Duration) {
+        loop {
+            self.inner.clock.sleep(interval).await;
+            self.check();
+        }
+    }
+
+    pub fn spawn(&self, interval: Duration) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(self.clone().run(interval))
+    }
+}
+
+fn diagnostics() -> String {
+    match sysinfo::process() {
+        Ok(p) => format!(
+            "rss={} fds={} threads={}",
+            p.rss_bytes, p.open_fds, p.threads
+        ),
+        Err(e) => format!("无法读取进程信息: {}", e),
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use std_app::actor::{self, Actor, ActorError, Reply};
+
+enum CounterMsg {
+    Add(String, u64),
+    Get(String, Reply<u64>),
+    Crash(Option<Reply<u64>>),
+    Block(Duration),
+}
+
+#[derive(Default)]
+struct Counters {
+    counts: HashMap<String, u64>,
+}
+
+impl Actor for Counters {
+    type Message = CounterMsg;
+
+    fn handle(&mut self, message: CounterMsg) {
+        match message {
+            CounterMsg::Add(key, n) => *self.counts.entry(key).or_default() += n,
+            CounterMsg::Get(key, reply) => reply.send(self.counts.get(&key).copied().unwrap_or(0)),
+            CounterMsg::Crash(_reply) => panic!("故意 panic"),
+            CounterMsg::Block(d) => std::thread::sleep(d),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_actor {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tell_and_ask() {
+        let counters = actor::spawn("counters", Counters::default);
+        counters.tell(CounterMsg::Add("a".into(), 2)).await.unwrap();
+        counters.tell(CounterMsg::Add("a".into(), 3)).await.unwrap();
+        counters.try_tell(CounterMsg::Add("b".into(), 1)).unwrap();
+        assert_eq!(
+            counters.ask(|r| CounterMsg::Get("a".into(), r)).await,
+            Ok(5)
+        );
+        assert_eq!(
+            counters.ask(|r| CounterMsg::Get("b".into(), r)).await,
+            Ok(1)
+        );
+        assert_eq!(counters.stats().processed, 5);
+    }
+
+    #[tokio::test]
+    async fn test_restart_on_panic() {
+        let created = Arc::new(AtomicU32::new(0));
+        let counter = created.clone();
+        let counters = actor::spawn("counters", move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+            Counters::default()
+        });
+        counters.tell(CounterMsg::Add("a".into(), 1)).await.unwrap();
+        // panic 的那条消息没有回复，状态被重新创建
+        let err = counters
+            .ask(|r| CounterMsg::Crash(Some(r)))
+            .await
+            .unwrap_err();
+        assert_eq!(err, ActorError::NoReply("counters".into()));
+        assert_eq!(
+            counters.ask(|r| CounterMsg::Get("a".into(), r)).await,
+            Ok(0)
+        );
+        assert_eq!(created.load(Ordering::Relaxed), 2);
+        assert_eq!(counters.stats().restarts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_stops_after_max_restarts() {
+        let counters = actor::builder("ledger")
+            .max_restarts(1)
+            .spawn(Counters::default);
+        counters.tell(CounterMsg::Crash(None)).await.unwrap();
+        counters.tell(CounterMsg::Crash(None)).await.unwrap();
+        counters.join().await;
+        assert!(counters.stats().stopped);
+        assert_eq!(
+            counters.tell(CounterMsg::Add("a".into(), 1)).await,
+            Err(ActorError::Stopped("ledger".into()))
+        );
+    }
+
+    // Block 会占住执行 actor 的线程
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_mailbox_full_and_timeout() {
+        let counters = actor::builder("slow").mailbox(1).spawn(Counters::default);
+        counters
+            .tell(CounterMsg::Block(Duration::from_millis(100)))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        counters.try_tell(CounterMsg::Add("a".into(), 1)).unwrap();
+        assert_eq!(
+            counters.try_tell(CounterMsg::Add("a".into(), 1)),
+            Err(ActorError::MailboxFull("slow".into()))
+        );
+        let err = counters
+            .ask_timeout(
+                |r| CounterMsg::Get("a".into(), r),
+                Duration::from_millis(10),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ActorError::Timeout { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_stop_calls_stopped() {
+        struct Flush(Arc<AtomicU32>);
+        impl Actor for Flush {
+            type Message = ();
+            fn handle(&mut self, _: ()) {}
+            fn stopped(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let flushed = Arc::new(AtomicU32::new(0));
+        let state = flushed.clone();
+        let flush = actor::spawn("flush", move || Flush(state.clone()));
+        flush.tell(()).await.unwrap();
+        flush.stop();
+        flush.join().await;
+        assert_eq!(flushed.load(Ordering::Relaxed), 1);
+        assert_eq!(
+            flush.tell(()).await,
+            Err(ActorError::Stopped("flush".into()))
+        );
+    }
+}
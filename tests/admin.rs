@@ -0,0 +1,143 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use std_app::admin::shell;
+use std_app::admin::{AdminClient, AdminError, AdminServer, Request};
+use std_app::cache::TtlCache;
+use std_app::testkit::TestWorkspace;
+
+fn server() -> (AdminServer, Arc<TtlCache<String, u32>>, Arc<AtomicBool>) {
+    let cache = Arc::new(TtlCache::new(Duration::from_secs(60)));
+    cache.insert("a".to_string(), 1);
+    cache.insert("b".to_string(), 2);
+    let beta = Arc::new(AtomicBool::new(false));
+    let server = AdminServer::new()
+        .cache("sessions", cache.clone())
+        .flag("beta", beta.clone())
+        .job("reindex", |args| Ok(json!({ "queued": args.len() })));
+    (server, cache, beta)
+}
+
+fn run(server: &AdminServer, line: &str) -> serde_json::Value {
+    let response = server.handle(&Request::parse_line(line).unwrap());
+    assert!(response.ok, "{} 失败: {:?}", line, response.error);
+    response.result
+}
+
+#[cfg(test)]
+mod test_admin_commands {
+    use super::*;
+
+    #[test]
+    fn test_caches() {
+        let (server, cache, _) = server();
+        assert_eq!(
+            run(&server, "caches"),
+            json!([{ "name": "sessions", "len": 2 }])
+        );
+        assert_eq!(
+            run(&server, "cache.clear sessions"),
+            json!({ "cleared": 2 })
+        );
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_flags() {
+        let (server, _, beta) = server();
+        assert_eq!(run(&server, "flags"), json!({ "beta": false }));
+        run(&server, "flag beta on");
+        assert!(beta.load(Ordering::SeqCst));
+        let response = server.handle(&Request::parse_line("flag beta maybe").unwrap());
+        assert!(!response.ok);
+        assert!(beta.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn test_enqueue() {
+        let (server, _, _) = server();
+        assert_eq!(run(&server, "jobs"), json!(["reindex"]));
+        assert_eq!(
+            run(&server, "enqueue reindex users orders"),
+            json!({ "queued": 2 })
+        );
+        let response = server.handle(&Request::parse_line("enqueue missing").unwrap());
+        assert_eq!(response.error.as_deref(), Some("没有名为 missing 的任务"));
+    }
+
+    #[test]
+    fn test_errors() {
+        let (server, _, _) = server();
+        let response = server.handle(&Request::parse_line("cache.clear").unwrap());
+        assert_eq!(response.error.as_deref(), Some("用法: cache.clear <名字>"));
+        let response = server.handle(&Request::parse_line("log db=loud").unwrap());
+        assert!(!response.ok);
+        let response = server.handle(&Request::parse_line("reboot").unwrap());
+        assert!(response.error.unwrap().contains("未知命令 reboot"));
+    }
+
+    #[test]
+    fn test_complete() {
+        let (server, _, _) = server();
+        assert_eq!(server.complete("fl"), vec!["flags", "flag"]);
+        assert_eq!(server.complete("flag "), vec!["beta"]);
+        assert_eq!(server.complete("flag beta o"), vec!["on", "off"]);
+        assert_eq!(server.complete("cache.clear se"), vec!["sessions"]);
+        assert_eq!(server.complete("enqueue "), vec!["reindex"]);
+        assert!(server.complete("caches x").is_empty());
+    }
+
+    #[test]
+    fn test_render() {
+        let (server, _, _) = server();
+        let response = server.handle(&Request::parse_line("jobs").unwrap());
+        assert_eq!(shell::render(&response, false), "reindex");
+        assert_eq!(
+            shell::render(&response, true),
+            r#"{"ok":true,"result":["reindex"]}"#
+        );
+        let response = server.handle(&Request::parse_line("reboot").unwrap());
+        assert!(shell::render(&response, false).starts_with("错误: 未知命令"));
+    }
+}
+
+#[cfg(test)]
+mod test_admin_socket {
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_client_round_trip() {
+        let ws = TestWorkspace::new().unwrap();
+        let (server, _, beta) = server();
+        let handle = server.serve(ws.path("admin.sock")).unwrap();
+        let path = handle.path().to_path_buf();
+
+        let result = tokio::task::spawn_blocking(move || {
+            let mut client = AdminClient::connect(&path).unwrap();
+            let flagged = client.call("flag", &["beta", "on"]).unwrap();
+            let caches = client.call("caches", &[]).unwrap();
+            let err = client.call("flag", &["gamma", "on"]).unwrap_err();
+            (flagged, caches, err)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(result.0["value"], json!(true));
+        assert_eq!(result.1[0]["len"], json!(2));
+        assert!(matches!(result.2, AdminError::Command(ref m) if m == "没有名为 gamma 的开关"));
+        assert!(beta.load(Ordering::SeqCst));
+
+        let path = handle.path().to_path_buf();
+        handle.shutdown();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_connect_without_server() {
+        let ws = TestWorkspace::new().unwrap();
+        let err = AdminClient::connect(ws.path("missing.sock")).err().unwrap();
+        assert!(matches!(err, AdminError::Io(_)));
+    }
+}
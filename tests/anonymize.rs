@@ -0,0 +1,135 @@
+use serde_json::json;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+
+use std_app::anonymize::{AnonymizeConfig, Anonymizer, FakeKind, Strategy};
+use std_app::reports::{Format, Report};
+
+async fn pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::query("CREATE TABLE users (id INTEGER, name TEXT, email TEXT, card TEXT, note TEXT)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool
+}
+
+fn anonymizer() -> Anonymizer {
+    let config: AnonymizeConfig = serde_json::from_value(json!({
+        "salt": "s3",
+        "fields": {
+            "name": {"strategy": "fake", "kind": "name"},
+            "email": {"strategy": "hash"},
+            "card": {"strategy": "mask", "keep_last": 4},
+            "note": {"strategy": "null"}
+        }
+    }))
+    .unwrap();
+    Anonymizer::from_config(&config)
+}
+
+#[cfg(test)]
+mod test_anonymize {
+    use super::*;
+
+    //同样的输入得到同样的输出，关联字段仍然能对上
+    #[test]
+    fn test_strategies() {
+        let a = anonymizer();
+        assert_eq!(
+            a.apply_field("card", "4111111111111111").unwrap(),
+            "************1111"
+        );
+        assert_eq!(a.apply_field("card", "12").unwrap(), "12");
+        assert_eq!(a.apply_field("note", "secret"), None);
+        assert_eq!(a.apply_field("other", "keep"), Some("keep".to_string()));
+
+        let hashed = a.apply_field("EMAIL", "alice@example.com").unwrap();
+        assert_eq!(hashed.len(), 16);
+        assert_eq!(a.apply_field("email", "alice@example.com").unwrap(), hashed);
+        assert_ne!(
+            Anonymizer::new("other")
+                .apply(&Strategy::Hash, "alice@example.com")
+                .unwrap(),
+            hashed
+        );
+
+        let phone = a
+            .apply(
+                &Strategy::Fake {
+                    kind: FakeKind::Phone,
+                },
+                "13800000000",
+            )
+            .unwrap();
+        assert!(phone.starts_with("555-"));
+        let email = a
+            .apply(
+                &Strategy::Fake {
+                    kind: FakeKind::Email,
+                },
+                "x",
+            )
+            .unwrap();
+        assert!(email.ends_with("@example.com"));
+    }
+
+    #[test]
+    fn test_json() {
+        let value = anonymizer()
+            .anonymize(&json!({
+                "id": 1,
+                "name": "Alice",
+                "orders": [{"card": 4111111111111111u64, "note": "gift", "total": 10}]
+            }))
+            .unwrap();
+        assert_eq!(value["id"], 1);
+        assert_ne!(value["name"], "Alice");
+        assert_eq!(value["orders"][0]["card"], "************1111");
+        assert_eq!(value["orders"][0]["note"], serde_json::Value::Null);
+        assert_eq!(value["orders"][0]["total"], 10);
+    }
+
+    //复制数据库和导出报表时匿名化
+    #[tokio::test]
+    async fn test_copy_and_export() {
+        let prod = pool().await;
+        sqlx::query("INSERT INTO users VALUES (1, 'Alice', 'alice@example.com', '4111111111111111', 'vip'), (2, 'Bob', NULL, NULL, NULL)")
+            .execute(&prod)
+            .await
+            .unwrap();
+        let test = pool().await;
+        assert_eq!(
+            anonymizer()
+                .copy_table(&prod, &test, "users")
+                .await
+                .unwrap(),
+            2
+        );
+
+        let (id, name, card, note): (i64, String, String, Option<String>) =
+            sqlx::query_as("SELECT id, name, card, note FROM users WHERE id = 1")
+                .fetch_one(&test)
+                .await
+                .unwrap();
+        assert_eq!(id, 1);
+        assert_ne!(name, "Alice");
+        assert_eq!(card, "************1111");
+        assert_eq!(note, None);
+
+        let mut out = Vec::new();
+        Report::new("SELECT id, name, email FROM users ORDER BY id", Format::Csv)
+            .anonymize(anonymizer())
+            .write_to(&prod, &mut out)
+            .await
+            .unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        assert!(!csv.contains("Alice") && !csv.contains("alice@example.com"));
+        assert!(csv.contains(&name));
+        assert!(csv.ends_with(",\r\n"));
+    }
+}
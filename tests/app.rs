@@ -0,0 +1,98 @@
+use std::sync::{Arc, Mutex};
+
+use std_app::app::{self, Bootstrap, BootstrapError};
+
+type Events = Arc<Mutex<Vec<String>>>;
+
+// 记录启动和关闭顺序的阶段
+fn stage(
+    b: Bootstrap,
+    events: &Events,
+    name: &'static str,
+    deps: &[&str],
+    fail: bool,
+) -> Bootstrap {
+    let start_events = events.clone();
+    let stop_events = events.clone();
+    b.stage_with_stop(
+        name,
+        deps,
+        move || async move {
+            if fail {
+                return Err(format!("{} 连接失败", name).into());
+            }
+            start_events.lock().unwrap().push(format!("start {}", name));
+            Ok(())
+        },
+        move || async move {
+            stop_events.lock().unwrap().push(format!("stop {}", name));
+        },
+    )
+}
+
+#[cfg(test)]
+mod test_bootstrap {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dependency_order() {
+        let events = Events::default();
+        let mut b = app::bootstrap();
+        b = stage(b, &events, "scheduler", &["db", "cache"], false);
+        b = stage(b, &events, "cache", &["db"], false);
+        b = stage(b, &events, "db", &["logging"], false);
+        b = stage(b, &events, "logging", &["config"], false);
+        b = stage(b, &events, "config", &[], false);
+        assert_eq!(
+            b.order().unwrap(),
+            vec!["config", "logging", "db", "cache", "scheduler"]
+        );
+
+        let mut started = b.run().await.unwrap();
+        assert_eq!(started.names().len(), 5);
+        assert_eq!(started.shutdown().await[0], "scheduler");
+        assert_eq!(events.lock().unwrap().last().unwrap(), "stop config");
+    }
+
+    //失败时报告阶段并逆序关闭已启动的组件
+    #[tokio::test]
+    async fn test_failure_rolls_back() {
+        let events = Events::default();
+        let mut b = app::bootstrap();
+        b = stage(b, &events, "config", &[], false);
+        b = stage(b, &events, "db", &["config"], false);
+        b = stage(b, &events, "cache", &["db"], true);
+        b = stage(b, &events, "scheduler", &["cache"], false);
+
+        match b.run().await {
+            Err(BootstrapError::StageFailed {
+                stage,
+                source,
+                rolled_back,
+            }) => {
+                assert_eq!(stage, "cache");
+                assert_eq!(source.to_string(), "cache 连接失败");
+                assert_eq!(rolled_back, vec!["db", "config"]);
+            }
+            other => panic!("期望 StageFailed, 实际: {:?}", other.err()),
+        }
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec!["start config", "start db", "stop db", "stop config"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_graphs() {
+        let events = Events::default();
+        let b = stage(app::bootstrap(), &events, "db", &["config"], false);
+        assert!(matches!(
+            b.order(),
+            Err(BootstrapError::UnknownDependency { .. })
+        ));
+
+        let b = stage(app::bootstrap(), &events, "a", &["b"], false);
+        let b = stage(b, &events, "b", &["a"], false);
+        assert!(matches!(b.order(), Err(BootstrapError::Cycle(names)) if names == ["a", "b"]));
+    }
+}
@@ -0,0 +1,142 @@
+#![cfg(all(feature = "http", feature = "db", feature = "scheduler"))]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use std_app::app::App;
+use std_app::clock::SimClock;
+use std_app::config::AppConfig;
+use std_app::logs;
+use std_app::scheduler::Scheduler;
+
+#[cfg(test)]
+mod test_app_builder {
+    use super::*;
+
+    //只初始化请求的子系统
+    #[tokio::test]
+    async fn test_selected_subsystems() {
+        let app = App::builder()
+            .with_db()
+            .with_scheduler()
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(app.subsystems(), vec!["db", "scheduler"]);
+        assert!(app.http().is_none());
+
+        let pool = app.db().unwrap();
+        let one: i64 = sqlx::query_scalar("SELECT 1")
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+        assert_eq!(one, 1);
+        assert!(app.scheduler().unwrap().tasks().is_empty());
+        app.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_http_only() {
+        let app = App::builder().with_http().build().await.unwrap();
+        assert_eq!(app.subsystems(), vec!["http"]);
+        assert!(app.http().is_some());
+        assert!(app.db().is_none());
+        assert_eq!(app.services().services().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_db_failure() {
+        let result = App::builder()
+            .with_db_url("sqlite:///no/such/dir/app.db")
+            .build()
+            .await;
+        assert!(result.is_err());
+    }
+
+    // 启动时按配置设置脱敏规则
+    #[tokio::test]
+    async fn test_config_applies_redaction() {
+        let mut config = AppConfig::default();
+        config.redact.mask = "<masked>".to_string();
+        let app = App::builder().with_config(config).build().await.unwrap();
+        assert_eq!(app.subsystems(), vec!["logging"]);
+        assert_eq!(app.config().unwrap().redact.mask, "<masked>");
+
+        logs::info("app_builder", "contact ops@example.com");
+        let record = logs::dump_recent()
+            .into_iter()
+            .rfind(|r| r.target == "app_builder")
+            .unwrap();
+        assert_eq!(record.message, "contact <masked>");
+    }
+
+    // SIGHUP 调用 reload，关闭后处理函数被注销
+    #[tokio::test]
+    async fn test_signals() {
+        let reloads = Arc::new(AtomicUsize::new(0));
+        let counter = reloads.clone();
+        let app = App::builder()
+            .with_signals(move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            })
+            .build()
+            .await
+            .unwrap();
+        assert_eq!(app.subsystems(), vec!["signals"]);
+
+        let kill = |name: &str| {
+            std::process::Command::new("kill")
+                .arg(format!("-{}", name))
+                .arg(std::process::id().to_string())
+                .status()
+                .unwrap()
+        };
+        assert!(kill("HUP").success());
+        for _ in 0..100 {
+            if reloads.load(Ordering::SeqCst) == 1 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(reloads.load(Ordering::SeqCst), 1);
+        assert!(kill("USR1").success());
+
+        app.shutdown().await;
+        assert!(kill("HUP").success());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(reloads.load(Ordering::SeqCst), 1);
+    }
+}
+
+#[cfg(test)]
+mod test_scheduler {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_every() {
+        let clock = SimClock::new();
+        let scheduler = Scheduler::with_clock(clock.shared());
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = runs.clone();
+        scheduler.every("cleanup", Duration::from_secs(60), move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+        assert_eq!(scheduler.tasks(), vec!["cleanup"]);
+
+        for expected in 1..=3 {
+            while clock.pending_timers() == 0 {
+                tokio::task::yield_now().await;
+            }
+            clock.advance(Duration::from_secs(60));
+            while runs.load(Ordering::SeqCst) < expected {
+                tokio::task::yield_now().await;
+            }
+        }
+        scheduler.shutdown();
+        assert!(scheduler.tasks().is_empty());
+    }
+}
@@ -0,0 +1,108 @@
+use std::fs;
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+
+use std_app::archive::{self, ArchiveError, ArchiveFormat};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("std-app-archive-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn seed(dir: &Path) {
+    fs::create_dir_all(dir.join("nested/deep")).unwrap();
+    fs::write(dir.join("a.txt"), "Hello, Rust!").unwrap();
+    fs::write(dir.join("nested/b.txt"), "nested file").unwrap();
+    fs::write(dir.join("nested/deep/c.bin"), vec![7u8; 4096]).unwrap();
+}
+
+#[cfg(test)]
+mod test_archive {
+    use super::*;
+
+    fn roundtrip(name: &str) -> Result<(), ArchiveError> {
+        let dir = temp_dir(name);
+        let src = dir.join("src");
+        seed(&src);
+
+        let file = dir.join(name);
+        assert_eq!(archive::create(&src, &file)?, 3);
+
+        let out = dir.join("out");
+        assert_eq!(archive::extract(&file, &out)?, 3);
+        assert_eq!(fs::read_to_string(out.join("a.txt"))?, "Hello, Rust!");
+        assert_eq!(fs::read_to_string(out.join("nested/b.txt"))?, "nested file");
+        assert_eq!(fs::read(out.join("nested/deep/c.bin"))?, vec![7u8; 4096]);
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_tar_roundtrip() -> Result<(), ArchiveError> {
+        roundtrip("data.tar")
+    }
+
+    #[test]
+    fn test_tar_gz_roundtrip() -> Result<(), ArchiveError> {
+        roundtrip("data.tar.gz")
+    }
+
+    #[test]
+    fn test_tar_zst_roundtrip() -> Result<(), ArchiveError> {
+        roundtrip("data.tar.zst")
+    }
+
+    #[test]
+    fn test_zip_roundtrip() -> Result<(), ArchiveError> {
+        roundtrip("data.zip")
+    }
+
+    #[test]
+    fn test_unknown_format() {
+        let result = archive::extract(Path::new("data.rar"), Path::new("out"));
+        assert!(matches!(result, Err(ArchiveError::UnknownFormat(_))));
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("x.TGZ")),
+            Some(ArchiveFormat::TarGz)
+        );
+    }
+
+    //路径穿越保护
+    #[test]
+    fn test_zip_path_traversal_rejected() -> Result<(), ArchiveError> {
+        let mut buffer = Cursor::new(Vec::new());
+        {
+            let mut zip = zip::ZipWriter::new(&mut buffer);
+            zip.start_file("../evil.txt", zip::write::SimpleFileOptions::default())?;
+            zip.write_all(b"pwned")?;
+            zip.finish()?;
+        }
+        buffer.set_position(0);
+
+        let dir = temp_dir("traversal");
+        let result = archive::read_archive(buffer, ArchiveFormat::Zip, &dir.join("out"));
+        match result {
+            Err(ArchiveError::UnsafePath(path)) => assert_eq!(path, PathBuf::from("../evil.txt")),
+            other => panic!("期望 UnsafePath, 实际: {:?}", other),
+        }
+        assert!(!dir.join("evil.txt").exists());
+
+        fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_safe_join() {
+        let dest = Path::new("/data/out");
+        assert_eq!(
+            archive::safe_join(dest, Path::new("./a/b.txt")).unwrap(),
+            PathBuf::from("/data/out/a/b.txt")
+        );
+        assert!(archive::safe_join(dest, Path::new("/etc/passwd")).is_err());
+        assert!(archive::safe_join(dest, Path::new("a/../../b")).is_err());
+        assert!(archive::safe_join(dest, Path::new(".")).is_err());
+    }
+}
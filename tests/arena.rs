@@ -0,0 +1,120 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use std_app::arena::{Arena, StrArena};
+use std_app::import::CsvRecords;
+
+// 统计当前线程的分配次数，用来比较 arena 与逐字段分配 String；
+// 按线程计数，并行执行的其他测试不影响结果
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations<T>(f: impl FnOnce() -> T) -> (T, u64) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+fn csv(rows: usize) -> String {
+    (0..rows)
+        .map(|i| format!("{},user{},user{}@example.com,\"note, {}\"\n", i, i, i, i))
+        .collect()
+}
+
+#[cfg(test)]
+mod test_arena {
+    use super::*;
+
+    #[test]
+    fn test_typed_arena() {
+        let mut arena = Arena::new();
+        let a = arena.alloc("a".to_string());
+        let b = arena.alloc("b".to_string());
+        arena[b].push('!');
+        assert_eq!(arena[a], "a");
+        assert_eq!(arena.get(b).map(String::as_str), Some("b!"));
+        assert_eq!(arena.iter().map(|(h, _)| h).collect::<Vec<_>>(), vec![a, b]);
+
+        let capacity = arena.capacity();
+        arena.clear();
+        assert!(arena.is_empty());
+        assert_eq!(arena.capacity(), capacity);
+        assert_eq!(arena.get(a), None);
+    }
+
+    #[test]
+    fn test_str_arena() {
+        let mut arena = StrArena::new();
+        let hello = arena.alloc("hello");
+        arena.push('w');
+        arena.push_str("orld");
+        let world = arena.finish();
+        let empty = arena.finish();
+        assert_eq!(&arena[hello], "hello");
+        assert_eq!(arena.get(world), "world");
+        assert!(empty.is_empty());
+        assert_eq!(arena.bytes(), 10);
+        arena.clear();
+        assert_eq!(arena.bytes(), 0);
+    }
+
+    #[test]
+    fn test_csv_records() -> std::io::Result<()> {
+        let data = "id,name\n1,\"Smith, J\"\n2,\"multi\nline\"\n";
+        let mut records = CsvRecords::new(data.as_bytes());
+        let header: Vec<String> = records
+            .next_record()?
+            .unwrap()
+            .iter()
+            .map(String::from)
+            .collect();
+        assert_eq!(header, vec!["id", "name"]);
+        assert_eq!(records.next_record()?.unwrap().get(1), Some("Smith, J"));
+        assert_eq!(records.next_record()?.unwrap().get(1), Some("multi\nline"));
+        assert_eq!(records.line(), 4);
+        assert!(records.next_record()?.is_none());
+        Ok(())
+    }
+
+    //解析 1 万行 CSV: arena 复用缓冲区，分配次数与行数无关
+    #[test]
+    fn test_fewer_allocations() -> std::io::Result<()> {
+        let data = csv(10_000);
+        let (fields, arena_allocs) = allocations(|| -> std::io::Result<usize> {
+            let mut records = CsvRecords::new(data.as_bytes());
+            let mut fields = 0;
+            while let Some(record) = records.next_record()? {
+                fields += record.len();
+            }
+            Ok(fields)
+        });
+        assert_eq!(fields?, 40_000);
+
+        let (records, string_allocs) = allocations(|| {
+            data.lines()
+                .map(|line| line.split(',').map(String::from).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(records.len(), 10_000);
+        assert!(arena_allocs < 100, "arena 分配了 {} 次", arena_allocs);
+        assert!(string_allocs > 50_000, "String 分配了 {} 次", string_allocs);
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+use std::fs;
+
+use sqlx::SqlitePool;
+use std_app::backup::{Backup, BackupError, FORMAT_VERSION};
+use std_app::blobs::BlobStore;
+use std_app::db::pool::Pool;
+use std_app::testkit::TestWorkspace;
+
+type UserRow = (i64, String, Option<String>, Option<f64>, Option<Vec<u8>>);
+
+async fn memory_db() -> Pool {
+    Pool::builder("sqlite::memory:").connect().await.unwrap()
+}
+
+// 旧版本的库: users 有 nickname 列，附带索引和一张 notes 表
+async fn seed(pool: &SqlitePool) {
+    for sql in [
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, nickname TEXT, \
+         score REAL, avatar BLOB)",
+        "CREATE INDEX idx_users_name ON users(name)",
+        "CREATE TABLE notes (id INTEGER PRIMARY KEY, user_id INTEGER, body TEXT)",
+        "INSERT INTO users VALUES (1, 'alice', 'al', 9.5, x'00ff10')",
+        "INSERT INTO users VALUES (2, 'bob', NULL, NULL, NULL)",
+        "INSERT INTO notes VALUES (1, 1, 'hello\nworld')",
+    ] {
+        sqlx::query(sql).execute(pool).await.unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test_backup {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_round_trip() {
+        let ws = TestWorkspace::new().unwrap();
+        let source = memory_db().await;
+        seed(source.inner()).await;
+        let blobs = BlobStore::open(ws.path("blobs")).unwrap();
+        let id = blobs.put(b"attachment").unwrap();
+        let out = ws.path("dump.tar.zst");
+
+        let manifest = Backup::new()
+            .db(source.inner())
+            .blobs(ws.path("blobs"))
+            .export(&out)
+            .await
+            .unwrap();
+        assert_eq!(manifest.format_version, FORMAT_VERSION);
+        assert_eq!(
+            manifest.tables.iter().map(|t| t.rows).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert!(!manifest.files.is_empty());
+
+        let target = memory_db().await;
+        let report = Backup::new()
+            .db(target.inner())
+            .blobs(ws.path("restored"))
+            .import(&out)
+            .await
+            .unwrap();
+        assert!(report.tables.iter().all(|t| t.created));
+        assert_eq!(report.files, manifest.files.len());
+
+        let rows: Vec<UserRow> =
+            sqlx::query_as("SELECT id, name, nickname, score, avatar FROM users ORDER BY id")
+                .fetch_all(target.inner())
+                .await
+                .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    1,
+                    "alice".into(),
+                    Some("al".into()),
+                    Some(9.5),
+                    Some(vec![0, 255, 16])
+                ),
+                (2, "bob".into(), None, None, None),
+            ]
+        );
+        let body: String = sqlx::query_scalar("SELECT body FROM notes")
+            .fetch_one(target.inner())
+            .await
+            .unwrap();
+        assert_eq!(body, "hello\nworld");
+        let index: Option<String> =
+            sqlx::query_scalar("SELECT name FROM sqlite_master WHERE name = 'idx_users_name'")
+                .fetch_optional(target.inner())
+                .await
+                .unwrap();
+        assert!(index.is_some());
+
+        let restored = BlobStore::open(ws.path("restored")).unwrap();
+        assert_eq!(restored.get(&id).unwrap(), b"attachment");
+    }
+
+    //新版本删除了 nickname、新增了带默认值的 status
+    #[tokio::test]
+    async fn test_import_into_newer_schema() {
+        let ws = TestWorkspace::new().unwrap();
+        let source = memory_db().await;
+        seed(source.inner()).await;
+        let out = ws.path("dump.tar.zst");
+        Backup::new().db(source.inner()).export(&out).await.unwrap();
+
+        let target = memory_db().await;
+        sqlx::query(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL, score REAL, \
+             avatar BLOB, status TEXT NOT NULL DEFAULT 'active')",
+        )
+        .execute(target.inner())
+        .await
+        .unwrap();
+        let report = Backup::new().db(target.inner()).import(&out).await.unwrap();
+        let users = report.tables.iter().find(|t| t.name == "users").unwrap();
+        assert!(!users.created);
+        assert_eq!(users.rows, 2);
+        assert_eq!(users.dropped_columns, vec!["nickname"]);
+        assert!(report.to_string().contains("忽略已删除的列 nickname"));
+
+        let statuses: Vec<String> = sqlx::query_scalar("SELECT status FROM users")
+            .fetch_all(target.inner())
+            .await
+            .unwrap();
+        assert_eq!(statuses, vec!["active", "active"]);
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_archive_is_rejected() {
+        let ws = TestWorkspace::new().unwrap();
+        let source = memory_db().await;
+        seed(source.inner()).await;
+        let out = ws.path("dump.tar");
+        Backup::new().db(source.inner()).export(&out).await.unwrap();
+
+        // 改掉一行数据，长度不变
+        let mut bytes = fs::read(&out).unwrap();
+        let pos = bytes.windows(5).position(|w| w == b"alice").unwrap();
+        bytes[pos..pos + 5].copy_from_slice(b"mallo");
+        fs::write(&out, bytes).unwrap();
+
+        let target = memory_db().await;
+        let err = Backup::new()
+            .db(target.inner())
+            .import(&out)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BackupError::Checksum(ref f) if f.ends_with("users.jsonl")));
+        let tables: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM sqlite_master")
+            .fetch_one(target.inner())
+            .await
+            .unwrap();
+        assert_eq!(tables, 0);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_newer_format_and_missing_target() {
+        let ws = TestWorkspace::new().unwrap();
+        ws.file(
+            "future/manifest.json",
+            format!(r#"{{"format_version": {}}}"#, FORMAT_VERSION + 1),
+        )
+        .unwrap();
+        let out = ws.path("future.tar");
+        std_app::archive::create(&ws.path("future"), &out).unwrap();
+        let err = Backup::new().import(&out).await.unwrap_err();
+        assert!(matches!(
+            err,
+            BackupError::UnsupportedVersion { found, .. } if found == FORMAT_VERSION + 1
+        ));
+
+        let source = memory_db().await;
+        seed(source.inner()).await;
+        let out = ws.path("dump.tar.zst");
+        Backup::new().db(source.inner()).export(&out).await.unwrap();
+        let err = Backup::new().import(&out).await.unwrap_err();
+        assert!(matches!(err, BackupError::MissingTarget(_)));
+    }
+}
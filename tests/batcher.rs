@@ -0,0 +1,100 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use std_app::batcher::{BatchError, Batcher};
+
+type Seen = Arc<Mutex<Vec<Vec<u32>>>>;
+
+// 记录每批的内容，结果是输入的两倍
+fn doubler(max_size: usize, max_delay: Duration) -> (Batcher<u32, u32>, Seen) {
+    let seen: Seen = Arc::default();
+    let record = seen.clone();
+    let batcher = Batcher::new(max_size, max_delay, move |items: Vec<u32>| {
+        record.lock().unwrap().push(items.clone());
+        async move { Ok::<_, String>(items.into_iter().map(|i| i * 2).collect()) }
+    });
+    (batcher, seen)
+}
+
+#[cfg(test)]
+mod test_batcher {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_flush_by_size() {
+        let (batcher, seen) = doubler(4, Duration::from_secs(60));
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let batcher = batcher.clone();
+                tokio::spawn(async move { batcher.submit(i).await })
+            })
+            .collect();
+        let mut results = Vec::new();
+        for handle in handles {
+            results.push(handle.await.unwrap().unwrap());
+        }
+        //每个提交者拿到自己那一条的结果
+        assert_eq!(results, vec![0, 2, 4, 6, 8, 10, 12, 14]);
+        assert!(seen.lock().unwrap().iter().all(|b| b.len() == 4));
+        let stats = batcher.stats();
+        assert_eq!((stats.batches, stats.items, stats.full_batches), (2, 8, 2));
+        assert_eq!(stats.average_size(), 4.0);
+    }
+
+    #[tokio::test]
+    async fn test_flush_by_delay() {
+        let (batcher, seen) = doubler(100, Duration::from_millis(30));
+        let start = Instant::now();
+        let (a, b) = tokio::join!(batcher.submit(1), batcher.submit(2));
+        assert_eq!((a, b), (Ok(2), Ok(4)));
+        assert!(start.elapsed() >= Duration::from_millis(30));
+        assert_eq!(*seen.lock().unwrap(), vec![vec![1, 2]]);
+        assert_eq!(batcher.stats().full_batches, 0);
+    }
+
+    #[tokio::test]
+    async fn test_batch_errors_reach_every_submitter() {
+        let failing: Batcher<u32, u32> = Batcher::new(2, Duration::from_millis(10), |_| async {
+            Err::<Vec<u32>, _>("数据库不可用")
+        });
+        let (a, b) = tokio::join!(failing.submit(1), failing.submit(2));
+        assert_eq!(a, Err(BatchError::Flush("数据库不可用".into())));
+        assert_eq!(b, a);
+
+        let short: Batcher<u32, u32> = Batcher::new(2, Duration::from_millis(10), |_| async {
+            Ok::<_, String>(vec![1])
+        });
+        let (a, _) = tokio::join!(short.submit(1), short.submit(2));
+        assert_eq!(
+            a,
+            Err(BatchError::Mismatch {
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+
+    //单条失败用 R = Result 表达
+    #[tokio::test]
+    async fn test_per_item_results() {
+        let batcher: Batcher<i32, Result<i32, String>> =
+            Batcher::new(3, Duration::from_millis(10), |items: Vec<i32>| async move {
+                Ok::<_, String>(
+                    items
+                        .into_iter()
+                        .map(|i| {
+                            if i < 0 {
+                                Err(format!("无效: {}", i))
+                            } else {
+                                Ok(i)
+                            }
+                        })
+                        .collect(),
+                )
+            });
+        let (a, b, c) = tokio::join!(batcher.submit(1), batcher.submit(-1), batcher.submit(3));
+        assert_eq!(a, Ok(Ok(1)));
+        assert_eq!(b, Ok(Err("无效: -1".into())));
+        assert_eq!(c, Ok(Ok(3)));
+    }
+}
@@ -0,0 +1,66 @@
+use std::hint::black_box;
+
+use std_app::bench::{self, Bench, Suite};
+
+#[cfg(test)]
+mod test_bench {
+    use super::*;
+
+    #[test]
+    fn test_percentile() {
+        let samples: Vec<u64> = (1..=100).collect();
+        assert_eq!(bench::percentile(&samples, 50.0), 50);
+        assert_eq!(bench::percentile(&samples, 90.0), 90);
+        assert_eq!(bench::percentile(&samples, 99.0), 99);
+        assert_eq!(bench::percentile(&samples, 0.0), 1);
+        assert_eq!(bench::percentile(&[], 50.0), 0);
+    }
+
+    #[test]
+    fn test_run_counts_and_ordering() {
+        let mut calls = 0;
+        let report = Bench::new("sum").warmup(5).iterations(20).run(|| {
+            calls += 1;
+            black_box((0..1000u64).sum::<u64>());
+        });
+        // 预热不计入统计
+        assert_eq!(calls, 25);
+        assert_eq!(report.iterations, 20);
+        assert!(report.min_ns <= report.p50_ns);
+        assert!(report.p50_ns <= report.p90_ns);
+        assert!(report.p90_ns <= report.p99_ns);
+        assert!(report.p99_ns <= report.max_ns);
+    }
+
+    //多个线程各自记录 digest，合并后出报告
+    #[test]
+    fn test_run_parallel() {
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let report = Bench::new("parallel")
+            .warmup(1)
+            .iterations(50)
+            .run_parallel(4, || {
+                calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                black_box((0..1000u64).sum::<u64>());
+            });
+        assert_eq!(calls.into_inner(), 4 * 51);
+        assert_eq!(report.iterations, 200);
+        assert!(report.min_ns <= report.p50_ns);
+        assert!(report.p50_ns <= report.p99_ns);
+        assert!(report.p99_ns <= report.max_ns);
+    }
+
+    //机器可读的 JSON 输出
+    #[test]
+    fn test_suite_json_output() -> Result<(), serde_json::Error> {
+        let reports = Suite::new()
+            .add(Bench::new("a").iterations(3), || {})
+            .add(Bench::new("b").iterations(4), || {})
+            .run();
+        let json: serde_json::Value = serde_json::from_str(&bench::to_json(&reports))?;
+        assert_eq!(json[0]["name"], "a");
+        assert_eq!(json[1]["iterations"], 4);
+        assert!(json[0]["p99_ns"].is_u64());
+        Ok(())
+    }
+}
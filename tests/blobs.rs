@@ -0,0 +1,189 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use std_app::blobs::{BlobError, BlobId, BlobStore};
+use std_app::chunker::Chunker;
+use std_app::fs_util::FileLock;
+
+fn temp_root(name: &str) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("std-app-blobs-{}-{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&root);
+    root
+}
+
+#[cfg(test)]
+mod test_blobs {
+    use super::*;
+
+    #[test]
+    fn test_put_and_get() -> Result<(), BlobError> {
+        let root = temp_root("put_get");
+        let store = BlobStore::open(&root)?;
+
+        let id = store.put(b"hello")?;
+        // echo -n hello | sha256sum
+        assert_eq!(
+            id.as_str(),
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+        );
+        assert_eq!(store.get(&id)?, b"hello");
+        assert!(store.verify(&id)?);
+
+        fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_dedup_and_refcount() -> Result<(), BlobError> {
+        let root = temp_root("dedup");
+        let store = BlobStore::open(&root)?;
+
+        let a = store.put(b"same content")?;
+        let b = store.put_reader(&b"same content"[..])?;
+        assert_eq!(a, b);
+        assert_eq!(store.list()?.len(), 1);
+        assert_eq!(store.refcount(&a)?, 2);
+
+        assert_eq!(store.release(&a)?, 1);
+        assert_eq!(store.gc()?.removed, 0);
+        assert_eq!(store.release(&a)?, 0);
+
+        let stats = store.gc()?;
+        assert_eq!(stats.removed, 1);
+        assert_eq!(stats.freed_bytes, 12);
+        assert!(!store.contains(&a));
+
+        fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_not_found_and_invalid_id() -> Result<(), BlobError> {
+        let root = temp_root("not_found");
+        let store = BlobStore::open(&root)?;
+
+        let missing: BlobId = "0".repeat(64).parse()?;
+        match store.get(&missing) {
+            Err(BlobError::NotFound(id)) => assert_eq!(id, missing),
+            other => panic!("期望 NotFound, 实际: {:?}", other),
+        }
+        assert!(matches!(
+            "not-a-hash".parse::<BlobId>(),
+            Err(BlobError::InvalidId(_))
+        ));
+
+        fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    //布隆过滤器判定不存在的 id 不访问磁盘
+    #[test]
+    fn test_bloom_filter() -> Result<(), BlobError> {
+        let root = temp_root("bloom");
+        let existing = BlobStore::open(&root)?.put(b"before")?;
+
+        let store = BlobStore::open(&root)?.with_bloom_filter(100)?;
+        assert!(store.contains(&existing));
+        let added = store.put(b"after")?;
+        assert_eq!(store.get(&added)?, b"after");
+
+        let missing: BlobId = "0".repeat(64).parse()?;
+        assert!(!store.contains(&missing));
+        assert!(matches!(store.get(&missing), Err(BlobError::NotFound(_))));
+        assert_eq!(store.skipped_lookups(), 2);
+
+        store.release(&existing)?;
+        assert_eq!(store.gc()?.removed, 1);
+        assert!(!store.contains(&existing));
+        assert!(store.contains(&added));
+
+        fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    //按内容切分存储，修改一小段后只新增少量块
+    #[test]
+    fn test_chunked_dedup() -> Result<(), BlobError> {
+        let root = temp_root("chunked");
+        let store = BlobStore::open(&root)?;
+        let chunker = Chunker::with_avg_size(1024);
+
+        let mut x = 0x2545f4914f6cdd1du64;
+        let old: Vec<u8> = (0..64 * 1024)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x as u8
+            })
+            .collect();
+        let first = store.put_chunked(&old[..], &chunker)?;
+        assert_eq!(first.size, old.len() as u64);
+        let stored = store.list()?.len();
+
+        let mut new = old.clone();
+        new[30_000..30_010].copy_from_slice(b"0123456789");
+        let second = store.put_chunked(&new[..], &chunker)?;
+        assert!(store.list()?.len() - stored <= 2);
+
+        let mut out = Vec::new();
+        store.read_chunked(&second, &mut out)?;
+        assert_eq!(out, new);
+
+        //清单可以序列化保存
+        let json = serde_json::to_string(&first).unwrap();
+        assert_eq!(
+            serde_json::from_str::<std_app::blobs::ChunkedBlob>(&json).unwrap(),
+            first
+        );
+
+        let total = store.list()?.len();
+        store.release_chunked(&first)?;
+        store.release_chunked(&second)?;
+        assert_eq!(store.gc()?.removed, total);
+        assert!(store.list()?.is_empty());
+
+        fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    // 只清理足够旧的临时文件
+    #[test]
+    fn test_gc_keeps_recent_tmp_files() -> Result<(), BlobError> {
+        let root = temp_root("gc_tmp");
+        let store = BlobStore::open(&root)?.tmp_max_age(Duration::from_secs(600));
+
+        let stale = root.join("tmp/999-0");
+        let fresh = root.join("tmp/999-1");
+        fs::write(&fresh, b"partial")?;
+        fs::File::create(&stale)?.set_modified(SystemTime::now() - Duration::from_secs(3600))?;
+
+        store.gc()?;
+        assert!(!stale.exists());
+        assert!(fresh.exists());
+
+        fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    // 其他进程写入期间 gc 等待，写入结束后才开始
+    #[test]
+    fn test_gc_waits_for_writers() -> Result<(), BlobError> {
+        let root = temp_root("gc_wait");
+        let store = BlobStore::open(&root)?;
+        let id = store.put(b"orphan")?;
+        store.release(&id)?;
+
+        let writer = FileLock::shared(root.join("gc.lock"))?;
+        let gc = std::thread::spawn(move || store.gc().map(|stats| stats.removed));
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!gc.is_finished());
+
+        drop(writer);
+        assert_eq!(gc.join().unwrap()?, 1);
+
+        fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use std_app::bulkhead::{Bulkhead, BulkheadError, Bulkheads};
+use std_app::metrics::{MetricValue, Registry};
+
+fn metric(registry: &Registry, name: &str) -> MetricValue {
+    registry
+        .snapshot()
+        .into_iter()
+        .find(|m| m.name == name)
+        .unwrap()
+        .value
+}
+
+#[cfg(test)]
+mod test_bulkhead {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rejects_when_full() {
+        let bulkhead = Bulkhead::new("webhooks", 2, Duration::ZERO);
+        let a = bulkhead.enter().await.unwrap();
+        let _b = bulkhead.try_enter().unwrap();
+        assert_eq!(
+            bulkhead.try_enter().err(),
+            Some(BulkheadError::Full("webhooks".into()))
+        );
+        assert!(bulkhead.enter().await.is_err());
+        let stats = bulkhead.stats();
+        assert_eq!((stats.active, stats.rejected), (2, 2));
+
+        drop(a);
+        assert!(bulkhead.try_enter().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_waits_up_to_max_wait() {
+        let bulkhead = Bulkhead::new("exports", 1, Duration::from_secs(5));
+        let held = bulkhead.enter().await.unwrap();
+        let (tx, rx) = oneshot::channel();
+        let waiter = {
+            let bulkhead = bulkhead.clone();
+            tokio::spawn(async move { bulkhead.run(async { rx.await.unwrap() }).await })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(held);
+        tx.send(7).unwrap();
+        assert_eq!(waiter.await.unwrap(), Ok(7));
+
+        let short = Bulkhead::new("short", 1, Duration::from_millis(20));
+        let _held = short.enter().await.unwrap();
+        assert!(short.run(async {}).await.is_err());
+        assert_eq!(short.stats().rejected, 1);
+    }
+
+    //一个舱壁占满不影响另一个
+    #[tokio::test]
+    async fn test_isolation_between_subsystems() {
+        let registry = Arc::new(Registry::new());
+        let bulkheads = Bulkheads::new().metrics(registry.clone());
+        let webhooks = bulkheads.define("webhooks", 1, Duration::ZERO);
+        bulkheads.define("api", 4, Duration::ZERO);
+
+        let _busy = webhooks.enter().await.unwrap();
+        assert!(bulkheads.run("webhooks", async {}).await.is_err());
+        assert_eq!(bulkheads.run("api", async { 1 }).await, Ok(1));
+        assert_eq!(
+            bulkheads.run("reports", async {}).await,
+            Err(BulkheadError::Unknown("reports".into()))
+        );
+
+        let stats = bulkheads.stats();
+        assert_eq!(stats[0].name, "api");
+        assert_eq!((stats[0].active, stats[0].rejected), (0, 0));
+        assert_eq!((stats[1].active, stats[1].rejected), (1, 1));
+        assert_eq!(
+            metric(&registry, "bulkhead_webhooks_rejected_total"),
+            MetricValue::Counter { value: 1 }
+        );
+        assert_eq!(
+            metric(&registry, "bulkhead_webhooks_active"),
+            MetricValue::Gauge { value: 1.0 }
+        );
+        assert_eq!(
+            metric(&registry, "bulkhead_api_active"),
+            MetricValue::Gauge { value: 0.0 }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_blocking_limits_threads() {
+        let bulkhead = Bulkhead::new("cpu", 1, Duration::ZERO);
+        let (tx, rx) = std::sync::mpsc::channel::<()>();
+        let running = {
+            let bulkhead = bulkhead.clone();
+            tokio::spawn(async move { bulkhead.run_blocking(move || rx.recv().is_ok()).await })
+        };
+        while bulkhead.stats().active == 0 {
+            tokio::task::yield_now().await;
+        }
+        assert!(bulkhead.run_blocking(|| ()).await.is_err());
+        tx.send(()).unwrap();
+        assert_eq!(running.await.unwrap(), Ok(true));
+        assert_eq!(bulkhead.stats().active, 0);
+    }
+}
@@ -0,0 +1,131 @@
+use std::io::{Cursor, Read, Write};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use thiserror::Error;
+
+use std_app::chaos::{Chaos, Fault, InjectedFault, OpStats, Trigger};
+
+#[derive(Error, Debug)]
+enum ApiError {
+    #[error("HTTP 请求失败: {0}")]
+    RequestFailed(String),
+}
+
+impl From<InjectedFault> for ApiError {
+    fn from(fault: InjectedFault) -> Self {
+        ApiError::RequestFailed(fault.to_string())
+    }
+}
+
+#[cfg(test)]
+mod test_chaos {
+    use super::*;
+
+    //重试逻辑可以被确定性地测试
+    #[tokio::test]
+    async fn test_retry_survives_scheduled_errors() {
+        let chaos = Chaos::new(1).rule(
+            "http",
+            Trigger::Calls(vec![1, 2]),
+            Fault::Error("connection reset".to_string()),
+        );
+
+        let mut attempts = 0;
+        let result = loop {
+            attempts += 1;
+            let r: Result<&str, ApiError> = chaos.call("http", || async { Ok("body") }).await;
+            if r.is_ok() || attempts == 5 {
+                break r;
+            }
+        };
+        assert_eq!(result.unwrap(), "body");
+        assert_eq!(attempts, 3);
+        assert_eq!(
+            chaos.stats("http"),
+            OpStats {
+                calls: 3,
+                injected: 2
+            }
+        );
+    }
+
+    //部分失败: 操作执行了但调用方收到错误
+    #[tokio::test]
+    async fn test_partial_failure_runs_operation() {
+        let chaos = Chaos::new(1).rule(
+            "db",
+            Trigger::Always,
+            Fault::Partial("响应丢失".to_string()),
+        );
+        let executed = AtomicUsize::new(0);
+        let result: Result<(), ApiError> = chaos
+            .call("db", || async {
+                executed.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .await;
+        assert!(matches!(result, Err(ApiError::RequestFailed(m)) if m.contains("响应丢失")));
+        assert_eq!(executed.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_latency_injection() {
+        let chaos = Chaos::new(1).rule(
+            "http",
+            Trigger::Schedule(vec![false, true]),
+            Fault::Latency(Duration::from_millis(30)),
+        );
+        let start = std::time::Instant::now();
+        for _ in 0..4 {
+            let _: Result<(), ApiError> = chaos.call("http", || async { Ok(()) }).await;
+        }
+        // 4 次调用中第 2、4 次各延迟 30ms
+        assert!(start.elapsed() >= Duration::from_millis(60));
+        assert_eq!(chaos.stats("http").injected, 0);
+    }
+
+    #[test]
+    fn test_probability_is_reproducible() {
+        let run = |seed| {
+            let chaos =
+                Chaos::new(seed).rule("*", Trigger::Probability(0.3), Fault::Error("x".into()));
+            (0..100)
+                .map(|_| !chaos.decide("file").is_empty())
+                .collect::<Vec<_>>()
+        };
+        let a = run(42);
+        assert_eq!(a, run(42));
+        let failures = a.iter().filter(|f| **f).count();
+        assert!((15..=45).contains(&failures), "failures = {}", failures);
+    }
+
+    #[test]
+    fn test_file_io_faults() {
+        let chaos = Chaos::new(1)
+            .rule("read", Trigger::Always, Fault::Partial("short read".into()))
+            .rule(
+                "write",
+                Trigger::Calls(vec![2]),
+                Fault::Error("disk full".into()),
+            );
+
+        let mut reader = chaos.reader("read", Cursor::new(b"Hello, Rust!".to_vec()));
+        let mut buf = [0u8; 8];
+        assert_eq!(reader.read(&mut buf).unwrap(), 4);
+
+        let mut writer = chaos.writer("write", Vec::new());
+        writer.write_all(b"ok").unwrap();
+        let err = writer.write_all(b"fail").unwrap_err();
+        assert!(err.to_string().contains("disk full"));
+        assert_eq!(writer.into_inner(), b"ok");
+    }
+
+    #[test]
+    fn test_disabled() {
+        let chaos = Chaos::disabled();
+        assert!(chaos.decide("http").is_empty());
+        let r: std::io::Result<u8> = chaos.call_sync("file", || Ok(1));
+        assert_eq!(r.unwrap(), 1);
+    }
+}
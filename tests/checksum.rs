@@ -0,0 +1,113 @@
+use std::io::{self, Read, Write};
+
+use sha2::Digest;
+use std_app::checksum::{self, ChecksumError, HashState, HashingReader, HashingWriter, Sha256};
+
+fn data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+fn reference(data: &[u8]) -> [u8; 32] {
+    sha2::Sha256::digest(data).into()
+}
+
+#[cfg(test)]
+mod test_sha256 {
+    use super::*;
+
+    #[test]
+    fn test_matches_sha2() {
+        for len in [0, 1, 55, 56, 63, 64, 65, 127, 128, 1000, 4097] {
+            let data = data(len);
+            assert_eq!(Sha256::digest(&data), reference(&data), "len={}", len);
+        }
+        assert_eq!(
+            Sha256::new().finalize_hex(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    //任意位置挂起再恢复，结果与一次性计算相同
+    #[test]
+    fn test_suspend_resume() -> Result<(), ChecksumError> {
+        let data = data(3000);
+        for split in [0, 1, 63, 64, 100, 2999, 3000] {
+            let mut first = Sha256::new();
+            first.update(&data[..split]);
+            let saved = first.suspend().to_bytes();
+
+            let mut second = Sha256::resume(&HashState::from_bytes(&saved)?)?;
+            assert_eq!(second.len(), split as u64);
+            for piece in data[split..].chunks(37) {
+                second.update(piece);
+            }
+            assert_eq!(second.finalize(), reference(&data));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_state_serde() -> Result<(), ChecksumError> {
+        let mut hasher = Sha256::new();
+        hasher.update(b"hello ");
+        let json = serde_json::to_string(&hasher.suspend()).unwrap();
+        let state: HashState = serde_json::from_str(&json).unwrap();
+        let mut resumed = Sha256::resume(&state)?;
+        resumed.update(b"world");
+        checksum::verify_hex(
+            resumed,
+            &hex::encode(reference(b"hello world")).to_uppercase(),
+        )
+    }
+
+    #[test]
+    fn test_invalid_state() {
+        assert!(matches!(
+            HashState::from_bytes(b"garbage"),
+            Err(ChecksumError::InvalidState(_))
+        ));
+        let mut state = Sha256::new().suspend();
+        state.len = 3;
+        assert!(Sha256::resume(&state).is_err());
+
+        let err = checksum::verify_hex(Sha256::new(), "00").unwrap_err();
+        assert!(matches!(err, ChecksumError::Mismatch { .. }));
+    }
+}
+
+#[cfg(test)]
+mod test_wrappers {
+    use super::*;
+
+    //模拟中断后续传: 第一次只下载了一部分，保存状态后从断点继续
+    #[test]
+    fn test_resumable_download() -> io::Result<()> {
+        let source = data(10_000);
+        let mut file = Vec::new();
+
+        let mut writer = HashingWriter::new(&mut file);
+        writer.write_all(&source[..4321])?;
+        let saved = writer.hasher().suspend();
+
+        let hasher = Sha256::resume(&saved).unwrap();
+        let offset = hasher.len() as usize;
+        let mut writer = HashingWriter::resume(&mut file, hasher);
+        writer.write_all(&source[offset..])?;
+        let (_, hasher) = writer.into_parts();
+
+        assert_eq!(file, source);
+        assert_eq!(hasher.finalize(), reference(&source));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hashing_reader() -> io::Result<()> {
+        let source = data(5000);
+        let mut reader = HashingReader::new(&source[..]);
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out)?;
+        let (_, hasher) = reader.into_parts();
+        assert_eq!(hasher.finalize(), reference(&source));
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use std::collections::HashSet;
+use std::io::{self, Cursor};
+
+use std_app::chunker::Chunker;
+
+// 伪随机数据，内容固定
+fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+    let mut x = seed | 1;
+    (0..len)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x as u8
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_chunker {
+    use super::*;
+
+    #[test]
+    fn test_chunk_sizes() {
+        let data = random_bytes(1 << 20, 1);
+        let chunker = Chunker::default();
+        let chunks: Vec<&[u8]> = chunker.chunks(&data).collect();
+        assert_eq!(chunks.concat(), data);
+        let (last, rest) = chunks.split_last().unwrap();
+        assert!(last.len() <= chunker.max_size());
+        for chunk in rest {
+            assert!(chunk.len() >= chunker.min_size());
+            assert!(chunk.len() <= chunker.max_size());
+        }
+        // 平均块大小接近配置值
+        let avg = data.len() / chunks.len();
+        assert!(
+            avg > chunker.avg_size() / 2 && avg < chunker.avg_size() * 2,
+            "avg={}",
+            avg
+        );
+    }
+
+    #[test]
+    fn test_small_and_uniform_input() {
+        let chunker = Chunker::with_avg_size(1024);
+        assert_eq!(chunker.chunks(b"").count(), 0);
+        assert_eq!(chunker.chunks(b"short").collect::<Vec<_>>(), vec![b"short"]);
+        // 全零数据找不到切分点，按 max_size 切
+        let zeros = vec![0u8; chunker.max_size() * 3];
+        assert!(chunker
+            .chunks(&zeros)
+            .all(|c| c.len() == chunker.max_size()));
+    }
+
+    //中间插入数据只影响附近的块
+    #[test]
+    fn test_insert_shifts_few_chunks() {
+        let chunker = Chunker::with_avg_size(4096);
+        let old = random_bytes(256 * 1024, 7);
+        let mut new = old.clone();
+        new.splice(100_000..100_000, b"inserted bytes".iter().copied());
+
+        let old_chunks: HashSet<&[u8]> = chunker.chunks(&old).collect();
+        let new_chunks: Vec<&[u8]> = chunker.chunks(&new).collect();
+        let changed = new_chunks
+            .iter()
+            .filter(|c| !old_chunks.contains(*c))
+            .count();
+        assert!(changed <= 3, "changed={} of {}", changed, new_chunks.len());
+    }
+
+    #[test]
+    fn test_stream_matches_slice() -> io::Result<()> {
+        let data = random_bytes(300_000, 3);
+        let chunker = Chunker::with_avg_size(2048);
+        let expected: Vec<&[u8]> = chunker.chunks(&data).collect();
+
+        let mut offset = 0;
+        let mut streamed = Vec::new();
+        for chunk in chunker.stream(Cursor::new(&data)) {
+            let chunk = chunk?;
+            assert_eq!(chunk.offset, offset);
+            offset += chunk.data.len() as u64;
+            streamed.push(chunk.data);
+        }
+        assert_eq!(streamed, expected);
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use std_app::cache::TtlCache;
+use std_app::clock::{Clock, SimClock, SystemClock};
+use std_app::ratelimit::TokenBucket;
+use std_app::retry::{retry, Backoff};
+
+// 等待任务进入 sleep
+async fn wait_for_timer(clock: &SimClock) {
+    while clock.pending_timers() == 0 {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[cfg(test)]
+mod test_clock {
+    use super::*;
+
+    #[test]
+    fn test_sim_clock_advance() {
+        let clock = SimClock::at(UNIX_EPOCH);
+        let start = clock.now();
+        clock.advance(Duration::from_secs(90));
+        assert_eq!(clock.now() - start, Duration::from_secs(90));
+        assert_eq!(clock.system_time(), UNIX_EPOCH + Duration::from_secs(90));
+    }
+
+    #[tokio::test]
+    async fn test_sim_sleep_wakes_on_advance() {
+        let clock = SimClock::new();
+        let task = tokio::spawn({
+            let clock = clock.clone();
+            async move { clock.sleep(Duration::from_secs(3600)).await }
+        });
+
+        wait_for_timer(&clock).await;
+        assert_eq!(clock.next_timer(), Some(Duration::from_secs(3600)));
+        clock.advance(Duration::from_secs(1800));
+        assert!(!task.is_finished());
+        clock.advance(Duration::from_secs(1800));
+        task.await.unwrap();
+        assert_eq!(clock.pending_timers(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_system_clock_sleep() {
+        let clock = SystemClock;
+        let start = clock.now();
+        clock.sleep(Duration::from_millis(10)).await;
+        assert!(clock.now() - start >= Duration::from_millis(10));
+    }
+}
+
+#[cfg(test)]
+mod test_consumers {
+    use super::*;
+
+    #[test]
+    fn test_ttl_cache_expiry() {
+        let clock = SimClock::new();
+        let cache = TtlCache::with_clock(Duration::from_secs(60), clock.shared());
+        cache.insert("key1", "value1");
+        cache.insert_with_ttl("key2", "value2", Duration::from_secs(10));
+
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(cache.get(&"key1"), Some("value1"));
+        assert_eq!(cache.get(&"key2"), None);
+
+        clock.advance(Duration::from_secs(30));
+        assert_eq!(cache.purge_expired(), 1);
+        assert!(cache.is_empty());
+    }
+
+    //退避等待在模拟时钟上完成，测试无需真的等待
+    #[tokio::test]
+    async fn test_retry_backoff() {
+        let clock = SimClock::new();
+        let backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5)).max_attempts(4);
+        assert_eq!(backoff.delay(1), Duration::from_secs(1));
+        assert_eq!(backoff.delay(3), Duration::from_secs(4));
+        assert_eq!(backoff.delay(10), Duration::from_secs(5));
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let task = tokio::spawn({
+            let clock = clock.clone();
+            let attempts = attempts.clone();
+            async move {
+                retry(&clock, &backoff, || async {
+                    match attempts.fetch_add(1, Ordering::SeqCst) {
+                        0..=2 => Err("unavailable"),
+                        n => Ok(n),
+                    }
+                })
+                .await
+            }
+        });
+
+        for _ in 0..3 {
+            wait_for_timer(&clock).await;
+            let next = clock.next_timer().unwrap();
+            clock.advance(next);
+        }
+        assert_eq!(task.await.unwrap(), Ok(3));
+        assert_eq!(clock.elapsed(), Duration::from_secs(1 + 2 + 4));
+    }
+
+    #[tokio::test]
+    async fn test_token_bucket() {
+        let clock = SimClock::new();
+        let bucket = TokenBucket::with_clock(2, 1.0, clock.shared());
+        assert!(bucket.try_acquire());
+        assert!(bucket.try_acquire());
+        assert!(!bucket.try_acquire());
+        assert_eq!(bucket.wait_time(), Duration::from_secs(1));
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(bucket.available(), 2);
+
+        let bucket = Arc::new(bucket);
+        bucket.try_acquire();
+        bucket.try_acquire();
+        let task = tokio::spawn({
+            let bucket = bucket.clone();
+            async move { bucket.acquire().await }
+        });
+        wait_for_timer(&clock).await;
+        clock.advance(Duration::from_secs(1));
+        task.await.unwrap();
+        assert_eq!(bucket.available(), 0);
+    }
+}
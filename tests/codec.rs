@@ -0,0 +1,83 @@
+use std::io::{self, Read};
+
+use std_app::codec::{self, FrameError, Framed};
+
+fn encode(frames: &[&[u8]]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for frame in frames {
+        codec::write_frame(&mut out, frame).unwrap();
+    }
+    out
+}
+
+// 每次最多返回 n 个字节，模拟网络上分段到达的数据
+struct Trickle<'a>(&'a [u8], usize);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.0.len().min(self.1).min(buf.len());
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod test_framed {
+    use super::*;
+
+    #[test]
+    fn test_borrowed_frames() -> Result<(), FrameError> {
+        let data = encode(&[b"hello", b"", b"world"]);
+        let mut framed = Framed::new(Trickle(&data, 3));
+        assert_eq!(framed.next_frame()?, Some(&b"hello"[..]));
+        assert_eq!(framed.next_frame()?, Some(&b""[..]));
+        assert_eq!(framed.next_frame()?, Some(&b"world"[..]));
+        assert_eq!(framed.next_frame()?, None);
+        Ok(())
+    }
+
+    //Bytes 与缓冲区共享内存，可以在读取后续帧之后继续使用
+    #[test]
+    fn test_bytes_outlive_next_read() -> Result<(), FrameError> {
+        let big = vec![7u8; 20_000];
+        let data = encode(&[b"first", &big, b"last"]);
+        let mut framed = Framed::new(data.as_slice());
+        let first = framed.next_bytes()?.unwrap();
+        let second = framed.next_bytes()?.unwrap();
+        let last = framed.next_frame()?.unwrap().to_vec();
+        assert_eq!(&first[..], b"first");
+        assert_eq!(second.len(), 20_000);
+        assert_eq!(last, b"last");
+        Ok(())
+    }
+
+    #[test]
+    fn test_owned_iterator() {
+        let data = encode(&[b"a", b"bc"]);
+        let frames: Vec<Vec<u8>> = Framed::new(data.as_slice())
+            .owned()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(frames, vec![b"a".to_vec(), b"bc".to_vec()]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let data = encode(&[b"0123456789"]);
+        let mut framed = Framed::new(&data[..8]);
+        assert!(matches!(
+            framed.next_frame(),
+            Err(FrameError::Truncated {
+                expected: 14,
+                actual: 8
+            })
+        ));
+
+        let mut framed = Framed::new(data.as_slice()).max_frame_len(4);
+        assert!(matches!(
+            framed.next_owned(),
+            Err(FrameError::TooLarge { len: 10, max: 4 })
+        ));
+    }
+}
@@ -0,0 +1,367 @@
+use std_app::collections::{BloomFilter, CountingBloomFilter};
+
+#[cfg(test)]
+mod test_bloom {
+    use super::*;
+
+    #[test]
+    fn test_no_false_negatives() {
+        let mut filter = BloomFilter::new(1_000, 0.01);
+        for i in 0..1_000 {
+            filter.insert(&format!("key{}", i));
+        }
+        assert_eq!(filter.len(), 1_000);
+        assert!((0..1_000).all(|i| filter.contains(&format!("key{}", i))));
+    }
+
+    //误判率接近配置值
+    #[test]
+    fn test_false_positive_rate() {
+        let mut filter = BloomFilter::new(10_000, 0.01);
+        for i in 0..10_000u64 {
+            filter.insert(&i);
+        }
+        let false_positives = (10_000..110_000u64).filter(|i| filter.contains(i)).count();
+        let rate = false_positives as f64 / 100_000.0;
+        assert!(rate < 0.02, "误判率 {}", rate);
+        assert!(filter.estimated_fp_rate() < 0.02);
+        assert_eq!(filter.num_hashes(), 7);
+    }
+
+    #[test]
+    fn test_serde_and_union() -> Result<(), serde_json::Error> {
+        let mut a = BloomFilter::new(100, 0.01);
+        a.insert("alice");
+        let decoded: BloomFilter = serde_json::from_str(&serde_json::to_string(&a)?)?;
+        assert_eq!(decoded, a);
+        assert!(decoded.contains("alice"));
+
+        let mut b = BloomFilter::new(100, 0.01);
+        b.insert("bob");
+        assert!(a.union(&b));
+        assert!(a.contains("alice") && a.contains("bob"));
+        assert!(!a.union(&BloomFilter::new(10, 0.1)));
+
+        a.clear();
+        assert!(a.is_empty());
+        assert!(!a.contains("alice"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_counting_remove() {
+        let mut filter = CountingBloomFilter::new(100, 0.01);
+        filter.insert("a");
+        filter.insert("a");
+        filter.insert("b");
+        assert!(filter.remove("a"));
+        assert!(filter.contains("a"));
+        assert!(filter.remove("a"));
+        assert!(!filter.contains("a"));
+        assert!(filter.contains("b"));
+        assert!(!filter.remove("missing"));
+        assert_eq!(filter.len(), 1);
+    }
+}
+
+#[cfg(test)]
+mod test_hash_ring {
+    use std::collections::HashMap;
+
+    use std_app::collections::HashRing;
+
+    fn shard_counts(ring: &HashRing<String>, keys: &[String]) -> HashMap<String, usize> {
+        let mut counts = HashMap::new();
+        for key in keys {
+            *counts.entry(ring.get(key).unwrap().clone()).or_default() += 1;
+        }
+        counts
+    }
+
+    fn keys() -> Vec<String> {
+        (0..10_000).map(|i| format!("user:{}", i)).collect()
+    }
+
+    #[test]
+    fn test_stable_and_balanced() {
+        let nodes = ["a", "b", "c", "d"].map(String::from);
+        let ring = HashRing::with_nodes(160, nodes.clone());
+        //节点加入顺序不影响映射
+        let reversed = HashRing::with_nodes(160, nodes.into_iter().rev());
+        let keys = keys();
+        assert!(keys.iter().all(|k| ring.get(k) == reversed.get(k)));
+
+        let counts = shard_counts(&ring, &keys);
+        assert_eq!(counts.len(), 4);
+        assert!(
+            counts.values().all(|&c| (1_800..3_200).contains(&c)),
+            "{:?}",
+            counts
+        );
+        assert_eq!(HashRing::<String>::default().get("x"), None);
+    }
+
+    //增删节点只迁移少量 key
+    #[test]
+    fn test_rebalance() {
+        let keys = keys();
+        let ring = HashRing::with_nodes(160, ["a", "b", "c", "d"].map(String::from));
+        let mut grown = ring.clone();
+        assert!(grown.add("e".to_string()));
+        assert!(!grown.add("e".to_string()));
+
+        let moves = ring.moves(&grown, &keys);
+        assert!(moves.iter().all(|(_, _, to)| to.as_str() == "e"));
+        assert!((1_400..2_600).contains(&moves.len()), "{}", moves.len());
+
+        assert!(grown.remove(&"e".to_string()));
+        assert!(!grown.remove(&"e".to_string()));
+        assert!(ring.moves(&grown, &keys).is_empty());
+    }
+
+    #[test]
+    fn test_weights_and_replicas() {
+        let mut ring = HashRing::new(100);
+        ring.add("small".to_string());
+        ring.add_weighted("big".to_string(), 3);
+        let counts = shard_counts(&ring, &keys());
+        assert!(counts["big"] > counts["small"] * 2);
+
+        ring.add("third".to_string());
+        let replicas = ring.get_n("job-42", 2);
+        assert_eq!(replicas.len(), 2);
+        assert_ne!(replicas[0], replicas[1]);
+        assert_eq!(replicas[0], ring.get("job-42").unwrap());
+        assert_eq!(ring.get_n("job-42", 10).len(), 3);
+    }
+}
+
+#[cfg(test)]
+mod test_ordered_map {
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use std_app::collections::OrderedMap;
+
+    #[test]
+    fn test_ordered_operations() {
+        let map = OrderedMap::with_shards(4);
+        for i in [5, 1, 9, 3, 7] {
+            assert_eq!(map.insert(i, i * 10), None);
+        }
+        assert_eq!(map.insert(3, 31), Some(30));
+        assert_eq!(map.len(), 5);
+        assert_eq!(map.get(&3), Some(31));
+        assert_eq!(map.first(), Some((1, 10)));
+        assert_eq!(map.range(3..8), vec![(3, 31), (5, 50), (7, 70)]);
+
+        assert_eq!(map.pop_first(), Some((1, 10)));
+        assert_eq!(map.pop_first_if(|k| *k > 3), None);
+        assert_eq!(map.remove(&3), Some(31));
+        assert_eq!(map.first(), Some((5, 50)));
+        map.clear();
+        assert!(map.is_empty());
+        assert_eq!(map.pop_first(), None);
+    }
+
+    //以 (截止时间, id) 为 key 的过期索引
+    #[test]
+    fn test_expiring() {
+        let now = Instant::now();
+        let map = OrderedMap::new();
+        map.insert((now + Duration::from_secs(30), "c"), ());
+        map.insert((now + Duration::from_secs(10), "a"), ());
+        map.insert((now + Duration::from_secs(10), "b"), ());
+        assert_eq!(map.next_deadline(), Some(now + Duration::from_secs(10)));
+
+        assert_eq!(map.pop_first_expiring(now), None);
+        let expired: Vec<&str> = map
+            .drain_expiring(now + Duration::from_secs(20))
+            .into_iter()
+            .map(|((_, id), _)| id)
+            .collect();
+        assert_eq!(expired, vec!["a", "b"]);
+        assert_eq!(map.len(), 1);
+    }
+
+    //多个线程同时 pop，每个条目只被取出一次
+    #[test]
+    fn test_concurrent_pop() {
+        let map = Arc::new(OrderedMap::new());
+        for i in 0..10_000u32 {
+            map.insert(i, ());
+        }
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let map = map.clone();
+                thread::spawn(move || {
+                    let mut popped = Vec::new();
+                    while let Some((k, _)) = map.pop_first() {
+                        popped.push(k);
+                    }
+                    popped
+                })
+            })
+            .collect();
+        let mut all: Vec<u32> = Vec::new();
+        for handle in handles {
+            let popped = handle.join().unwrap();
+            assert!(popped.windows(2).all(|w| w[0] < w[1]));
+            all.extend(popped);
+        }
+        all.sort();
+        assert_eq!(all, (0..10_000).collect::<Vec<_>>());
+    }
+}
+
+#[cfg(test)]
+mod test_snapshot_map {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::thread;
+
+    use std_app::collections::SnapshotMap;
+
+    #[test]
+    fn test_snapshot_isolation() {
+        let flags: SnapshotMap<String, bool> = SnapshotMap::new();
+        flags.insert("new_ui".to_string(), false);
+        let before = flags.snapshot();
+
+        flags.update(|map| {
+            map.insert("new_ui".to_string(), true);
+            map.insert("beta".to_string(), true);
+        });
+        //已经取得的快照不受影响
+        assert_eq!(before.get("new_ui"), Some(&false));
+        assert_eq!(before.len(), 1);
+        assert_eq!(flags.get("new_ui"), Some(true));
+        assert_eq!(flags.len(), 2);
+
+        let old = flags.replace(HashMap::from([("only".to_string(), true)]));
+        assert_eq!(old.len(), 2);
+        assert!(flags.contains_key("only"));
+        assert_eq!(flags.remove("only"), Some(true));
+        assert!(flags.is_empty());
+    }
+
+    //100 个线程并发读，同时有写入；读者总是看到完整的快照
+    #[test]
+    fn test_concurrent_reads() {
+        let map: Arc<SnapshotMap<String, String>> = Arc::new(
+            (0..1_000)
+                .map(|i| (format!("key{}", i), format!("value{}", i)))
+                .collect::<HashMap<_, _>>()
+                .into(),
+        );
+        let writer = {
+            let map = map.clone();
+            thread::spawn(move || {
+                for round in 0..50 {
+                    map.update(|m| {
+                        for i in 0..1_000 {
+                            m.insert(format!("key{}", i), format!("value{}-{}", i, round));
+                        }
+                    });
+                }
+            })
+        };
+        let readers: Vec<_> = (0..100)
+            .map(|_| {
+                let map = map.clone();
+                thread::spawn(move || {
+                    for _ in 0..20 {
+                        let snapshot = map.snapshot();
+                        assert_eq!(snapshot.len(), 1_000);
+                        let suffix = snapshot["key0"].trim_start_matches("value0");
+                        assert!(snapshot.values().all(|v| v.ends_with(suffix)));
+                        for i in 0..1_000 {
+                            assert!(map.get(&format!("key{}", i)).is_some());
+                        }
+                    }
+                })
+            })
+            .collect();
+        writer.join().unwrap();
+        readers.into_iter().for_each(|h| h.join().unwrap());
+        assert_eq!(map.get("key999"), Some("value999-49".to_string()));
+    }
+}
+
+#[cfg(test)]
+mod test_rcu_cell {
+    use std::sync::Arc;
+    use std::thread;
+
+    use std_app::collections::RcuCell;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Settings {
+        port: u16,
+        hosts: Vec<String>,
+    }
+
+    #[test]
+    fn test_versions() {
+        let cell = RcuCell::new(Settings {
+            port: 80,
+            hosts: vec!["a".into()],
+        });
+        let before = cell.load();
+        assert_eq!(cell.version(), 0);
+
+        cell.modify(|s| s.hosts.push("b".into()));
+        let next = cell.update(|s| Settings {
+            port: 8080,
+            ..s.clone()
+        });
+        //已经取得的快照不受影响
+        assert_eq!(before.port, 80);
+        assert_eq!(before.hosts.len(), 1);
+        assert_eq!(next.hosts, vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(cell.read(|s| s.port), 8080);
+        assert_eq!(cell.version(), 2);
+
+        let old = cell.store(Settings {
+            port: 1,
+            hosts: vec![],
+        });
+        assert_eq!(old.port, 8080);
+        assert_eq!(cell.version(), 3);
+    }
+
+    //并发 modify 不会丢失更新，读者总是看到一致的版本
+    #[test]
+    fn test_concurrent_writers_and_readers() {
+        let cell = Arc::new(RcuCell::new((0u64, 0u64)));
+        let writers: Vec<_> = (0..4)
+            .map(|_| {
+                let cell = cell.clone();
+                thread::spawn(move || {
+                    for _ in 0..500 {
+                        cell.modify(|(a, b)| {
+                            *a += 1;
+                            *b += 2;
+                        });
+                    }
+                })
+            })
+            .collect();
+        let readers: Vec<_> = (0..8)
+            .map(|_| {
+                let cell = cell.clone();
+                thread::spawn(move || {
+                    for _ in 0..2_000 {
+                        let (a, b) = *cell.load();
+                        assert_eq!(b, a * 2);
+                    }
+                })
+            })
+            .collect();
+        writers.into_iter().for_each(|h| h.join().unwrap());
+        readers.into_iter().for_each(|h| h.join().unwrap());
+        assert_eq!(*cell.load(), (2_000, 4_000));
+        assert_eq!(cell.version(), 2_000);
+    }
+}
@@ -0,0 +1,616 @@
+use serde::{Deserialize, Serialize};
+
+use std_app::config::{Config as _, ConfigError, ConfigFormat, ConfigLoader};
+use std_app::testkit::TestWorkspace;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Config {
+    host: String,
+    port: u16,
+    debug: bool,
+    db: DbConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct DbConfig {
+    url: String,
+    max_connections: u32,
+}
+
+impl std_app::config::Config for Config {}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            host: "127.0.0.1".into(),
+            port: 8080,
+            debug: false,
+            db: DbConfig {
+                url: "sqlite::memory:".into(),
+                max_connections: 10,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_config_loader {
+    use super::*;
+
+    #[test]
+    fn test_defaults_only() {
+        let config: Config = ConfigLoader::new()
+            .defaults(&Config::default())
+            .load()
+            .unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_file_overrides_defaults_per_key() {
+        let ws = TestWorkspace::new().unwrap();
+        let path = ws
+            .file(
+                "config.toml",
+                r#"
+                port = 9000
+                [db]
+                url = "sqlite://app.db"
+                "#,
+            )
+            .unwrap();
+        let config: Config = ConfigLoader::new()
+            .defaults(&Config::default())
+            .file(&path)
+            .load()
+            .unwrap();
+        assert_eq!(config.port, 9000);
+        assert_eq!(config.host, "127.0.0.1");
+        // 嵌套表逐键合并
+        assert_eq!(config.db.url, "sqlite://app.db");
+        assert_eq!(config.db.max_connections, 10);
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let mut ws = TestWorkspace::new().unwrap();
+        let path = ws
+            .file("config.toml", "port = 9000\nhost = \"0.0.0.0\"")
+            .unwrap();
+        ws.set_env("CFGTEST_PORT", "7000");
+        ws.set_env("CFGTEST_HOST", "123");
+        ws.set_env("CFGTEST_DEBUG", "true");
+        ws.set_env("CFGTEST_DB__MAX_CONNECTIONS", "32");
+        // env_prefix 先于文件添加，仍然覆盖文件
+        let config: Config = ConfigLoader::new()
+            .env_prefix("CFGTEST")
+            .defaults(&Config::default())
+            .file(&path)
+            .load()
+            .unwrap();
+        assert_eq!(config.port, 7000);
+        // 原来是字符串的值不会被当作数字
+        assert_eq!(config.host, "123");
+        assert!(config.debug);
+        assert_eq!(config.db.max_connections, 32);
+    }
+
+    #[test]
+    fn test_set_has_highest_precedence() {
+        let mut ws = TestWorkspace::new().unwrap();
+        ws.set_env("CFGSET_PORT", "7000");
+        let config: Config = ConfigLoader::new()
+            .defaults(&Config::default())
+            .env_prefix("CFGSET")
+            .set("port", 6000)
+            .set("db.url", "sqlite://cli.db")
+            .load()
+            .unwrap();
+        assert_eq!(config.port, 6000);
+        assert_eq!(config.db.url, "sqlite://cli.db");
+    }
+
+    #[test]
+    fn test_missing_and_optional_files() {
+        let ws = TestWorkspace::new().unwrap();
+        let err = ConfigLoader::new()
+            .defaults(&Config::default())
+            .file(ws.path("missing.toml"))
+            .load::<Config>()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Io { .. }));
+
+        let config: Config = ConfigLoader::new()
+            .defaults(&Config::default())
+            .optional_file(ws.path("local.toml"))
+            .load()
+            .unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_parse_and_type_errors() {
+        let ws = TestWorkspace::new().unwrap();
+        let bad = ws.file("bad.toml", "port = ").unwrap();
+        let err = ConfigLoader::new().file(&bad).load::<Config>().unwrap_err();
+        assert!(matches!(err, ConfigError::Parse { path, .. } if path == bad));
+
+        let wrong = ws.file("wrong.toml", "port = \"http\"").unwrap();
+        let err = ConfigLoader::new()
+            .defaults(&Config::default())
+            .file(&wrong)
+            .load::<Config>()
+            .unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid(_)));
+    }
+}
+
+#[cfg(test)]
+mod test_config_format {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Server {
+        host: String,
+        port: u16,
+    }
+
+    impl std_app::config::Config for Server {}
+
+    fn expected() -> Server {
+        Server {
+            host: "localhost".into(),
+            port: 8080,
+        }
+    }
+
+    //三种格式解析成同一个结构体
+    #[test]
+    fn test_from_path_detects_format() {
+        let ws = TestWorkspace::new().unwrap();
+        let toml = ws
+            .file("server.toml", "host = \"localhost\"\nport = 8080")
+            .unwrap();
+        let yaml = ws
+            .file("server.yml", "host: localhost\nport: 8080\n")
+            .unwrap();
+        let json = ws
+            .file("server.json", r#"{"host": "localhost", "port": 8080}"#)
+            .unwrap();
+        for path in [toml, yaml, json] {
+            assert_eq!(Server::from_path(&path).unwrap(), expected());
+        }
+    }
+
+    #[test]
+    fn test_layers_mix_formats() {
+        let ws = TestWorkspace::new().unwrap();
+        let base = ws
+            .file("base.toml", "host = \"localhost\"\nport = 80")
+            .unwrap();
+        let local = ws.file("local.yaml", "port: 8080\n").unwrap();
+        let server: Server = ConfigLoader::new().file(base).file(local).load().unwrap();
+        assert_eq!(server, expected());
+    }
+
+    #[test]
+    fn test_unknown_extension_and_explicit_format() {
+        let ws = TestWorkspace::new().unwrap();
+        let path = ws
+            .file("server.conf", "host: localhost\nport: 8080\n")
+            .unwrap();
+        assert!(matches!(
+            Server::from_path(&path),
+            Err(ConfigError::UnknownFormat(p)) if p == path
+        ));
+        let server: Server = ConfigLoader::new()
+            .file_with_format(&path, ConfigFormat::Yaml)
+            .load()
+            .unwrap();
+        assert_eq!(server, expected());
+        assert_eq!(
+            Server::from_str_as(r#"{"host":"localhost","port":8080}"#, ConfigFormat::Json).unwrap(),
+            expected()
+        );
+        assert_eq!(
+            ConfigFormat::from_extension("YML"),
+            Some(ConfigFormat::Yaml)
+        );
+    }
+
+    //YAML 文件中的语法错误报告为 Parse，并带上文件路径
+    #[test]
+    fn test_yaml_parse_error() {
+        let ws = TestWorkspace::new().unwrap();
+        let path = ws.file("server.yaml", "host: [localhost\n").unwrap();
+        let err = Server::from_path(&path).unwrap_err();
+        assert!(matches!(&err, ConfigError::Parse { path: p, .. } if *p == path));
+        assert!(err.to_string().contains("server.yaml"));
+    }
+}
+
+#[cfg(test)]
+mod test_config_validate {
+    use super::*;
+    use std_app::config::{FieldError, Validate, Validator};
+
+    impl Validate for DbConfig {
+        fn validate(&self, v: &mut Validator) {
+            v.field("url", &self.url).not_empty().matches("^sqlite:");
+            v.field("max_connections", self.max_connections)
+                .range(1..=100);
+        }
+    }
+
+    impl Validate for Config {
+        fn validate(&self, v: &mut Validator) {
+            v.field("port", self.port).nonzero();
+            v.field("host", &self.host).not_empty().hostname();
+            v.nested("db", &self.db);
+        }
+    }
+
+    fn field_errors(err: ConfigError) -> Vec<String> {
+        match err {
+            ConfigError::Validation(errors) => errors.into_iter().map(|e| e.field).collect(),
+            other => panic!("期望 Validation 错误，实际 {}", other),
+        }
+    }
+
+    #[test]
+    fn test_valid_config() {
+        assert!(Config::default().check().is_ok());
+        let config = Config {
+            host: "api-1.internal.example.com".into(),
+            ..Default::default()
+        };
+        assert!(config.check().is_ok());
+    }
+
+    //所有失败的字段一起报告
+    #[test]
+    fn test_collects_all_errors() {
+        let config = Config {
+            host: "bad_host!".into(),
+            port: 0,
+            debug: false,
+            db: DbConfig {
+                url: "postgres://db".into(),
+                max_connections: 0,
+            },
+        };
+        let err = config.check().unwrap_err();
+        assert!(err.to_string().contains("port: 不能为 0"));
+        assert_eq!(
+            field_errors(err),
+            vec!["port", "host", "db.url", "db.max_connections"]
+        );
+    }
+
+    #[test]
+    fn test_empty_host_and_custom_rules() {
+        let mut v = Validator::new();
+        v.field("host", "").not_empty().hostname();
+        v.field("level", "verbose")
+            .one_of(&["debug", "info", "warn"]);
+        v.field("workers", 3u32).custom(|n| {
+            if n % 2 == 0 {
+                Ok(())
+            } else {
+                Err("必须是偶数".into())
+            }
+        });
+        v.error("limits", "min 不能大于 max");
+        assert_eq!(
+            v.errors(),
+            &[
+                FieldError {
+                    field: "host".into(),
+                    message: "不能为空".into()
+                },
+                FieldError {
+                    field: "level".into(),
+                    message: "\"verbose\" 不是 debug/info/warn 之一".into()
+                },
+                FieldError {
+                    field: "workers".into(),
+                    message: "必须是偶数".into()
+                },
+                FieldError {
+                    field: "limits".into(),
+                    message: "min 不能大于 max".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_load_validated() {
+        let ws = TestWorkspace::new().unwrap();
+        let path = ws.file("config.toml", "port = 0\nhost = \"\"").unwrap();
+        let err = ConfigLoader::new()
+            .defaults(&Config::default())
+            .file(&path)
+            .load_validated::<Config>()
+            .unwrap_err();
+        assert_eq!(field_errors(err), vec!["port", "host"]);
+    }
+}
+
+#[cfg(test)]
+mod test_config_schema {
+    use super::*;
+    use std_app::config::schema::{self, UnknownKey};
+    use std_app::config::AppConfig;
+
+    #[test]
+    fn test_keys_from_defaults() {
+        let keys = schema::keys::<AppConfig>();
+        let port = keys.iter().find(|k| k.key == "port").unwrap();
+        assert_eq!(port.kind, "integer");
+        assert_eq!(port.default, serde_json::json!(8080));
+        assert_eq!(port.description, Some("监听端口"));
+        let base_url = keys.iter().find(|k| k.key == "http.base_url").unwrap();
+        assert_eq!(base_url.kind, "optional");
+        assert!(keys.iter().any(|k| k.key == "db.max_connections"));
+        // 嵌套表本身不是键
+        assert!(keys.iter().all(|k| k.key != "db"));
+    }
+
+    #[test]
+    fn test_unknown_keys_with_suggestion() {
+        let config = serde_json::json!({
+            "prot": 80,
+            "db": { "max_conections": 5, "url": "sqlite::memory:" },
+            "totally_unrelated": true
+        });
+        let unknown = schema::unknown_keys::<AppConfig>(&config);
+        assert_eq!(
+            unknown,
+            vec![
+                UnknownKey {
+                    key: "db.max_conections".into(),
+                    suggestion: Some("db.max_connections".into())
+                },
+                UnknownKey {
+                    key: "prot".into(),
+                    suggestion: Some("port".into())
+                },
+                UnknownKey {
+                    key: "totally_unrelated".into(),
+                    suggestion: None
+                },
+            ]
+        );
+        assert_eq!(unknown[1].to_string(), "未知的键 prot，是不是 port?");
+    }
+
+    #[test]
+    fn test_check_file() {
+        let ws = TestWorkspace::new().unwrap();
+        let good = ws
+            .file("good.toml", "port = 9000\n[db]\nurl = \"sqlite://x.db\"")
+            .unwrap();
+        assert!(schema::check_file::<AppConfig>(&good).is_ok());
+
+        let bad = ws
+            .file(
+                "bad.toml",
+                "port = 0\nlog_levle = \"info\"\n[db]\nmax_connections = 0",
+            )
+            .unwrap();
+        let report = schema::check_file::<AppConfig>(&bad);
+        assert!(!report.is_ok());
+        assert_eq!(report.unknown.len(), 1);
+        assert_eq!(report.unknown[0].suggestion.as_deref(), Some("log_level"));
+        assert_eq!(report.errors.len(), 2);
+        assert!(report.errors[0].contains("port"));
+
+        // 类型错误和语法错误同样报告
+        let wrong_type = ws.file("type.toml", "port = \"abc\"").unwrap();
+        assert_eq!(schema::check_file::<AppConfig>(&wrong_type).errors.len(), 1);
+        let broken = ws.file("broken.toml", "port = ").unwrap();
+        assert_eq!(schema::check_file::<AppConfig>(&broken).errors.len(), 1);
+    }
+
+    #[test]
+    fn test_reference() {
+        let docs = schema::reference::<AppConfig>();
+        assert!(docs.starts_with("| 键 | 类型 | 默认值 | 说明 |"));
+        assert!(docs.contains("| port | integer | `8080` | 监听端口 |"));
+        assert!(docs.contains("| http.base_url | optional | - |"));
+    }
+}
+
+#[cfg(test)]
+mod test_config_profile {
+    use super::*;
+    use std_app::config::PROFILE_ENV;
+
+    fn workspace() -> TestWorkspace {
+        let mut ws = TestWorkspace::new().unwrap();
+        // 同时拿到环境变量锁，避免和其他修改 APP_ 变量的测试交错
+        ws.remove_env(PROFILE_ENV);
+        ws.file(
+            "config.toml",
+            "host = \"base\"\nport = 8000\ndebug = false\n[db]\nurl = \"sqlite://base.db\"\nmax_connections = 5",
+        )
+        .unwrap();
+        ws.file("config.prod.toml", "port = 80\n[db]\nmax_connections = 50")
+            .unwrap();
+        ws
+    }
+
+    #[test]
+    fn test_profile_overrides_base() {
+        let ws = workspace();
+        let config = Config::load_for_profile_in(ws.root(), Some("prod")).unwrap();
+        assert_eq!(config.host, "base");
+        assert_eq!(config.port, 80);
+        assert_eq!(config.db.url, "sqlite://base.db");
+        assert_eq!(config.db.max_connections, 50);
+    }
+
+    #[test]
+    fn test_profile_from_env() {
+        let mut ws = workspace();
+        let config = Config::load_for_profile_in(ws.root(), None).unwrap();
+        assert_eq!(config.port, 8000);
+
+        ws.set_env(PROFILE_ENV, "prod");
+        ws.set_env("APP_PORT", "443");
+        let config = Config::load_for_profile_in(ws.root(), None).unwrap();
+        assert_eq!(config.port, 443);
+        assert_eq!(config.db.max_connections, 50);
+    }
+
+    #[test]
+    fn test_profile_env_is_not_a_config_key() {
+        let mut ws = workspace();
+        ws.set_env(PROFILE_ENV, "prod");
+        let merged = ConfigLoader::new()
+            .profile(ws.root(), Some("prod"))
+            .unwrap()
+            .env_prefix("APP")
+            .merged()
+            .unwrap();
+        assert!(merged.get("env").is_none());
+    }
+
+    #[test]
+    fn test_missing_profile_file_uses_base() {
+        let ws = workspace();
+        let config = Config::load_for_profile_in(ws.root(), Some("dev")).unwrap();
+        assert_eq!(config.port, 8000);
+    }
+
+    #[test]
+    fn test_invalid_profile_name() {
+        let ws = workspace();
+        let err = Config::load_for_profile_in(ws.root(), Some("../etc")).unwrap_err();
+        assert!(matches!(err, ConfigError::Invalid(_)));
+    }
+}
+
+#[cfg(test)]
+mod test_config_secrets {
+    use super::*;
+
+    #[test]
+    fn test_env_and_file_placeholders() {
+        let mut ws = TestWorkspace::new().unwrap();
+        ws.set_env("TEST_SECRET_DB_HOST", "db.internal");
+        let secret = ws.file("db_pass", "s3cret\n").unwrap();
+        let path = ws
+            .file(
+                "config.toml",
+                format!(
+                    "[db]\nurl = \"sqlite://${{TEST_SECRET_DB_HOST}}/app?pass=${{file:{}}}\"",
+                    secret.display()
+                ),
+            )
+            .unwrap();
+        let config: Config = ConfigLoader::new()
+            .defaults(&Config::default())
+            .file(&path)
+            .load()
+            .unwrap();
+        assert_eq!(config.db.url, "sqlite://db.internal/app?pass=s3cret");
+    }
+
+    #[test]
+    fn test_escaped_placeholder() {
+        let config: Config = ConfigLoader::new()
+            .defaults(&Config::default())
+            .set("host", "$${NOT_EXPANDED}")
+            .load()
+            .unwrap();
+        assert_eq!(config.host, "${NOT_EXPANDED}");
+    }
+
+    #[test]
+    fn test_missing_secret() {
+        let mut ws = TestWorkspace::new().unwrap();
+        ws.remove_env("TEST_SECRET_MISSING");
+        let err = ConfigLoader::new()
+            .defaults(&Config::default())
+            .set("db.url", "sqlite://${TEST_SECRET_MISSING}")
+            .load::<Config>()
+            .unwrap_err();
+        match err {
+            ConfigError::MissingSecret {
+                key, placeholder, ..
+            } => {
+                assert_eq!(key, "db.url");
+                assert_eq!(placeholder, "TEST_SECRET_MISSING");
+            }
+            other => panic!("期望 MissingSecret，实际 {}", other),
+        }
+
+        let err = ConfigLoader::new()
+            .set("host", "${file:/nonexistent/secret}")
+            .merged()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("配置项 host 的占位符 ${file:/nonexistent/secret} 无法解析"));
+    }
+}
+
+#[cfg(test)]
+mod test_config_default_file {
+    use super::*;
+    use std_app::config::{AppConfig, Documented};
+    use std_app::json::Schema;
+
+    #[test]
+    fn test_write_default_round_trip() {
+        let ws = TestWorkspace::new().unwrap();
+        for name in ["app.toml", "app.yaml", "app.json"] {
+            let path = ws.path(name);
+            AppConfig::write_default(&path).unwrap();
+            let loaded = AppConfig::from_path(&path).unwrap();
+            assert_eq!(loaded, AppConfig::default(), "{}", name);
+        }
+        let text = std::fs::read_to_string(ws.path("app.toml")).unwrap();
+        assert!(text.contains("# 监听端口\nport = 8080\n"));
+        assert!(text.contains("\n[db]\n"));
+        assert!(text.contains("# base_url =\n"));
+    }
+
+    #[test]
+    fn test_write_default_does_not_overwrite() {
+        let ws = TestWorkspace::new().unwrap();
+        let path = ws.file("app.toml", "port = 1").unwrap();
+        let err = AppConfig::write_default(&path).unwrap_err();
+        assert!(matches!(err, ConfigError::Io { .. }));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "port = 1");
+        assert!(matches!(
+            AppConfig::write_default(ws.path("app.ini")),
+            Err(ConfigError::UnknownFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema = AppConfig::schema();
+        assert_eq!(schema["properties"]["port"]["type"], "integer");
+        assert_eq!(schema["properties"]["port"]["default"], 8080);
+        assert_eq!(schema["properties"]["port"]["description"], "监听端口");
+        assert_eq!(
+            schema["properties"]["db"]["properties"]["url"]["type"],
+            "string"
+        );
+        assert_eq!(schema["additionalProperties"], false);
+
+        let compiled = Schema::compile(&schema).unwrap();
+        let defaults = serde_json::to_value(AppConfig::default()).unwrap();
+        assert!(compiled.validate(&defaults).is_ok());
+        assert!(compiled
+            .validate(&serde_json::json!({ "port": "80" }))
+            .is_err());
+        assert!(compiled
+            .validate(&serde_json::json!({ "db": { "uri": "x" } }))
+            .is_err());
+    }
+}
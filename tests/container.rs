@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use std_app::container::{ContainerError, ServiceContainer};
+use std_app::context::RequestContext;
+
+struct Config {
+    db_url: String,
+}
+
+struct Pool {
+    url: String,
+}
+
+// 每个请求一个，持有请求上下文和共享连接池
+struct UserService {
+    pool: Arc<Pool>,
+    ctx: Arc<RequestContext>,
+}
+
+static POOLS_CREATED: AtomicUsize = AtomicUsize::new(0);
+
+fn container() -> ServiceContainer {
+    ServiceContainer::builder()
+        .instance(Config {
+            db_url: "sqlite::memory:".to_string(),
+        })
+        .singleton(|c| {
+            POOLS_CREATED.fetch_add(1, Ordering::SeqCst);
+            Ok(Pool {
+                url: c.resolve::<Config>()?.db_url.clone(),
+            })
+        })
+        .scoped(|_| Ok(RequestContext::new()))
+        .scoped(|c| {
+            Ok(UserService {
+                pool: c.resolve()?,
+                ctx: c.resolve()?,
+            })
+        })
+        .build()
+}
+
+#[cfg(test)]
+mod test_container {
+    use super::*;
+
+    #[test]
+    fn test_lifetimes() -> Result<(), ContainerError> {
+        let container = container();
+        assert_eq!(container.services().len(), 4);
+        assert!(container.contains::<Pool>());
+        let pool = container.resolve::<Pool>()?;
+        assert_eq!(pool.url, "sqlite::memory:");
+
+        let scope1 = container.scope();
+        let scope2 = container.scope();
+        let a = scope1.resolve::<UserService>()?;
+        let b = scope1.resolve::<UserService>()?;
+        let c = scope2.resolve::<UserService>()?;
+
+        // 同一作用域内复用，不同作用域各自构造，连接池全局只有一个
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+        assert_ne!(a.ctx.request_id, c.ctx.request_id);
+        assert!(Arc::ptr_eq(&a.pool, &c.pool));
+        assert_eq!(POOLS_CREATED.load(Ordering::SeqCst), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        let container = container();
+        assert_eq!(
+            container.resolve::<UserService>().err(),
+            Some(ContainerError::ScopeRequired(std::any::type_name::<
+                UserService,
+            >()))
+        );
+        assert!(matches!(
+            container.resolve::<String>(),
+            Err(ContainerError::NotRegistered(_))
+        ));
+
+        struct A;
+        struct B;
+        let cyclic = ServiceContainer::builder()
+            .singleton(|c| c.resolve::<B>().map(|_| A))
+            .singleton(|c| c.resolve::<A>().map(|_| B))
+            .build();
+        assert!(matches!(
+            cyclic.resolve::<A>(),
+            Err(ContainerError::Cycle(_))
+        ));
+    }
+}
@@ -0,0 +1,169 @@
+use std::time::Duration;
+
+use sqlx::sqlite::SqlitePoolOptions;
+
+use std_app::cache::TtlCache;
+use std_app::context::costs::{self, CostTracker};
+use std_app::context::{self, RequestContext};
+use std_app::db::timeout;
+use std_app::http::HttpClient;
+use std_app::logscan::{self, Filter};
+use std_app::testkit::http::MockHttp;
+
+#[cfg(test)]
+mod test_context {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_task_scope() {
+        assert_eq!(context::current(), None);
+
+        let ctx = RequestContext::with_request_id("req-1")
+            .user("42")
+            .locale("zh-CN");
+        context::scope(ctx.clone(), async {
+            let current = context::current().unwrap();
+            assert_eq!(current.request_id, "req-1");
+            assert_eq!(
+                current.to_string(),
+                "request_id=req-1 user_id=42 locale=zh-CN"
+            );
+
+            // 通过 context::spawn 启动的任务会继承上下文
+            let inherited = context::spawn(async { context::current() }).await.unwrap();
+            assert_eq!(inherited.map(|c| c.request_id), Some("req-1".to_string()));
+
+            // 直接 tokio::spawn 则不会
+            let plain = tokio::spawn(async { context::current() }).await.unwrap();
+            assert_eq!(plain, None);
+        })
+        .await;
+
+        assert_eq!(context::current(), None);
+    }
+
+    #[test]
+    fn test_sync_scope_nesting() {
+        context::sync_scope(RequestContext::with_request_id("outer"), || {
+            context::sync_scope(RequestContext::with_request_id("inner"), || {
+                assert_eq!(context::current().unwrap().request_id, "inner");
+            });
+            assert_eq!(context::current().unwrap().request_id, "outer");
+        });
+        assert_eq!(context::current(), None);
+    }
+
+    #[test]
+    fn test_deadline_and_headers() {
+        let ctx = RequestContext::new()
+            .user("7")
+            .timeout(Duration::from_secs(60));
+        assert!(!ctx.is_expired());
+        assert!(ctx.remaining().unwrap() > Duration::from_secs(59));
+        assert!(RequestContext::new().timeout(Duration::ZERO).is_expired());
+
+        let headers = ctx.headers();
+        assert_eq!(headers[0].0, "x-request-id");
+        assert_eq!(headers[1], ("x-user-id", "7".to_string()));
+
+        // 自动生成的 request id 不重复
+        assert_ne!(
+            RequestContext::new().request_id,
+            RequestContext::new().request_id
+        );
+    }
+
+    #[test]
+    fn test_inject_headers() {
+        let client = reqwest::Client::new();
+        let request = context::sync_scope(RequestContext::with_request_id("abc"), || {
+            context::inject(client.get("http://127.0.0.1/"))
+                .build()
+                .unwrap()
+        });
+        assert_eq!(request.headers()["x-request-id"], "abc");
+        assert!(request.headers().get("x-user-id").is_none());
+    }
+}
+
+#[cfg(test)]
+mod test_costs {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_downstream_costs_are_accumulated() {
+        let pool = SqlitePoolOptions::new()
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let cache: TtlCache<&str, i32> = TtlCache::new(Duration::from_secs(60));
+        cache.insert("a", 1);
+        let server = MockHttp::start().await.unwrap();
+        let client = HttpClient::new(server.url());
+
+        let ctx = RequestContext::with_request_id("costs-1").track_costs();
+        let summary = context::scope(ctx, async {
+            for _ in 0..2 {
+                timeout::run(&pool, None, |conn| {
+                    Box::pin(sqlx::query_scalar::<_, i64>("SELECT 1").fetch_one(conn))
+                })
+                .await
+                .unwrap();
+            }
+            assert_eq!(cache.get(&"a"), Some(1));
+            assert_eq!(cache.get(&"b"), None);
+            // spawn 出去的任务记到同一个请求上
+            let client = client.clone();
+            context::spawn(
+                async move { client.send(client.request(reqwest::Method::GET, "/")).await },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+            context::current().unwrap().cost_summary().unwrap()
+        })
+        .await;
+        assert_eq!(summary.db_queries, 2);
+        assert_eq!((summary.cache_hits, summary.cache_misses), (1, 1));
+        assert_eq!(summary.http_calls, 1);
+        assert!(summary.http_time > Duration::ZERO);
+
+        //没有开启统计时不记录
+        cache.get(&"a");
+        assert_eq!(costs::current(), None);
+    }
+
+    #[tokio::test]
+    async fn test_instrument_logs_summary() {
+        let cache: TtlCache<&str, i32> = TtlCache::new(Duration::from_secs(60));
+        let ctx = RequestContext::with_request_id("costs-2");
+        let value = costs::instrument("GET /items", ctx, async {
+            costs::record_db(Duration::from_millis(5));
+            cache.get(&"missing");
+            7
+        })
+        .await;
+        assert_eq!(value, 7);
+
+        let lines = logscan::recent(&Filter::new().field("request_id", "costs-2"));
+        assert_eq!(lines.len(), 1);
+        let message = &lines[0].message;
+        assert!(
+            message.starts_with("GET /items 完成 elapsed_ms="),
+            "{}",
+            message
+        );
+        assert!(message.contains("db_queries=1 db_ms=5.0 cache_hits=0 cache_misses=1"));
+        assert!(message.contains("http_calls=0"));
+    }
+
+    #[test]
+    fn test_tracker_identity() {
+        let tracker = CostTracker::new();
+        let shared = tracker.clone();
+        shared.cache(true);
+        assert_eq!(tracker.summary().cache_hits, 1);
+        assert_eq!(tracker, shared);
+        assert_ne!(tracker, CostTracker::new());
+    }
+}
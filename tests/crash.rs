@@ -0,0 +1,71 @@
+use serde::Serialize;
+
+use std_app::crash::{self, CrashReporter};
+use std_app::testkit::TestWorkspace;
+
+#[derive(Serialize)]
+struct Config {
+    host: String,
+    password: String,
+}
+
+fn config(password: &str) -> Config {
+    Config {
+        host: "localhost".to_string(),
+        password: password.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test_crash {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint() {
+        let a = crash::fingerprint(&config("secret1")).unwrap();
+        assert_eq!(a, crash::fingerprint(&config("secret1")).unwrap());
+        assert_ne!(a, crash::fingerprint(&config("secret2")).unwrap());
+        assert_eq!(a.len(), 16);
+    }
+
+    //panic 时写入的报告包含消息、位置、脱敏后的配置和最近日志
+    #[test]
+    fn test_panic_hook_writes_report() {
+        let ws = TestWorkspace::new().unwrap();
+        CrashReporter::new(ws.path("crashes"))
+            .config(&config("hunter2"))
+            .unwrap()
+            .recent_logs(|| vec!["INFO starting".to_string(), "WARN slow query".to_string()])
+            .install();
+
+        let result = std::thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(|| panic!("数据库连接丢失"))
+            .unwrap()
+            .join();
+        assert!(result.is_err());
+        let _ = std::panic::take_hook();
+
+        let files: Vec<_> = std::fs::read_dir(ws.path("crashes"))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        let text = std::fs::read_to_string(&files[0]).unwrap();
+        assert!(!text.contains("hunter2"));
+
+        let report: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(report["message"], "数据库连接丢失");
+        assert_eq!(report["thread"], "worker");
+        assert!(report["location"]
+            .as_str()
+            .unwrap()
+            .contains("tests/crash.rs"));
+        assert_eq!(report["config"]["host"], "localhost");
+        assert_eq!(report["recent_logs"][1], "WARN slow query");
+        assert_eq!(
+            report["config_fingerprint"],
+            crash::fingerprint(&config("hunter2")).unwrap()
+        );
+    }
+}
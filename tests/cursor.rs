@@ -5,7 +5,7 @@ use std::io::{Read, Seek, SeekFrom, Write};
 mod tests {
     use serde::Deserialize;
     use serde::Serialize;
-    use std::time::Instant;
+    use std_app::bench::Bench;
 
     use super::*;
 
@@ -145,19 +145,19 @@ mod tests {
         let data = vec![1u8; size];
 
         // 使用Vec直接操作
-        let start = Instant::now();
-        let mut vec = Vec::new();
-        vec.extend_from_slice(&data);
-        let vec_time = start.elapsed();
+        let vec_report = Bench::new("vec").warmup(2).iterations(10).run(|| {
+            let mut vec = Vec::new();
+            vec.extend_from_slice(&data);
+        });
 
         // 使用Cursor操作
-        let start = Instant::now();
-        let mut cursor = Cursor::new(Vec::with_capacity(size));
-        cursor.write_all(&data)?;
-        let cursor_time = start.elapsed();
+        let cursor_report = Bench::new("cursor").warmup(2).iterations(10).run(|| {
+            let mut cursor = Cursor::new(Vec::with_capacity(size));
+            cursor.write_all(&data).unwrap();
+        });
 
-        println!("Vec time: {:?}", vec_time);
-        println!("Cursor time: {:?}", cursor_time);
+        println!("{}", vec_report);
+        println!("{}", cursor_report);
 
         Ok(())
     }
@@ -0,0 +1,121 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use std_app::app::BoxError;
+use std_app::dag::{self, DagError, NodeStatus, OnError};
+use std_app::pool::jobs::{JobContext, JobPool};
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+// 记录执行顺序的任务
+fn record(
+    log: &Log,
+    name: &'static str,
+) -> impl FnOnce(&JobContext) -> Result<(), BoxError> + Send + 'static {
+    let log = log.clone();
+    move |_| {
+        thread::sleep(Duration::from_millis(5));
+        log.lock().unwrap().push(name.to_string());
+        Ok(())
+    }
+}
+
+fn position(log: &Log, name: &str) -> usize {
+    log.lock().unwrap().iter().position(|n| n == name).unwrap()
+}
+
+#[cfg(test)]
+mod test_dag {
+    use super::*;
+
+    #[test]
+    fn test_runs_in_topological_order() {
+        let pool = JobPool::new(4);
+        let log: Log = Arc::default();
+        //fetch → (parse, checksum) → import → index
+        let report = dag::dag()
+            .job("index", &["import"], record(&log, "index"))
+            .job("import", &["parse", "checksum"], record(&log, "import"))
+            .job("parse", &["fetch"], record(&log, "parse"))
+            .job("checksum", &["fetch"], record(&log, "checksum"))
+            .job("fetch", &[], record(&log, "fetch"))
+            .run(&pool, OnError::FailFast)
+            .unwrap();
+        assert!(report.is_success());
+        assert!(position(&log, "fetch") < position(&log, "parse"));
+        assert!(position(&log, "checksum") < position(&log, "import"));
+        assert!(position(&log, "parse") < position(&log, "import"));
+        assert_eq!(position(&log, "index"), 4);
+
+        //每个任务都有耗时，下游任务在上游结束后才开始
+        let fetch = report.get("fetch").unwrap();
+        let import = report.get("import").unwrap();
+        assert!(fetch.duration.unwrap() >= Duration::from_millis(5));
+        assert!(import.started_at.unwrap() >= fetch.started_at.unwrap() + fetch.duration.unwrap());
+        assert_eq!(report.nodes[0].name, "index");
+    }
+
+    #[test]
+    fn test_invalid_graphs() {
+        let pool = JobPool::new(1);
+        let noop = |_: &_| Ok(());
+        assert!(matches!(
+            dag::dag().job("a", &[], noop).job("a", &[], noop).order(),
+            Err(DagError::Duplicate(name)) if name == "a"
+        ));
+        assert!(matches!(
+            dag::dag().job("a", &["b"], noop).run(&pool, OnError::FailFast),
+            Err(DagError::UnknownDependency { job, dependency }) if job == "a" && dependency == "b"
+        ));
+        assert!(matches!(
+            dag::dag()
+                .job("a", &["c"], noop)
+                .job("b", &["a"], noop)
+                .job("c", &["b"], noop)
+                .job("d", &[], noop)
+                .order(),
+            Err(DagError::Cycle(names)) if names == vec!["a", "b", "c"]
+        ));
+    }
+
+    #[test]
+    fn test_continue_on_error_skips_only_dependents() {
+        let pool = JobPool::new(2);
+        let log: Log = Arc::default();
+        let report = dag::dag()
+            .job("fetch", &[], |_| Err("连接被拒绝".into()))
+            .job("parse", &["fetch"], record(&log, "parse"))
+            .job("import", &["parse"], record(&log, "import"))
+            .job("thumbnails", &[], record(&log, "thumbnails"))
+            .job("report", &["thumbnails"], record(&log, "report"))
+            .run(&pool, OnError::Continue)
+            .unwrap();
+        assert!(!report.is_success());
+        assert_eq!(report.failed(), vec!["fetch"]);
+        assert_eq!(
+            report.get("fetch").unwrap().status,
+            NodeStatus::Failed("连接被拒绝".into())
+        );
+        assert_eq!(report.skipped(), vec!["parse", "import"]);
+        assert_eq!(report.get("parse").unwrap().started_at, None);
+        assert_eq!(*log.lock().unwrap(), vec!["thumbnails", "report"]);
+    }
+
+    #[test]
+    fn test_fail_fast_cancels_pending() {
+        let pool = JobPool::new(1);
+        let log: Log = Arc::default();
+        let report = dag::dag()
+            .job("a", &[], |_| -> Result<(), _> { panic!("坏数据") })
+            .job("b", &[], record(&log, "b"))
+            .job("c", &["b"], record(&log, "c"))
+            .run(&pool, OnError::FailFast)
+            .unwrap();
+        assert_eq!(report.failed(), vec!["a"]);
+        //b 已经提交，仍然执行完；c 不再启动
+        assert_eq!(report.get("b").unwrap().status, NodeStatus::Succeeded);
+        assert_eq!(report.get("c").unwrap().status, NodeStatus::Cancelled);
+        assert_eq!(*log.lock().unwrap(), vec!["b"]);
+    }
+}
@@ -0,0 +1,109 @@
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+
+use std_app::db::{self, DbError};
+
+async fn pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::query(
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, customer TEXT NOT NULL, total INTEGER NOT NULL);
+         CREATE INDEX orders_customer ON orders (customer);
+         CREATE TABLE customers (name TEXT PRIMARY KEY, region TEXT)",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool
+}
+
+#[cfg(test)]
+mod test_db_plan {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_index_search() {
+        let pool = pool().await;
+        let plan = db::explain(&pool, "SELECT * FROM orders WHERE customer = ?")
+            .await
+            .unwrap();
+        assert_eq!(plan.nodes.len(), 1);
+        assert!(plan.nodes[0]
+            .detail
+            .starts_with("SEARCH orders USING INDEX orders_customer"));
+        assert!(plan.full_scans().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_full_scan_and_sort() {
+        let pool = pool().await;
+        let plan = db::explain(
+            &pool,
+            "SELECT * FROM orders WHERE total > 100 ORDER BY total",
+        )
+        .await
+        .unwrap();
+        assert_eq!(plan.full_scans(), vec!["orders"]);
+        assert!(plan.uses_temp_btree());
+        assert!(plan.to_string().starts_with("QUERY PLAN\n  SCAN orders"));
+    }
+
+    //子查询的计划挂在父节点下
+    #[tokio::test]
+    async fn test_nested_plan() {
+        let pool = pool().await;
+        let plan = db::explain(
+            &pool,
+            "SELECT * FROM orders WHERE customer IN (SELECT name FROM customers WHERE region = 'eu')",
+        )
+        .await
+        .unwrap();
+        assert!(plan.all().len() > plan.nodes.len());
+        assert!(plan.nodes.iter().any(|n| !n.children.is_empty()));
+        assert_eq!(plan.full_scans(), vec!["customers"]);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_query() {
+        let pool = pool().await;
+        assert!(matches!(
+            db::explain(&pool, "SELECT * FROM missing").await,
+            Err(DbError::Query(_))
+        ));
+    }
+
+    // 第二条语句不会被执行
+    #[tokio::test]
+    async fn test_multiple_statements_rejected() {
+        let pool = pool().await;
+        sqlx::query("INSERT INTO orders (customer, total) VALUES ('a;b', 1)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for query in [
+            "SELECT 1; DELETE FROM orders",
+            "SELECT 1;DELETE FROM orders;",
+            "SELECT 1 -- x\n; DELETE FROM orders",
+        ] {
+            assert!(matches!(
+                db::explain(&pool, query).await,
+                Err(DbError::Rejected(_))
+            ));
+        }
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM orders")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+
+        // 末尾的分号和字符串里的分号可以接受
+        assert!(
+            db::explain(&pool, "SELECT * FROM orders WHERE customer = 'a;b';")
+                .await
+                .is_ok()
+        );
+    }
+}
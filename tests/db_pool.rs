@@ -0,0 +1,65 @@
+use std::time::Duration;
+
+use std_app::db::pool::Pool;
+use std_app::db::users::{NewUser, UserRepository};
+use std_app::db::DbError;
+use std_app::testkit::TestWorkspace;
+
+fn url(ws: &TestWorkspace) -> String {
+    format!("sqlite://{}", ws.path("app.db").display())
+}
+
+#[cfg(test)]
+mod test_db_pool {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_warm_up() {
+        let ws = TestWorkspace::new().unwrap();
+        let pool = Pool::builder(&url(&ws))
+            .max_connections(4)
+            .connect()
+            .await
+            .unwrap();
+        assert_eq!(pool.warm_up(3).await.unwrap(), 3);
+        //不超过连接池上限
+        assert_eq!(pool.warm_up(10).await.unwrap(), 4);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(pool.idle(), 4);
+    }
+
+    //内存数据库只使用一个连接
+    #[tokio::test]
+    async fn test_memory_single_connection() {
+        let pool = Pool::builder("sqlite::memory:")
+            .max_connections(8)
+            .pre_ping(true)
+            .connect()
+            .await
+            .unwrap();
+        assert_eq!(pool.warm_up(8).await.unwrap(), 1);
+
+        let repo = UserRepository::new(pool.clone().into());
+        repo.migrate().await.unwrap();
+        let user = repo
+            .create(&NewUser {
+                name: "alice".to_string(),
+                email: "alice@example.com".to_string(),
+            })
+            .await
+            .unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT count(*) FROM users")
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+        assert_eq!((user.id, count), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_invalid_url() {
+        assert!(matches!(
+            Pool::connect("postgres://localhost/app").await,
+            Err(DbError::Connection(_))
+        ));
+    }
+}
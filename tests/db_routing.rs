@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+
+use std_app::db::pool::Pool;
+use std_app::db::routing::Router;
+use std_app::db::users::{NewUser, UserRepository};
+use std_app::db::DbError;
+
+async fn database(name: &str) -> SqlitePool {
+    let pool: SqlitePool = Pool::connect("sqlite::memory:").await.unwrap().into();
+    UserRepository::new(pool.clone()).migrate().await.unwrap();
+    // 用不同的数据区分查询落到了哪个库
+    sqlx::query("INSERT INTO users (name, email) VALUES (?, ?)")
+        .bind(name)
+        .bind(format!("{}@example.com", name))
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool
+}
+
+async fn router() -> Router {
+    Router::new(
+        database("primary").await,
+        vec![
+            ("replica-a".to_string(), database("a").await),
+            ("replica-b".to_string(), database("b").await),
+        ],
+    )
+}
+
+async fn first_name(repo: &UserRepository) -> String {
+    repo.get(1).await.unwrap().name
+}
+
+#[cfg(test)]
+mod test_db_routing {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_reads_round_robin() {
+        let repo = UserRepository::with_router(router().await);
+        let mut names = vec![first_name(&repo).await, first_name(&repo).await];
+        names.sort();
+        assert_eq!(names, vec!["a", "b"]);
+        assert_eq!(first_name(&repo.primary()).await, "primary");
+    }
+
+    //写入主库，写后读也从主库读取
+    #[tokio::test]
+    async fn test_writes_go_to_primary() -> Result<(), DbError> {
+        let repo = UserRepository::with_router(router().await);
+        let user = repo
+            .create(&NewUser {
+                name: "carol".to_string(),
+                email: "carol@example.com".to_string(),
+            })
+            .await?;
+        assert_eq!(user.id, 2);
+        //副本上还没有这条记录
+        assert!(matches!(repo.get(2).await, Err(DbError::NotFound(2))));
+        assert_eq!(repo.primary().get(2).await?, user);
+        Ok(())
+    }
+
+    //不健康的副本被跳过，全部不可用时回落到主库
+    #[tokio::test]
+    async fn test_health_checks() {
+        let replica = database("a").await;
+        let router = Router::new(
+            database("primary").await,
+            vec![("replica-a".to_string(), replica.clone())],
+        );
+        let repo = UserRepository::with_router(router.clone());
+        assert_eq!(first_name(&repo).await, "a");
+
+        replica.close().await;
+        assert_eq!(router.check_health(Duration::from_secs(1)).await, 0);
+        assert!(router.healthy_replicas().is_empty());
+        assert_eq!(first_name(&repo).await, "primary");
+    }
+}
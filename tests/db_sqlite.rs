@@ -0,0 +1,100 @@
+use sqlx::sqlite::SqlitePoolOptions;
+
+use std_app::db::pool::Pool;
+use std_app::db::sqlite::{self, CheckpointMode, Profile};
+use std_app::testkit::TestWorkspace;
+
+fn url(ws: &TestWorkspace) -> String {
+    format!("sqlite://{}", ws.path("app.db").display())
+}
+
+#[cfg(test)]
+mod test_db_sqlite {
+    use super::*;
+
+    //连接池的每个连接都应用了配置
+    #[tokio::test]
+    async fn test_pool_profile() {
+        let ws = TestWorkspace::new().unwrap();
+        let pool = Pool::builder(&url(&ws))
+            .max_connections(2)
+            .profile(Profile::Server)
+            .connect()
+            .await
+            .unwrap();
+        let mut a = pool.acquire().await.unwrap();
+        let mut b = pool.acquire().await.unwrap();
+        for conn in [&mut a, &mut b] {
+            let report = sqlite::verify(conn, Profile::Server).await.unwrap();
+            assert!(report.is_ok(), "{:?}", report);
+        }
+        let report = sqlite::verify(&mut a, Profile::Embedded).await.unwrap();
+        let pragmas: Vec<_> = report.mismatches.iter().map(|m| m.pragma).collect();
+        assert_eq!(
+            pragmas,
+            vec!["synchronous", "busy_timeout", "cache_size", "mmap_size"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_profile() {
+        let ws = TestWorkspace::new().unwrap();
+        let pool = Pool::connect(&url(&ws)).await.unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        let report = sqlite::apply_profile(&mut conn, Profile::Embedded)
+            .await
+            .unwrap();
+        assert!(report.is_ok(), "{:?}", report);
+    }
+
+    //内存数据库不支持 WAL 和 mmap
+    #[tokio::test]
+    async fn test_memory_reports_mismatch() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let mut conn = pool.acquire().await.unwrap();
+        let report = sqlite::apply_profile(&mut conn, Profile::Server)
+            .await
+            .unwrap();
+        let pragmas: Vec<_> = report.mismatches.iter().map(|m| m.pragma).collect();
+        assert_eq!(pragmas, vec!["journal_mode", "mmap_size"]);
+        assert_eq!(report.mismatches[0].actual.as_deref(), Some("memory"));
+        assert_eq!(report.mismatches[1].actual, None);
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint() {
+        let ws = TestWorkspace::new().unwrap();
+        let pool = Pool::builder(&url(&ws))
+            .profile(Profile::Server)
+            .connect()
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE t (x INTEGER)")
+            .execute(&*pool)
+            .await
+            .unwrap();
+        for i in 0..10 {
+            sqlx::query("INSERT INTO t VALUES (?)")
+                .bind(i)
+                .execute(&*pool)
+                .await
+                .unwrap();
+        }
+        let passive = sqlite::checkpoint(&pool, CheckpointMode::Passive)
+            .await
+            .unwrap();
+        assert!(!passive.busy);
+        assert!(passive.log_frames > 0);
+        assert_eq!(passive.checkpointed_frames, passive.log_frames);
+
+        let truncated = sqlite::checkpoint(&pool, CheckpointMode::Truncate)
+            .await
+            .unwrap();
+        assert_eq!(truncated.log_frames, 0);
+        assert_eq!(std::fs::metadata(ws.path("app.db-wal")).unwrap().len(), 0);
+    }
+}
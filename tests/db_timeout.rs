@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{SqliteConnection, SqlitePool};
+
+use std_app::context::{self, RequestContext};
+use std_app::db::timeout::{self, QueryFuture};
+use std_app::db::DbError;
+use std_app::testkit::TestWorkspace;
+
+// 文件数据库，连接被分离后新建的连接仍然能看到数据
+async fn file_pool(ws: &TestWorkspace) -> SqlitePool {
+    let options = SqliteConnectOptions::new()
+        .filename(ws.path("app.db"))
+        .create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .unwrap();
+    sqlx::query("CREATE TABLE items (id INTEGER PRIMARY KEY)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO items (id) VALUES (1), (2)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool
+}
+
+// 需要执行很久的查询
+fn slow(conn: &mut SqliteConnection) -> QueryFuture<'_, i64> {
+    Box::pin(
+        sqlx::query_scalar(
+            "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < 1000000000)
+             SELECT count(*) FROM c",
+        )
+        .fetch_one(conn),
+    )
+}
+
+fn count(conn: &mut SqliteConnection) -> QueryFuture<'_, i64> {
+    Box::pin(sqlx::query_scalar("SELECT count(*) FROM items").fetch_one(conn))
+}
+
+#[cfg(test)]
+mod test_db_timeout {
+    use super::*;
+
+    //只有一个连接，超时后连接池仍然可用
+    #[tokio::test]
+    async fn test_timeout() {
+        let ws = TestWorkspace::new().unwrap();
+        let pool = file_pool(&ws).await;
+        let result = timeout::run(&pool, Some(Duration::from_millis(50)), slow).await;
+        let Err(DbError::Timeout { elapsed }) = result else {
+            panic!("查询应该超时");
+        };
+        assert!(elapsed >= Duration::from_millis(50));
+        assert_eq!(
+            timeout::run(&pool, Some(Duration::from_secs(5)), count)
+                .await
+                .unwrap(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_context_deadline() {
+        let ws = TestWorkspace::new().unwrap();
+        let pool = file_pool(&ws).await;
+        let ctx = RequestContext::new().timeout(Duration::from_millis(50));
+        let result = context::scope(ctx, timeout::run_in_context(&pool, slow)).await;
+        assert!(matches!(result, Err(DbError::Timeout { .. })));
+
+        //已经超时的请求直接失败
+        let ctx = RequestContext::new().timeout(Duration::ZERO);
+        let result = context::scope(ctx, timeout::run_in_context(&pool, count)).await;
+        assert!(matches!(
+            result,
+            Err(DbError::Timeout { elapsed }) if elapsed == Duration::ZERO
+        ));
+
+        //没有上下文时不限时
+        assert_eq!(timeout::run_in_context(&pool, count).await.unwrap(), 2);
+    }
+
+    //超时的查询也计入请求的数据库耗时
+    #[tokio::test]
+    async fn test_timeout_cost_recorded() {
+        let ws = TestWorkspace::new().unwrap();
+        let pool = file_pool(&ws).await;
+        let ctx = RequestContext::new()
+            .timeout(Duration::from_millis(50))
+            .track_costs();
+        let (result, summary) = context::scope(ctx, async {
+            let result = timeout::run_in_context(&pool, slow).await;
+            (result, context::current().unwrap().cost_summary().unwrap())
+        })
+        .await;
+        assert!(matches!(result, Err(DbError::Timeout { .. })));
+        assert_eq!(summary.db_queries, 1);
+        assert!(summary.db_time >= Duration::from_millis(50));
+    }
+
+    //外层 future 被取消时连接不会回到连接池
+    #[tokio::test]
+    async fn test_dropped_query() {
+        let ws = TestWorkspace::new().unwrap();
+        let pool = file_pool(&ws).await;
+        let cancelled =
+            tokio::time::timeout(Duration::from_millis(50), timeout::run(&pool, None, slow)).await;
+        assert!(cancelled.is_err());
+        assert_eq!(
+            timeout::run(&pool, Some(Duration::from_secs(5)), count)
+                .await
+                .unwrap(),
+            2
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use std_app::clock::SimClock;
+use std_app::debounce::{debounce, throttle};
+
+type Calls = Arc<Mutex<Vec<u32>>>;
+
+fn recorder() -> (Calls, impl Fn(u32) + Send + Sync + 'static) {
+    let calls: Calls = Arc::default();
+    let sink = calls.clone();
+    (calls, move |event| sink.lock().unwrap().push(event))
+}
+
+// 让后台任务进入等待或处理完到期的计时
+async fn settle() {
+    for _ in 0..20 {
+        tokio::task::yield_now().await;
+    }
+}
+
+async fn advance(clock: &SimClock, ms: u64) {
+    settle().await;
+    clock.advance(Duration::from_millis(ms));
+    settle().await;
+}
+
+fn calls(c: &Calls) -> Vec<u32> {
+    c.lock().unwrap().clone()
+}
+
+#[cfg(test)]
+mod test_debounce {
+    use super::*;
+
+    //连续保存配置只触发一次，处理最后一个事件
+    #[tokio::test]
+    async fn test_trailing() {
+        let clock = SimClock::new();
+        let (seen, handler) = recorder();
+        let d = debounce(Duration::from_millis(100), handler).clock(clock.shared());
+        for i in 1..=5 {
+            d.call(i);
+            advance(&clock, 30).await;
+        }
+        assert!(calls(&seen).is_empty());
+        advance(&clock, 100).await;
+        assert_eq!(calls(&seen), vec![5]);
+
+        d.call(6);
+        advance(&clock, 100).await;
+        assert_eq!(calls(&seen), vec![5, 6]);
+    }
+
+    #[tokio::test]
+    async fn test_leading() {
+        let clock = SimClock::new();
+        let (seen, handler) = recorder();
+        let d = debounce(Duration::from_millis(100), handler)
+            .leading(true)
+            .trailing(false)
+            .clock(clock.shared());
+        d.call(1);
+        assert_eq!(calls(&seen), vec![1]);
+        d.call(2);
+        advance(&clock, 50).await;
+        d.call(3);
+        advance(&clock, 100).await;
+        assert_eq!(calls(&seen), vec![1]);
+        //静默之后的新一串事件再次立即触发
+        d.call(4);
+        assert_eq!(calls(&seen), vec![1, 4]);
+    }
+
+    #[tokio::test]
+    async fn test_leading_and_trailing() {
+        let clock = SimClock::new();
+        let (seen, handler) = recorder();
+        let d = debounce(Duration::from_millis(100), handler)
+            .leading(true)
+            .clock(clock.shared());
+        let callback = d.handler();
+        callback(1);
+        callback(2);
+        callback(3);
+        advance(&clock, 100).await;
+        assert_eq!(calls(&seen), vec![1, 3]);
+
+        //只有一个事件时不重复处理
+        callback(4);
+        advance(&clock, 100).await;
+        assert_eq!(calls(&seen), vec![1, 3, 4]);
+    }
+}
+
+#[cfg(test)]
+mod test_throttle {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_leading_and_trailing() {
+        let clock = SimClock::new();
+        let (seen, handler) = recorder();
+        let t = throttle(Duration::from_millis(100), handler).clock(clock.shared());
+        t.call(1);
+        t.call(2);
+        t.call(3);
+        assert_eq!(calls(&seen), vec![1]);
+        advance(&clock, 100).await;
+        assert_eq!(calls(&seen), vec![1, 3]);
+        //trailing 触发的事件开始新窗口
+        t.call(4);
+        advance(&clock, 50).await;
+        assert_eq!(calls(&seen), vec![1, 3]);
+        advance(&clock, 50).await;
+        assert_eq!(calls(&seen), vec![1, 3, 4]);
+        advance(&clock, 100).await;
+        t.call(5);
+        assert_eq!(calls(&seen), vec![1, 3, 4, 5]);
+    }
+
+    #[tokio::test]
+    async fn test_steady_stream_is_rate_limited() {
+        let clock = SimClock::new();
+        let (seen, handler) = recorder();
+        let t = throttle(Duration::from_millis(100), handler)
+            .trailing(false)
+            .clock(clock.shared());
+        for i in 0..10 {
+            t.call(i);
+            advance(&clock, 25).await;
+        }
+        assert_eq!(calls(&seen), vec![0, 4, 8]);
+    }
+
+    #[tokio::test]
+    async fn test_trailing_only() {
+        let clock = SimClock::new();
+        let (seen, handler) = recorder();
+        let t = throttle(Duration::from_millis(100), handler)
+            .leading(false)
+            .clock(clock.shared());
+        t.call(1);
+        t.call(2);
+        assert!(calls(&seen).is_empty());
+        advance(&clock, 100).await;
+        assert_eq!(calls(&seen), vec![2]);
+    }
+}
@@ -0,0 +1,136 @@
+use std::time::Duration;
+
+use std_app::clock::SimClock;
+use std_app::degrade::{DegradeError, Dependencies, Dependency, Fallback, Health, Served};
+
+async fn ok(v: u32) -> Result<u32, String> {
+    Ok(v)
+}
+
+async fn fail() -> Result<u32, String> {
+    Err("connection refused".to_string())
+}
+
+fn dependency(clock: &SimClock) -> Dependency {
+    Dependency::builder("rates")
+        .failure_threshold(2)
+        .cooldown(Duration::from_secs(10))
+        .clock(clock.shared())
+        .build()
+}
+
+#[cfg(test)]
+mod test_degrade {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_breaker_trips_and_serves_stale() {
+        let clock = SimClock::new();
+        let rates = dependency(&clock).with_fallback(Fallback::ServeStale { max_age: None });
+        assert_eq!(rates.call(|| ok(7)).await, Ok(Served::Live(7)));
+
+        // 第一次失败还没有达到阈值，原样返回错误
+        assert_eq!(
+            rates.call(fail).await,
+            Err(DegradeError::Failed("connection refused".into()))
+        );
+        clock.advance(Duration::from_secs(1));
+        // 第二次失败打开断路器，立即降级为旧值
+        assert_eq!(
+            rates.call(fail).await,
+            Ok(Served::Stale {
+                value: 7,
+                age: Duration::from_secs(1)
+            })
+        );
+        assert_eq!(
+            rates.dependency().status().health,
+            Health::Open { failures: 2 }
+        );
+
+        // 断路器打开期间不再调用依赖
+        let mut called = false;
+        let served = rates
+            .call(|| {
+                called = true;
+                ok(8)
+            })
+            .await
+            .unwrap();
+        assert!(!called);
+        assert!(served.is_degraded());
+        assert_eq!(served.into_inner(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_half_open_probe() {
+        let clock = SimClock::new();
+        let rates = dependency(&clock).with_fallback(Fallback::Default(0));
+        rates.call(fail).await.unwrap_err();
+        assert_eq!(rates.call(fail).await, Ok(Served::Default(0)));
+
+        // cooldown 之后放行一次试探，失败则重新打开
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(rates.call(fail).await, Ok(Served::Default(0)));
+        assert_eq!(rates.call(|| ok(1)).await, Ok(Served::Default(0)));
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(rates.call(|| ok(2)).await, Ok(Served::Live(2)));
+        assert!(rates.dependency().is_available());
+        assert_eq!(rates.dependency().status().degraded_calls, 3);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_rejects() {
+        let clock = SimClock::new();
+        let db = Dependency::builder("db").clock(clock.shared()).build();
+        let writes = db.with_fallback::<u32>(Fallback::Reject);
+        db.report_health(Err("磁盘已满".into()));
+        assert_eq!(
+            writes.call(|| ok(1)).await,
+            Err(DegradeError::<String>::Unavailable {
+                dependency: "db".into(),
+                reason: "健康检查失败: 磁盘已满".into()
+            })
+        );
+        db.report_health(Ok(()));
+        assert_eq!(writes.call(|| ok(1)).await, Ok(Served::Live(1)));
+    }
+
+    //旧值超过 max_age 后不再使用
+    #[tokio::test]
+    async fn test_stale_max_age() {
+        let clock = SimClock::new();
+        let rates = dependency(&clock).with_fallback(Fallback::ServeStale {
+            max_age: Some(Duration::from_secs(60)),
+        });
+        rates.call(|| ok(7)).await.unwrap();
+        rates.dependency().report_health(Err("timeout".into()));
+        assert!(rates.call(|| ok(8)).await.unwrap().is_degraded());
+        clock.advance(Duration::from_secs(61));
+        assert!(matches!(
+            rates.call(|| ok(8)).await,
+            Err(DegradeError::Unavailable { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_registry_and_health_check_task() {
+        let clock = SimClock::new();
+        let deps = Dependencies::new();
+        let cache = deps.register(Dependency::builder("cache").clock(clock.shared()).build());
+        deps.register(Dependency::new("db"));
+        let task = cache.spawn_health_check(Duration::from_secs(5), || async {
+            Err("持久化目录不可写".to_string())
+        });
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(deps.degraded(), vec!["cache".to_string()]);
+        let status = deps.status();
+        assert_eq!(status.len(), 2);
+        assert_eq!(status[1].health, Health::Healthy);
+        assert!(deps.get("cache").is_some());
+        task.abort();
+    }
+}
@@ -0,0 +1,101 @@
+use std_app::chunker::Chunker;
+use std_app::delta::{self, Op, Patch, PatchError};
+
+fn random_bytes(len: usize, seed: u64) -> Vec<u8> {
+    let mut x = seed | 1;
+    (0..len)
+        .map(|_| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x as u8
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_delta {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip_small_edit() -> Result<(), PatchError> {
+        let old = random_bytes(200_000, 1);
+        let mut new = old.clone();
+        new[50_000..50_100].fill(0xaa);
+        new.splice(150_000..150_000, b"appended in the middle".iter().copied());
+
+        let patch = delta::diff_bytes(&old, &new);
+        assert_eq!(delta::apply(&old, &patch)?, new);
+        assert_eq!(patch.new_len, new.len() as u64);
+        // 只需要传输改动附近的几个块
+        assert!(patch.insert_bytes() < 16 * 1024, "{}", patch.insert_bytes());
+        assert_eq!(patch.copy_bytes() + patch.insert_bytes(), patch.new_len);
+        Ok(())
+    }
+
+    #[test]
+    fn test_identity_and_empty() -> Result<(), PatchError> {
+        let data = random_bytes(10_000, 2);
+        let patch = delta::diff_bytes(&data, &data);
+        assert!(patch.is_identity());
+        assert_eq!(
+            patch.ops,
+            vec![Op::Copy {
+                offset: 0,
+                len: 10_000
+            }]
+        );
+
+        let patch = delta::diff_bytes(b"", &data);
+        assert_eq!(patch.insert_bytes(), 10_000);
+        assert_eq!(delta::apply(b"", &patch)?, data);
+
+        let patch = delta::diff_bytes(&data, b"");
+        assert!(patch.ops.is_empty());
+        assert_eq!(delta::apply(&data, &patch)?, b"");
+        Ok(())
+    }
+
+    //块顺序调换后仍然可以从旧数据复制
+    #[test]
+    fn test_reordered_blocks() -> Result<(), PatchError> {
+        let a = random_bytes(20_000, 3);
+        let b = random_bytes(20_000, 4);
+        let old = [a.clone(), b.clone()].concat();
+        let new = [b, a].concat();
+        let patch = delta::diff_bytes_with(&old, &new, &Chunker::with_avg_size(512));
+        assert!(patch.insert_bytes() < 4096);
+        assert_eq!(delta::apply(&old, &patch)?, new);
+        Ok(())
+    }
+
+    #[test]
+    fn test_verification() {
+        let old = random_bytes(5000, 5);
+        let new = random_bytes(5000, 6);
+        let patch = delta::diff_bytes(&old, &new);
+
+        let err = delta::apply(&new, &patch).unwrap_err();
+        assert!(matches!(err, PatchError::SourceMismatch { .. }));
+
+        let mut broken: Patch =
+            serde_json::from_str(&serde_json::to_string(&patch).unwrap()).unwrap();
+        broken.ops.push(Op::Insert(b"x".to_vec()));
+        assert!(matches!(
+            delta::apply(&old, &broken),
+            Err(PatchError::Corrupt { .. })
+        ));
+
+        broken.ops = vec![Op::Copy {
+            offset: 4000,
+            len: 2000,
+        }];
+        assert_eq!(
+            delta::apply(&old, &broken),
+            Err(PatchError::OutOfRange {
+                offset: 4000,
+                len: 2000
+            })
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use serde::Serialize;
+use serde_json::json;
+
+use std_app::diff::{self, Change};
+
+#[derive(Serialize)]
+struct Config {
+    port: u16,
+    host: String,
+}
+
+#[cfg(test)]
+mod test_diff {
+    use super::*;
+
+    #[test]
+    fn test_equal_values() {
+        let a = json!({"name": "Alice", "tags": [1, 2]});
+        assert!(diff::compare(&a, &a.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_nested_changes() {
+        let a = json!({
+            "name": "Alice",
+            "age": 30,
+            "items": [{"name": "a"}, {"name": "b"}]
+        });
+        let b = json!({
+            "name": "Alice",
+            "email": "alice@example.com",
+            "items": [{"name": "a"}, {"name": "c"}, {"name": "d"}]
+        });
+
+        let changes = diff::compare(&a, &b);
+        assert_eq!(
+            changes,
+            vec![
+                Change::Removed {
+                    path: "/age".to_string(),
+                    value: json!(30)
+                },
+                Change::Modified {
+                    path: "/items/1/name".to_string(),
+                    old: json!("b"),
+                    new: json!("c")
+                },
+                Change::Added {
+                    path: "/items/2".to_string(),
+                    value: json!({"name": "d"})
+                },
+                Change::Added {
+                    path: "/email".to_string(),
+                    value: json!("alice@example.com")
+                },
+            ]
+        );
+        assert_eq!(
+            diff::render(&changes[..2]),
+            "- /age: 30\n~ /items/1/name: \"b\" -> \"c\""
+        );
+    }
+
+    #[test]
+    fn test_pointer_escaping() {
+        let changes = diff::compare(&json!({"a/b": 1, "c~d": 1}), &json!({"a/b": 2, "c~d": 2}));
+        let paths: Vec<_> = changes.iter().map(|c| c.path()).collect();
+        assert_eq!(paths, vec!["/a~1b", "/c~0d"]);
+    }
+
+    //TOML 配置与结构体比较
+    #[test]
+    fn test_compare_toml_with_struct() -> Result<(), Box<dyn std::error::Error>> {
+        let file: toml::Value = toml::from_str(
+            r#"
+            host = "localhost"
+            port = 8080
+        "#,
+        )?;
+        let current = Config {
+            port: 9090,
+            host: "localhost".to_string(),
+        };
+
+        let changes = diff::compare_values(&file, &current)?;
+        assert_eq!(
+            changes,
+            vec![Change::Modified {
+                path: "/port".to_string(),
+                old: json!(8080),
+                new: json!(9090)
+            }]
+        );
+        Ok(())
+    }
+}
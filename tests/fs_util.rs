@@ -0,0 +1,168 @@
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use std_app::fs_util::{self, CopyError, CopyOptions};
+use std_app::testkit::TestWorkspace;
+
+fn content(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7 % 253) as u8).collect()
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::Digest;
+    hex::encode(sha2::Sha256::digest(data))
+}
+
+#[cfg(test)]
+mod test_copy_resumable {
+    use super::*;
+
+    #[test]
+    fn test_copy() -> Result<(), CopyError> {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let (src, dst) = (root.join("src.bin"), root.join("dst.bin"));
+        let data = content(100_000);
+        fs::write(&src, &data)?;
+
+        let report = fs_util::copy_resumable(&src, &dst)?;
+        assert_eq!(report.bytes, 100_000);
+        assert_eq!(report.resumed_from, 0);
+        assert_eq!(report.sha256, sha256_hex(&data));
+        assert_eq!(fs::read(&dst)?, data);
+        assert!(!root.join("dst.bin.part").exists());
+        assert!(!root.join("dst.bin.part.state").exists());
+
+        fs::write(&src, b"")?;
+        assert_eq!(fs_util::copy_resumable(&src, &dst)?.bytes, 0);
+        assert_eq!(fs::read(&dst)?, b"");
+        Ok(())
+    }
+
+    //中断后再次调用从记录的位置继续
+    #[test]
+    fn test_resume_after_interrupt() -> Result<(), CopyError> {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let (src, dst) = (root.join("src.bin"), root.join("dst.bin"));
+        let data = content(50_000);
+        fs::write(&src, &data)?;
+
+        let options = CopyOptions::new()
+            .chunk_size(8192)
+            .on_progress(|done, _| done < 20_000);
+        match fs_util::copy_resumable_with(&src, &dst, &options) {
+            Err(CopyError::Interrupted { copied }) => assert_eq!(copied, 24_576),
+            other => panic!("期望 Interrupted, 实际: {:?}", other),
+        }
+        assert!(!dst.exists());
+
+        // 模拟崩溃时数据写了一半但进度还没更新
+        let mut part = fs::read(root.join("dst.bin.part"))?;
+        part.extend_from_slice(b"garbage");
+        fs::write(root.join("dst.bin.part"), part)?;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let options = CopyOptions::new()
+            .chunk_size(8192)
+            .on_progress(move |_, total| {
+                assert_eq!(total, 50_000);
+                counter.fetch_add(1, Ordering::SeqCst);
+                true
+            });
+        let report = fs_util::copy_resumable_with(&src, &dst, &options)?;
+        assert_eq!(report.resumed_from, 24_576);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert_eq!(report.sha256, sha256_hex(&data));
+        assert_eq!(fs::read(&dst)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_source_changed_restarts() -> Result<(), CopyError> {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let (src, dst) = (root.join("src.bin"), root.join("dst.bin"));
+        fs::write(&src, content(30_000))?;
+        let stop = CopyOptions::new()
+            .chunk_size(4096)
+            .on_progress(|_, _| false);
+        assert!(fs_util::copy_resumable_with(&src, &dst, &stop).is_err());
+
+        let data = content(40_000);
+        fs::write(&src, &data)?;
+        let report = fs_util::copy_resumable(&src, &dst)?;
+        assert_eq!(report.resumed_from, 0);
+        assert_eq!(fs::read(&dst)?, data);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_file_lock {
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use std_app::fs_util::FileLock;
+
+    use super::*;
+
+    #[test]
+    fn test_try_and_release() -> std::io::Result<()> {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let path = root.join("data/compact.lock");
+
+        let lock = FileLock::try_exclusive(&path)?.expect("第一次加锁应该成功");
+        assert_eq!(lock.path(), path);
+        assert_eq!(FileLock::holder(&path), Some(std::process::id()));
+        assert!(FileLock::try_exclusive(&path)?.is_none());
+
+        drop(lock);
+        assert!(FileLock::try_exclusive(&path)?.is_some());
+        Ok(())
+    }
+
+    // 共享锁之间不互斥，和排他锁互斥
+    #[test]
+    fn test_shared() -> std::io::Result<()> {
+        let ws = TestWorkspace::new().unwrap();
+        let path = ws.path("gc.lock");
+
+        let first = FileLock::shared(&path)?;
+        let second = FileLock::shared(&path)?;
+        assert!(FileLock::try_exclusive(&path)?.is_none());
+        drop(first);
+        assert!(FileLock::try_exclusive(&path)?.is_none());
+        drop(second);
+        assert!(FileLock::try_exclusive(&path)?.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_timeout_and_blocking() -> std::io::Result<()> {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let path = root.join("migrate.lock");
+        let lock = FileLock::exclusive(&path)?;
+
+        let start = Instant::now();
+        let err = FileLock::exclusive_timeout(&path, Duration::from_millis(50)).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+        assert!(start.elapsed() >= Duration::from_millis(50));
+
+        //持有者释放后等待的一方取得锁
+        let waiter = {
+            let path = path.clone();
+            thread::spawn(move || {
+                FileLock::exclusive_timeout(&path, Duration::from_secs(5)).map(|_| ())
+            })
+        };
+        thread::sleep(Duration::from_millis(30));
+        drop(lock);
+        waiter.join().unwrap()?;
+        Ok(())
+    }
+}
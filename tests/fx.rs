@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+use std_app::clock::{Clock, SimClock};
+use std_app::fx::{self, convert_with, Currency, FxError, HttpRates, Money, StaticRates};
+use std_app::testkit::http::{MockHttp, MockResponse};
+
+fn usd(amount: i64) -> Money {
+    Money::new(amount, "USD".parse().unwrap())
+}
+
+fn eur() -> Currency {
+    "eur".parse().unwrap()
+}
+
+#[cfg(test)]
+mod test_fx {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_static_rates() -> Result<(), FxError> {
+        let rates = StaticRates::new(UNIX_EPOCH).rate("USD", "EUR", 0.5)?;
+        let converted = convert_with(&rates, &usd(1_001), &eur()).await?;
+        assert_eq!(converted.money.amount, 501);
+        assert_eq!(converted.money.to_string(), "5.01 EUR");
+        // 反向汇率
+        let back = convert_with(&rates, &converted.money, &"USD".parse()?).await?;
+        assert_eq!(back.money.amount, 1_002);
+        assert!(matches!(
+            convert_with(&rates, &usd(1), &"JPY".parse()?).await,
+            Err(FxError::UnknownRate(_, _))
+        ));
+        assert!(matches!(
+            "US".parse::<Currency>(),
+            Err(FxError::InvalidCurrency(_))
+        ));
+        Ok(())
+    }
+
+    //HTTP 汇率按 TTL 缓存，失败时退回静态汇率
+    #[tokio::test]
+    async fn test_http_rates_cache_and_fallback() -> Result<(), FxError> {
+        let server = MockHttp::with_handler(|req| {
+            if req.path.contains("base=USD") {
+                MockResponse::ok(r#"{"timestamp": 1000, "rates": {"EUR": 0.9}}"#)
+            } else {
+                MockResponse::new(500)
+            }
+        })
+        .await
+        .unwrap();
+        let clock = SimClock::at(UNIX_EPOCH + Duration::from_secs(1_060));
+        let rates = HttpRates::with_clock(server.url(), Duration::from_secs(60), clock.shared())
+            .fallback(StaticRates::new(UNIX_EPOCH).rate("GBP", "EUR", 1.2)?);
+
+        let converted = convert_with(&rates, &usd(100), &eur()).await?;
+        assert_eq!(converted.money.amount, 90);
+        assert_eq!(
+            converted.rate.as_of,
+            UNIX_EPOCH + Duration::from_secs(1_000)
+        );
+        convert_with(&rates, &usd(100), &eur()).await?;
+        assert_eq!(server.requests().len(), 1);
+        clock.advance(Duration::from_secs(61));
+        convert_with(&rates, &usd(100), &eur()).await?;
+        assert_eq!(server.requests().len(), 2);
+
+        let gbp = Money::new(100, "GBP".parse()?);
+        let fallback = convert_with(&rates, &gbp, &eur()).await?;
+        assert_eq!(fallback.money.amount, 120);
+        assert_eq!(fallback.rate.as_of, UNIX_EPOCH);
+        Ok(())
+    }
+
+    //业务代码可以拒绝过期的汇率
+    #[tokio::test]
+    async fn test_global_convert_and_staleness() -> Result<(), FxError> {
+        let clock = SimClock::at(UNIX_EPOCH + Duration::from_secs(3_600));
+        fx::set_provider(Arc::new(
+            StaticRates::new(UNIX_EPOCH).rate("USD", "EUR", 2.0)?,
+        ));
+        let converted = fx::convert(usd(10), eur()).await?;
+        assert_eq!(converted.money.amount, 20);
+        assert_eq!(
+            converted.age(clock.system_time()),
+            Duration::from_secs(3_600)
+        );
+        assert!(converted
+            .clone()
+            .ensure_fresh(Duration::from_secs(7_200), clock.system_time())
+            .is_ok());
+        assert!(matches!(
+            converted.ensure_fresh(Duration::from_secs(60), clock.system_time()),
+            Err(FxError::Stale { .. })
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,647 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Deserialize;
+use sqlx::sqlite::SqlitePoolOptions;
+
+use std_app::clock::SimClock;
+use std_app::http::budget::RetryBudget;
+use std_app::http::conditional::{Fetched, ValidatorStore, Validators};
+use std_app::http::negotiate::{Body, Encoding, ACCEPT, ACCEPT_ENCODING};
+use std_app::http::pool::ConnectionConfig;
+use std_app::http::signing::{HmacSigner, SignatureError, SignedParts, TimestampDotBody};
+use std_app::http::{ApiError, HttpClient, Method, Middleware};
+use std_app::metrics::{MetricValue, Registry};
+use std_app::retry::Backoff;
+use std_app::serde_any::{self, Format};
+use std_app::testkit::http::{MockHttp, MockResponse};
+
+async fn get_three(client: &HttpClient) {
+    for _ in 0..3 {
+        let body: serde_json::Value = client.get_json("/ping").await.unwrap();
+        assert_eq!(body, serde_json::json!({"ok": true}));
+    }
+}
+
+async fn server() -> MockHttp {
+    MockHttp::with_handler(|_| MockResponse::ok(r#"{"ok":true}"#))
+        .await
+        .unwrap()
+}
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+fn brotli(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    {
+        let mut writer = brotli::CompressorWriter::new(&mut out, 4096, 5, 22);
+        writer.write_all(data).unwrap();
+    }
+    out
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+struct Settings {
+    name: String,
+    port: u16,
+}
+
+async fn negotiation_server() -> MockHttp {
+    MockHttp::with_handler(|req| match req.path.as_str() {
+        "/json" => MockResponse::ok(gzip(br#"{"name":"api","port":80}"#))
+            .header("content-type", "application/json; charset=utf-8")
+            .header("content-encoding", "gzip"),
+        "/toml" => MockResponse::ok(brotli(b"name = \"api\"\nport = 80\n"))
+            .header("content-type", "application/toml")
+            .header("content-encoding", "br"),
+        "/text" => MockResponse::ok("hello").header("content-type", "text/plain"),
+        "/zstd" => MockResponse::ok("x")
+            .header("content-type", "text/plain")
+            .header("content-encoding", "zstd"),
+        _ => MockResponse::ok("<x/>").header("content-type", "application/xml"),
+    })
+    .await
+    .unwrap()
+}
+
+// 内容为 v1 时返回 ETag，/dated 只返回 Last-Modified
+async fn conditional_server() -> MockHttp {
+    MockHttp::with_handler(|req| match req.path.as_str() {
+        "/dated" if req.header("if-modified-since") == Some("Wed, 01 Jan 2025 00:00:00 GMT") => {
+            MockResponse::new(304)
+        }
+        "/dated" => {
+            MockResponse::ok("dated").header("last-modified", "Wed, 01 Jan 2025 00:00:00 GMT")
+        }
+        _ if req.header("if-none-match") == Some("\"v1\"") => MockResponse::new(304),
+        _ => MockResponse::ok("v1").header("etag", "\"v1\""),
+    })
+    .await
+    .unwrap()
+}
+
+fn sim_clock() -> SimClock {
+    SimClock::at(std::time::UNIX_EPOCH + Duration::from_secs(1_700_000_000))
+}
+
+// 前 failures 个请求返回 503
+async fn flaky_server(failures: usize) -> MockHttp {
+    let seen = Arc::new(AtomicUsize::new(0));
+    MockHttp::with_handler(move |_| {
+        if seen.fetch_add(1, Ordering::SeqCst) < failures {
+            MockResponse::new(503)
+        } else {
+            MockResponse::ok(r#"{"ok":true}"#)
+        }
+    })
+    .await
+    .unwrap()
+}
+
+// 第一个请求要 600ms 才返回，之后的请求立即返回
+async fn slow_first_server() -> MockHttp {
+    let calls = AtomicUsize::new(0);
+    MockHttp::with_handler(move |_| {
+        if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+            std::thread::sleep(Duration::from_millis(600));
+            MockResponse::ok("slow")
+        } else {
+            MockResponse::ok("fast")
+        }
+    })
+    .await
+    .unwrap()
+}
+
+// 给每个实际发出的请求加上 x-attempt: 序号
+#[derive(Default)]
+struct CountAttempts(AtomicUsize);
+
+impl Middleware for CountAttempts {
+    fn handle(&self, request: &mut reqwest::Request) -> Result<(), ApiError> {
+        let n = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+        request.headers_mut().insert("x-attempt", n.into());
+        Ok(())
+    }
+}
+
+fn attempts(server: &MockHttp) -> Vec<String> {
+    server
+        .requests()
+        .iter()
+        .map(|r| r.header("x-attempt").unwrap_or_default().to_string())
+        .collect()
+}
+
+fn fast_retry() -> Backoff {
+    Backoff::new(Duration::from_millis(1), Duration::from_millis(5)).max_attempts(3)
+}
+
+fn metric(registry: &Registry, name: &str) -> MetricValue {
+    registry
+        .snapshot()
+        .into_iter()
+        .find(|m| m.name == name)
+        .unwrap()
+        .value
+}
+
+#[cfg(test)]
+mod test_http_client {
+    use super::*;
+
+    //MockHttp 支持 keep-alive，顺序请求复用同一个连接
+    #[tokio::test]
+    async fn test_connection_reuse() {
+        let server = server().await;
+        let client = HttpClient::builder(server.url())
+            .tcp_keepalive(Duration::from_secs(30))
+            .pool_idle_timeout(Duration::from_secs(90))
+            .build()
+            .unwrap();
+        get_three(&client).await;
+        let stats = client.stats();
+        assert_eq!(stats.requests, 3);
+        assert_eq!(stats.connections, 1);
+        assert_eq!(stats.reused(), 2);
+        assert!((stats.reuse_rate() - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_no_idle_connections() {
+        let server = server().await;
+        let client = HttpClient::builder(server.url())
+            .pool_max_idle_per_host(0)
+            .build()
+            .unwrap();
+        get_three(&client).await;
+        let stats = client.stats();
+        assert_eq!(stats.connections, 3);
+        assert_eq!(stats.reuse_rate(), 0.0);
+    }
+
+    #[test]
+    fn test_config_from_file() {
+        let config: ConnectionConfig = serde_any::from_str(
+            "http2_prior_knowledge = true\nmax_idle_per_host = 4\ntcp_keepalive_secs = 60\n",
+            Format::Toml,
+        )
+        .unwrap();
+        assert!(config.http2_prior_knowledge);
+        assert_eq!(config.max_idle_per_host, Some(4));
+        assert_eq!(config.idle_timeout(), None);
+        assert_eq!(config.tcp_keepalive(), Some(Duration::from_secs(60)));
+    }
+
+    //mock 服务端只支持 HTTP/1.1，直接使用 HTTP/2 的请求会失败
+    #[tokio::test]
+    async fn test_http2_prior_knowledge() {
+        let server = server().await;
+        let config = ConnectionConfig {
+            http2_prior_knowledge: true,
+            ..ConnectionConfig::default()
+        };
+        let client = HttpClient::builder(server.url())
+            .connection(&config)
+            .build()
+            .unwrap();
+        let result: Result<serde_json::Value, _> = client.get_json("/ping").await;
+        assert!(matches!(result, Err(ApiError::Request(_))));
+        assert_eq!(client.stats().requests, 1);
+    }
+
+    #[tokio::test]
+    async fn test_negotiate_decompresses() {
+        let server = negotiation_server().await;
+        let client = HttpClient::new(server.url());
+        assert_eq!(
+            client.negotiate("/json").await.unwrap(),
+            Body::Json(serde_json::json!({"name": "api", "port": 80}))
+        );
+        assert!(matches!(
+            client.negotiate("/toml").await.unwrap(),
+            Body::Toml(_)
+        ));
+        assert_eq!(
+            client.negotiate("/text").await.unwrap(),
+            Body::Text("hello".to_string())
+        );
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("accept"), Some(ACCEPT));
+        assert_eq!(request.header("accept-encoding"), Some(ACCEPT_ENCODING));
+    }
+
+    //JSON 和 TOML 响应都可以反序列化为同一个类型
+    #[tokio::test]
+    async fn test_fetch_typed() {
+        let server = negotiation_server().await;
+        let client = HttpClient::new(server.url());
+        let expected = Settings {
+            name: "api".to_string(),
+            port: 80,
+        };
+        assert_eq!(client.fetch::<Settings>("/json").await.unwrap(), expected);
+        assert_eq!(client.fetch::<Settings>("/toml").await.unwrap(), expected);
+        assert_eq!(client.fetch::<String>("/text").await.unwrap(), "hello");
+        assert!(client.fetch::<Settings>("/text").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_content() {
+        let server = negotiation_server().await;
+        let client = HttpClient::new(server.url());
+        assert!(matches!(
+            client.negotiate("/zstd").await,
+            Err(ApiError::Content(_))
+        ));
+        assert!(matches!(
+            client.negotiate("/xml").await,
+            Err(ApiError::Content(_))
+        ));
+    }
+
+    //压缩炸弹: 几 KB 的响应解压出几 MB，超过上限时停止解压
+    #[tokio::test]
+    async fn test_decompression_bomb() {
+        let bomb = gzip(&vec![0; 4 * 1024 * 1024]);
+        assert!(bomb.len() < 64 * 1024);
+        let server = MockHttp::with_handler(move |_| {
+            MockResponse::ok(bomb.clone())
+                .header("content-type", "text/plain")
+                .header("content-encoding", "gzip")
+        })
+        .await
+        .unwrap();
+        let client = HttpClient::builder(server.url())
+            .max_decoded_size(1024 * 1024)
+            .build()
+            .unwrap();
+        assert!(matches!(
+            client.negotiate("/bomb").await,
+            Err(ApiError::TooLarge { limit }) if limit == 1024 * 1024
+        ));
+
+        let bomb = brotli(&vec![0; 1024 * 1024]);
+        assert!(matches!(
+            Encoding::Brotli.decode(bomb.clone(), 1000),
+            Err(ApiError::TooLarge { limit: 1000 })
+        ));
+        assert_eq!(
+            Encoding::Brotli.decode(bomb, 1024 * 1024).unwrap().len(),
+            1024 * 1024
+        );
+
+        let mut out = b"kept".to_vec();
+        assert!(Encoding::Gzip
+            .decode_into(&gzip(b"hello world"), &mut out, 5)
+            .is_err());
+        assert_eq!(out, b"kept");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_if_changed() {
+        let server = conditional_server().await;
+        let client = HttpClient::new(server.url());
+        let Fetched::Changed(body, validators) =
+            client.fetch_if_changed("/data", None).await.unwrap()
+        else {
+            panic!("第一次请求应该下载内容");
+        };
+        assert_eq!(body, b"v1");
+        assert_eq!(validators.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(
+            client
+                .fetch_if_changed("/data", Some(&validators))
+                .await
+                .unwrap(),
+            Fetched::Unchanged
+        );
+        //校验信息不匹配时重新下载
+        let stale = Validators {
+            etag: Some("\"v0\"".to_string()),
+            last_modified: None,
+        };
+        assert!(matches!(
+            client
+                .fetch_if_changed("/data", Some(&stale))
+                .await
+                .unwrap(),
+            Fetched::Changed(..)
+        ));
+    }
+
+    //校验信息保存在数据库中，新建的 store 也能使用
+    #[tokio::test]
+    async fn test_validator_store() {
+        let server = conditional_server().await;
+        let client = HttpClient::new(server.url());
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let store = ValidatorStore::new(pool.clone());
+        store.migrate().await.unwrap();
+
+        for path in ["/data", "/dated"] {
+            assert!(matches!(
+                store.fetch_if_changed(&client, path).await.unwrap(),
+                Fetched::Changed(..)
+            ));
+        }
+        let store = ValidatorStore::new(pool);
+        for path in ["/data", "/dated"] {
+            assert_eq!(
+                store.fetch_if_changed(&client, path).await.unwrap(),
+                Fetched::Unchanged
+            );
+        }
+        assert_eq!(
+            store
+                .get("/dated")
+                .await
+                .unwrap()
+                .unwrap()
+                .last_modified
+                .as_deref(),
+            Some("Wed, 01 Jan 2025 00:00:00 GMT")
+        );
+        assert_eq!(server.requests().len(), 4);
+    }
+
+    //接收方用同一个密钥校验中间件加上的签名
+    #[tokio::test]
+    async fn test_signing_middleware() {
+        let server = server().await;
+        let clock = sim_clock();
+        let signer = HmacSigner::new("secret")
+            .clock(clock.shared())
+            .key_id("x-key-id", "k1");
+        let client = HttpClient::builder(server.url())
+            .middleware(signer.clone())
+            .build()
+            .unwrap();
+        let _: serde_json::Value = client
+            .post_json("/orders?dry_run=1", &serde_json::json!({"sku": "a"}))
+            .await
+            .unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.header("x-signature-timestamp"), Some("1700000000"));
+        assert_eq!(request.header("x-key-id"), Some("k1"));
+        let signature = request.header("x-signature").unwrap();
+        assert_eq!(
+            signer.verify(
+                "POST",
+                "/orders?dry_run=1",
+                &request.body,
+                "1700000000",
+                signature
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            signer.verify("POST", "/orders", &request.body, "1700000000", signature),
+            Err(SignatureError::Mismatch)
+        );
+        assert_eq!(
+            HmacSigner::new("other").clock(clock.shared()).verify(
+                "POST",
+                "/orders?dry_run=1",
+                &request.body,
+                "1700000000",
+                signature
+            ),
+            Err(SignatureError::Mismatch)
+        );
+    }
+
+    #[test]
+    fn test_replay_window() {
+        let clock = sim_clock();
+        let signer = HmacSigner::new("secret")
+            .canonicalize(TimestampDotBody)
+            .replay_window(Duration::from_secs(60))
+            .clock(clock.shared());
+        let parts = SignedParts {
+            method: "POST",
+            path: "/hook",
+            body: b"{}",
+            timestamp: 1_700_000_000,
+        };
+        let signature = signer.sign(&parts);
+        //这种格式不包含 method 和 path
+        assert_eq!(
+            signer.verify("GET", "/other", b"{}", "1700000000", &signature),
+            Ok(())
+        );
+
+        clock.advance(Duration::from_secs(61));
+        assert_eq!(
+            signer.verify("POST", "/hook", b"{}", "1700000000", &signature),
+            Err(SignatureError::Expired {
+                skew: Duration::from_secs(61)
+            })
+        );
+        assert!(matches!(
+            signer.verify("POST", "/hook", b"{}", "soon", &signature),
+            Err(SignatureError::InvalidTimestamp(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_retry_until_success() {
+        let server = flaky_server(2).await;
+        let client = HttpClient::builder(server.url())
+            .retry(fast_retry())
+            .build()
+            .unwrap();
+        let body: serde_json::Value = client.get_json("/ping").await.unwrap();
+        assert_eq!(body, serde_json::json!({"ok": true}));
+        assert_eq!(server.requests().len(), 3);
+        let budget = client.retry_budget().unwrap().stats();
+        assert_eq!((budget.requests, budget.retries), (1, 2));
+    }
+
+    // 每次重试都重新运行中间件，签名不会沿用第一次的时间戳
+    #[tokio::test]
+    async fn test_middleware_runs_per_attempt() {
+        let server = flaky_server(2).await;
+        let client = HttpClient::builder(server.url())
+            .middleware(CountAttempts::default())
+            .retry(fast_retry())
+            .build()
+            .unwrap();
+        let _: serde_json::Value = client.get_json("/ping").await.unwrap();
+        assert_eq!(attempts(&server), vec!["1", "2", "3"]);
+    }
+
+    //非幂等请求不重试
+    #[tokio::test]
+    async fn test_post_not_retried() {
+        let server = flaky_server(1).await;
+        let client = HttpClient::builder(server.url())
+            .retry(fast_retry())
+            .build()
+            .unwrap();
+        let result: Result<serde_json::Value, _> =
+            client.post_json("/orders", &serde_json::json!({})).await;
+        assert!(matches!(result, Err(ApiError::Status { status: 503, .. })));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    //预算按整个客户端计算，用完后直接返回 RetryBudgetExhausted
+    #[tokio::test]
+    async fn test_retry_budget_exhausted() {
+        let server = flaky_server(usize::MAX).await;
+        let registry = Registry::new();
+        let budget = RetryBudget::new(0.0, Duration::from_secs(60))
+            .min_retries(2)
+            .metrics(&registry);
+        let client = HttpClient::builder(server.url())
+            .retry_with_budget(fast_retry(), budget)
+            .build()
+            .unwrap();
+
+        let first: Result<serde_json::Value, _> = client.get_json("/ping").await;
+        assert!(matches!(first, Err(ApiError::Status { status: 503, .. })));
+        let second: Result<serde_json::Value, _> = client.clone().get_json("/ping").await;
+        let Err(ApiError::RetryBudgetExhausted(source)) = second else {
+            panic!("预算应该已经用完");
+        };
+        assert!(matches!(*source, ApiError::Status { status: 503, .. }));
+        assert_eq!(server.requests().len(), 4);
+
+        assert_eq!(
+            metric(&registry, "http_client_retries_total"),
+            MetricValue::Counter { value: 2 }
+        );
+        assert_eq!(
+            metric(&registry, "http_client_retries_denied_total"),
+            MetricValue::Counter { value: 1 }
+        );
+        assert_eq!(
+            metric(&registry, "http_client_retry_budget_available"),
+            MetricValue::Gauge { value: 0.0 }
+        );
+    }
+
+    #[test]
+    fn test_budget_window() {
+        let clock = sim_clock();
+        let budget = RetryBudget::new(0.5, Duration::from_secs(10))
+            .min_retries(0)
+            .clock(clock.shared());
+        for _ in 0..4 {
+            budget.record_request();
+        }
+        assert!(budget.try_retry());
+        assert!(budget.try_retry());
+        assert!(!budget.try_retry());
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(budget.stats().requests, 0);
+        assert_eq!(budget.stats().available, 0);
+    }
+
+    //handler 会阻塞线程，需要多线程运行时
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_hedged_request_takes_faster_response() {
+        let server = slow_first_server().await;
+        let client = HttpClient::new(server.url());
+        let start = std::time::Instant::now();
+        let response = client
+            .get_hedged("/item", Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "fast");
+        assert!(start.elapsed() < Duration::from_millis(500));
+        assert_eq!(client.stats().hedged, 1);
+    }
+
+    // 对冲请求从重试预算中扣除，不算新的请求；预算用完时不再对冲
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_hedge_charged_to_retry_budget() {
+        let server = slow_first_server().await;
+        let budget = RetryBudget::new(0.0, Duration::from_secs(60)).min_retries(1);
+        let client = HttpClient::builder(server.url())
+            .retry_with_budget(fast_retry(), budget)
+            .build()
+            .unwrap();
+        let response = client
+            .get_hedged("/item", Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "fast");
+        let stats = client.retry_budget().unwrap().stats();
+        assert_eq!((stats.requests, stats.retries), (1, 1));
+
+        assert_eq!(client.stats().hedged, 1);
+
+        let server = slow_first_server().await;
+        let budget = RetryBudget::new(0.0, Duration::from_secs(60)).min_retries(0);
+        let client = HttpClient::builder(server.url())
+            .retry_with_budget(fast_retry(), budget)
+            .build()
+            .unwrap();
+        let response = client
+            .get_hedged("/item", Duration::from_millis(50))
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "slow");
+        assert_eq!(client.stats().hedged, 0);
+        let stats = client.retry_budget().unwrap().stats();
+        assert_eq!((stats.requests, stats.retries), (1, 0));
+    }
+
+    // 对冲请求单独运行中间件
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_middleware_runs_per_hedge() {
+        let server = slow_first_server().await;
+        let client = HttpClient::builder(server.url())
+            .middleware(CountAttempts::default())
+            .build()
+            .unwrap();
+        client
+            .get_hedged("/item", Duration::from_millis(50))
+            .await
+            .unwrap();
+        // 慢请求处理完才会被记录
+        tokio::time::sleep(Duration::from_millis(700)).await;
+        let mut seen = attempts(&server);
+        seen.sort();
+        assert_eq!(seen, vec!["1", "2"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_hedge_only_when_slow_and_idempotent() {
+        let server = server().await;
+        let client = HttpClient::new(server.url());
+        client
+            .get_hedged("/ping", Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(client.stats().hedged, 0);
+        assert_eq!(server.requests().len(), 1);
+
+        //POST 不是幂等的，即使很慢也只发一次
+        let server = slow_first_server().await;
+        let client = HttpClient::new(server.url());
+        let response = client
+            .send_hedged(
+                client.request(Method::POST, "/orders").body("{}"),
+                Duration::from_millis(50),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "slow");
+        assert_eq!(client.stats().hedged, 0);
+        assert_eq!(server.requests().len(), 1);
+    }
+}
@@ -0,0 +1,111 @@
+use regex::Regex;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+
+use std_app::import::{self, ImportError, ImportSpec, Rule};
+use std_app::reports::Format;
+
+async fn pool() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::query("CREATE TABLE people (name TEXT NOT NULL, age INTEGER, email TEXT)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool
+}
+
+fn spec(format: Format) -> ImportSpec {
+    ImportSpec::new("people", format)
+        .column("name", vec![Rule::Required, Rule::MaxLen(20)])
+        .column("age", vec![Rule::Integer])
+        .column(
+            "email",
+            vec![Rule::Pattern(Regex::new(r"^[^@]+@[^@]+$").unwrap())],
+        )
+        .batch_size(2)
+}
+
+#[cfg(test)]
+mod test_import {
+    use super::*;
+
+    //不合格的记录进入报告，其他记录照常导入
+    #[tokio::test]
+    async fn test_csv_partial_failure() -> Result<(), ImportError> {
+        let pool = pool().await;
+        let input = "name,age,email\n\
+                     alice,30,alice@example.com\n\
+                     ,abc,bad\n\
+                     \"bob, \"\"the builder\"\"\",41,\n\
+                     carol,7\n\
+                     \"多行\n名字\",5,x@y\n";
+        let report = import::run(&pool, input.as_bytes(), &spec(Format::Csv)).await?;
+        assert_eq!(report.inserted, 3);
+        assert_eq!(report.rejected.len(), 2);
+        assert_eq!(report.rejected[0].line, 3);
+        assert_eq!(
+            report.rejected[0].reasons,
+            vec![
+                "name: 不能为空".to_string(),
+                "age: 不是整数: abc".to_string(),
+                "email: 格式不符合 ^[^@]+@[^@]+$".to_string(),
+            ]
+        );
+        assert_eq!(report.rejected[1].line, 5);
+
+        let names: Vec<String> = sqlx::query_scalar("SELECT name FROM people ORDER BY rowid")
+            .fetch_all(&pool)
+            .await?;
+        assert_eq!(names, vec!["alice", "bob, \"the builder\"", "多行\n名字"]);
+        let age: i64 = sqlx::query_scalar("SELECT age FROM people WHERE name = 'alice'")
+            .fetch_one(&pool)
+            .await?;
+        assert_eq!(age, 30);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_json_lines() -> Result<(), ImportError> {
+        let pool = pool().await;
+        let input = r#"{"name": "alice", "age": 30, "extra": true}
+not json
+{"name": "a name longer than twenty", "age": null}
+
+{"name": "dave", "age": 12}
+"#;
+        let spec = spec(Format::JsonLines).column(
+            "email",
+            vec![Rule::custom(|v| {
+                if v.ends_with(".com") {
+                    Ok(())
+                } else {
+                    Err("只允许 .com".to_string())
+                }
+            })],
+        );
+        let report = import::run(&pool, input.as_bytes(), &spec).await?;
+        assert_eq!(report.inserted, 2);
+        let lines: Vec<usize> = report.rejected.iter().map(|r| r.line).collect();
+        assert_eq!(lines, vec![2, 3]);
+        assert!(report.rejected[0].reasons[0].starts_with("无效的 JSON"));
+        assert_eq!(
+            report.rejected[1].reasons,
+            vec!["name: 长度超过 20".to_string()]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invalid_table() {
+        let pool = pool().await;
+        let spec = ImportSpec::new("people; DROP TABLE people", Format::Csv);
+        assert!(matches!(
+            import::run(&pool, "a\n".as_bytes(), &spec).await,
+            Err(ImportError::InvalidIdentifier(_))
+        ));
+    }
+}
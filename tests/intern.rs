@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+use std::sync::MutexGuard;
+
+use std_app::intern::{self, Symbol};
+use std_app::metrics::Registry;
+
+// 驻留表是全局的，测试之间串行执行
+static TABLE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+fn lock() -> MutexGuard<'static, ()> {
+    let guard = TABLE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    intern::reset();
+    guard
+}
+
+#[cfg(test)]
+mod test_intern {
+    use super::*;
+
+    #[test]
+    fn test_same_string_same_symbol() {
+        let _guard = lock();
+        let a = intern::intern("http_requests_total");
+        let b = intern::intern(&String::from("http_requests_total"));
+        assert_eq!(a, b);
+        assert!(std::ptr::eq(a.as_str(), b.as_str()));
+        assert_ne!(a, intern::intern("db_queries_total"));
+        assert_eq!(intern::get("http_requests_total"), Some(a));
+        assert_eq!(intern::get("unknown"), None);
+
+        //可以用 &str 查找以 Symbol 为 key 的映射
+        let mut counts: HashMap<Symbol, u32> = HashMap::new();
+        counts.insert(a, 1);
+        assert_eq!(counts.get("http_requests_total"), Some(&1));
+        assert_eq!(a.to_string(), "http_requests_total");
+        assert_eq!(
+            serde_json::to_string(&a).unwrap(),
+            "\"http_requests_total\""
+        );
+    }
+
+    #[test]
+    fn test_stats_and_dump() {
+        let _guard = lock();
+        for _ in 0..10 {
+            intern::intern("config.database.url");
+        }
+        intern::intern("app");
+
+        let stats = intern::stats();
+        assert_eq!(stats.symbols, 2);
+        assert_eq!(stats.bytes, 19 + 3);
+        assert_eq!(stats.lookups, 11);
+        assert_eq!(stats.saved_bytes(), 9 * 19);
+
+        let dump = intern::dump();
+        assert_eq!(dump[0], (Symbol::from("config.database.url"), 10));
+        assert_eq!(dump[1].1, 1);
+        let text = intern::dump_text();
+        assert!(text.starts_with("symbols=2 bytes=22 lookups=12"));
+        assert!(text.contains("      11 config.database.url"));
+    }
+
+    //reset 之后旧的 Symbol 仍然可用，并且与新驻留的相等
+    #[test]
+    fn test_reset() {
+        let _guard = lock();
+        let before = intern::intern("db");
+        intern::reset();
+        assert_eq!(intern::stats().symbols, 0);
+        assert_eq!(before.as_str(), "db");
+        assert_eq!(before, intern::intern("db"));
+    }
+
+    #[test]
+    fn test_metric_names_interned() {
+        let _guard = lock();
+        let registry = Registry::new();
+        registry.counter("jobs_total").inc();
+        registry.counter("jobs_total").inc();
+        assert_eq!(registry.counter("jobs_total").get(), 2);
+        assert!(intern::get("jobs_total").is_some());
+        assert_eq!(registry.snapshot()[0].name, "jobs_total");
+    }
+}
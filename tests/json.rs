@@ -0,0 +1,141 @@
+use serde_json::{json, Value};
+
+use std_app::json::{self, JsonError};
+
+fn sample() -> Value {
+    json!({
+        "total": 3,
+        "items": [
+            {"name": "apple", "price": 3, "meta": {"status": "active"}},
+            {"name": "pear", "price": 12, "meta": {"status": "sold out"}},
+            {"name": "a/b", "price": 20, "meta": {"status": "active"}}
+        ]
+    })
+}
+
+#[cfg(test)]
+mod test_json_path {
+    use super::*;
+
+    #[test]
+    fn test_get_path() {
+        let v = sample();
+        assert_eq!(json::get_path(&v, "/items/0/name"), Some(&json!("apple")));
+        assert_eq!(json::get_path(&v, "/total"), Some(&json!(3)));
+        assert_eq!(json::get_path(&v, "/items/9/name"), None);
+    }
+
+    #[test]
+    fn test_wildcard() -> Result<(), JsonError> {
+        let v = sample();
+        let names = json::query(&v, "/items/*/name")?;
+        assert_eq!(names, vec![&json!("apple"), &json!("pear"), &json!("a/b")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_filters() -> Result<(), JsonError> {
+        let v = sample();
+        let names = json::query(&v, "/items/[price>10]/name")?;
+        assert_eq!(names, vec![&json!("pear"), &json!("a/b")]);
+
+        let names = json::query(&v, r#"/items/[meta.status=="active"]/name"#)?;
+        assert_eq!(names, vec![&json!("apple"), &json!("a/b")]);
+
+        // 字面量中包含 `/`
+        let price = json::query_one(&v, r#"/items/[name=="a/b"]/price"#)?;
+        assert_eq!(price, Some(&json!(20)));
+
+        let names = json::query(&v, "/items/[meta.status!=active]/name")?;
+        assert_eq!(names, vec![&json!("pear")]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_query() {
+        let v = sample();
+        assert!(matches!(
+            json::query(&v, "items"),
+            Err(JsonError::InvalidQuery(_))
+        ));
+        assert!(matches!(
+            json::query(&v, "/items/[price]"),
+            Err(JsonError::InvalidQuery(_))
+        ));
+        assert!(matches!(
+            json::query(&v, "/items/[price>1"),
+            Err(JsonError::InvalidQuery(_))
+        ));
+    }
+}
+
+#[cfg(test)]
+mod test_json_schema {
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use std_app::json::{self, JsonError, Schema, Violation};
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Person {
+        name: String,
+        age: u32,
+    }
+
+    fn person_schema() -> Schema {
+        Schema::compile(&json!({
+            "type": "object",
+            "required": ["name", "age"],
+            "properties": {
+                "name": {"type": "string", "minLength": 1},
+                "age": {"type": "integer", "minimum": 0, "maximum": 150}
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_valid() -> Result<(), JsonError> {
+        let person: Person =
+            json::parse_validated(br#"{"name": "John Doe", "age": 30}"#, &person_schema())?;
+        assert_eq!(
+            person,
+            Person {
+                name: "John Doe".to_string(),
+                age: 30
+            }
+        );
+        Ok(())
+    }
+
+    //一次报告所有错误
+    #[test]
+    fn test_reports_all_violations() {
+        let result =
+            json::parse_validated::<Person>(br#"{"name": "", "age": 200}"#, &person_schema());
+        match result {
+            Err(JsonError::Validation(violations)) => {
+                let paths: Vec<_> = violations.iter().map(|v| v.path.as_str()).collect();
+                assert_eq!(violations.len(), 2, "{:?}", violations);
+                assert!(paths.contains(&"/name"));
+                assert!(paths.contains(&"/age"));
+            }
+            other => panic!("期望 Validation 错误, 实际: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_syntax_and_schema_errors() {
+        let result = json::parse_validated::<Person>(b"{not json", &person_schema());
+        assert!(matches!(result, Err(JsonError::Parse(_))));
+
+        let result = Schema::compile(&json!({"type": 12}));
+        assert!(matches!(result, Err(JsonError::InvalidSchema(_))));
+
+        let violation = Violation {
+            path: String::new(),
+            message: "\"age\" is a required property".to_string(),
+        };
+        assert_eq!(violation.to_string(), "/: \"age\" is a required property");
+    }
+}
@@ -0,0 +1,146 @@
+use std::time::Duration;
+
+use sqlx::sqlite::SqlitePoolOptions;
+
+use std_app::clock::{Clock, SimClock};
+use std_app::ledger::{Ledger, LedgerError, Posting};
+
+async fn ledger(clock: &SimClock) -> Ledger {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let ledger = Ledger::with_clock(pool, clock.shared());
+    ledger.migrate().await.unwrap();
+    ledger
+}
+
+#[cfg(test)]
+mod test_ledger {
+    use super::*;
+
+    //原来的 process_payment 变成持久化的转账
+    #[tokio::test]
+    async fn test_payment_and_balances() -> Result<(), LedgerError> {
+        let clock = SimClock::new();
+        let ledger = ledger(&clock).await;
+        let bank = ledger.open_account("bank", true).await?;
+        let alice = ledger.open_account("alice", false).await?;
+        let shop = ledger.open_account("shop", false).await?;
+
+        ledger.transfer(bank.id, alice.id, 10_000, "充值").await?;
+        let after_deposit = clock.system_time();
+        clock.advance(Duration::from_secs(60));
+        ledger.transfer(alice.id, shop.id, 3_000, "购买").await?;
+
+        assert_eq!(ledger.balance(alice.id).await?, 7_000);
+        assert_eq!(ledger.balance(shop.id).await?, 3_000);
+        assert_eq!(ledger.balance(bank.id).await?, -10_000);
+        assert_eq!(ledger.balance_at(alice.id, after_deposit).await?, 10_000);
+        assert_eq!(ledger.trial_balance().await?, 0);
+
+        let entries = ledger.entries(alice.id).await?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].description, "购买");
+        assert_eq!(entries[1].amount, -3_000);
+        assert_eq!(ledger.account("shop").await?, shop);
+        Ok(())
+    }
+
+    //余额不足时整个交易回滚
+    #[tokio::test]
+    async fn test_insufficient_funds_rolls_back() -> Result<(), LedgerError> {
+        let clock = SimClock::new();
+        let ledger = ledger(&clock).await;
+        let bank = ledger.open_account("bank", true).await?;
+        let alice = ledger.open_account("alice", false).await?;
+        let shop = ledger.open_account("shop", false).await?;
+        ledger.transfer(bank.id, alice.id, 50, "充值").await?;
+
+        match ledger.transfer(alice.id, shop.id, 100, "购买").await {
+            Err(LedgerError::InsufficientFunds {
+                account,
+                required,
+                available,
+            }) => {
+                assert_eq!(account, "alice");
+                assert_eq!(required, 100);
+                assert_eq!(available, 50);
+            }
+            other => panic!("期望 InsufficientFunds, 实际: {:?}", other),
+        }
+        assert_eq!(ledger.balance(alice.id).await?, 50);
+        assert_eq!(ledger.balance(shop.id).await?, 0);
+        assert_eq!(ledger.entries(shop.id).await?.len(), 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_invariants() -> Result<(), LedgerError> {
+        let clock = SimClock::new();
+        let ledger = ledger(&clock).await;
+        let a = ledger.open_account("a", true).await?;
+        let b = ledger.open_account("b", true).await?;
+        let posting = |account, amount| Posting { account, amount };
+
+        assert!(matches!(
+            ledger
+                .post("x", &[posting(a.id, 10), posting(b.id, -5)])
+                .await,
+            Err(LedgerError::Unbalanced(5))
+        ));
+        assert!(matches!(
+            ledger.post("x", &[posting(a.id, 10)]).await,
+            Err(LedgerError::TooFewPostings)
+        ));
+        assert!(matches!(
+            ledger
+                .post("x", &[posting(a.id, 0), posting(b.id, 0)])
+                .await,
+            Err(LedgerError::ZeroAmount)
+        ));
+        assert!(matches!(
+            ledger.transfer(a.id, 999, 1, "x").await,
+            Err(LedgerError::AccountNotFound(_))
+        ));
+        assert!(matches!(
+            ledger.account("missing").await,
+            Err(LedgerError::AccountNotFound(_))
+        ));
+        assert_eq!(ledger.trial_balance().await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_amount_overflow_is_rejected() -> Result<(), LedgerError> {
+        let clock = SimClock::new();
+        let ledger = ledger(&clock).await;
+        let a = ledger.open_account("a", true).await?;
+        let b = ledger.open_account("b", true).await?;
+        let c = ledger.open_account("c", true).await?;
+        let posting = |account, amount| Posting { account, amount };
+
+        // 合计会回绕成 0 的交易
+        assert!(matches!(
+            ledger
+                .post(
+                    "x",
+                    &[
+                        posting(a.id, i64::MAX),
+                        posting(b.id, i64::MAX),
+                        posting(c.id, 2)
+                    ]
+                )
+                .await,
+            Err(LedgerError::AmountOutOfRange)
+        ));
+        assert!(matches!(
+            ledger.transfer(a.id, b.id, i64::MIN, "x").await,
+            Err(LedgerError::AmountOutOfRange)
+        ));
+        assert_eq!(ledger.trial_balance().await?, 0);
+        assert!(ledger.entries(a.id).await?.is_empty());
+        Ok(())
+    }
+}
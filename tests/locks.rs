@@ -0,0 +1,104 @@
+use std::thread;
+use std::time::Duration;
+
+use std_app::locks::{self, TrackedMutex, TrackedRwLock};
+
+// 测试并行执行，记录是全局的，每个测试使用自己的锁名并只看相关的记录
+
+#[cfg(test)]
+mod test_tracked_locks {
+    use super::*;
+
+    #[test]
+    fn test_detects_order_inversion() {
+        let accounts = TrackedMutex::new("test.accounts", 0);
+        let ledger = TrackedMutex::new("test.ledger", Vec::<i32>::new());
+        {
+            let _a = accounts.lock().unwrap();
+            ledger.lock().unwrap().push(1);
+        }
+        assert!(locks::report()
+            .involving(&["test.accounts", "test.ledger"])
+            .is_clean());
+
+        // 另一个线程按相反的顺序加锁；这里不会真的死锁，但顺序已经不一致
+        thread::scope(|s| {
+            s.spawn(|| {
+                let _l = ledger.lock().unwrap();
+                *accounts.lock().unwrap() += 1;
+            });
+        });
+        let report = locks::report().involving(&["test.accounts", "test.ledger"]);
+        assert_eq!(report.inversions.len(), 1);
+        let inversion = &report.inversions[0];
+        assert_eq!(
+            (inversion.first, inversion.second),
+            ("test.ledger", "test.accounts")
+        );
+        assert!(inversion
+            .to_string()
+            .contains("test.accounts -> test.ledger"));
+
+        // 同一个反转只记录一次
+        {
+            let _l = ledger.lock().unwrap();
+            let _a = accounts.lock().unwrap();
+        }
+        let report = locks::report().involving(&["test.accounts", "test.ledger"]);
+        assert_eq!(report.inversions.len(), 1);
+    }
+
+    //经过中间锁形成的环也能发现
+    #[test]
+    fn test_transitive_inversion() {
+        let a = TrackedRwLock::new("test.chain.a", ());
+        let b = TrackedMutex::new("test.chain.b", ());
+        let c = TrackedRwLock::new("test.chain.c", ());
+        {
+            let _a = a.read().unwrap();
+            let _b = b.lock().unwrap();
+        }
+        {
+            let _b = b.lock().unwrap();
+            let _c = c.write().unwrap();
+        }
+        {
+            let _c = c.read().unwrap();
+            let _a = a.write().unwrap();
+        }
+        let report = locks::report().involving(&["test.chain.a", "test.chain.c"]);
+        assert_eq!(report.inversions.len(), 1);
+        assert_eq!(report.inversions[0].first, "test.chain.c");
+    }
+
+    #[test]
+    fn test_long_hold() {
+        locks::set_hold_threshold(Duration::from_millis(30));
+        let cache = TrackedMutex::new("test.slow_cache", 0);
+        {
+            let mut guard = cache.lock().unwrap();
+            *guard += 1;
+            thread::sleep(Duration::from_millis(50));
+        }
+        *cache.lock().unwrap() += 1;
+        let report = locks::report().involving(&["test.slow_cache"]);
+        assert_eq!(report.long_holds.len(), 1);
+        assert!(report.long_holds[0].held >= Duration::from_millis(50));
+        assert_eq!(cache.into_inner().unwrap(), 2);
+    }
+
+    //持有锁时 panic 同样会毒化，和标准库一致
+    #[test]
+    fn test_poison() {
+        let lock = TrackedMutex::new("test.poison", 1);
+        let _ = thread::scope(|s| {
+            s.spawn(|| {
+                let _guard = lock.lock().unwrap();
+                panic!("故意 panic");
+            })
+            .join()
+        });
+        let err = lock.lock().unwrap_err();
+        assert_eq!(*err.into_inner(), 1);
+    }
+}
@@ -0,0 +1,162 @@
+use std_app::context::{self, RequestContext};
+use std_app::logs::{self, Level};
+use std_app::ring::RingBuffer;
+
+// 读写全局缓冲区的测试需要串行执行
+static BUFFER_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+#[cfg(test)]
+mod test_ring {
+    use super::*;
+
+    #[test]
+    fn test_overwrite_oldest() {
+        let mut ring = RingBuffer::new(3);
+        for i in 1..=3 {
+            assert_eq!(ring.push(i), None);
+        }
+        assert_eq!(ring.push(4), Some(1));
+        assert_eq!(ring.to_vec(), vec![2, 3, 4]);
+
+        ring.set_capacity(2);
+        assert_eq!(ring.iter().copied().collect::<Vec<_>>(), vec![3, 4]);
+    }
+}
+
+#[cfg(test)]
+mod test_logs {
+    use super::*;
+
+    #[test]
+    fn test_dump_recent() {
+        let _lock = BUFFER_LOCK.lock().unwrap();
+        logs::set_capacity(3);
+        logs::set_buffer_level(Level::Debug);
+        logs::set_output_level(Level::Error);
+
+        logs::info("app", "starting");
+        logs::debug("db", "connect");
+        logs::log(Level::Trace, "db", "not buffered");
+        context::sync_scope(RequestContext::with_request_id("req-1"), || {
+            logs::warn("http", "slow request");
+        });
+        logs::error("http", "upstream failed");
+
+        let recent = logs::dump_recent();
+        let messages: Vec<_> = recent.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, vec!["connect", "slow request", "upstream failed"]);
+        assert_eq!(recent[1].request_id.as_deref(), Some("req-1"));
+
+        let lines = logs::recent_lines();
+        assert!(lines[1].contains("WARN  http: slow request request_id=req-1"));
+
+        let json: serde_json::Value = serde_json::from_str(&logs::dump_json()).unwrap();
+        assert_eq!(json[2]["level"], "ERROR");
+
+        logs::clear();
+        assert!(logs::dump_recent().is_empty());
+        assert_eq!("warning".parse::<Level>().unwrap(), Level::Warn);
+    }
+}
+
+#[cfg(test)]
+mod test_redaction {
+    use std_app::redact::RedactConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_messages_are_redacted() {
+        let _lock = BUFFER_LOCK.lock().unwrap();
+        logs::set_capacity(100);
+        logs::set_buffer_level(Level::Debug);
+
+        logs::info(
+            "redact_pay",
+            "charge 4111 1111 1111 1111 for alice@example.com",
+        );
+        let message = |target: &str| {
+            logs::dump_recent()
+                .into_iter()
+                .rfind(|r| r.target == target)
+                .unwrap()
+                .message
+        };
+        assert_eq!(message("redact_pay"), "charge *** for ***");
+
+        // 配置文件中的规则
+        let config = RedactConfig {
+            mask: "[hidden]".to_string(),
+            patterns: vec![std_app::redact::PatternRule {
+                name: "order".to_string(),
+                regex: r"ORD-\d+".to_string(),
+            }],
+            ..RedactConfig::default()
+        };
+        logs::set_redaction(&config).unwrap();
+        logs::info("redact_order", "order ORD-42 by bob@example.com");
+        assert_eq!(message("redact_order"), "order [hidden] by [hidden]");
+        logs::set_redaction(&RedactConfig::default()).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod test_levels {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_set_level_per_module() {
+        logs::set_level("levels_db", Level::Debug);
+        assert!(logs::enabled(Level::Debug, "levels_db"));
+        assert!(logs::enabled(Level::Debug, "levels_db::pool"));
+        assert!(!logs::enabled(Level::Debug, "levels_dbx"));
+        assert!(!logs::enabled(Level::Debug, "levels_http"));
+
+        // 更具体的模块优先
+        logs::set_level("levels_db::pool", Level::Warn);
+        assert!(!logs::enabled(Level::Info, "levels_db::pool"));
+        logs::reset_level("levels_db");
+        logs::reset_level("levels_db::pool");
+        assert!(!logs::enabled(Level::Debug, "levels_db"));
+    }
+
+    //临时调整的级别到期后自动恢复
+    #[test]
+    fn test_automatic_revert() {
+        logs::set_level_for("levels_cache", Level::Trace, Duration::from_millis(50));
+        assert!(logs::enabled(Level::Trace, "levels_cache"));
+        std::thread::sleep(Duration::from_millis(80));
+        assert!(!logs::enabled(Level::Trace, "levels_cache"));
+    }
+
+    #[test]
+    fn test_directive() {
+        logs::apply_directive("levels_job=debug@300").unwrap();
+        assert!(logs::enabled(Level::Debug, "levels_job"));
+        assert!(logs::apply_directive("levels_job").is_err());
+        assert!(logs::apply_directive("levels_job=loud").is_err());
+        assert!(logs::apply_directive("levels_job=info@soon").is_err());
+        logs::reset_level("levels_job");
+    }
+
+    #[test]
+    fn test_sampling() {
+        let _lock = BUFFER_LOCK.lock().unwrap();
+        logs::set_capacity(100);
+        logs::set_buffer_level(Level::Debug);
+        logs::set_sampling("levels_hot", 3);
+        for i in 0..9 {
+            logs::debug("levels_hot", &format!("hot {}", i));
+        }
+        logs::warn("levels_hot", "always kept");
+        let kept: Vec<_> = logs::dump_recent()
+            .into_iter()
+            .filter(|r| r.target == "levels_hot")
+            .map(|r| r.message)
+            .collect();
+        assert_eq!(kept, vec!["hot 0", "hot 3", "hot 6", "always kept"]);
+        logs::set_sampling("levels_hot", 1);
+    }
+}
@@ -0,0 +1,134 @@
+use std::io::Cursor;
+use std::sync::Mutex;
+
+use std_app::logs::{self, Level};
+use std_app::logscan::{self, Filter, Record, ScanError};
+
+// logs 的缓冲区是全局的
+static BUFFER_LOCK: Mutex<()> = Mutex::new(());
+
+const SAMPLE: &str = r#"{"timestamp_ms":1000,"level":"INFO","target":"http","message":"request done","status":200,"path":"/users"}
+{"timestamp_ms":2000,"level":"WARN","target":"db::pool","message":"slow query","elapsed_ms":812,"request_id":"r-1"}
+
+{"timestamp_ms":3000,"level":"ERROR","target":"db","message":"connection timeout","user_id":42}
+not json
+{"timestamp_ms":4000,"level":"debug","target":"dbx","message":"noise"}
+"#;
+
+fn collect(filter: &str) -> Vec<Record> {
+    logscan::scan(Cursor::new(SAMPLE), Filter::parse(filter).unwrap())
+        .filter_map(Result::ok)
+        .collect()
+}
+
+fn lines(records: &[Record]) -> Vec<usize> {
+    records.iter().map(|r| r.line).collect()
+}
+
+#[cfg(test)]
+mod test_parse {
+    use super::*;
+
+    #[test]
+    fn test_record_fields() {
+        let records = collect("");
+        assert_eq!(lines(&records), vec![1, 2, 4, 6]);
+        let warn = &records[1];
+        assert_eq!(warn.level, Level::Warn);
+        assert_eq!(warn.target, "db::pool");
+        assert_eq!(warn.request_id.as_deref(), Some("r-1"));
+        assert_eq!(warn.field("elapsed_ms"), Some(&serde_json::json!(812)));
+        assert!(warn.field("message").is_none());
+    }
+
+    //无法解析的行返回错误，后面的行继续处理
+    #[test]
+    fn test_errors_do_not_stop_scan() {
+        let results: Vec<_> = logscan::scan(Cursor::new(SAMPLE), Filter::new()).collect();
+        assert_eq!(results.len(), 5);
+        assert!(matches!(results[3], Err(ScanError::Json { line: 5, .. })));
+        assert!(results[4].is_ok());
+
+        let missing = Record::parse(1, r#"{"level":"INFO"}"#).unwrap_err();
+        assert!(matches!(
+            missing,
+            ScanError::Missing {
+                field: "message",
+                ..
+            }
+        ));
+        assert!(Record::parse(1, r#"{"level":"LOUD","message":"x"}"#).is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_filter {
+    use super::*;
+
+    #[test]
+    fn test_level_and_target() {
+        assert_eq!(lines(&collect("level>=warn")), vec![2, 4]);
+        assert_eq!(lines(&collect("level=info")), vec![1]);
+        assert_eq!(lines(&collect("level<=info")), vec![1, 6]);
+        // dbx 不是 db 的子模块
+        assert_eq!(lines(&collect("target=db")), vec![2, 4]);
+    }
+
+    #[test]
+    fn test_time_and_fields() {
+        assert_eq!(lines(&collect("since=2000 until=4000")), vec![2, 4]);
+        assert_eq!(lines(&collect("user_id=42")), vec![4]);
+        assert_eq!(lines(&collect("status=200 path~user")), vec![1]);
+        assert_eq!(lines(&collect("request_id=r-1")), vec![2]);
+        assert_eq!(lines(&collect(r#"message~"slow query""#)), vec![2]);
+        assert!(collect("missing=1").is_empty());
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        for expr in [
+            "level>=loud",
+            "since=yesterday",
+            "noop",
+            "=x",
+            r#"message~"open"#,
+        ] {
+            assert!(
+                matches!(Filter::parse(expr), Err(ScanError::Filter(_))),
+                "{}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn test_builder_matches_parse() {
+        let built = Filter::new()
+            .min_level(Level::Warn)
+            .target("db")
+            .since(3000);
+        assert_eq!(
+            built,
+            Filter::parse("level>=warn target=db since=3000").unwrap()
+        );
+        let records: Vec<_> = logscan::scan(Cursor::new(SAMPLE), built)
+            .filter_map(Result::ok)
+            .collect();
+        assert_eq!(lines(&records), vec![4]);
+    }
+
+    //断言内存缓冲区中的日志
+    #[test]
+    fn test_recent_logs() {
+        let _lock = BUFFER_LOCK.lock().unwrap();
+        logs::clear();
+        logs::info("logscan::test", "started");
+        logs::warn("logscan::test", "disk almost full");
+        logs::warn("other", "ignored");
+
+        let found = logscan::recent(&Filter::parse("level>=warn target=logscan").unwrap());
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].message, "disk almost full");
+        logs::clear();
+    }
+}
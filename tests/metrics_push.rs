@@ -0,0 +1,156 @@
+use std::net::UdpSocket;
+use std::time::Duration;
+
+use std_app::http::HttpClient;
+use std_app::metrics::prometheus;
+use std_app::metrics::push::{PushGateway, StatsdClient};
+use std_app::metrics::Registry;
+use std_app::testkit::http::{MockHttp, MockResponse};
+
+fn registry() -> Registry {
+    let registry = Registry::new();
+    registry.counter("cli.runs_total").add(3);
+    registry.gauge("queue_depth").set(7.5);
+    let latency = registry.histogram("latency_ms");
+    for v in 1..=100 {
+        latency.observe(v as f64);
+    }
+    registry
+}
+
+fn receiver() -> UdpSocket {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket
+        .set_read_timeout(Some(Duration::from_secs(2)))
+        .unwrap();
+    socket
+}
+
+fn recv(socket: &UdpSocket) -> String {
+    let mut buf = [0u8; 65536];
+    let n = socket.recv(&mut buf).unwrap();
+    String::from_utf8_lossy(&buf[..n]).into_owned()
+}
+
+#[cfg(test)]
+mod test_prometheus {
+    use super::*;
+
+    #[test]
+    fn test_render_text_format() {
+        let text = registry().render_prometheus();
+        assert!(text.contains("# TYPE cli_runs_total counter\ncli_runs_total 3\n"));
+        assert!(text.contains("# TYPE queue_depth gauge\nqueue_depth 7.5\n"));
+        assert!(text.contains("# TYPE latency_ms summary\n"));
+        assert!(text.contains("latency_ms{quantile=\"0.99\"}"));
+        assert!(text.contains("latency_ms_sum 5050\nlatency_ms_count 100\n"));
+    }
+
+    #[test]
+    fn test_sanitize_name() {
+        assert_eq!(
+            prometheus::sanitize_name("http.requests-total"),
+            "http_requests_total"
+        );
+        assert_eq!(prometheus::sanitize_name("5xx"), "_5xx");
+        assert_eq!(prometheus::sanitize_name("a:b_c"), "a:b_c");
+    }
+}
+
+#[cfg(test)]
+mod test_push_gateway {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_push_puts_to_grouping_path() {
+        let server = MockHttp::start().await.unwrap();
+        let gateway =
+            PushGateway::new(HttpClient::new(server.url()), "nightly").label("instance", "a/b");
+        gateway.push(&registry()).await.unwrap();
+        gateway.push_add(&registry()).await.unwrap();
+        gateway.delete().await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].method, "PUT");
+        assert_eq!(requests[0].path, "/metrics/job/nightly/instance/a%2Fb");
+        assert!(requests[0].body_str().contains("cli_runs_total 3"));
+        assert_eq!(requests[1].method, "POST");
+        assert_eq!(requests[2].method, "DELETE");
+    }
+
+    #[tokio::test]
+    async fn test_push_failure_is_tolerated() {
+        let server = MockHttp::with_handler(|_| MockResponse::new(503))
+            .await
+            .unwrap();
+        let gateway = PushGateway::new(HttpClient::new(server.url()), "nightly");
+        assert!(gateway.push(&registry()).await.is_err());
+        //push_or_warn 只记日志
+        assert!(!gateway.push_or_warn(&registry()).await);
+    }
+}
+
+#[cfg(test)]
+mod test_statsd {
+    use super::*;
+
+    #[test]
+    fn test_lines_are_batched_into_one_packet() {
+        let socket = receiver();
+        let client = StatsdClient::new(socket.local_addr().unwrap(), "app").unwrap();
+        client.count("runs", 1);
+        client.gauge("depth", 2.5);
+        client.timing("took", Duration::from_millis(12));
+        client.flush();
+        assert_eq!(
+            recv(&socket),
+            "app.runs:1|c\napp.depth:2.5|g\napp.took:12|ms"
+        );
+        assert_eq!(client.sent(), 1);
+    }
+
+    #[test]
+    fn test_packets_respect_max_size() {
+        let socket = receiver();
+        let client = StatsdClient::new(socket.local_addr().unwrap(), "")
+            .unwrap()
+            .max_packet(100);
+        for i in 0..20 {
+            client.count(&format!("metric_{:02}", i), 1);
+        }
+        client.flush();
+        let mut lines = 0;
+        while lines < 20 {
+            let packet = recv(&socket);
+            assert!(packet.len() <= 100);
+            lines += packet.lines().count();
+        }
+        assert!(client.sent() > 1);
+    }
+
+    #[test]
+    fn test_send_registry_sends_counter_deltas() {
+        let socket = receiver();
+        let client = StatsdClient::new(socket.local_addr().unwrap(), "").unwrap();
+        let registry = registry();
+        client.send_registry(&registry);
+        let first = recv(&socket);
+        assert!(first.contains("cli.runs_total:3|c"));
+        assert!(first.contains("queue_depth:7.5|g"));
+        assert!(first.contains("latency_ms.count:100|g"));
+
+        registry.counter("cli.runs_total").add(2);
+        client.send_registry(&registry);
+        assert!(recv(&socket).contains("cli.runs_total:2|c"));
+    }
+
+    #[test]
+    fn test_drop_flushes_pending() {
+        let socket = receiver();
+        let client = StatsdClient::new(socket.local_addr().unwrap(), "").unwrap();
+        client.count("last", 1);
+        drop(client);
+        assert_eq!(recv(&socket), "last:1|c");
+    }
+}
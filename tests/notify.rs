@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use std_app::app::BoxError;
+use std_app::clock::SimClock;
+use std_app::mail::Mailer;
+use std_app::notify::{
+    Dispatcher, EmailNotifier, LogNotifier, Notification, Notifier, NotifyConfig, NotifyFuture,
+    RateLimit, WebhookNotifier,
+};
+use std_app::retry::Backoff;
+use std_app::testkit::http::MockHttp;
+use std_app::testkit::smtp::MockSmtp;
+
+async fn wait_until(f: impl Fn() -> bool) {
+    for _ in 0..200 {
+        if f() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("等待超时");
+}
+
+// 前 n 次发送失败
+struct Flaky {
+    failures: AtomicUsize,
+    sent: Arc<AtomicUsize>,
+}
+
+impl Notifier for Flaky {
+    fn send<'a>(&'a self, _: &'a Notification) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            if self.failures.load(Ordering::SeqCst) > 0 {
+                self.failures.fetch_sub(1, Ordering::SeqCst);
+                return Err(BoxError::from("暂时不可用"));
+            }
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_notify {
+    use super::*;
+
+    //按事件类型选择渠道
+    #[tokio::test]
+    async fn test_routing() {
+        let smtp = MockSmtp::start().await.unwrap();
+        let http = MockHttp::start().await.unwrap();
+        let config: NotifyConfig = serde_json::from_str(
+            r#"{"routes": {"payment.failed": ["email", "webhook"]}, "default": ["log"]}"#,
+        )
+        .unwrap();
+        let dispatcher = Dispatcher::builder(config)
+            .channel(
+                "email",
+                EmailNotifier::new(
+                    Mailer::new(smtp.addr()),
+                    "app@example.com",
+                    &["ops@example.com"],
+                ),
+            )
+            .channel(
+                "webhook",
+                WebhookNotifier::new(&format!("{}/hook", http.url())),
+            )
+            .channel("log", LogNotifier)
+            .start();
+
+        dispatcher.notify(Notification::new(
+            "payment.failed",
+            "支付失败",
+            "订单 42\n.单独一行的点",
+        ));
+        dispatcher.notify(Notification::new("user.created", "新用户", "alice"));
+        wait_until(|| dispatcher.deliveries().len() == 3).await;
+
+        let mails = smtp.mails();
+        assert_eq!(mails.len(), 1);
+        assert_eq!(mails[0].to, vec!["ops@example.com".to_string()]);
+        assert_eq!(mails[0].header("Subject"), Some("支付失败"));
+        assert!(mails[0].body().contains(".单独一行的点"));
+        let hooks = http.requests();
+        assert_eq!(hooks[0].path, "/hook");
+        assert!(hooks[0].body_str().contains("payment.failed"));
+        assert!(dispatcher.deliveries().iter().all(|d| d.error.is_none()));
+        assert_eq!(dispatcher.channels_for("other"), vec!["log".to_string()]);
+    }
+
+    //失败重试，且每个渠道单独限流
+    #[tokio::test]
+    async fn test_retry_and_rate_limit() {
+        let clock = SimClock::new();
+        let sent = Arc::new(AtomicUsize::new(0));
+        let config = NotifyConfig {
+            default: vec!["flaky".to_string()],
+            rate_limits: HashMap::from([(
+                "flaky".to_string(),
+                RateLimit {
+                    burst: 2,
+                    per_second: 1.0,
+                },
+            )]),
+            ..NotifyConfig::default()
+        };
+        let dispatcher = Dispatcher::builder(config)
+            .channel(
+                "flaky",
+                Flaky {
+                    failures: AtomicUsize::new(2),
+                    sent: sent.clone(),
+                },
+            )
+            .clock(clock.shared())
+            .backoff(Backoff::new(
+                Duration::from_millis(1),
+                Duration::from_millis(1),
+            ))
+            .start();
+
+        for i in 0..3 {
+            dispatcher.notify(Notification::new("e", &i.to_string(), ""));
+        }
+        // 重试的等待也走模拟时钟
+        wait_until(|| {
+            clock.advance(Duration::from_millis(1));
+            sent.load(Ordering::SeqCst) == 2
+        })
+        .await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(sent.load(Ordering::SeqCst), 2);
+
+        clock.advance(Duration::from_secs(1));
+        wait_until(|| sent.load(Ordering::SeqCst) == 3).await;
+        assert_eq!(dispatcher.deliveries().len(), 3);
+    }
+}
@@ -0,0 +1,81 @@
+use std_app::http::{ApiError, HttpClient};
+use std_app::openapi::{self, CodegenError};
+use std_app::status::HttpStatus;
+use std_app::testkit::http::{MockHttp, MockResponse};
+
+mod petstore {
+    include!("openapi/petstore.rs");
+}
+
+use petstore::{NewPet, PetType, PetstoreClient};
+
+const SPEC: &str = include_str!("openapi/petstore.json");
+
+#[cfg(test)]
+mod test_openapi {
+    use super::*;
+
+    //提交的生成代码必须和生成器输出一致
+    #[test]
+    fn test_generated_code_up_to_date() {
+        let spec = serde_json::from_str(SPEC).unwrap();
+        let code = openapi::generate(&spec, "PetstoreClient").unwrap();
+        assert_eq!(code, include_str!("openapi/petstore.rs"));
+    }
+
+    #[test]
+    fn test_invalid_spec() {
+        assert!(matches!(
+            openapi::generate(&serde_json::json!({}), "X"),
+            Err(CodegenError::Invalid(_))
+        ));
+        let spec = serde_json::json!({
+            "paths": {},
+            "components": {"schemas": {"A": {"properties": {"b": {"$ref": "other.json#/B"}}}}}
+        });
+        assert!(matches!(
+            openapi::generate(&spec, "X"),
+            Err(CodegenError::UnsupportedRef(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_generated_client() {
+        let server = MockHttp::with_handler(|req| match (req.method.as_str(), req.path.as_str()) {
+            ("GET", "/pets?status=available&limit=2") => MockResponse::ok(
+                r#"[{"id": "1", "name": "Tom", "type": "cat", "birthYear": 2020}]"#,
+            ),
+            ("POST", "/pets") => MockResponse::new(409).body("名字重复"),
+            ("GET", "/pets/a%2Fb") => {
+                MockResponse::ok(r#"{"id": "a/b", "name": "Rex", "type": "guinea-pig"}"#)
+            }
+            ("DELETE", _) => MockResponse::new(204),
+            _ => MockResponse::new(404),
+        })
+        .await
+        .unwrap();
+        let client = PetstoreClient::new(HttpClient::new(server.url()));
+
+        let pets = client.list_pets(Some("available"), 2).await.unwrap();
+        assert_eq!(pets[0].name, "Tom");
+        assert_eq!(pets[0].r#type, Some(PetType::Cat));
+        assert_eq!(pets[0].birth_year, Some(2020));
+
+        let pet = client.get_pet("a/b").await.unwrap();
+        assert_eq!(pet.r#type, Some(PetType::GuineaPig));
+        client.delete_pet("a/b").await.unwrap();
+
+        let new_pet = NewPet {
+            name: "Tom".to_string(),
+            r#type: None,
+        };
+        let err = client.create_pet(&new_pet).await.unwrap_err();
+        assert!(matches!(&err, ApiError::Conflict(body) if body == "名字重复"));
+        assert_eq!(err.status(), 409);
+        assert_eq!(server.requests()[3].body_str(), r#"{"name":"Tom"}"#);
+        assert!(matches!(
+            client.list_pets(None, 1).await,
+            Err(ApiError::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,74 @@
+// 由 std_app::openapi 根据 Petstore 1.0.0 生成，不要手动修改
+
+use serde::{Deserialize, Serialize};
+use std_app::http::{encode_path, ApiError, HttpClient, Method};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewPet {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<PetType>,
+}
+
+// 一只宠物
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Pet {
+    // 出生年份
+    #[serde(rename = "birthYear", default, skip_serializing_if = "Option::is_none")]
+    pub birth_year: Option<i64>,
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#type: Option<PetType>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PetType {
+    #[serde(rename = "cat")]
+    Cat,
+    #[serde(rename = "dog")]
+    Dog,
+    #[serde(rename = "guinea-pig")]
+    GuineaPig,
+}
+
+pub struct PetstoreClient {
+    http: HttpClient,
+}
+
+impl PetstoreClient {
+    pub fn new(http: HttpClient) -> Self {
+        PetstoreClient { http }
+    }
+
+    // GET /pets: 按状态列出宠物
+    pub async fn list_pets(&self, status: Option<&str>, limit: i32) -> Result<Vec<Pet>, ApiError> {
+        let path = "/pets";
+        let mut query: Vec<(&str, String)> = Vec::new();
+        if let Some(value) = status {
+            query.push(("status", value.to_string()));
+        }
+        query.push(("limit", limit.to_string()));
+        self.http.call::<(), _>(Method::GET, path, &query, None).await
+    }
+
+    // POST /pets
+    pub async fn create_pet(&self, body: &NewPet) -> Result<Pet, ApiError> {
+        let path = "/pets";
+        self.http.call::<NewPet, _>(Method::POST, path, &[], Some(body)).await
+    }
+
+    // GET /pets/{petId}
+    pub async fn get_pet(&self, pet_id: &str) -> Result<Pet, ApiError> {
+        let path = format!("/pets/{}", encode_path(pet_id));
+        self.http.call::<(), _>(Method::GET, &path, &[], None).await
+    }
+
+    // DELETE /pets/{petId}
+    pub async fn delete_pet(&self, pet_id: &str) -> Result<(), ApiError> {
+        let path = format!("/pets/{}", encode_path(pet_id));
+        self.http.call::<(), _>(Method::DELETE, &path, &[], None).await
+    }
+}
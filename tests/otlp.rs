@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use std_app::metrics::Registry;
+use std_app::otlp::{OtlpConfig, OtlpExporter};
+use std_app::testkit::http::MockHttp;
+use std_app::trace::{self, SpanData};
+
+fn config(endpoint: &str) -> OtlpConfig {
+    OtlpConfig {
+        enabled: true,
+        endpoint: endpoint.to_string(),
+        headers: HashMap::from([("x-api-key".to_string(), "secret".to_string())]),
+        ..OtlpConfig::default()
+    }
+}
+
+#[cfg(test)]
+mod test_trace {
+    use super::*;
+
+    #[test]
+    fn test_nested_spans() {
+        let (trace_id, parent_id) = {
+            let mut parent = trace::span("handle_request");
+            parent.attr("http.method", "GET");
+            let ids = (parent.trace_id().to_string(), parent.span_id().to_string());
+            let _child = trace::span("db_query");
+            ids
+        };
+        let spans: Vec<_> = trace::drain()
+            .into_iter()
+            .filter(|s| s.trace_id == trace_id)
+            .collect();
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].name, "db_query");
+        assert_eq!(spans[0].parent_span_id.as_deref(), Some(parent_id.as_str()));
+        assert_eq!(
+            spans[1].attributes,
+            vec![("http.method".to_string(), "GET".to_string())]
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_otlp {
+    use super::*;
+
+    #[test]
+    fn test_config_from_toml() {
+        let config: OtlpConfig = toml::from_str(
+            r#"
+            enabled = true
+            endpoint = "http://collector:4318"
+            sampling_ratio = 0.25
+            [headers]
+            authorization = "Bearer token"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.sampling_ratio, 0.25);
+        assert_eq!(config.service_name, "std-app");
+    }
+
+    #[test]
+    fn test_sampling_ratio() {
+        let exporter = OtlpExporter::new(OtlpConfig {
+            sampling_ratio: 0.5,
+            ..OtlpConfig::default()
+        });
+        assert!(exporter.sampled("00000000000000000000000000000001"));
+        assert!(!exporter.sampled("0000000000000000ffffffffffffffff"));
+    }
+
+    #[tokio::test]
+    async fn test_export_to_collector() {
+        let collector = MockHttp::start().await.unwrap();
+        let exporter = OtlpExporter::new(config(collector.url()));
+
+        let registry = Registry::new();
+        registry.counter("http_requests_total").add(3);
+        registry.gauge("queue_depth").set(1.5);
+        exporter.export_metrics(&registry).await.unwrap();
+
+        // 其他测试会并发 drain 全局收集器，这里直接构造 span
+        let now = std::time::SystemTime::now();
+        let spans = vec![SpanData {
+            trace_id: "0123456789abcdef0123456789abcdef".to_string(),
+            span_id: "0123456789abcdef".to_string(),
+            parent_span_id: None,
+            name: "export_test".to_string(),
+            start: now,
+            end: now,
+            attributes: Vec::new(),
+        }];
+        exporter.export_spans(&spans).await.unwrap();
+
+        let requests = collector.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].path, "/v1/metrics");
+        assert_eq!(requests[0].header("x-api-key"), Some("secret"));
+        let body: Value = serde_json::from_slice(&requests[0].body).unwrap();
+        let metrics = &body["resourceMetrics"][0]["scopeMetrics"][0]["metrics"];
+        assert_eq!(metrics[0]["name"], "http_requests_total");
+        assert_eq!(metrics[0]["sum"]["dataPoints"][0]["asInt"], "3");
+
+        assert_eq!(requests[1].path, "/v1/traces");
+        let body: Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(
+            body["resourceSpans"][0]["scopeSpans"][0]["spans"][0]["name"],
+            "export_test"
+        );
+
+        // 运行时关闭后不再发送
+        exporter.set_enabled(false);
+        exporter.export_metrics(&registry).await.unwrap();
+        assert_eq!(collector.requests().len(), 2);
+    }
+}
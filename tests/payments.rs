@@ -0,0 +1,292 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::sqlite::SqlitePoolOptions;
+
+use std_app::clock::SimClock;
+use std_app::ledger::{Account, Ledger};
+use std_app::payments::{
+    Gateway, GatewayError, GatewayFuture, HttpGateway, MaxAmount, MockGateway, PaymentError,
+    PaymentRequest, Payments, ReconcileReport, DEFAULT_LEASE,
+};
+use std_app::testkit::http::{MockHttp, MockResponse};
+
+struct Fixture {
+    payments: Payments,
+    ledger: Ledger,
+    alice: Account,
+    shop: Account,
+    clock: SimClock,
+}
+
+async fn fixture(gateway: Arc<dyn Gateway>) -> Fixture {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let ledger = Ledger::new(pool);
+    let clock = SimClock::new();
+    let payments = Payments::new(ledger.clone(), gateway)
+        .rule(MaxAmount(100_000))
+        .clock(clock.shared());
+    payments.migrate().await.unwrap();
+    let bank = ledger.open_account("bank", true).await.unwrap();
+    let alice = ledger.open_account("alice", false).await.unwrap();
+    let shop = ledger.open_account("shop", false).await.unwrap();
+    ledger
+        .transfer(bank.id, alice.id, 10_000, "充值")
+        .await
+        .unwrap();
+    Fixture {
+        payments,
+        ledger,
+        alice,
+        shop,
+        clock,
+    }
+}
+
+fn request(f: &Fixture, key: &str, amount: i64) -> PaymentRequest {
+    PaymentRequest {
+        idempotency_key: key.to_string(),
+        from: f.alice.id,
+        to: f.shop.id,
+        amount,
+        description: "订单".to_string(),
+    }
+}
+
+// 扣款成功后不再返回，模拟进程在扣款和记账之间崩溃
+struct CrashAfterCharge {
+    inner: MockGateway,
+    crash: AtomicBool,
+}
+
+impl Gateway for CrashAfterCharge {
+    fn charge<'a>(
+        &'a self,
+        request: &'a PaymentRequest,
+    ) -> GatewayFuture<'a, Result<String, GatewayError>> {
+        Box::pin(async move {
+            let id = self.inner.charge(request).await?;
+            if self.crash.swap(false, Ordering::SeqCst) {
+                std::future::pending::<()>().await;
+            }
+            Ok(id)
+        })
+    }
+
+    fn refund<'a>(&'a self, charge_id: &'a str) -> GatewayFuture<'a, Result<(), GatewayError>> {
+        self.inner.refund(charge_id)
+    }
+}
+
+#[cfg(test)]
+mod test_payments {
+    use super::*;
+
+    //同一个幂等键只扣款一次
+    #[tokio::test]
+    async fn test_idempotent_payment() -> Result<(), PaymentError> {
+        let gateway = MockGateway::new();
+        let f = fixture(Arc::new(gateway.clone())).await;
+
+        let first = f
+            .payments
+            .process_payment(&request(&f, "order-1", 3_000))
+            .await?;
+        let again = f
+            .payments
+            .process_payment(&request(&f, "order-1", 3_000))
+            .await?;
+        assert_eq!(first, again);
+        assert_eq!(gateway.charges().len(), 1);
+        assert_eq!(f.ledger.balance(f.alice.id).await?, 7_000);
+        assert_eq!(f.ledger.balance(f.shop.id).await?, 3_000);
+
+        assert!(matches!(
+            f.payments.process_payment(&request(&f, "order-1", 1)).await,
+            Err(PaymentError::IdempotencyConflict(_))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_validation_and_risk() {
+        let gateway = MockGateway::new();
+        let f = fixture(Arc::new(gateway.clone())).await;
+
+        match f.payments.process_payment(&request(&f, "a", 0)).await {
+            Err(PaymentError::Validation(message)) => assert_eq!(message, "金额必须大于0"),
+            other => panic!("期望 Validation, 实际: {:?}", other),
+        }
+        assert!(matches!(
+            f.payments.process_payment(&request(&f, "b", 200_000)).await,
+            Err(PaymentError::Rejected { rule, .. }) if rule == "max_amount"
+        ));
+        assert!(gateway.charges().is_empty());
+    }
+
+    //记账失败时退款，幂等键可以重试
+    #[tokio::test]
+    async fn test_reversal_on_ledger_failure() -> Result<(), PaymentError> {
+        let gateway = MockGateway::new();
+        let f = fixture(Arc::new(gateway.clone())).await;
+
+        let result = f
+            .payments
+            .process_payment(&request(&f, "big", 50_000))
+            .await;
+        assert!(matches!(result, Err(PaymentError::Reversed(_))));
+        assert_eq!(gateway.refunds(), vec!["ch_1".to_string()]);
+        assert_eq!(f.ledger.balance(f.shop.id).await?, 0);
+
+        gateway.fail_next_charge(GatewayError::Declined("卡片过期".to_string()));
+        assert!(matches!(
+            f.payments
+                .process_payment(&request(&f, "big", 50_000))
+                .await,
+            Err(PaymentError::Gateway(GatewayError::Declined(_)))
+        ));
+
+        gateway.fail_next_refund(GatewayError::Unavailable("超时".to_string()));
+        assert!(matches!(
+            f.payments
+                .process_payment(&request(&f, "big", 50_000))
+                .await,
+            Err(PaymentError::ReversalFailed { .. })
+        ));
+        // 退款失败的记录保留下来，不能直接重试
+        assert!(matches!(
+            f.payments
+                .process_payment(&request(&f, "big", 50_000))
+                .await,
+            Err(PaymentError::InProgress(_))
+        ));
+
+        // 租期过后 reconcile 重试退款并释放幂等键
+        f.clock.advance(DEFAULT_LEASE);
+        let report = f.payments.reconcile().await?;
+        assert_eq!(report.released, vec!["big".to_string()]);
+        assert_eq!(
+            gateway.refunds(),
+            vec!["ch_1".to_string(), "ch_2".to_string()]
+        );
+        assert!(matches!(
+            f.payments
+                .process_payment(&request(&f, "big", 50_000))
+                .await,
+            Err(PaymentError::Reversed(_))
+        ));
+        Ok(())
+    }
+
+    //扣款之后进程崩溃，租期过后 reconcile 继续记账，不会重复扣款
+    #[tokio::test]
+    async fn test_reconcile_after_crash() -> Result<(), PaymentError> {
+        let gateway = MockGateway::new();
+        let f = fixture(Arc::new(CrashAfterCharge {
+            inner: gateway.clone(),
+            crash: AtomicBool::new(true),
+        }))
+        .await;
+
+        let crashed = tokio::time::timeout(
+            Duration::from_millis(100),
+            f.payments.process_payment(&request(&f, "order-1", 3_000)),
+        )
+        .await;
+        assert!(crashed.is_err());
+        assert_eq!(gateway.charges().len(), 1);
+        assert!(matches!(
+            f.payments
+                .process_payment(&request(&f, "order-1", 3_000))
+                .await,
+            Err(PaymentError::InProgress(_))
+        ));
+
+        // 租期内的记录不处理
+        assert_eq!(f.payments.reconcile().await?, ReconcileReport::default());
+
+        f.clock.advance(DEFAULT_LEASE + Duration::from_secs(1));
+        let report = f.payments.reconcile().await?;
+        assert_eq!(report.completed.len(), 1);
+        assert_eq!(report.completed[0].charge_id, "ch_1");
+        assert_eq!(gateway.charges().len(), 1);
+        assert_eq!(f.ledger.balance(f.alice.id).await?, 7_000);
+        assert_eq!(f.ledger.balance(f.shop.id).await?, 3_000);
+
+        let receipt = f
+            .payments
+            .process_payment(&request(&f, "order-1", 3_000))
+            .await?;
+        assert_eq!(receipt, report.completed[0]);
+        assert_eq!(f.payments.reconcile().await?, ReconcileReport::default());
+        Ok(())
+    }
+
+    // 幂等记录更新失败时记账一起回滚并退款，不会留下已记账的 pending 记录
+    #[tokio::test]
+    async fn test_record_update_failure_rolls_back_transfer() -> Result<(), PaymentError> {
+        let gateway = MockGateway::new();
+        let f = fixture(Arc::new(gateway.clone())).await;
+        sqlx::query(
+            "CREATE TRIGGER fail_complete BEFORE UPDATE ON payments
+             WHEN NEW.status = 'completed'
+             BEGIN SELECT RAISE(ABORT, 'disk full'); END",
+        )
+        .execute(f.ledger.pool())
+        .await?;
+
+        assert!(matches!(
+            f.payments
+                .process_payment(&request(&f, "order-1", 3_000))
+                .await,
+            Err(PaymentError::Reversed(_))
+        ));
+        assert_eq!(gateway.refunds(), vec!["ch_1".to_string()]);
+        assert_eq!(f.ledger.balance(f.alice.id).await?, 10_000);
+        assert_eq!(f.ledger.balance(f.shop.id).await?, 0);
+
+        // 幂等键已释放，可以重试
+        sqlx::query("DROP TRIGGER fail_complete")
+            .execute(f.ledger.pool())
+            .await?;
+        let receipt = f
+            .payments
+            .process_payment(&request(&f, "order-1", 3_000))
+            .await?;
+        assert_eq!(receipt.charge_id, "ch_2");
+        assert_eq!(f.ledger.balance(f.shop.id).await?, 3_000);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_http_gateway() -> Result<(), PaymentError> {
+        let server = MockHttp::with_handler(|req| match req.path.as_str() {
+            "/charges" => MockResponse::ok(r#"{"id":"ch_remote"}"#),
+            _ => MockResponse::new(402).body("declined"),
+        })
+        .await
+        .unwrap();
+        let f = fixture(Arc::new(HttpGateway::new(server.url()))).await;
+
+        let receipt = f
+            .payments
+            .process_payment(&request(&f, "http-1", 500))
+            .await?;
+        assert_eq!(receipt.charge_id, "ch_remote");
+        let requests = server.requests();
+        assert_eq!(requests[0].header("idempotency-key"), Some("http-1"));
+        assert!(requests[0].body_str().contains("500"));
+
+        let gateway = HttpGateway::new(server.url());
+        assert!(matches!(
+            gateway.refund("ch_remote").await,
+            Err(GatewayError::Declined(_))
+        ));
+        Ok(())
+    }
+}
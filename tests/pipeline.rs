@@ -0,0 +1,120 @@
+use std::io::{self, Cursor};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use std_app::pipeline::Pipeline;
+use std_app::transform;
+
+fn input(len: usize) -> Vec<u8> {
+    (0..len).map(|i| b'a' + (i % 26) as u8).collect()
+}
+
+// 按块内容决定的伪随机延迟，让并行阶段的完成顺序被打乱
+fn jitter(chunk: &[u8]) -> Duration {
+    let seed = chunk
+        .iter()
+        .fold(7u64, |h, &b| h.wrapping_mul(31) ^ b as u64);
+    Duration::from_millis(seed % 5)
+}
+
+#[cfg(test)]
+mod test_pipeline {
+    use super::*;
+
+    #[test]
+    fn test_parallel_stage_keeps_order() -> io::Result<()> {
+        let data = input(10_000);
+        let mut out = Vec::new();
+        let stats = Pipeline::new()
+            .chunk_size(97)
+            .parallel("upper", 4, |mut chunk| {
+                thread::sleep(jitter(&chunk));
+                transform::make_ascii_uppercase(&mut chunk);
+                Ok(chunk)
+            })
+            .run(Cursor::new(&data), &mut out)?;
+        assert_eq!(out, transform::to_ascii_uppercase(&data));
+        assert_eq!(stats.chunks, 104);
+        assert_eq!(stats.bytes_in, 10_000);
+        assert_eq!(stats.bytes_out, 10_000);
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrency_limit() -> io::Result<()> {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max = Arc::new(AtomicUsize::new(0));
+        let (counter, peak) = (in_flight.clone(), max.clone());
+        Pipeline::new()
+            .chunk_size(10)
+            .parallel("slow", 3, move |chunk| {
+                let now = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                thread::sleep(Duration::from_millis(5));
+                counter.fetch_sub(1, Ordering::SeqCst);
+                Ok(chunk)
+            })
+            .run(Cursor::new(input(500)), io::sink())?;
+        assert!(max.load(Ordering::SeqCst) <= 3);
+        assert!(max.load(Ordering::SeqCst) >= 2);
+        Ok(())
+    }
+
+    //并行阶段之后的串行阶段按输入顺序看到每个块
+    #[test]
+    fn test_sequential_stage_after_parallel() -> io::Result<()> {
+        let data = input(1000);
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        let mut out = Vec::new();
+        let pipeline = Pipeline::new()
+            .chunk_size(64)
+            .parallel("upper", 8, |chunk| {
+                thread::sleep(jitter(&chunk));
+                Ok(transform::to_ascii_uppercase(&chunk))
+            })
+            .stage("checksum", move |chunk| {
+                log.lock().unwrap().push(chunk.clone());
+                Ok(chunk)
+            });
+        assert_eq!(pipeline.stage_names(), vec!["upper", "checksum"]);
+        pipeline.run(Cursor::new(&data), &mut out)?;
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 16);
+        assert_eq!(seen.concat(), transform::to_ascii_uppercase(&data));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stage_error_stops_pipeline() {
+        let processed = Arc::new(AtomicUsize::new(0));
+        let count = processed.clone();
+        let result = Pipeline::new()
+            .chunk_size(1)
+            .queue(2)
+            .parallel("fail", 2, move |chunk| {
+                count.fetch_add(1, Ordering::SeqCst);
+                if chunk == b"x" {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "bad chunk"));
+                }
+                Ok(chunk)
+            })
+            .run(Cursor::new(b"abcx".repeat(1000)), io::sink());
+        let err = result.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(processed.load(Ordering::SeqCst) < 4000);
+    }
+
+    #[test]
+    fn test_empty_input() -> io::Result<()> {
+        let stats = Pipeline::new()
+            .stage("noop", Ok)
+            .run(io::empty(), io::sink())?;
+        assert_eq!(stats.chunks, 0);
+        assert_eq!(stats.bytes_in, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use serde_json::json;
+
+use std_app::app::BoxError;
+use std_app::plugins::{Health, Plugin, PluginContext, PluginHost};
+use std_app::register_plugin;
+
+// 通过宏注册的插件
+struct AuditPlugin {
+    prefix: String,
+}
+
+impl Plugin for AuditPlugin {
+    fn name(&self) -> &str {
+        "audit"
+    }
+
+    fn init(&mut self, ctx: &PluginContext) -> Result<(), BoxError> {
+        self.prefix = ctx.config["prefix"].as_str().unwrap_or("none").to_string();
+        ctx.publish("audit.ready", json!({"prefix": self.prefix}));
+        Ok(())
+    }
+
+    fn health(&self) -> Health {
+        Health::Degraded(format!("prefix={}", self.prefix))
+    }
+}
+
+register_plugin!(AuditPlugin {
+    prefix: String::new()
+});
+
+struct TestPlugin {
+    name: &'static str,
+    fail: bool,
+    shutdowns: Arc<AtomicUsize>,
+}
+
+impl Plugin for TestPlugin {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn init(&mut self, _ctx: &PluginContext) -> Result<(), BoxError> {
+        if self.fail {
+            return Err("缺少配置".into());
+        }
+        Ok(())
+    }
+
+    fn shutdown(&mut self) {
+        self.shutdowns.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod test_plugins {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_registered_plugin() {
+        let mut host = PluginHost::new(json!({"plugins": {"audit": {"prefix": "app"}}}));
+        assert_eq!(host.names(), vec!["audit"]);
+        let mut events = host.subscribe();
+
+        host.init().unwrap();
+        let event = events.recv().await.unwrap();
+        assert_eq!(event.topic, "audit.ready");
+        assert_eq!(event.payload["prefix"], "app");
+        assert_eq!(
+            host.health(),
+            vec![(
+                "audit".to_string(),
+                Health::Degraded("prefix=app".to_string())
+            )]
+        );
+        host.shutdown();
+        assert!(host.health().is_empty());
+    }
+
+    //初始化失败时关闭已初始化的插件
+    #[test]
+    fn test_init_failure() {
+        let shutdowns = Arc::new(AtomicUsize::new(0));
+        let plugin = |name, fail| {
+            Box::new(TestPlugin {
+                name,
+                fail,
+                shutdowns: shutdowns.clone(),
+            })
+        };
+        let mut host = PluginHost::new(json!({}))
+            .plugin(plugin("ok", false))
+            .plugin(plugin("broken", true));
+
+        let err = host.init().unwrap_err();
+        assert_eq!(err.plugin, "broken");
+        assert_eq!(err.to_string(), "插件 broken 初始化失败: 缺少配置");
+        // audit 和 ok 都被关闭，只有 ok 会计数
+        assert_eq!(shutdowns.load(Ordering::SeqCst), 1);
+        assert!(host.health().is_empty());
+    }
+}
@@ -0,0 +1,207 @@
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use std_app::clock::SimClock;
+use std_app::pool::jobs::{JobError, JobPool};
+use std_app::pool::{self, BufferPool, StringPool};
+
+// 等待任务开始运行
+fn wait_running(pool: &JobPool, n: usize) {
+    while pool.stats().running < n {
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[cfg(test)]
+mod test_buffer_pool {
+    use super::*;
+
+    #[test]
+    fn test_reuse_on_drop() -> std::io::Result<()> {
+        let pool = BufferPool::new(1024, 4);
+        {
+            let mut buffer = pool.get();
+            assert!(buffer.capacity() >= 1024);
+            for i in 0..100 {
+                writeln!(buffer, "Line {}", i)?;
+            }
+            assert_eq!(pool.stats().in_use, 1);
+        }
+        //归还后内容被清空，容量保留
+        let buffer = pool.get();
+        assert!(buffer.is_empty());
+        assert!(buffer.capacity() >= 1024);
+        drop(buffer);
+
+        let stats = pool.stats();
+        assert_eq!(
+            (stats.allocated, stats.reused, stats.in_use, stats.idle),
+            (1, 1, 0, 1)
+        );
+        assert_eq!(stats.reuse_rate(), 0.5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_high_water_and_limits() {
+        let pool = BufferPool::with_max_capacity(16, 2, 64);
+        let held: Vec<_> = (0..3).map(|_| pool.get()).collect();
+        assert_eq!(pool.stats().high_water, 3);
+        drop(held);
+        //最多保留 2 个空闲缓冲区
+        let stats = pool.stats();
+        assert_eq!((stats.idle, stats.discarded, stats.high_water), (2, 1, 3));
+
+        //容量过大的缓冲区不归还
+        let mut big = pool.get();
+        big.extend_from_slice(&[0u8; 1024]);
+        drop(big);
+        assert_eq!(pool.stats().discarded, 2);
+        assert_eq!(pool.stats().idle, 1);
+
+        let owned = pool.get().detach();
+        assert_eq!(owned.capacity(), 16);
+        assert_eq!(pool.stats().in_use, 0);
+        pool.shrink();
+        assert_eq!(pool.stats().idle, 0);
+    }
+
+    #[test]
+    fn test_string_pool_across_threads() {
+        let pool = Arc::new(StringPool::new(256, 8));
+        pool.prefill(8);
+        let handles: Vec<_> = (0..8)
+            .map(|t| {
+                let pool = pool.clone();
+                thread::spawn(move || {
+                    for i in 0..100 {
+                        let mut s = pool.get();
+                        write!(s, "thread {} item {}", t, i).unwrap();
+                        assert!(s.starts_with("thread"));
+                    }
+                })
+            })
+            .collect();
+        handles.into_iter().for_each(|h| h.join().unwrap());
+        let stats = pool.stats();
+        assert_eq!(stats.allocated + stats.reused, 808);
+        assert!(stats.allocated <= 16);
+        assert_eq!(stats.in_use, 0);
+    }
+
+    #[test]
+    fn test_global_buffers() {
+        let buffer = pool::buffers().get();
+        assert!(buffer.capacity() >= pool::DEFAULT_BUFFER_CAPACITY);
+    }
+}
+
+#[cfg(test)]
+mod test_job_pool {
+    use super::*;
+
+    #[test]
+    fn test_results_and_panics() {
+        let pool = JobPool::new(2);
+        let handles: Vec<_> = (0..8)
+            .map(|i| pool.submit("square", move |_| Ok(i * i)))
+            .collect();
+        let results: Vec<i32> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert_eq!(results, vec![0, 1, 4, 9, 16, 25, 36, 49]);
+
+        let failed = pool.submit("boom", |_| -> Result<(), JobError> { panic!("坏数据") });
+        assert_eq!(
+            failed.join(),
+            Err(JobError::Panicked {
+                name: "boom".into(),
+                message: "坏数据".into()
+            })
+        );
+        //panic 之后工作线程仍然可用
+        assert_eq!(
+            pool.submit("after", |ctx| Ok(ctx.name().to_string()))
+                .join(),
+            Ok("after".into())
+        );
+        let stats = pool.stats();
+        assert_eq!((stats.completed, stats.panicked), (9, 1));
+    }
+
+    #[test]
+    fn test_should_yield_when_others_wait() {
+        let clock = SimClock::new();
+        let pool = JobPool::builder(1)
+            .time_slice(Duration::from_millis(100))
+            .clock(clock.shared())
+            .build();
+        let release = Arc::new(AtomicBool::new(false));
+        let flag = release.clone();
+        let long = pool.submit("long", move |ctx| {
+            let mut yields = Vec::new();
+            while !flag.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            yields.push(ctx.should_yield());
+            ctx.checkpoint()?;
+            yields.push(ctx.should_yield());
+            Ok(yields)
+        });
+        wait_running(&pool, 1);
+        let queued = pool.submit("short", |_| Ok(()));
+        clock.advance(Duration::from_millis(150));
+        release.store(true, Ordering::SeqCst);
+        //超过时间片且有任务排队时应该让出；checkpoint 之后重新计时
+        assert_eq!(long.join(), Ok(vec![true, false]));
+        queued.join().unwrap();
+    }
+
+    #[test]
+    fn test_watchdog_reports_and_aborts_overdue() {
+        let clock = SimClock::new();
+        let pool = JobPool::builder(2)
+            .max_runtime(Duration::from_secs(60))
+            .abort_overdue(true)
+            .check_interval(Duration::from_secs(3600))
+            .clock(clock.shared())
+            .build();
+        let runaway = pool.submit("runaway", |ctx| loop {
+            ctx.checkpoint()?;
+            thread::sleep(Duration::from_millis(1));
+        });
+        wait_running(&pool, 1);
+        assert!(pool.check().is_empty());
+
+        clock.advance(Duration::from_secs(61));
+        let overdue = pool.check();
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].name, "runaway");
+        assert!(overdue[0].aborted);
+        //同一个任务只报告一次
+        assert!(pool.check().is_empty());
+
+        let result: Result<(), JobError> = runaway.join();
+        assert!(matches!(result, Err(JobError::Aborted { ref name, .. }) if name == "runaway"));
+        let stats = pool.stats();
+        assert_eq!((stats.overdue, stats.aborted, stats.running), (1, 1, 0));
+    }
+
+    #[test]
+    fn test_watchdog_thread_only_logs_by_default() {
+        let pool = JobPool::builder(1)
+            .max_runtime(Duration::from_millis(20))
+            .check_interval(Duration::from_millis(5))
+            .build();
+        let slow = pool.submit("slow", |ctx| {
+            thread::sleep(Duration::from_millis(100));
+            ctx.checkpoint()?;
+            Ok(ctx.is_aborted())
+        });
+        assert_eq!(slow.join(), Ok(false));
+        assert_eq!(pool.stats().overdue, 1);
+        pool.shutdown();
+    }
+}
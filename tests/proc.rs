@@ -0,0 +1,77 @@
+use std::time::Duration;
+
+use std_app::proc::{Command, Line, ProcError, Stream};
+
+fn sh(script: &str) -> Command {
+    Command::new("sh").arg("-c").arg(script)
+}
+
+#[cfg(test)]
+mod test_proc {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_output_capture() -> Result<(), ProcError> {
+        let output = sh("echo out1; echo err1 >&2; echo out2").output().await?;
+        assert!(output.success());
+        assert_eq!(output.stdout, "out1\nout2\n");
+        assert_eq!(output.stderr, "err1\n");
+        assert_eq!(output.combined.lines().count(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_args_env_stdin() -> Result<(), ProcError> {
+        let output = Command::new("sh")
+            .args(["-c", "echo \"$GREETING $0\"; cat", "Rust"])
+            .env("GREETING", "Hello,")
+            .current_dir(std::env::temp_dir())
+            .stdin("from stdin\n")
+            .output()
+            .await?;
+        assert_eq!(output.stdout, "Hello, Rust\nfrom stdin\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_failure() {
+        match sh("echo boom >&2; exit 3").check().await {
+            Err(ProcError::Failed { code, stderr, .. }) => {
+                assert_eq!(code, Some(3));
+                assert_eq!(stderr, "boom");
+            }
+            other => panic!("期望 Failed, 实际: {:?}", other),
+        }
+        assert!(matches!(
+            Command::new("no-such-program-xyz").output().await,
+            Err(ProcError::Spawn { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_kill_on_timeout() {
+        let start = std::time::Instant::now();
+        let result = sh("sleep 10")
+            .timeout(Duration::from_millis(100))
+            .output()
+            .await;
+        assert!(matches!(result, Err(ProcError::Timeout { .. })));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    //异步逐行读取输出
+    #[tokio::test]
+    async fn test_stream_lines() {
+        let (mut rx, handle) = sh("echo a; echo b >&2").stream();
+        let mut lines = Vec::new();
+        while let Some(line) = rx.recv().await {
+            lines.push(line);
+        }
+        assert!(handle.await.unwrap().unwrap().success());
+        assert!(lines.contains(&Line {
+            stream: Stream::Stderr,
+            text: "b".to_string()
+        }));
+        assert_eq!(lines.len(), 2);
+    }
+}
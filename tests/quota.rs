@@ -0,0 +1,139 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+
+use std_app::clock::SimClock;
+use std_app::ledger::Ledger;
+use std_app::payments::{
+    MockGateway, PaymentError, PaymentRequest, Payments, QuotaRule, QUOTA_SPEND,
+};
+use std_app::quota::{Quota, QuotaError};
+use std_app::testkit::TestWorkspace;
+
+const DAY: Duration = Duration::from_secs(86_400);
+
+async fn pool() -> SqlitePool {
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test_quota {
+    use super::*;
+
+    //滚动窗口: 超过一天的用量不再计入
+    #[tokio::test]
+    async fn test_rolling_window() -> Result<(), QuotaError> {
+        let clock = SimClock::new();
+        let quota = Quota::with_clock(pool().await, clock.shared()).limit("requests", 3, DAY);
+        quota.migrate().await?;
+
+        for _ in 0..3 {
+            quota.consume("alice", "requests", 1).await?;
+        }
+        match quota.consume("alice", "requests", 1).await {
+            Err(QuotaError::Exceeded { limit, used, .. }) => {
+                assert_eq!(limit, 3);
+                assert_eq!(used, 3);
+            }
+            other => panic!("期望 Exceeded, 实际: {:?}", other),
+        }
+        // 其他用户不受影响
+        quota.consume("bob", "requests", 1).await?;
+        assert_eq!(quota.remaining("alice", "requests").await?, 0);
+
+        clock.advance(DAY / 2);
+        assert_eq!(quota.usage("alice", "requests").await?, 3);
+        clock.advance(DAY);
+        assert_eq!(quota.usage("alice", "requests").await?, 0);
+        quota.consume("alice", "requests", 1).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_refund() -> Result<(), QuotaError> {
+        let quota = Quota::new(pool().await).limit("spend", 100, DAY);
+        quota.migrate().await?;
+        quota.consume("alice", "spend", 80).await?;
+        assert!(quota.consume("alice", "spend", 30).await.is_err());
+        quota.refund("alice", "spend", 80).await?;
+        quota.consume("alice", "spend", 30).await?;
+        assert_eq!(quota.usage("alice", "spend").await?, 30);
+        Ok(())
+    }
+
+    // 两个连接池并发计数，不会同时通过检查，也不会因为锁升级失败
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_consume() -> Result<(), QuotaError> {
+        let ws = TestWorkspace::new().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", ws.path("quota.db").display());
+        let mut quotas = Vec::new();
+        for _ in 0..2 {
+            let pool = SqlitePoolOptions::new()
+                .max_connections(4)
+                .connect(&url)
+                .await
+                .unwrap();
+            quotas.push(Quota::new(pool).limit("requests", 5, DAY));
+        }
+        quotas[0].migrate().await?;
+
+        let tasks: Vec<_> = (0..20)
+            .map(|i| {
+                let quota = quotas[i % 2].clone();
+                tokio::spawn(async move { quota.consume("alice", "requests", 1).await })
+            })
+            .collect();
+        let mut granted = 0;
+        for task in tasks {
+            match task.await.unwrap() {
+                Ok(()) => granted += 1,
+                Err(QuotaError::Exceeded { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        assert_eq!(granted, 5);
+        assert_eq!(quotas[1].usage("alice", "requests").await?, 5);
+        Ok(())
+    }
+
+    //支付超出每日限额时返回类型化的错误
+    #[tokio::test]
+    async fn test_payment_limit() -> Result<(), PaymentError> {
+        let pool = pool().await;
+        let ledger = Ledger::new(pool.clone());
+        let quota = Quota::new(pool).limit(QUOTA_SPEND, 5_000, DAY);
+        let payments = Payments::new(ledger.clone(), Arc::new(MockGateway::new()))
+            .rule(QuotaRule(quota.clone()));
+        payments.migrate().await?;
+        let bank = ledger.open_account("bank", true).await?;
+        let alice = ledger.open_account("alice", false).await?;
+        let shop = ledger.open_account("shop", false).await?;
+        ledger.transfer(bank.id, alice.id, 100_000, "充值").await?;
+
+        let pay = |key: &str, amount| PaymentRequest {
+            idempotency_key: key.to_string(),
+            from: alice.id,
+            to: shop.id,
+            amount,
+            description: "订单".to_string(),
+        };
+        payments.process_payment(&pay("1", 4_000)).await?;
+        assert!(matches!(
+            payments.process_payment(&pay("2", 2_000)).await,
+            Err(PaymentError::Quota(QuotaError::Exceeded { .. }))
+        ));
+        // 被拒绝的请求不占用幂等键和限额
+        payments.process_payment(&pay("2", 1_000)).await?;
+        assert_eq!(
+            quota.usage(&alice.id.to_string(), QUOTA_SPEND).await?,
+            5_000
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use std::fs;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use std_app::metrics::{MetricValue, Registry};
+use std_app::quota_fs::{DiskQuota, OnFull, QuotaFsError};
+use std_app::testkit::TestWorkspace;
+
+fn gauge(registry: &Registry, name: &str) -> f64 {
+    registry
+        .snapshot()
+        .into_iter()
+        .find(|m| m.name == name)
+        .map(|m| match m.value {
+            MetricValue::Gauge { value } => value,
+            _ => panic!("{} 不是仪表", name),
+        })
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test_quota_fs {
+    use super::*;
+
+    #[test]
+    fn test_reject_when_full() -> Result<(), QuotaFsError> {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        fs::create_dir_all(root)?;
+        fs::write(root.join("existing.log"), vec![0u8; 600])?;
+
+        let registry = Arc::new(Registry::new());
+        let quota = DiskQuota::new().metrics(registry.clone());
+        let usage = quota.manage("logs", root, 1000, OnFull::Reject)?;
+        assert_eq!(usage.used, 600);
+
+        quota.write("logs", "a.log", &[1u8; 300])?;
+        match quota.write("logs", "b.log", &[1u8; 300]) {
+            Err(QuotaFsError::Exceeded {
+                used,
+                limit,
+                requested,
+                ..
+            }) => {
+                assert_eq!((used, limit, requested), (900, 1000, 300));
+            }
+            other => panic!("期望 Exceeded, 实际: {:?}", other),
+        }
+        assert!(!root.join("b.log").exists());
+
+        //覆盖写入只计算增加的部分
+        quota.write("logs", "a.log", &[1u8; 100])?;
+        assert_eq!(quota.usage("logs").unwrap().used, 700);
+        assert_eq!(quota.usage("logs").unwrap().rejected, 1);
+        assert_eq!(gauge(&registry, "disk_quota_logs_used_bytes"), 700.0);
+        assert_eq!(gauge(&registry, "disk_quota_logs_limit_bytes"), 1000.0);
+
+        assert!(matches!(
+            quota.reserve("nope", 1),
+            Err(QuotaFsError::UnknownDir(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_evict_oldest() -> Result<(), QuotaFsError> {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let quota = DiskQuota::new();
+        quota.manage("cache", root, 1000, OnFull::EvictOldest)?;
+        let base = SystemTime::now() - Duration::from_secs(100);
+        for (i, name) in ["old", "mid", "new"].iter().enumerate() {
+            let path = quota.write("cache", name, &[0u8; 300])?;
+            fs::File::options()
+                .write(true)
+                .open(path)?
+                .set_modified(base + Duration::from_secs(i as u64 * 10))?;
+        }
+
+        quota.write("cache", "incoming", &[0u8; 400])?;
+        assert!(!root.join("old").exists());
+        assert!(root.join("mid").exists());
+        let usage = quota.usage("cache").unwrap();
+        assert_eq!(usage.used, 1000);
+        assert_eq!(usage.reclaimed, 300);
+
+        // 比上限还大的写入无论如何都放不下
+        assert!(quota.write("cache", "huge", &[0u8; 2000]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_callback_and_rescan() -> Result<(), QuotaFsError> {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let quota = DiskQuota::new();
+        let compact = Arc::new(|dir: &std::path::Path, needed: u64| {
+            assert!(needed > 0);
+            let path = dir.join("garbage");
+            let len = fs::metadata(&path)?.len();
+            fs::remove_file(path)?;
+            Ok(len)
+        });
+        quota.manage("blobs", root, 500, OnFull::Compact(compact))?;
+        quota.write("blobs", "garbage", &[0u8; 400])?;
+        quota.write("blobs", "live", &[0u8; 300])?;
+        assert!(!root.join("garbage").exists());
+        assert_eq!(quota.usage("blobs").unwrap().used, 300);
+
+        fs::write(root.join("external"), [0u8; 50])?;
+        assert_eq!(quota.rescan("blobs")?.used, 350);
+        Ok(())
+    }
+}
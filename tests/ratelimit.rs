@@ -0,0 +1,154 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use std_app::clock::SimClock;
+use std_app::http::{ApiError, HttpClient, Method};
+use std_app::metrics::{MetricValue, Registry};
+use std_app::ratelimit::adaptive::AdaptiveLimiter;
+use std_app::testkit::http::{MockHttp, MockResponse};
+
+fn gauge(registry: &Registry, name: &str) -> f64 {
+    registry
+        .snapshot()
+        .into_iter()
+        .find(|m| m.name == name)
+        .map(|m| match m.value {
+            MetricValue::Gauge { value } => value,
+            _ => panic!("{} 不是仪表", name),
+        })
+        .unwrap()
+}
+
+async fn get(client: &HttpClient, path: &str) -> Result<(), ApiError> {
+    client.send(client.request(Method::GET, path)).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_adaptive {
+    use super::*;
+
+    #[test]
+    fn test_limit_caps_inflight() {
+        let limiter = AdaptiveLimiter::new(2);
+        let a = limiter.try_acquire().unwrap();
+        let _b = limiter.try_acquire().unwrap();
+        assert!(limiter.try_acquire().is_none());
+        assert_eq!(limiter.stats().rejected, 1);
+        //直接 drop 只释放名额
+        drop(a);
+        assert!(limiter.try_acquire().is_some());
+        assert_eq!(limiter.limit(), 2);
+    }
+
+    #[test]
+    fn test_additive_increase_when_saturated() {
+        let limiter = AdaptiveLimiter::builder(4).max_limit(6).build();
+        for _ in 0..100 {
+            let permits: Vec<_> = (0..limiter.limit())
+                .map(|_| limiter.try_acquire().unwrap())
+                .collect();
+            permits.into_iter().for_each(|p| p.success());
+        }
+        assert_eq!(limiter.limit(), 6);
+
+        //并发没有用满时不增加
+        let limiter = AdaptiveLimiter::new(10);
+        for _ in 0..50 {
+            limiter.try_acquire().unwrap().success();
+        }
+        assert_eq!(limiter.limit(), 10);
+    }
+
+    #[test]
+    fn test_multiplicative_decrease() {
+        let limiter = AdaptiveLimiter::builder(20)
+            .min_limit(3)
+            .backoff_ratio(0.5)
+            .build();
+        limiter.try_acquire().unwrap().failure();
+        assert_eq!(limiter.limit(), 10);
+        for _ in 0..5 {
+            limiter.try_acquire().unwrap().failure();
+        }
+        assert_eq!(limiter.limit(), 3);
+    }
+
+    #[test]
+    fn test_slow_responses_decrease() {
+        let clock = SimClock::new();
+        let limiter = AdaptiveLimiter::builder(10)
+            .latency_threshold(Duration::from_millis(100))
+            .clock(clock.shared())
+            .build();
+        let permit = limiter.try_acquire().unwrap();
+        clock.advance(Duration::from_millis(50));
+        permit.success();
+        assert_eq!(limiter.limit(), 10);
+
+        let permit = limiter.try_acquire().unwrap();
+        clock.advance(Duration::from_millis(300));
+        assert_eq!(permit.elapsed(), Duration::from_millis(300));
+        permit.success();
+        assert_eq!(limiter.limit(), 9);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_release() {
+        let limiter = AdaptiveLimiter::new(1);
+        let held = limiter.acquire().await;
+        let waiter = {
+            let limiter = limiter.clone();
+            tokio::spawn(async move { limiter.acquire().await.success() })
+        };
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!waiter.is_finished());
+        held.success();
+        tokio::time::timeout(Duration::from_secs(2), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_metrics() {
+        let registry = Arc::new(Registry::new());
+        let limiter = AdaptiveLimiter::builder(8)
+            .metrics(registry.clone(), "upstream_users")
+            .build();
+        assert_eq!(gauge(&registry, "upstream_users_concurrency_limit"), 8.0);
+        let permit = limiter.try_acquire().unwrap();
+        assert_eq!(gauge(&registry, "upstream_users_inflight"), 1.0);
+        permit.failure();
+        assert_eq!(gauge(&registry, "upstream_users_concurrency_limit"), 7.0);
+        assert_eq!(gauge(&registry, "upstream_users_inflight"), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_http_client_feeds_limiter() {
+        let server = MockHttp::with_handler(|req| match req.path.as_str() {
+            "/busy" => MockResponse::new(503),
+            "/missing" => MockResponse::new(404),
+            _ => MockResponse::ok("ok"),
+        })
+        .await
+        .unwrap();
+        let limiter = AdaptiveLimiter::builder(10).backoff_ratio(0.5).build();
+        let client = HttpClient::builder(server.url())
+            .concurrency_limit(limiter.clone())
+            .build()
+            .unwrap();
+
+        get(&client, "/ok").await.unwrap();
+        assert_eq!(limiter.limit(), 10);
+        //4xx 是调用方的问题，不减小并发
+        assert!(matches!(
+            get(&client, "/missing").await,
+            Err(ApiError::NotFound(_))
+        ));
+        assert_eq!(limiter.limit(), 10);
+        assert!(get(&client, "/busy").await.is_err());
+        assert_eq!(limiter.limit(), 5);
+        assert_eq!(limiter.stats().inflight, 0);
+    }
+}
@@ -0,0 +1,85 @@
+use std::time::Duration;
+
+use sqlx::sqlite::SqlitePoolOptions;
+
+use std_app::cache::TtlCache;
+use std_app::clock::SimClock;
+use std_app::context::RequestContext;
+use std_app::db::DbError;
+use std_app::rbac::{AuthError, Rbac};
+use std_app::status::HttpStatus;
+
+async fn rbac(clock: &SimClock) -> Rbac {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let rbac = Rbac::with_cache(
+        pool,
+        TtlCache::with_clock(Duration::from_secs(60), clock.shared()),
+    );
+    rbac.migrate().await.unwrap();
+    rbac
+}
+
+#[cfg(test)]
+mod test_rbac {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_authorize() -> Result<(), AuthError> {
+        let rbac = rbac(&SimClock::new()).await;
+        rbac.grant("clerk", "orders:read").await?;
+        rbac.grant("manager", "orders:*").await?;
+        rbac.grant("admin", "*").await?;
+        rbac.assign("alice", "clerk").await?;
+        rbac.assign("bob", "manager").await?;
+
+        let alice = RequestContext::new().user("alice");
+        let bob = RequestContext::new().user("bob");
+        rbac.authorize(&alice, "orders:read").await?;
+        match rbac.authorize(&alice, "orders:refund").await {
+            Err(e @ AuthError::Forbidden { .. }) => {
+                assert_eq!(e.status(), 403);
+                assert_eq!(e.to_string(), "没有权限: 需要 orders:refund");
+            }
+            other => panic!("期望 Forbidden, 实际: {:?}", other),
+        }
+        rbac.authorize(&bob, "orders:refund").await?;
+        // "orders:*" 不匹配 "ordersx:read"
+        assert!(!rbac.has_permission("bob", "ordersx:read").await?);
+        assert!(!rbac.has_permission("bob", "users:delete").await?);
+
+        let anonymous = RequestContext::new();
+        let err = rbac.authorize(&anonymous, "orders:read").await.unwrap_err();
+        assert_eq!(err.status(), 401);
+        assert_eq!(rbac.roles("bob").await?, vec!["manager".to_string()]);
+        Ok(())
+    }
+
+    //授权关系变化后缓存失效
+    #[tokio::test]
+    async fn test_cache_invalidation() -> Result<(), AuthError> {
+        let rbac = rbac(&SimClock::new()).await;
+        rbac.grant("clerk", "orders:read").await?;
+        rbac.assign("alice", "clerk").await?;
+        assert!(rbac.has_permission("alice", "orders:read").await?);
+
+        rbac.revoke("clerk", "orders:read").await?;
+        assert!(!rbac.has_permission("alice", "orders:read").await?);
+        rbac.assign("alice", "admin").await?;
+        rbac.grant("admin", "*").await?;
+        assert!(rbac.has_permission("alice", "anything").await?);
+        rbac.unassign("alice", "admin").await?;
+        assert!(!rbac.has_permission("alice", "anything").await?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_db_status() {
+        assert_eq!(DbError::NotFound(1).status(), 404);
+        assert_eq!(DbError::Duplicate("x".to_string()).status(), 409);
+        assert!(DbError::Duplicate("x".to_string()).is_client_error());
+    }
+}
@@ -0,0 +1,107 @@
+use serde::Serialize;
+use serde_json::json;
+use thiserror::Error;
+
+use std_app::redact::{RedactConfig, RedactError, Redactor};
+
+#[derive(Serialize)]
+struct Login {
+    user: String,
+    password: String,
+    note: String,
+}
+
+#[derive(Error, Debug)]
+enum ApiError {
+    #[error("请求失败: {0}")]
+    RequestFailed(String),
+}
+
+#[cfg(test)]
+mod test_redact {
+    use super::*;
+
+    #[test]
+    fn test_redact_fields() -> Result<(), serde_json::Error> {
+        let redactor = Redactor::default();
+        let login = Login {
+            user: "alice".to_string(),
+            password: "hunter2".to_string(),
+            note: "contact alice@example.com".to_string(),
+        };
+        let value = redactor.redact(&login)?;
+        assert_eq!(
+            value,
+            json!({"user": "alice", "password": "***", "note": "contact ***"})
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_and_case_insensitive() {
+        let redactor = Redactor::default();
+        let mut value = json!({
+            "headers": {"Authorization": "Bearer abc"},
+            "items": [{"Token": "t1"}, {"id": 1}]
+        });
+        redactor.redact_value(&mut value);
+        assert_eq!(
+            value,
+            json!({
+                "headers": {"Authorization": "***"},
+                "items": [{"Token": "***"}, {"id": 1}]
+            })
+        );
+    }
+
+    //信用卡号需要通过 Luhn 校验
+    #[test]
+    fn test_card_numbers() {
+        let redactor = Redactor::default();
+        assert_eq!(
+            redactor.redact_str("card 4111 1111 1111 1111 paid"),
+            "card *** paid"
+        );
+        assert_eq!(
+            redactor.redact_str("order 1234567890123456"),
+            "order 1234567890123456"
+        );
+    }
+
+    #[test]
+    fn test_from_config() -> Result<(), Box<dyn std::error::Error>> {
+        let config: RedactConfig = toml::from_str(
+            r#"
+            builtin = false
+            fields = ["ssn"]
+            mask = "[REDACTED]"
+
+            [[patterns]]
+            name = "phone"
+            regex = '1\d{10}'
+        "#,
+        )?;
+        let redactor = Redactor::from_config(&config)?;
+        assert_eq!(redactor.pattern_names(), vec!["phone"]);
+
+        let value =
+            redactor.redact(&json!({"ssn": "123", "password": "x", "msg": "call 13800138000"}))?;
+        assert_eq!(
+            value,
+            json!({"ssn": "[REDACTED]", "password": "x", "msg": "call [REDACTED]"})
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_pattern_and_error_message() {
+        let result = Redactor::empty().pattern("bad", "(");
+        assert!(matches!(result, Err(RedactError::InvalidPattern { .. })));
+
+        let err = ApiError::RequestFailed("user bob@example.com not found".to_string());
+        assert_eq!(
+            Redactor::default().redact_error(&err),
+            "请求失败: user *** not found"
+        );
+    }
+}
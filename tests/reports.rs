@@ -0,0 +1,101 @@
+use std::io::Read;
+use std::sync::{Arc, Mutex};
+
+use flate2::read::GzDecoder;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+
+use std_app::reports::{self, Format, Report, ReportError};
+
+async fn orders() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::raw_sql(
+        "CREATE TABLE orders (id INTEGER PRIMARY KEY, customer TEXT, total REAL, note TEXT);
+         INSERT INTO orders (customer, total, note) VALUES
+            ('alice', 12.5, NULL),
+            ('bob, jr', 3.0, 'say \"hi\"');",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool
+}
+
+#[cfg(test)]
+mod test_reports {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_csv() -> Result<(), ReportError> {
+        let pool = orders().await;
+        let mut out = Vec::new();
+        let rows = reports::generate(
+            &pool,
+            "SELECT * FROM orders ORDER BY id",
+            Format::Csv,
+            &mut out,
+        )
+        .await?;
+        assert_eq!(rows, 2);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "id,customer,total,note\r\n1,alice,12.5,\r\n2,\"bob, jr\",3.0,\"say \"\"hi\"\"\"\r\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_json_lines() -> Result<(), ReportError> {
+        let pool = orders().await;
+        let mut out = Vec::new();
+        reports::generate(
+            &pool,
+            "SELECT id, customer, note FROM orders",
+            Format::JsonLines,
+            &mut out,
+        )
+        .await?;
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["id"], 1);
+        assert_eq!(lines[0]["note"], serde_json::Value::Null);
+        assert_eq!(lines[1]["customer"], "bob, jr");
+        Ok(())
+    }
+
+    //大结果集压缩导出，按进度回调
+    #[tokio::test]
+    async fn test_gzip_with_progress() -> Result<(), ReportError> {
+        let pool = orders().await;
+        sqlx::raw_sql(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 2500)
+             INSERT INTO orders (customer, total) SELECT 'c' || i, i FROM n",
+        )
+        .execute(&pool)
+        .await?;
+
+        let progress = Arc::new(Mutex::new(Vec::new()));
+        let seen = progress.clone();
+        let mut out = Vec::new();
+        let rows = Report::new("SELECT id, customer FROM orders", Format::Csv)
+            .gzip(true)
+            .progress(1000, move |n| seen.lock().unwrap().push(n))
+            .write_to(&pool, &mut out)
+            .await?;
+        assert_eq!(rows, 2502);
+        assert_eq!(*progress.lock().unwrap(), vec![1000, 2000, 2502]);
+
+        let mut text = String::new();
+        GzDecoder::new(&out[..]).read_to_string(&mut text).unwrap();
+        assert_eq!(text.lines().count(), 2503);
+        Ok(())
+    }
+}
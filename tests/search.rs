@@ -0,0 +1,156 @@
+use sqlx::sqlite::SqlitePoolOptions;
+
+use std_app::db::users::{NewUser, UserRepository, UserUpdate};
+use std_app::page::PageRequest;
+use std_app::search::{SearchDoc, SearchIndex};
+
+async fn setup() -> (UserRepository, SearchIndex) {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let index = SearchIndex::new(pool.clone());
+    index.migrate().await.unwrap();
+    let repo = UserRepository::new(pool).hooks(|h| index.register(h));
+    repo.migrate().await.unwrap();
+    (repo, index)
+}
+
+fn new_user(name: &str, email: &str) -> NewUser {
+    NewUser {
+        name: name.to_string(),
+        email: email.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test_search {
+    use super::*;
+
+    //仓库的增删改通过钩子自动同步到索引
+    #[tokio::test]
+    async fn test_index_follows_repository() {
+        let (repo, index) = setup().await;
+        let alice = repo
+            .create(&new_user("Alice Liddell", "alice@wonderland.org"))
+            .await
+            .unwrap();
+        let bob = repo
+            .create(&new_user("Bob Builder", "bob@example.com"))
+            .await
+            .unwrap();
+
+        let page = index.query("alice", PageRequest::default()).await.unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.items[0].kind, "user");
+        assert_eq!(page.items[0].id, alice.id);
+        assert_eq!(page.items[0].title, "<mark>Alice</mark> Liddell");
+        assert!(page.items[0].snippet.contains("<mark>alice</mark>"));
+
+        let update = UserUpdate {
+            name: Some("Robert Builder".to_string()),
+            email: None,
+        };
+        repo.update(bob.id, &update).await.unwrap();
+        assert_eq!(
+            index
+                .query("bob", PageRequest::default())
+                .await
+                .unwrap()
+                .items[0]
+                .title,
+            "Robert Builder"
+        );
+        assert_eq!(
+            index
+                .query("robert", PageRequest::default())
+                .await
+                .unwrap()
+                .total,
+            1
+        );
+
+        repo.delete(bob.id).await.unwrap();
+        assert_eq!(
+            index
+                .query("builder", PageRequest::default())
+                .await
+                .unwrap()
+                .total,
+            0
+        );
+    }
+
+    //最后一个词按前缀匹配，标题匹配排在正文匹配前面
+    #[tokio::test]
+    async fn test_prefix_and_ranking() {
+        let (_, index) = setup().await;
+        index
+            .index_doc(&SearchDoc {
+                kind: "note",
+                id: 1,
+                title: "Deploy checklist".to_string(),
+                body: "Remember to rotate the keys before release".to_string(),
+            })
+            .await
+            .unwrap();
+        index
+            .index_doc(&SearchDoc {
+                kind: "note",
+                id: 2,
+                title: "Key rotation".to_string(),
+                body: "Rotate every quarter".to_string(),
+            })
+            .await
+            .unwrap();
+        let page = index.query("rot", PageRequest::default()).await.unwrap();
+        let ids: Vec<i64> = page.items.iter().map(|h| h.id).collect();
+        assert_eq!(ids, vec![2, 1]);
+
+        //FTS5 语法字符按普通文本处理
+        assert_eq!(
+            index
+                .query("\"rotate OR (", PageRequest::default())
+                .await
+                .unwrap()
+                .total,
+            0
+        );
+        assert_eq!(
+            index
+                .query("   ", PageRequest::default())
+                .await
+                .unwrap()
+                .total,
+            0
+        );
+
+        index.remove("note", 2).await.unwrap();
+        assert_eq!(
+            index
+                .query("rot", PageRequest::default())
+                .await
+                .unwrap()
+                .total,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pagination() {
+        let (repo, index) = setup().await;
+        for i in 1..=12 {
+            repo.create(&new_user(
+                &format!("Tester {}", i),
+                &format!("t{}@example.com", i),
+            ))
+            .await
+            .unwrap();
+        }
+        let page = index.query("tester", PageRequest::new(2, 5)).await.unwrap();
+        assert_eq!(page.total, 12);
+        assert_eq!(page.items.len(), 5);
+        assert!(page.has_next());
+    }
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use std_app::selftest::{Outcome, SelfTest};
+use std_app::testkit::http::{MockHttp, MockResponse};
+use std_app::testkit::TestWorkspace;
+
+#[cfg(test)]
+mod test_selftest {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_all_pass_from_config() {
+        let ws = TestWorkspace::new().unwrap();
+        let server = MockHttp::with_handler(|_| MockResponse::new(404))
+            .await
+            .unwrap();
+        std::fs::create_dir(ws.path("data")).unwrap();
+        let config = ws
+            .file(
+                "app.toml",
+                format!(
+                    "data_dir = \"{}\"\n[db]\nurl = \"sqlite://{}\"\n[http]\nendpoints = [\"{}\"]\n",
+                    ws.path("data").display(),
+                    ws.path("app.db").display(),
+                    server.url()
+                ),
+            )
+            .unwrap();
+        let report = SelfTest::new().config(&config).run().await;
+        let subsystems: Vec<_> = report.results.iter().map(|r| r.subsystem).collect();
+        assert_eq!(subsystems, vec!["config", "db", "http", "cache", "dir"]);
+        assert!(report.is_success(), "{}", report);
+        //404 也说明端点可达
+        assert_eq!(report.results[2].detail, "HTTP 404");
+        assert!(report.to_string().ends_with("5 项通过，0 项失败"));
+    }
+
+    #[tokio::test]
+    async fn test_failures_have_hints() {
+        let ws = TestWorkspace::new().unwrap();
+        let bad = ws.file("app.yaml", "db: [").unwrap();
+        let report = SelfTest::new()
+            .config(&bad)
+            .db(&format!(
+                "sqlite://{}",
+                ws.path("missing/dir/app.db").display()
+            ))
+            .endpoint("http://127.0.0.1:1")
+            .data_dir(ws.path("nope"))
+            .timeout(Duration::from_secs(2))
+            .run()
+            .await;
+        assert!(!report.is_success());
+        let failed: Vec<_> = report.failures().iter().map(|r| r.subsystem).collect();
+        assert_eq!(failed, vec!["config", "db", "http", "dir"]);
+        assert!(report.failures().iter().all(|r| r.hint.is_some()));
+        assert!(report.to_string().contains("mkdir -p"));
+
+        let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(json["results"][0]["outcome"], "fail");
+        let cache = &report.results[3];
+        assert_eq!((cache.subsystem, cache.outcome), ("cache", Outcome::Pass));
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use std_app::context::{self, RequestContext};
+use std_app::db::pool::Pool;
+use std_app::db::DbError;
+use std_app::http::{ApiError, HttpClient};
+use std_app::semaphore::{AcquireError, Priority, PrioritySemaphore};
+use std_app::testkit::http::{MockHttp, MockResponse};
+
+// 让已经 spawn 的等待者都进入队列
+async fn settle() {
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[cfg(test)]
+mod test_priority_semaphore {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_try_acquire() {
+        let sem = PrioritySemaphore::new(2);
+        let a = sem.try_acquire().unwrap();
+        let _b = sem.try_acquire().unwrap();
+        assert!(sem.try_acquire().is_none());
+        assert_eq!(sem.stats().available, 0);
+        drop(a);
+        assert_eq!(sem.stats().available, 1);
+        assert!(sem.try_acquire().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_higher_priority_wins() {
+        let sem = PrioritySemaphore::new(1);
+        let held = sem.try_acquire().unwrap();
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = Vec::new();
+        // 到达顺序: low, normal, critical, high, normal
+        for (i, priority) in [
+            Priority::Low,
+            Priority::Normal,
+            Priority::Critical,
+            Priority::High,
+            Priority::Normal,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let (sem, order) = (sem.clone(), order.clone());
+            tasks.push(tokio::spawn(async move {
+                let _permit = sem.acquire(priority).await;
+                order.lock().unwrap().push((i, priority));
+            }));
+            settle().await;
+        }
+        assert_eq!(sem.stats().waiting, 5);
+
+        drop(held);
+        for task in tasks {
+            task.await.unwrap();
+        }
+        // 同一优先级按到达顺序
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec![
+                (2, Priority::Critical),
+                (3, Priority::High),
+                (1, Priority::Normal),
+                (4, Priority::Normal),
+                (0, Priority::Low),
+            ]
+        );
+        assert_eq!(sem.stats().available, 1);
+    }
+
+    #[tokio::test]
+    async fn test_deadline_times_out() {
+        let sem = PrioritySemaphore::new(1);
+        let held = sem.try_acquire().unwrap();
+        let err = sem
+            .acquire_timeout(Priority::High, Duration::from_millis(20))
+            .await
+            .unwrap_err();
+        let AcquireError::Timeout { priority, waited } = err;
+        assert_eq!(priority, Priority::High);
+        assert!(waited >= Duration::from_millis(20));
+        assert_eq!(sem.stats().timeouts, 1);
+        assert_eq!(sem.stats().waiting, 0);
+
+        // 超时的等待者不会占用之后释放的许可
+        drop(held);
+        assert_eq!(sem.stats().available, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_waiter_releases_permit() {
+        let sem = PrioritySemaphore::new(1);
+        let held = sem.try_acquire().unwrap();
+        let waiter = {
+            let sem = sem.clone();
+            tokio::spawn(async move { sem.acquire(Priority::Critical).await })
+        };
+        settle().await;
+        waiter.abort();
+        let _ = waiter.await;
+
+        let next = {
+            let sem = sem.clone();
+            tokio::spawn(async move { sem.acquire(Priority::Low).await })
+        };
+        settle().await;
+        drop(held);
+        drop(next.await.unwrap());
+        assert_eq!(sem.stats().available, 1);
+    }
+
+    #[test]
+    fn test_priority_parse() {
+        assert_eq!("critical".parse::<Priority>(), Ok(Priority::Critical));
+        assert_eq!("HIGH".parse::<Priority>(), Ok(Priority::High));
+        assert!("urgent".parse::<Priority>().is_err());
+        assert_eq!(Priority::default().to_string(), "normal");
+        assert!(Priority::Critical > Priority::Low);
+    }
+}
+
+#[cfg(test)]
+mod test_gates {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_db_pool_acquire_with_priority() {
+        let pool = Pool::builder("sqlite::memory:").connect().await.unwrap();
+        let conn = pool.acquire_with(Priority::Low).await.unwrap();
+        assert_eq!(pool.gate().stats().available, 0);
+
+        let ctx = RequestContext::new()
+            .priority(Priority::Critical)
+            .timeout(Duration::from_millis(30));
+        let err = context::scope(ctx, pool.acquire_prioritized())
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err, DbError::Timeout { .. }));
+
+        drop(conn);
+        let mut conn = pool.acquire_with(Priority::Normal).await.unwrap();
+        let one: i64 = sqlx::query_scalar("SELECT 1")
+            .fetch_one(&mut *conn)
+            .await
+            .unwrap();
+        assert_eq!(one, 1);
+    }
+
+    #[tokio::test]
+    async fn test_http_gate_times_out_with_context_deadline() {
+        let server = MockHttp::with_handler(|_| MockResponse::ok("{}"))
+            .await
+            .unwrap();
+        let gate = PrioritySemaphore::new(1);
+        let client = HttpClient::builder(server.url())
+            .priority_gate(gate.clone())
+            .build()
+            .unwrap();
+        client.get_json::<serde_json::Value>("/a").await.unwrap();
+
+        let _held = gate.try_acquire().unwrap();
+        let ctx = RequestContext::new()
+            .priority(Priority::High)
+            .timeout(Duration::from_millis(20));
+        let err = context::scope(ctx, client.get_json::<serde_json::Value>("/b"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ApiError::Saturated(AcquireError::Timeout {
+                priority: Priority::High,
+                ..
+            })
+        ));
+        assert_eq!(server.requests().len(), 1);
+    }
+}
@@ -0,0 +1,89 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use std_app::serde_any::{self, Format, SerdeAnyError};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Database {
+    url: String,
+    pool_size: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct Config {
+    host: String,
+    port: u16,
+    debug: bool,
+    database: Database,
+}
+
+fn sample() -> Config {
+    Config {
+        host: "localhost".to_string(),
+        port: 8080,
+        debug: true,
+        database: Database {
+            url: "sqlite://example.db".to_string(),
+            pool_size: 4,
+        },
+    }
+}
+
+fn temp_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("std-app-serde-any-{}-{}", std::process::id(), name))
+}
+
+#[cfg(test)]
+mod test_serde_any {
+    use super::*;
+
+    //各格式写入再读回
+    #[test]
+    fn test_roundtrip_all_formats() -> Result<(), SerdeAnyError> {
+        for name in ["config.json", "config.toml", "config.yaml", "config.ini"] {
+            let path = temp_file(name);
+            serde_any::to_path(&path, &sample())?;
+            let config: Config = serde_any::from_path(&path)?;
+            fs::remove_file(&path)?;
+            assert_eq!(config, sample(), "{} 读回的内容不一致", name);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_ini() -> Result<(), SerdeAnyError> {
+        let text = r#"
+host = localhost
+port = 8080
+debug = true
+
+[database]
+url = sqlite://example.db
+pool_size = 4
+"#;
+        let config: Config = serde_any::from_str(text, Format::Ini)?;
+        assert_eq!(config, sample());
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_detection() {
+        assert_eq!(
+            Format::from_path(&PathBuf::from("a/b.YML")),
+            Some(Format::Yaml)
+        );
+        assert_eq!(Format::from_path(&PathBuf::from("a/b.xml")), None);
+        assert!(matches!(
+            serde_any::from_path::<Config>("config.xml"),
+            Err(SerdeAnyError::UnknownFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_yaml_missing_field() {
+        let result = serde_any::from_str::<Config>("hst: localhsot\nport: 8080\n", Format::Yaml);
+        assert!(matches!(result, Err(SerdeAnyError::Yaml(_))));
+    }
+}
@@ -0,0 +1,77 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use std_app::metrics::Registry;
+use std_app::signals::{self, SignalError, SIGHUP, SIGTERM, SIGUSR1};
+
+// 给当前进程发送信号
+fn raise(name: &str) {
+    let status = std::process::Command::new("kill")
+        .arg(format!("-{}", name))
+        .arg(std::process::id().to_string())
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+async fn wait_until(f: impl Fn() -> bool) {
+    for _ in 0..100 {
+        if f() {
+            return;
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("等待超时");
+}
+
+#[cfg(test)]
+mod test_signals {
+    use super::*;
+
+    // SIGHUP 重新加载配置，SIGUSR1 输出运行时状态
+    #[tokio::test]
+    async fn test_handlers() {
+        let reloads = Arc::new(AtomicUsize::new(0));
+        let dumps = Arc::new(Mutex::new(Vec::new()));
+
+        let counter = reloads.clone();
+        let reload = signals::on(SIGHUP, move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        })
+        .unwrap();
+        let out = dumps.clone();
+        let handle = tokio::runtime::Handle::current();
+        let registry = Registry::new();
+        registry.counter("requests").add(3);
+        signals::on(SIGUSR1, move || {
+            out.lock().unwrap().push(signals::dump(&handle, &registry))
+        })
+        .unwrap();
+
+        raise("HUP");
+        wait_until(|| reloads.load(Ordering::SeqCst) == 1).await;
+        raise("USR1");
+        wait_until(|| !dumps.lock().unwrap().is_empty()).await;
+        let dump = dumps.lock().unwrap()[0].clone();
+        let mut lines = dump.lines();
+        assert!(lines.next().unwrap().starts_with("workers="));
+        let metric: serde_json::Value = serde_json::from_str(lines.next().unwrap()).unwrap();
+        assert_eq!(metric["name"], "requests");
+        assert_eq!(metric["value"], 3);
+
+        // 移除后不再触发
+        signals::off(reload);
+        raise("HUP");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(reloads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_signals_reserved() {
+        assert!(matches!(
+            signals::on(SIGTERM, || {}),
+            Err(SignalError::Reserved(_))
+        ));
+    }
+}
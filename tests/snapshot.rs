@@ -0,0 +1,61 @@
+use serde::Serialize;
+use serde_json::json;
+
+use std_app::testkit::assert_snapshot;
+use std_app::testkit::snapshot::{SnapshotError, Snapshots};
+
+#[derive(Serialize)]
+struct User {
+    id: u32,
+    name: String,
+    roles: Vec<&'static str>,
+}
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("std-app-snapshot-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[cfg(test)]
+mod test_snapshot {
+    use super::*;
+
+    //快照文件保存在 tests/snapshots/user.json
+    #[test]
+    fn test_assert_snapshot() {
+        let user = User {
+            id: 1,
+            name: "Alice".to_string(),
+            roles: vec!["admin", "dev"],
+        };
+        assert_snapshot("user", &user);
+    }
+
+    #[test]
+    fn test_mismatch_shows_diff() -> Result<(), SnapshotError> {
+        let dir = temp_dir("mismatch");
+        let snapshots = Snapshots::new(&dir).update(false);
+        snapshots.check("config", &json!({"port": 8080, "host": "localhost"}))?;
+        assert!(snapshots.path("config").exists());
+
+        let err = snapshots
+            .check(
+                "config",
+                &json!({"port": 9090, "host": "localhost", "tls": true}),
+            )
+            .unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("~ /port: 8080 -> 9090"), "{}", message);
+        assert!(message.contains("+ /tls: true"), "{}", message);
+
+        // 更新模式下覆盖旧快照
+        let updating = Snapshots::new(&dir).update(true);
+        updating.check("config", &json!({"port": 9090}))?;
+        snapshots.check("config", &json!({"port": 9090}))?;
+
+        std::fs::remove_dir_all(dir)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use std_app::http::sse::{SseEvent, LAST_EVENT_ID};
+use std_app::http::{ApiError, HttpClient};
+use std_app::retry::Backoff;
+use std_app::testkit::http::{MockHttp, MockResponse};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+fn event(id: &str, event: &str, data: &str) -> SseEvent {
+    SseEvent {
+        id: Some(id.to_string()),
+        event: event.to_string(),
+        data: data.to_string(),
+    }
+}
+
+fn fast() -> Backoff {
+    Backoff::new(Duration::from_millis(10), Duration::from_millis(50)).max_attempts(3)
+}
+
+#[cfg(test)]
+mod test_sse {
+    use super::*;
+
+    //每次连接发送一部分事件后断开，客户端带上 Last-Event-ID 重连，服务端返回 204 结束
+    #[tokio::test]
+    async fn test_reconnect_with_last_event_id() {
+        let server = MockHttp::with_handler(|req| match req.header(LAST_EVENT_ID) {
+            None => MockResponse::ok(
+                ": hello\r\nretry: 5\r\nid: 1\r\ndata: first\r\n\r\nid: 2\r\nevent: update\r\ndata: a\r\ndata: b\r\n\r\ndata: partial",
+            )
+            .header("content-type", "text/event-stream"),
+            Some("2") => MockResponse::ok("id: 3\ndata: third\n\n"),
+            Some(_) => MockResponse::new(204),
+        })
+        .await
+        .unwrap();
+
+        let client = HttpClient::new(server.url());
+        let mut stream = client.sse("/events").backoff(fast());
+        let mut events = Vec::new();
+        while let Some(event) = stream.next().await {
+            events.push(event.unwrap());
+        }
+        assert_eq!(
+            events,
+            vec![
+                event("1", "message", "first"),
+                event("2", "update", "a\nb"),
+                event("3", "message", "third"),
+            ]
+        );
+        assert_eq!(stream.last_event_id(), Some("3"));
+        assert_eq!(stream.reconnects(), 2);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[0].header("accept"), Some("text/event-stream"));
+        assert_eq!(requests[1].header(LAST_EVENT_ID), Some("2"));
+        assert_eq!(requests[2].header(LAST_EVENT_ID), Some("3"));
+    }
+
+    //连接保持但长时间没有数据，超时后重连
+    #[tokio::test]
+    async fn test_heartbeat_timeout() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut held = Vec::new();
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await;
+                let head = "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n";
+                let body = "id: 7\ndata: tick\n\n";
+                let chunk = format!("{}{:x}\r\n{}\r\n", head, body.len(), body);
+                socket.write_all(chunk.as_bytes()).await.unwrap();
+                //不再发送数据，也不关闭连接
+                held.push(socket);
+            }
+        });
+
+        let client = HttpClient::new(&format!("http://{}", addr));
+        let mut stream = client
+            .sse("/events")
+            .backoff(fast())
+            .heartbeat_timeout(Duration::from_millis(100));
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            event("7", "message", "tick")
+        );
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            event("7", "message", "tick")
+        );
+        assert_eq!(stream.reconnects(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_client_error_is_fatal() {
+        let server = MockHttp::with_handler(|_| MockResponse::new(404).body("gone"))
+            .await
+            .unwrap();
+        let client = HttpClient::new(server.url());
+        let mut stream = client.sse("/events").backoff(fast());
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(ApiError::NotFound(_)))
+        ));
+        assert!(stream.next().await.is_none());
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    //服务端错误按退避重试，次数用完后返回最后一次的错误
+    #[tokio::test]
+    async fn test_server_error_retries() {
+        let server = MockHttp::with_handler(|_| MockResponse::new(503))
+            .await
+            .unwrap();
+        let client = HttpClient::new(server.url());
+        let mut stream = client.sse("/events").backoff(fast());
+        assert!(matches!(
+            stream.next().await,
+            Some(Err(ApiError::Status { status: 503, .. }))
+        ));
+        assert!(stream.next().await.is_none());
+        assert_eq!(server.requests().len(), 3);
+    }
+}
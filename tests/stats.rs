@@ -0,0 +1,137 @@
+use std::time::Duration;
+
+use std_app::clock::SimClock;
+use std_app::metrics::Registry;
+use std_app::stats::{Digest, SlidingWindow};
+
+// 1..=n 打乱顺序，避免按顺序插入掩盖问题
+fn shuffled(n: u64) -> Vec<f64> {
+    let mut values: Vec<f64> = (1..=n).map(|v| v as f64).collect();
+    let mut seed = 42u64;
+    for i in (1..values.len()).rev() {
+        seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+        values.swap(i, (seed >> 33) as usize % (i + 1));
+    }
+    values
+}
+
+fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (actual - expected).abs() <= expected * tolerance,
+        "{} 与期望值 {} 相差超过 {}",
+        actual,
+        expected,
+        tolerance
+    );
+}
+
+#[cfg(test)]
+mod test_digest {
+    use super::*;
+
+    #[test]
+    fn test_quantiles() {
+        let mut digest: Digest = shuffled(10_000).into_iter().collect();
+        assert_eq!(digest.count(), 10_000);
+        assert_eq!(digest.mean(), Some(5000.5));
+        assert_eq!(digest.quantile(0.0), Some(1.0));
+        assert_eq!(digest.quantile(1.0), Some(10_000.0));
+        assert_close(digest.quantile(0.5).unwrap(), 5000.0, 0.01);
+        assert_close(digest.quantile(0.9).unwrap(), 9000.0, 0.01);
+        assert_close(digest.quantile(0.99).unwrap(), 9900.0, 0.002);
+        assert!(digest.centroids().len() <= 100);
+    }
+
+    #[test]
+    fn test_empty_and_single() {
+        let mut digest = Digest::default();
+        assert_eq!(digest.quantile(0.5), None);
+        assert_eq!(digest.mean(), None);
+        digest.add(3.0);
+        digest.add(f64::NAN);
+        assert_eq!(digest.count(), 1);
+        assert_eq!(digest.quantile(0.5), Some(3.0));
+        assert_eq!(digest.quantile(0.99), Some(3.0));
+    }
+
+    //各 worker 分别记录，合并后与整体统计一致
+    #[test]
+    fn test_merge() {
+        let values = shuffled(20_000);
+        let mut merged = Digest::default();
+        for chunk in values.chunks(5_000) {
+            let worker: Digest = chunk.iter().copied().collect();
+            merged.merge(&worker);
+        }
+        assert_eq!(merged.count(), 20_000);
+        assert_eq!(merged.min(), Some(1.0));
+        assert_eq!(merged.max(), Some(20_000.0));
+        assert_close(merged.quantile(0.5).unwrap(), 10_000.0, 0.01);
+        assert_close(merged.quantile(0.99).unwrap(), 19_800.0, 0.002);
+    }
+
+    #[test]
+    fn test_serde_roundtrip() -> Result<(), serde_json::Error> {
+        let mut digest: Digest = shuffled(1_000).into_iter().collect();
+        let mut decoded: Digest = serde_json::from_str(&serde_json::to_string(&digest)?)?;
+        assert_eq!(decoded.count(), 1_000);
+        assert_eq!(decoded.quantile(0.9), digest.quantile(0.9));
+        Ok(())
+    }
+
+    #[test]
+    fn test_histogram_summary() {
+        let registry = Registry::new();
+        let latency = registry.histogram("latency_ms");
+        shuffled(1_000).into_iter().for_each(|v| latency.observe(v));
+        let other: Digest = [5_000.0].into_iter().collect();
+        latency.merge(&other);
+
+        let summary = latency.summary();
+        assert_eq!(summary.count, 1_001);
+        assert_eq!(summary.max, 5_000.0);
+        assert_close(summary.p50, 500.0, 0.02);
+        assert!(summary.p50 <= summary.p90 && summary.p90 <= summary.p99);
+    }
+}
+
+#[cfg(test)]
+mod test_sliding_window {
+    use super::*;
+
+    #[test]
+    fn test_expiry() {
+        let clock = SimClock::new();
+        let mut window = SlidingWindow::new(Duration::from_secs(60), 6).clock(clock.shared());
+        for v in 1..=10 {
+            window.record(v as f64);
+        }
+        clock.advance(Duration::from_secs(30));
+        window.record(100.0);
+        assert_eq!(window.count(), 11);
+        assert_eq!(window.digest().max(), Some(100.0));
+
+        //第一批数据所在的桶过期，后面记录的还在
+        clock.advance(Duration::from_secs(35));
+        assert_eq!(window.count(), 1);
+        assert_eq!(window.mean(), Some(100.0));
+        assert_eq!(window.quantile(0.5), Some(100.0));
+
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(window.count(), 0);
+        assert_eq!(window.quantile(0.5), None);
+    }
+
+    #[test]
+    fn test_rate() {
+        let clock = SimClock::new();
+        let mut window = SlidingWindow::new(Duration::from_secs(10), 10).clock(clock.shared());
+        for _ in 0..50 {
+            window.record(1.0);
+            clock.advance(Duration::from_millis(100));
+        }
+        assert_eq!(window.window(), Duration::from_secs(10));
+        assert_eq!(window.rate(), 5.0);
+        assert_eq!(window.sum(), 50.0);
+    }
+}
@@ -0,0 +1,201 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use sqlx::sqlite::SqlitePoolOptions;
+
+use std_app::clock;
+use std_app::http::sse::LAST_EVENT_ID;
+use std_app::http::HttpClient;
+use std_app::page::{Page, PageRequest};
+use std_app::ratelimit::TokenBucket;
+use std_app::retry::Backoff;
+use std_app::streamx;
+use std_app::testkit::http::{MockHttp, MockResponse};
+
+// 每个元素之间间隔 gap 的流
+fn spaced(items: Vec<u32>, gap: Duration) -> impl futures_util::Stream<Item = u32> {
+    stream::iter(items).then(move |i| async move {
+        tokio::time::sleep(gap).await;
+        i
+    })
+}
+
+fn fast() -> Backoff {
+    Backoff::new(Duration::from_millis(1), Duration::from_millis(5)).max_attempts(3)
+}
+
+#[cfg(test)]
+mod test_streamx {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_chunked_timeout() {
+        let chunks: Vec<Vec<u32>> =
+            streamx::chunked_timeout(stream::iter(1..=7), 3, Duration::from_secs(60))
+                .collect()
+                .await;
+        assert_eq!(chunks, vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]]);
+
+        //上游很慢时不等凑满
+        let chunks: Vec<Vec<u32>> = streamx::chunked_timeout(
+            spaced(vec![1, 2, 3], Duration::from_millis(40)),
+            10,
+            Duration::from_millis(10),
+        )
+        .collect()
+        .await;
+        assert_eq!(chunks, vec![vec![1], vec![2], vec![3]]);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let bucket = Arc::new(TokenBucket::new(2, 100.0));
+        let start = Instant::now();
+        let items: Vec<u32> = streamx::rate_limit(stream::iter(0..6), bucket)
+            .collect()
+            .await;
+        assert_eq!(items, vec![0, 1, 2, 3, 4, 5]);
+        //突发 2 个，之后每 10ms 一个
+        assert!(start.elapsed() >= Duration::from_millis(35));
+    }
+
+    #[tokio::test]
+    async fn test_retry_stream_resumes() {
+        let attempts = AtomicUsize::new(0);
+        let last = Arc::new(Mutex::new(0u32));
+        let cursor = last.clone();
+        let items: Vec<Result<u32, String>> =
+            streamx::retry_stream(clock::system(), fast(), || {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                let from = *cursor.lock().unwrap();
+                //第一次连接读到 2 就断开
+                let items: Vec<Result<u32, String>> = if attempt == 0 {
+                    vec![Ok(1), Ok(2), Err("连接断开".into())]
+                } else {
+                    (from + 1..=4).map(Ok).collect()
+                };
+                stream::iter(items)
+            })
+            .inspect(|item| {
+                if let Ok(i) = item {
+                    *last.lock().unwrap() = *i;
+                }
+            })
+            .collect()
+            .await;
+        assert_eq!(items, vec![Ok(1), Ok(2), Ok(3), Ok(4)]);
+
+        //连续失败超过次数后输出错误并结束
+        let items: Vec<Result<u32, String>> =
+            streamx::retry_stream(clock::system(), fast(), || {
+                stream::iter(vec![Err::<u32, _>("不可用".to_string())])
+            })
+            .collect()
+            .await;
+        assert_eq!(items, vec![Err("不可用".into())]);
+    }
+
+    #[tokio::test]
+    async fn test_parallel_map() {
+        let active = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let results: Vec<u32> = streamx::parallel_map(stream::iter(0..12u32), 3, |i| {
+            let active = active.clone();
+            let peak = peak.clone();
+            async move {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                //越靠前越慢，输出仍然按输入顺序
+                tokio::time::sleep(Duration::from_millis(12 - i as u64)).await;
+                active.fetch_sub(1, Ordering::SeqCst);
+                i * 10
+            }
+        })
+        .collect()
+        .await;
+        assert_eq!(results, (0..12).map(|i| i * 10).collect::<Vec<_>>());
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+
+        let unordered: Vec<u32> =
+            streamx::parallel_map_unordered(stream::iter(0..5u32), 5, |i| async move {
+                tokio::time::sleep(Duration::from_millis(10 * (5 - i as u64))).await;
+                i
+            })
+            .collect()
+            .await;
+        assert_eq!(unordered, vec![4, 3, 2, 1, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_paginate() {
+        let data: Vec<u32> = (0..45).collect();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        let items: Vec<u32> = streamx::paginate(20, |req: PageRequest| {
+            seen.lock().unwrap().push(req.page);
+            let items = data
+                .iter()
+                .skip(req.offset() as usize)
+                .take(req.limit() as usize)
+                .copied()
+                .collect();
+            let total = data.len() as u64;
+            async move { Ok::<_, String>(Page::new(items, total, req)) }
+        })
+        .try_collect()
+        .await
+        .unwrap();
+        assert_eq!(items, data);
+        assert_eq!(*requests.lock().unwrap(), vec![1, 2, 3]);
+
+        let failed: Vec<Result<u32, String>> =
+            streamx::paginate(20, |_| async { Err::<Page<u32>, _>("超时".to_string()) })
+                .collect()
+                .await;
+        assert_eq!(failed, vec![Err("超时".into())]);
+    }
+
+    //sqlx 的查询结果流可以直接分批
+    #[tokio::test]
+    async fn test_db_rows_in_chunks() {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let rows = sqlx::query_scalar::<_, i64>(
+            "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c WHERE x < 10) SELECT x FROM c",
+        )
+        .fetch(&pool);
+        let chunks: Vec<Vec<i64>> = streamx::chunked_timeout(rows, 4, Duration::from_secs(1))
+            .map(|chunk| chunk.into_iter().collect::<Result<_, _>>().unwrap())
+            .collect()
+            .await;
+        assert_eq!(
+            chunks,
+            vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8], vec![9, 10]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sse_into_stream() {
+        let server = MockHttp::with_handler(|req| match req.header(LAST_EVENT_ID) {
+            None => MockResponse::ok("id: 1\ndata: a\n\nid: 2\ndata: b\n\n"),
+            Some(_) => MockResponse::new(204),
+        })
+        .await
+        .unwrap();
+        let client = HttpClient::new(server.url());
+        let data: Vec<String> = client
+            .sse("/events")
+            .backoff(fast())
+            .into_stream()
+            .map_ok(|event| event.data)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(data, vec!["a", "b"]);
+    }
+}
@@ -0,0 +1,143 @@
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use std_app::sync::{self, Action, Compare, SyncError, SyncOptions};
+use std_app::testkit::s3::MockS3;
+use std_app::testkit::TestWorkspace;
+
+fn write(root: &Path, name: &str, content: &str) {
+    let path = root.join(name);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, content).unwrap();
+}
+
+#[cfg(test)]
+mod test_local {
+    use super::*;
+
+    #[test]
+    fn test_copy_update_delete() -> Result<(), SyncError> {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let (src, dst) = (root.join("src"), root.join("dst"));
+        write(&src, "a.txt", "alpha");
+        write(&src, "nested/b.txt", "bravo");
+
+        let summary = sync::run(&src, &dst, &SyncOptions::new())?;
+        assert_eq!(summary.paths(Action::Create), vec!["a.txt", "nested/b.txt"]);
+        assert_eq!(summary.bytes_copied, 10);
+        assert_eq!(fs::read_to_string(dst.join("nested/b.txt"))?, "bravo");
+        // echo -n alpha | sha256sum
+        assert_eq!(
+            summary.entries[0].sha256.as_deref(),
+            Some("8ed3f6ad685b959ead7022518e1af76cd816f8e8ec7ccdda1ed4018e8f2223f8")
+        );
+
+        //再次同步没有变化
+        let summary = sync::run(&src, &dst, &SyncOptions::new())?;
+        assert!(summary.is_noop());
+        assert_eq!(summary.unchanged, 2);
+
+        write(&src, "a.txt", "ALPHA!");
+        write(&dst, "extra.txt", "stale");
+        let summary = sync::run(&src, &dst, &SyncOptions::new().delete(true))?;
+        assert_eq!(summary.paths(Action::Update), vec!["a.txt"]);
+        assert_eq!(summary.paths(Action::Delete), vec!["extra.txt"]);
+        assert!(!dst.join("extra.txt").exists());
+        assert_eq!(fs::read_to_string(dst.join("a.txt"))?, "ALPHA!");
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run_and_checksum() -> Result<(), SyncError> {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let (src, dst) = (root.join("src"), root.join("dst"));
+        write(&src, "a.txt", "same");
+        write(&dst, "a.txt", "same");
+        write(&dst, "old.txt", "x");
+        let old = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        fs::File::options()
+            .write(true)
+            .open(dst.join("a.txt"))?
+            .set_modified(old)?;
+
+        // 修改时间不同，按大小和时间比较需要复制，按哈希比较则不需要
+        let options = SyncOptions::new().dry_run(true).delete(true);
+        let summary = sync::run(&src, &dst, &options)?;
+        assert_eq!(summary.paths(Action::Update), vec!["a.txt"]);
+        assert_eq!(summary.paths(Action::Delete), vec!["old.txt"]);
+        assert!(dst.join("old.txt").exists());
+
+        let summary = sync::run(&src, &dst, &options.compare(Compare::Checksum))?;
+        assert_eq!(summary.unchanged, 1);
+        assert_eq!(summary.paths(Action::Delete), vec!["old.txt"]);
+
+        let json: serde_json::Value = serde_json::from_str(&summary.to_json()).unwrap();
+        assert_eq!(json["dry_run"], true);
+        assert_eq!(json["entries"][0]["action"], "delete");
+        Ok(())
+    }
+
+    #[test]
+    fn test_progress_and_errors() -> Result<(), SyncError> {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let (src, dst) = (root.join("src"), root.join("dst"));
+        write(&src, "big.bin", &"x".repeat(200_000));
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = seen.clone();
+        let options = SyncOptions::new().on_progress(move |path, done, total| {
+            log.lock().unwrap().push((path.to_string(), done, total));
+        });
+        sync::run(&src, &dst, &options)?;
+        let seen = seen.lock().unwrap();
+        assert!(seen.len() > 1);
+        assert_eq!(
+            seen.last().unwrap(),
+            &("big.bin".to_string(), 200_000, 200_000)
+        );
+
+        assert!(matches!(
+            sync::run(&root.join("missing"), &dst, &SyncOptions::new()),
+            Err(SyncError::NotADirectory(_))
+        ));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_remote {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sync_to_object_storage() -> Result<(), SyncError> {
+        let mock = MockS3::start().await?;
+        let storage = mock.storage();
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        write(root, "a.txt", "alpha");
+        write(root, "dir/b.txt", "bravo");
+        storage.put("backup/stale.txt", b"old".to_vec()).await?;
+        storage.put("other/keep.txt", b"keep".to_vec()).await?;
+
+        let options = SyncOptions::new().delete(true);
+        let summary = sync::run_remote(root, &storage, "backup/", &options).await?;
+        assert_eq!(summary.paths(Action::Create), vec!["a.txt", "dir/b.txt"]);
+        assert_eq!(summary.paths(Action::Delete), vec!["stale.txt"]);
+        assert_eq!(storage.get("backup/dir/b.txt").await?, b"bravo");
+        assert_eq!(storage.get("other/keep.txt").await?, b"keep");
+
+        //大小相同但内容不同，只有按哈希比较才能发现
+        write(root, "a.txt", "ALPHA");
+        let summary = sync::run_remote(root, &storage, "backup", &options).await?;
+        assert!(summary.is_noop());
+        let checksum = options.compare(Compare::Checksum);
+        let summary = sync::run_remote(root, &storage, "backup", &checksum).await?;
+        assert_eq!(summary.paths(Action::Update), vec!["a.txt"]);
+        assert_eq!(storage.get("backup/a.txt").await?, b"ALPHA");
+        Ok(())
+    }
+}
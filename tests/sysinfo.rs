@@ -0,0 +1,51 @@
+use std::hint::black_box;
+
+use std_app::sysinfo::{self, CpuSampler, Thresholds};
+
+#[cfg(test)]
+mod test_sysinfo {
+    use super::*;
+
+    #[test]
+    fn test_process_stats() -> std::io::Result<()> {
+        let before = sysinfo::process()?;
+        assert!(before.rss_bytes > 0);
+        assert!(before.threads >= 1);
+
+        let _file = std::fs::File::open("Cargo.toml")?;
+        assert!(sysinfo::process()?.open_fds > before.open_fds);
+        Ok(())
+    }
+
+    #[test]
+    fn test_disk_and_cpu() -> std::io::Result<()> {
+        let disk = sysinfo::disk(std::env::temp_dir())?;
+        assert!(disk.total_bytes > 0);
+        assert!(disk.available_bytes <= disk.total_bytes);
+        assert!(sysinfo::disk("/no/such/dir").is_err());
+
+        let mut cpu = CpuSampler::new()?;
+        black_box((0..2_000_000u64).sum::<u64>());
+        assert!(cpu.sample()? >= 0.0);
+        Ok(())
+    }
+
+    //阈值从配置文件读取
+    #[test]
+    fn test_thresholds() {
+        let thresholds: Thresholds = toml::from_str(
+            r#"
+            max_rss_bytes = 1
+            min_disk_available_ratio = 1.1
+            data_dirs = ["/tmp"]
+            "#,
+        )
+        .unwrap();
+        let snapshot = thresholds.snapshot(None).unwrap();
+        let problems = thresholds.check(&snapshot);
+        assert_eq!(problems.len(), 2);
+        assert!(problems[0].starts_with("内存占用过高"));
+
+        assert!(Thresholds::default().check(&snapshot).is_empty());
+    }
+}
@@ -0,0 +1,91 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use std_app::tail::{self, Follow};
+use std_app::testkit::TestWorkspace;
+
+fn append(path: &Path, text: &str) {
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .unwrap();
+    file.write_all(text.as_bytes()).unwrap();
+}
+
+fn fast(follow: Follow) -> Follow {
+    follow
+        .poll_interval(Duration::from_millis(5))
+        .idle_timeout(Duration::from_millis(300))
+}
+
+async fn next_line(follow: &mut Follow) -> String {
+    follow.next().await.expect("超时前应该有新行").unwrap()
+}
+
+#[cfg(test)]
+mod test_follow {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_follow_new_lines() {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let path = root.join("app.log");
+        append(&path, "old line\n");
+
+        let mut follow = fast(tail::follow(&path));
+        append(&path, "first\r\nsec");
+        assert_eq!(next_line(&mut follow).await, "first");
+        append(&path, "ond\n");
+        assert_eq!(next_line(&mut follow).await, "second");
+        //空闲超时后结束
+        assert!(follow.next().await.is_none());
+
+        let mut all = fast(tail::follow(&path).from_start());
+        assert_eq!(next_line(&mut all).await, "old line");
+    }
+
+    #[tokio::test]
+    async fn test_rotation_and_truncation() {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let path = root.join("app.log");
+        append(&path, "");
+        let mut follow = fast(tail::follow(&path));
+
+        append(&path, "before rotate\nno newline");
+        assert_eq!(next_line(&mut follow).await, "before rotate");
+
+        fs::rename(&path, root.join("app.log.1")).unwrap();
+        append(&path, "after rotate\n");
+        assert_eq!(next_line(&mut follow).await, "no newline");
+        assert_eq!(next_line(&mut follow).await, "after rotate");
+        assert_eq!(follow.rotations(), 1);
+
+        fs::write(&path, "").unwrap();
+        append(&path, "x\n");
+        assert_eq!(next_line(&mut follow).await, "x");
+        assert_eq!(follow.truncations(), 1);
+    }
+
+    //文件在开始跟踪之后才创建
+    #[tokio::test]
+    async fn test_wait_for_file() {
+        let ws = TestWorkspace::new().unwrap();
+        let root = ws.root();
+        let path = root.join("later.log");
+        let mut follow = fast(tail::follow(&path));
+        let writer = {
+            let path = path.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                append(&path, "hello\n");
+            })
+        };
+        assert_eq!(next_line(&mut follow).await, "hello");
+        writer.await.unwrap();
+    }
+}
@@ -0,0 +1,241 @@
+use std::time::Duration;
+
+use serde_json::json;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+
+use std_app::context::{self, RequestContext};
+use std_app::db::users::{NewUser, UserRepository};
+use std_app::db::DbError;
+use std_app::page::PageRequest;
+use std_app::tenant::{
+    self, TenantCache, TenantConfig, TenantError, TenantId, TenantOwned, TenantPools,
+};
+use std_app::testkit::TestWorkspace;
+
+#[derive(Debug, sqlx::FromRow)]
+struct Order {
+    tenant_id: String,
+    item: String,
+}
+
+impl TenantOwned for Order {
+    fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+}
+
+fn ctx(tenant: &str) -> RequestContext {
+    RequestContext::new().tenant(tenant.parse().unwrap())
+}
+
+async fn orders() -> SqlitePool {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    sqlx::raw_sql(
+        "CREATE TABLE orders (tenant_id TEXT NOT NULL, item TEXT NOT NULL);
+         INSERT INTO orders VALUES ('acme', 'anvil'), ('acme', 'rocket'), ('globex', 'laser');",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+    pool
+}
+
+#[cfg(test)]
+mod test_tenant {
+    use super::*;
+
+    //查询只返回当前租户的数据
+    #[tokio::test]
+    async fn test_scoped_queries() -> Result<(), TenantError> {
+        let pool = orders().await;
+        let sql = "SELECT tenant_id, item FROM orders WHERE tenant_id = ?1 AND item != ?2";
+        let acme: Vec<Order> =
+            context::scope(ctx("acme"), tenant::fetch_all(&pool, sql, &["rocket"])).await?;
+        assert_eq!(acme.len(), 1);
+        assert_eq!(acme[0].item, "anvil");
+
+        assert!(matches!(
+            tenant::fetch_all::<Order>(&pool, sql, &[]).await,
+            Err(TenantError::Missing)
+        ));
+        assert!(matches!(
+            context::scope(
+                ctx("acme"),
+                tenant::fetch_all::<Order>(&pool, "SELECT * FROM orders", &[])
+            )
+            .await,
+            Err(TenantError::Unscoped(_))
+        ));
+        Ok(())
+    }
+
+    // 只有 ?10 或者其他列引用 ?1 都不算按租户过滤
+    #[tokio::test]
+    async fn test_tenant_predicate_required() {
+        let pool = orders().await;
+        for sql in [
+            "SELECT * FROM orders WHERE item = ?10",
+            "SELECT * FROM orders WHERE item = ?1",
+            "SELECT * FROM orders WHERE tenant_id = ?11",
+        ] {
+            assert!(matches!(
+                context::scope(ctx("acme"), tenant::fetch_all::<Order>(&pool, sql, &[])).await,
+                Err(TenantError::Unscoped(_))
+            ));
+        }
+        let rows: Vec<Order> = context::scope(
+            ctx("acme"),
+            tenant::fetch_all(&pool, "SELECT * FROM orders o WHERE o.TENANT_ID=?1", &[]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(rows.len(), 2);
+    }
+
+    // 仓库的读写都限定在当前租户内
+    #[tokio::test]
+    async fn test_scoped_user_repository() -> Result<(), DbError> {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        let repo = UserRepository::new(pool).tenant_scoped();
+        repo.migrate().await?;
+        let user = |name: &str| NewUser {
+            name: name.to_string(),
+            email: "ops@example.com".to_string(),
+        };
+
+        let acme = context::scope(ctx("acme"), repo.create(&user("Acme Ops"))).await?;
+        // 同一个邮箱可以在另一个租户下注册
+        let globex = context::scope(ctx("globex"), repo.create(&user("Globex Ops"))).await?;
+
+        context::scope(ctx("acme"), async {
+            assert_eq!(repo.get(acme.id).await?, acme);
+            assert!(matches!(
+                repo.get(globex.id).await,
+                Err(DbError::NotFound(_))
+            ));
+            assert_eq!(
+                repo.find_many(&[acme.id, globex.id]).await?.missing,
+                vec![globex.id]
+            );
+            assert_eq!(repo.list(PageRequest::default()).await?.total, 1);
+            assert_eq!(
+                repo.find_by_email("ops@example.com").await?,
+                Some(acme.clone())
+            );
+            assert!(matches!(
+                repo.delete(globex.id).await,
+                Err(DbError::NotFound(_))
+            ));
+            Ok::<_, DbError>(())
+        })
+        .await?;
+
+        assert!(matches!(repo.get(acme.id).await, Err(DbError::Rejected(_))));
+        Ok(())
+    }
+
+    //debug 构建下跨租户读取直接 panic
+    #[cfg(debug_assertions)]
+    #[tokio::test]
+    #[should_panic(expected = "跨租户读取")]
+    async fn test_cross_tenant_guard() {
+        let pool = orders().await;
+        let _: Vec<Order> = context::scope(
+            ctx("acme"),
+            tenant::fetch_all(&pool, "SELECT * FROM orders WHERE tenant_id = ?1 OR 1", &[]),
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cache_namespaces() -> Result<(), TenantError> {
+        let cache = TenantCache::new(Duration::from_secs(60));
+        context::sync_scope(ctx("acme"), || cache.insert("plan", "gold"))?;
+        context::sync_scope(ctx("globex"), || cache.insert("plan", "free"))?;
+        assert_eq!(
+            context::sync_scope(ctx("acme"), || cache.get("plan"))?,
+            Some("gold")
+        );
+        assert_eq!(
+            context::sync_scope(ctx("globex"), || cache.get("plan"))?,
+            Some("free")
+        );
+        assert!(cache.get("plan").is_err());
+        assert_eq!(
+            context::sync_scope(ctx("acme"), || tenant::namespace("k"))?,
+            "tenant:acme:k"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_config_overrides() {
+        let config: TenantConfig = serde_json::from_value(json!({
+            "base": {"limits": {"users": 10, "storage": 5}, "theme": "light"},
+            "overrides": {"acme": {"limits": {"users": 100}}}
+        }))
+        .unwrap();
+        let acme = context::sync_scope(ctx("acme"), || config.get());
+        assert_eq!(
+            acme,
+            json!({"limits": {"users": 100, "storage": 5}, "theme": "light"})
+        );
+        assert_eq!(
+            context::sync_scope(ctx("globex"), || config.get()),
+            config.base
+        );
+        assert!(serde_json::from_value::<TenantConfig>(
+            json!({"base": {}, "overrides": {"a/b": {}}})
+        )
+        .is_err());
+    }
+
+    //每个租户一个数据库文件
+    #[tokio::test]
+    async fn test_database_per_tenant() -> Result<(), TenantError> {
+        let ws = TestWorkspace::new().unwrap();
+        let pools = TenantPools::new(&format!(
+            "sqlite://{}/{{tenant}}.db?mode=rwc",
+            ws.root().display()
+        ));
+        for name in ["acme", "globex"] {
+            let pool = context::scope(ctx(name), pools.pool()).await?;
+            sqlx::query("CREATE TABLE notes (body TEXT)")
+                .execute(&pool)
+                .await?;
+            sqlx::query("INSERT INTO notes VALUES (?)")
+                .bind(name)
+                .execute(&pool)
+                .await?;
+        }
+        let acme = pools.pool_for(&TenantId::new("acme")?).await?;
+        let bodies: Vec<String> = sqlx::query_scalar("SELECT body FROM notes")
+            .fetch_all(&acme)
+            .await?;
+        assert_eq!(bodies, vec!["acme"]);
+        assert_eq!(pools.tenants().len(), 2);
+        assert!(ws.path("globex.db").exists());
+        assert!(matches!(
+            TenantId::new("../x"),
+            Err(TenantError::Invalid(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_context_headers() {
+        let ctx = ctx("acme");
+        assert!(ctx.headers().contains(&("x-tenant-id", "acme".to_string())));
+        assert!(ctx.to_string().contains("tenant_id=acme"));
+    }
+}
@@ -0,0 +1,102 @@
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use std_app::testkit::TestEnv;
+
+// 最简单的 SMTP 客户端，逐条发送命令并检查响应码
+async fn send_mail(addr: &str, from: &str, to: &[&str], data: &str) -> std::io::Result<()> {
+    let mut stream = BufReader::new(TcpStream::connect(addr).await?);
+    let mut line = String::new();
+    stream.read_line(&mut line).await?;
+    assert!(line.starts_with("220"));
+
+    let mut commands = vec![
+        "HELO localhost".to_string(),
+        format!("MAIL FROM:<{}>", from),
+    ];
+    commands.extend(to.iter().map(|t| format!("RCPT TO:<{}>", t)));
+    commands.push("DATA".to_string());
+    for command in commands {
+        stream
+            .get_mut()
+            .write_all(format!("{}\r\n", command).as_bytes())
+            .await?;
+        line.clear();
+        stream.read_line(&mut line).await?;
+        assert!(line.starts_with('2') || line.starts_with('3'), "{}", line);
+    }
+    stream
+        .get_mut()
+        .write_all(format!("{}\r\n.\r\nQUIT\r\n", data).as_bytes())
+        .await?;
+    line.clear();
+    stream.read_line(&mut line).await?;
+    assert!(line.starts_with("250"));
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_env {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_smtp_sink() {
+        let env = TestEnv::builder().smtp().build().await.unwrap();
+        send_mail(
+            env.smtp().addr(),
+            "noreply@example.com",
+            &["alice@example.com", "bob@example.com"],
+            "Subject: Welcome\r\n\r\nHello, Rust!\r\n..dot",
+        )
+        .await
+        .unwrap();
+
+        let mails = env.smtp().mails();
+        assert_eq!(mails.len(), 1);
+        assert_eq!(mails[0].from, "noreply@example.com");
+        assert_eq!(mails[0].to, vec!["alice@example.com", "bob@example.com"]);
+        assert_eq!(mails[0].header("subject"), Some("Welcome"));
+        assert_eq!(mails[0].body(), "Hello, Rust!\r\n.dot\r\n");
+    }
+
+    #[tokio::test]
+    async fn test_object_storage() {
+        let env = TestEnv::builder().object_storage().build().await.unwrap();
+        let storage = env.s3().storage();
+        storage.put("a.txt", b"hello".to_vec()).await.unwrap();
+        assert_eq!(env.s3().object("a.txt").unwrap(), b"hello");
+        assert_eq!(env.s3().keys(), vec!["a.txt"]);
+    }
+
+    //每个数据库互相隔离，并且都执行过迁移
+    #[tokio::test]
+    async fn test_ephemeral_sqlite() {
+        let env = TestEnv::builder()
+            .migration("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT NOT NULL)")
+            .build()
+            .await
+            .unwrap();
+        let dir = env.dir().to_path_buf();
+
+        let db1 = env.sqlite().await.unwrap();
+        let db2 = env.sqlite().await.unwrap();
+        sqlx::query("INSERT INTO users (name) VALUES ('Alice')")
+            .execute(&db1)
+            .await
+            .unwrap();
+
+        let count = |pool| async move {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM users")
+                .fetch_one(&pool)
+                .await
+                .unwrap()
+        };
+        assert_eq!(count(db1.clone()).await, 1);
+        assert_eq!(count(db2.clone()).await, 0);
+
+        db1.close().await;
+        db2.close().await;
+        drop(env);
+        assert!(!dir.exists());
+    }
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+use std_app::serde_any::{self, Format};
+use std_app::testkit::gen::{self, Gen};
+use std_app::{prop_assert, prop_assert_eq, prop_check};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct Person {
+    name: String,
+    age: u32,
+    address: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct Config {
+    port: u16,
+    host: String,
+}
+
+fn person() -> impl Gen<Value = Person> {
+    (gen::alnum(12), gen::u32s(0, 150), gen::any_text(30)).map(
+        |(name, age, address)| Person { name, age, address },
+        |p| (p.name.clone(), p.age, p.address.clone()),
+    )
+}
+
+const HOSTS: [&str; 3] = ["localhost", "127.0.0.1", "example.com"];
+
+fn config() -> impl Gen<Value = Config> {
+    (gen::i32s(0, 65535), gen::one_of(HOSTS.to_vec())).map(
+        |(port, host)| Config {
+            port: port as u16,
+            host: host.to_string(),
+        },
+        |c| {
+            let host = HOSTS.iter().find(|h| **h == c.host).unwrap_or(&HOSTS[0]);
+            (c.port as i32, *host)
+        },
+    )
+}
+
+fn validate_port(config: &Config) -> Result<(), String> {
+    if config.port == 0 {
+        return Err("端口号无效: 0".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_gen {
+    use super::*;
+
+    //序列化往返
+    #[test]
+    fn test_person_json_roundtrip() {
+        prop_check!(person(), |p| {
+            let json = serde_json::to_string(p).map_err(|e| e.to_string())?;
+            let back: Person = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+            prop_assert_eq!(&back, p);
+        });
+    }
+
+    #[test]
+    fn test_config_toml_and_yaml_roundtrip() {
+        prop_check!(config(), |c| {
+            for format in [Format::Toml, Format::Yaml] {
+                let text = serde_any::to_string(c, format).map_err(|e| e.to_string())?;
+                let back: Config = serde_any::from_str(&text, format).map_err(|e| e.to_string())?;
+                prop_assert_eq!(&back, c);
+            }
+        });
+    }
+
+    //校验层: 只有 0 端口会被拒绝
+    #[test]
+    fn test_port_validation_property() {
+        prop_check!(config(), |c| {
+            prop_assert!(
+                validate_port(c).is_ok() == (c.port != 0),
+                "port={} 校验结果不符合预期",
+                c.port
+            );
+        });
+    }
+
+    //失败的属性会被缩小到最简单的反例
+    #[test]
+    fn test_shrinking_finds_minimal_counterexample() {
+        let failure = gen::check(&gen::u32s(0, 1000), |&n| {
+            if n < 50 {
+                Ok(())
+            } else {
+                Err(format!("{} 太大", n))
+            }
+        })
+        .unwrap_err();
+        assert_eq!(failure.shrunk, 50);
+        assert_eq!(failure.message, "50 太大");
+
+        let failure = gen::check(&gen::vecs(gen::u32s(0, 100), 20), |v| {
+            if v.iter().sum::<u32>() <= 100 {
+                Ok(())
+            } else {
+                Err("sum > 100".to_string())
+            }
+        })
+        .unwrap_err();
+        assert!(failure.shrunk.iter().sum::<u32>() > 100);
+        assert!(failure.shrunk.len() <= 3, "{:?}", failure.shrunk);
+
+        let failure = gen::check(&person(), |p| {
+            if p.name.contains('z') {
+                Err("名字包含 z".to_string())
+            } else {
+                Ok(())
+            }
+        })
+        .unwrap_err();
+        assert_eq!(failure.shrunk.name, "z");
+        assert_eq!(failure.shrunk.age, 0);
+        assert_eq!(failure.shrunk.address, "");
+    }
+
+    #[test]
+    fn test_same_seed_same_values() {
+        let mut a = gen::Rng::new(7);
+        let mut b = gen::Rng::new(7);
+        let g = gen::vecs(gen::alnum(8), 5);
+        assert_eq!(g.generate(&mut a), g.generate(&mut b));
+    }
+}
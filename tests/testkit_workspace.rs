@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use std_app::serde_any;
+use std_app::testkit::TestWorkspace;
+
+const ENV_KEY: &str = "STD_APP_WORKSPACE_TEST";
+
+#[cfg(test)]
+mod test_workspace {
+    use super::*;
+
+    #[test]
+    fn test_unique_dirs_cleaned_up() -> std::io::Result<()> {
+        let a = TestWorkspace::new()?;
+        let b = TestWorkspace::new()?;
+        assert_ne!(a.root(), b.root());
+
+        let path = a.file("nested/config.toml", "port = 8080")?;
+        assert!(path.starts_with(a.root()));
+        assert_eq!(a.read("nested/config.toml")?, "port = 8080");
+
+        let root = a.root().to_path_buf();
+        drop(a);
+        assert!(!root.exists());
+        assert!(b.root().exists());
+        Ok(())
+    }
+
+    #[test]
+    fn test_seeded_config() {
+        let ws = TestWorkspace::new().unwrap();
+        let config = HashMap::from([("host", "localhost"), ("port", "8080")]);
+        let path = ws.config("app.yaml", &config).unwrap();
+        let loaded: HashMap<String, String> = serde_any::from_path(path).unwrap();
+        assert_eq!(loaded["host"], "localhost");
+    }
+
+    //环境变量在 workspace 释放后恢复
+    #[test]
+    fn test_env_scoping() {
+        {
+            let mut ws = TestWorkspace::new().unwrap();
+            ws.set_env(ENV_KEY, "first");
+            ws.set_env(ENV_KEY, "second");
+            assert_eq!(std::env::var(ENV_KEY).unwrap(), "second");
+        }
+        assert!(std::env::var(ENV_KEY).is_err());
+    }
+}
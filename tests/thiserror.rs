@@ -2,6 +2,8 @@ use std::path::PathBuf;
 
 use thiserror::Error;
 
+use std_app::testkit::TestWorkspace;
+
 #[derive(Error, Debug)]
 enum FileError {
     #[error("文件 {path} 读取失败")]
@@ -49,16 +51,13 @@ mod tests_file {
     #[test]
     fn test_read_file_success() -> Result<(), std::io::Error> {
         let test_content = "Hello, World!";
-        let temp_file = "test_file.txt";
+        let ws = TestWorkspace::new()?;
 
         // Create a temporary file with test content
-        fs::write(temp_file, test_content)?;
+        let temp_file = ws.file("test_file.txt", test_content)?;
 
         // Test reading the file
-        let result = read_file(PathBuf::from(temp_file));
-
-        // Clean up
-        fs::remove_file(temp_file)?;
+        let result = read_file(temp_file);
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), test_content);
@@ -80,20 +79,19 @@ mod tests_file {
     fn test_read_file_permission_error() -> Result<(), std::io::Error> {
         use std::os::unix::fs::PermissionsExt;
 
-        let test_file = "readonly_test.txt";
+        let ws = TestWorkspace::new()?;
 
         // Create a file
-        fs::write(test_file, "test content")?;
+        let test_file = ws.file("readonly_test.txt", "test content")?;
 
         // Set permissions to read-only (no write permission)
-        fs::set_permissions(test_file, fs::Permissions::from_mode(0o444))?;
+        fs::set_permissions(&test_file, fs::Permissions::from_mode(0o444))?;
 
         // Try to read the file
-        let result = read_file(PathBuf::from(test_file));
+        let result = read_file(test_file.clone());
 
         // Clean up (need to make writable again to delete)
-        fs::set_permissions(test_file, fs::Permissions::from_mode(0o666))?;
-        fs::remove_file(test_file)?;
+        fs::set_permissions(&test_file, fs::Permissions::from_mode(0o666))?;
 
         match result {
             Ok(content) => {
@@ -101,7 +99,7 @@ mod tests_file {
                 Ok(())
             }
             Err(FileError::ReadError { path, source: _ }) => {
-                assert_eq!(path, PathBuf::from(test_file));
+                assert_eq!(path, test_file);
                 Ok(())
             }
             _ => panic!("Expected FileError::ReadError"),
@@ -167,6 +165,8 @@ mod test_config {
     use std::fs;
     use thiserror::Error;
 
+    use std_app::testkit::TestWorkspace;
+
     #[derive(Error, Debug)]
     enum ConfigError {
         #[error("配置文件读取失败: {0}")]
@@ -205,14 +205,15 @@ mod test_config {
 
     #[test]
     fn test_load_config_when_file_is_yaml() -> Result<(), std::io::Error> {
-        fs::write(
+        let ws = TestWorkspace::new()?;
+        let path = ws.file(
             "test_config_yaml.yaml",
             r#"
         hst: localhsot
         port: 8080
         "#,
         )?;
-        let config = load_config("test_config_yaml.yaml");
+        let config = load_config(path.to_str().unwrap());
         assert!(config.is_err());
         match config {
             Err(e @ ConfigError::ParseError(_)) => {
@@ -220,23 +221,24 @@ mod test_config {
             }
             _ => panic!("期望返回 ParseError 错误"),
         }
-        fs::remove_file("test_config_yaml.yaml")?;
         Ok(())
     }
 
     #[test]
     fn test_invalid_port_value() {
         // 创建一个包含无效端口的 TOML 文件
-        fs::write(
-            "zero_port_config.toml",
-            r#"
+        let ws = TestWorkspace::new().unwrap();
+        let path = ws
+            .file(
+                "zero_port_config.toml",
+                r#"
             host = "localhost"
             port = 0
         "#,
-        )
-        .unwrap();
+            )
+            .unwrap();
 
-        let result = load_config("zero_port_config.toml");
+        let result = load_config(path.to_str().unwrap());
 
         assert!(result.is_err());
 
@@ -247,8 +249,6 @@ mod test_config {
             }
             _ => panic!("Expected InvalidPort error, got a different error type"),
         }
-
-        fs::remove_file("zero_port_config.toml").unwrap();
     }
 }
 
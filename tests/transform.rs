@@ -0,0 +1,82 @@
+use std_app::transform::{self, scalar, Backend, HexError};
+
+// 所有字节值，长度覆盖向量宽度的各种余数
+fn samples() -> Vec<Vec<u8>> {
+    let all: Vec<u8> = (0..=255u8).cycle().take(1000).collect();
+    let mut samples: Vec<Vec<u8>> = (0..70).map(|n| all[n * 3..n * 3 + n].to_vec()).collect();
+    samples.push(all);
+    samples
+}
+
+#[cfg(test)]
+mod test_transform {
+    use super::*;
+
+    #[test]
+    fn test_backend_detected() {
+        if cfg!(target_arch = "x86_64") {
+            assert!(transform::backend() >= Backend::Sse2);
+        }
+    }
+
+    //向量化实现与逐字节实现结果一致
+    #[test]
+    fn test_case_matches_scalar() {
+        for sample in samples() {
+            let (mut upper, mut expected) = (sample.clone(), sample.clone());
+            transform::make_ascii_uppercase(&mut upper);
+            scalar::make_ascii_uppercase(&mut expected);
+            assert_eq!(upper, expected);
+
+            let (mut lower, mut expected) = (sample.clone(), sample.clone());
+            transform::make_ascii_lowercase(&mut lower);
+            scalar::make_ascii_lowercase(&mut expected);
+            assert_eq!(lower, expected);
+        }
+        assert_eq!(
+            transform::to_ascii_uppercase(b"Hello World!"),
+            b"HELLO WORLD!"
+        );
+    }
+
+    #[test]
+    fn test_hex_roundtrip() {
+        for sample in samples() {
+            let encoded = transform::hex_encode(&sample);
+            assert_eq!(encoded, hex::encode(&sample));
+            assert_eq!(transform::hex_decode(&encoded).unwrap(), sample);
+            assert_eq!(
+                transform::hex_decode(encoded.to_uppercase()).unwrap(),
+                sample
+            );
+        }
+    }
+
+    #[test]
+    fn test_hex_errors() {
+        assert_eq!(transform::hex_decode("abc"), Err(HexError::OddLength(3)));
+        assert_eq!(
+            transform::hex_decode("00112233445566zz"),
+            Err(HexError::InvalidChar {
+                index: 14,
+                byte: b'z'
+            })
+        );
+    }
+
+    #[test]
+    fn test_find_and_split() {
+        for sample in samples() {
+            for needle in [0u8, b',', 200] {
+                assert_eq!(
+                    transform::find_byte(&sample, needle),
+                    scalar::find_byte(&sample, needle)
+                );
+            }
+        }
+        let line = b"a,bb,,ccc,".repeat(10);
+        let fields: Vec<&[u8]> = transform::split(&line, b',').collect();
+        let expected: Vec<&[u8]> = line.split(|&b| b == b',').collect();
+        assert_eq!(fields, expected);
+    }
+}
@@ -0,0 +1,124 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use sqlx::sqlite::SqlitePoolOptions;
+
+use std_app::clock::SimClock;
+use std_app::metrics::Registry;
+use std_app::tsdb::{Resolution, Tsdb};
+
+// 2023-11-14 22:13:00 UTC，正好是整分钟
+fn start() -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(1_700_000_000 - 1_700_000_000 % 60)
+}
+
+async fn tsdb(clock: &SimClock) -> Tsdb {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let tsdb = Tsdb::with_clock(pool, clock.shared());
+    tsdb.migrate().await.unwrap();
+    tsdb
+}
+
+#[cfg(test)]
+mod test_tsdb {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_rollups() {
+        let clock = SimClock::at(start());
+        let tsdb = tsdb(&clock).await;
+        for value in [1.0, 5.0, 3.0] {
+            tsdb.record("cpu", value);
+            clock.advance(Duration::from_secs(10));
+        }
+        clock.advance(Duration::from_secs(60));
+        tsdb.record("cpu", 10.0);
+        assert_eq!(tsdb.flush().await.unwrap(), 4);
+        assert_eq!(tsdb.buffered(), 0);
+
+        let end = start() + Duration::from_secs(3600);
+        let minutes = tsdb
+            .query("cpu", Resolution::Minute, start(), end)
+            .await
+            .unwrap();
+        assert_eq!(minutes.len(), 2);
+        assert_eq!(
+            (minutes[0].count, minutes[0].min, minutes[0].max),
+            (3, 1.0, 5.0)
+        );
+        assert_eq!(minutes[0].avg(), 3.0);
+        assert_eq!(minutes[1].bucket_ms - minutes[0].bucket_ms, 60_000);
+
+        let hours = tsdb
+            .query("cpu", Resolution::Hour, start(), end)
+            .await
+            .unwrap();
+        assert_eq!(hours.len(), 1);
+        assert_eq!((hours[0].count, hours[0].max), (4, 10.0));
+    }
+
+    //同一个时间桶分多次 flush，结果合并
+    #[tokio::test]
+    async fn test_flush_merges() {
+        let clock = SimClock::at(start());
+        let tsdb = tsdb(&clock).await;
+        tsdb.record("latency", 20.0);
+        tsdb.flush().await.unwrap();
+        clock.advance(Duration::from_secs(5));
+        tsdb.record("latency", 40.0);
+        tsdb.flush().await.unwrap();
+
+        let rollups = tsdb
+            .query(
+                "latency",
+                Resolution::Minute,
+                start(),
+                start() + Duration::from_secs(60),
+            )
+            .await
+            .unwrap();
+        assert_eq!(rollups.len(), 1);
+        assert_eq!(
+            (rollups[0].count, rollups[0].min, rollups[0].max),
+            (2, 20.0, 40.0)
+        );
+        assert_eq!(rollups[0].avg(), 30.0);
+    }
+
+    #[tokio::test]
+    async fn test_registry_and_prune() {
+        let clock = SimClock::at(start());
+        let tsdb = tsdb(&clock).await.capacity(2);
+        let registry = Registry::new();
+        registry.counter("requests_total").add(7);
+        registry.gauge("queue_depth").set(2.5);
+        registry.histogram("empty");
+        tsdb.sample_registry(&registry);
+        //超过容量时丢弃最旧的采样
+        tsdb.record("extra", 1.0);
+        assert_eq!(tsdb.buffered(), 2);
+        tsdb.flush().await.unwrap();
+        assert_eq!(
+            tsdb.series().await.unwrap(),
+            vec!["extra", "requests_total"]
+        );
+
+        let later = start() + Duration::from_secs(120);
+        assert_eq!(tsdb.prune(Resolution::Minute, later).await.unwrap(), 2);
+        assert!(tsdb
+            .query("extra", Resolution::Minute, start(), later)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            tsdb.query("extra", Resolution::Hour, start(), later)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,220 @@
+use std::sync::{Arc, Mutex};
+
+use sqlx::sqlite::SqlitePoolOptions;
+
+use std_app::db::users::{NewUser, User, UserRepository, UserUpdate};
+use std_app::db::DbError;
+use std_app::page::PageRequest;
+
+async fn repository() -> UserRepository {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let repo = UserRepository::new(pool);
+    repo.migrate().await.unwrap();
+    repo
+}
+
+fn new_user(name: &str, email: &str) -> NewUser {
+    NewUser {
+        name: name.to_string(),
+        email: email.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test_users {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_crud() -> Result<(), DbError> {
+        let repo = repository().await;
+        let alice = repo.create(&new_user("Alice", "alice@example.com")).await?;
+        assert_eq!(repo.get(alice.id).await?, alice);
+
+        let updated = repo
+            .update(
+                alice.id,
+                &UserUpdate {
+                    name: Some("Alice Liddell".to_string()),
+                    email: None,
+                },
+            )
+            .await?;
+        assert_eq!(updated.name, "Alice Liddell");
+        assert_eq!(updated.email, "alice@example.com");
+
+        repo.delete(alice.id).await?;
+        assert!(matches!(repo.get(alice.id).await, Err(DbError::NotFound(id)) if id == alice.id));
+        assert!(matches!(
+            repo.delete(alice.id).await,
+            Err(DbError::NotFound(_))
+        ));
+        Ok(())
+    }
+
+    //邮箱唯一约束不区分大小写
+    #[tokio::test]
+    async fn test_duplicate_email() -> Result<(), DbError> {
+        let repo = repository().await;
+        let alice = repo.create(&new_user("Alice", "alice@example.com")).await?;
+        let bob = repo.create(&new_user("Bob", "bob@example.com")).await?;
+
+        let err = repo
+            .create(&new_user("Alice 2", "ALICE@example.com"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DbError::Duplicate(_)), "{:?}", err);
+
+        let update = UserUpdate {
+            email: Some(alice.email.clone()),
+            ..UserUpdate::default()
+        };
+        assert!(matches!(
+            repo.update(bob.id, &update).await,
+            Err(DbError::Duplicate(_))
+        ));
+        assert_eq!(
+            repo.find_by_email("Bob@Example.com").await?.map(|u| u.id),
+            Some(bob.id)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_search_and_pagination() -> Result<(), DbError> {
+        let repo = repository().await;
+        for i in 1..=25 {
+            repo.create(&new_user(
+                &format!("User {}", i),
+                &format!("user{}@example.com", i),
+            ))
+            .await?;
+        }
+        repo.create(&new_user("Alice", "ALICE@corp.com")).await?;
+        repo.create(&new_user("percent%", "p@example.com")).await?;
+
+        let page = repo.list(PageRequest::new(2, 10)).await?;
+        assert_eq!(page.total, 27);
+        assert_eq!(page.total_pages(), 3);
+        assert_eq!(page.items[0].name, "User 11");
+        assert!(page.has_next());
+
+        let found = repo.search("alice", PageRequest::default()).await?;
+        assert_eq!(found.total, 1);
+        assert_eq!(found.items[0].email, "ALICE@corp.com");
+
+        let found = repo.search("CORP", PageRequest::default()).await?;
+        assert_eq!(found.total, 1);
+
+        // % 按字面匹配
+        let found = repo.search("%", PageRequest::default()).await?;
+        assert_eq!(found.total, 1);
+
+        let last = repo.search("user", PageRequest::new(3, 10)).await?;
+        assert_eq!(last.items.len(), 5);
+        assert!(!last.has_next());
+        Ok(())
+    }
+
+    //钩子按注册顺序调用，before 钩子可以拒绝写入
+    #[tokio::test]
+    async fn test_hooks() -> Result<(), DbError> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = |prefix: &'static str| {
+            let events = events.clone();
+            move |user: &User| {
+                events
+                    .lock()
+                    .unwrap()
+                    .push(format!("{} {}", prefix, user.name))
+            }
+        };
+        let deleted = events.clone();
+        let repo = repository()
+            .await
+            .on_before_insert(|new| {
+                if new.email.ends_with("@blocked.com") {
+                    return Err(format!("邮箱域名被禁用: {}", new.email));
+                }
+                Ok(())
+            })
+            .on_after_insert(log("inserted"))
+            .on_before_update(|_, update| match &update.name {
+                Some(name) if name.is_empty() => Err("名称不能为空".to_string()),
+                _ => Ok(()),
+            })
+            .on_after_update(log("updated"))
+            .on_before_delete(|id| {
+                if id == 1 {
+                    Err("不能删除管理员".to_string())
+                } else {
+                    Ok(())
+                }
+            })
+            .on_after_delete(move |id| deleted.lock().unwrap().push(format!("deleted {}", id)));
+
+        let admin = repo.create(&new_user("Admin", "admin@example.com")).await?;
+        let bob = repo.create(&new_user("Bob", "bob@example.com")).await?;
+        assert!(matches!(
+            repo.create(&new_user("Eve", "eve@blocked.com")).await,
+            Err(DbError::Rejected(_))
+        ));
+        let rename = |name: &str| UserUpdate {
+            name: Some(name.to_string()),
+            email: None,
+        };
+        repo.update(bob.id, &rename("Robert")).await?;
+        assert!(matches!(
+            repo.update(bob.id, &rename("")).await,
+            Err(DbError::Rejected(_))
+        ));
+        assert!(matches!(
+            repo.delete(admin.id).await,
+            Err(DbError::Rejected(_))
+        ));
+        //克隆出来的仓库共享钩子
+        repo.clone().delete(bob.id).await?;
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "inserted Admin",
+                "inserted Bob",
+                "updated Robert",
+                "deleted 2"
+            ]
+        );
+        assert_eq!(repo.list(PageRequest::default()).await?.total, 1);
+        Ok(())
+    }
+
+    //结果按请求顺序返回，分批查询对调用方透明
+    #[tokio::test]
+    async fn test_find_many() -> Result<(), DbError> {
+        let repo = repository().await;
+        for i in 1..=600 {
+            repo.create(&new_user(
+                &format!("User {}", i),
+                &format!("user{}@example.com", i),
+            ))
+            .await?;
+        }
+        let result = repo.find_many(&[3, 999, 1, 3, 2]).await?;
+        let ids: Vec<i64> = result.found.iter().map(|u| u.id).collect();
+        assert_eq!(ids, vec![3, 1, 2]);
+        assert_eq!(result.missing, vec![999]);
+        assert!(matches!(result.require_all(), Err(DbError::NotFound(999))));
+
+        let ids: Vec<i64> = (1..=700).rev().collect();
+        let result = repo.find_many(&ids).await?;
+        assert_eq!(result.found.len(), 600);
+        assert_eq!(result.found[0].id, 600);
+        assert_eq!(result.missing, (601..=700).rev().collect::<Vec<_>>());
+
+        assert!(repo.find_many(&[]).await?.is_complete());
+        Ok(())
+    }
+}
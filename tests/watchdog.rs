@@ -0,0 +1,91 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use std_app::clock::SimClock;
+use std_app::watchdog::Watchdog;
+
+#[cfg(test)]
+mod test_watchdog {
+    use super::*;
+
+    #[test]
+    fn test_missed_heartbeat() {
+        let clock = SimClock::new();
+        let watchdog = Watchdog::with_clock(clock.shared());
+        let scheduler = watchdog.register("scheduler", Duration::from_secs(30));
+        let worker = watchdog.register("job-worker", Duration::from_secs(60));
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        watchdog.on_stall(move |stall| sink.lock().unwrap().push(stall.name.clone()));
+
+        clock.advance(Duration::from_secs(20));
+        scheduler.beat();
+        worker.beat();
+        clock.advance(Duration::from_secs(20));
+        assert!(watchdog.check().is_empty());
+
+        clock.advance(Duration::from_secs(15));
+        let stalls = watchdog.check();
+        assert_eq!(stalls.len(), 1);
+        assert_eq!(stalls[0].name, "scheduler");
+        assert_eq!(stalls[0].silent_for, Duration::from_secs(35));
+        assert!(!stalls[0].restarted);
+
+        // 同一次停滞不重复报告，恢复心跳后重新计时
+        assert!(watchdog.check().is_empty());
+        scheduler.beat();
+        worker.beat();
+        clock.advance(Duration::from_secs(31));
+        assert_eq!(watchdog.check()[0].name, "scheduler");
+        assert_eq!(*events.lock().unwrap(), vec!["scheduler", "scheduler"]);
+    }
+
+    #[test]
+    fn test_restart() {
+        let clock = SimClock::new();
+        let watchdog = Watchdog::with_clock(clock.shared());
+        let restarts = Arc::new(AtomicUsize::new(0));
+        let counter = restarts.clone();
+        let _relay =
+            watchdog.register_with_restart("outbox-relay", Duration::from_secs(10), move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+            });
+
+        clock.advance(Duration::from_secs(10));
+        assert!(watchdog.check()[0].restarted);
+        assert_eq!(restarts.load(Ordering::SeqCst), 1);
+
+        // 重启后重新计时
+        clock.advance(Duration::from_secs(5));
+        assert!(watchdog.check().is_empty());
+        clock.advance(Duration::from_secs(5));
+        watchdog.check();
+        assert_eq!(restarts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_run_loop() {
+        let clock = SimClock::new();
+        let watchdog = Watchdog::with_clock(clock.shared());
+        let _hb = watchdog.register("scheduler", Duration::from_secs(5));
+        let stalled = Arc::new(AtomicUsize::new(0));
+        let counter = stalled.clone();
+        watchdog.on_stall(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+
+        let handle = watchdog.spawn(Duration::from_secs(1));
+        for _ in 0..6 {
+            while clock.pending_timers() == 0 {
+                tokio::task::yield_now().await;
+            }
+            clock.advance(Duration::from_secs(1));
+        }
+        while stalled.load(Ordering::SeqCst) == 0 {
+            tokio::task::yield_now().await;
+        }
+        handle.abort();
+    }
+}